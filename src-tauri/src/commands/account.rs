@@ -73,16 +73,24 @@ pub async fn set_current_account(app: tauri::AppHandle, account_id: String) -> R
 #[tauri::command]
 pub async fn fetch_account_quota(account_id: String) -> AppResult<models::QuotaData> {
     let mut account = modules::load_account(&account_id).map_err(AppError::Account)?;
-    let quota = modules::fetch_quota_with_retry(&mut account, true).await?;
-    modules::update_account_quota(&account_id, quota.clone()).map_err(AppError::Account)?;
-    Ok(quota)
+    let result = async {
+        let quota = modules::fetch_quota_with_retry(&mut account, true).await?;
+        modules::update_account_quota(&account_id, quota.clone()).map_err(AppError::Account)?;
+        Ok(quota)
+    }
+    .await;
+    // 失败时账号上也记录了 quota_error，同样需要比较
+    modules::quota_events::publish_logged(&modules::account::AntigravityPlatform);
+    result
 }
 
 #[tauri::command]
 pub async fn refresh_all_quotas(
     app: tauri::AppHandle,
 ) -> Result<modules::account::RefreshStats, String> {
-    let result = modules::account::refresh_all_quotas_logic().await;
+    let result = modules::account::refresh_all_quotas_logic()
+        .await
+        .map(|report| modules::account::RefreshStats::from(&report));
     if result.is_ok() {
         let mut switched = false;
         match modules::account::run_auto_switch_if_needed().await {
@@ -111,10 +119,15 @@ pub async fn refresh_current_quota(app: tauri::AppHandle) -> Result<(), String>
         return Err("未找到当前账号".to_string());
     };
     let mut account = account;
-    let quota = modules::fetch_quota_with_retry(&mut account, true)
-        .await
-        .map_err(|e| e.to_string())?;
-    modules::update_account_quota(&account.id, quota).map_err(|e| e.to_string())?;
+    let result = async {
+        let quota = modules::fetch_quota_with_retry(&mut account, true)
+            .await
+            .map_err(|e| e.to_string())?;
+        modules::update_account_quota(&account.id, quota)
+    }
+    .await;
+    modules::quota_events::publish_logged(&modules::account::AntigravityPlatform);
+    result?;
 
     let mut switched = false;
     match modules::account::run_auto_switch_if_needed().await {
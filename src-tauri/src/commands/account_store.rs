@@ -0,0 +1,25 @@
+//! 统一账号存储（cockpit.db）相关命令
+
+use crate::modules::account_store::{self, AccountStoreStatus, PlatformStoreReport};
+
+/// 获取统一账号存储状态
+#[tauri::command]
+pub fn account_store_get_status() -> Result<AccountStoreStatus, String> {
+    account_store::get_status()
+}
+
+/// 从 JSON 文件布局导入账号并启用数据库存储（导入后删除原账号文件）
+#[tauri::command]
+pub fn account_store_import_from_json() -> Result<Vec<PlatformStoreReport>, String> {
+    let reports = account_store::import_from_json()?;
+    crate::modules::websocket::broadcast_data_changed("account_store_import");
+    Ok(reports)
+}
+
+/// 将数据库内容导出回 JSON 文件布局，并切回文件存储
+#[tauri::command]
+pub fn account_store_rollback_to_json() -> Result<Vec<PlatformStoreReport>, String> {
+    let reports = account_store::export_to_json()?;
+    crate::modules::websocket::broadcast_data_changed("account_store_rollback");
+    Ok(reports)
+}
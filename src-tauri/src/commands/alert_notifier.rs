@@ -0,0 +1,15 @@
+//! 预警通知调度（免打扰、去重、汇总）相关命令
+
+use crate::modules::alert_notifier::{self, AlertNotifySettings};
+
+#[tauri::command]
+pub fn alert_notify_get_settings() -> AlertNotifySettings {
+    alert_notifier::load_settings()
+}
+
+#[tauri::command]
+pub fn alert_notify_save_settings(
+    settings: AlertNotifySettings,
+) -> Result<AlertNotifySettings, String> {
+    alert_notifier::save_settings(settings)
+}
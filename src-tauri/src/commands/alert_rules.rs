@@ -0,0 +1,27 @@
+//! 配额预警规则相关命令
+
+use crate::modules::alert_rules::{self, AlertMatch, AlertRule};
+
+/// 列出全部预警规则（含由平台预警设置生成的内置规则）
+#[tauri::command]
+pub fn alert_rule_list() -> Vec<AlertRule> {
+    alert_rules::list_rules()
+}
+
+/// 新增或更新自定义规则；id 为空时视为新增
+#[tauri::command]
+pub fn alert_rule_save(rule: AlertRule) -> Result<AlertRule, String> {
+    alert_rules::save_rule(rule)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn alert_rule_delete(ruleId: String) -> Result<(), String> {
+    alert_rules::delete_rule(&ruleId)
+}
+
+/// 用当前数据试运行规则，返回命中的账号（不发出预警）
+#[tauri::command]
+pub fn alert_rule_test(rule: AlertRule) -> Result<Vec<AlertMatch>, String> {
+    alert_rules::test_rule(&rule)
+}
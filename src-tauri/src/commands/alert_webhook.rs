@@ -0,0 +1,21 @@
+//! 预警 Webhook 通知相关命令
+
+use crate::modules::alert_webhook::{self, WebhookDelivery, WebhookSettings};
+
+#[tauri::command]
+pub fn alert_webhook_get_settings() -> WebhookSettings {
+    alert_webhook::load_settings()
+}
+
+#[tauri::command]
+pub fn alert_webhook_save_settings(settings: WebhookSettings) -> Result<WebhookSettings, String> {
+    alert_webhook::save_settings(settings)
+}
+
+/// 发送测试消息；传入 settings 时用未保存的设置测试
+#[tauri::command]
+pub async fn alert_webhook_send_test(
+    settings: Option<WebhookSettings>,
+) -> Result<WebhookDelivery, String> {
+    alert_webhook::send_test(settings).await
+}
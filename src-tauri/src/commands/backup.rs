@@ -0,0 +1,53 @@
+//! 数据目录快照备份相关命令
+
+use crate::modules::backup::{self, BackupSettings, RestoreReport, SnapshotDiff, SnapshotMeta};
+
+/// 列出快照（最新的在前）
+#[tauri::command]
+pub fn backup_list_snapshots() -> Result<Vec<SnapshotMeta>, String> {
+    backup::list_snapshots()
+}
+
+/// 立即创建一个手动快照
+#[tauri::command]
+pub fn backup_create_snapshot() -> Result<SnapshotMeta, String> {
+    backup::create_snapshot(backup::REASON_MANUAL)
+}
+
+/// 对比快照与当前数据（compareTo 为空时），或与另一个快照对比
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn backup_diff_snapshot(
+    snapshotId: String,
+    compareTo: Option<String>,
+) -> Result<SnapshotDiff, String> {
+    backup::diff_snapshot(&snapshotId, compareTo.as_deref())
+}
+
+/// 将数据目录恢复到指定快照
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn backup_restore_snapshot(
+    app: tauri::AppHandle,
+    snapshotId: String,
+) -> Result<RestoreReport, String> {
+    let report = backup::restore_snapshot(&snapshotId)?;
+    crate::modules::websocket::broadcast_data_changed("backup_restore");
+    if let Err(err) = crate::modules::tray::update_tray_menu(&app) {
+        crate::modules::logger::log_warn(&format!("[Backup] 更新托盘菜单失败: {}", err));
+    }
+    Ok(report)
+}
+
+/// 获取备份设置
+#[tauri::command]
+pub fn backup_get_settings() -> Result<BackupSettings, String> {
+    Ok(backup::load_settings())
+}
+
+/// 保存备份设置
+#[tauri::command]
+pub fn backup_save_settings(settings: BackupSettings) -> Result<BackupSettings, String> {
+    backup::save_settings(&settings)?;
+    Ok(settings)
+}
@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use crate::models::{DefaultInstanceSettings, InstanceProfileView};
+use crate::models::InstanceProfileView;
 use crate::modules;
 
 const DEFAULT_INSTANCE_ID: &str = "__default__";
@@ -9,19 +9,6 @@ fn is_profile_initialized(user_data_dir: &str) -> bool {
     modules::instance::is_profile_initialized(Path::new(user_data_dir))
 }
 
-fn resolve_default_account_id(settings: &DefaultInstanceSettings) -> Option<String> {
-    if settings.follow_local_account {
-        resolve_local_account_id()
-    } else {
-        settings.bind_account_id.clone()
-    }
-}
-
-fn resolve_local_account_id() -> Option<String> {
-    let account = modules::codex_account::get_current_account()?;
-    Some(account.id)
-}
-
 #[tauri::command]
 pub async fn codex_get_instance_defaults() -> Result<modules::instance::InstanceDefaults, String> {
     modules::codex_instance::get_instance_defaults()
@@ -58,7 +45,8 @@ pub async fn codex_list_instances() -> Result<Vec<InstanceProfileView>, String>
         &process_entries,
     );
     let default_running = default_pid.is_some();
-    let default_bind_account_id = resolve_default_account_id(&default_settings);
+    let default_bind_account_id =
+        modules::codex_instance::resolve_default_account_id(&default_settings);
     result.push(InstanceProfileView {
         id: DEFAULT_INSTANCE_ID.to_string(),
         name: String::new(),
@@ -124,7 +112,7 @@ pub async fn codex_update_instance(
             .last_pid
             .map(modules::process::is_pid_running)
             .unwrap_or(false);
-        let default_bind_account_id = resolve_default_account_id(&updated);
+        let default_bind_account_id = modules::codex_instance::resolve_default_account_id(&updated);
         return Ok(InstanceProfileView {
             id: DEFAULT_INSTANCE_ID.to_string(),
             name: String::new(),
@@ -190,19 +178,12 @@ pub async fn codex_start_instance(instance_id: String) -> Result<InstanceProfile
     modules::process::ensure_codex_launch_path_configured()?;
 
     if instance_id == DEFAULT_INSTANCE_ID {
+        let pid = modules::codex_instance::start_default_instance().await?;
         let default_dir = modules::codex_instance::get_default_codex_home()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
         let default_settings = modules::codex_instance::load_default_settings()?;
-        let default_bind_account_id = resolve_default_account_id(&default_settings);
-        if let Some(pid) = modules::process::resolve_codex_pid(default_settings.last_pid, None) {
-            modules::process::close_pid(pid, 20)?;
-            let _ = modules::codex_instance::update_default_pid(None)?;
-        }
-        if let Some(ref account_id) = default_bind_account_id {
-            modules::codex_instance::inject_account_to_profile(&default_dir, account_id).await?;
-        }
-        let pid = modules::process::start_codex_default()?;
-        let _ = modules::codex_instance::update_default_pid(Some(pid))?;
+        let default_bind_account_id =
+            modules::codex_instance::resolve_default_account_id(&default_settings);
         let running = modules::process::is_pid_running(pid);
         return Ok(InstanceProfileView {
             id: DEFAULT_INSTANCE_ID.to_string(),
@@ -265,7 +246,8 @@ pub async fn codex_stop_instance(instance_id: String) -> Result<InstanceProfileV
         }
         let _ = modules::codex_instance::update_default_pid(None)?;
         let running = false;
-        let default_bind_account_id = resolve_default_account_id(&default_settings);
+        let default_bind_account_id =
+            modules::codex_instance::resolve_default_account_id(&default_settings);
         return Ok(InstanceProfileView {
             id: DEFAULT_INSTANCE_ID.to_string(),
             name: String::new(),
@@ -0,0 +1,135 @@
+use std::time::Instant;
+use tauri::AppHandle;
+
+use crate::models::cursor::CursorAccount;
+use crate::modules::{cursor_account, logger, quota_events};
+
+#[tauri::command]
+pub fn list_cursor_accounts() -> Result<Vec<CursorAccount>, String> {
+    Ok(cursor_account::list_accounts())
+}
+
+#[tauri::command]
+pub fn delete_cursor_account(account_id: String) -> Result<(), String> {
+    cursor_account::remove_account(&account_id)
+}
+
+#[tauri::command]
+pub fn delete_cursor_accounts(account_ids: Vec<String>) -> Result<(), String> {
+    cursor_account::remove_accounts(&account_ids)
+}
+
+#[tauri::command]
+pub fn import_cursor_from_json(json_content: String) -> Result<Vec<CursorAccount>, String> {
+    cursor_account::import_from_json(&json_content)
+}
+
+#[tauri::command]
+pub async fn import_cursor_from_local(app: AppHandle) -> Result<Vec<CursorAccount>, String> {
+    let auth = cursor_account::read_local_auth()?
+        .ok_or_else(|| "未在本机 Cursor 中找到登录信息，请先在 Cursor 中登录".to_string())?;
+    let account = cursor_account::upsert_from_local_auth(auth)?;
+    let account = match cursor_account::refresh_account_token(&account.id, true).await {
+        Ok(refreshed) => refreshed,
+        Err(e) => {
+            logger::log_warn(&format!(
+                "[Cursor Import] 导入后刷新用量失败: account_id={}, error={}",
+                account.id, e
+            ));
+            cursor_account::load_account(&account.id).unwrap_or(account)
+        }
+    };
+    let _ = crate::modules::tray::update_tray_menu(&app);
+    Ok(vec![account])
+}
+
+#[tauri::command]
+pub fn export_cursor_accounts(account_ids: Vec<String>) -> Result<String, String> {
+    cursor_account::export_accounts(&account_ids)
+}
+
+#[tauri::command]
+pub async fn refresh_cursor_token(
+    app: AppHandle,
+    account_id: String,
+) -> Result<CursorAccount, String> {
+    let started_at = Instant::now();
+    logger::log_info(&format!(
+        "[Cursor Command] 手动刷新账号开始: account_id={}",
+        account_id
+    ));
+
+    let result = cursor_account::refresh_account_token(&account_id, true).await;
+    quota_events::publish_logged(&cursor_account::CursorPlatform);
+    match result {
+        Ok(account) => {
+            if let Err(e) = cursor_account::run_quota_alert_if_needed() {
+                logger::log_warn(&format!("[QuotaAlert][Cursor] 预警检查失败: {}", e));
+            }
+            let _ = crate::modules::tray::update_tray_menu(&app);
+            logger::log_info(&format!(
+                "[Cursor Command] 手动刷新账号完成: account_id={}, email={}, elapsed={}ms",
+                account.id,
+                account.email,
+                started_at.elapsed().as_millis()
+            ));
+            Ok(account)
+        }
+        Err(err) => {
+            logger::log_warn(&format!(
+                "[Cursor Command] 手动刷新账号失败: account_id={}, elapsed={}ms, error={}",
+                account_id,
+                started_at.elapsed().as_millis(),
+                err
+            ));
+            Err(err)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn refresh_all_cursor_tokens(app: AppHandle) -> Result<i32, String> {
+    let started_at = Instant::now();
+    logger::log_info("[Cursor Command] 手动批量刷新开始");
+
+    let results = cursor_account::refresh_all_tokens().await?.results;
+    let success_count = results.iter().filter(|(_, item)| item.is_ok()).count();
+    let failed_count = results.len().saturating_sub(success_count);
+
+    logger::log_info(&format!(
+        "[Cursor Command] 手动批量刷新完成: success={}, failed={}, elapsed={}ms",
+        success_count,
+        failed_count,
+        started_at.elapsed().as_millis()
+    ));
+
+    if success_count > 0 {
+        if let Err(e) = cursor_account::run_quota_alert_if_needed() {
+            logger::log_warn(&format!(
+                "[QuotaAlert][Cursor] 全量刷新后预警检查失败: {}",
+                e
+            ));
+        }
+    }
+
+    let _ = crate::modules::tray::update_tray_menu(&app);
+    Ok(success_count as i32)
+}
+
+#[tauri::command]
+pub async fn update_cursor_account_tags(
+    account_id: String,
+    tags: Vec<String>,
+) -> Result<CursorAccount, String> {
+    cursor_account::update_account_tags(&account_id, tags)
+}
+
+#[tauri::command]
+pub fn get_cursor_accounts_index_path() -> Result<String, String> {
+    cursor_account::accounts_index_path_string()
+}
+
+#[tauri::command]
+pub async fn inject_cursor_to_vscode(account_id: String) -> Result<String, String> {
+    cursor_account::switch_account_internal(&account_id)
+}
@@ -1,13 +1,13 @@
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
 use crate::models::github_copilot::{GitHubCopilotAccount, GitHubCopilotOAuthStartResponse};
-use crate::modules::{github_copilot_account, github_copilot_oauth, logger};
+use crate::modules::{github_copilot_account, github_copilot_oauth, logger, quota_events};
 
 async fn refresh_github_copilot_account_after_login(
     account: GitHubCopilotAccount,
 ) -> GitHubCopilotAccount {
     let account_id = account.id.clone();
-    match github_copilot_account::refresh_account_token(&account_id).await {
+    match github_copilot_account::refresh_account_token(&account_id, true).await {
         Ok(refreshed) => refreshed,
         Err(e) => {
             logger::log_warn(&format!(
@@ -57,7 +57,8 @@ pub async fn refresh_github_copilot_token(
     app: AppHandle,
     account_id: String,
 ) -> Result<GitHubCopilotAccount, String> {
-    let result = github_copilot_account::refresh_account_token(&account_id).await;
+    let result = github_copilot_account::refresh_account_token(&account_id, true).await;
+    quota_events::publish_logged(&github_copilot_account::GitHubCopilotPlatform);
     if result.is_ok() {
         if let Err(e) = github_copilot_account::run_quota_alert_if_needed() {
             logger::log_warn(&format!("[QuotaAlert][GitHubCopilot] 预警检查失败: {}", e));
@@ -70,7 +71,7 @@ pub async fn refresh_github_copilot_token(
 /// 刷新所有账号 Copilot token/配额信息（GitHub API）
 #[tauri::command]
 pub async fn refresh_all_github_copilot_tokens(app: AppHandle) -> Result<i32, String> {
-    let results = github_copilot_account::refresh_all_tokens().await?;
+    let results = github_copilot_account::refresh_all_tokens().await?.results;
     let success_count = results.iter().filter(|(_, r)| r.is_ok()).count();
     if success_count > 0 {
         if let Err(e) = github_copilot_account::run_quota_alert_if_needed() {
@@ -157,63 +158,6 @@ pub fn get_github_copilot_accounts_index_path() -> Result<String, String> {
 
 /// 切换 GitHub Copilot 账号并按默认实例启动流程生效（PID 精准关闭 + 注入 + 启动）。
 #[tauri::command]
-pub async fn inject_github_copilot_to_vscode(
-    app: AppHandle,
-    account_id: String,
-) -> Result<String, String> {
-    logger::log_info(&format!("开始切换 GitHub Copilot 账号: {}", account_id));
-    let account = github_copilot_account::load_account(&account_id)
-        .ok_or_else(|| format!("GitHub Copilot account not found: {}", account_id))?;
-    logger::log_info(&format!(
-        "正在切换到 GitHub Copilot 账号: {} (ID: {})",
-        account.github_login, account.id
-    ));
-
-    // 同步更新 VS Code 默认实例绑定账号，确保后续走默认实例启动链路时注入目标明确。
-    if let Err(e) = crate::modules::github_copilot_instance::update_default_settings(
-        Some(Some(account_id.clone())),
-        None,
-        Some(false),
-    ) {
-        logger::log_warn(&format!("更新 GitHub Copilot 默认实例绑定账号失败: {}", e));
-    } else {
-        logger::log_info(&format!(
-            "已同步更新 GitHub Copilot 默认实例绑定账号: {}",
-            account_id
-        ));
-    }
-
-    let launch_warning =
-        match crate::commands::github_copilot_instance::github_copilot_start_instance(
-            "__default__".to_string(),
-        )
-        .await
-        {
-            Ok(_) => None,
-            Err(e) => {
-                if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("启动 VS Code 失败") {
-                    logger::log_warn(&format!("GitHub Copilot 默认实例启动失败: {}", e));
-                    if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("APP_PATH_NOT_FOUND:") {
-                        let _ = app.emit(
-                            "app:path_missing",
-                            serde_json::json!({ "app": "vscode", "retry": { "kind": "default" } }),
-                        );
-                    }
-                    Some(e)
-                } else {
-                    return Err(e);
-                }
-            }
-        };
-
-    logger::log_info(&format!(
-        "GitHub Copilot 账号切换完成: {}",
-        account.github_login
-    ));
-    let _ = crate::modules::tray::update_tray_menu(&app);
-    if let Some(err) = launch_warning {
-        Ok(format!("切换完成，但 VS Code 启动失败: {}", err))
-    } else {
-        Ok("切换完成".to_string())
-    }
+pub async fn inject_github_copilot_to_vscode(account_id: String) -> Result<String, String> {
+    github_copilot_account::switch_account_internal(&account_id)
 }
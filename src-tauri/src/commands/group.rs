@@ -1,5 +1,6 @@
 //! 分组配置相关命令
 
+use crate::modules::group_capacity::{self, GroupCapacity};
 use crate::modules::group_settings::{self, GroupSettings};
 use std::collections::HashMap;
 
@@ -27,14 +28,13 @@ pub fn save_group_settings(
     groupNames: HashMap<String, String>,
     groupOrder: Vec<String>,
 ) -> Result<(), String> {
-    let mut settings = group_settings::load_group_settings();
-    settings.group_mappings = groupMappings;
-    settings.group_names = groupNames;
-    settings.group_order = groupOrder;
-    settings.updated_at = chrono::Utc::now().timestamp_millis();
-    settings.updated_by = group_settings::ConfigSource::Desktop;
-
-    group_settings::update_group_settings(settings)?;
+    group_settings::modify_group_settings("save_group_settings", |settings| {
+        settings.group_mappings = groupMappings;
+        settings.group_names = groupNames;
+        settings.group_order = groupOrder;
+        settings.updated_at = chrono::Utc::now().timestamp_millis();
+        settings.updated_by = group_settings::ConfigSource::Desktop;
+    })?;
     refresh_tray_menu(&app);
     Ok(())
 }
@@ -43,9 +43,9 @@ pub fn save_group_settings(
 #[tauri::command]
 #[allow(non_snake_case)]
 pub fn set_model_group(app: tauri::AppHandle, modelId: String, groupId: String) -> Result<(), String> {
-    let mut settings = group_settings::load_group_settings();
-    settings.set_model_group(&modelId, &groupId);
-    group_settings::update_group_settings(settings)?;
+    group_settings::modify_group_settings("set_model_group", |settings| {
+        settings.set_model_group(&modelId, &groupId);
+    })?;
     refresh_tray_menu(&app);
     Ok(())
 }
@@ -54,9 +54,9 @@ pub fn set_model_group(app: tauri::AppHandle, modelId: String, groupId: String)
 #[tauri::command]
 #[allow(non_snake_case)]
 pub fn remove_model_group(app: tauri::AppHandle, modelId: String) -> Result<(), String> {
-    let mut settings = group_settings::load_group_settings();
-    settings.remove_model_group(&modelId);
-    group_settings::update_group_settings(settings)?;
+    group_settings::modify_group_settings("remove_model_group", |settings| {
+        settings.remove_model_group(&modelId);
+    })?;
     refresh_tray_menu(&app);
     Ok(())
 }
@@ -65,9 +65,9 @@ pub fn remove_model_group(app: tauri::AppHandle, modelId: String) -> Result<(),
 #[tauri::command]
 #[allow(non_snake_case)]
 pub fn set_group_name(app: tauri::AppHandle, groupId: String, name: String) -> Result<(), String> {
-    let mut settings = group_settings::load_group_settings();
-    settings.set_group_name(&groupId, &name);
-    group_settings::update_group_settings(settings)?;
+    group_settings::modify_group_settings("set_group_name", |settings| {
+        settings.set_group_name(&groupId, &name);
+    })?;
     refresh_tray_menu(&app);
     Ok(())
 }
@@ -76,9 +76,9 @@ pub fn set_group_name(app: tauri::AppHandle, groupId: String, name: String) -> R
 #[tauri::command]
 #[allow(non_snake_case)]
 pub fn delete_group(app: tauri::AppHandle, groupId: String) -> Result<(), String> {
-    let mut settings = group_settings::load_group_settings();
-    settings.delete_group(&groupId);
-    group_settings::update_group_settings(settings)?;
+    group_settings::modify_group_settings("delete_group", |settings| {
+        settings.delete_group(&groupId);
+    })?;
     refresh_tray_menu(&app);
     Ok(())
 }
@@ -86,9 +86,9 @@ pub fn delete_group(app: tauri::AppHandle, groupId: String) -> Result<(), String
 /// 更新分组排序
 #[tauri::command]
 pub fn update_group_order(app: tauri::AppHandle, order: Vec<String>) -> Result<(), String> {
-    let mut settings = group_settings::load_group_settings();
-    settings.set_group_order(order);
-    group_settings::update_group_settings(settings)?;
+    group_settings::modify_group_settings("update_group_order", |settings| {
+        settings.set_group_order(order);
+    })?;
     refresh_tray_menu(&app);
     Ok(())
 }
@@ -114,6 +114,12 @@ pub fn get_display_groups() -> Result<Vec<DisplayGroup>, String> {
     Ok(groups)
 }
 
+/// 获取各分组在全部 Antigravity 账号上的汇总容量
+#[tauri::command]
+pub fn get_group_capacity() -> Result<Vec<GroupCapacity>, String> {
+    group_capacity::group_capacity()
+}
+
 /// 显示用分组信息
 #[derive(serde::Serialize)]
 pub struct DisplayGroup {
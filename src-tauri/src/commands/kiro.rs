@@ -1,12 +1,12 @@
 use std::time::Instant;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
 use crate::models::kiro::{KiroAccount, KiroOAuthStartResponse};
-use crate::modules::{kiro_account, kiro_oauth, logger};
+use crate::modules::{kiro_account, kiro_oauth, logger, quota_events};
 
 async fn refresh_kiro_account_after_login(account: KiroAccount) -> KiroAccount {
     let account_id = account.id.clone();
-    match kiro_account::refresh_account_token(&account_id).await {
+    match kiro_account::refresh_account_token(&account_id, true).await {
         Ok(refreshed) => refreshed,
         Err(e) => {
             logger::log_warn(&format!(
@@ -59,7 +59,9 @@ pub async fn refresh_kiro_token(app: AppHandle, account_id: String) -> Result<Ki
         account_id
     ));
 
-    match kiro_account::refresh_account_token(&account_id).await {
+    let result = kiro_account::refresh_account_token(&account_id, true).await;
+    quota_events::publish_logged(&kiro_account::KiroPlatform);
+    match result {
         Ok(account) => {
             if let Err(e) = kiro_account::run_quota_alert_if_needed() {
                 logger::log_warn(&format!("[QuotaAlert][Kiro] 预警检查失败: {}", e));
@@ -90,7 +92,7 @@ pub async fn refresh_all_kiro_tokens(app: AppHandle) -> Result<i32, String> {
     let started_at = Instant::now();
     logger::log_info("[Kiro Command] 手动批量刷新开始");
 
-    let results = kiro_account::refresh_all_tokens().await?;
+    let results = kiro_account::refresh_all_tokens().await?.results;
     let success_count = results.iter().filter(|(_, item)| item.is_ok()).count();
     let failed_count = results.len().saturating_sub(success_count);
 
@@ -171,64 +173,6 @@ pub fn get_kiro_accounts_index_path() -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn inject_kiro_to_vscode(app: AppHandle, account_id: String) -> Result<String, String> {
-    let started_at = Instant::now();
-    logger::log_info(&format!(
-        "[Kiro Switch] 开始切换账号: account_id={}",
-        account_id
-    ));
-
-    let account = kiro_account::load_account(&account_id)
-        .ok_or_else(|| format!("Kiro account not found: {}", account_id))?;
-
-    if let Err(err) = crate::modules::kiro_instance::update_default_settings(
-        Some(Some(account_id.clone())),
-        None,
-        Some(false),
-    ) {
-        logger::log_warn(&format!("更新 Kiro 默认实例绑定账号失败: {}", err));
-    }
-
-    let launch_warning = match crate::commands::kiro_instance::kiro_start_instance(
-        "__default__".to_string(),
-    )
-    .await
-    {
-        Ok(_) => None,
-        Err(err) => {
-            if err.starts_with("APP_PATH_NOT_FOUND:") || err.contains("启动 Kiro 失败") {
-                logger::log_warn(&format!("Kiro 默认实例启动失败: {}", err));
-                if err.starts_with("APP_PATH_NOT_FOUND:") {
-                    let _ = app.emit(
-                        "app:path_missing",
-                        serde_json::json!({ "app": "kiro", "retry": { "kind": "default" } }),
-                    );
-                }
-                Some(err)
-            } else {
-                return Err(err);
-            }
-        }
-    };
-
-    let _ = crate::modules::tray::update_tray_menu(&app);
-
-    if let Some(err) = launch_warning {
-        logger::log_warn(&format!(
-            "[Kiro Switch] 切号完成但启动失败: account_id={}, email={}, elapsed={}ms, error={}",
-            account.id,
-            account.email,
-            started_at.elapsed().as_millis(),
-            err
-        ));
-        Ok(format!("切换完成，但 Kiro 启动失败: {}", err))
-    } else {
-        logger::log_info(&format!(
-            "[Kiro Switch] 切号成功: account_id={}, email={}, elapsed={}ms",
-            account.id,
-            account.email,
-            started_at.elapsed().as_millis()
-        ));
-        Ok(format!("切换完成: {}", account.email))
-    }
+pub async fn inject_kiro_to_vscode(account_id: String) -> Result<String, String> {
+    kiro_account::switch_account_internal(&account_id)
 }
@@ -16,32 +16,6 @@ fn is_profile_initialized(user_data_dir: &str) -> bool {
     }
 }
 
-fn inject_bound_account_for_instance_start(
-    user_data_dir: &str,
-    bind_account_id: Option<&str>,
-) -> Result<(), String> {
-    let bind_id = bind_account_id
-        .map(str::trim)
-        .filter(|value| !value.is_empty());
-    let Some(bind_id) = bind_id else {
-        return Ok(());
-    };
-
-    let account = modules::kiro_account::load_account(bind_id)
-        .ok_or_else(|| format!("绑定账号不存在: {}", bind_id))?;
-
-    modules::logger::log_info(&format!(
-        "实例启动检测到绑定 Kiro 账号，准备注入: bind_account_id={}, email={}, user_data_dir={}",
-        bind_id, account.email, user_data_dir
-    ));
-
-    modules::kiro_instance::close_kiro(&[user_data_dir.to_string()], 20)?;
-    modules::kiro_instance::inject_account_to_profile(Path::new(user_data_dir), bind_id)?;
-
-    modules::logger::log_info(&format!("Kiro 账号注入完成: {}", account.email));
-    Ok(())
-}
-
 #[tauri::command]
 pub async fn kiro_get_instance_defaults() -> Result<modules::instance::InstanceDefaults, String> {
     modules::kiro_instance::get_instance_defaults()
@@ -214,29 +188,10 @@ pub async fn kiro_start_instance(instance_id: String) -> Result<InstanceProfileV
     modules::kiro_instance::ensure_kiro_launch_path_configured()?;
 
     if instance_id == DEFAULT_INSTANCE_ID {
+        let pid = modules::kiro_instance::start_default_instance()?;
         let default_dir = modules::kiro_instance::get_default_kiro_user_data_dir()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
         let default_settings = modules::kiro_instance::load_default_settings()?;
-
-        if let Some(pid) = modules::kiro_instance::resolve_kiro_pid(default_settings.last_pid, None)
-        {
-            modules::process::close_pid(pid, 20)?;
-            let _ = modules::kiro_instance::update_default_pid(None)?;
-        }
-
-        modules::kiro_instance::close_kiro(&[default_dir_str.clone()], 20)?;
-        inject_bound_account_for_instance_start(
-            &default_dir_str,
-            default_settings.bind_account_id.as_deref(),
-        )?;
-
-        let extra_args = modules::process::parse_extra_args(&default_settings.extra_args);
-        let pid = modules::kiro_instance::start_kiro_default_with_args_with_new_window(
-            &extra_args,
-            true,
-        )?;
-        let _ = modules::kiro_instance::update_default_pid(Some(pid))?;
-
         let running = modules::kiro_instance::resolve_kiro_pid(Some(pid), None).is_some();
         return Ok(InstanceProfileView {
             id: DEFAULT_INSTANCE_ID.to_string(),
@@ -269,7 +224,7 @@ pub async fn kiro_start_instance(instance_id: String) -> Result<InstanceProfileV
     }
 
     modules::kiro_instance::close_kiro(&[instance.user_data_dir.clone()], 20)?;
-    inject_bound_account_for_instance_start(
+    modules::kiro_instance::inject_bound_account(
         &instance.user_data_dir,
         instance.bind_account_id.as_deref(),
     )?;
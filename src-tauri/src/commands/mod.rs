@@ -1,7 +1,14 @@
 pub mod account;
+pub mod account_store;
+pub mod alert_notifier;
+pub mod alert_rules;
+pub mod alert_webhook;
 pub mod announcement;
+pub mod backup;
 pub mod codex;
 pub mod codex_instance;
+pub mod cursor;
+pub mod cursor_instance;
 pub mod device;
 pub mod github_copilot;
 pub mod github_copilot_instance;
@@ -10,9 +17,16 @@ pub mod import;
 pub mod instance;
 pub mod kiro;
 pub mod kiro_instance;
+pub mod model_catalog;
 pub mod oauth;
+pub mod platform;
+pub mod quota_cache;
+pub mod quota_history;
+pub mod store_fsck;
 pub mod system;
 pub mod update;
+pub mod vault;
 pub mod wakeup;
 pub mod windsurf;
 pub mod windsurf_instance;
+pub mod ws_auth;
@@ -0,0 +1,42 @@
+//! 模型目录相关命令
+
+use crate::modules::model_catalog::{self, CatalogChange, CatalogSnapshot, GroupSuggestion};
+
+/// 各订阅等级最近一次的模型目录快照
+#[tauri::command]
+pub fn model_catalog_list_snapshots() -> Vec<CatalogSnapshot> {
+    model_catalog::list_snapshots()
+}
+
+/// 最近的模型目录变化记录（新的在前）
+#[tauri::command]
+pub fn model_catalog_list_changes(limit: Option<usize>) -> Vec<CatalogChange> {
+    model_catalog::list_changes(limit)
+}
+
+/// 尚未归入分组的新模型
+#[tauri::command]
+pub fn model_catalog_list_suggestions() -> Vec<GroupSuggestion> {
+    model_catalog::list_suggestions()
+}
+
+/// 把新模型加入分组；groupId 为空时使用推测的分组
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn model_catalog_accept_suggestion(
+    app: tauri::AppHandle,
+    modelId: String,
+    groupId: Option<String>,
+) -> Result<(), String> {
+    model_catalog::accept_suggestion(&modelId, groupId)?;
+    if let Err(err) = crate::modules::tray::update_tray_menu(&app) {
+        crate::modules::logger::log_warn(&format!("[ModelCatalog] 更新托盘菜单失败: {}", err));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn model_catalog_dismiss_suggestion(modelId: String) -> Result<(), String> {
+    model_catalog::dismiss_suggestion(&modelId)
+}
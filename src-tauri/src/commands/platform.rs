@@ -0,0 +1,123 @@
+//! 跨平台通用账号命令（由 `modules::platform` 注册表驱动）
+
+use tauri::AppHandle;
+
+use crate::modules::platform::{self, PlatformAccount, PlatformInfo};
+use crate::modules::refresh_executor::{self, RefreshReport};
+use crate::modules::refresh_scheduler::{self, RefreshScheduleStatus, RefreshTrigger};
+use crate::modules::{logger, quota_cache, tray, websocket};
+
+fn notify_changed(app: &AppHandle, source: &str) {
+    websocket::broadcast_data_changed(source);
+    if let Err(e) = tray::update_tray_menu(app) {
+        logger::log_warn(&format!("[Platform] 更新托盘菜单失败: {}", e));
+    }
+}
+
+/// 列出已注册的平台及账号数量
+#[tauri::command]
+pub fn platform_list() -> Result<Vec<PlatformInfo>, String> {
+    platform::all()
+        .iter()
+        .map(|item| platform::describe(*item))
+        .collect()
+}
+
+#[tauri::command]
+pub fn platform_list_accounts(platform: String) -> Result<Vec<PlatformAccount>, String> {
+    let target = platform::get(&platform)?;
+    let mut accounts = target.list_accounts()?;
+    quota_cache::mark_staleness(target.id(), &mut accounts);
+    Ok(accounts)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn platform_delete_accounts(
+    app: AppHandle,
+    platform: String,
+    accountIds: Vec<String>,
+) -> Result<(), String> {
+    platform::get(&platform)?.remove_accounts(&accountIds)?;
+    if let Err(e) = refresh_executor::clear_backoff(&platform, &accountIds) {
+        logger::log_warn(&format!("[Platform] 清除刷新退避状态失败: {}", e));
+    }
+    notify_changed(&app, "platform_accounts_deleted");
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn platform_update_account_tags(
+    app: AppHandle,
+    platform: String,
+    accountId: String,
+    tags: Vec<String>,
+) -> Result<(), String> {
+    platform::get(&platform)?.update_account_tags(&accountId, tags)?;
+    notify_changed(&app, "platform_account_tags_updated");
+    Ok(())
+}
+
+/// accountIds 为空时导出该平台全部账号
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn platform_export_accounts(
+    platform: String,
+    accountIds: Vec<String>,
+) -> Result<String, String> {
+    platform::export_accounts(platform::get(&platform)?, &accountIds)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn platform_import_from_json(
+    app: AppHandle,
+    platform: String,
+    jsonContent: String,
+) -> Result<usize, String> {
+    let imported = platform::get(&platform)?
+        .import_from_json(jsonContent)
+        .await?;
+    notify_changed(&app, "platform_accounts_imported");
+    Ok(imported)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn platform_refresh_account(
+    app: AppHandle,
+    platform: String,
+    accountId: String,
+) -> Result<(), String> {
+    refresh_scheduler::refresh_account(platform::get(&platform)?, accountId).await?;
+    notify_changed(&app, "platform_account_refreshed");
+    Ok(())
+}
+
+/// 切换平台账号，返回切换结果说明
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn platform_switch_account(
+    platform: String,
+    accountId: String,
+) -> Result<String, String> {
+    platform::get(&platform)?.switch_account(accountId).await
+}
+
+#[tauri::command]
+pub async fn platform_refresh_all(
+    app: AppHandle,
+    platform: String,
+) -> Result<RefreshReport, String> {
+    let target = platform::get(&platform)?;
+    let report = refresh_scheduler::refresh_platform(target, RefreshTrigger::Manual).await?;
+    notify_changed(&app, "platform_accounts_refreshed");
+    Ok(report)
+}
+
+/// 各平台后台自动刷新的计划
+#[tauri::command]
+pub fn platform_refresh_schedule() -> Vec<RefreshScheduleStatus> {
+    refresh_scheduler::status()
+}
@@ -0,0 +1,16 @@
+//! 配额接口缓存与离线模式相关命令
+
+use crate::modules::quota_cache::{self, QuotaCacheSettings};
+
+#[tauri::command]
+pub fn quota_cache_get_settings() -> QuotaCacheSettings {
+    quota_cache::load_settings()
+}
+
+/// 保存缓存设置；开启离线模式后刷新只读取缓存，不再请求各平台接口
+#[tauri::command]
+pub fn quota_cache_save_settings(
+    settings: QuotaCacheSettings,
+) -> Result<QuotaCacheSettings, String> {
+    quota_cache::save_settings(settings)
+}
@@ -0,0 +1,31 @@
+//! 配额历史查询与耗尽预测相关命令
+
+use crate::modules::platform;
+use crate::modules::quota_forecast::{self, QuotaForecast};
+use crate::modules::quota_history::{self, QuotaHistoryPoint, QuotaHistoryQuery};
+
+/// 按时间范围查询配额历史；bucketSeconds 为空时返回原始采样
+#[tauri::command]
+pub fn quota_history_query(query: QuotaHistoryQuery) -> Result<Vec<QuotaHistoryPoint>, String> {
+    quota_history::query(&query)
+}
+
+/// 清空配额历史；platform 为空时清空全部平台，返回删除的采样数
+#[tauri::command]
+pub fn quota_history_clear(platform: Option<String>) -> Result<usize, String> {
+    quota_history::clear(platform.as_deref())
+}
+
+/// 预测配额耗尽时间；accountId 为空时返回该平台全部账号
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn quota_forecast_list(
+    platform: String,
+    accountId: Option<String>,
+) -> Result<Vec<QuotaForecast>, String> {
+    let target = platform::get(&platform)?;
+    match accountId {
+        Some(account_id) => quota_forecast::forecast_account(target, &account_id),
+        None => quota_forecast::forecast_platform(target),
+    }
+}
@@ -0,0 +1,21 @@
+//! 账号存储一致性检查相关命令
+
+use crate::modules::store_fsck::{self, FsckReport};
+
+/// 检查所有平台的账号存储；repair 为 true 时尝试修复发现的问题
+#[tauri::command]
+pub fn store_fsck_run(app: tauri::AppHandle, repair: bool) -> Result<FsckReport, String> {
+    let report = store_fsck::run(repair);
+    let repaired = report
+        .platforms
+        .iter()
+        .flat_map(|platform| platform.issues.iter())
+        .any(|issue| issue.repaired);
+    if repaired {
+        crate::modules::websocket::broadcast_data_changed("store_fsck_repair");
+        if let Err(err) = crate::modules::tray::update_tray_menu(&app) {
+            crate::modules::logger::log_warn(&format!("[Fsck] 更新托盘菜单失败: {}", err));
+        }
+    }
+    Ok(report)
+}
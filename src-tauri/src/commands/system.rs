@@ -37,6 +37,12 @@ pub struct GeneralConfig {
     pub windsurf_auto_refresh_minutes: i32,
     /// Kiro 自动刷新间隔（分钟），-1 表示禁用
     pub kiro_auto_refresh_minutes: i32,
+    /// Cursor 自动刷新间隔（分钟），-1 表示禁用
+    pub cursor_auto_refresh_minutes: i32,
+    /// 批量刷新的最大并发账号数
+    pub refresh_concurrency: i32,
+    /// 批量刷新时同一主机两次请求的最小间隔（毫秒）
+    pub refresh_host_interval_ms: u64,
     /// 窗口关闭行为: "ask", "minimize", "quit"
     pub close_behavior: String,
     /// 窗口最小化行为（macOS）: "dock_and_tray", "tray_only"
@@ -55,6 +61,8 @@ pub struct GeneralConfig {
     pub windsurf_app_path: String,
     /// Kiro 启动路径（为空则使用默认路径）
     pub kiro_app_path: String,
+    /// Cursor 启动路径（为空则使用默认路径）
+    pub cursor_app_path: String,
     /// 切换 Codex 时是否自动重启 OpenCode
     pub opencode_sync_on_switch: bool,
     /// 切换 Codex 时是否自动启动/重启 Codex App
@@ -83,6 +91,10 @@ pub struct GeneralConfig {
     pub kiro_quota_alert_enabled: bool,
     /// Kiro 配额预警阈值（百分比）
     pub kiro_quota_alert_threshold: i32,
+    /// 是否启用 Cursor 配额预警通知
+    pub cursor_quota_alert_enabled: bool,
+    /// Cursor 配额预警阈值（百分比）
+    pub cursor_quota_alert_threshold: i32,
 }
 
 #[tauri::command]
@@ -165,6 +177,9 @@ pub fn save_network_config(ws_enabled: bool, ws_port: u16) -> Result<bool, Strin
         ghcp_auto_refresh_minutes: current.ghcp_auto_refresh_minutes,
         windsurf_auto_refresh_minutes: current.windsurf_auto_refresh_minutes,
         kiro_auto_refresh_minutes: current.kiro_auto_refresh_minutes,
+        cursor_auto_refresh_minutes: current.cursor_auto_refresh_minutes,
+        refresh_concurrency: current.refresh_concurrency,
+        refresh_host_interval_ms: current.refresh_host_interval_ms,
         close_behavior: current.close_behavior,
         minimize_behavior: current.minimize_behavior,
         hide_dock_icon: current.hide_dock_icon,
@@ -174,6 +189,7 @@ pub fn save_network_config(ws_enabled: bool, ws_port: u16) -> Result<bool, Strin
         vscode_app_path: current.vscode_app_path,
         windsurf_app_path: current.windsurf_app_path,
         kiro_app_path: current.kiro_app_path,
+        cursor_app_path: current.cursor_app_path,
         opencode_sync_on_switch: current.opencode_sync_on_switch,
         codex_launch_on_switch: current.codex_launch_on_switch,
         auto_switch_enabled: current.auto_switch_enabled,
@@ -188,6 +204,8 @@ pub fn save_network_config(ws_enabled: bool, ws_port: u16) -> Result<bool, Strin
         windsurf_quota_alert_threshold: current.windsurf_quota_alert_threshold,
         kiro_quota_alert_enabled: current.kiro_quota_alert_enabled,
         kiro_quota_alert_threshold: current.kiro_quota_alert_threshold,
+        cursor_quota_alert_enabled: current.cursor_quota_alert_enabled,
+        cursor_quota_alert_threshold: current.cursor_quota_alert_threshold,
     };
 
     config::save_user_config(&new_config)?;
@@ -218,6 +236,9 @@ pub fn get_general_config() -> Result<GeneralConfig, String> {
         ghcp_auto_refresh_minutes: user_config.ghcp_auto_refresh_minutes,
         windsurf_auto_refresh_minutes: user_config.windsurf_auto_refresh_minutes,
         kiro_auto_refresh_minutes: user_config.kiro_auto_refresh_minutes,
+        cursor_auto_refresh_minutes: user_config.cursor_auto_refresh_minutes,
+        refresh_concurrency: user_config.refresh_concurrency,
+        refresh_host_interval_ms: user_config.refresh_host_interval_ms,
         close_behavior: close_behavior_str.to_string(),
         minimize_behavior: minimize_behavior_str.to_string(),
         hide_dock_icon: user_config.hide_dock_icon,
@@ -227,6 +248,7 @@ pub fn get_general_config() -> Result<GeneralConfig, String> {
         vscode_app_path: user_config.vscode_app_path,
         windsurf_app_path: user_config.windsurf_app_path,
         kiro_app_path: user_config.kiro_app_path,
+        cursor_app_path: user_config.cursor_app_path,
         opencode_sync_on_switch: user_config.opencode_sync_on_switch,
         codex_launch_on_switch: user_config.codex_launch_on_switch,
         auto_switch_enabled: user_config.auto_switch_enabled,
@@ -241,6 +263,8 @@ pub fn get_general_config() -> Result<GeneralConfig, String> {
         windsurf_quota_alert_threshold: user_config.windsurf_quota_alert_threshold,
         kiro_quota_alert_enabled: user_config.kiro_quota_alert_enabled,
         kiro_quota_alert_threshold: user_config.kiro_quota_alert_threshold,
+        cursor_quota_alert_enabled: user_config.cursor_quota_alert_enabled,
+        cursor_quota_alert_threshold: user_config.cursor_quota_alert_threshold,
     })
 }
 
@@ -255,6 +279,9 @@ pub fn save_general_config(
     ghcp_auto_refresh_minutes: Option<i32>,
     windsurf_auto_refresh_minutes: Option<i32>,
     kiro_auto_refresh_minutes: Option<i32>,
+    cursor_auto_refresh_minutes: Option<i32>,
+    refresh_concurrency: Option<i32>,
+    refresh_host_interval_ms: Option<u64>,
     close_behavior: String,
     minimize_behavior: Option<String>,
     hide_dock_icon: Option<bool>,
@@ -264,6 +291,7 @@ pub fn save_general_config(
     vscode_app_path: String,
     windsurf_app_path: Option<String>,
     kiro_app_path: Option<String>,
+    cursor_app_path: Option<String>,
     opencode_sync_on_switch: bool,
     codex_launch_on_switch: bool,
     auto_switch_enabled: Option<bool>,
@@ -278,6 +306,8 @@ pub fn save_general_config(
     windsurf_quota_alert_threshold: Option<i32>,
     kiro_quota_alert_enabled: Option<bool>,
     kiro_quota_alert_threshold: Option<i32>,
+    cursor_quota_alert_enabled: Option<bool>,
+    cursor_quota_alert_threshold: Option<i32>,
 ) -> Result<(), String> {
     let current = config::get_user_config();
     let normalized_opencode_path = opencode_app_path.trim().to_string();
@@ -290,6 +320,9 @@ pub fn save_general_config(
     let normalized_kiro_path = kiro_app_path
         .map(|value| value.trim().to_string())
         .unwrap_or_else(|| current.kiro_app_path.clone());
+    let normalized_cursor_path = cursor_app_path
+        .map(|value| value.trim().to_string())
+        .unwrap_or_else(|| current.cursor_app_path.clone());
     // 标准化语言代码为小写，确保与插件端格式一致
     let normalized_language = language.to_lowercase();
     let language_changed = current.language != normalized_language;
@@ -324,6 +357,13 @@ pub fn save_general_config(
             .unwrap_or(current.windsurf_auto_refresh_minutes),
         kiro_auto_refresh_minutes: kiro_auto_refresh_minutes
             .unwrap_or(current.kiro_auto_refresh_minutes),
+        cursor_auto_refresh_minutes: cursor_auto_refresh_minutes
+            .unwrap_or(current.cursor_auto_refresh_minutes),
+        refresh_concurrency: refresh_concurrency
+            .map(|value| value.clamp(1, modules::refresh_executor::MAX_CONCURRENCY as i32))
+            .unwrap_or(current.refresh_concurrency),
+        refresh_host_interval_ms: refresh_host_interval_ms
+            .unwrap_or(current.refresh_host_interval_ms),
         close_behavior: close_behavior_enum,
         minimize_behavior: minimize_behavior_enum,
         hide_dock_icon: hide_dock_icon_value,
@@ -333,6 +373,7 @@ pub fn save_general_config(
         vscode_app_path: normalized_vscode_path,
         windsurf_app_path: normalized_windsurf_path,
         kiro_app_path: normalized_kiro_path,
+        cursor_app_path: normalized_cursor_path,
         opencode_sync_on_switch,
         codex_launch_on_switch,
         auto_switch_enabled: auto_switch_enabled.unwrap_or(current.auto_switch_enabled),
@@ -355,9 +396,14 @@ pub fn save_general_config(
             .unwrap_or(current.kiro_quota_alert_enabled),
         kiro_quota_alert_threshold: kiro_quota_alert_threshold
             .unwrap_or(current.kiro_quota_alert_threshold),
+        cursor_quota_alert_enabled: cursor_quota_alert_enabled
+            .unwrap_or(current.cursor_quota_alert_enabled),
+        cursor_quota_alert_threshold: cursor_quota_alert_threshold
+            .unwrap_or(current.cursor_quota_alert_threshold),
     };
 
     config::save_user_config(&new_config)?;
+    modules::refresh_scheduler::reschedule();
 
     #[cfg(target_os = "macos")]
     if hide_dock_icon_changed {
@@ -405,6 +451,7 @@ pub fn set_app_path(app: String, path: String) -> Result<(), String> {
         "vscode" => current.vscode_app_path = normalized_path,
         "windsurf" => current.windsurf_app_path = normalized_path,
         "kiro" => current.kiro_app_path = normalized_path,
+        "cursor" => current.cursor_app_path = normalized_path,
         "opencode" => current.opencode_app_path = normalized_path,
         _ => return Err("未知应用类型".to_string()),
     }
@@ -420,6 +467,9 @@ pub fn detect_app_path(app: String, force: Option<bool>) -> Result<Option<String
         "kiro" => Ok(modules::kiro_instance::detect_and_save_kiro_launch_path(
             force,
         )),
+        "cursor" => Ok(modules::cursor_instance::detect_and_save_cursor_launch_path(
+            force,
+        )),
         "antigravity" | "codex" | "vscode" | "opencode" => Ok(
             modules::process::detect_and_save_app_path(app.as_str(), force),
         ),
@@ -530,17 +580,29 @@ pub async fn delete_corrupted_file(path: String) -> Result<(), String> {
         return Ok(());
     }
 
-    // 创建备份文件名
-    let timestamp = chrono::Utc::now().timestamp();
-    let backup_name = format!("{}.corrupted.{}", path, timestamp);
-
-    // 备份文件
-    std::fs::rename(&path, &backup_name).map_err(|e| format!("备份损坏文件失败: {}", e))?;
+    // 备份为 <path>.corrupted.<timestamp>
+    let backup_path = modules::store_fsck::quarantine_file(file_path)?;
 
     modules::logger::log_info(&format!(
         "已备份并删除损坏文件: {} -> {}",
-        path, backup_name
+        path,
+        backup_path.display()
     ));
 
     Ok(())
 }
+
+/// 诊断共享数据文件的锁占用情况（accounts.json / group_settings.json / sync_settings.json）
+#[tauri::command]
+pub fn get_shared_file_lock_status() -> Result<Vec<crate::utils::file_lock::FileLockStatus>, String> {
+    modules::account::get_shared_file_lock_status()
+}
+
+/// 各数据文件最近一次读取到的格式版本（含迁移来源）
+#[tauri::command]
+pub fn get_schema_versions() -> Result<
+    std::collections::BTreeMap<String, modules::schema_migration::SchemaRecord>,
+    String,
+> {
+    modules::schema_migration::list_records()
+}
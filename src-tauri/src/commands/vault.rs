@@ -0,0 +1,37 @@
+//! 凭据保险库相关命令
+
+use crate::modules::credential_vault::{self, VaultKeySource, VaultStatus};
+
+/// 获取保险库状态
+#[tauri::command]
+pub fn vault_get_status() -> Result<VaultStatus, String> {
+    Ok(credential_vault::get_status())
+}
+
+/// 启用保险库（keySource: passphrase | keyring）
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn vault_enable(
+    keySource: VaultKeySource,
+    passphrase: Option<String>,
+) -> Result<VaultStatus, String> {
+    credential_vault::enable(keySource, passphrase.as_deref())
+}
+
+/// 解锁保险库
+#[tauri::command]
+pub fn vault_unlock(passphrase: Option<String>) -> Result<VaultStatus, String> {
+    credential_vault::unlock(passphrase.as_deref())
+}
+
+/// 锁定保险库
+#[tauri::command]
+pub fn vault_lock() -> Result<VaultStatus, String> {
+    Ok(credential_vault::lock())
+}
+
+/// 关闭保险库，账号恢复为明文存储
+#[tauri::command]
+pub fn vault_disable(passphrase: Option<String>) -> Result<VaultStatus, String> {
+    credential_vault::disable(passphrase.as_deref())
+}
@@ -1,12 +1,12 @@
 use std::time::Instant;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
 use crate::models::windsurf::{WindsurfAccount, WindsurfOAuthStartResponse};
-use crate::modules::{logger, windsurf_account, windsurf_oauth};
+use crate::modules::{logger, quota_events, windsurf_account, windsurf_oauth};
 
 async fn refresh_windsurf_account_after_login(account: WindsurfAccount) -> WindsurfAccount {
     let account_id = account.id.clone();
-    match windsurf_account::refresh_account_token(&account_id).await {
+    match windsurf_account::refresh_account_token(&account_id, true).await {
         Ok(refreshed) => refreshed,
         Err(e) => {
             logger::log_warn(&format!(
@@ -68,7 +68,9 @@ pub async fn refresh_windsurf_token(
         "[Windsurf Command] 手动刷新账号开始: account_id={}",
         account_id
     ));
-    match windsurf_account::refresh_account_token(&account_id).await {
+    let result = windsurf_account::refresh_account_token(&account_id, true).await;
+    quota_events::publish_logged(&windsurf_account::WindsurfPlatform);
+    match result {
         Ok(account) => {
             if let Err(e) = windsurf_account::run_quota_alert_if_needed() {
                 logger::log_warn(&format!("[QuotaAlert][Windsurf] 预警检查失败: {}", e));
@@ -98,7 +100,7 @@ pub async fn refresh_windsurf_token(
 pub async fn refresh_all_windsurf_tokens(app: AppHandle) -> Result<i32, String> {
     let started_at = Instant::now();
     logger::log_info("[Windsurf Command] 手动批量刷新开始");
-    let results = windsurf_account::refresh_all_tokens().await?;
+    let results = windsurf_account::refresh_all_tokens().await?.results;
     let success_count = results.iter().filter(|(_, r)| r.is_ok()).count();
     let failed: Vec<String> = results
         .iter()
@@ -208,71 +210,6 @@ pub fn get_windsurf_accounts_index_path() -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn inject_windsurf_to_vscode(
-    app: AppHandle,
-    account_id: String,
-) -> Result<String, String> {
-    let started_at = Instant::now();
-    logger::log_info(&format!(
-        "[Windsurf Switch] 开始切换账号: account_id={}",
-        account_id
-    ));
-    let account = windsurf_account::load_account(&account_id)
-        .ok_or_else(|| format!("Windsurf account not found: {}", account_id))?;
-    logger::log_info(&format!(
-        "[Windsurf Switch] 目标账号信息: login={}, email={}",
-        account.github_login,
-        account.github_email.as_deref().unwrap_or("-")
-    ));
-
-    if let Err(e) = crate::modules::windsurf_instance::update_default_settings(
-        Some(Some(account_id.clone())),
-        None,
-        Some(false),
-    ) {
-        logger::log_warn(&format!("更新 Windsurf 默认实例绑定账号失败: {}", e));
-    }
-
-    let launch_warning = match crate::commands::windsurf_instance::windsurf_start_instance(
-        "__default__".to_string(),
-    )
-    .await
-    {
-        Ok(_) => None,
-        Err(e) => {
-            if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("启动 Windsurf 失败") {
-                logger::log_warn(&format!("Windsurf 默认实例启动失败: {}", e));
-                if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("APP_PATH_NOT_FOUND:") {
-                    let _ = app.emit(
-                        "app:path_missing",
-                        serde_json::json!({ "app": "windsurf", "retry": { "kind": "default" } }),
-                    );
-                }
-                Some(e)
-            } else {
-                return Err(e);
-            }
-        }
-    };
-
-    if let Some(err) = launch_warning {
-        let _ = crate::modules::tray::update_tray_menu(&app);
-        logger::log_warn(&format!(
-            "[Windsurf Switch] 切号完成但启动失败: account_id={}, login={}, elapsed={}ms, error={}",
-            account.id,
-            account.github_login,
-            started_at.elapsed().as_millis(),
-            err
-        ));
-        Ok(format!("切换完成，但 Windsurf 启动失败: {}", err))
-    } else {
-        let _ = crate::modules::tray::update_tray_menu(&app);
-        logger::log_info(&format!(
-            "[Windsurf Switch] 切号成功: account_id={}, login={}, elapsed={}ms",
-            account.id,
-            account.github_login,
-            started_at.elapsed().as_millis()
-        ));
-        Ok(format!("切换完成: {}", account.github_login))
-    }
+pub async fn inject_windsurf_to_vscode(account_id: String) -> Result<String, String> {
+    windsurf_account::switch_account_internal(&account_id)
 }
@@ -16,30 +16,6 @@ fn is_profile_initialized(user_data_dir: &str) -> bool {
     }
 }
 
-fn inject_bound_account_for_instance_start(
-    user_data_dir: &str,
-    bind_account_id: Option<&str>,
-) -> Result<(), String> {
-    let bind_id = bind_account_id
-        .map(str::trim)
-        .filter(|value| !value.is_empty());
-    let Some(bind_id) = bind_id else {
-        return Ok(());
-    };
-
-    let account = modules::windsurf_account::load_account(bind_id)
-        .ok_or_else(|| format!("绑定账号不存在: {}", bind_id))?;
-    modules::logger::log_info(&format!(
-        "实例启动检测到绑定账号，准备注入: bind_account_id={}, login={}, user_data_dir={}",
-        bind_id, account.github_login, user_data_dir
-    ));
-
-    modules::windsurf_instance::close_windsurf(&[user_data_dir.to_string()], 20)?;
-    modules::windsurf_instance::inject_account_to_profile(Path::new(user_data_dir), bind_id)?;
-    modules::logger::log_info(&format!("Windsurf 账号注入完成: {}", account.github_login));
-    Ok(())
-}
-
 #[tauri::command]
 pub async fn windsurf_get_instance_defaults() -> Result<modules::instance::InstanceDefaults, String>
 {
@@ -215,26 +191,10 @@ pub async fn windsurf_start_instance(instance_id: String) -> Result<InstanceProf
     modules::windsurf_instance::ensure_windsurf_launch_path_configured()?;
 
     if instance_id == DEFAULT_INSTANCE_ID {
+        let pid = modules::windsurf_instance::start_default_instance()?;
         let default_dir = modules::windsurf_instance::get_default_windsurf_user_data_dir()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
         let default_settings = modules::windsurf_instance::load_default_settings()?;
-        if let Some(pid) =
-            modules::windsurf_instance::resolve_windsurf_pid(default_settings.last_pid, None)
-        {
-            modules::process::close_pid(pid, 20)?;
-            let _ = modules::windsurf_instance::update_default_pid(None)?;
-        }
-        modules::windsurf_instance::close_windsurf(&[default_dir_str.clone()], 20)?;
-        inject_bound_account_for_instance_start(
-            &default_dir_str,
-            default_settings.bind_account_id.as_deref(),
-        )?;
-        let extra_args = modules::process::parse_extra_args(&default_settings.extra_args);
-        let pid = modules::windsurf_instance::start_windsurf_default_with_args_with_new_window(
-            &extra_args,
-            true,
-        )?;
-        let _ = modules::windsurf_instance::update_default_pid(Some(pid))?;
         let running = modules::windsurf_instance::resolve_windsurf_pid(Some(pid), None).is_some();
         return Ok(InstanceProfileView {
             id: DEFAULT_INSTANCE_ID.to_string(),
@@ -267,7 +227,7 @@ pub async fn windsurf_start_instance(instance_id: String) -> Result<InstanceProf
         let _ = modules::windsurf_instance::update_instance_pid(&instance.id, None)?;
     }
     modules::windsurf_instance::close_windsurf(&[instance.user_data_dir.clone()], 20)?;
-    inject_bound_account_for_instance_start(
+    modules::windsurf_instance::inject_bound_account(
         &instance.user_data_dir,
         instance.bind_account_id.as_deref(),
     )?;
@@ -0,0 +1,39 @@
+//! WebSocket 客户端授权相关命令
+
+use crate::modules::websocket;
+use crate::modules::ws_auth::{self, PairedClient, PairingRequest, WsScope};
+
+/// 已配对的客户端
+#[tauri::command]
+pub fn ws_auth_list_clients() -> Vec<PairedClient> {
+    ws_auth::list_clients()
+}
+
+/// 撤销客户端授权，已建立的连接立即失去权限
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn ws_auth_revoke_client(clientId: String) -> Result<(), String> {
+    websocket::revoke_client(&clientId).await
+}
+
+/// 等待批准的配对请求
+#[tauri::command]
+pub fn ws_auth_list_pairings() -> Vec<PairingRequest> {
+    ws_auth::list_pairings()
+}
+
+/// 批准配对；scopes 为空时授予客户端申请的全部权限
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn ws_auth_approve_pairing(
+    pairingId: String,
+    scopes: Option<Vec<WsScope>>,
+) -> Result<PairedClient, String> {
+    websocket::approve_pairing(&pairingId, scopes).await
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn ws_auth_reject_pairing(pairingId: String) -> Result<(), String> {
+    websocket::reject_pairing(&pairingId).await
+}
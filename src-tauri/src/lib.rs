@@ -103,6 +103,24 @@ pub fn run() {
                 }
             }
 
+            // 统一账号存储：首次启动时从 JSON 布局导入
+            modules::account_store::init_on_startup();
+
+            // 加载凭据保险库（系统钥匙串模式自动解锁）
+            modules::credential_vault::init_on_startup();
+
+            // 定时快照数据目录
+            modules::backup::start_scheduler();
+
+            // 记录配额基线，之后每次刷新对比产生配额变化事件
+            modules::quota_events::prime();
+
+            // 后台按各平台间隔自动刷新账号
+            modules::refresh_scheduler::start();
+
+            // 免打扰结束或汇总周期到达时发出暂存的预警
+            modules::alert_notifier::start();
+
             // 启动 WebSocket 服务（使用 Tauri 的 async runtime）
             tauri::async_runtime::spawn(async {
                 modules::websocket::start_server().await;
@@ -209,6 +227,8 @@ pub fn run() {
             commands::system::handle_window_close,
             commands::system::open_folder,
             commands::system::delete_corrupted_file,
+            commands::system::get_shared_file_lock_status,
+            commands::system::get_schema_versions,
             // Wakeup Commands
             commands::wakeup::wakeup_ensure_runtime_ready,
             commands::wakeup::trigger_wakeup,
@@ -241,6 +261,7 @@ pub fn run() {
             commands::group::delete_group,
             commands::group::update_group_order,
             commands::group::get_display_groups,
+            commands::group::get_group_capacity,
             // Codex Commands
             commands::codex::list_codex_accounts,
             commands::codex::get_current_codex_account,
@@ -318,6 +339,18 @@ pub fn run() {
             commands::kiro::update_kiro_account_tags,
             commands::kiro::get_kiro_accounts_index_path,
             commands::kiro::inject_kiro_to_vscode,
+            // Cursor Commands
+            commands::cursor::list_cursor_accounts,
+            commands::cursor::delete_cursor_account,
+            commands::cursor::delete_cursor_accounts,
+            commands::cursor::import_cursor_from_json,
+            commands::cursor::import_cursor_from_local,
+            commands::cursor::export_cursor_accounts,
+            commands::cursor::refresh_cursor_token,
+            commands::cursor::refresh_all_cursor_tokens,
+            commands::cursor::update_cursor_account_tags,
+            commands::cursor::get_cursor_accounts_index_path,
+            commands::cursor::inject_cursor_to_vscode,
             // Windsurf Instance Commands
             commands::windsurf_instance::windsurf_get_instance_defaults,
             commands::windsurf_instance::windsurf_list_instances,
@@ -338,6 +371,16 @@ pub fn run() {
             commands::kiro_instance::kiro_stop_instance,
             commands::kiro_instance::kiro_open_instance_window,
             commands::kiro_instance::kiro_close_all_instances,
+            // Cursor Instance Commands
+            commands::cursor_instance::cursor_get_instance_defaults,
+            commands::cursor_instance::cursor_list_instances,
+            commands::cursor_instance::cursor_create_instance,
+            commands::cursor_instance::cursor_update_instance,
+            commands::cursor_instance::cursor_delete_instance,
+            commands::cursor_instance::cursor_start_instance,
+            commands::cursor_instance::cursor_stop_instance,
+            commands::cursor_instance::cursor_open_instance_window,
+            commands::cursor_instance::cursor_close_all_instances,
             // Codex Instance Commands
             commands::codex_instance::codex_get_instance_defaults,
             commands::codex_instance::codex_list_instances,
@@ -358,6 +401,67 @@ pub fn run() {
             commands::instance::stop_instance,
             commands::instance::open_instance_window,
             commands::instance::close_all_instances,
+            // Account Store Commands
+            commands::account_store::account_store_get_status,
+            commands::account_store::account_store_import_from_json,
+            commands::account_store::account_store_rollback_to_json,
+            // Vault Commands
+            commands::vault::vault_get_status,
+            commands::vault::vault_enable,
+            commands::vault::vault_unlock,
+            commands::vault::vault_lock,
+            commands::vault::vault_disable,
+            // Backup Commands
+            commands::backup::backup_list_snapshots,
+            commands::backup::backup_create_snapshot,
+            commands::backup::backup_diff_snapshot,
+            commands::backup::backup_restore_snapshot,
+            commands::backup::backup_get_settings,
+            commands::backup::backup_save_settings,
+            // Store Fsck Commands
+            commands::store_fsck::store_fsck_run,
+            // Platform Commands
+            commands::platform::platform_list,
+            commands::platform::platform_list_accounts,
+            commands::platform::platform_delete_accounts,
+            commands::platform::platform_update_account_tags,
+            commands::platform::platform_export_accounts,
+            commands::platform::platform_import_from_json,
+            commands::platform::platform_refresh_account,
+            commands::platform::platform_switch_account,
+            commands::platform::platform_refresh_all,
+            commands::platform::platform_refresh_schedule,
+            // Quota History Commands
+            commands::quota_history::quota_history_query,
+            commands::quota_history::quota_history_clear,
+            commands::quota_history::quota_forecast_list,
+            // Alert Rule Commands
+            commands::alert_rules::alert_rule_list,
+            commands::alert_rules::alert_rule_save,
+            commands::alert_rules::alert_rule_delete,
+            commands::alert_rules::alert_rule_test,
+            // Alert Webhook Commands
+            commands::alert_webhook::alert_webhook_get_settings,
+            commands::alert_webhook::alert_webhook_save_settings,
+            commands::alert_webhook::alert_webhook_send_test,
+            // Alert Notification Commands
+            commands::alert_notifier::alert_notify_get_settings,
+            commands::alert_notifier::alert_notify_save_settings,
+            // Quota Cache Commands
+            commands::quota_cache::quota_cache_get_settings,
+            commands::quota_cache::quota_cache_save_settings,
+            // Model Catalog Commands
+            commands::model_catalog::model_catalog_list_snapshots,
+            commands::model_catalog::model_catalog_list_changes,
+            commands::model_catalog::model_catalog_list_suggestions,
+            commands::model_catalog::model_catalog_accept_suggestion,
+            commands::model_catalog::model_catalog_dismiss_suggestion,
+            // WebSocket Auth Commands
+            commands::ws_auth::ws_auth_list_clients,
+            commands::ws_auth::ws_auth_revoke_client,
+            commands::ws_auth::ws_auth_list_pairings,
+            commands::ws_auth::ws_auth_approve_pairing,
+            commands::ws_auth::ws_auth_reject_pairing,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
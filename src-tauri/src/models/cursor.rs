@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorAccount {
+    pub id: String,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sign_up_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+
+    // 敏感字段：仅用于切号/刷新，不应打印到日志。
+    pub access_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub membership_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub premium_requests_used: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub premium_requests_limit: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage_reset_at: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_usage_raw: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_reason: Option<String>,
+
+    pub created_at: i64,
+    pub last_used: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorAccountSummary {
+    pub id: String,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub membership_type: Option<String>,
+    pub created_at: i64,
+    pub last_used: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorAccountIndex {
+    pub version: String,
+    pub accounts: Vec<CursorAccountSummary>,
+}
+
+impl CursorAccountIndex {
+    pub fn new() -> Self {
+        Self {
+            version: "1.0".to_string(),
+            accounts: Vec::new(),
+        }
+    }
+}
+
+impl Default for CursorAccountIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 从本机 state.vscdb 读取到的 Cursor 登录信息
+#[derive(Debug, Clone)]
+pub struct CursorLocalAuth {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub email: Option<String>,
+    pub sign_up_type: Option<String>,
+    pub membership_type: Option<String>,
+}
+
+impl CursorAccount {
+    pub fn summary(&self) -> CursorAccountSummary {
+        CursorAccountSummary {
+            id: self.id.clone(),
+            email: self.email.clone(),
+            tags: self.tags.clone(),
+            membership_type: self.membership_type.clone(),
+            created_at: self.created_at,
+            last_used: self.last_used,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
@@ -11,20 +12,46 @@ use crate::models::{
     QuotaErrorInfo, TokenData,
 };
 use crate::modules;
+use crate::modules::account_store::{self, StoreSpec};
+use crate::modules::alert_rules::AlertSeverity;
+use crate::modules::platform::{self, Platform, PlatformAccount, QuotaMetric, UnifiedQuota};
+use crate::modules::refresh_executor::{self, RefreshReport};
+use crate::modules::schema_migration::{self, SchemaSpec, VersionEncoding};
+use crate::modules::store_fsck;
+use crate::utils::{atomic_file, file_lock};
 
 static ACCOUNT_INDEX_LOCK: std::sync::LazyLock<Mutex<()>> =
     std::sync::LazyLock::new(|| Mutex::new(()));
 static AUTO_SWITCH_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
-static QUOTA_ALERT_LAST_SENT: std::sync::LazyLock<Mutex<HashMap<String, i64>>> =
-    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
-
-const QUOTA_ALERT_COOLDOWN_SECONDS: i64 = 300;
 
 // 使用与 AntigravityCockpit 插件相同的数据目录
 const DATA_DIR: &str = ".antigravity_cockpit";
 const ACCOUNTS_INDEX: &str = "accounts.json";
 const ACCOUNTS_DIR: &str = "accounts";
 const DELETED_ACCOUNT_FP_BINDINGS: &str = "deleted_account_fingerprint_bindings.json";
+/// 账号文件中需要由凭据保险库加密的字段
+const SECRET_FIELDS: &[&str] = &["/token/access_token", "/token/refresh_token"];
+
+/// accounts.json 的格式版本（与插件端共享）
+const INDEX_SCHEMA: SchemaSpec = SchemaSpec {
+    name: ACCOUNTS_INDEX,
+    version_field: "version",
+    encoding: VersionEncoding::Dotted,
+    current: 2,
+    baseline: 2,
+    migrations: &[],
+};
+
+pub const STORE_SPEC: StoreSpec = StoreSpec {
+    platform: "antigravity",
+    base_dir: get_data_dir,
+    index_file: ACCOUNTS_INDEX,
+    accounts_dir: ACCOUNTS_DIR,
+    secret_fields: SECRET_FIELDS,
+    quota_fields: &["quota", "quota_error"],
+    // accounts.json 同时供 VS Code 扩展读取
+    mirror_index: true,
+};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct DeletedAccountFingerprintBindings {
@@ -66,7 +93,7 @@ fn save_deleted_account_fp_bindings(
     let path = get_deleted_account_fp_bindings_path()?;
     let content =
         serde_json::to_string_pretty(bindings).map_err(|e| format!("序列化指纹映射失败: {}", e))?;
-    fs::write(path, content).map_err(|e| format!("保存指纹映射失败: {}", e))
+    atomic_file::write(path, content).map_err(|e| format!("保存指纹映射失败: {}", e))
 }
 
 fn remember_deleted_account_fingerprint(account: &Account) -> Result<(), String> {
@@ -141,6 +168,32 @@ pub fn get_data_dir() -> Result<PathBuf, String> {
     Ok(data_dir)
 }
 
+/// 账号索引锁：进程内互斥 + 跨进程文件锁（与 VS Code 插件共享 accounts.json）
+pub struct AccountIndexLock {
+    _file: file_lock::FileLockGuard,
+    _guard: std::sync::MutexGuard<'static, ()>,
+}
+
+pub fn lock_account_index(purpose: &str) -> Result<AccountIndexLock, String> {
+    let guard = ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let file = file_lock::lock_exclusive(&get_data_dir()?.join(ACCOUNTS_INDEX), purpose)?;
+    Ok(AccountIndexLock {
+        _file: file,
+        _guard: guard,
+    })
+}
+
+/// 获取共享数据文件的锁状态（用于排查多端同时写入）
+pub fn get_shared_file_lock_status() -> Result<Vec<file_lock::FileLockStatus>, String> {
+    let shared_dir = get_data_dir()?;
+    Ok([ACCOUNTS_INDEX, "group_settings.json", "sync_settings.json"]
+        .iter()
+        .map(|name| file_lock::inspect(&shared_dir.join(name)))
+        .collect())
+}
+
 /// 获取账号目录路径
 pub fn get_accounts_dir() -> Result<PathBuf, String> {
     let data_dir = get_data_dir()?;
@@ -155,6 +208,10 @@ pub fn get_accounts_dir() -> Result<PathBuf, String> {
 
 /// 加载账号索引
 pub fn load_account_index() -> Result<AccountIndex, String> {
+    if account_store::is_active() {
+        return Ok(account_store::load_index(&STORE_SPEC)?.unwrap_or_default());
+    }
+
     let data_dir = get_data_dir()?;
     let index_path = data_dir.join(ACCOUNTS_INDEX);
 
@@ -168,32 +225,41 @@ pub fn load_account_index() -> Result<AccountIndex, String> {
     if content.trim().is_empty() {
         return Ok(AccountIndex::new());
     }
+    let content = schema_migration::upgrade_on_load(&INDEX_SCHEMA, &index_path, content);
 
-    serde_json::from_str(&content).map_err(|e| {
-        crate::error::file_corrupted_error(
-            ACCOUNTS_INDEX,
-            &index_path.to_string_lossy(),
-            &e.to_string(),
-        )
+    serde_json::from_str(&content).or_else(|e| {
+        atomic_file::recover_json(&index_path).ok_or_else(|| {
+            crate::error::file_corrupted_error(
+                ACCOUNTS_INDEX,
+                &index_path.to_string_lossy(),
+                &e.to_string(),
+            )
+        })
     })
 }
 
 /// 保存账号索引
 pub fn save_account_index(index: &AccountIndex) -> Result<(), String> {
-    let data_dir = get_data_dir()?;
-    let index_path = data_dir.join(ACCOUNTS_INDEX);
-    let temp_path = data_dir.join(format!("{}.tmp", ACCOUNTS_INDEX));
+    if account_store::is_active() {
+        return account_store::save_index(&STORE_SPEC, index);
+    }
+
+    let index_path = get_data_dir()?.join(ACCOUNTS_INDEX);
+    schema_migration::ensure_writable(&INDEX_SCHEMA, &index_path)?;
 
     let content =
         serde_json::to_string_pretty(index).map_err(|e| format!("序列化账号索引失败: {}", e))?;
 
-    fs::write(&temp_path, content).map_err(|e| format!("写入临时索引文件失败: {}", e))?;
-
-    fs::rename(temp_path, index_path).map_err(|e| format!("替换索引文件失败: {}", e))
+    atomic_file::write(&index_path, content).map_err(|e| format!("写入账号索引失败: {}", e))
 }
 
 /// 加载账号数据
 pub fn load_account(account_id: &str) -> Result<Account, String> {
+    if account_store::is_active() {
+        return account_store::load_record(&STORE_SPEC, account_id)?
+            .ok_or_else(|| format!("账号不存在: {}", account_id));
+    }
+
     let accounts_dir = get_accounts_dir()?;
     let account_path = accounts_dir.join(format!("{}.json", account_id));
 
@@ -204,18 +270,78 @@ pub fn load_account(account_id: &str) -> Result<Account, String> {
     let content =
         fs::read_to_string(&account_path).map_err(|e| format!("读取账号数据失败: {}", e))?;
 
-    serde_json::from_str(&content).map_err(|e| format!("解析账号数据失败: {}", e))
+    modules::credential_vault::from_sealed_json(&content, SECRET_FIELDS)
 }
 
 /// 保存账号数据
 pub fn save_account(account: &Account) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_record(&STORE_SPEC, &account.id, account);
+    }
+
     let accounts_dir = get_accounts_dir()?;
     let account_path = accounts_dir.join(format!("{}.json", account.id));
 
-    let content =
-        serde_json::to_string_pretty(account).map_err(|e| format!("序列化账号数据失败: {}", e))?;
+    let content = modules::credential_vault::to_sealed_json(account, SECRET_FIELDS)?;
 
-    fs::write(&account_path, content).map_err(|e| format!("保存账号数据失败: {}", e))
+    atomic_file::write_secret(&account_path, content)
+        .map_err(|e| format!("保存账号数据失败: {}", e))
+}
+
+/// 删除账号数据
+fn delete_account_file(account_id: &str) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::delete_record(&STORE_SPEC, account_id);
+    }
+
+    let account_path = get_accounts_dir()?.join(format!("{}.json", account_id));
+    if account_path.exists() {
+        fs::remove_file(&account_path).map_err(|e| format!("删除账号文件失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 以账号数据为准重建索引（一致性修复使用），返回索引中的账号数
+pub fn rebuild_account_index() -> Result<usize, String> {
+    let _lock = lock_account_index("rebuild_account_index")?;
+    let previous = store_fsck::read_index(&STORE_SPEC).unwrap_or_default();
+    let accounts: Vec<Account> = store_fsck::list_stored_ids(&STORE_SPEC)?
+        .iter()
+        .filter_map(|id| load_account(id).ok())
+        .collect();
+    let accounts =
+        store_fsck::order_by_index(accounts, &previous.account_ids, |a| &a.id, |a| a.created_at);
+
+    let mut index = AccountIndex::new();
+    index.accounts = accounts
+        .iter()
+        .map(|account| AccountSummary {
+            id: account.id.clone(),
+            email: account.email.clone(),
+            name: account.name.clone(),
+            created_at: account.created_at,
+            last_used: account.last_used,
+        })
+        .collect();
+    let ids: Vec<String> = index.accounts.iter().map(|s| s.id.clone()).collect();
+    index.current_account_id =
+        store_fsck::repair_current_account_id(previous.current_account_id, &ids);
+    save_account_index(&index)?;
+    Ok(ids.len())
+}
+
+/// 按凭据保险库当前状态重新写回所有账号文件（启用/关闭保险库时调用）
+pub fn reseal_account_files() -> Result<usize, String> {
+    let _lock = lock_account_index("reseal_account_files")?;
+    let index = load_account_index()?;
+    let mut count = 0;
+    for summary in &index.accounts {
+        if let Ok(account) = load_account(&summary.id) {
+            save_account(&account)?;
+            count += 1;
+        }
+    }
+    Ok(count)
 }
 
 fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
@@ -261,7 +387,7 @@ pub fn list_accounts() -> Result<Vec<Account>, String> {
     for summary in &index.accounts {
         match load_account(&summary.id) {
             Ok(mut account) => {
-                let _ = modules::quota_cache::apply_cached_quota(&mut account, "authorized");
+                let _ = modules::quota::apply_cached_quota(&mut account);
                 accounts.push(account);
             }
             Err(e) => {
@@ -332,9 +458,7 @@ pub fn add_account(
     name: Option<String>,
     token: TokenData,
 ) -> Result<Account, String> {
-    let _lock = ACCOUNT_INDEX_LOCK
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let _lock = lock_account_index("add_account")?;
     let mut index = load_account_index()?;
 
     if find_matching_account_id(&index, &email, &token)?.is_some() {
@@ -367,8 +491,6 @@ pub fn add_account(
         account.fingerprint_id = Some(fingerprint.id.clone());
     }
 
-    save_account(&account)?;
-
     index.accounts.push(AccountSummary {
         id: account_id.clone(),
         email: email.clone(),
@@ -381,7 +503,10 @@ pub fn add_account(
         index.current_account_id = Some(account_id);
     }
 
-    save_account_index(&index)?;
+    account_store::write_batch(|| {
+        save_account(&account)?;
+        save_account_index(&index)
+    })?;
 
     if reused_fp_id.is_some() {
         if let Err(e) = clear_deleted_account_fingerprint(&email) {
@@ -401,9 +526,7 @@ pub fn upsert_account(
     name: Option<String>,
     token: TokenData,
 ) -> Result<Account, String> {
-    let _lock = ACCOUNT_INDEX_LOCK
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let _lock = lock_account_index("upsert_account")?;
     let mut index = load_account_index()?;
 
     let existing_account_id = find_matching_account_id(&index, &email, &token)?;
@@ -419,12 +542,16 @@ pub fn upsert_account(
                     account.disabled_at = None;
                 }
                 account.update_last_used();
-                save_account(&account)?;
-
-                if let Some(idx_summary) = index.accounts.iter_mut().find(|s| s.id == account_id) {
-                    idx_summary.name = name;
-                    save_account_index(&index)?;
-                }
+                account_store::write_batch(|| {
+                    save_account(&account)?;
+                    if let Some(idx_summary) =
+                        index.accounts.iter_mut().find(|s| s.id == account_id)
+                    {
+                        idx_summary.name = name;
+                        save_account_index(&index)?;
+                    }
+                    Ok(())
+                })?;
 
                 return Ok(account);
             }
@@ -434,12 +561,16 @@ pub fn upsert_account(
                 account.name = name.clone();
                 let fingerprint = crate::modules::fingerprint::generate_fingerprint(email.clone())?;
                 account.fingerprint_id = Some(fingerprint.id.clone());
-                save_account(&account)?;
-
-                if let Some(idx_summary) = index.accounts.iter_mut().find(|s| s.id == account_id) {
-                    idx_summary.name = name;
-                    save_account_index(&index)?;
-                }
+                account_store::write_batch(|| {
+                    save_account(&account)?;
+                    if let Some(idx_summary) =
+                        index.accounts.iter_mut().find(|s| s.id == account_id)
+                    {
+                        idx_summary.name = name;
+                        save_account_index(&index)?;
+                    }
+                    Ok(())
+                })?;
 
                 return Ok(account);
             }
@@ -452,9 +583,7 @@ pub fn upsert_account(
 
 /// 删除账号
 pub fn delete_account(account_id: &str) -> Result<(), String> {
-    let _lock = ACCOUNT_INDEX_LOCK
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let _lock = lock_account_index("delete_account")?;
     let mut index = load_account_index()?;
 
     if let Ok(account) = load_account(account_id) {
@@ -477,61 +606,49 @@ pub fn delete_account(account_id: &str) -> Result<(), String> {
         index.current_account_id = index.accounts.first().map(|s| s.id.clone());
     }
 
-    save_account_index(&index)?;
-
-    let accounts_dir = get_accounts_dir()?;
-    let account_path = accounts_dir.join(format!("{}.json", account_id));
-
-    if account_path.exists() {
-        fs::remove_file(&account_path).map_err(|e| format!("删除账号文件失败: {}", e))?;
-    }
-
-    Ok(())
+    account_store::write_batch(|| {
+        save_account_index(&index)?;
+        delete_account_file(account_id)
+    })
 }
 
 /// 批量删除账号
 pub fn delete_accounts(account_ids: &[String]) -> Result<(), String> {
-    let _lock = ACCOUNT_INDEX_LOCK
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    modules::backup::snapshot_before("delete_accounts");
+    let _lock = lock_account_index("delete_accounts")?;
     let mut index = load_account_index()?;
 
-    let accounts_dir = get_accounts_dir()?;
-
-    for account_id in account_ids {
-        if let Ok(account) = load_account(account_id) {
-            if let Err(e) = remember_deleted_account_fingerprint(&account) {
-                modules::logger::log_warn(&format!(
-                    "批量删除时记录账号指纹映射失败: account_id={}, email={}, error={}",
-                    account_id, account.email, e
-                ));
+    account_store::write_batch(|| {
+        for account_id in account_ids {
+            if let Ok(account) = load_account(account_id) {
+                if let Err(e) = remember_deleted_account_fingerprint(&account) {
+                    modules::logger::log_warn(&format!(
+                        "批量删除时记录账号指纹映射失败: account_id={}, email={}, error={}",
+                        account_id, account.email, e
+                    ));
+                }
             }
-        }
 
-        index.accounts.retain(|s| &s.id != account_id);
+            index.accounts.retain(|s| &s.id != account_id);
 
-        if index.current_account_id.as_deref() == Some(account_id) {
-            index.current_account_id = None;
-        }
+            if index.current_account_id.as_deref() == Some(account_id) {
+                index.current_account_id = None;
+            }
 
-        let account_path = accounts_dir.join(format!("{}.json", account_id));
-        if account_path.exists() {
-            let _ = fs::remove_file(&account_path);
+            let _ = delete_account_file(account_id);
         }
-    }
 
-    if index.current_account_id.is_none() {
-        index.current_account_id = index.accounts.first().map(|s| s.id.clone());
-    }
+        if index.current_account_id.is_none() {
+            index.current_account_id = index.accounts.first().map(|s| s.id.clone());
+        }
 
-    save_account_index(&index)
+        save_account_index(&index)
+    })
 }
 
 /// 重新排序账号列表
 pub fn reorder_accounts(account_ids: &[String]) -> Result<(), String> {
-    let _lock = ACCOUNT_INDEX_LOCK
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let _lock = lock_account_index("reorder_accounts")?;
     let mut index = load_account_index()?;
 
     let id_to_summary: std::collections::HashMap<_, _> = index
@@ -568,7 +685,7 @@ pub fn get_current_account_id() -> Result<Option<String>, String> {
 pub fn get_current_account() -> Result<Option<Account>, String> {
     if let Some(id) = get_current_account_id()? {
         let mut account = load_account(&id)?;
-        let _ = modules::quota_cache::apply_cached_quota(&mut account, "authorized");
+        let _ = modules::quota::apply_cached_quota(&mut account);
         Ok(Some(account))
     } else {
         Ok(None)
@@ -577,9 +694,7 @@ pub fn get_current_account() -> Result<Option<Account>, String> {
 
 /// 设置当前激活账号 ID
 pub fn set_current_account_id(account_id: &str) -> Result<(), String> {
-    let _lock = ACCOUNT_INDEX_LOCK
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let _lock = lock_account_index("set_current_account_id")?;
     let mut index = load_account_index()?;
     index.current_account_id = Some(account_id.to_string());
     save_account_index(&index)?;
@@ -641,9 +756,6 @@ pub fn update_account_quota(account_id: &str, quota: QuotaData) -> Result<(), St
 
     account.update_quota(quota);
     save_account(&account)?;
-    if let Some(ref quota) = account.quota {
-        let _ = modules::quota_cache::write_quota_cache("authorized", &account.email, quota);
-    }
     Ok(())
 }
 
@@ -750,7 +862,7 @@ pub fn delete_device_version(_account_id: &str, version_id: &str) -> Result<(),
     crate::modules::fingerprint::delete_fingerprint(version_id)
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RefreshStats {
     pub total: usize,
     pub success: usize,
@@ -758,7 +870,7 @@ pub struct RefreshStats {
     pub details: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuotaAlertPayload {
     pub platform: String,
     pub current_account_id: String,
@@ -769,16 +881,23 @@ pub struct QuotaAlertPayload {
     pub recommended_account_id: Option<String>,
     pub recommended_email: Option<String>,
     pub triggered_at: i64,
+    pub rule_id: String,
+    pub rule_name: String,
+    /// 命中条件的类型（如 remaining_below），通知去重按账号邮箱与此区分
+    #[serde(default)]
+    pub condition_kind: String,
+    pub severity: AlertSeverity,
+    /// 规则命中的描述，用于通知正文
+    pub message: String,
+    /// 汇总通知包含的原始预警，非汇总时为空
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub digest: Vec<QuotaAlertPayload>,
 }
 
 fn normalize_auto_switch_threshold(raw: i32) -> i32 {
     raw.clamp(0, 100)
 }
 
-fn normalize_quota_alert_threshold(raw: i32) -> i32 {
-    raw.clamp(0, 100)
-}
-
 fn should_trigger_auto_switch(account: &Account, threshold: i32) -> bool {
     if account.disabled {
         return true;
@@ -811,22 +930,6 @@ fn can_be_auto_switch_candidate(account: &Account, current_id: &str, threshold:
     quota.models.iter().all(|m| m.percentage >= threshold)
 }
 
-fn can_be_quota_alert_candidate(account: &Account, current_id: &str) -> bool {
-    if account.id == current_id || account.disabled {
-        return false;
-    }
-
-    let Some(quota) = account.quota.as_ref() else {
-        return false;
-    };
-
-    if quota.is_forbidden || quota.models.is_empty() {
-        return false;
-    }
-
-    true
-}
-
 fn average_quota_percentage(account: &Account) -> f64 {
     let Some(quota) = account.quota.as_ref() else {
         return 0.0;
@@ -838,77 +941,12 @@ fn average_quota_percentage(account: &Account) -> f64 {
     sum as f64 / quota.models.len() as f64
 }
 
-fn build_quota_alert_cooldown_key(account_id: &str, threshold: i32) -> String {
-    format!("{}:{}", account_id, threshold)
-}
-
-fn should_emit_quota_alert(cooldown_key: &str, now: i64) -> bool {
-    let Ok(mut state) = QUOTA_ALERT_LAST_SENT.lock() else {
-        return true;
-    };
-
-    if let Some(last_sent) = state.get(cooldown_key) {
-        if now - *last_sent < QUOTA_ALERT_COOLDOWN_SECONDS {
-            return false;
-        }
-    }
-
-    state.insert(cooldown_key.to_string(), now);
-    true
-}
-
-fn clear_quota_alert_cooldown(account_id: &str, threshold: i32) {
-    if let Ok(mut state) = QUOTA_ALERT_LAST_SENT.lock() {
-        state.remove(&build_quota_alert_cooldown_key(account_id, threshold));
-    }
-}
-
-fn pick_quota_alert_recommendation(accounts: &[Account], current_id: &str) -> Option<Account> {
-    let mut candidates: Vec<Account> = accounts
-        .iter()
-        .filter(|a| can_be_quota_alert_candidate(a, current_id))
-        .cloned()
-        .collect();
-
-    if candidates.is_empty() {
-        return None;
-    }
-
-    candidates.sort_by(|a, b| {
-        let avg_a = average_quota_percentage(a);
-        let avg_b = average_quota_percentage(b);
-        avg_b
-            .partial_cmp(&avg_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then_with(|| a.last_used.cmp(&b.last_used))
-    });
-
-    candidates.into_iter().next()
-}
-
-fn build_quota_alert_notification_text(payload: &QuotaAlertPayload) -> (String, String) {
-    let title = format!(
-        "{} 配额预警",
-        match payload.platform.as_str() {
-            "codex" => "Codex",
-            "github_copilot" => "GitHub Copilot",
-            "windsurf" => "Windsurf",
-            _ => "Antigravity",
-        }
-    );
-    let model_text = if payload.low_models.is_empty() {
-        "未知模型".to_string()
-    } else {
-        payload.low_models.join(", ")
-    };
-    let mut body = format!(
-        "{} 低于 {}%（最低 {}%，模型：{}）",
-        payload.current_email, payload.threshold, payload.lowest_percentage, model_text
-    );
+pub fn build_quota_alert_notification_text(payload: &QuotaAlertPayload) -> (String, String) {
+    let mut body = payload.message.clone();
     if let Some(email) = payload.recommended_email.as_ref() {
         body.push_str(&format!("，建议切换到 {}", email));
     }
-    (title, body)
+    (payload.rule_name.clone(), body)
 }
 
 fn focus_main_window_and_emit_quota_alert(
@@ -994,73 +1032,12 @@ pub fn dispatch_quota_alert(payload: &QuotaAlertPayload) {
         emit_quota_alert(app_handle, payload);
     }
     send_quota_alert_native_notification(payload);
+    modules::alert_webhook::notify(payload);
+    modules::websocket::broadcast_quota_alert(payload);
 }
 
-pub fn run_quota_alert_if_needed() -> Result<Option<QuotaAlertPayload>, String> {
-    let cfg = crate::modules::config::get_user_config();
-    if !cfg.quota_alert_enabled {
-        return Ok(None);
-    }
-
-    let threshold = normalize_quota_alert_threshold(cfg.quota_alert_threshold);
-    let current_id = match get_current_account_id()? {
-        Some(id) => id,
-        None => return Ok(None),
-    };
-
-    let accounts = list_accounts()?;
-    let current = match accounts.iter().find(|a| a.id == current_id) {
-        Some(acc) => acc,
-        None => return Ok(None),
-    };
-
-    if current.disabled {
-        clear_quota_alert_cooldown(&current_id, threshold);
-        return Ok(None);
-    }
-
-    let Some(quota) = current.quota.as_ref() else {
-        clear_quota_alert_cooldown(&current_id, threshold);
-        return Ok(None);
-    };
-
-    let low_models: Vec<(String, i32)> = if quota.is_forbidden {
-        vec![("all".to_string(), 0)]
-    } else {
-        quota
-            .models
-            .iter()
-            .filter(|model| model.percentage <= threshold)
-            .map(|model| (model.name.clone(), model.percentage))
-            .collect()
-    };
-
-    if low_models.is_empty() {
-        clear_quota_alert_cooldown(&current_id, threshold);
-        return Ok(None);
-    }
-
-    let now = chrono::Utc::now().timestamp();
-    let cooldown_key = build_quota_alert_cooldown_key(&current_id, threshold);
-    if !should_emit_quota_alert(&cooldown_key, now) {
-        return Ok(None);
-    }
-
-    let recommendation = pick_quota_alert_recommendation(&accounts, &current_id);
-    let lowest_percentage = low_models.iter().map(|(_, pct)| *pct).min().unwrap_or(0);
-    let payload = QuotaAlertPayload {
-        platform: "antigravity".to_string(),
-        current_account_id: current_id.clone(),
-        current_email: current.email.clone(),
-        threshold,
-        lowest_percentage,
-        low_models: low_models.into_iter().map(|(name, _)| name).collect(),
-        recommended_account_id: recommendation.as_ref().map(|acc| acc.id.clone()),
-        recommended_email: recommendation.as_ref().map(|acc| acc.email.clone()),
-        triggered_at: now,
-    };
-    dispatch_quota_alert(&payload);
-    Ok(Some(payload))
+pub fn run_quota_alert_if_needed() -> Result<Vec<QuotaAlertPayload>, String> {
+    modules::alert_rules::run(&AntigravityPlatform)
 }
 
 async fn run_auto_switch_if_needed_inner() -> Result<Option<Account>, String> {
@@ -1130,24 +1107,41 @@ pub async fn run_auto_switch_if_needed() -> Result<Option<Account>, String> {
     result
 }
 
-/// 批量刷新所有账号配额
-pub async fn refresh_all_quotas_logic() -> Result<RefreshStats, String> {
-    use futures::future::join_all;
-    use std::sync::Arc;
-    use tokio::sync::Semaphore;
+/// 导出账号（邮箱 + refresh_token），`account_ids` 为空时导出全部
+pub fn export_accounts(account_ids: &[String]) -> Result<String, String> {
+    modules::credential_vault::ensure_unlocked()?;
+    let accounts_to_export = if account_ids.is_empty() {
+        list_accounts()?
+    } else {
+        account_ids
+            .iter()
+            .filter_map(|id| load_account(id).ok())
+            .collect()
+    };
 
-    const MAX_CONCURRENT: usize = 5;
-    let start = std::time::Instant::now();
+    #[derive(Serialize)]
+    struct SimpleAccount {
+        email: String,
+        refresh_token: String,
+    }
 
-    modules::logger::log_info(&format!(
-        "开始批量刷新所有账号配额 (并发模式, 最大并发: {})",
-        MAX_CONCURRENT
-    ));
-    let accounts = list_accounts()?;
+    let simplified: Vec<SimpleAccount> = accounts_to_export
+        .into_iter()
+        .map(|account| SimpleAccount {
+            email: account.email,
+            refresh_token: account.token.refresh_token,
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&simplified).map_err(|e| format!("序列化失败: {}", e))
+}
 
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+/// 批量刷新时按此主机限速
+const REFRESH_HOST: &str = "cloudcode-pa.googleapis.com";
 
-    let tasks: Vec<_> = accounts
+/// 批量刷新所有账号配额（跳过已禁用和无权限的账号）
+pub async fn refresh_all_quotas_logic() -> Result<RefreshReport, String> {
+    let account_ids: Vec<String> = list_accounts()?
         .into_iter()
         .filter(|account| {
             if account.disabled {
@@ -1162,61 +1156,30 @@ pub async fn refresh_all_quotas_logic() -> Result<RefreshStats, String> {
             }
             true
         })
-        .map(|mut account| {
-            let email = account.email.clone();
-            let account_id = account.id.clone();
-            let permit = semaphore.clone();
-            async move {
-                let _guard = permit.acquire().await.unwrap();
-                match fetch_quota_with_retry(&mut account, false).await {
-                    Ok(quota) => {
-                        if let Err(e) = update_account_quota(&account_id, quota) {
-                            let msg = format!("Account {}: Save quota failed - {}", email, e);
-                            Err(msg)
-                        } else {
-                            Ok(())
-                        }
-                    }
-                    Err(e) => {
-                        let msg = format!("Account {}: Fetch quota failed - {}", email, e);
-                        Err(msg)
-                    }
-                }
-            }
-        })
+        .map(|account| account.id)
         .collect();
 
-    let total = tasks.len();
-    let results = join_all(tasks).await;
-
-    let mut success = 0;
-    let mut failed = 0;
-    let mut details = Vec::new();
-
-    for result in results {
-        match result {
-            Ok(()) => success += 1,
-            Err(msg) => {
-                failed += 1;
-                details.push(msg);
-            }
-        }
-    }
-
-    let elapsed = start.elapsed();
-    modules::logger::log_info(&format!(
-        "批量刷新完成: {} 成功, {} 失败, 耗时: {}ms",
-        success,
-        failed,
-        elapsed.as_millis()
-    ));
-
-    Ok(RefreshStats {
-        total,
-        success,
-        failed,
-        details,
-    })
+    let run = refresh_executor::run(
+        AntigravityPlatform.id(),
+        REFRESH_HOST,
+        account_ids,
+        |account_id| async move {
+            let mut account = load_account(&account_id)?;
+            let quota = fetch_quota_with_retry(&mut account, false)
+                .await
+                .map_err(|e| format!("Fetch quota failed - {}", e))?;
+            update_account_quota(&account_id, quota)
+                .map_err(|e| format!("Save quota failed - {}", e))
+        },
+    )
+    .await;
+
+    modules::quota_history::record_refreshed_logged(
+        &AntigravityPlatform,
+        &modules::quota_history::succeeded_ids(&run.results),
+    );
+    modules::quota_events::publish_logged(&AntigravityPlatform);
+    Ok(run.report)
 }
 
 /// 带重试的配额查询
@@ -1228,6 +1191,17 @@ pub async fn fetch_quota_with_retry(
     use crate::error::AppError;
     use crate::modules::oauth;
 
+    modules::credential_vault::ensure_unlocked().map_err(AppError::Account)?;
+
+    // 离线模式：不刷新令牌也不请求接口，沿用缓存中的配额
+    if modules::quota_cache::is_offline() {
+        let _ = modules::quota::apply_cached_quota(account);
+        return account
+            .quota
+            .clone()
+            .ok_or_else(|| AppError::Unknown("离线模式下没有该账号的配额缓存".to_string()));
+    }
+
     let token = match oauth::ensure_fresh_token(&account.token).await {
         Ok(t) => t,
         Err(e) => {
@@ -1340,6 +1314,7 @@ pub async fn switch_account_internal(account_id: &str) -> Result<Account, String
 
 /// 准备账号注入：确保 Token 新鲜并落盘
 pub async fn prepare_account_for_injection(account_id: &str) -> Result<Account, String> {
+    modules::credential_vault::ensure_unlocked()?;
     let mut account = load_account(account_id)?;
     let fresh_token = modules::oauth::ensure_fresh_token(&account.token)
         .await
@@ -1351,3 +1326,132 @@ pub async fn prepare_account_for_injection(account_id: &str) -> Result<Account,
     }
     Ok(account)
 }
+
+/// 统一配额视图：每个模型一项，指标 ID 为模型名；无权限账号以单项 0% 表示
+pub fn unified_quota(account: &Account) -> UnifiedQuota {
+    let metrics = match account.quota.as_ref() {
+        _ if account.disabled => Vec::new(),
+        None => Vec::new(),
+        Some(quota) if quota.is_forbidden => vec![QuotaMetric::from_percent("all", "all", 0)],
+        Some(quota) => quota
+            .models
+            .iter()
+            .map(|model| {
+                let label = model
+                    .display_name
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|text| !text.is_empty())
+                    .unwrap_or(&model.name);
+                QuotaMetric::from_percent(&model.name, label, model.percentage).with_resets_at(
+                    chrono::DateTime::parse_from_rfc3339(&model.reset_time)
+                        .ok()
+                        .map(|value| value.timestamp()),
+                )
+            })
+            .collect(),
+    };
+    UnifiedQuota::new(metrics)
+}
+
+fn to_platform_account(account: &Account) -> PlatformAccount {
+    let forbidden = account.quota.as_ref().is_some_and(|quota| quota.is_forbidden);
+
+    PlatformAccount {
+        platform: AntigravityPlatform.id().to_string(),
+        id: account.id.clone(),
+        email: account.email.clone(),
+        tags: account.tags.clone(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+        disabled: account.disabled || forbidden,
+        forbidden,
+        quota_error: account.quota_error.as_ref().map(|err| err.message.clone()),
+        token_expires_at: Some(account.token.expiry_timestamp),
+        quota_stale: false,
+        quota: unified_quota(account),
+    }
+}
+
+pub struct AntigravityPlatform;
+
+impl Platform for AntigravityPlatform {
+    fn id(&self) -> &'static str {
+        "antigravity"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Antigravity"
+    }
+
+    fn list_accounts(&self) -> Result<Vec<PlatformAccount>, String> {
+        Ok(list_accounts()?.iter().map(to_platform_account).collect())
+    }
+
+    fn current_account_id(&self, _accounts: &[PlatformAccount]) -> Option<String> {
+        get_current_account_id().ok().flatten()
+    }
+
+    fn remove_accounts(&self, account_ids: &[String]) -> Result<(), String> {
+        delete_accounts(account_ids)
+    }
+
+    fn update_account_tags(&self, account_id: &str, tags: Vec<String>) -> Result<(), String> {
+        update_account_tags(account_id, tags).map(|_| ())
+    }
+
+    fn export_accounts(&self, account_ids: &[String]) -> Result<String, String> {
+        export_accounts(account_ids)
+    }
+
+    fn import_from_json(&self, json_content: String) -> BoxFuture<'static, Result<usize, String>> {
+        Box::pin(async move {
+            modules::import::import_from_json_logic(json_content)
+                .await
+                .map(|accounts| accounts.len())
+        })
+    }
+
+    fn switch_account(&self, account_id: String) -> BoxFuture<'static, Result<String, String>> {
+        Box::pin(async move {
+            let result = switch_account_internal(&account_id).await;
+            let error = result.as_ref().err().map(String::as_str);
+            platform::notify_account_switched("antigravity", error);
+            let account = result?;
+            modules::websocket::broadcast_account_switched(&account.id, &account.email);
+            Ok(format!("切换完成: {}", account.email))
+        })
+    }
+
+    fn refresh_account(&self, account_id: String) -> BoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            let mut account = load_account(&account_id)?;
+            let quota = fetch_quota_with_retry(&mut account, true)
+                .await
+                .map_err(|e| e.to_string())?;
+            update_account_quota(&account_id, quota)
+        })
+    }
+
+    fn refresh_all(&self) -> BoxFuture<'static, Result<RefreshReport, String>> {
+        Box::pin(async move {
+            let report = refresh_all_quotas_logic().await?;
+            if let Err(e) = run_auto_switch_if_needed().await {
+                modules::logger::log_warn(&format!("[AutoSwitch] 自动切号执行失败: {}", e));
+            }
+            Ok(report)
+        })
+    }
+
+    fn refresh_interval_minutes(&self) -> i32 {
+        crate::modules::config::get_user_config().auto_refresh_minutes
+    }
+
+    fn quota_alert_settings(&self) -> platform::QuotaAlertSettings {
+        let cfg = crate::modules::config::get_user_config();
+        platform::QuotaAlertSettings {
+            enabled: cfg.quota_alert_enabled,
+            threshold: cfg.quota_alert_threshold,
+        }
+    }
+}
@@ -0,0 +1,1120 @@
+//! 统一账号存储（cockpit.db）
+//!
+//! 各平台原先以「索引文件 + 每账号一个 JSON 文件」的方式存储，写入中断时索引与账号文件容易不一致。
+//! 此模块将所有平台的账号统一存入一个 SQLite 数据库，所有写入都在事务内完成：
+//!
+//! - `accounts`: 账号主体（去除 token、配额、标签后的 JSON）
+//! - `tokens`: 敏感字段（启用凭据保险库时保持加密）
+//! - `quotas`: 配额相关字段
+//! - `tags`: 账号标签
+//! - `account_index`: 各平台的账号列表摘要与排序（对应原索引文件中的 `accounts`）
+//! - `store_meta`: 索引的其余字段（如 `current_account_id`）与存储状态
+//!
+//! 数据库存储需用户通过 `import_from_json` 主动启用，未启用时各平台继续使用与外部程序共享的 JSON 布局；
+//! `export_to_json` 可将数据写回原布局并切回文件存储。导入提交后删除已导入的账号文件及其 `.bak`
+//! （其中可能是明文凭据），数据库开启 `secure_delete`，被覆盖的旧记录不会残留在空闲页中。
+//! 账号与索引的成对写入通过 `write_batch` 放在同一个事务中；
+//! 外部程序也会读取的索引文件（Antigravity 的 `accounts.json`，VS Code 扩展使用）在提交后继续同步写出。
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::modules::{
+    account, codex_account, credential_vault, cursor_account, github_copilot_account, kiro_account,
+    logger, windsurf_account,
+};
+use crate::utils::atomic_file;
+
+const DB_FILE: &str = "cockpit.db";
+const SCHEMA_VERSION: i64 = 1;
+
+const META_SCHEMA_VERSION: &str = "schema_version";
+const META_IMPORTED_AT: &str = "imported_at";
+const META_ROLLED_BACK_AT: &str = "rolled_back_at";
+const META_ACTIVE: &str = "active";
+
+const STATE_UNKNOWN: u8 = 0;
+const STATE_ACTIVE: u8 = 1;
+const STATE_INACTIVE: u8 = 2;
+
+static STORE_STATE: AtomicU8 = AtomicU8::new(STATE_UNKNOWN);
+static SCHEMA_READY: AtomicBool = AtomicBool::new(false);
+
+/// 平台账号存储描述：原 JSON 布局位置，以及账号 JSON 中各类字段的拆分规则
+pub struct StoreSpec {
+    pub platform: &'static str,
+    /// 索引文件与账号目录所在的目录
+    pub base_dir: fn() -> Result<PathBuf, String>,
+    pub index_file: &'static str,
+    pub accounts_dir: &'static str,
+    /// 敏感字段（JSON 指针），存入 `tokens` 表
+    pub secret_fields: &'static [&'static str],
+    /// 配额相关的顶层字段，存入 `quotas` 表
+    pub quota_fields: &'static [&'static str],
+    /// 索引文件同时被外部程序读取，启用数据库存储后仍需同步写出
+    pub mirror_index: bool,
+}
+
+/// `write_batch` 期间本线程共用的连接（已开启事务）与提交后要写出的索引文件
+struct Batch {
+    conn: Connection,
+    mirrors: Vec<(PathBuf, Value)>,
+}
+
+thread_local! {
+    static BATCH: RefCell<Option<Batch>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformStoreReport {
+    pub platform: String,
+    pub accounts: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountStoreStatus {
+    pub active: bool,
+    pub db_path: String,
+    pub schema_version: Option<i64>,
+    pub imported_at: Option<i64>,
+    pub rolled_back_at: Option<i64>,
+    pub platforms: Vec<PlatformStoreReport>,
+}
+
+fn all_specs() -> [&'static StoreSpec; 6] {
+    [
+        &account::STORE_SPEC,
+        &codex_account::STORE_SPEC,
+        &github_copilot_account::STORE_SPEC,
+        &windsurf_account::STORE_SPEC,
+        &kiro_account::STORE_SPEC,
+        &cursor_account::STORE_SPEC,
+    ]
+}
+
+fn now_ts() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+pub fn get_db_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(DB_FILE))
+}
+
+fn db_err(context: &str, e: rusqlite::Error) -> String {
+    format!("{}: {}", context, e)
+}
+
+fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS store_meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS accounts (
+            platform TEXT NOT NULL,
+            id TEXT NOT NULL,
+            data TEXT NOT NULL,
+            created_at INTEGER,
+            last_used INTEGER,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (platform, id)
+        );
+        CREATE TABLE IF NOT EXISTS tokens (
+            platform TEXT NOT NULL,
+            account_id TEXT NOT NULL,
+            data TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (platform, account_id)
+        );
+        CREATE TABLE IF NOT EXISTS quotas (
+            platform TEXT NOT NULL,
+            account_id TEXT NOT NULL,
+            data TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (platform, account_id)
+        );
+        CREATE TABLE IF NOT EXISTS tags (
+            platform TEXT NOT NULL,
+            account_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (platform, account_id, position)
+        );
+        CREATE TABLE IF NOT EXISTS account_index (
+            platform TEXT NOT NULL,
+            account_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            summary TEXT NOT NULL,
+            PRIMARY KEY (platform, account_id)
+        );
+        ",
+    )
+    .map_err(|e| db_err("初始化账号数据库失败", e))?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO store_meta (key, value) VALUES (?1, ?2)",
+        params![META_SCHEMA_VERSION, SCHEMA_VERSION.to_string()],
+    )
+    .map_err(|e| db_err("写入数据库版本失败", e))?;
+    Ok(())
+}
+
+fn open_connection() -> Result<Connection, String> {
+    let path = get_db_path()?;
+    let conn = Connection::open(&path).map_err(|e| db_err("打开账号数据库失败", e))?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))
+        .map_err(|e| db_err("设置数据库超时失败", e))?;
+    conn.pragma_update(None, "journal_mode", "WAL")
+        .map_err(|e| db_err("设置数据库日志模式失败", e))?;
+    // 重新加密、删除账号时用零覆盖旧数据，避免明文凭据残留在数据库文件中
+    conn.pragma_update(None, "secure_delete", "ON")
+        .map_err(|e| db_err("设置数据库安全删除失败", e))?;
+    if !SCHEMA_READY.load(Ordering::Acquire) {
+        ensure_schema(&conn)?;
+        SCHEMA_READY.store(true, Ordering::Release);
+    }
+    Ok(conn)
+}
+
+fn read_meta(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT value FROM store_meta WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| db_err("读取数据库元数据失败", e))
+}
+
+fn write_meta(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO store_meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+    .map_err(|e| db_err("写入数据库元数据失败", e))?;
+    Ok(())
+}
+
+fn index_meta_key(platform: &str) -> String {
+    format!("index:{}", platform)
+}
+
+/// 统一账号存储是否已接管读写
+pub fn is_active() -> bool {
+    match STORE_STATE.load(Ordering::Acquire) {
+        STATE_ACTIVE => true,
+        STATE_INACTIVE => false,
+        _ => {
+            let active = load_active_flag().unwrap_or_else(|e| {
+                logger::log_error(&format!("[AccountStore] 读取存储状态失败: {}", e));
+                false
+            });
+            set_active_state(active);
+            active
+        }
+    }
+}
+
+fn load_active_flag() -> Result<bool, String> {
+    if !get_db_path()?.exists() {
+        return Ok(false);
+    }
+    let conn = open_connection()?;
+    Ok(read_meta(&conn, META_ACTIVE)?.as_deref() == Some("1"))
+}
+
+fn set_active_state(active: bool) {
+    STORE_STATE.store(
+        if active { STATE_ACTIVE } else { STATE_INACTIVE },
+        Ordering::Release,
+    );
+}
+
+/// 数据库文件被外部替换（如恢复快照）后调用，下次访问时重新读取状态与表结构
+pub fn reset_cached_state() {
+    STORE_STATE.store(STATE_UNKNOWN, Ordering::Release);
+    SCHEMA_READY.store(false, Ordering::Release);
+}
+
+/// 使用 `VACUUM INTO` 导出数据库的一致副本（包含 WAL 中尚未合并的内容）
+///
+/// 数据库不存在时返回 `Ok(false)`；`dest` 必须是不存在的路径。
+pub fn backup_to(dest: &Path) -> Result<bool, String> {
+    if !get_db_path()?.exists() {
+        return Ok(false);
+    }
+    let conn = open_connection()?;
+    conn.execute("VACUUM INTO ?1", params![dest.to_string_lossy()])
+        .map_err(|e| db_err("导出数据库副本失败", e))?;
+    Ok(true)
+}
+
+// ============================================================================
+// 账号 JSON 拆分 / 组装
+// ============================================================================
+
+struct RecordParts {
+    data: Value,
+    tokens: Value,
+    quota: Value,
+    tags: Option<Vec<String>>,
+}
+
+fn take_pointer(root: &mut Value, pointer: &str) -> Option<Value> {
+    let (parent, key) = pointer.rsplit_once('/')?;
+    let parent = if parent.is_empty() {
+        Some(root)
+    } else {
+        root.pointer_mut(parent)
+    }?;
+    parent.as_object_mut()?.remove(key)
+}
+
+fn put_pointer(root: &mut Value, pointer: &str, value: Value) {
+    let mut current = root;
+    let segments: Vec<&str> = pointer.split('/').skip(1).collect();
+    let Some((last, parents)) = segments.split_last() else {
+        return;
+    };
+    for segment in parents {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .expect("checked object")
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if !current.is_object() {
+        *current = Value::Object(Map::new());
+    }
+    if let Some(map) = current.as_object_mut() {
+        map.insert(last.to_string(), value);
+    }
+}
+
+fn split_record(spec: &StoreSpec, mut value: Value) -> Result<RecordParts, String> {
+    if !value.is_object() {
+        return Err("账号数据不是 JSON 对象".to_string());
+    }
+
+    let mut tokens = Value::Object(Map::new());
+    for pointer in spec.secret_fields {
+        if let Some(secret) = take_pointer(&mut value, pointer) {
+            put_pointer(&mut tokens, pointer, secret);
+        }
+    }
+
+    let mut quota = Map::new();
+    for field in spec.quota_fields {
+        if let Some(item) = value.as_object_mut().and_then(|map| map.remove(*field)) {
+            quota.insert(field.to_string(), item);
+        }
+    }
+
+    let tags = match value.as_object_mut().and_then(|map| map.remove("tags")) {
+        Some(Value::Array(items)) => Some(
+            items
+                .into_iter()
+                .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                .collect(),
+        ),
+        _ => None,
+    };
+
+    Ok(RecordParts {
+        data: value,
+        tokens,
+        quota: Value::Object(quota),
+        tags,
+    })
+}
+
+fn join_record(spec: &StoreSpec, parts: RecordParts) -> Value {
+    let RecordParts {
+        mut data,
+        tokens,
+        quota,
+        tags,
+    } = parts;
+
+    for pointer in spec.secret_fields {
+        if let Some(secret) = tokens.pointer(pointer) {
+            put_pointer(&mut data, pointer, secret.clone());
+        }
+    }
+    if let (Some(target), Value::Object(quota)) = (data.as_object_mut(), quota) {
+        target.extend(quota);
+    }
+    if let (Some(target), Some(tags)) = (data.as_object_mut(), tags) {
+        target.insert(
+            "tags".to_string(),
+            Value::Array(tags.into_iter().map(Value::String).collect()),
+        );
+    }
+    data
+}
+
+fn i64_field(value: &Value, key: &str) -> Option<i64> {
+    value.get(key).and_then(|v| v.as_i64())
+}
+
+/// 写入一条账号记录（原样保存敏感字段，不做加解密）
+fn write_record_value(
+    tx: &Connection,
+    spec: &StoreSpec,
+    account_id: &str,
+    value: Value,
+) -> Result<(), String> {
+    let parts = split_record(spec, value)?;
+    let now = now_ts();
+    let data = serde_json::to_string(&parts.data).map_err(|e| format!("序列化账号失败: {}", e))?;
+    let tokens =
+        serde_json::to_string(&parts.tokens).map_err(|e| format!("序列化账号失败: {}", e))?;
+    let quota =
+        serde_json::to_string(&parts.quota).map_err(|e| format!("序列化账号失败: {}", e))?;
+
+    tx.execute(
+        "INSERT INTO accounts (platform, id, data, created_at, last_used, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(platform, id) DO UPDATE SET
+            data = excluded.data,
+            created_at = excluded.created_at,
+            last_used = excluded.last_used,
+            updated_at = excluded.updated_at",
+        params![
+            spec.platform,
+            account_id,
+            data,
+            i64_field(&parts.data, "created_at"),
+            i64_field(&parts.data, "last_used"),
+            now
+        ],
+    )
+    .map_err(|e| db_err("写入账号失败", e))?;
+    tx.execute(
+        "INSERT INTO tokens (platform, account_id, data, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(platform, account_id) DO UPDATE SET
+            data = excluded.data, updated_at = excluded.updated_at",
+        params![spec.platform, account_id, tokens, now],
+    )
+    .map_err(|e| db_err("写入账号凭据失败", e))?;
+    tx.execute(
+        "INSERT INTO quotas (platform, account_id, data, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(platform, account_id) DO UPDATE SET
+            data = excluded.data, updated_at = excluded.updated_at",
+        params![spec.platform, account_id, quota, now],
+    )
+    .map_err(|e| db_err("写入账号配额失败", e))?;
+
+    tx.execute(
+        "DELETE FROM tags WHERE platform = ?1 AND account_id = ?2",
+        params![spec.platform, account_id],
+    )
+    .map_err(|e| db_err("写入账号标签失败", e))?;
+    // 用 -1 占位行区分「无 tags 字段」与「空 tags 列表」
+    let tag_rows: Vec<(i64, String)> = match parts.tags {
+        Some(tags) if tags.is_empty() => vec![(-1, String::new())],
+        Some(tags) => tags
+            .into_iter()
+            .enumerate()
+            .map(|(i, tag)| (i as i64, tag))
+            .collect(),
+        None => Vec::new(),
+    };
+    for (position, tag) in tag_rows {
+        tx.execute(
+            "INSERT INTO tags (platform, account_id, position, tag) VALUES (?1, ?2, ?3, ?4)",
+            params![spec.platform, account_id, position, tag],
+        )
+        .map_err(|e| db_err("写入账号标签失败", e))?;
+    }
+    Ok(())
+}
+
+fn parse_json_column(raw: Option<String>) -> Result<Value, String> {
+    match raw {
+        Some(text) => {
+            serde_json::from_str(&text).map_err(|e| format!("解析数据库中的账号数据失败: {}", e))
+        }
+        None => Ok(Value::Object(Map::new())),
+    }
+}
+
+/// 读取一条账号记录（敏感字段保持存储时的原样）
+fn read_record_value(
+    conn: &Connection,
+    spec: &StoreSpec,
+    account_id: &str,
+) -> Result<Option<Value>, String> {
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT data FROM accounts WHERE platform = ?1 AND id = ?2",
+            params![spec.platform, account_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| db_err("读取账号失败", e))?;
+    let Some(data) = data else {
+        return Ok(None);
+    };
+
+    let tokens: Option<String> = conn
+        .query_row(
+            "SELECT data FROM tokens WHERE platform = ?1 AND account_id = ?2",
+            params![spec.platform, account_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| db_err("读取账号凭据失败", e))?;
+    let quota: Option<String> = conn
+        .query_row(
+            "SELECT data FROM quotas WHERE platform = ?1 AND account_id = ?2",
+            params![spec.platform, account_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| db_err("读取账号配额失败", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT position, tag FROM tags WHERE platform = ?1 AND account_id = ?2
+             ORDER BY position",
+        )
+        .map_err(|e| db_err("读取账号标签失败", e))?;
+    let tag_rows = stmt
+        .query_map(params![spec.platform, account_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| db_err("读取账号标签失败", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| db_err("读取账号标签失败", e))?;
+    let tags = if tag_rows.is_empty() {
+        None
+    } else {
+        Some(
+            tag_rows
+                .into_iter()
+                .filter(|(position, _)| *position >= 0)
+                .map(|(_, tag)| tag)
+                .collect(),
+        )
+    };
+
+    Ok(Some(join_record(
+        spec,
+        RecordParts {
+            data: parse_json_column(Some(data))?,
+            tokens: parse_json_column(tokens)?,
+            quota: parse_json_column(quota)?,
+            tags,
+        },
+    )))
+}
+
+fn delete_record_rows(tx: &Connection, platform: &str, account_id: &str) -> Result<(), String> {
+    for table in ["tokens", "quotas", "tags"] {
+        tx.execute(
+            &format!(
+                "DELETE FROM {} WHERE platform = ?1 AND account_id = ?2",
+                table
+            ),
+            params![platform, account_id],
+        )
+        .map_err(|e| db_err("删除账号失败", e))?;
+    }
+    tx.execute(
+        "DELETE FROM accounts WHERE platform = ?1 AND id = ?2",
+        params![platform, account_id],
+    )
+    .map_err(|e| db_err("删除账号失败", e))?;
+    Ok(())
+}
+
+fn write_index_value(tx: &Connection, spec: &StoreSpec, mut index: Value) -> Result<(), String> {
+    let summaries = match index.as_object_mut().and_then(|map| map.remove("accounts")) {
+        Some(Value::Array(items)) => items,
+        _ => Vec::new(),
+    };
+    tx.execute(
+        "DELETE FROM account_index WHERE platform = ?1",
+        params![spec.platform],
+    )
+    .map_err(|e| db_err("写入账号索引失败", e))?;
+    for (position, summary) in summaries.into_iter().enumerate() {
+        let Some(account_id) = summary.get("id").and_then(|v| v.as_str()).map(String::from) else {
+            continue;
+        };
+        let text =
+            serde_json::to_string(&summary).map_err(|e| format!("序列化账号索引失败: {}", e))?;
+        tx.execute(
+            "INSERT OR REPLACE INTO account_index (platform, account_id, position, summary)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![spec.platform, account_id, position as i64, text],
+        )
+        .map_err(|e| db_err("写入账号索引失败", e))?;
+    }
+    let extras = serde_json::to_string(&index).map_err(|e| format!("序列化账号索引失败: {}", e))?;
+    tx.execute(
+        "INSERT INTO store_meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![index_meta_key(spec.platform), extras],
+    )
+    .map_err(|e| db_err("写入账号索引失败", e))?;
+    Ok(())
+}
+
+fn read_index_value(conn: &Connection, spec: &StoreSpec) -> Result<Option<Value>, String> {
+    let Some(extras) = read_meta(conn, &index_meta_key(spec.platform))? else {
+        return Ok(None);
+    };
+    let mut index: Value =
+        serde_json::from_str(&extras).map_err(|e| format!("解析账号索引失败: {}", e))?;
+
+    let mut stmt = conn
+        .prepare("SELECT summary FROM account_index WHERE platform = ?1 ORDER BY position")
+        .map_err(|e| db_err("读取账号索引失败", e))?;
+    let summaries = stmt
+        .query_map(params![spec.platform], |row| row.get::<_, String>(0))
+        .map_err(|e| db_err("读取账号索引失败", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| db_err("读取账号索引失败", e))?
+        .into_iter()
+        .filter_map(|text| serde_json::from_str::<Value>(&text).ok())
+        .collect();
+
+    if let Some(map) = index.as_object_mut() {
+        map.insert("accounts".to_string(), Value::Array(summaries));
+    }
+    Ok(Some(index))
+}
+
+// ============================================================================
+// 供各平台账号模块调用的读写接口
+// ============================================================================
+
+/// 在 `write_batch` 的事务内读取，保证能读到本批次尚未提交的写入
+fn with_read_conn<T>(f: impl FnOnce(&Connection) -> Result<T, String>) -> Result<T, String> {
+    BATCH.with(|cell| match cell.borrow().as_ref() {
+        Some(batch) => f(&batch.conn),
+        None => f(&open_connection()?),
+    })
+}
+
+/// 在 `write_batch` 的事务内写入；不在批次中时单独开启并提交一个事务
+fn with_write_tx(f: impl FnOnce(&Connection) -> Result<(), String>) -> Result<(), String> {
+    BATCH.with(|cell| match cell.borrow().as_ref() {
+        Some(batch) => f(&batch.conn),
+        None => {
+            let mut conn = open_connection()?;
+            let tx = conn
+                .transaction()
+                .map_err(|e| db_err("开启数据库事务失败", e))?;
+            f(&tx)?;
+            tx.commit().map_err(|e| db_err("提交数据库事务失败", e))
+        }
+    })
+}
+
+/// 清理本线程的批次状态；`f` panic 时连接随之关闭，未提交的事务由 SQLite 回滚
+struct BatchGuard;
+
+impl Drop for BatchGuard {
+    fn drop(&mut self) {
+        BATCH.with(|cell| cell.borrow_mut().take());
+    }
+}
+
+/// 在同一个事务中执行 `f` 内的账号与索引写入：全部成功才提交，任一失败整体回滚
+///
+/// 未启用数据库存储时直接执行 `f`；嵌套调用并入外层批次。
+pub fn write_batch<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    if !is_active() || BATCH.with(|cell| cell.borrow().is_some()) {
+        return f();
+    }
+    let conn = open_connection()?;
+    conn.execute_batch("BEGIN IMMEDIATE")
+        .map_err(|e| db_err("开启数据库事务失败", e))?;
+    BATCH.with(|cell| {
+        *cell.borrow_mut() = Some(Batch {
+            conn,
+            mirrors: Vec::new(),
+        })
+    });
+    let _guard = BatchGuard;
+
+    let result = f();
+    let batch = BATCH
+        .with(|cell| cell.borrow_mut().take())
+        .ok_or("数据库批量写入状态丢失")?;
+    match result {
+        Ok(value) => {
+            batch
+                .conn
+                .execute_batch("COMMIT")
+                .map_err(|e| db_err("提交数据库事务失败", e))?;
+            for (path, index) in &batch.mirrors {
+                write_index_mirror(path, index);
+            }
+            Ok(value)
+        }
+        Err(e) => {
+            let _ = batch.conn.execute_batch("ROLLBACK");
+            Err(e)
+        }
+    }
+}
+
+/// 镜像只供外部程序读取，数据库才是准数据源：写出失败只记录日志
+fn write_index_mirror(path: &Path, index: &Value) {
+    if let Err(e) = write_json_file(path, index) {
+        logger::log_warn(&format!(
+            "[AccountStore] 同步索引文件失败: path={}, error={}",
+            path.display(),
+            e
+        ));
+    }
+}
+
+/// 读取账号（解密敏感字段）
+pub fn load_record<T: DeserializeOwned>(
+    spec: &StoreSpec,
+    account_id: &str,
+) -> Result<Option<T>, String> {
+    let Some(mut value) = with_read_conn(|conn| read_record_value(conn, spec, account_id))? else {
+        return Ok(None);
+    };
+    credential_vault::open_fields(&mut value, spec.secret_fields);
+    serde_json::from_value(value)
+        .map(Some)
+        .map_err(|e| format!("解析账号数据失败: {}", e))
+}
+
+/// 保存账号（按保险库状态加密敏感字段）
+pub fn save_record<T: Serialize>(
+    spec: &StoreSpec,
+    account_id: &str,
+    record: &T,
+) -> Result<(), String> {
+    let mut value = serde_json::to_value(record).map_err(|e| format!("序列化账号失败: {}", e))?;
+    credential_vault::seal_fields(&mut value, spec.secret_fields)?;
+    with_write_tx(|conn| write_record_value(conn, spec, account_id, value))
+}
+
+pub fn delete_record(spec: &StoreSpec, account_id: &str) -> Result<(), String> {
+    with_write_tx(|conn| delete_record_rows(conn, spec.platform, account_id))
+}
+
+/// 列出平台在数据库中的全部账号 ID（不经过索引）
+pub fn list_account_ids(spec: &StoreSpec) -> Result<Vec<String>, String> {
+    with_read_conn(|conn| list_record_ids(conn, spec.platform))
+}
+
+/// 读取平台账号索引；从未保存过索引时返回 None
+pub fn load_index<T: DeserializeOwned>(spec: &StoreSpec) -> Result<Option<T>, String> {
+    let Some(value) = with_read_conn(|conn| read_index_value(conn, spec))? else {
+        return Ok(None);
+    };
+    serde_json::from_value(value)
+        .map(Some)
+        .map_err(|e| format!("解析账号索引失败: {}", e))
+}
+
+pub fn save_index<T: Serialize>(spec: &StoreSpec, index: &T) -> Result<(), String> {
+    let value = serde_json::to_value(index).map_err(|e| format!("序列化账号索引失败: {}", e))?;
+    let mirror = if spec.mirror_index {
+        Some(((spec.base_dir)()?.join(spec.index_file), value.clone()))
+    } else {
+        None
+    };
+    with_write_tx(|conn| write_index_value(conn, spec, value))?;
+
+    if let Some(mirror) = mirror {
+        // 批次中的镜像等事务提交后再写出
+        let pending = BATCH.with(|cell| match cell.borrow_mut().as_mut() {
+            Some(batch) => {
+                batch.mirrors.push(mirror);
+                None
+            }
+            None => Some(mirror),
+        });
+        if let Some((path, index)) = pending {
+            write_index_mirror(&path, &index);
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// 导入 / 回滚导出
+// ============================================================================
+
+fn read_json_file(path: &Path) -> Result<Option<Value>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}", e))?;
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(&content).map(Some).map_err(|e| {
+        crate::error::file_corrupted_error(
+            &path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            &path.to_string_lossy(),
+            &e.to_string(),
+        )
+    })
+}
+
+fn import_platform(tx: &Transaction, spec: &StoreSpec) -> Result<PlatformStoreReport, String> {
+    let base_dir = (spec.base_dir)()?;
+    let accounts_dir = base_dir.join(spec.accounts_dir);
+
+    for table in ["accounts", "tokens", "quotas", "tags", "account_index"] {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE platform = ?1", table),
+            params![spec.platform],
+        )
+        .map_err(|e| db_err("清理旧数据失败", e))?;
+    }
+
+    let mut imported = 0usize;
+    let mut skipped = 0usize;
+    if accounts_dir.exists() {
+        let entries =
+            fs::read_dir(&accounts_dir).map_err(|e| format!("读取账号目录失败: {}", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(account_id) = path.file_stem().and_then(|s| s.to_str()).map(String::from)
+            else {
+                continue;
+            };
+            match read_json_file(&path) {
+                Ok(Some(value)) if value.is_object() => {
+                    write_record_value(tx, spec, &account_id, value)?;
+                    imported += 1;
+                }
+                Ok(_) => skipped += 1,
+                Err(e) => {
+                    logger::log_warn(&format!(
+                        "[AccountStore] 跳过无法解析的账号文件: platform={}, path={}, error={}",
+                        spec.platform,
+                        path.display(),
+                        e
+                    ));
+                    skipped += 1;
+                }
+            }
+        }
+    }
+
+    // 索引文件损坏时中止导入，避免丢失排序与当前账号信息
+    if let Some(index) = read_json_file(&base_dir.join(spec.index_file))? {
+        write_index_value(tx, spec, index)?;
+    }
+
+    Ok(PlatformStoreReport {
+        platform: spec.platform.to_string(),
+        accounts: imported,
+        skipped,
+    })
+}
+
+/// 删除已导入数据库的账号文件及其 `.bak`；未导入（解析失败）的文件保留
+fn remove_imported_files(accounts_dir: &Path, ids: &HashSet<String>) -> usize {
+    let Ok(entries) = fs::read_dir(accounts_dir) else {
+        return 0;
+    };
+    let mut removed = 0usize;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let stem = name
+            .strip_suffix(".json.bak")
+            .or_else(|| name.strip_suffix(".json"));
+        if stem.is_some_and(|id| ids.contains(id)) && fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// 数据库接管后，原布局中的账号文件不再更新，保险库重新加密也不会处理它们，需要删除
+fn purge_imported_files(conn: &Connection) -> Result<(), String> {
+    for spec in all_specs() {
+        let ids: HashSet<String> = list_record_ids(conn, spec.platform)?.into_iter().collect();
+        let accounts_dir = (spec.base_dir)()?.join(spec.accounts_dir);
+        let removed = remove_imported_files(&accounts_dir, &ids);
+        if removed > 0 {
+            logger::log_info(&format!(
+                "[AccountStore] 已删除导入后的账号文件: platform={}, files={}",
+                spec.platform, removed
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 从原 JSON 布局导入全部平台账号，并切换到数据库存储；导入成功后删除原账号文件
+pub fn import_from_json() -> Result<Vec<PlatformStoreReport>, String> {
+    let mut conn = open_connection()?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| db_err("开启数据库事务失败", e))?;
+
+    let mut reports = Vec::new();
+    for spec in all_specs() {
+        reports.push(import_platform(&tx, spec)?);
+    }
+    write_meta(&tx, META_IMPORTED_AT, &now_ts().to_string())?;
+    write_meta(&tx, META_ACTIVE, "1")?;
+    tx.commit().map_err(|e| db_err("提交数据库事务失败", e))?;
+    set_active_state(true);
+    if let Err(e) = purge_imported_files(&conn) {
+        logger::log_warn(&format!("[AccountStore] 删除导入后的账号文件失败: {}", e));
+    }
+
+    for report in &reports {
+        logger::log_info(&format!(
+            "[AccountStore] 已导入 {}: {} 个账号，跳过 {} 个",
+            report.platform, report.accounts, report.skipped
+        ));
+    }
+    Ok(reports)
+}
+
+fn list_record_ids(conn: &Connection, platform: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM accounts WHERE platform = ?1 ORDER BY id")
+        .map_err(|e| db_err("读取账号列表失败", e))?;
+    let ids = stmt
+        .query_map(params![platform], |row| row.get::<_, String>(0))
+        .map_err(|e| db_err("读取账号列表失败", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| db_err("读取账号列表失败", e))?;
+    Ok(ids)
+}
+
+fn write_json_file(path: &Path, value: &Value) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value).map_err(|e| format!("序列化失败: {}", e))?;
+    atomic_file::write(path, content).map_err(|e| format!("写入文件失败: {}", e))
+}
+
+fn export_platform(conn: &Connection, spec: &StoreSpec) -> Result<PlatformStoreReport, String> {
+    let base_dir = (spec.base_dir)()?;
+    let accounts_dir = base_dir.join(spec.accounts_dir);
+    fs::create_dir_all(&accounts_dir).map_err(|e| format!("创建账号目录失败: {}", e))?;
+
+    let ids = list_record_ids(conn, spec.platform)?;
+    let mut exported = 0usize;
+    for account_id in &ids {
+        if let Some(value) = read_record_value(conn, spec, account_id)? {
+            let content =
+                serde_json::to_string_pretty(&value).map_err(|e| format!("序列化失败: {}", e))?;
+            atomic_file::write_secret(accounts_dir.join(format!("{}.json", account_id)), content)
+                .map_err(|e| format!("写入文件失败: {}", e))?;
+            exported += 1;
+        }
+    }
+
+    // 清理数据库中已删除、但导入前遗留的旧账号文件
+    let keep: HashSet<String> = ids.into_iter().collect();
+    let mut removed = 0usize;
+    if let Ok(entries) = fs::read_dir(&accounts_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            if !keep.contains(stem) && fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+    }
+
+    if let Some(index) = read_index_value(conn, spec)? {
+        write_json_file(&base_dir.join(spec.index_file), &index)?;
+    }
+
+    Ok(PlatformStoreReport {
+        platform: spec.platform.to_string(),
+        accounts: exported,
+        skipped: removed,
+    })
+}
+
+/// 回滚：将数据库内容写回原 JSON 布局，并切回文件存储
+pub fn export_to_json() -> Result<Vec<PlatformStoreReport>, String> {
+    let conn = open_connection()?;
+    let mut reports = Vec::new();
+    for spec in all_specs() {
+        reports.push(export_platform(&conn, spec)?);
+    }
+    write_meta(&conn, META_ACTIVE, "0")?;
+    write_meta(&conn, META_ROLLED_BACK_AT, &now_ts().to_string())?;
+    set_active_state(false);
+
+    logger::log_info("[AccountStore] 已回滚到 JSON 文件存储");
+    Ok(reports)
+}
+
+pub fn get_status() -> Result<AccountStoreStatus, String> {
+    let db_path = get_db_path()?;
+    let mut status = AccountStoreStatus {
+        active: is_active(),
+        db_path: db_path.to_string_lossy().to_string(),
+        schema_version: None,
+        imported_at: None,
+        rolled_back_at: None,
+        platforms: Vec::new(),
+    };
+    if !db_path.exists() {
+        return Ok(status);
+    }
+
+    let conn = open_connection()?;
+    let parse = |value: Option<String>| value.and_then(|v| v.parse::<i64>().ok());
+    status.schema_version = parse(read_meta(&conn, META_SCHEMA_VERSION)?);
+    status.imported_at = parse(read_meta(&conn, META_IMPORTED_AT)?);
+    status.rolled_back_at = parse(read_meta(&conn, META_ROLLED_BACK_AT)?);
+    for spec in all_specs() {
+        status.platforms.push(PlatformStoreReport {
+            platform: spec.platform.to_string(),
+            accounts: list_record_ids(&conn, spec.platform)?.len(),
+            skipped: 0,
+        });
+    }
+    Ok(status)
+}
+
+/// 启动时读取存储状态：数据库存储需用户主动导入启用，默认继续使用 JSON 文件；
+/// 已启用时清理导入后遗留的账号文件
+pub fn init_on_startup() {
+    let result = (|| -> Result<(), String> {
+        if !get_db_path()?.exists() {
+            set_active_state(false);
+            return Ok(());
+        }
+        let conn = open_connection()?;
+        let active = read_meta(&conn, META_ACTIVE)?.as_deref() == Some("1");
+        set_active_state(active);
+        if active {
+            if let Err(e) = purge_imported_files(&conn) {
+                logger::log_warn(&format!("[AccountStore] 删除导入后的账号文件失败: {}", e));
+            }
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        set_active_state(false);
+        logger::log_error(&format!(
+            "[AccountStore] 读取统一账号存储状态失败，继续使用 JSON 文件: {}",
+            e
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn no_dir() -> Result<PathBuf, String> {
+        Err("unused".to_string())
+    }
+
+    const SPEC: StoreSpec = StoreSpec {
+        platform: "test",
+        base_dir: no_dir,
+        index_file: "test_accounts.json",
+        accounts_dir: "test_accounts",
+        secret_fields: &["/token/access_token", "/token/refresh_token", "/api_key"],
+        quota_fields: &["quota", "quota_error"],
+        mirror_index: false,
+    };
+
+    #[test]
+    fn split_and_join_roundtrip() {
+        let original = json!({
+            "id": "a1",
+            "email": "a@example.com",
+            "tags": ["work", "main"],
+            "token": { "access_token": "at", "refresh_token": "rt", "expires_in": 3600 },
+            "api_key": "k",
+            "quota": { "models": [] },
+            "created_at": 1,
+            "last_used": 2
+        });
+        let parts = split_record(&SPEC, original.clone()).unwrap();
+
+        assert_eq!(parts.data["token"], json!({ "expires_in": 3600 }));
+        assert!(parts.data.get("api_key").is_none());
+        assert!(parts.data.get("quota").is_none());
+        assert_eq!(parts.tokens["token"]["access_token"], "at");
+        assert_eq!(parts.quota["quota"], json!({ "models": [] }));
+        assert_eq!(
+            parts.tags,
+            Some(vec!["work".to_string(), "main".to_string()])
+        );
+
+        assert_eq!(join_record(&SPEC, parts), original);
+    }
+
+    #[test]
+    fn removes_only_imported_account_files() {
+        let dir = std::env::temp_dir().join(format!("cockpit-store-purge-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "a1.json",
+            "a1.json.bak",
+            "broken.json",
+            "broken.json.bak",
+            "notes.txt",
+        ] {
+            fs::write(dir.join(name), "{}").unwrap();
+        }
+
+        let ids: HashSet<String> = ["a1".to_string()].into_iter().collect();
+        assert_eq!(remove_imported_files(&dir, &ids), 2);
+
+        let mut left: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["broken.json", "broken.json.bak", "notes.txt"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn split_keeps_missing_fields_absent() {
+        let original = json!({ "id": "a2", "created_at": 1, "last_used": 2 });
+        let parts = split_record(&SPEC, original.clone()).unwrap();
+        assert!(parts.tags.is_none());
+        assert_eq!(parts.tokens, json!({}));
+        assert_eq!(join_record(&SPEC, parts), original);
+    }
+}
@@ -0,0 +1,393 @@
+//! 预警通知调度
+//!
+//! 规则命中后的预警统一经由这里发出：
+//! - 去重：同一规则对同一邮箱的预警在去重窗口内只通知一次（同一邮箱在多个平台同时命中时只提醒一次）
+//! - 免打扰：处于免打扰时段时暂存预警，时段结束后再发出（多条时合并为一条汇总）
+//! - 汇总：开启后每个汇总周期最多发出一条汇总通知
+//!
+//! 暂存的预警只保存在内存中，应用退出时丢弃。
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::modules::account::{self, QuotaAlertPayload};
+use crate::modules::{logger, wakeup_scheduler};
+use crate::utils::atomic_file;
+
+const SETTINGS_FILE: &str = "alert_notification.json";
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+const MIN_DIGEST_INTERVAL_MINUTES: i32 = 5;
+/// 汇总通知正文中最多列出的预警条数
+const DIGEST_MAX_LINES: usize = 5;
+pub const DIGEST_RULE_ID: &str = "digest";
+
+fn default_quiet_start() -> String {
+    "23:00".to_string()
+}
+
+fn default_quiet_end() -> String {
+    "08:00".to_string()
+}
+
+fn default_digest_interval_minutes() -> i32 {
+    30
+}
+
+fn default_dedup_window_minutes() -> i32 {
+    10
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertNotifySettings {
+    #[serde(default)]
+    pub quiet_hours_enabled: bool,
+    /// 免打扰开始时间（HH:MM，本地时间）
+    #[serde(default = "default_quiet_start")]
+    pub quiet_start: String,
+    /// 免打扰结束时间（HH:MM），早于开始时间表示跨天
+    #[serde(default = "default_quiet_end")]
+    pub quiet_end: String,
+    #[serde(default)]
+    pub digest_enabled: bool,
+    #[serde(default = "default_digest_interval_minutes")]
+    pub digest_interval_minutes: i32,
+    /// 去重窗口（分钟），0 表示不去重
+    #[serde(default = "default_dedup_window_minutes")]
+    pub dedup_window_minutes: i32,
+}
+
+impl Default for AlertNotifySettings {
+    fn default() -> Self {
+        Self {
+            quiet_hours_enabled: false,
+            quiet_start: default_quiet_start(),
+            quiet_end: default_quiet_end(),
+            digest_enabled: false,
+            digest_interval_minutes: default_digest_interval_minutes(),
+            dedup_window_minutes: default_dedup_window_minutes(),
+        }
+    }
+}
+
+impl AlertNotifySettings {
+    fn in_quiet_hours(&self, now: DateTime<Local>) -> bool {
+        self.quiet_hours_enabled
+            && self.quiet_start != self.quiet_end
+            && wakeup_scheduler::is_in_time_window(
+                Some(&self.quiet_start),
+                Some(&self.quiet_end),
+                now,
+            )
+    }
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(SETTINGS_FILE))
+}
+
+pub fn load_settings() -> AlertNotifySettings {
+    let Ok(path) = get_settings_path() else {
+        return AlertNotifySettings::default();
+    };
+    std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .or_else(|| atomic_file::recover_json(&path))
+        .unwrap_or_default()
+}
+
+fn is_valid_time(value: &str) -> bool {
+    let Some((hour, minute)) = value.split_once(':') else {
+        return false;
+    };
+    matches!(
+        (hour.parse::<u32>(), minute.parse::<u32>()),
+        (Ok(hour), Ok(minute)) if hour < 24 && minute < 60
+    )
+}
+
+pub fn save_settings(mut settings: AlertNotifySettings) -> Result<AlertNotifySettings, String> {
+    settings.quiet_start = settings.quiet_start.trim().to_string();
+    settings.quiet_end = settings.quiet_end.trim().to_string();
+    if !is_valid_time(&settings.quiet_start) || !is_valid_time(&settings.quiet_end) {
+        return Err("免打扰时间格式应为 HH:MM".to_string());
+    }
+    settings.digest_interval_minutes = settings
+        .digest_interval_minutes
+        .max(MIN_DIGEST_INTERVAL_MINUTES);
+    settings.dedup_window_minutes = settings.dedup_window_minutes.max(0);
+
+    let path = get_settings_path()?;
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("序列化预警通知设置失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存预警通知设置失败: {}", e))?;
+
+    // 关闭免打扰或汇总后立即发出暂存的预警
+    flush_due(&settings);
+    Ok(settings)
+}
+
+// ============ 调度状态 ============
+
+#[derive(Debug, Default)]
+struct NotifierState {
+    /// 等待发出的预警
+    pending: Vec<QuotaAlertPayload>,
+    /// 去重键 -> 最近一次接收时间
+    recent: BTreeMap<String, i64>,
+    last_flush_at: i64,
+}
+
+/// 同一邮箱、同类条件的预警视为重复，不区分平台与规则（各平台的内置规则 ID 不同）
+fn dedup_key(payload: &QuotaAlertPayload) -> String {
+    format!(
+        "{}|{}",
+        payload.condition_kind,
+        payload.current_email.trim().to_lowercase()
+    )
+}
+
+impl NotifierState {
+    /// 接收一条预警，去重窗口内的重复预警返回 false
+    fn accept(&mut self, payload: QuotaAlertPayload, dedup_seconds: i64, now: i64) -> bool {
+        let key = dedup_key(&payload);
+        self.recent.retain(|_, at| now - *at < dedup_seconds);
+        if self.recent.contains_key(&key) {
+            // 暂存中的同类预警替换为最新内容
+            if let Some(existing) = self.pending.iter_mut().find(|item| dedup_key(item) == key) {
+                *existing = payload;
+            }
+            return false;
+        }
+        if dedup_seconds > 0 {
+            self.recent.insert(key, now);
+        }
+        self.pending.push(payload);
+        true
+    }
+
+    /// 按设置判断当前是否应发出暂存的预警，应发出时取走全部暂存
+    fn take_due(
+        &mut self,
+        settings: &AlertNotifySettings,
+        now: i64,
+        local_now: DateTime<Local>,
+    ) -> Vec<QuotaAlertPayload> {
+        if self.pending.is_empty() || settings.in_quiet_hours(local_now) {
+            return Vec::new();
+        }
+        if settings.digest_enabled
+            && now - self.last_flush_at < i64::from(settings.digest_interval_minutes) * 60
+        {
+            return Vec::new();
+        }
+        self.last_flush_at = now;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+fn state() -> &'static Mutex<NotifierState> {
+    static STATE: OnceLock<Mutex<NotifierState>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(NotifierState::default()))
+}
+
+/// 把多条预警合并为一条汇总预警，原始预警放在 `digest` 中
+fn build_digest(items: Vec<QuotaAlertPayload>, now: i64) -> QuotaAlertPayload {
+    let mut emails: Vec<String> = Vec::new();
+    let mut low_models: Vec<String> = Vec::new();
+    for item in &items {
+        if !emails.contains(&item.current_email) {
+            emails.push(item.current_email.clone());
+        }
+        for model in &item.low_models {
+            if !low_models.contains(model) {
+                low_models.push(model.clone());
+            }
+        }
+    }
+    let mut lines: Vec<String> = items
+        .iter()
+        .take(DIGEST_MAX_LINES)
+        .map(|item| format!("[{}] {}", item.platform, item.message))
+        .collect();
+    if items.len() > DIGEST_MAX_LINES {
+        lines.push(format!("等共 {} 条预警", items.len()));
+    }
+
+    QuotaAlertPayload {
+        platform: items[0].platform.clone(),
+        current_account_id: String::new(),
+        current_email: emails.join(", "),
+        threshold: items.iter().map(|item| item.threshold).max().unwrap_or(0),
+        lowest_percentage: items
+            .iter()
+            .map(|item| item.lowest_percentage)
+            .min()
+            .unwrap_or(0),
+        low_models,
+        recommended_account_id: None,
+        recommended_email: None,
+        triggered_at: now,
+        rule_id: DIGEST_RULE_ID.to_string(),
+        rule_name: format!("配额预警汇总（{} 条）", items.len()),
+        condition_kind: String::new(),
+        severity: items
+            .iter()
+            .map(|item| item.severity)
+            .max()
+            .unwrap_or_default(),
+        message: lines.join("\n"),
+        digest: items,
+    }
+}
+
+fn dispatch(mut items: Vec<QuotaAlertPayload>, now: i64) {
+    match items.len() {
+        0 => {}
+        1 => account::dispatch_quota_alert(&items.remove(0)),
+        count => {
+            logger::log_info(&format!("[AlertNotifier] 发出汇总预警: {} 条", count));
+            account::dispatch_quota_alert(&build_digest(items, now));
+        }
+    }
+}
+
+fn flush_due(settings: &AlertNotifySettings) {
+    let now = chrono::Utc::now().timestamp();
+    let due = match state().lock() {
+        Ok(mut guard) => guard.take_due(settings, now, Local::now()),
+        Err(_) => return,
+    };
+    dispatch(due, now);
+}
+
+/// 提交规则命中的预警；未开启免打扰与汇总时立即发出。返回是否被接收（未被去重）
+pub fn submit(payload: QuotaAlertPayload) -> bool {
+    let settings = load_settings();
+    let now = chrono::Utc::now().timestamp();
+    let dedup_seconds = i64::from(settings.dedup_window_minutes.max(0)) * 60;
+    let accepted = match state().lock() {
+        Ok(mut guard) => guard.accept(payload, dedup_seconds, now),
+        Err(_) => return false,
+    };
+    if accepted {
+        flush_due(&settings);
+    }
+    accepted
+}
+
+/// 启动后台定时检查，免打扰结束或汇总周期到达时发出暂存的预警
+pub fn start() {
+    static STARTED: OnceLock<()> = OnceLock::new();
+    if STARTED.set(()).is_err() {
+        return;
+    }
+    tauri::async_runtime::spawn(async {
+        loop {
+            tokio::time::sleep(TICK_INTERVAL).await;
+            flush_due(&load_settings());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::alert_rules::AlertSeverity;
+    use chrono::TimeZone;
+
+    fn alert(platform: &str, email: &str, severity: AlertSeverity) -> QuotaAlertPayload {
+        QuotaAlertPayload {
+            platform: platform.to_string(),
+            current_account_id: email.to_string(),
+            current_email: email.to_string(),
+            threshold: 20,
+            lowest_percentage: 10,
+            low_models: vec!["5h".to_string()],
+            recommended_account_id: None,
+            recommended_email: None,
+            triggered_at: 0,
+            rule_id: "custom".to_string(),
+            rule_name: "低配额".to_string(),
+            condition_kind: "remaining_below".to_string(),
+            severity,
+            message: format!("{} 低于 20%", email),
+            digest: Vec::new(),
+        }
+    }
+
+    fn local(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn dedups_same_email_across_platforms_within_window() {
+        let mut state = NotifierState::default();
+        assert!(state.accept(alert("codex", "a@x.com", AlertSeverity::Warning), 600, 0));
+        assert!(!state.accept(
+            alert("windsurf", "A@x.com", AlertSeverity::Warning),
+            600,
+            60
+        ));
+        assert!(state.accept(alert("codex", "b@x.com", AlertSeverity::Warning), 600, 60));
+        assert_eq!(state.pending.len(), 2);
+        assert_eq!(state.pending[0].platform, "windsurf");
+        assert!(state.accept(alert("codex", "a@x.com", AlertSeverity::Warning), 600, 600));
+    }
+
+    #[test]
+    fn dedups_builtin_rules_by_condition_kind_not_rule_id() {
+        let builtin = |platform: &str, email: &str| QuotaAlertPayload {
+            rule_id: format!("builtin:{}", platform),
+            rule_name: format!("{} 配额预警", platform),
+            ..alert(platform, email, AlertSeverity::Warning)
+        };
+        let mut state = NotifierState::default();
+        assert!(state.accept(builtin("codex", "a@x.com"), 600, 0));
+        assert!(!state.accept(builtin("github_copilot", "a@x.com"), 600, 30));
+        assert!(!state.accept(alert("kiro", "a@x.com", AlertSeverity::Warning), 600, 40));
+
+        let expiring = QuotaAlertPayload {
+            condition_kind: "token_expiring".to_string(),
+            ..builtin("github_copilot", "a@x.com")
+        };
+        assert!(state.accept(expiring, 600, 60));
+        assert!(state.accept(builtin("github_copilot", "b@x.com"), 600, 60));
+        assert_eq!(state.pending.len(), 3);
+        assert_eq!(state.pending[0].platform, "kiro");
+    }
+
+    #[test]
+    fn holds_alerts_in_quiet_hours_and_batches_digest() {
+        let settings = AlertNotifySettings {
+            quiet_hours_enabled: true,
+            quiet_start: "22:00".to_string(),
+            quiet_end: "07:00".to_string(),
+            digest_enabled: true,
+            digest_interval_minutes: 30,
+            ..AlertNotifySettings::default()
+        };
+        let mut state = NotifierState {
+            last_flush_at: 10_000,
+            ..NotifierState::default()
+        };
+        state.accept(alert("codex", "a@x.com", AlertSeverity::Info), 0, 10_000);
+        state.accept(alert("kiro", "b@x.com", AlertSeverity::Critical), 0, 10_000);
+
+        assert!(state.take_due(&settings, 20_000, local(23)).is_empty());
+        assert!(state.take_due(&settings, 10_600, local(9)).is_empty());
+        let due = state.take_due(&settings, 20_000, local(9));
+        assert_eq!(due.len(), 2);
+        assert!(state.pending.is_empty());
+
+        let digest = build_digest(due, 20_000);
+        assert_eq!(digest.rule_id, DIGEST_RULE_ID);
+        assert_eq!(digest.severity, AlertSeverity::Critical);
+        assert_eq!(digest.current_email, "a@x.com, b@x.com");
+        assert_eq!(digest.digest.len(), 2);
+    }
+}
@@ -0,0 +1,699 @@
+//! 规则化配额预警
+//!
+//! 内置规则由各平台原有的「配额预警开关 + 阈值」设置生成（只读，在设置页修改），
+//! 自定义规则持久化到 alert_rules.json。每次刷新后按平台评估全部启用的规则，
+//! 命中且不在冷却期内时交给 `alert_notifier` 按免打扰、去重与汇总设置发出预警。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::modules::account::{self, QuotaAlertPayload};
+use crate::modules::platform::{self, Platform, PlatformAccount};
+use crate::modules::quota_forecast::{self, QuotaForecast};
+use crate::modules::{alert_notifier, group_settings, logger};
+use crate::utils::atomic_file;
+
+const RULES_FILE: &str = "alert_rules.json";
+const BUILTIN_PREFIX: &str = "builtin:";
+pub const DEFAULT_COOLDOWN_MINUTES: i64 = 5;
+
+/// 规则作用范围，各字段同时满足才匹配；为空的字段不做限制
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertScope {
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub account_id: Option<String>,
+    /// `group_settings` 中的模型分组 ID，配额类条件只比较该分组内的模型
+    #[serde(default)]
+    pub model_group: Option<String>,
+    /// 只检查平台的当前账号
+    #[serde(default)]
+    pub current_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// 任一指标剩余百分比不高于 percent
+    RemainingBelow { percent: i32 },
+    /// 按配额历史预测将在 minutes 分钟内、且在重置前耗尽
+    ExhaustsWithin { minutes: i64 },
+    /// 访问令牌将在 minutes 分钟内过期（含已过期）
+    TokenExpiring { minutes: i64 },
+    /// 账号已禁用、封禁或无权限
+    AccountDisabled,
+}
+
+impl AlertCondition {
+    /// 条件类型，与序列化时的 type 字段一致
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AlertCondition::RemainingBelow { .. } => "remaining_below",
+            AlertCondition::ExhaustsWithin { .. } => "exhausts_within",
+            AlertCondition::TokenExpiring { .. } => "token_expiring",
+            AlertCondition::AccountDisabled => "account_disabled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_cooldown_minutes() -> i64 {
+    DEFAULT_COOLDOWN_MINUTES
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// 新建规则时留空，保存时生成
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub scope: AlertScope,
+    pub condition: AlertCondition,
+    /// 同一规则对同一账号的最短提醒间隔
+    #[serde(default = "default_cooldown_minutes")]
+    pub cooldown_minutes: i64,
+    #[serde(default)]
+    pub severity: AlertSeverity,
+    /// 由平台预警设置生成的内置规则，不能在规则列表中编辑
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+/// 规则命中的账号
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertMatch {
+    pub platform: String,
+    pub account_id: String,
+    pub email: String,
+    /// 触发的指标名称（配额类条件）
+    pub metrics: Vec<String>,
+    /// 范围内指标的最低剩余百分比
+    pub lowest_percentage: Option<i32>,
+    pub message: String,
+}
+
+// ============ 规则存储 ============
+
+static RULES_LOCK: Mutex<()> = Mutex::new(());
+
+fn get_rules_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(RULES_FILE))
+}
+
+fn load_custom_rules() -> Vec<AlertRule> {
+    get_rules_path()
+        .ok()
+        .and_then(|path| atomic_file::recover_json::<Vec<AlertRule>>(&path))
+        .unwrap_or_default()
+}
+
+fn save_custom_rules(rules: &[AlertRule]) -> Result<(), String> {
+    let path = get_rules_path()?;
+    let content =
+        serde_json::to_string_pretty(rules).map_err(|e| format!("序列化预警规则失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存预警规则失败: {}", e))
+}
+
+/// 各平台「配额预警」设置对应的内置规则：当前账号任一模型低于阈值
+fn builtin_rules() -> Vec<AlertRule> {
+    platform::all()
+        .iter()
+        .map(|target| {
+            let settings = target.quota_alert_settings();
+            AlertRule {
+                id: format!("{}{}", BUILTIN_PREFIX, target.id()),
+                name: format!("{} 配额预警", target.display_name()),
+                enabled: settings.enabled,
+                scope: AlertScope {
+                    platform: Some(target.id().to_string()),
+                    current_only: true,
+                    ..AlertScope::default()
+                },
+                condition: AlertCondition::RemainingBelow {
+                    percent: settings.threshold.clamp(0, 100),
+                },
+                cooldown_minutes: DEFAULT_COOLDOWN_MINUTES,
+                severity: AlertSeverity::Warning,
+                builtin: true,
+            }
+        })
+        .collect()
+}
+
+/// 全部规则：内置规则在前，自定义规则按保存顺序
+pub fn list_rules() -> Vec<AlertRule> {
+    let mut rules = builtin_rules();
+    rules.extend(load_custom_rules());
+    rules
+}
+
+fn validate_rule(rule: &AlertRule) -> Result<(), String> {
+    if rule.name.trim().is_empty() {
+        return Err("规则名称不能为空".to_string());
+    }
+    if let Some(platform_id) = rule.scope.platform.as_deref() {
+        platform::get(platform_id)?;
+    }
+    if rule.cooldown_minutes < 0 {
+        return Err("冷却时间不能为负数".to_string());
+    }
+    match rule.condition {
+        AlertCondition::RemainingBelow { percent } if !(0..=100).contains(&percent) => {
+            Err("剩余百分比需在 0-100 之间".to_string())
+        }
+        AlertCondition::ExhaustsWithin { minutes } | AlertCondition::TokenExpiring { minutes }
+            if minutes <= 0 =>
+        {
+            Err("时间窗口需大于 0 分钟".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 新增或更新自定义规则，返回保存后的规则
+pub fn save_rule(mut rule: AlertRule) -> Result<AlertRule, String> {
+    if rule.builtin || rule.id.starts_with(BUILTIN_PREFIX) {
+        return Err("内置规则请在设置中修改对应平台的配额预警".to_string());
+    }
+    validate_rule(&rule)?;
+    rule.name = rule.name.trim().to_string();
+    if rule.id.is_empty() {
+        rule.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    let _lock = RULES_LOCK
+        .lock()
+        .map_err(|_| "获取预警规则锁失败".to_string())?;
+    let mut rules = load_custom_rules();
+    match rules.iter_mut().find(|item| item.id == rule.id) {
+        Some(existing) => *existing = rule.clone(),
+        None => rules.push(rule.clone()),
+    }
+    save_custom_rules(&rules)?;
+    Ok(rule)
+}
+
+pub fn delete_rule(rule_id: &str) -> Result<(), String> {
+    if rule_id.starts_with(BUILTIN_PREFIX) {
+        return Err("内置规则不能删除".to_string());
+    }
+    let _lock = RULES_LOCK
+        .lock()
+        .map_err(|_| "获取预警规则锁失败".to_string())?;
+    let mut rules = load_custom_rules();
+    let before = rules.len();
+    rules.retain(|item| item.id != rule_id);
+    if rules.len() == before {
+        return Err(format!("预警规则不存在: {}", rule_id));
+    }
+    save_custom_rules(&rules)
+}
+
+// ============ 评估 ============
+
+/// 评估一个平台所需的数据（一次读取，供该平台的全部规则共用）
+struct PlatformData {
+    platform: String,
+    accounts: Vec<PlatformAccount>,
+    current_id: Option<String>,
+    forecasts: Vec<QuotaForecast>,
+    /// 模型 ID -> 分组 ID
+    group_mappings: HashMap<String, String>,
+}
+
+impl PlatformData {
+    fn load(target: &dyn Platform, rules: &[&AlertRule]) -> Result<Self, String> {
+        let accounts = target.list_accounts()?;
+        let current_id = target.current_account_id(&accounts);
+        let forecasts = if rules
+            .iter()
+            .any(|rule| matches!(rule.condition, AlertCondition::ExhaustsWithin { .. }))
+        {
+            quota_forecast::forecast_platform(target)?
+        } else {
+            Vec::new()
+        };
+        let group_mappings = if rules.iter().any(|rule| rule.scope.model_group.is_some()) {
+            group_settings::load_group_settings().group_mappings
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            platform: target.id().to_string(),
+            accounts,
+            current_id,
+            forecasts,
+            group_mappings,
+        })
+    }
+
+    /// 指标是否属于范围内的模型分组；按 `group_settings::model_matches` 匹配模型版本变体
+    fn in_model_group(&self, scope: &AlertScope, metric_id: &str) -> bool {
+        scope.model_group.as_deref().is_none_or(|group| {
+            self.group_mappings.iter().any(|(model, group_id)| {
+                group_id == group && group_settings::model_matches(metric_id, model)
+            })
+        })
+    }
+
+    fn account_in_scope(&self, scope: &AlertScope, account: &PlatformAccount) -> bool {
+        if scope.current_only && self.current_id.as_deref() != Some(account.id.as_str()) {
+            return false;
+        }
+        if let Some(account_id) = scope.account_id.as_deref() {
+            if account.id != account_id {
+                return false;
+            }
+        }
+        if let Some(tag) = scope.tag.as_deref() {
+            if !account
+                .tags
+                .iter()
+                .any(|item| item.eq_ignore_ascii_case(tag))
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn evaluate_account(
+        &self,
+        rule: &AlertRule,
+        account: &PlatformAccount,
+        now: i64,
+    ) -> Option<AlertMatch> {
+        let scoped_metrics: Vec<_> = account
+            .quota
+            .metrics
+            .iter()
+            .filter(|metric| self.in_model_group(&rule.scope, &metric.id))
+            .collect();
+        let lowest_percentage = scoped_metrics
+            .iter()
+            .map(|metric| metric.remaining_pct)
+            .min();
+
+        let (metrics, message) = match rule.condition {
+            AlertCondition::RemainingBelow { percent } => {
+                let low: Vec<String> = scoped_metrics
+                    .iter()
+                    .filter(|metric| metric.remaining_pct <= percent)
+                    .map(|metric| metric.label.clone())
+                    .collect();
+                if low.is_empty() {
+                    return None;
+                }
+                let message = format!(
+                    "{} 低于 {}%（最低 {}%，模型：{}）",
+                    account.email,
+                    percent,
+                    lowest_percentage.unwrap_or(0),
+                    low.join(", ")
+                );
+                (low, message)
+            }
+            AlertCondition::ExhaustsWithin { minutes } => {
+                let deadline = now + minutes * 60;
+                let exhausting: Vec<String> = self
+                    .forecasts
+                    .iter()
+                    .filter(|forecast| forecast.account_id == account.id)
+                    .filter(|forecast| self.in_model_group(&rule.scope, &forecast.metric))
+                    .filter(|forecast| forecast.exhausts_before_reset)
+                    .filter(|forecast| forecast.exhausts_at.is_some_and(|at| at <= deadline))
+                    .map(|forecast| forecast.label.clone())
+                    .collect();
+                if exhausting.is_empty() {
+                    return None;
+                }
+                let message = format!(
+                    "{} 预计 {} 分钟内耗尽（模型：{}）",
+                    account.email,
+                    minutes,
+                    exhausting.join(", ")
+                );
+                (exhausting, message)
+            }
+            AlertCondition::TokenExpiring { minutes } => {
+                let expires_at = account.token_expires_at?;
+                if account.disabled || expires_at > now + minutes * 60 {
+                    return None;
+                }
+                let message = if expires_at <= now {
+                    format!("{} 的令牌已过期", account.email)
+                } else {
+                    format!(
+                        "{} 的令牌将在 {} 分钟内过期",
+                        account.email,
+                        (expires_at - now + 59) / 60
+                    )
+                };
+                (Vec::new(), message)
+            }
+            AlertCondition::AccountDisabled => {
+                if !account.disabled {
+                    return None;
+                }
+                (Vec::new(), format!("{} 已禁用或无权限", account.email))
+            }
+        };
+
+        Some(AlertMatch {
+            platform: self.platform.clone(),
+            account_id: account.id.clone(),
+            email: account.email.clone(),
+            metrics,
+            lowest_percentage,
+            message,
+        })
+    }
+
+    fn evaluate(&self, rule: &AlertRule, now: i64) -> Vec<AlertMatch> {
+        self.accounts
+            .iter()
+            .filter(|account| self.account_in_scope(&rule.scope, account))
+            .filter_map(|account| self.evaluate_account(rule, account, now))
+            .collect()
+    }
+}
+
+fn rule_applies_to(rule: &AlertRule, platform_id: &str) -> bool {
+    rule.scope
+        .platform
+        .as_deref()
+        .is_none_or(|id| id == platform_id)
+}
+
+/// 用当前数据试运行规则：不受冷却和启用状态影响，也不会发出预警
+pub fn test_rule(rule: &AlertRule) -> Result<Vec<AlertMatch>, String> {
+    validate_rule(rule)?;
+    let now = chrono::Utc::now().timestamp();
+    let mut matches = Vec::new();
+    for target in platform::all() {
+        if !rule_applies_to(rule, target.id()) {
+            continue;
+        }
+        let data = PlatformData::load(*target, &[rule])?;
+        matches.extend(data.evaluate(rule, now));
+    }
+    Ok(matches)
+}
+
+// ============ 冷却与发送 ============
+
+/// 规则 + 平台 + 账号 -> 上次提醒时间
+static LAST_SENT: Mutex<BTreeMap<String, i64>> = Mutex::new(BTreeMap::new());
+
+fn cooldown_key(rule_id: &str, platform_id: &str, account_id: &str) -> String {
+    format!("{}|{}|{}", rule_id, platform_id, account_id)
+}
+
+/// 过滤出冷却期外的命中并记录提醒时间；不再命中的账号清除冷却，恢复后再次命中会立即提醒
+fn take_due_matches(
+    last_sent: &mut BTreeMap<String, i64>,
+    rule: &AlertRule,
+    platform_id: &str,
+    matches: Vec<AlertMatch>,
+    now: i64,
+) -> Vec<AlertMatch> {
+    let prefix = cooldown_key(&rule.id, platform_id, "");
+    let active: HashSet<String> = matches
+        .iter()
+        .map(|item| cooldown_key(&rule.id, platform_id, &item.account_id))
+        .collect();
+    last_sent.retain(|key, _| !key.starts_with(&prefix) || active.contains(key));
+
+    let cooldown_seconds = rule.cooldown_minutes.max(0) * 60;
+    matches
+        .into_iter()
+        .filter(|item| {
+            let key = cooldown_key(&rule.id, platform_id, &item.account_id);
+            if let Some(last) = last_sent.get(&key) {
+                if now - *last < cooldown_seconds {
+                    return false;
+                }
+            }
+            last_sent.insert(key, now);
+            true
+        })
+        .collect()
+}
+
+fn build_payload(
+    rule: &AlertRule,
+    accounts: &[PlatformAccount],
+    item: AlertMatch,
+    now: i64,
+) -> QuotaAlertPayload {
+    let recommendation = platform::pick_quota_alert_recommendation(accounts, &item.account_id);
+    QuotaAlertPayload {
+        platform: item.platform,
+        current_account_id: item.account_id,
+        current_email: item.email,
+        threshold: match rule.condition {
+            AlertCondition::RemainingBelow { percent } => percent,
+            _ => 0,
+        },
+        lowest_percentage: item.lowest_percentage.unwrap_or(0),
+        low_models: item.metrics,
+        recommended_account_id: recommendation.map(|account| account.id.clone()),
+        recommended_email: recommendation.map(|account| account.email.clone()),
+        triggered_at: now,
+        rule_id: rule.id.clone(),
+        rule_name: rule.name.clone(),
+        condition_kind: rule.condition.kind().to_string(),
+        severity: rule.severity,
+        message: item.message,
+        digest: Vec::new(),
+    }
+}
+
+/// 评估平台的全部启用规则并提交预警，返回本次提交（未被去重）的预警
+pub fn run(target: &dyn Platform) -> Result<Vec<QuotaAlertPayload>, String> {
+    let rules = list_rules();
+    let active: Vec<&AlertRule> = rules
+        .iter()
+        .filter(|rule| rule.enabled && rule_applies_to(rule, target.id()))
+        .collect();
+    if active.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let data = PlatformData::load(target, &active)?;
+    let now = chrono::Utc::now().timestamp();
+    let mut payloads = Vec::new();
+    for rule in active {
+        let matches = data.evaluate(rule, now);
+        let due = {
+            let mut last_sent = LAST_SENT
+                .lock()
+                .map_err(|_| "获取预警冷却锁失败".to_string())?;
+            take_due_matches(&mut last_sent, rule, target.id(), matches, now)
+        };
+        for item in due {
+            let payload = build_payload(rule, &data.accounts, item, now);
+            if alert_notifier::submit(payload.clone()) {
+                payloads.push(payload);
+            }
+        }
+    }
+    if !payloads.is_empty() {
+        logger::log_info(&format!(
+            "[AlertRules][{}] 发出预警: {}",
+            target.display_name(),
+            payloads.len()
+        ));
+    }
+    Ok(payloads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::platform::{QuotaMetric, UnifiedQuota};
+
+    fn account(id: &str, tags: &[&str], disabled: bool, percentages: &[i32]) -> PlatformAccount {
+        PlatformAccount {
+            platform: "antigravity".to_string(),
+            id: id.to_string(),
+            email: format!("{}@example.com", id),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            created_at: 0,
+            last_used: 0,
+            disabled,
+            forbidden: false,
+            quota_error: None,
+            token_expires_at: Some(1_000 + 600),
+            quota_stale: false,
+            quota: UnifiedQuota::new(
+                percentages
+                    .iter()
+                    .enumerate()
+                    .map(|(index, pct)| {
+                        QuotaMetric::from_percent(
+                            format!("m{}", index),
+                            format!("M{}", index),
+                            *pct,
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn rule(scope: AlertScope, condition: AlertCondition) -> AlertRule {
+        AlertRule {
+            id: "r".to_string(),
+            name: "test".to_string(),
+            enabled: true,
+            scope,
+            condition,
+            cooldown_minutes: 10,
+            severity: AlertSeverity::Warning,
+            builtin: false,
+        }
+    }
+
+    fn data() -> PlatformData {
+        PlatformData {
+            platform: "antigravity".to_string(),
+            accounts: vec![
+                account("current", &[], false, &[50, 10]),
+                account("team", &["Team"], false, &[5, 90]),
+                account("banned", &["team"], true, &[]),
+            ],
+            current_id: Some("current".to_string()),
+            forecasts: Vec::new(),
+            group_mappings: HashMap::from([("m1".to_string(), "flash".to_string())]),
+        }
+    }
+
+    #[test]
+    fn scope_and_conditions_select_matching_accounts() {
+        let data = data();
+        let ids = |rule: AlertRule| -> Vec<String> {
+            data.evaluate(&rule, 1_000)
+                .into_iter()
+                .map(|item| item.account_id)
+                .collect()
+        };
+
+        let below = AlertCondition::RemainingBelow { percent: 20 };
+        assert_eq!(
+            ids(rule(AlertScope::default(), below.clone())),
+            vec!["current", "team"]
+        );
+        let current_only = AlertScope {
+            current_only: true,
+            ..AlertScope::default()
+        };
+        assert_eq!(ids(rule(current_only, below.clone())), vec!["current"]);
+        let flash_group = AlertScope {
+            model_group: Some("flash".to_string()),
+            ..AlertScope::default()
+        };
+        assert_eq!(ids(rule(flash_group, below)), vec!["current"]);
+
+        let team = AlertScope {
+            tag: Some("team".to_string()),
+            ..AlertScope::default()
+        };
+        assert_eq!(
+            ids(rule(team.clone(), AlertCondition::AccountDisabled)),
+            vec!["banned"]
+        );
+        assert_eq!(
+            ids(rule(team, AlertCondition::TokenExpiring { minutes: 10 })),
+            vec!["team"]
+        );
+        assert!(ids(rule(
+            AlertScope::default(),
+            AlertCondition::TokenExpiring { minutes: 5 }
+        ))
+        .is_empty());
+    }
+
+    #[test]
+    fn model_group_scope_matches_model_variants() {
+        let mut variant = account("variant", &[], false, &[]);
+        variant.quota = UnifiedQuota::new(vec![
+            QuotaMetric::from_percent("gemini-2.5-flash-lite", "Gemini Flash", 5),
+            QuotaMetric::from_percent("claude-opus-4-6-thinking", "Claude Opus", 90),
+        ]);
+        let data = PlatformData {
+            accounts: vec![variant],
+            current_id: None,
+            group_mappings: HashMap::from([
+                ("gemini-3-flash".to_string(), "flash".to_string()),
+                ("claude-opus-4-6-thinking".to_string(), "claude".to_string()),
+            ]),
+            ..data()
+        };
+        let scoped = |group: &str| {
+            let scope = AlertScope {
+                model_group: Some(group.to_string()),
+                ..AlertScope::default()
+            };
+            data.evaluate(
+                &rule(scope, AlertCondition::RemainingBelow { percent: 20 }),
+                1_000,
+            )
+        };
+
+        let flash = scoped("flash");
+        assert_eq!(flash.len(), 1);
+        assert_eq!(flash[0].metrics, vec!["Gemini Flash"]);
+        assert!(scoped("claude").is_empty());
+    }
+
+    #[test]
+    fn cooldown_suppresses_repeats_until_condition_clears() {
+        let data = data();
+        let rule = rule(
+            AlertScope::default(),
+            AlertCondition::RemainingBelow { percent: 20 },
+        );
+        let mut last_sent = BTreeMap::new();
+        let mut due = |now: i64, data: &PlatformData| {
+            take_due_matches(
+                &mut last_sent,
+                &rule,
+                "antigravity",
+                data.evaluate(&rule, now),
+                now,
+            )
+            .len()
+        };
+
+        assert_eq!(due(1_000, &data), 2);
+        assert_eq!(due(1_300, &data), 0);
+        assert_eq!(due(1_600, &data), 2);
+
+        let mut recovered = data;
+        recovered.accounts.retain(|account| account.id != "team");
+        assert_eq!(due(1_700, &recovered), 0);
+        recovered.accounts.push(account("team", &[], false, &[5]));
+        assert_eq!(due(1_800, &recovered), 1);
+    }
+}
@@ -0,0 +1,360 @@
+//! 预警 Webhook 通知渠道
+//!
+//! 预警发出时按设置把消息 POST 到 HTTP Webhook：默认发送完整 JSON，也可选择 Slack / Discord / 飞书
+//! 兼容的消息格式。网络错误、429 与 5xx 按指数间隔重试；配置了密钥时附带 HMAC-SHA256 签名头，
+//! 飞书格式同时按飞书自定义机器人的规则在消息体中签名。
+
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::modules::account::{self, QuotaAlertPayload};
+use crate::modules::alert_rules::AlertSeverity;
+use crate::modules::{credential_vault, logger};
+use crate::utils::{atomic_file, http};
+
+const SETTINGS_FILE: &str = "alert_webhook.json";
+/// 设置文件中需要由凭据保险库加密的字段
+const SECRET_POINTERS: &[&str] = &["/secret"];
+pub const SIGNATURE_HEADER: &str = "X-Cockpit-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Cockpit-Timestamp";
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const MAX_RETRIES_LIMIT: u32 = 10;
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10;
+/// 第 n 次重试前等待 500ms * 2^(n-1)
+const RETRY_BASE_DELAY_MS: u64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookTemplate {
+    /// 完整的预警 JSON
+    #[default]
+    Json,
+    Slack,
+    Discord,
+    Feishu,
+}
+
+fn default_max_retries() -> u32 {
+    DEFAULT_MAX_RETRIES
+}
+
+fn default_timeout_seconds() -> u64 {
+    DEFAULT_TIMEOUT_SECONDS
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub template: WebhookTemplate,
+    /// 签名密钥，为空时不签名
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            template: WebhookTemplate::default(),
+            secret: None,
+            max_retries: DEFAULT_MAX_RETRIES,
+            timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+        }
+    }
+}
+
+/// 一次投递的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub status: u16,
+    /// 实际发送次数（含首次）
+    pub attempts: u32,
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(SETTINGS_FILE))
+}
+
+pub fn load_settings() -> WebhookSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| credential_vault::from_sealed_json(&content, SECRET_POINTERS).ok())
+        .unwrap_or_default()
+}
+
+fn validate_settings(settings: &WebhookSettings) -> Result<(), String> {
+    let parsed =
+        url::Url::parse(settings.url.trim()).map_err(|e| format!("Webhook 地址无效: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Webhook 地址需以 http:// 或 https:// 开头".to_string());
+    }
+    Ok(())
+}
+
+pub fn save_settings(mut settings: WebhookSettings) -> Result<WebhookSettings, String> {
+    settings.url = settings.url.trim().to_string();
+    settings.secret = settings
+        .secret
+        .map(|secret| secret.trim().to_string())
+        .filter(|secret| !secret.is_empty());
+    settings.max_retries = settings.max_retries.min(MAX_RETRIES_LIMIT);
+    settings.timeout_seconds = settings.timeout_seconds.clamp(1, 60);
+    if settings.enabled || !settings.url.is_empty() {
+        validate_settings(&settings)?;
+    }
+
+    let path = get_settings_path()?;
+    let content = credential_vault::to_sealed_json(&settings, SECRET_POINTERS)?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存 Webhook 设置失败: {}", e))?;
+    Ok(settings)
+}
+
+// ============ 消息格式与签名 ============
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 通用签名：`sha256=` + hex(HMAC-SHA256(secret, "{timestamp}.{body}"))
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let message = format!("{}.{}", timestamp, body);
+    format!(
+        "sha256={}",
+        to_hex(&hmac_sha256(secret.as_bytes(), message.as_bytes()))
+    )
+}
+
+/// 飞书自定义机器人签名：以 "{timestamp}\n{secret}" 为密钥对空消息做 HMAC-SHA256 后 Base64
+fn feishu_sign(secret: &str, timestamp: i64) -> String {
+    let key = format!("{}\n{}", timestamp, secret);
+    general_purpose::STANDARD.encode(hmac_sha256(key.as_bytes(), b""))
+}
+
+fn build_body(
+    settings: &WebhookSettings,
+    event: &str,
+    payload: &QuotaAlertPayload,
+    timestamp: i64,
+) -> serde_json::Value {
+    let (title, text) = account::build_quota_alert_notification_text(payload);
+    match settings.template {
+        WebhookTemplate::Json => json!({
+            "event": event,
+            "sent_at": timestamp,
+            "title": title,
+            "text": text,
+            "alert": payload,
+        }),
+        WebhookTemplate::Slack => json!({ "text": format!("*{}*\n{}", title, text) }),
+        WebhookTemplate::Discord => json!({ "content": format!("**{}**\n{}", title, text) }),
+        WebhookTemplate::Feishu => {
+            let mut body = json!({
+                "msg_type": "text",
+                "content": { "text": format!("{}\n{}", title, text) },
+            });
+            if let Some(secret) = settings.secret.as_deref() {
+                body["timestamp"] = json!(timestamp.to_string());
+                body["sign"] = json!(feishu_sign(secret, timestamp));
+            }
+            body
+        }
+    }
+}
+
+// ============ 发送 ============
+
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+async fn deliver(
+    settings: &WebhookSettings,
+    event: &str,
+    payload: &QuotaAlertPayload,
+) -> Result<WebhookDelivery, String> {
+    validate_settings(settings)?;
+    let timestamp = chrono::Utc::now().timestamp();
+    let body = build_body(settings, event, payload, timestamp).to_string();
+    let client = http::create_client(settings.timeout_seconds.max(1));
+
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let mut request = client
+            .post(&settings.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(secret) = settings.secret.as_deref() {
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(SIGNATURE_HEADER, sign(secret, timestamp, &body));
+        }
+
+        let error = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                return Ok(WebhookDelivery {
+                    status: response.status().as_u16(),
+                    attempts,
+                });
+            }
+            Ok(response) if !is_retryable(response.status()) => {
+                return Err(format!("Webhook 返回 {}", response.status()));
+            }
+            Ok(response) => format!("Webhook 返回 {}", response.status()),
+            Err(e) => format!("Webhook 请求失败: {}", e),
+        };
+        if attempts > settings.max_retries {
+            return Err(format!("{}（已尝试 {} 次）", error, attempts));
+        }
+        let delay = RETRY_BASE_DELAY_MS << (attempts - 1).min(6);
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+    }
+}
+
+/// 预警发出时调用：按设置异步推送，失败只记录日志
+pub fn notify(payload: &QuotaAlertPayload) {
+    let settings = load_settings();
+    if !settings.enabled || settings.url.is_empty() {
+        return;
+    }
+    let payload = payload.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = deliver(&settings, "quota_alert", &payload).await {
+            logger::log_warn(&format!(
+                "[AlertWebhook] 推送预警失败: rule={}, account={}, error={}",
+                payload.rule_id, payload.current_account_id, e
+            ));
+        }
+    });
+}
+
+/// 发送一条测试消息；`settings` 为空时使用已保存的设置
+pub async fn send_test(settings: Option<WebhookSettings>) -> Result<WebhookDelivery, String> {
+    let settings = settings.unwrap_or_else(load_settings);
+    let payload = QuotaAlertPayload {
+        platform: "antigravity".to_string(),
+        current_account_id: "test".to_string(),
+        current_email: "test@example.com".to_string(),
+        threshold: 20,
+        lowest_percentage: 15,
+        low_models: vec!["Test Model".to_string()],
+        recommended_account_id: None,
+        recommended_email: None,
+        triggered_at: chrono::Utc::now().timestamp(),
+        rule_id: "test".to_string(),
+        rule_name: "Cockpit Tools Webhook 测试".to_string(),
+        condition_kind: "remaining_below".to_string(),
+        severity: AlertSeverity::Info,
+        message: "这是一条测试消息，收到即表示 Webhook 配置正确".to_string(),
+        digest: Vec::new(),
+    };
+    deliver(&settings, "test", &payload).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn sample() -> QuotaAlertPayload {
+        QuotaAlertPayload {
+            platform: "codex".to_string(),
+            current_account_id: "a".to_string(),
+            current_email: "a@example.com".to_string(),
+            threshold: 20,
+            lowest_percentage: 10,
+            low_models: vec!["5h".to_string()],
+            recommended_account_id: None,
+            recommended_email: None,
+            triggered_at: 1,
+            rule_id: "r".to_string(),
+            rule_name: "Codex 配额预警".to_string(),
+            condition_kind: "remaining_below".to_string(),
+            severity: AlertSeverity::Warning,
+            message: "a@example.com 低于 20%".to_string(),
+            digest: Vec::new(),
+        }
+    }
+
+    /// 本地 HTTP 替身：依次用 `statuses` 应答，并把收到的签名头和请求体发回测试
+    fn spawn_stand_in(statuses: Vec<u16>) -> (String, mpsc::Receiver<(Option<String>, String)>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr().to_ip().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for status in statuses {
+                let Ok(mut request) = server.recv() else {
+                    return;
+                };
+                let signature = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv(SIGNATURE_HEADER))
+                    .map(|header| header.value.to_string());
+                let mut body = String::new();
+                let _ = request.as_reader().read_to_string(&mut body);
+                let _ = tx.send((signature, body));
+                let _ = request.respond(tiny_http::Response::empty(status));
+            }
+        });
+        (url, rx)
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_and_signs_body() {
+        let (url, rx) = spawn_stand_in(vec![503, 200]);
+        let settings = WebhookSettings {
+            enabled: true,
+            url,
+            secret: Some("s3cret".to_string()),
+            ..WebhookSettings::default()
+        };
+
+        let delivery = deliver(&settings, "quota_alert", &sample()).await.unwrap();
+        assert_eq!((delivery.status, delivery.attempts), (200, 2));
+
+        let (signature, body) = rx.recv().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["alert"]["rule_id"], "r");
+        let timestamp = value["sent_at"].as_i64().unwrap();
+        assert_eq!(signature, Some(sign("s3cret", timestamp, &body)));
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let (url, rx) = spawn_stand_in(vec![400, 200]);
+        let settings = WebhookSettings {
+            enabled: true,
+            url,
+            template: WebhookTemplate::Slack,
+            ..WebhookSettings::default()
+        };
+
+        assert!(deliver(&settings, "test", &sample()).await.is_err());
+        let (signature, body) = rx.recv().unwrap();
+        assert_eq!(signature, None);
+        assert!(body.contains("*Codex 配额预警*"));
+        assert!(rx.try_recv().is_err());
+    }
+}
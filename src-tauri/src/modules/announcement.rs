@@ -6,6 +6,7 @@ use std::path::{Path, PathBuf};
 
 use super::config;
 use super::logger;
+use crate::utils::atomic_file;
 
 const ANNOUNCEMENT_URL: &str =
     "https://raw.githubusercontent.com/jlcodes99/cockpit-tools/main/announcements.json";
@@ -202,7 +203,7 @@ fn save_cache(announcements: &[Announcement]) -> Result<(), String> {
     };
     let content =
         serde_json::to_string_pretty(&cache).map_err(|e| format!("序列化公告缓存失败: {}", e))?;
-    fs::write(get_cache_path()?, content).map_err(|e| format!("写入公告缓存失败: {}", e))?;
+    atomic_file::write(get_cache_path()?, content).map_err(|e| format!("写入公告缓存失败: {}", e))?;
     Ok(())
 }
 
@@ -229,7 +230,7 @@ fn get_read_ids() -> Result<Vec<String>, String> {
 fn save_read_ids(ids: &[String]) -> Result<(), String> {
     let content =
         serde_json::to_string_pretty(ids).map_err(|e| format!("序列化公告已读状态失败: {}", e))?;
-    fs::write(get_read_ids_path()?, content).map_err(|e| format!("写入公告已读状态失败: {}", e))?;
+    atomic_file::write(get_read_ids_path()?, content).map_err(|e| format!("写入公告已读状态失败: {}", e))?;
     Ok(())
 }
 
@@ -0,0 +1,997 @@
+//! 数据目录快照备份
+//!
+//! 定时以及在批量删除账号、导入账号、删除指纹等高风险操作前，对 `~/.antigravity_cockpit` 整体做快照：
+//!
+//! - 快照保存为 `backups/<id>.tar.gz`：文件逐个流式写入 `data/` 下，最后写入带 SHA-256 的清单 `manifest.json`，
+//!   元数据记录在 `backups/snapshots.json`
+//! - `cockpit.db` 通过 `VACUUM INTO` 导出一致副本，而不是直接复制可能处于 WAL 中间状态的文件
+//! - 日志、缓存、多开实例目录（IDE 用户数据，体积大且可重建）、锁文件、`.bak` 与临时文件不纳入快照
+//! - 按数量与天数清理旧快照；恢复前会先对当前状态做一次快照，恢复本身也可撤销
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::modules::{account, account_store, credential_vault, logger};
+use crate::utils::atomic_file;
+
+const BACKUP_DIR: &str = "backups";
+const SNAPSHOT_INDEX_FILE: &str = "snapshots.json";
+const BACKUP_SETTINGS_FILE: &str = "backup_settings.json";
+const SNAPSHOT_EXT: &str = ".tar.gz";
+const ARCHIVE_VERSION: u32 = 1;
+/// 归档内数据文件的前缀与清单条目
+const DATA_PREFIX: &str = "data/";
+const MANIFEST_ENTRY: &str = "manifest.json";
+
+/// 不纳入快照的顶层目录
+const EXCLUDED_DIRS: &[&str] = &[BACKUP_DIR, "logs", "cache", "instances"];
+/// 数据库在快照中的路径
+const DB_ENTRY: &str = "cockpit.db";
+const VAULT_META_ENTRY: &str = "vault.json";
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(30 * 60);
+
+pub const REASON_SCHEDULED: &str = "scheduled";
+pub const REASON_MANUAL: &str = "manual";
+const REASON_BEFORE_RESTORE: &str = "before_restore";
+
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+const DEFAULT_RETENTION_COUNT: usize = 30;
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+
+/// 串行化快照的创建、清理与恢复
+static BACKUP_LOCK: std::sync::LazyLock<Mutex<()>> = std::sync::LazyLock::new(|| Mutex::new(()));
+
+/// 备份设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSettings {
+    /// 是否启用自动快照（定时与高风险操作前）；手动快照不受影响
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 定时快照间隔（小时），0 表示不做定时快照
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u64,
+    /// 最多保留的快照数量，0 表示不限制
+    #[serde(default = "default_retention_count")]
+    pub retention_count: usize,
+    /// 快照最长保留天数，0 表示不限制
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_interval_hours() -> u64 {
+    DEFAULT_INTERVAL_HOURS
+}
+
+fn default_retention_count() -> usize {
+    DEFAULT_RETENTION_COUNT
+}
+
+fn default_retention_days() -> u64 {
+    DEFAULT_RETENTION_DAYS
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            interval_hours: DEFAULT_INTERVAL_HOURS,
+            retention_count: DEFAULT_RETENTION_COUNT,
+            retention_days: DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+/// 快照元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMeta {
+    pub id: String,
+    /// 触发原因：scheduled / manual / before_restore / before_<操作名>
+    pub reason: String,
+    pub created_at: i64,
+    pub file_count: usize,
+    /// 原始数据大小（字节）
+    pub total_size: u64,
+    /// 压缩后大小（字节）
+    pub compressed_size: u64,
+}
+
+/// 快照与当前数据（或另一个快照）的差异
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub from: String,
+    /// 对比目标：快照 ID，或 `current` 表示当前数据目录
+    pub to: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub snapshot_id: String,
+    /// 恢复前为当前状态创建的快照，可用于撤销本次恢复
+    pub safety_snapshot_id: String,
+    pub restored: usize,
+    pub removed: usize,
+}
+
+/// 归档末尾的清单
+#[derive(Serialize, Deserialize)]
+struct SnapshotManifest {
+    version: u32,
+    id: String,
+    reason: String,
+    created_at: i64,
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestEntry {
+    /// 相对数据目录的路径，统一使用 `/` 分隔
+    path: String,
+    size: u64,
+    sha256: String,
+}
+
+/// 读取时顺带计算 SHA-256，文件内容不需要整体读入内存
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+    len: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            len: 0,
+        }
+    }
+
+    fn finish(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+/// 临时文件或目录，离开作用域时删除
+struct TempPath(PathBuf);
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if self.0.is_dir() {
+            let _ = fs::remove_dir_all(&self.0);
+        } else {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+}
+
+fn now_ts() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn temp_suffix() -> String {
+    format!(
+        "{}.{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+    )
+}
+
+fn get_backup_dir() -> Result<PathBuf, String> {
+    let dir = account::get_data_dir()?.join(BACKUP_DIR);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
+    }
+    Ok(dir)
+}
+
+fn snapshot_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("无效的快照 ID: {}", id));
+    }
+    Ok(get_backup_dir()?.join(format!("{}{}", id, SNAPSHOT_EXT)))
+}
+
+// ============================================================================
+// 设置与索引
+// ============================================================================
+
+pub fn load_settings() -> BackupSettings {
+    let path = match get_backup_dir() {
+        Ok(dir) => dir.join(BACKUP_SETTINGS_FILE),
+        Err(_) => return BackupSettings::default(),
+    };
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_settings(settings: &BackupSettings) -> Result<(), String> {
+    let path = get_backup_dir()?.join(BACKUP_SETTINGS_FILE);
+    let content =
+        serde_json::to_string_pretty(settings).map_err(|e| format!("序列化备份设置失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存备份设置失败: {}", e))
+}
+
+fn load_index() -> Result<Vec<SnapshotMeta>, String> {
+    let path = get_backup_dir()?.join(SNAPSHOT_INDEX_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取快照索引失败: {}", e))?;
+    match serde_json::from_str(&content) {
+        Ok(index) => Ok(index),
+        Err(e) => atomic_file::recover_json(&path).ok_or_else(|| {
+            crate::error::file_corrupted_error(
+                SNAPSHOT_INDEX_FILE,
+                &path.to_string_lossy(),
+                &e.to_string(),
+            )
+        }),
+    }
+}
+
+fn save_index(index: &[SnapshotMeta]) -> Result<(), String> {
+    let path = get_backup_dir()?.join(SNAPSHOT_INDEX_FILE);
+    let content =
+        serde_json::to_string_pretty(index).map_err(|e| format!("序列化快照索引失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存快照索引失败: {}", e))
+}
+
+/// 列出快照（最新的在前），忽略文件已丢失的条目
+pub fn list_snapshots() -> Result<Vec<SnapshotMeta>, String> {
+    let mut index = load_index()?;
+    index.retain(|meta| {
+        snapshot_path(&meta.id)
+            .map(|path| path.exists())
+            .unwrap_or(false)
+    });
+    index.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    Ok(index)
+}
+
+// ============================================================================
+// 采集数据目录
+// ============================================================================
+
+/// 锁文件、`.bak` 与临时文件；数据库在顶层单独导出
+fn is_excluded_file(name: &str, top_level: bool) -> bool {
+    name.ends_with(".lock")
+        || name.ends_with(".bak")
+        || name.contains(".tmp.")
+        || (top_level && name.starts_with(DB_ENTRY))
+}
+
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    files: &mut Vec<(String, PathBuf)>,
+) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("读取目录失败: {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let rel = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if prefix.is_empty() && EXCLUDED_DIRS.contains(&name.as_str()) {
+                continue;
+            }
+            collect_files(&entry.path(), &rel, files)?;
+        } else if file_type.is_file() && !is_excluded_file(&name, prefix.is_empty()) {
+            files.push((rel, entry.path()));
+        }
+    }
+    Ok(())
+}
+
+/// 需要纳入快照的文件：相对路径与源文件
+struct CapturedSources {
+    files: Vec<(String, PathBuf)>,
+    /// 数据库的一致副本，随本结构一起删除
+    _db_copy: Option<TempPath>,
+}
+
+/// 列出需要纳入快照的文件，数据库使用导出的一致副本
+fn capture_sources() -> Result<CapturedSources, String> {
+    let data_dir = account::get_data_dir()?;
+    let mut sources = Vec::new();
+    collect_files(&data_dir, "", &mut sources)?;
+
+    let db_copy =
+        TempPath(get_backup_dir()?.join(format!(".{}.snapshot.{}", DB_ENTRY, temp_suffix())));
+    let db_copy = if account_store::backup_to(&db_copy.0)? {
+        sources.push((DB_ENTRY.to_string(), db_copy.0.clone()));
+        Some(db_copy)
+    } else {
+        None
+    };
+
+    sources.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(CapturedSources {
+        files: sources,
+        _db_copy: db_copy,
+    })
+}
+
+/// 当前数据目录中各文件的 SHA-256
+fn current_hashes() -> Result<BTreeMap<String, String>, String> {
+    let sources = capture_sources()?;
+    let mut hashes = BTreeMap::new();
+    for (rel, path) in &sources.files {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            // 采集过程中被删除的文件直接忽略
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("读取文件失败: {}: {}", rel, e)),
+        };
+        let mut reader = HashingReader::new(file);
+        io::copy(&mut reader, &mut io::sink())
+            .map_err(|e| format!("读取文件失败: {}: {}", rel, e))?;
+        hashes.insert(rel.clone(), reader.finish());
+    }
+    Ok(hashes)
+}
+
+// ============================================================================
+// 快照读写
+// ============================================================================
+
+fn new_snapshot_id(reason: &str) -> String {
+    let reason: String = reason
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "{}-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S%3f"),
+        reason
+    )
+}
+
+fn append_entry<W: Write>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    size: u64,
+    data: impl Read,
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(size);
+    header.set_mode(0o600);
+    header.set_mtime(now_ts().max(0) as u64);
+    builder.append_data(&mut header, name, data)
+}
+
+/// 将源文件逐个流式写入 `path`（先写临时文件再替换），返回清单
+fn write_archive(
+    path: &Path,
+    id: &str,
+    reason: &str,
+    created_at: i64,
+    sources: &[(String, PathBuf)],
+) -> Result<SnapshotManifest, String> {
+    let write_err = |e: io::Error| format!("写入快照文件失败: {}", e);
+    let tmp = TempPath(path.with_file_name(format!(".{}{}.{}", id, SNAPSHOT_EXT, temp_suffix())));
+    let file = File::create(&tmp.0).map_err(write_err)?;
+    let encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    let mut files = Vec::with_capacity(sources.len());
+    for (rel, source) in sources {
+        let file = match File::open(source) {
+            Ok(file) => file,
+            // 采集过程中被删除的文件直接忽略
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("读取文件失败: {}: {}", rel, e)),
+        };
+        let size = file
+            .metadata()
+            .map_err(|e| format!("读取文件失败: {}: {}", rel, e))?
+            .len();
+        let mut reader = HashingReader::new(file.take(size));
+        append_entry(
+            &mut builder,
+            &format!("{}{}", DATA_PREFIX, rel),
+            size,
+            &mut reader,
+        )
+        .map_err(|e| format!("写入快照文件失败: {}: {}", rel, e))?;
+        // 头部已写入声明的大小，读到的内容变短时归档会错位
+        if reader.len != size {
+            return Err(format!("文件在快照过程中被修改: {}", rel));
+        }
+        files.push(ManifestEntry {
+            path: rel.clone(),
+            size,
+            sha256: reader.finish(),
+        });
+    }
+
+    let manifest = SnapshotManifest {
+        version: ARCHIVE_VERSION,
+        id: id.to_string(),
+        reason: reason.to_string(),
+        created_at,
+        files,
+    };
+    let json = serde_json::to_vec(&manifest).map_err(|e| format!("序列化快照清单失败: {}", e))?;
+    append_entry(
+        &mut builder,
+        MANIFEST_ENTRY,
+        json.len() as u64,
+        json.as_slice(),
+    )
+    .map_err(write_err)?;
+
+    let writer = builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(write_err)?;
+    let file = writer.into_inner().map_err(|e| write_err(e.into_error()))?;
+    file.sync_all().map_err(write_err)?;
+    drop(file);
+    fs::rename(&tmp.0, path).map_err(write_err)?;
+    Ok(manifest)
+}
+
+fn open_archive(path: &Path) -> Result<tar::Archive<GzDecoder<File>>, String> {
+    let file = File::open(path).map_err(|e| format!("打开快照失败: {}", e))?;
+    Ok(tar::Archive::new(GzDecoder::new(file)))
+}
+
+fn entry_name<R: Read>(entry: &tar::Entry<'_, R>) -> String {
+    String::from_utf8_lossy(&entry.path_bytes()).to_string()
+}
+
+fn parse_manifest(reader: impl Read) -> Result<SnapshotManifest, String> {
+    let manifest: SnapshotManifest =
+        serde_json::from_reader(reader).map_err(|e| format!("解析快照清单失败: {}", e))?;
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(format!("快照版本过新，无法读取: v{}", manifest.version));
+    }
+    Ok(manifest)
+}
+
+fn existing_snapshot_path(id: &str) -> Result<PathBuf, String> {
+    let path = snapshot_path(id)?;
+    if !path.exists() {
+        return Err(format!("快照不存在: {}", id));
+    }
+    Ok(path)
+}
+
+fn read_manifest(path: &Path) -> Result<SnapshotManifest, String> {
+    let mut archive = open_archive(path)?;
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("解压快照失败: {}", e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("解压快照失败: {}", e))?;
+        if entry_name(&entry) == MANIFEST_ENTRY {
+            return parse_manifest(entry);
+        }
+    }
+    Err("快照缺少清单，文件可能已损坏".to_string())
+}
+
+/// 将归档解包到 `staging` 并按清单校验每个文件，返回清单
+///
+/// 校验全部通过前不会改动数据目录，避免恢复到一半才发现快照损坏。
+fn stage_archive(path: &Path, staging: &Path) -> Result<SnapshotManifest, String> {
+    let mut archive = open_archive(path)?;
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("解压快照失败: {}", e))?;
+    let mut staged = HashMap::new();
+    let mut manifest = None;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("解压快照失败: {}", e))?;
+        let name = entry_name(&entry);
+        if name == MANIFEST_ENTRY {
+            manifest = Some(parse_manifest(&mut entry)?);
+            continue;
+        }
+        let Some(rel) = name.strip_prefix(DATA_PREFIX) else {
+            continue;
+        };
+        let target = resolve_entry_path(staging, rel)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建恢复目录失败: {}", e))?;
+        }
+        let mut out = File::create(&target).map_err(|e| format!("解压快照失败: {}: {}", rel, e))?;
+        let mut reader = HashingReader::new(&mut entry);
+        io::copy(&mut reader, &mut out)
+            .and_then(|_| out.sync_all())
+            .map_err(|e| format!("解压快照失败: {}: {}", rel, e))?;
+        staged.insert(rel.to_string(), reader.finish());
+    }
+
+    let manifest = manifest.ok_or("快照缺少清单，文件可能已损坏")?;
+    for file in &manifest.files {
+        if staged.get(&file.path) != Some(&file.sha256) {
+            return Err(format!("快照文件校验失败: {}", file.path));
+        }
+    }
+    Ok(manifest)
+}
+
+/// 删除超出保留策略的旧快照；`keep` 中的快照不会被删除
+fn apply_retention(
+    index: &mut Vec<SnapshotMeta>,
+    settings: &BackupSettings,
+    keep: &[&str],
+) -> usize {
+    index.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    let cutoff = if settings.retention_days > 0 {
+        now_ts() - (settings.retention_days as i64) * 86_400
+    } else {
+        i64::MIN
+    };
+
+    let mut kept = Vec::with_capacity(index.len());
+    let mut removed = 0;
+    for (position, meta) in index.drain(..).enumerate() {
+        let over_count = settings.retention_count > 0 && position >= settings.retention_count;
+        // 至少保留最新的一个快照
+        let expired = position > 0 && (over_count || meta.created_at < cutoff);
+        if !expired || keep.contains(&meta.id.as_str()) {
+            kept.push(meta);
+            continue;
+        }
+        if let Ok(path) = snapshot_path(&meta.id) {
+            if let Err(e) = fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    logger::log_warn(&format!(
+                        "[Backup] 删除过期快照失败: id={}, error={}",
+                        meta.id, e
+                    ));
+                    kept.push(meta);
+                    continue;
+                }
+            }
+        }
+        removed += 1;
+    }
+    *index = kept;
+    removed
+}
+
+fn create_snapshot_locked(reason: &str, keep: &[&str]) -> Result<SnapshotMeta, String> {
+    let sources = capture_sources()?;
+    let id = new_snapshot_id(reason);
+    let created_at = now_ts();
+    let path = snapshot_path(&id)?;
+    let manifest = write_archive(&path, &id, reason, created_at, &sources.files)?;
+    let compressed_size = fs::metadata(&path)
+        .map(|meta| meta.len())
+        .map_err(|e| format!("读取快照文件失败: {}", e))?;
+
+    let meta = SnapshotMeta {
+        id: id.clone(),
+        reason: reason.to_string(),
+        created_at,
+        file_count: manifest.files.len(),
+        total_size: manifest.files.iter().map(|f| f.size).sum(),
+        compressed_size,
+    };
+
+    let mut index = load_index().unwrap_or_else(|e| {
+        logger::log_warn(&format!("[Backup] 快照索引不可用，将重建: {}", e));
+        Vec::new()
+    });
+    index.retain(|m| m.id != id);
+    index.push(meta.clone());
+    let mut protected = keep.to_vec();
+    protected.push(id.as_str());
+    let removed = apply_retention(&mut index, &load_settings(), &protected);
+    save_index(&index)?;
+
+    logger::log_info(&format!(
+        "[Backup] 已创建快照: id={}, files={}, size={}, compressed={}, pruned={}",
+        meta.id, meta.file_count, meta.total_size, meta.compressed_size, removed
+    ));
+    Ok(meta)
+}
+
+/// 创建快照
+pub fn create_snapshot(reason: &str) -> Result<SnapshotMeta, String> {
+    let _guard = BACKUP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    create_snapshot_locked(reason, &[])
+}
+
+/// 高风险操作前调用：自动快照已启用时创建快照，失败只记录日志，不阻断操作
+pub fn snapshot_before(operation: &str) {
+    if !load_settings().enabled {
+        return;
+    }
+    if let Err(e) = create_snapshot(&format!("before_{}", operation)) {
+        logger::log_error(&format!(
+            "[Backup] 操作前快照失败: operation={}, error={}",
+            operation, e
+        ));
+    }
+}
+
+// ============================================================================
+// 对比与恢复
+// ============================================================================
+
+fn diff_hashes(
+    from: &BTreeMap<String, String>,
+    to: &BTreeMap<String, String>,
+) -> (Vec<String>, Vec<String>, Vec<String>, usize) {
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut modified = Vec::new();
+    let mut unchanged = 0;
+    for (path, hash) in from {
+        match to.get(path) {
+            None => removed.push(path.clone()),
+            Some(other) if other != hash => modified.push(path.clone()),
+            Some(_) => unchanged += 1,
+        }
+    }
+    for path in to.keys() {
+        if !from.contains_key(path) {
+            added.push(path.clone());
+        }
+    }
+    (added, removed, modified, unchanged)
+}
+
+fn snapshot_hashes(id: &str) -> Result<BTreeMap<String, String>, String> {
+    let manifest = read_manifest(&existing_snapshot_path(id)?)?;
+    Ok(manifest
+        .files
+        .into_iter()
+        .map(|f| (f.path, f.sha256))
+        .collect())
+}
+
+/// 对比快照与当前数据目录（`compare_to` 为 None）或另一个快照
+pub fn diff_snapshot(id: &str, compare_to: Option<&str>) -> Result<SnapshotDiff, String> {
+    let from = snapshot_hashes(id)?;
+    let (to_label, to) = match compare_to {
+        Some(other) => (other.to_string(), snapshot_hashes(other)?),
+        None => ("current".to_string(), current_hashes()?),
+    };
+    let (added, removed, modified, unchanged) = diff_hashes(&from, &to);
+    Ok(SnapshotDiff {
+        from: id.to_string(),
+        to: to_label,
+        added,
+        removed,
+        modified,
+        unchanged,
+    })
+}
+
+/// 快照中的相对路径只允许普通路径段，防止写出数据目录
+fn resolve_entry_path(data_dir: &Path, rel: &str) -> Result<PathBuf, String> {
+    let rel_path = Path::new(rel);
+    if rel.is_empty()
+        || !rel_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(format!("快照包含非法路径: {}", rel));
+    }
+    Ok(data_dir.join(rel_path))
+}
+
+/// 旧的 WAL/SHM 属于被替换的数据库，必须先删除，否则会被回放到恢复后的文件上
+fn remove_db_side_files(db_path: &Path) -> Result<(), String> {
+    for suffix in ["-wal", "-shm"] {
+        let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+        name.push(suffix);
+        let side_file = db_path.with_file_name(name);
+        if side_file.exists() {
+            fs::remove_file(&side_file).map_err(|e| format!("删除数据库日志文件失败: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+fn restore_database(staged: &Path) -> Result<(), String> {
+    let db_path = account_store::get_db_path()?;
+    remove_db_side_files(&db_path)?;
+    fs::rename(staged, &db_path).map_err(|e| format!("恢复数据库失败: {}", e))
+}
+
+/// 将数据目录恢复到指定快照的状态
+///
+/// 恢复前会先为当前状态创建快照；快照中不存在的文件会被删除（日志、缓存、多开实例与备份目录除外）。
+pub fn restore_snapshot(id: &str) -> Result<RestoreReport, String> {
+    let _guard = BACKUP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let archive_path = existing_snapshot_path(id)?;
+    let data_dir = account::get_data_dir()?;
+
+    // 暂存目录与数据目录位于同一文件系统，校验通过后逐个 rename 到位
+    let staging = TempPath(get_backup_dir()?.join(format!(".restore.{}", temp_suffix())));
+    let manifest = stage_archive(&archive_path, &staging.0)?;
+    let mut entries = Vec::with_capacity(manifest.files.len());
+    for file in &manifest.files {
+        let target = if file.path == DB_ENTRY {
+            None
+        } else {
+            Some(resolve_entry_path(&data_dir, &file.path)?)
+        };
+        let staged = resolve_entry_path(&staging.0, &file.path)?;
+        entries.push((file.path.as_str(), target, staged));
+    }
+
+    let safety = create_snapshot_locked(REASON_BEFORE_RESTORE, &[id])?;
+    let _index_lock = account::lock_account_index("restore_snapshot")?;
+
+    let mut current = Vec::new();
+    collect_files(&data_dir, "", &mut current)?;
+    let vault_before = fs::read(data_dir.join(VAULT_META_ENTRY)).ok();
+
+    let mut restored_paths = HashSet::new();
+    let mut db_restored = false;
+    for (rel, target, staged) in &entries {
+        match target {
+            Some(target) => {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("恢复文件失败: {}: {}", rel, e))?;
+                }
+                fs::rename(staged, target).map_err(|e| format!("恢复文件失败: {}: {}", rel, e))?;
+            }
+            None => {
+                restore_database(staged)?;
+                db_restored = true;
+            }
+        }
+        restored_paths.insert(rel.to_string());
+    }
+
+    let mut removed = 0;
+    for (rel, path) in current {
+        if restored_paths.contains(&rel) {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => logger::log_warn(&format!(
+                "[Backup] 删除快照中不存在的文件失败: path={}, error={}",
+                rel, e
+            )),
+        }
+    }
+    if !db_restored {
+        let db_path = account_store::get_db_path()?;
+        if db_path.exists() {
+            // 快照时尚未使用统一存储：移除数据库，回到 JSON 文件存储
+            remove_db_side_files(&db_path)?;
+            fs::remove_file(&db_path).map_err(|e| format!("删除数据库失败: {}", e))?;
+            removed += 1;
+        }
+    }
+
+    account_store::reset_cached_state();
+    if fs::read(data_dir.join(VAULT_META_ENTRY)).ok() != vault_before {
+        credential_vault::reload();
+    }
+
+    logger::log_info(&format!(
+        "[Backup] 已恢复快照: id={}, restored={}, removed={}, safety_snapshot={}",
+        id,
+        entries.len(),
+        removed,
+        safety.id
+    ));
+    Ok(RestoreReport {
+        snapshot_id: id.to_string(),
+        safety_snapshot_id: safety.id,
+        restored: entries.len(),
+        removed,
+    })
+}
+
+// ============================================================================
+// 定时快照
+// ============================================================================
+
+fn run_scheduled_if_due() {
+    let settings = load_settings();
+    if !settings.enabled || settings.interval_hours == 0 {
+        return;
+    }
+    let last_scheduled = list_snapshots()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|meta| meta.reason == REASON_SCHEDULED)
+        .map(|meta| meta.created_at)
+        .max()
+        .unwrap_or(0);
+    if now_ts() - last_scheduled < (settings.interval_hours as i64) * 3600 {
+        return;
+    }
+    if let Err(e) = create_snapshot(REASON_SCHEDULED) {
+        logger::log_error(&format!("[Backup] 定时快照失败: {}", e));
+    }
+}
+
+/// 启动定时快照任务（应用启动时调用一次）
+pub fn start_scheduler() {
+    tauri::async_runtime::spawn(async {
+        loop {
+            if let Err(e) = tokio::task::spawn_blocking(run_scheduled_if_due).await {
+                logger::log_error(&format!("[Backup] 定时快照任务异常: {}", e));
+            }
+            tokio::time::sleep(SCHEDULER_TICK).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, hash)| (path.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn diff_hashes_classifies_changes() {
+        let from = hashes(&[
+            ("accounts.json", "a"),
+            ("accounts/1.json", "b"),
+            ("old.json", "c"),
+        ]);
+        let to = hashes(&[
+            ("accounts.json", "a"),
+            ("accounts/1.json", "x"),
+            ("new.json", "d"),
+        ]);
+        let (added, removed, modified, unchanged) = diff_hashes(&from, &to);
+        assert_eq!(added, vec!["new.json"]);
+        assert_eq!(removed, vec!["old.json"]);
+        assert_eq!(modified, vec!["accounts/1.json"]);
+        assert_eq!(unchanged, 1);
+    }
+
+    #[test]
+    fn resolve_entry_path_rejects_escapes() {
+        let root = Path::new("/data");
+        assert!(resolve_entry_path(root, "accounts/1.json").is_ok());
+        assert!(resolve_entry_path(root, "../etc/passwd").is_err());
+        assert!(resolve_entry_path(root, "/etc/passwd").is_err());
+        assert!(resolve_entry_path(root, "").is_err());
+    }
+
+    #[test]
+    fn excluded_files_are_skipped() {
+        assert!(is_excluded_file("cockpit.db", true));
+        assert!(is_excluded_file("cockpit.db-wal", true));
+        assert!(!is_excluded_file("cockpit.db", false));
+        assert!(is_excluded_file("accounts.json.lock", true));
+        assert!(is_excluded_file("1.json.bak", false));
+        assert!(is_excluded_file(".accounts.json.tmp.1.2", true));
+        assert!(!is_excluded_file("accounts.json", true));
+    }
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("cockpit-{}-{}", name, temp_suffix()));
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn collect_files_skips_excluded_dirs() {
+        let root = temp_root("backup-collect");
+        for rel in [
+            "accounts.json",
+            "accounts/1.json",
+            "instances/codex/profile/state.db",
+            "logs/app.log",
+            "backups/old.tar.gz",
+        ] {
+            let path = root.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, rel).unwrap();
+        }
+
+        let mut files = Vec::new();
+        collect_files(&root, "", &mut files).unwrap();
+        let mut paths: Vec<String> = files.into_iter().map(|(rel, _)| rel).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["accounts.json", "accounts/1.json"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn archive_round_trips_and_detects_tampering() {
+        let root = temp_root("backup-archive");
+        let source = root.join("accounts.json");
+        fs::write(&source, b"{\"accounts\":[]}").unwrap();
+        let nested = root.join("nested.json");
+        fs::write(&nested, vec![7u8; 256 * 1024]).unwrap();
+        let sources = vec![
+            ("accounts.json".to_string(), source),
+            ("accounts/1.json".to_string(), nested),
+        ];
+
+        let archive = root.join("snap.tar.gz");
+        let manifest = write_archive(&archive, "snap", REASON_MANUAL, 1, &sources).unwrap();
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(read_manifest(&archive).unwrap().files.len(), 2);
+
+        let staging = root.join("staging");
+        stage_archive(&archive, &staging).unwrap();
+        assert_eq!(
+            fs::read(staging.join("accounts.json")).unwrap(),
+            b"{\"accounts\":[]}"
+        );
+        assert_eq!(
+            fs::read(staging.join("accounts/1.json")).unwrap().len(),
+            256 * 1024
+        );
+
+        let mut tampered = manifest;
+        tampered.files[0].sha256 = "0".repeat(64);
+        let forged = root.join("forged.tar.gz");
+        let file = File::create(&forged).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let data = fs::read(staging.join("accounts.json")).unwrap();
+        let name = format!("{}accounts.json", DATA_PREFIX);
+        append_entry(&mut builder, &name, data.len() as u64, data.as_slice()).unwrap();
+        let json = serde_json::to_vec(&tampered).unwrap();
+        append_entry(
+            &mut builder,
+            MANIFEST_ENTRY,
+            json.len() as u64,
+            json.as_slice(),
+        )
+        .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        assert!(stage_archive(&forged, &root.join("staging-forged")).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -2,16 +2,48 @@ use crate::models::codex::{
     CodexAccount, CodexAccountIndex, CodexAccountSummary, CodexAuthFile, CodexAuthTokens,
     CodexJwtPayload, CodexTokens,
 };
-use crate::modules::{codex_oauth, logger};
+use crate::modules::refresh_executor::RefreshReport;
+use crate::modules::account_store::{self, StoreSpec};
+use crate::modules::platform::{self, Platform, PlatformAccount, QuotaMetric, UnifiedQuota};
+use crate::modules::unified_quota::QuotaWindow;
+use crate::modules::schema_migration::{self, SchemaSpec, VersionEncoding};
+use crate::modules::{
+    codex_instance, codex_oauth, codex_quota, config, credential_vault, logger, opencode_auth,
+    process, store_fsck,
+};
+use crate::utils::atomic_file;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
-use std::collections::HashMap;
+use futures::future::BoxFuture;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
 
-static CODEX_QUOTA_ALERT_LAST_SENT: std::sync::LazyLock<Mutex<HashMap<String, i64>>> =
-    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
-const CODEX_QUOTA_ALERT_COOLDOWN_SECONDS: i64 = 300;
+
+/// codex_accounts.json 的格式版本
+const INDEX_SCHEMA: SchemaSpec = SchemaSpec {
+    name: "codex_accounts.json",
+    version_field: "version",
+    encoding: VersionEncoding::Dotted,
+    current: 1,
+    baseline: 1,
+    migrations: &[],
+};
+
+/// 账号文件中需要由凭据保险库加密的字段
+const SECRET_FIELDS: &[&str] = &[
+    "/tokens/id_token",
+    "/tokens/access_token",
+    "/tokens/refresh_token",
+];
+
+pub const STORE_SPEC: StoreSpec = StoreSpec {
+    platform: "codex",
+    base_dir: get_storage_root,
+    index_file: "codex_accounts.json",
+    accounts_dir: "codex_accounts",
+    secret_fields: SECRET_FIELDS,
+    quota_fields: &["quota", "quota_error"],
+    mirror_index: false,
+};
 
 /// 获取 Codex 数据目录
 pub fn get_codex_home() -> PathBuf {
@@ -23,6 +55,16 @@ pub fn get_auth_json_path() -> PathBuf {
     get_codex_home().join("auth.json")
 }
 
+/// 获取多账号存储根目录（索引文件与账号目录所在位置）
+fn get_storage_root() -> Result<PathBuf, String> {
+    let data_dir = dirs::data_local_dir()
+        .or_else(dirs::home_dir)
+        .ok_or("无法获取用户目录")?
+        .join("com.antigravity.cockpit-tools");
+    fs::create_dir_all(&data_dir).map_err(|e| format!("创建 Codex 数据目录失败: {}", e))?;
+    Ok(data_dir)
+}
+
 /// 获取我们的多账号存储路径
 fn get_accounts_storage_path() -> PathBuf {
     let data_dir = dirs::data_local_dir()
@@ -33,7 +75,7 @@ fn get_accounts_storage_path() -> PathBuf {
 }
 
 /// 获取账号详情存储目录
-fn get_accounts_dir() -> PathBuf {
+pub fn get_accounts_dir() -> PathBuf {
     let data_dir = dirs::data_local_dir()
         .unwrap_or_else(|| dirs::home_dir().expect("无法获取用户目录"))
         .join("com.antigravity.cockpit-tools")
@@ -216,49 +258,116 @@ pub fn extract_user_info(
 
 /// 读取账号索引
 pub fn load_account_index() -> CodexAccountIndex {
+    if account_store::is_active() {
+        return account_store::load_index(&STORE_SPEC)
+            .unwrap_or_else(|e| {
+                logger::log_error(&format!("读取 Codex 账号索引失败: {}", e));
+                None
+            })
+            .unwrap_or_default();
+    }
+
     let path = get_accounts_storage_path();
     if !path.exists() {
         return CodexAccountIndex::new();
     }
 
     match fs::read_to_string(&path) {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|_| CodexAccountIndex::new()),
+        Ok(content) => {
+            let content = schema_migration::upgrade_on_load(&INDEX_SCHEMA, &path, content);
+            serde_json::from_str(&content).unwrap_or_else(|_| CodexAccountIndex::new())
+        }
         Err(_) => CodexAccountIndex::new(),
     }
 }
 
 /// 保存账号索引
 pub fn save_account_index(index: &CodexAccountIndex) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_index(&STORE_SPEC, index);
+    }
+
     let path = get_accounts_storage_path();
+    schema_migration::ensure_writable(&INDEX_SCHEMA, &path)?;
     let content = serde_json::to_string_pretty(index).map_err(|e| format!("序列化失败: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
     Ok(())
 }
 
 /// 读取单个账号详情
 pub fn load_account(account_id: &str) -> Option<CodexAccount> {
+    if account_store::is_active() {
+        return account_store::load_record(&STORE_SPEC, account_id)
+            .ok()
+            .flatten();
+    }
+
     let path = get_accounts_dir().join(format!("{}.json", account_id));
     if !path.exists() {
         return None;
     }
 
     match fs::read_to_string(&path) {
-        Ok(content) => serde_json::from_str(&content).ok(),
+        Ok(content) => credential_vault::from_sealed_json(&content, SECRET_FIELDS).ok(),
         Err(_) => None,
     }
 }
 
 /// 保存单个账号详情
 pub fn save_account(account: &CodexAccount) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_record(&STORE_SPEC, &account.id, account);
+    }
+
     let path = get_accounts_dir().join(format!("{}.json", &account.id));
-    let content =
-        serde_json::to_string_pretty(account).map_err(|e| format!("序列化失败: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+    let content = credential_vault::to_sealed_json(account, SECRET_FIELDS)?;
+    atomic_file::write_secret(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
     Ok(())
 }
 
+/// 以账号详情为准重建索引（一致性修复使用），返回索引中的账号数
+pub fn rebuild_account_index() -> Result<usize, String> {
+    let previous = store_fsck::read_index(&STORE_SPEC).unwrap_or_default();
+    let accounts: Vec<CodexAccount> = store_fsck::list_stored_ids(&STORE_SPEC)?
+        .iter()
+        .filter_map(|id| load_account(id))
+        .collect();
+    let accounts =
+        store_fsck::order_by_index(accounts, &previous.account_ids, |a| &a.id, |a| a.created_at);
+
+    let mut index = CodexAccountIndex::new();
+    index.accounts = accounts
+        .iter()
+        .map(|account| CodexAccountSummary {
+            id: account.id.clone(),
+            email: account.email.clone(),
+            plan_type: account.plan_type.clone(),
+            created_at: account.created_at,
+            last_used: account.last_used,
+        })
+        .collect();
+    let ids: Vec<String> = index.accounts.iter().map(|s| s.id.clone()).collect();
+    index.current_account_id =
+        store_fsck::repair_current_account_id(previous.current_account_id, &ids);
+    save_account_index(&index)?;
+    Ok(ids.len())
+}
+
+/// 按凭据保险库当前状态重新写回所有账号详情
+pub fn reseal_account_files() -> Result<usize, String> {
+    let accounts = list_accounts();
+    for account in &accounts {
+        save_account(account)?;
+    }
+    Ok(accounts.len())
+}
+
 /// 删除单个账号
 pub fn delete_account_file(account_id: &str) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::delete_record(&STORE_SPEC, account_id);
+    }
+
     let path = get_accounts_dir().join(format!("{}.json", account_id));
     if path.exists() {
         fs::remove_file(&path).map_err(|e| format!("删除文件失败: {}", e))?;
@@ -344,9 +453,6 @@ fn upsert_account_with_hints(
         acc
     };
 
-    // 保存账号详情
-    save_account(&account)?;
-
     // 更新索引中的摘要信息
     if let Some(summary) = index.accounts.iter_mut().find(|a| a.id == account.id) {
         summary.email = account.email.clone();
@@ -362,7 +468,11 @@ fn upsert_account_with_hints(
         });
     }
 
-    save_account_index(&index)?;
+    // 账号详情与索引在同一事务中保存
+    account_store::write_batch(|| {
+        save_account(&account)?;
+        save_account_index(&index)
+    })?;
 
     logger::log_info(&format!(
         "Codex 账号已保存: email={}, account_id={:?}, organization_id={:?}",
@@ -384,10 +494,10 @@ pub fn remove_account(account_id: &str) -> Result<(), String> {
         index.current_account_id = None;
     }
 
-    save_account_index(&index)?;
-    delete_account_file(account_id)?;
-
-    Ok(())
+    account_store::write_batch(|| {
+        save_account_index(&index)?;
+        delete_account_file(account_id)
+    })
 }
 
 /// 批量删除账号
@@ -481,12 +591,13 @@ pub fn write_auth_file_to_dir(base_dir: &Path, account: &CodexAccount) -> Result
     let auth_file = build_auth_file(account);
     let content =
         serde_json::to_string_pretty(&auth_file).map_err(|e| format!("序列化失败: {}", e))?;
-    fs::write(&auth_path, content).map_err(|e| format!("写入 auth.json 失败: {}", e))?;
+    atomic_file::replace(&auth_path, content).map_err(|e| format!("写入 auth.json 失败: {}", e))?;
     Ok(())
 }
 
 /// 准备账号注入：如有必要刷新 Token 并写回存储
 pub async fn prepare_account_for_injection(account_id: &str) -> Result<CodexAccount, String> {
+    credential_vault::ensure_unlocked()?;
     let mut account =
         load_account(account_id).ok_or_else(|| format!("账号不存在: {}", account_id))?;
     if codex_oauth::is_token_expired(&account.tokens.access_token) {
@@ -515,15 +626,15 @@ pub fn switch_account(account_id: &str) -> Result<CodexAccount, String> {
     let account = load_account(account_id).ok_or_else(|| format!("账号不存在: {}", account_id))?;
     write_auth_file_to_dir(&get_codex_home(), &account)?;
 
-    // 更新索引中的 current_account_id
+    // 更新索引中的 current_account_id 与账号的 last_used
     let mut index = load_account_index();
     index.current_account_id = Some(account_id.to_string());
-    save_account_index(&index)?;
-
-    // 更新账号的 last_used
     let mut updated_account = account.clone();
     updated_account.update_last_used();
-    save_account(&updated_account)?;
+    account_store::write_batch(|| {
+        save_account_index(&index)?;
+        save_account(&updated_account)
+    })?;
 
     logger::log_info(&format!("已切换到 Codex 账号: {}", account.email));
 
@@ -583,6 +694,7 @@ pub fn import_from_json(json_content: &str) -> Result<Vec<CodexAccount>, String>
 
 /// 导出账号为 JSON
 pub fn export_accounts(account_ids: &[String]) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
     let accounts: Vec<CodexAccount> = account_ids
         .iter()
         .filter_map(|id| load_account(id))
@@ -601,10 +713,6 @@ pub fn update_account_tags(account_id: &str, tags: Vec<String>) -> Result<CodexA
     Ok(account)
 }
 
-fn normalize_quota_alert_threshold(raw: i32) -> i32 {
-    raw.clamp(0, 100)
-}
-
 fn format_codex_quota_metric_label(window_minutes: Option<i64>, fallback: &str) -> String {
     const HOUR_MINUTES: i64 = 60;
     const DAY_MINUTES: i64 = 24 * HOUR_MINUTES;
@@ -636,9 +744,33 @@ fn format_codex_quota_metric_label(window_minutes: Option<i64>, fallback: &str)
     format!("{}m", minutes)
 }
 
-fn extract_quota_metrics(account: &CodexAccount) -> Vec<(String, i32)> {
+/// 统一配额视图：5 小时窗口（hourly）与每周窗口（weekly），按接口返回的窗口存在标记取舍
+pub fn unified_quota(account: &CodexAccount) -> UnifiedQuota {
     let Some(quota) = account.quota.as_ref() else {
-        return Vec::new();
+        return UnifiedQuota::default();
+    };
+
+    let hourly = || {
+        QuotaMetric::from_percent(
+            "hourly",
+            format_codex_quota_metric_label(quota.hourly_window_minutes, "5h"),
+            quota.hourly_percentage,
+        )
+        .with_window(Some(QuotaWindow::Rolling {
+            minutes: quota.hourly_window_minutes.unwrap_or(5 * 60),
+        }))
+        .with_resets_at(quota.hourly_reset_time)
+    };
+    let weekly = || {
+        QuotaMetric::from_percent(
+            "weekly",
+            format_codex_quota_metric_label(quota.weekly_window_minutes, "Weekly"),
+            quota.weekly_percentage,
+        )
+        .with_window(Some(QuotaWindow::Rolling {
+            minutes: quota.weekly_window_minutes.unwrap_or(7 * 24 * 60),
+        }))
+        .with_resets_at(quota.weekly_reset_time)
     };
 
     let has_presence =
@@ -646,159 +778,198 @@ fn extract_quota_metrics(account: &CodexAccount) -> Vec<(String, i32)> {
     let mut metrics = Vec::new();
 
     if !has_presence || quota.hourly_window_present.unwrap_or(false) {
-        metrics.push((
-            format_codex_quota_metric_label(quota.hourly_window_minutes, "5h"),
-            quota.hourly_percentage.clamp(0, 100),
-        ));
+        metrics.push(hourly());
     }
 
     if !has_presence || quota.weekly_window_present.unwrap_or(false) {
-        metrics.push((
-            format_codex_quota_metric_label(quota.weekly_window_minutes, "Weekly"),
-            quota.weekly_percentage.clamp(0, 100),
-        ));
+        metrics.push(weekly());
     }
 
     if metrics.is_empty() {
-        metrics.push((
-            format_codex_quota_metric_label(quota.hourly_window_minutes, "5h"),
-            quota.hourly_percentage.clamp(0, 100),
-        ));
+        metrics.push(hourly());
     }
 
-    metrics
+    UnifiedQuota::new(metrics)
 }
 
-fn average_quota_percentage(metrics: &[(String, i32)]) -> f64 {
-    if metrics.is_empty() {
-        return 0.0;
+fn resolve_current_account_id(accounts: &[PlatformAccount]) -> Option<String> {
+    if let Some(account) = get_current_account() {
+        return Some(account.id);
     }
-    let sum: i32 = metrics.iter().map(|(_, pct)| *pct).sum();
-    sum as f64 / metrics.len() as f64
-}
-
-fn build_quota_alert_cooldown_key(account_id: &str, threshold: i32) -> String {
-    format!("codex:{}:{}", account_id, threshold)
-}
-
-fn should_emit_quota_alert(cooldown_key: &str, now: i64) -> bool {
-    let Ok(mut state) = CODEX_QUOTA_ALERT_LAST_SENT.lock() else {
-        return true;
-    };
 
-    if let Some(last_sent) = state.get(cooldown_key) {
-        if now - *last_sent < CODEX_QUOTA_ALERT_COOLDOWN_SECONDS {
-            return false;
+    if let Ok(settings) = crate::modules::codex_instance::load_default_settings() {
+        if let Some(bind_id) = settings.bind_account_id {
+            let trimmed = bind_id.trim();
+            if !trimmed.is_empty() {
+                return Some(trimmed.to_string());
+            }
         }
     }
 
-    state.insert(cooldown_key.to_string(), now);
-    true
+    platform::most_recently_used(accounts)
 }
 
-fn clear_quota_alert_cooldown(account_id: &str, threshold: i32) {
-    if let Ok(mut state) = CODEX_QUOTA_ALERT_LAST_SENT.lock() {
-        state.remove(&build_quota_alert_cooldown_key(account_id, threshold));
+pub fn run_quota_alert_if_needed(
+) -> Result<Vec<crate::modules::account::QuotaAlertPayload>, String> {
+    crate::modules::alert_rules::run(&CodexPlatform)
+}
+
+fn to_platform_account(account: &CodexAccount) -> PlatformAccount {
+    PlatformAccount {
+        platform: CodexPlatform.id().to_string(),
+        id: account.id.clone(),
+        email: account.email.clone(),
+        tags: account.tags.clone().unwrap_or_default(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+        disabled: false,
+        forbidden: false,
+        quota_error: account.quota_error.as_ref().map(|err| err.message.clone()),
+        token_expires_at: decode_jwt_payload_value(&account.tokens.access_token)
+            .and_then(|payload| payload.get("exp").and_then(|exp| exp.as_i64())),
+        quota_stale: false,
+        quota: unified_quota(account),
+    }
+}
+
+/// 切换账号：刷新令牌并写入 auth.json，同步默认实例绑定与 OpenCode，按设置重启 Codex App
+pub async fn switch_account_internal(account_id: &str) -> Result<CodexAccount, String> {
+    let _ = prepare_account_for_injection(account_id).await?;
+
+    // 切换账号（写入 auth.json）
+    let account = switch_account(account_id)?;
+
+    // 同步更新 Codex 默认实例的绑定账号（不同步到 Antigravity，因为账号体系不同）
+    if let Err(e) = codex_instance::update_default_settings(
+        Some(Some(account_id.to_string())),
+        None,
+        Some(false),
+    ) {
+        logger::log_warn(&format!("更新 Codex 默认实例绑定账号失败: {}", e));
+    } else {
+        logger::log_info(&format!(
+            "已同步更新 Codex 默认实例绑定账号: {}",
+            account_id
+        ));
     }
-}
 
-fn resolve_current_account_id(accounts: &[CodexAccount]) -> Option<String> {
-    if let Some(account) = get_current_account() {
-        return Some(account.id);
+    let mut opencode_updated = false;
+    match opencode_auth::replace_openai_entry_from_codex(&account) {
+        Ok(()) => {
+            opencode_updated = true;
+        }
+        Err(e) => {
+            logger::log_warn(&format!("OpenCode auth.json 更新跳过: {}", e));
+        }
     }
 
-    if let Ok(settings) = crate::modules::codex_instance::load_default_settings() {
-        if let Some(bind_id) = settings.bind_account_id {
-            let trimmed = bind_id.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
+    let user_config = config::get_user_config();
+    if user_config.opencode_sync_on_switch {
+        if opencode_updated {
+            if process::is_opencode_running() {
+                if let Err(e) = process::close_opencode(20) {
+                    logger::log_warn(&format!("OpenCode 关闭失败: {}", e));
+                }
+            } else {
+                logger::log_info("OpenCode 未在运行，准备启动");
+            }
+            if let Err(e) = process::start_opencode_with_path(Some(&user_config.opencode_app_path))
+            {
+                logger::log_warn(&format!("OpenCode 启动失败: {}", e));
             }
+        } else {
+            logger::log_info("OpenCode 未更新 auth.json，跳过启动/重启");
         }
+    } else {
+        logger::log_info("已关闭 OpenCode 自动重启");
     }
 
-    accounts
-        .iter()
-        .max_by_key(|account| account.last_used)
-        .map(|account| account.id.clone())
+    #[cfg(target_os = "macos")]
+    let launch_error = if user_config.codex_launch_on_switch {
+        if process::is_codex_running() {
+            logger::log_info("检测到 Codex 正在运行，将按默认实例 PID 逻辑重启");
+        }
+        match codex_instance::start_default_instance().await {
+            Ok(_) => None,
+            Err(e) => {
+                logger::log_warn(&format!("Codex 启动失败: {}", e));
+                Some(e)
+            }
+        }
+    } else {
+        logger::log_info("已关闭切换 Codex 时自动启动 Codex App");
+        None
+    };
+    #[cfg(not(target_os = "macos"))]
+    let launch_error: Option<String> = None;
+
+    platform::notify_account_switched("codex", launch_error.as_deref());
+    Ok(account)
 }
 
-fn pick_quota_alert_recommendation(
-    accounts: &[CodexAccount],
-    current_id: &str,
-) -> Option<CodexAccount> {
-    let mut candidates: Vec<CodexAccount> = accounts
-        .iter()
-        .filter(|account| account.id != current_id)
-        .filter(|account| !extract_quota_metrics(account).is_empty())
-        .cloned()
-        .collect();
+pub struct CodexPlatform;
 
-    if candidates.is_empty() {
-        return None;
+impl Platform for CodexPlatform {
+    fn id(&self) -> &'static str {
+        "codex"
     }
 
-    candidates.sort_by(|a, b| {
-        let avg_a = average_quota_percentage(&extract_quota_metrics(a));
-        let avg_b = average_quota_percentage(&extract_quota_metrics(b));
-        avg_b
-            .partial_cmp(&avg_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then_with(|| a.last_used.cmp(&b.last_used))
-    });
+    fn display_name(&self) -> &'static str {
+        "Codex"
+    }
 
-    candidates.into_iter().next()
-}
+    fn list_accounts(&self) -> Result<Vec<PlatformAccount>, String> {
+        Ok(list_accounts().iter().map(to_platform_account).collect())
+    }
 
-pub fn run_quota_alert_if_needed(
-) -> Result<Option<crate::modules::account::QuotaAlertPayload>, String> {
-    let cfg = crate::modules::config::get_user_config();
-    if !cfg.codex_quota_alert_enabled {
-        return Ok(None);
+    fn current_account_id(&self, accounts: &[PlatformAccount]) -> Option<String> {
+        resolve_current_account_id(accounts)
     }
 
-    let threshold = normalize_quota_alert_threshold(cfg.codex_quota_alert_threshold);
-    let accounts = list_accounts();
-    let current_id = match resolve_current_account_id(&accounts) {
-        Some(id) => id,
-        None => return Ok(None),
-    };
+    fn remove_accounts(&self, account_ids: &[String]) -> Result<(), String> {
+        remove_accounts(account_ids)
+    }
 
-    let current = match accounts.iter().find(|account| account.id == current_id) {
-        Some(account) => account,
-        None => return Ok(None),
-    };
+    fn update_account_tags(&self, account_id: &str, tags: Vec<String>) -> Result<(), String> {
+        update_account_tags(account_id, tags).map(|_| ())
+    }
 
-    let metrics = extract_quota_metrics(current);
-    let low_models: Vec<(String, i32)> = metrics
-        .into_iter()
-        .filter(|(_, pct)| *pct <= threshold)
-        .collect();
+    fn export_accounts(&self, account_ids: &[String]) -> Result<String, String> {
+        export_accounts(account_ids)
+    }
 
-    if low_models.is_empty() {
-        clear_quota_alert_cooldown(&current_id, threshold);
-        return Ok(None);
-    }
-
-    let now = chrono::Utc::now().timestamp();
-    let cooldown_key = build_quota_alert_cooldown_key(&current_id, threshold);
-    if !should_emit_quota_alert(&cooldown_key, now) {
-        return Ok(None);
-    }
-
-    let recommendation = pick_quota_alert_recommendation(&accounts, &current_id);
-    let lowest_percentage = low_models.iter().map(|(_, pct)| *pct).min().unwrap_or(0);
-    let payload = crate::modules::account::QuotaAlertPayload {
-        platform: "codex".to_string(),
-        current_account_id: current_id,
-        current_email: current.email.clone(),
-        threshold,
-        lowest_percentage,
-        low_models: low_models.into_iter().map(|(name, _)| name).collect(),
-        recommended_account_id: recommendation.as_ref().map(|account| account.id.clone()),
-        recommended_email: recommendation.as_ref().map(|account| account.email.clone()),
-        triggered_at: now,
-    };
+    fn import_from_json(&self, json_content: String) -> BoxFuture<'static, Result<usize, String>> {
+        Box::pin(async move { import_from_json(&json_content).map(|accounts| accounts.len()) })
+    }
+
+    fn switch_account(&self, account_id: String) -> BoxFuture<'static, Result<String, String>> {
+        Box::pin(async move {
+            let account = switch_account_internal(&account_id).await?;
+            Ok(format!("切换完成: {}", account.email))
+        })
+    }
 
-    crate::modules::account::dispatch_quota_alert(&payload);
-    Ok(Some(payload))
+    fn refresh_account(&self, account_id: String) -> BoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            codex_quota::refresh_account_quota(&account_id)
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn refresh_all(&self) -> BoxFuture<'static, Result<RefreshReport, String>> {
+        Box::pin(async move { Ok(codex_quota::refresh_all_quotas().await?.report) })
+    }
+
+    fn refresh_interval_minutes(&self) -> i32 {
+        crate::modules::config::get_user_config().codex_auto_refresh_minutes
+    }
+
+    fn quota_alert_settings(&self) -> platform::QuotaAlertSettings {
+        let cfg = crate::modules::config::get_user_config();
+        platform::QuotaAlertSettings {
+            enabled: cfg.codex_quota_alert_enabled,
+            threshold: cfg.codex_quota_alert_threshold,
+        }
+    }
 }
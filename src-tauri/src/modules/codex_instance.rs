@@ -1,40 +1,51 @@
-use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use chrono::Utc;
-use uuid::Uuid;
-
 use crate::models::{DefaultInstanceSettings, InstanceProfile, InstanceStore};
 use crate::modules;
 use crate::modules::instance::InstanceDefaults;
-use crate::modules::instance_store;
+use crate::modules::instance_manager::{InstanceBackend, InstanceManager};
 
 pub use crate::modules::instance_store::{CreateInstanceParams, UpdateInstanceParams};
 
-static CODEX_INSTANCE_STORE_LOCK: std::sync::LazyLock<Mutex<()>> =
-    std::sync::LazyLock::new(|| Mutex::new(()));
+static CODEX_INSTANCE_STORE_LOCK: Mutex<()> = Mutex::new(());
 
 const CODEX_INSTANCES_FILE: &str = "codex_instances.json";
 
-fn instances_path() -> Result<PathBuf, String> {
-    let data_dir = modules::account::get_data_dir()?;
-    Ok(data_dir.join(CODEX_INSTANCES_FILE))
+pub struct CodexInstances;
+
+impl InstanceBackend for CodexInstances {
+    fn display_name(&self) -> &'static str {
+        "Codex"
+    }
+
+    fn store_file(&self) -> &'static str {
+        CODEX_INSTANCES_FILE
+    }
+
+    fn store_lock(&self) -> &'static Mutex<()> {
+        &CODEX_INSTANCE_STORE_LOCK
+    }
+
+    fn default_user_data_dir(&self) -> Result<PathBuf, String> {
+        get_default_codex_home()
+    }
+
+    fn instances_root_dir(&self) -> Result<PathBuf, String> {
+        get_default_instances_root_dir()
+    }
 }
 
 pub fn load_instance_store() -> Result<InstanceStore, String> {
-    let path = instances_path()?;
-    instance_store::load_instance_store(&path, CODEX_INSTANCES_FILE)
+    CodexInstances.load_instance_store()
 }
 
 pub fn save_instance_store(store: &InstanceStore) -> Result<(), String> {
-    let path = instances_path()?;
-    instance_store::save_instance_store(&path, CODEX_INSTANCES_FILE, store)
+    CodexInstances.save_instance_store(store)
 }
 
 pub fn load_default_settings() -> Result<DefaultInstanceSettings, String> {
-    let store = load_instance_store()?;
-    Ok(store.default_settings)
+    CodexInstances.load_default_settings()
 }
 
 pub fn update_default_settings(
@@ -42,33 +53,7 @@ pub fn update_default_settings(
     extra_args: Option<String>,
     follow_local_account: Option<bool>,
 ) -> Result<DefaultInstanceSettings, String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let settings = &mut store.default_settings;
-
-    if follow_local_account == Some(true) {
-        settings.follow_local_account = true;
-        settings.bind_account_id = None;
-    }
-
-    if let Some(bind) = bind_account_id {
-        settings.bind_account_id = bind;
-        settings.follow_local_account = false;
-    }
-
-    if follow_local_account == Some(false) && settings.bind_account_id.is_none() {
-        settings.follow_local_account = false;
-    }
-
-    if let Some(args) = extra_args {
-        settings.extra_args = args.trim().to_string();
-    }
-
-    let updated = settings.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    CodexInstances.update_default_settings(bind_account_id, extra_args, follow_local_account)
 }
 
 pub fn get_default_codex_home() -> Result<PathBuf, String> {
@@ -93,249 +78,65 @@ pub fn get_default_instances_root_dir() -> Result<PathBuf, String> {
 }
 
 pub fn get_instance_defaults() -> Result<InstanceDefaults, String> {
-    let root_dir = get_default_instances_root_dir()?;
-    let default_user_data_dir = get_default_codex_home()?;
-    Ok(InstanceDefaults {
-        root_dir: root_dir.to_string_lossy().to_string(),
-        default_user_data_dir: default_user_data_dir.to_string_lossy().to_string(),
-    })
+    CodexInstances.get_instance_defaults()
 }
 
 pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile, String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-
-    let name = instance_store::normalize_name(&params.name)?;
-    let user_data_dir = params.user_data_dir.trim().to_string();
-    if user_data_dir.is_empty() {
-        return Err("实例目录不能为空".to_string());
-    }
-
-    instance_store::ensure_unique(&store, &name, &user_data_dir, None)?;
-
-    let user_dir_path = PathBuf::from(&user_data_dir);
-    let init_mode = params
-        .init_mode
-        .as_deref()
-        .unwrap_or("copy")
-        .to_ascii_lowercase();
-    let create_empty = init_mode == "empty";
-
-    if create_empty {
-        if user_dir_path.exists() {
-            let mut has_entries = false;
-            if let Ok(mut iter) = fs::read_dir(&user_dir_path) {
-                if iter.next().is_some() {
-                    has_entries = true;
-                }
-            }
-            if has_entries {
-                let resolved_path = instance_store::display_path(&user_dir_path);
-                return Err(format!("空白实例需要目标目录为空: {}", resolved_path));
-            }
-        }
-        fs::create_dir_all(&user_dir_path).map_err(|e| format!("创建实例目录失败: {}", e))?;
-    } else {
-        let source_dir = match params.copy_source_instance_id.as_deref() {
-            Some("__default__") | None => get_default_codex_home()?,
-            Some(source_id) => {
-                let source_instance = store
-                    .instances
-                    .iter()
-                    .find(|item| item.id == source_id)
-                    .ok_or("复制来源实例不存在")?;
-                PathBuf::from(&source_instance.user_data_dir)
-            }
-        };
-
-        if user_dir_path.exists() {
-            let mut has_entries = false;
-            if let Ok(mut iter) = fs::read_dir(&user_dir_path) {
-                if iter.next().is_some() {
-                    has_entries = true;
-                }
-            }
-            if has_entries {
-                let resolved_path = instance_store::display_path(&user_dir_path);
-                modules::logger::log_info(&format!(
-                    "[Codex Instance] 复制来源实例需要空目录，但目标已存在: {}",
-                    resolved_path
-                ));
-                return Err(format!("复制来源实例需要目标目录为空: {}", resolved_path));
-            }
-        }
-
-        if !source_dir.exists() {
-            return Err("未找到复制来源目录，请先确保来源实例已初始化".to_string());
-        }
-
-        instance_store::copy_dir_recursive(&source_dir, &user_dir_path)?;
-    }
-
-    let instance = InstanceProfile {
-        id: Uuid::new_v4().to_string(),
-        name,
-        user_data_dir,
-        extra_args: params.extra_args.trim().to_string(),
-        bind_account_id: if create_empty {
-            None
-        } else {
-            params.bind_account_id
-        },
-        created_at: Utc::now().timestamp_millis(),
-        last_launched_at: None,
-        last_pid: None,
-    };
-
-    store.instances.push(instance.clone());
-    save_instance_store(&store)?;
-    Ok(instance)
+    CodexInstances.create_instance(params)
 }
 
 pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile, String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let index = store
-        .instances
-        .iter()
-        .position(|instance| instance.id == params.instance_id)
-        .ok_or("实例不存在")?;
-
-    let current_id = store.instances[index].id.clone();
-    let current_dir = store.instances[index].user_data_dir.clone();
-    let next_name = params
-        .name
-        .as_ref()
-        .map(|name| instance_store::normalize_name(name))
-        .transpose()?;
-
-    if let Some(ref normalized) = next_name {
-        instance_store::ensure_unique(&store, normalized, &current_dir, Some(&current_id))?;
-    }
-
-    let instance = &mut store.instances[index];
-    if let Some(normalized) = next_name {
-        instance.name = normalized;
-    }
-    if let Some(ref extra_args) = params.extra_args {
-        instance.extra_args = extra_args.trim().to_string();
-    }
-    if let Some(bind) = params.bind_account_id.clone() {
-        instance.bind_account_id = bind;
-    }
-
-    let updated = instance.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    CodexInstances.update_instance(params)
 }
 
 pub fn delete_instance(instance_id: &str) -> Result<(), String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let index = store
-        .instances
-        .iter()
-        .position(|instance| instance.id == instance_id)
-        .ok_or("实例不存在")?;
-    let user_data_dir = store.instances[index].user_data_dir.clone();
-
-    if !user_data_dir.trim().is_empty() {
-        let dir_path = PathBuf::from(&user_data_dir);
-        modules::instance::delete_instance_directory(&dir_path)?;
-    }
-
-    store.instances.remove(index);
-    save_instance_store(&store)?;
-    Ok(())
-}
-
-#[allow(dead_code)]
-pub fn update_instance_last_launched(instance_id: &str) -> Result<InstanceProfile, String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_launched_at = Some(Utc::now().timestamp_millis());
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    CodexInstances.delete_instance(instance_id)
 }
 
 pub fn update_instance_after_start(instance_id: &str, pid: u32) -> Result<InstanceProfile, String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_launched_at = Some(Utc::now().timestamp_millis());
-            instance.last_pid = Some(pid);
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    CodexInstances.update_instance_after_start(instance_id, pid)
 }
 
 pub fn update_instance_pid(instance_id: &str, pid: Option<u32>) -> Result<InstanceProfile, String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_pid = pid;
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    CodexInstances.update_instance_pid(instance_id, pid)
 }
 
 pub fn update_default_pid(pid: Option<u32>) -> Result<DefaultInstanceSettings, String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    store.default_settings.last_pid = pid;
-    let updated = store.default_settings.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    CodexInstances.update_default_pid(pid)
 }
 
 pub fn clear_all_pids() -> Result<(), String> {
-    let _lock = CODEX_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    store.default_settings.last_pid = None;
-    for instance in &mut store.instances {
-        instance.last_pid = None;
-    }
-    save_instance_store(&store)?;
-    Ok(())
+    CodexInstances.clear_all_pids()
 }
 
 pub async fn inject_account_to_profile(profile_dir: &Path, account_id: &str) -> Result<(), String> {
     let account = modules::codex_account::prepare_account_for_injection(account_id).await?;
     modules::codex_account::write_auth_file_to_dir(profile_dir, &account)
 }
+
+/// 默认实例实际使用的账号：跟随本地账号时取当前账号，否则取绑定账号
+pub fn resolve_default_account_id(settings: &DefaultInstanceSettings) -> Option<String> {
+    if settings.follow_local_account {
+        modules::codex_account::get_current_account().map(|account| account.id)
+    } else {
+        settings.bind_account_id.clone()
+    }
+}
+
+/// 重启默认实例：关闭默认实例进程并注入账号后重新启动，返回新进程 PID
+pub async fn start_default_instance() -> Result<u32, String> {
+    modules::process::ensure_codex_launch_path_configured()?;
+
+    let default_dir = get_default_codex_home()?;
+    let default_settings = load_default_settings()?;
+    if let Some(pid) = modules::process::resolve_codex_pid(default_settings.last_pid, None) {
+        modules::process::close_pid(pid, 20)?;
+        let _ = update_default_pid(None)?;
+    }
+    if let Some(account_id) = resolve_default_account_id(&default_settings) {
+        inject_account_to_profile(&default_dir, &account_id).await?;
+    }
+    let pid = modules::process::start_codex_default()?;
+    let _ = update_default_pid(Some(pid))?;
+    Ok(pid)
+}
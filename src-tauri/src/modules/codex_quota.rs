@@ -1,5 +1,8 @@
 use crate::models::codex::{CodexAccount, CodexQuota, CodexQuotaErrorInfo};
-use crate::modules::{codex_account, logger};
+use crate::modules::platform::Platform;
+use crate::modules::quota_cache::{self, QuotaApiError};
+use crate::modules::refresh_executor::{self, RefreshRun};
+use crate::modules::{codex_account, logger, quota_events, quota_history};
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION};
 use serde::{Deserialize, Serialize};
 
@@ -142,19 +145,47 @@ pub async fn fetch_quota(account: &CodexAccount) -> Result<CodexQuota, String> {
         USAGE_URL, account_id
     ));
 
+    let cached = quota_cache::resolve(
+        codex_account::CodexPlatform.id(),
+        &account.id,
+        None,
+        true,
+        request_usage(client, headers),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    if cached.stale {
+        logger::log_warn(&format!(
+            "Codex 配额使用缓存数据: account={}, updated_at={}",
+            account.email, cached.updated_at
+        ));
+    }
+
+    // 解析响应
+    let usage: UsageResponse = serde_json::from_value(cached.payload.clone())
+        .map_err(|e| format!("解析 JSON 失败: {}", e))?;
+
+    parse_quota_from_usage(&usage, &cached.payload.to_string())
+}
+
+/// 请求 wham/usage 并返回原始响应；429 与 5xx 视为暂时性失败，其余非成功状态视为明确拒绝
+async fn request_usage(
+    client: reqwest::Client,
+    headers: HeaderMap,
+) -> Result<serde_json::Value, QuotaApiError> {
     let response = client
         .get(USAGE_URL)
         .headers(headers)
         .send()
         .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+        .map_err(|e| QuotaApiError::Transient(format!("请求失败: {}", e)))?;
 
     let status = response.status();
     let headers = response.headers().clone();
     let body = response
         .text()
         .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
+        .map_err(|e| QuotaApiError::Transient(format!("读取响应失败: {}", e)))?;
 
     let request_id = get_header_value(&headers, "request-id");
     let x_request_id = get_header_value(&headers, "x-request-id");
@@ -184,14 +215,17 @@ pub async fn fetch_quota(account: &CodexAccount) -> Result<CodexQuota, String> {
             error_message.push_str(&format!(" [error_code:{}]", code));
         }
         error_message.push_str(&format!(" - {}", body_preview));
-        return Err(error_message);
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(QuotaApiError::Transient(error_message));
+        }
+        return Err(QuotaApiError::Rejected {
+            status: status.as_u16(),
+            message: error_message,
+        });
     }
 
-    // 解析响应
-    let usage: UsageResponse =
-        serde_json::from_str(&body).map_err(|e| format!("解析 JSON 失败: {}", e))?;
-
-    parse_quota_from_usage(&usage, &body)
+    serde_json::from_str(&body)
+        .map_err(|e| QuotaApiError::Transient(format!("解析 JSON 失败: {}", e)))
 }
 
 /// 从使用率响应中解析配额信息
@@ -245,8 +279,10 @@ pub async fn refresh_account_quota(account_id: &str) -> Result<CodexQuota, Strin
     let mut account = codex_account::load_account(account_id)
         .ok_or_else(|| format!("账号不存在: {}", account_id))?;
 
-    // 检查 token 是否过期，如果过期则刷新
-    if crate::modules::codex_oauth::is_token_expired(&account.tokens.access_token) {
+    // 检查 token 是否过期，如果过期则刷新（离线模式下跳过，直接使用缓存的配额）
+    if !quota_cache::is_offline()
+        && crate::modules::codex_oauth::is_token_expired(&account.tokens.access_token)
+    {
         logger::log_info(&format!("账号 {} 的 Token 已过期，尝试刷新", account.email));
 
         if let Some(ref refresh_token) = account.tokens.refresh_token {
@@ -294,39 +330,27 @@ pub async fn refresh_account_quota(account_id: &str) -> Result<CodexQuota, Strin
     Ok(quota)
 }
 
-/// 刷新所有账号配额
-pub async fn refresh_all_quotas() -> Result<Vec<(String, Result<CodexQuota, String>)>, String> {
-    use futures::future::join_all;
-    use std::sync::Arc;
-    use tokio::sync::Semaphore;
-
-    const MAX_CONCURRENT: usize = 5;
-    let accounts = codex_account::list_accounts();
+/// 批量刷新时按此主机限速
+const REFRESH_HOST: &str = "chatgpt.com";
 
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
-    let tasks: Vec<_> = accounts
+/// 刷新所有账号配额
+pub async fn refresh_all_quotas() -> Result<RefreshRun<CodexQuota>, String> {
+    let account_ids = codex_account::list_accounts()
         .into_iter()
-        .map(|account| {
-            let account_id = account.id;
-            let semaphore = semaphore.clone();
-            async move {
-                let _permit = semaphore
-                    .acquire_owned()
-                    .await
-                    .map_err(|e| format!("获取 Codex 刷新并发许可失败: {}", e))?;
-                let result = refresh_account_quota(&account_id).await;
-                Ok::<(String, Result<CodexQuota, String>), String>((account_id, result))
-            }
-        })
+        .map(|account| account.id)
         .collect();
-
-    let mut results = Vec::with_capacity(tasks.len());
-    for task in join_all(tasks).await {
-        match task {
-            Ok(item) => results.push(item),
-            Err(err) => return Err(err),
-        }
-    }
-
-    Ok(results)
+    let run = refresh_executor::run(
+        codex_account::CodexPlatform.id(),
+        REFRESH_HOST,
+        account_ids,
+        |account_id| async move { refresh_account_quota(&account_id).await },
+    )
+    .await;
+
+    quota_history::record_refreshed_logged(
+        &codex_account::CodexPlatform,
+        &quota_history::succeeded_ids(&run.results),
+    );
+    quota_events::publish_logged(&codex_account::CodexPlatform);
+    Ok(run)
 }
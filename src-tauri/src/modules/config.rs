@@ -6,6 +6,7 @@ use serde_json::json;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{OnceLock, RwLock};
+use crate::utils::atomic_file;
 
 /// 默认 WebSocket 端口
 pub const DEFAULT_WS_PORT: u16 = 19528;
@@ -33,6 +34,9 @@ pub struct ServerStatus {
     pub pid: u32,
     /// 启动时间戳
     pub started_at: i64,
+    /// WebSocket 认证密钥（每次启动重新生成，客户端凭此认证）
+    #[serde(default)]
+    pub auth_secret: String,
 }
 
 /// 用户配置（持久化存储）
@@ -65,6 +69,15 @@ pub struct UserConfig {
     /// Kiro 自动刷新间隔（分钟），-1 表示禁用
     #[serde(default = "default_kiro_auto_refresh")]
     pub kiro_auto_refresh_minutes: i32,
+    /// Cursor 自动刷新间隔（分钟），-1 表示禁用
+    #[serde(default = "default_cursor_auto_refresh")]
+    pub cursor_auto_refresh_minutes: i32,
+    /// 批量刷新的最大并发账号数
+    #[serde(default = "default_refresh_concurrency")]
+    pub refresh_concurrency: i32,
+    /// 批量刷新时同一主机两次请求的最小间隔（毫秒）
+    #[serde(default = "default_refresh_host_interval_ms")]
+    pub refresh_host_interval_ms: u64,
     /// 窗口关闭行为
     #[serde(default = "default_close_behavior")]
     pub close_behavior: CloseWindowBehavior,
@@ -92,6 +105,9 @@ pub struct UserConfig {
     /// Kiro 启动路径（为空则使用默认路径）
     #[serde(default = "default_kiro_app_path")]
     pub kiro_app_path: String,
+    /// Cursor 启动路径（为空则使用默认路径）
+    #[serde(default = "default_cursor_app_path")]
+    pub cursor_app_path: String,
     /// 切换 Codex 时是否自动重启 OpenCode
     #[serde(default = "default_opencode_sync_on_switch")]
     pub opencode_sync_on_switch: bool,
@@ -134,6 +150,12 @@ pub struct UserConfig {
     /// Kiro 配额预警阈值（百分比）
     #[serde(default = "default_kiro_quota_alert_threshold")]
     pub kiro_quota_alert_threshold: i32,
+    /// 是否启用 Cursor 配额预警通知
+    #[serde(default = "default_cursor_quota_alert_enabled")]
+    pub cursor_quota_alert_enabled: bool,
+    /// Cursor 配额预警阈值（百分比）
+    #[serde(default = "default_cursor_quota_alert_threshold")]
+    pub cursor_quota_alert_threshold: i32,
 }
 
 /// 窗口关闭行为
@@ -197,6 +219,15 @@ fn default_windsurf_auto_refresh() -> i32 {
 fn default_kiro_auto_refresh() -> i32 {
     10
 } // 默认 10 分钟
+fn default_cursor_auto_refresh() -> i32 {
+    10
+} // 默认 10 分钟
+fn default_refresh_concurrency() -> i32 {
+    crate::modules::refresh_executor::DEFAULT_CONCURRENCY as i32
+}
+fn default_refresh_host_interval_ms() -> u64 {
+    crate::modules::refresh_executor::DEFAULT_HOST_INTERVAL_MS
+}
 fn default_close_behavior() -> CloseWindowBehavior {
     CloseWindowBehavior::Ask
 }
@@ -224,6 +255,9 @@ fn default_windsurf_app_path() -> String {
 fn default_kiro_app_path() -> String {
     String::new()
 }
+fn default_cursor_app_path() -> String {
+    String::new()
+}
 fn default_opencode_sync_on_switch() -> bool {
     true
 }
@@ -266,6 +300,12 @@ fn default_kiro_quota_alert_enabled() -> bool {
 fn default_kiro_quota_alert_threshold() -> i32 {
     20
 }
+fn default_cursor_quota_alert_enabled() -> bool {
+    false
+}
+fn default_cursor_quota_alert_threshold() -> i32 {
+    20
+}
 
 impl Default for UserConfig {
     fn default() -> Self {
@@ -279,6 +319,9 @@ impl Default for UserConfig {
             ghcp_auto_refresh_minutes: default_ghcp_auto_refresh(),
             windsurf_auto_refresh_minutes: default_windsurf_auto_refresh(),
             kiro_auto_refresh_minutes: default_kiro_auto_refresh(),
+            cursor_auto_refresh_minutes: default_cursor_auto_refresh(),
+            refresh_concurrency: default_refresh_concurrency(),
+            refresh_host_interval_ms: default_refresh_host_interval_ms(),
             close_behavior: default_close_behavior(),
             minimize_behavior: default_minimize_behavior(),
             hide_dock_icon: default_hide_dock_icon(),
@@ -288,6 +331,7 @@ impl Default for UserConfig {
             vscode_app_path: default_vscode_app_path(),
             windsurf_app_path: default_windsurf_app_path(),
             kiro_app_path: default_kiro_app_path(),
+            cursor_app_path: default_cursor_app_path(),
             opencode_sync_on_switch: default_opencode_sync_on_switch(),
             codex_launch_on_switch: default_codex_launch_on_switch(),
             auto_switch_enabled: default_auto_switch_enabled(),
@@ -302,6 +346,8 @@ impl Default for UserConfig {
             windsurf_quota_alert_threshold: default_windsurf_quota_alert_threshold(),
             kiro_quota_alert_enabled: default_kiro_quota_alert_enabled(),
             kiro_quota_alert_threshold: default_kiro_quota_alert_threshold(),
+            cursor_quota_alert_enabled: default_cursor_quota_alert_enabled(),
+            cursor_quota_alert_threshold: default_cursor_quota_alert_threshold(),
         }
     }
 }
@@ -468,7 +514,7 @@ pub fn save_user_config(config: &UserConfig) -> Result<(), String> {
     let json =
         serde_json::to_string_pretty(config).map_err(|e| format!("序列化配置失败: {}", e))?;
 
-    fs::write(&config_path, json).map_err(|e| format!("写入配置文件失败: {}", e))?;
+    atomic_file::write(&config_path, json).map_err(|e| format!("写入配置文件失败: {}", e))?;
 
     // 更新运行时状态
     if let Ok(mut state) = get_runtime_state().write() {
@@ -479,6 +525,7 @@ pub fn save_user_config(config: &UserConfig) -> Result<(), String> {
         "[Config] 用户配置已保存: ws_enabled={}, ws_port={}",
         config.ws_enabled, config.ws_port
     ));
+    crate::modules::websocket::broadcast_config_changed(config);
 
     Ok(())
 }
@@ -518,7 +565,9 @@ pub fn save_server_status(status: &ServerStatus) -> Result<(), String> {
     let json =
         serde_json::to_string_pretty(status).map_err(|e| format!("序列化状态失败: {}", e))?;
 
-    fs::write(&status_path, json).map_err(|e| format!("写入状态文件失败: {}", e))?;
+    // 状态文件包含认证密钥，只允许当前用户读取
+    atomic_file::write_private(&status_path, json)
+        .map_err(|e| format!("写入状态文件失败: {}", e))?;
 
     crate::modules::logger::log_info(&format!(
         "[Config] 服务状态已保存: ws_port={}, pid={}",
@@ -540,6 +589,7 @@ pub fn init_server_status(actual_port: u16) -> Result<(), String> {
         version: env!("CARGO_PKG_VERSION").to_string(),
         pid: std::process::id(),
         started_at: chrono::Utc::now().timestamp(),
+        auth_secret: crate::modules::ws_auth::server_secret().to_string(),
     };
 
     save_server_status(&status)?;
@@ -0,0 +1,945 @@
+//! 凭据保险库
+//!
+//! 对各平台账号文件中的敏感字段（access/refresh token、API Key 等）做 AES-256-GCM 加密，
+//! 非敏感字段保持明文，索引、统计与配额展示不受影响。
+//!
+//! ## 密钥来源
+//!
+//! - passphrase: 用户口令经 PBKDF2-HMAC-SHA256 派生，仅保存在内存中，重启后需重新解锁
+//! - keyring: 随机生成的 256 位密钥，托管在系统凭据存储中，启动时自动解锁
+//!   - macOS: Keychain（`security` 命令）
+//!   - Linux: Secret Service（`secret-tool` 命令）
+//!   - Windows: DPAPI 加密后写入 `vault.key`
+//!
+//! ## 存储格式
+//!
+//! 加密字段以字符串 `enc:v1:<base64(nonce || ciphertext)>` 原位替换，明文为字段值的 JSON 文本。
+//! 读取时遇到明文字段原样返回，因此启用前的存量账号可以直接读取；首次解锁时统一重新加密写回。
+
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::{Aead, AeadCore, OsRng};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use pbkdf2::pbkdf2_hmac;
+use rand::RngCore;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{LocalFree, HLOCAL};
+#[cfg(target_os = "windows")]
+use windows::Win32::Security::Cryptography::{
+    CryptProtectData, CryptUnprotectData, CRYPT_INTEGER_BLOB,
+};
+
+use crate::modules::{
+    account, codex_account, cursor_account, github_copilot_account, kiro_account, logger,
+    windsurf_account,
+};
+use crate::utils::atomic_file;
+
+const VAULT_META_FILE: &str = "vault.json";
+const VAULT_META_VERSION: u32 = 1;
+const SEALED_PREFIX: &str = "enc:v1:";
+const CHECK_PLAINTEXT: &str = "cockpit-vault-check";
+const KDF_ITERATIONS: u32 = 200_000;
+const MIN_PASSPHRASE_LEN: usize = 8;
+const NONCE_LEN: usize = 12;
+const LOCKED_ERROR: &str = "凭据保险库已锁定，请先解锁";
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+const KEYRING_SERVICE: &str = "Cockpit Tools Vault";
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+const KEYRING_ACCOUNT: &str = "credential-vault";
+#[cfg(target_os = "windows")]
+const WINDOWS_KEY_FILE: &str = "vault.key";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VaultKeySource {
+    Passphrase,
+    Keyring,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultMeta {
+    version: u32,
+    key_source: VaultKeySource,
+    /// PBKDF2 盐值（仅 passphrase 模式）
+    #[serde(default)]
+    salt: Option<String>,
+    #[serde(default)]
+    kdf_iterations: Option<u32>,
+    /// 用当前密钥加密的校验串，用于验证口令/钥匙串密钥是否正确
+    check: String,
+    created_at: i64,
+    /// 存量明文账号完成加密迁移的时间
+    #[serde(default)]
+    migrated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    pub enabled: bool,
+    pub unlocked: bool,
+    pub key_source: Option<VaultKeySource>,
+    pub migrated_at: Option<i64>,
+}
+
+#[derive(Default)]
+struct VaultState {
+    loaded: bool,
+    meta: Option<VaultMeta>,
+    key: Option<[u8; 32]>,
+}
+
+static VAULT_STATE: std::sync::LazyLock<RwLock<VaultState>> =
+    std::sync::LazyLock::new(|| RwLock::new(VaultState::default()));
+
+fn now_ts() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn get_meta_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(VAULT_META_FILE))
+}
+
+fn read_meta_file() -> Result<Option<VaultMeta>, String> {
+    let path = get_meta_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取保险库配置失败: {}", e))?;
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(&content).map(Some).map_err(|e| {
+        crate::error::file_corrupted_error(VAULT_META_FILE, &path.to_string_lossy(), &e.to_string())
+    })
+}
+
+fn write_meta_file(meta: &VaultMeta) -> Result<(), String> {
+    let path = get_meta_path()?;
+    let content =
+        serde_json::to_string_pretty(meta).map_err(|e| format!("序列化保险库配置失败: {}", e))?;
+    atomic_file::write(path, content).map_err(|e| format!("保存保险库配置失败: {}", e))
+}
+
+fn remove_meta_file() -> Result<(), String> {
+    let path = get_meta_path()?;
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("删除保险库配置失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 确保内存中的元数据已从磁盘加载
+fn ensure_loaded() {
+    {
+        let state = VAULT_STATE.read().unwrap_or_else(|e| e.into_inner());
+        if state.loaded {
+            return;
+        }
+    }
+    let mut state = VAULT_STATE.write().unwrap_or_else(|e| e.into_inner());
+    if state.loaded {
+        return;
+    }
+    match read_meta_file() {
+        Ok(meta) => state.meta = meta,
+        Err(e) => logger::log_error(&format!("[Vault] 加载保险库配置失败: {}", e)),
+    }
+    state.loaded = true;
+}
+
+fn current_key() -> Option<[u8; 32]> {
+    ensure_loaded();
+    VAULT_STATE.read().unwrap_or_else(|e| e.into_inner()).key
+}
+
+fn current_meta() -> Option<VaultMeta> {
+    ensure_loaded();
+    VAULT_STATE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .meta
+        .clone()
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut buf = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut buf);
+    buf
+}
+
+fn encrypt_bytes(key: &[u8; 32], plaintext: &[u8]) -> Result<String, String> {
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "加密凭据失败".to_string())?;
+    let mut combined = nonce.to_vec();
+    combined.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(combined)))
+}
+
+fn decrypt_string(key: &[u8; 32], sealed: &str) -> Result<Vec<u8>, String> {
+    let encoded = sealed
+        .strip_prefix(SEALED_PREFIX)
+        .ok_or_else(|| "不是保险库加密格式".to_string())?;
+    let combined = STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("解码加密凭据失败: {}", e))?;
+    if combined.len() <= NONCE_LEN {
+        return Err("加密凭据长度异常".to_string());
+    }
+    let (nonce, ciphertext) = combined.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "解密凭据失败（密钥不匹配或数据已损坏）".to_string())
+}
+
+fn is_sealed(value: &Value) -> bool {
+    value
+        .as_str()
+        .map(|s| s.starts_with(SEALED_PREFIX))
+        .unwrap_or(false)
+}
+
+fn verify_key(meta: &VaultMeta, key: &[u8; 32]) -> bool {
+    decrypt_string(key, &meta.check)
+        .map(|plain| plain == CHECK_PLAINTEXT.as_bytes())
+        .unwrap_or(false)
+}
+
+/// 保险库是否已启用
+pub fn is_enabled() -> bool {
+    current_meta().is_some()
+}
+
+/// 保险库是否处于可读写状态（未启用视为可用）
+pub fn is_unlocked() -> bool {
+    !is_enabled() || current_key().is_some()
+}
+
+/// 在使用 Token 之前调用：保险库已锁定时直接返回错误，避免拿密文去请求上游
+pub fn ensure_unlocked() -> Result<(), String> {
+    if is_unlocked() {
+        Ok(())
+    } else {
+        Err(LOCKED_ERROR.to_string())
+    }
+}
+
+pub fn get_status() -> VaultStatus {
+    let meta = current_meta();
+    VaultStatus {
+        enabled: meta.is_some(),
+        unlocked: meta.is_some() && current_key().is_some(),
+        key_source: meta.as_ref().map(|m| m.key_source),
+        migrated_at: meta.and_then(|m| m.migrated_at),
+    }
+}
+
+/// 对 JSON 中指定指针位置的敏感字段加密（原位替换）
+///
+/// 未启用保险库时不做任何处理；已启用但锁定时，明文字段无法加密，直接报错，避免明文落盘。
+/// 已是密文的字段保持不变，因此锁定状态下读出再写回不会丢失数据。
+fn seal_value(root: &mut Value, pointers: &[&str], key: Option<&[u8; 32]>) -> Result<(), String> {
+    for pointer in pointers {
+        let Some(slot) = root.pointer_mut(pointer) else {
+            continue;
+        };
+        if slot.is_null() || is_sealed(slot) {
+            continue;
+        }
+        let key = key.ok_or_else(|| "凭据保险库已锁定，无法保存敏感字段".to_string())?;
+        let plaintext =
+            serde_json::to_vec(slot).map_err(|e| format!("序列化敏感字段失败: {}", e))?;
+        *slot = Value::String(encrypt_bytes(key, &plaintext)?);
+    }
+    Ok(())
+}
+
+/// 对 JSON 中指定指针位置的密文字段解密（原位替换）；明文字段原样保留
+fn open_value(root: &mut Value, pointers: &[&str], key: Option<&[u8; 32]>) {
+    let Some(key) = key else {
+        return;
+    };
+    for pointer in pointers {
+        let Some(slot) = root.pointer_mut(pointer) else {
+            continue;
+        };
+        let Some(sealed) = slot.as_str().filter(|s| s.starts_with(SEALED_PREFIX)) else {
+            continue;
+        };
+        match decrypt_string(key, sealed)
+            .and_then(|plain| serde_json::from_slice::<Value>(&plain).map_err(|e| e.to_string()))
+        {
+            Ok(opened) => *slot = opened,
+            Err(e) => logger::log_warn(&format!("[Vault] 字段 {} 解密失败: {}", pointer, e)),
+        }
+    }
+}
+
+/// 按保险库状态加密 JSON 中的敏感字段（未启用时不处理）
+pub fn seal_fields(value: &mut Value, pointers: &[&str]) -> Result<(), String> {
+    if is_enabled() {
+        seal_value(value, pointers, current_key().as_ref())?;
+    }
+    Ok(())
+}
+
+/// 解密 JSON 中的敏感字段；锁定或明文时原样保留
+pub fn open_fields(value: &mut Value, pointers: &[&str]) {
+    open_value(value, pointers, current_key().as_ref());
+}
+
+/// 序列化账号数据，并按保险库状态加密敏感字段
+pub fn to_sealed_json<T: Serialize>(data: &T, pointers: &[&str]) -> Result<String, String> {
+    let mut value = serde_json::to_value(data).map_err(|e| format!("序列化账号失败: {}", e))?;
+    seal_fields(&mut value, pointers)?;
+    serde_json::to_string_pretty(&value).map_err(|e| format!("序列化账号失败: {}", e))
+}
+
+/// 解析账号数据，并解密其中的敏感字段
+pub fn from_sealed_json<T: DeserializeOwned>(
+    content: &str,
+    pointers: &[&str],
+) -> Result<T, String> {
+    let mut value: Value =
+        serde_json::from_str(content).map_err(|e| format!("解析账号数据失败: {}", e))?;
+    open_fields(&mut value, pointers);
+    serde_json::from_value(value).map_err(|e| format!("解析账号数据失败: {}", e))
+}
+
+fn set_state(meta: Option<VaultMeta>, key: Option<[u8; 32]>) {
+    let mut state = VAULT_STATE.write().unwrap_or_else(|e| e.into_inner());
+    state.loaded = true;
+    state.meta = meta;
+    state.key = key;
+}
+
+/// 各平台账号文件所在目录
+fn account_dirs() -> Result<Vec<PathBuf>, String> {
+    Ok(vec![
+        account::get_accounts_dir()?,
+        codex_account::get_accounts_dir(),
+        github_copilot_account::get_accounts_dir()?,
+        windsurf_account::get_accounts_dir()?,
+        kiro_account::get_accounts_dir()?,
+        cursor_account::get_accounts_dir()?,
+    ])
+}
+
+/// 删除目录中遗留的 `*.json.bak`：其中保存的是重新加密前的凭据
+fn remove_stale_backups(dir: &Path) -> Result<usize, String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(0);
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        if !entry.file_name().to_string_lossy().ends_with(".json.bak") {
+            continue;
+        }
+        fs::remove_file(entry.path()).map_err(|e| format!("删除旧账号备份失败: {}", e))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// 将所有平台的账号文件按当前保险库状态重新写回，并清理旧版本留下的备份
+fn reseal_all_stores() -> Result<usize, String> {
+    let mut total = 0usize;
+    total += account::reseal_account_files()?;
+    total += codex_account::reseal_account_files()?;
+    total += github_copilot_account::reseal_account_files()?;
+    total += windsurf_account::reseal_account_files()?;
+    total += kiro_account::reseal_account_files()?;
+    total += cursor_account::reseal_account_files()?;
+
+    let mut removed = 0usize;
+    for dir in account_dirs()? {
+        removed += remove_stale_backups(&dir)?;
+    }
+    if removed > 0 {
+        logger::log_info(&format!("[Vault] 已清理旧账号备份: {} 个", removed));
+    }
+    Ok(total)
+}
+
+/// 首次解锁时迁移存量明文账号
+fn migrate_if_needed() -> Result<(), String> {
+    let Some(mut meta) = current_meta() else {
+        return Ok(());
+    };
+    if meta.migrated_at.is_some() {
+        return Ok(());
+    }
+    let count = reseal_all_stores()?;
+    meta.migrated_at = Some(now_ts());
+    write_meta_file(&meta)?;
+    let key = current_key();
+    set_state(Some(meta), key);
+    logger::log_info(&format!("[Vault] 存量账号加密迁移完成: {} 个账号", count));
+    Ok(())
+}
+
+fn passphrase_key(meta: &VaultMeta, passphrase: &str) -> Result<[u8; 32], String> {
+    let salt = meta
+        .salt
+        .as_deref()
+        .ok_or_else(|| "保险库配置缺少 salt".to_string())?;
+    let salt = STANDARD
+        .decode(salt)
+        .map_err(|e| format!("解析 salt 失败: {}", e))?;
+    let iterations = meta.kdf_iterations.unwrap_or(KDF_ITERATIONS);
+    Ok(derive_key(passphrase, &salt, iterations))
+}
+
+/// 启用保险库并加密所有存量账号
+pub fn enable(key_source: VaultKeySource, passphrase: Option<&str>) -> Result<VaultStatus, String> {
+    if is_enabled() {
+        return Err("凭据保险库已启用".to_string());
+    }
+
+    let (key, salt, iterations) = match key_source {
+        VaultKeySource::Passphrase => {
+            let passphrase = passphrase.unwrap_or_default();
+            if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+                return Err(format!("口令长度至少 {} 位", MIN_PASSPHRASE_LEN));
+            }
+            let salt = random_bytes::<16>();
+            (
+                derive_key(passphrase, &salt, KDF_ITERATIONS),
+                Some(STANDARD.encode(salt)),
+                Some(KDF_ITERATIONS),
+            )
+        }
+        VaultKeySource::Keyring => {
+            let key = random_bytes::<32>();
+            keyring_store(&key)?;
+            (key, None, None)
+        }
+    };
+
+    let meta = VaultMeta {
+        version: VAULT_META_VERSION,
+        key_source,
+        salt,
+        kdf_iterations: iterations,
+        check: encrypt_bytes(&key, CHECK_PLAINTEXT.as_bytes())?,
+        created_at: now_ts(),
+        migrated_at: None,
+    };
+    write_meta_file(&meta)?;
+    set_state(Some(meta), Some(key));
+    logger::log_info(&format!(
+        "[Vault] 凭据保险库已启用: source={:?}",
+        key_source
+    ));
+
+    migrate_if_needed()?;
+    Ok(get_status())
+}
+
+/// 解锁保险库；keyring 模式忽略口令参数
+pub fn unlock(passphrase: Option<&str>) -> Result<VaultStatus, String> {
+    let meta = current_meta().ok_or_else(|| "凭据保险库未启用".to_string())?;
+    if current_key().is_some() {
+        return Ok(get_status());
+    }
+
+    let key = match meta.key_source {
+        VaultKeySource::Passphrase => {
+            let passphrase = passphrase.ok_or_else(|| "请输入保险库口令".to_string())?;
+            passphrase_key(&meta, passphrase)?
+        }
+        VaultKeySource::Keyring => keyring_load()?,
+    };
+    if !verify_key(&meta, &key) {
+        return Err("保险库口令错误".to_string());
+    }
+    set_state(Some(meta), Some(key));
+    logger::log_info("[Vault] 凭据保险库已解锁");
+
+    migrate_if_needed()?;
+    Ok(get_status())
+}
+
+/// 锁定保险库（清除内存中的密钥）
+pub fn lock() -> VaultStatus {
+    let meta = current_meta();
+    set_state(meta, None);
+    logger::log_info("[Vault] 凭据保险库已锁定");
+    get_status()
+}
+
+/// 关闭保险库：解密所有账号写回明文，并清理密钥
+pub fn disable(passphrase: Option<&str>) -> Result<VaultStatus, String> {
+    let meta = current_meta().ok_or_else(|| "凭据保险库未启用".to_string())?;
+    if current_key().is_none() {
+        unlock(passphrase)?;
+    }
+    let key = current_key();
+
+    // 先关闭加密（写入走明文），保留内存密钥用于读取密文
+    {
+        let mut state = VAULT_STATE.write().unwrap_or_else(|e| e.into_inner());
+        state.meta = None;
+    }
+    if let Err(e) = reseal_all_stores() {
+        set_state(Some(meta), key);
+        return Err(format!("解密账号数据失败: {}", e));
+    }
+
+    remove_meta_file()?;
+    if meta.key_source == VaultKeySource::Keyring {
+        if let Err(e) = keyring_delete() {
+            logger::log_warn(&format!("[Vault] 清理系统钥匙串密钥失败: {}", e));
+        }
+    }
+    set_state(None, None);
+    logger::log_info("[Vault] 凭据保险库已关闭，账号已恢复为明文存储");
+    Ok(get_status())
+}
+
+/// 启动时加载保险库配置；keyring 模式自动解锁
+pub fn init_on_startup() {
+    let Some(meta) = current_meta() else {
+        return;
+    };
+    if meta.key_source != VaultKeySource::Keyring {
+        logger::log_info("[Vault] 凭据保险库处于锁定状态，等待用户解锁");
+        return;
+    }
+    if let Err(e) = unlock(None) {
+        logger::log_error(&format!("[Vault] 启动时自动解锁失败: {}", e));
+    }
+}
+
+/// vault.json 被外部替换（如恢复快照）后重新加载；原密钥仍能通过校验时保持解锁
+pub fn reload() {
+    let previous_key = {
+        let mut state = VAULT_STATE.write().unwrap_or_else(|e| e.into_inner());
+        let key = state.key.take();
+        *state = VaultState::default();
+        key
+    };
+    let Some(meta) = current_meta() else {
+        return;
+    };
+    if let Some(key) = previous_key {
+        if verify_key(&meta, &key) {
+            VAULT_STATE.write().unwrap_or_else(|e| e.into_inner()).key = Some(key);
+            return;
+        }
+    }
+    init_on_startup();
+}
+
+// ============================================================================
+// 系统钥匙串
+// ============================================================================
+
+#[cfg(target_os = "macos")]
+fn keyring_store(key: &[u8; 32]) -> Result<(), String> {
+    let output = Command::new("security")
+        .args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            KEYRING_SERVICE,
+            "-a",
+            KEYRING_ACCOUNT,
+            "-w",
+            &STANDARD.encode(key),
+        ])
+        .output()
+        .map_err(|e| format!("调用 security 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "写入 Keychain 失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn keyring_load() -> Result<[u8; 32], String> {
+    let output = Command::new("security")
+        .args([
+            "find-generic-password",
+            "-w",
+            "-s",
+            KEYRING_SERVICE,
+            "-a",
+            KEYRING_ACCOUNT,
+        ])
+        .output()
+        .map_err(|e| format!("调用 security 失败: {}", e))?;
+    if !output.status.success() {
+        return Err("未在 Keychain 中找到保险库密钥".to_string());
+    }
+    decode_key(String::from_utf8_lossy(&output.stdout).trim())
+}
+
+#[cfg(target_os = "macos")]
+fn keyring_delete() -> Result<(), String> {
+    let output = Command::new("security")
+        .args([
+            "delete-generic-password",
+            "-s",
+            KEYRING_SERVICE,
+            "-a",
+            KEYRING_ACCOUNT,
+        ])
+        .output()
+        .map_err(|e| format!("调用 security 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn keyring_store(key: &[u8; 32]) -> Result<(), String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new("secret-tool")
+        .args([
+            "store",
+            "--label",
+            KEYRING_SERVICE,
+            "service",
+            KEYRING_SERVICE,
+            "account",
+            KEYRING_ACCOUNT,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("调用 secret-tool 失败: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(STANDARD.encode(key).as_bytes())
+            .map_err(|e| format!("写入 secret-tool 失败: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("等待 secret-tool 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "写入 Secret Service 失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn keyring_load() -> Result<[u8; 32], String> {
+    let output = Command::new("secret-tool")
+        .args([
+            "lookup",
+            "service",
+            KEYRING_SERVICE,
+            "account",
+            KEYRING_ACCOUNT,
+        ])
+        .output()
+        .map_err(|e| format!("调用 secret-tool 失败: {}", e))?;
+    if !output.status.success() {
+        return Err("未在 Secret Service 中找到保险库密钥".to_string());
+    }
+    decode_key(String::from_utf8_lossy(&output.stdout).trim())
+}
+
+#[cfg(target_os = "linux")]
+fn keyring_delete() -> Result<(), String> {
+    let output = Command::new("secret-tool")
+        .args([
+            "clear",
+            "service",
+            KEYRING_SERVICE,
+            "account",
+            KEYRING_ACCOUNT,
+        ])
+        .output()
+        .map_err(|e| format!("调用 secret-tool 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn dpapi_call(data: &[u8], protect: bool) -> Result<Vec<u8>, String> {
+    unsafe {
+        let mut input = CRYPT_INTEGER_BLOB {
+            cbData: data.len() as u32,
+            pbData: data.as_ptr() as *mut u8,
+        };
+        let mut output = CRYPT_INTEGER_BLOB {
+            cbData: 0,
+            pbData: std::ptr::null_mut(),
+        };
+
+        if protect {
+            CryptProtectData(
+                &mut input,
+                windows::core::PCWSTR::null(),
+                None,
+                None,
+                None,
+                0,
+                &mut output,
+            )
+            .map_err(|_| "DPAPI CryptProtectData call failed".to_string())?;
+        } else {
+            CryptUnprotectData(&mut input, None, None, None, None, 0, &mut output)
+                .map_err(|_| "DPAPI CryptUnprotectData call failed".to_string())?;
+        }
+
+        let result = std::slice::from_raw_parts(output.pbData, output.cbData as usize).to_vec();
+        LocalFree(HLOCAL(output.pbData as *mut _));
+        Ok(result)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn windows_key_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(WINDOWS_KEY_FILE))
+}
+
+#[cfg(target_os = "windows")]
+fn keyring_store(key: &[u8; 32]) -> Result<(), String> {
+    let protected = dpapi_call(key, true)?;
+    atomic_file::write(windows_key_path()?, STANDARD.encode(protected))
+        .map_err(|e| format!("保存保险库密钥失败: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+fn keyring_load() -> Result<[u8; 32], String> {
+    let path = windows_key_path()?;
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取保险库密钥失败: {}", e))?;
+    let protected = STANDARD
+        .decode(content.trim())
+        .map_err(|e| format!("解码保险库密钥失败: {}", e))?;
+    let raw = dpapi_call(&protected, false)?;
+    raw.as_slice()
+        .try_into()
+        .map_err(|_| "保险库密钥长度异常".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn keyring_delete() -> Result<(), String> {
+    let path = windows_key_path()?;
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("删除保险库密钥失败: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn keyring_store(_key: &[u8; 32]) -> Result<(), String> {
+    Err("当前系统不支持系统钥匙串".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn keyring_load() -> Result<[u8; 32], String> {
+    Err("当前系统不支持系统钥匙串".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn keyring_delete() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn decode_key(encoded: &str) -> Result<[u8; 32], String> {
+    let raw = STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("解码保险库密钥失败: {}", e))?;
+    raw.as_slice()
+        .try_into()
+        .map_err(|_| "保险库密钥长度异常".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const POINTERS: &[&str] = &["/token/access_token", "/token/refresh_token", "/missing"];
+
+    #[test]
+    fn seal_then_open_roundtrip() {
+        let key = random_bytes::<32>();
+        let original = json!({
+            "email": "a@example.com",
+            "token": { "access_token": "ya29.abc", "refresh_token": "1//xyz" }
+        });
+        let mut value = original.clone();
+        seal_value(&mut value, POINTERS, Some(&key)).unwrap();
+
+        assert_eq!(value["email"], "a@example.com");
+        assert!(is_sealed(&value["token"]["access_token"]));
+        assert!(is_sealed(&value["token"]["refresh_token"]));
+
+        open_value(&mut value, POINTERS, Some(&key));
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn seal_is_idempotent_and_keeps_ciphertext_when_locked() {
+        let key = random_bytes::<32>();
+        let mut value = json!({ "token": { "access_token": "t", "refresh_token": null } });
+        seal_value(&mut value, POINTERS, Some(&key)).unwrap();
+        let sealed = value.clone();
+
+        // 已加密字段不会重复加密，null 字段跳过，锁定状态下也可以写回
+        seal_value(&mut value, POINTERS, None).unwrap();
+        assert_eq!(value, sealed);
+
+        // 锁定时读取保留密文
+        open_value(&mut value, POINTERS, None);
+        assert_eq!(value, sealed);
+    }
+
+    #[test]
+    fn seal_rejects_plaintext_when_locked() {
+        let mut value = json!({ "token": { "access_token": "plain" } });
+        assert!(seal_value(&mut value, POINTERS, None).is_err());
+    }
+
+    #[test]
+    fn open_with_wrong_key_leaves_value_sealed() {
+        let key = random_bytes::<32>();
+        let other = random_bytes::<32>();
+        let mut value = json!({ "token": { "access_token": "t" } });
+        seal_value(&mut value, POINTERS, Some(&key)).unwrap();
+        let sealed = value.clone();
+        open_value(&mut value, POINTERS, Some(&other));
+        assert_eq!(value, sealed);
+    }
+
+    #[test]
+    fn resealed_account_dir_keeps_no_plaintext_token() {
+        let dir = std::env::temp_dir().join(format!("cockpit-vault-reseal-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("acc-1.json");
+        let account = json!({
+            "id": "acc-1",
+            "token": { "access_token": "plain-access", "refresh_token": "plain-refresh" }
+        });
+
+        // 启用前的普通写入会留下 .bak
+        atomic_file::write(&path, account.to_string()).unwrap();
+        atomic_file::write(&path, account.to_string()).unwrap();
+        fs::write(dir.join("acc-2.json.bak"), account.to_string()).unwrap();
+
+        let key = random_bytes::<32>();
+        let mut sealed = account.clone();
+        seal_value(&mut sealed, POINTERS, Some(&key)).unwrap();
+        atomic_file::write_secret(&path, sealed.to_string()).unwrap();
+        assert_eq!(remove_stale_backups(&dir).unwrap(), 1);
+
+        for entry in fs::read_dir(&dir).unwrap().flatten() {
+            let content = fs::read_to_string(entry.path()).unwrap();
+            assert!(
+                !content.contains("plain-access") && !content.contains("plain-refresh"),
+                "plaintext token left in {:?}",
+                entry.file_name()
+            );
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn locked_vault_blocks_inject_switch_and_export() {
+        use crate::modules::{
+            cursor_instance, github_copilot_instance, kiro_instance, windsurf_instance,
+        };
+
+        let meta = VaultMeta {
+            version: VAULT_META_VERSION,
+            key_source: VaultKeySource::Passphrase,
+            salt: Some(STANDARD.encode(random_bytes::<16>())),
+            kdf_iterations: Some(1),
+            check: encrypt_bytes(&random_bytes::<32>(), CHECK_PLAINTEXT.as_bytes()).unwrap(),
+            created_at: 0,
+            migrated_at: Some(0),
+        };
+        set_state(Some(meta), None);
+
+        let dir = std::env::temp_dir().join(format!("cockpit-vault-locked-{}", std::process::id()));
+        let dir_text = dir.to_string_lossy().to_string();
+        let ids = vec!["missing".to_string()];
+        let results: Vec<Result<(), String>> = vec![
+            kiro_instance::inject_account_to_profile(&dir, "missing"),
+            windsurf_instance::inject_account_to_profile(&dir, "missing"),
+            cursor_instance::inject_account_to_profile(&dir, "missing"),
+            kiro_instance::inject_bound_account(&dir_text, Some("missing")),
+            github_copilot_instance::inject_bound_account(&dir_text, Some("missing")),
+            kiro_account::switch_account_internal("missing").map(|_| ()),
+            windsurf_account::switch_account_internal("missing").map(|_| ()),
+            cursor_account::switch_account_internal("missing").map(|_| ()),
+            github_copilot_account::switch_account_internal("missing").map(|_| ()),
+            account::export_accounts(&ids).map(|_| ()),
+            codex_account::export_accounts(&ids).map(|_| ()),
+            kiro_account::export_accounts(&ids).map(|_| ()),
+            windsurf_account::export_accounts(&ids).map(|_| ()),
+            cursor_account::export_accounts(&ids).map(|_| ()),
+            github_copilot_account::export_accounts(&ids).map(|_| ()),
+        ];
+        *VAULT_STATE.write().unwrap_or_else(|e| e.into_inner()) = VaultState::default();
+
+        for result in results {
+            assert_eq!(result, Err(LOCKED_ERROR.to_string()));
+        }
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn passphrase_check_value_verifies_key() {
+        let salt = random_bytes::<16>();
+        let key = derive_key("correct horse", &salt, 1_000);
+        let meta = VaultMeta {
+            version: VAULT_META_VERSION,
+            key_source: VaultKeySource::Passphrase,
+            salt: Some(STANDARD.encode(salt)),
+            kdf_iterations: Some(1_000),
+            check: encrypt_bytes(&key, CHECK_PLAINTEXT.as_bytes()).unwrap(),
+            created_at: 0,
+            migrated_at: None,
+        };
+        assert!(verify_key(
+            &meta,
+            &passphrase_key(&meta, "correct horse").unwrap()
+        ));
+        assert!(!verify_key(
+            &meta,
+            &passphrase_key(&meta, "wrong horse").unwrap()
+        ));
+    }
+}
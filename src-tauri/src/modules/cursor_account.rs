@@ -0,0 +1,722 @@
+use futures::future::BoxFuture;
+use rusqlite::{Connection, OptionalExtension};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::models::cursor::{CursorAccount, CursorAccountIndex, CursorLocalAuth};
+use crate::modules::account_store::{self, StoreSpec};
+use crate::modules::cursor_usage::{self, CursorUsageSnapshot};
+use crate::modules::platform::{self, Platform, PlatformAccount, QuotaMetric, UnifiedQuota};
+use crate::modules::quota_cache::{self, QuotaApiError, Refreshed};
+use crate::modules::unified_quota::QuotaWindow;
+use crate::modules::refresh_executor::{self, RefreshReport, RefreshRun};
+use crate::modules::schema_migration::{self, SchemaSpec, VersionEncoding};
+use crate::modules::{
+    account, credential_vault, cursor_instance, logger, quota_events, quota_history, store_fsck,
+};
+use crate::utils::atomic_file;
+
+const ACCOUNTS_INDEX_FILE: &str = "cursor_accounts.json";
+const ACCOUNTS_DIR: &str = "cursor_accounts";
+
+/// cursor_accounts.json 的格式版本
+const INDEX_SCHEMA: SchemaSpec = SchemaSpec {
+    name: ACCOUNTS_INDEX_FILE,
+    version_field: "version",
+    encoding: VersionEncoding::Dotted,
+    current: 1,
+    baseline: 1,
+    migrations: &[],
+};
+
+/// state.vscdb 中 Cursor 登录信息所在的键
+pub const LOCAL_ACCESS_TOKEN_KEY: &str = "cursorAuth/accessToken";
+pub const LOCAL_REFRESH_TOKEN_KEY: &str = "cursorAuth/refreshToken";
+pub const LOCAL_EMAIL_KEY: &str = "cursorAuth/cachedEmail";
+pub const LOCAL_SIGN_UP_TYPE_KEY: &str = "cursorAuth/cachedSignUpType";
+pub const LOCAL_MEMBERSHIP_TYPE_KEY: &str = "cursorAuth/stripeMembershipType";
+
+const STATUS_EXPIRED: &str = "expired";
+
+static CURSOR_ACCOUNT_INDEX_LOCK: Mutex<()> = Mutex::new(());
+/// 账号文件中需要由凭据保险库加密的字段
+const SECRET_FIELDS: &[&str] = &["/access_token", "/refresh_token"];
+
+pub const STORE_SPEC: StoreSpec = StoreSpec {
+    platform: "cursor",
+    base_dir: get_data_dir,
+    index_file: ACCOUNTS_INDEX_FILE,
+    accounts_dir: ACCOUNTS_DIR,
+    secret_fields: SECRET_FIELDS,
+    quota_fields: &[
+        "premium_requests_used",
+        "premium_requests_limit",
+        "usage_reset_at",
+        "cursor_usage_raw",
+    ],
+    mirror_index: false,
+};
+
+fn now_ts() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn get_data_dir() -> Result<PathBuf, String> {
+    account::get_data_dir()
+}
+
+pub fn get_accounts_dir() -> Result<PathBuf, String> {
+    let base = get_data_dir()?;
+    let dir = base.join(ACCOUNTS_DIR);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| format!("创建 Cursor 账号目录失败: {}", e))?;
+    }
+    Ok(dir)
+}
+
+fn get_accounts_index_path() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join(ACCOUNTS_INDEX_FILE))
+}
+
+pub fn accounts_index_path_string() -> Result<String, String> {
+    Ok(get_accounts_index_path()?.to_string_lossy().to_string())
+}
+
+pub fn load_account(account_id: &str) -> Option<CursorAccount> {
+    if account_store::is_active() {
+        return account_store::load_record(&STORE_SPEC, account_id)
+            .ok()
+            .flatten();
+    }
+
+    let account_path = get_accounts_dir()
+        .ok()
+        .map(|dir| dir.join(format!("{}.json", account_id)))?;
+    if !account_path.exists() {
+        return None;
+    }
+    let content = fs::read_to_string(account_path).ok()?;
+    credential_vault::from_sealed_json(&content, SECRET_FIELDS).ok()
+}
+
+fn save_account_file(account: &CursorAccount) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_record(&STORE_SPEC, &account.id, account);
+    }
+
+    let path = get_accounts_dir()?.join(format!("{}.json", account.id));
+    let content = credential_vault::to_sealed_json(account, SECRET_FIELDS)?;
+    atomic_file::write_secret(path, content).map_err(|e| format!("保存账号失败: {}", e))
+}
+
+fn delete_account_file(account_id: &str) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::delete_record(&STORE_SPEC, account_id);
+    }
+
+    let path = get_accounts_dir()?.join(format!("{}.json", account_id));
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("删除账号文件失败: {}", e))?;
+    }
+    Ok(())
+}
+
+fn load_account_index() -> CursorAccountIndex {
+    if account_store::is_active() {
+        return account_store::load_index(&STORE_SPEC)
+            .unwrap_or_else(|e| {
+                logger::log_error(&format!("读取 Cursor 账号索引失败: {}", e));
+                None
+            })
+            .unwrap_or_default();
+    }
+
+    let path = match get_accounts_index_path() {
+        Ok(p) => p,
+        Err(_) => return CursorAccountIndex::new(),
+    };
+
+    if !path.exists() {
+        return CursorAccountIndex::new();
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(content) => {
+            let content = schema_migration::upgrade_on_load(&INDEX_SCHEMA, &path, content);
+            serde_json::from_str(&content).unwrap_or_else(|_| CursorAccountIndex::new())
+        }
+        Err(_) => CursorAccountIndex::new(),
+    }
+}
+
+fn save_account_index(index: &CursorAccountIndex) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_index(&STORE_SPEC, index);
+    }
+
+    let path = get_accounts_index_path()?;
+    schema_migration::ensure_writable(&INDEX_SCHEMA, &path)?;
+    let content =
+        serde_json::to_string_pretty(index).map_err(|e| format!("序列化账号索引失败: {}", e))?;
+    atomic_file::write(path, content).map_err(|e| format!("写入账号索引失败: {}", e))
+}
+
+fn refresh_summary(index: &mut CursorAccountIndex, account: &CursorAccount) {
+    if let Some(summary) = index.accounts.iter_mut().find(|item| item.id == account.id) {
+        *summary = account.summary();
+        return;
+    }
+    index.accounts.push(account.summary());
+}
+
+fn upsert_account_record(account: CursorAccount) -> Result<CursorAccount, String> {
+    let _lock = CURSOR_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Cursor 账号锁失败".to_string())?;
+    let mut index = load_account_index();
+    refresh_summary(&mut index, &account);
+    account_store::write_batch(|| {
+        save_account_file(&account)?;
+        save_account_index(&index)
+    })?;
+    Ok(account)
+}
+
+fn normalize_non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(|text| text.to_string())
+}
+
+fn normalize_email(value: Option<&str>) -> Option<String> {
+    normalize_non_empty(value)
+        .filter(|email| email.contains('@'))
+        .map(|email| email.to_lowercase())
+}
+
+fn account_matches_identity(
+    account: &CursorAccount,
+    user_id: Option<&str>,
+    email: Option<&str>,
+) -> bool {
+    if let (Some(incoming), Some(existing)) = (user_id, account.user_id.as_deref()) {
+        return incoming == existing;
+    }
+    match (email, normalize_email(Some(account.email.as_str()))) {
+        (Some(incoming), Some(existing)) => incoming == existing,
+        _ => false,
+    }
+}
+
+fn is_expired_account(account: &CursorAccount) -> bool {
+    account.status.as_deref() == Some(STATUS_EXPIRED)
+}
+
+pub fn list_accounts() -> Vec<CursorAccount> {
+    let index = load_account_index();
+    index
+        .accounts
+        .iter()
+        .filter_map(|summary| load_account(&summary.id))
+        .collect()
+}
+
+/// 以账号文件为准重建索引（一致性修复使用），返回索引中的账号数
+pub fn rebuild_account_index() -> Result<usize, String> {
+    let _lock = CURSOR_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Cursor 账号锁失败".to_string())?;
+    let previous = store_fsck::read_index(&STORE_SPEC).unwrap_or_default();
+    let accounts: Vec<CursorAccount> = store_fsck::list_stored_ids(&STORE_SPEC)?
+        .iter()
+        .filter_map(|id| load_account(id))
+        .collect();
+    let accounts =
+        store_fsck::order_by_index(accounts, &previous.account_ids, |a| &a.id, |a| a.created_at);
+
+    let mut index = CursorAccountIndex::new();
+    index.accounts = accounts.iter().map(CursorAccount::summary).collect();
+    save_account_index(&index)?;
+    Ok(index.accounts.len())
+}
+
+/// 按凭据保险库当前状态重新写回所有账号文件
+pub fn reseal_account_files() -> Result<usize, String> {
+    let _lock = CURSOR_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Cursor 账号锁失败".to_string())?;
+    let index = load_account_index();
+    let mut count = 0;
+    for summary in &index.accounts {
+        if let Some(account) = load_account(&summary.id) {
+            save_account_file(&account)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// 保存从本机读取到的登录信息；按用户 ID / 邮箱合并到已有账号
+pub fn upsert_from_local_auth(auth: CursorLocalAuth) -> Result<CursorAccount, String> {
+    let _lock = CURSOR_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Cursor 账号锁失败".to_string())?;
+
+    let now = now_ts();
+    let mut index = load_account_index();
+    let user_id = cursor_usage::extract_user_id(&auth.access_token);
+    let email = normalize_email(auth.email.as_deref());
+    let identity = user_id
+        .clone()
+        .or_else(|| email.clone())
+        .unwrap_or_else(|| auth.access_token.clone());
+    let generated_id = format!("cursor_{:x}", md5::compute(identity));
+
+    let existing = index
+        .accounts
+        .iter()
+        .filter_map(|item| load_account(&item.id))
+        .find(|account| account_matches_identity(account, user_id.as_deref(), email.as_deref()));
+    let account_id = existing
+        .as_ref()
+        .map(|account| account.id.clone())
+        .unwrap_or(generated_id);
+
+    let mut account = existing.unwrap_or(CursorAccount {
+        id: account_id.clone(),
+        email: String::new(),
+        user_id: None,
+        sign_up_type: None,
+        tags: None,
+        access_token: String::new(),
+        refresh_token: None,
+        membership_type: None,
+        premium_requests_used: None,
+        premium_requests_limit: None,
+        usage_reset_at: None,
+        cursor_usage_raw: None,
+        status: None,
+        status_reason: None,
+        created_at: now,
+        last_used: now,
+    });
+
+    account.access_token = auth.access_token;
+    if auth.refresh_token.is_some() {
+        account.refresh_token = auth.refresh_token;
+    }
+    if let Some(email) = email {
+        account.email = email;
+    }
+    if user_id.is_some() {
+        account.user_id = user_id;
+    }
+    if auth.sign_up_type.is_some() {
+        account.sign_up_type = auth.sign_up_type;
+    }
+    if auth.membership_type.is_some() {
+        account.membership_type = auth.membership_type;
+    }
+    account.status = None;
+    account.status_reason = None;
+    account.last_used = now;
+
+    refresh_summary(&mut index, &account);
+    account_store::write_batch(|| {
+        save_account_file(&account)?;
+        save_account_index(&index)
+    })?;
+
+    logger::log_info(&format!(
+        "Cursor 账号已保存: id={}, email={}",
+        account.id, account.email
+    ));
+    Ok(account)
+}
+
+fn apply_usage(account: &mut CursorAccount, usage: CursorUsageSnapshot) {
+    account.premium_requests_used = usage.premium_requests_used;
+    account.premium_requests_limit = usage.premium_requests_limit;
+    account.usage_reset_at = usage.usage_reset_at;
+    if usage.membership_type.is_some() {
+        account.membership_type = usage.membership_type;
+    }
+    account.cursor_usage_raw = Some(usage.raw);
+    account.status = None;
+    account.status_reason = None;
+}
+
+/// 刷新账号用量；`skip_cache` 为 true 时忽略配额缓存有效期（单个账号的手动刷新）
+pub async fn refresh_account_token(
+    account_id: &str,
+    skip_cache: bool,
+) -> Result<CursorAccount, String> {
+    credential_vault::ensure_unlocked()?;
+    let started_at = Instant::now();
+    let mut account = load_account(account_id).ok_or_else(|| "账号不存在".to_string())?;
+    if quota_cache::is_offline() {
+        // 离线模式：不发起请求，沿用上次保存的数据
+        return Ok(account);
+    }
+    logger::log_info(&format!(
+        "[Cursor Refresh] 开始刷新账号: id={}, email={}",
+        account.id, account.email
+    ));
+
+    let user_id = account
+        .user_id
+        .clone()
+        .or_else(|| cursor_usage::extract_user_id(&account.access_token))
+        .ok_or_else(|| "无法从 access token 中解析 Cursor 用户 ID".to_string())?;
+
+    let request = async {
+        cursor_usage::fetch_usage(&user_id, &account.access_token)
+            .await
+            .map_err(|err| {
+                if cursor_usage::is_auth_expired_error(&err) {
+                    QuotaApiError::Rejected {
+                        status: 401,
+                        message: err,
+                    }
+                } else {
+                    QuotaApiError::Transient(err)
+                }
+            })
+    };
+    let refreshed = quota_cache::refresh(
+        CursorPlatform.id(),
+        &account.id,
+        None,
+        skip_cache,
+        request,
+        |usage: &CursorUsageSnapshot| serde_json::to_value(usage).unwrap_or_default(),
+    )
+    .await;
+    let usage = match refreshed {
+        Ok(Refreshed::Fetched(usage)) => Ok(usage),
+        Ok(Refreshed::Cached(cached)) => serde_json::from_value(cached.payload)
+            .map_err(|e| format!("解析 Cursor 用量缓存失败: {}", e)),
+        Err(e) => Err(e.to_string()),
+    };
+    match usage {
+        Ok(usage) => {
+            account.user_id = Some(user_id);
+            apply_usage(&mut account, usage);
+        }
+        Err(err) => {
+            logger::log_warn(&format!(
+                "[Cursor Refresh] 刷新失败: id={}, email={}, error={}",
+                account.id, account.email, err
+            ));
+            if cursor_usage::is_auth_expired_error(&err) {
+                account.status = Some(STATUS_EXPIRED.to_string());
+                account.status_reason = Some(err.clone());
+                upsert_account_record(account)?;
+            }
+            return Err(err);
+        }
+    }
+
+    let updated = account.clone();
+    upsert_account_record(account)?;
+    logger::log_info(&format!(
+        "[Cursor Refresh] 刷新完成: id={}, email={}, elapsed={}ms",
+        updated.id,
+        updated.email,
+        started_at.elapsed().as_millis()
+    ));
+    Ok(updated)
+}
+
+/// 批量刷新时按此主机限速
+const REFRESH_HOST: &str = "www.cursor.com";
+
+pub async fn refresh_all_tokens() -> Result<RefreshRun<CursorAccount>, String> {
+    let account_ids = list_accounts().into_iter().map(|account| account.id).collect();
+    let run = refresh_executor::run(CursorPlatform.id(), REFRESH_HOST, account_ids, |id| async move {
+        refresh_account_token(&id, false).await
+    })
+    .await;
+
+    quota_history::record_refreshed_logged(
+        &CursorPlatform,
+        &quota_history::succeeded_ids(&run.results),
+    );
+    quota_events::publish_logged(&CursorPlatform);
+    Ok(run)
+}
+
+pub fn remove_account(account_id: &str) -> Result<(), String> {
+    let _lock = CURSOR_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Cursor 账号锁失败".to_string())?;
+    let mut index = load_account_index();
+    index.accounts.retain(|item| item.id != account_id);
+    account_store::write_batch(|| {
+        save_account_index(&index)?;
+        delete_account_file(account_id)
+    })
+}
+
+pub fn remove_accounts(account_ids: &[String]) -> Result<(), String> {
+    for id in account_ids {
+        remove_account(id)?;
+    }
+    Ok(())
+}
+
+pub fn update_account_tags(account_id: &str, tags: Vec<String>) -> Result<CursorAccount, String> {
+    let mut account = load_account(account_id).ok_or_else(|| "账号不存在".to_string())?;
+    account.tags = Some(tags);
+    account.last_used = now_ts();
+    let updated = account.clone();
+    upsert_account_record(account)?;
+    Ok(updated)
+}
+
+pub fn import_from_json(json_content: &str) -> Result<Vec<CursorAccount>, String> {
+    if let Ok(account) = serde_json::from_str::<CursorAccount>(json_content) {
+        let saved = upsert_account_record(account)?;
+        return Ok(vec![saved]);
+    }
+
+    if let Ok(accounts) = serde_json::from_str::<Vec<CursorAccount>>(json_content) {
+        let mut result = Vec::new();
+        for account in accounts {
+            let saved = upsert_account_record(account)?;
+            result.push(saved);
+        }
+        return Ok(result);
+    }
+
+    Err("无法解析 JSON 内容".to_string())
+}
+
+pub fn export_accounts(account_ids: &[String]) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
+    let accounts: Vec<CursorAccount> = account_ids
+        .iter()
+        .filter_map(|id| load_account(id))
+        .collect();
+    serde_json::to_string_pretty(&accounts).map_err(|e| format!("序列化失败: {}", e))
+}
+
+pub fn get_default_state_db_path() -> Result<PathBuf, String> {
+    Ok(cursor_instance::get_default_cursor_user_data_dir()?
+        .join("User")
+        .join("globalStorage")
+        .join("state.vscdb"))
+}
+
+fn read_item(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    let value = conn
+        .query_row("SELECT value FROM ItemTable WHERE key = ?1", [key], |row| {
+            row.get::<_, String>(0)
+        })
+        .optional()
+        .map_err(|e| format!("读取 {} 失败: {}", key, e))?;
+    Ok(normalize_non_empty(value.as_deref()))
+}
+
+/// 读取本机 Cursor 客户端当前登录信息；未登录时返回 None
+pub fn read_local_auth() -> Result<Option<CursorLocalAuth>, String> {
+    let db_path = get_default_state_db_path()?;
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let conn =
+        Connection::open(&db_path).map_err(|e| format!("打开 Cursor 本地数据库失败: {}", e))?;
+
+    let Some(access_token) = read_item(&conn, LOCAL_ACCESS_TOKEN_KEY)? else {
+        return Ok(None);
+    };
+    Ok(Some(CursorLocalAuth {
+        access_token,
+        refresh_token: read_item(&conn, LOCAL_REFRESH_TOKEN_KEY)?,
+        email: read_item(&conn, LOCAL_EMAIL_KEY)?,
+        sign_up_type: read_item(&conn, LOCAL_SIGN_UP_TYPE_KEY)?,
+        membership_type: read_item(&conn, LOCAL_MEMBERSHIP_TYPE_KEY)?,
+    }))
+}
+
+/// 统一配额视图：高级请求用量，按自然月重置
+pub fn unified_quota(account: &CursorAccount) -> UnifiedQuota {
+    let metrics = QuotaMetric::from_usage(
+        "premium_requests",
+        "Premium Requests",
+        account.premium_requests_used,
+        account.premium_requests_limit,
+    )
+    .map(|metric| {
+        metric
+            .with_window(Some(QuotaWindow::BillingCycle))
+            .with_resets_at(account.usage_reset_at)
+    });
+    UnifiedQuota::new(metrics.into_iter().collect())
+}
+
+fn resolve_current_account_id(accounts: &[PlatformAccount]) -> Option<String> {
+    if let Ok(settings) = cursor_instance::load_default_settings() {
+        if let Some(bind_id) = settings.bind_account_id {
+            let trimmed = bind_id.trim();
+            if !trimmed.is_empty() {
+                return Some(trimmed.to_string());
+            }
+        }
+    }
+
+    platform::most_recently_used(accounts)
+}
+
+fn display_email(account: &CursorAccount) -> String {
+    let trimmed = account.email.trim();
+    if trimmed.is_empty() {
+        account.id.clone()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+pub fn run_quota_alert_if_needed(
+) -> Result<Vec<crate::modules::account::QuotaAlertPayload>, String> {
+    crate::modules::alert_rules::run(&CursorPlatform)
+}
+
+fn to_platform_account(account: &CursorAccount) -> PlatformAccount {
+    // 登录失效的账号不参与预警与推荐
+    let expired = is_expired_account(account);
+    let quota = if expired {
+        UnifiedQuota::default()
+    } else {
+        unified_quota(account)
+    };
+    PlatformAccount {
+        platform: CursorPlatform.id().to_string(),
+        id: account.id.clone(),
+        email: display_email(account),
+        tags: account.tags.clone().unwrap_or_default(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+        disabled: expired,
+        forbidden: false,
+        quota_error: None,
+        token_expires_at: None,
+        quota_stale: false,
+        quota,
+    }
+}
+
+/// 切换账号：更新默认实例绑定账号并重启 Cursor 默认实例（启动失败不阻断切号），返回切换结果说明
+pub fn switch_account_internal(account_id: &str) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
+    let started_at = Instant::now();
+    logger::log_info(&format!(
+        "[Cursor Switch] 开始切换账号: account_id={}",
+        account_id
+    ));
+
+    let account = load_account(account_id)
+        .ok_or_else(|| format!("Cursor account not found: {}", account_id))?;
+
+    if let Err(err) = cursor_instance::update_default_settings(
+        Some(Some(account_id.to_string())),
+        None,
+        Some(false),
+    ) {
+        logger::log_warn(&format!("更新 Cursor 默认实例绑定账号失败: {}", err));
+    }
+
+    let launch_warning = match cursor_instance::start_default_instance() {
+        Ok(_) => None,
+        Err(err) if err.starts_with("APP_PATH_NOT_FOUND:") || err.contains("启动 Cursor 失败") =>
+        {
+            logger::log_warn(&format!("Cursor 默认实例启动失败: {}", err));
+            Some(err)
+        }
+        Err(err) => return Err(err),
+    };
+
+    platform::notify_account_switched("cursor", launch_warning.as_deref());
+
+    if let Some(err) = launch_warning {
+        logger::log_warn(&format!(
+            "[Cursor Switch] 切号完成但启动失败: account_id={}, email={}, elapsed={}ms, error={}",
+            account.id,
+            account.email,
+            started_at.elapsed().as_millis(),
+            err
+        ));
+        Ok(format!("切换完成，但 Cursor 启动失败: {}", err))
+    } else {
+        logger::log_info(&format!(
+            "[Cursor Switch] 切号成功: account_id={}, email={}, elapsed={}ms",
+            account.id,
+            account.email,
+            started_at.elapsed().as_millis()
+        ));
+        Ok(format!("切换完成: {}", account.email))
+    }
+}
+
+pub struct CursorPlatform;
+
+impl Platform for CursorPlatform {
+    fn id(&self) -> &'static str {
+        "cursor"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Cursor"
+    }
+
+    fn list_accounts(&self) -> Result<Vec<PlatformAccount>, String> {
+        Ok(list_accounts().iter().map(to_platform_account).collect())
+    }
+
+    fn current_account_id(&self, accounts: &[PlatformAccount]) -> Option<String> {
+        resolve_current_account_id(accounts)
+    }
+
+    fn remove_accounts(&self, account_ids: &[String]) -> Result<(), String> {
+        remove_accounts(account_ids)
+    }
+
+    fn update_account_tags(&self, account_id: &str, tags: Vec<String>) -> Result<(), String> {
+        update_account_tags(account_id, tags).map(|_| ())
+    }
+
+    fn export_accounts(&self, account_ids: &[String]) -> Result<String, String> {
+        export_accounts(account_ids)
+    }
+
+    fn import_from_json(&self, json_content: String) -> BoxFuture<'static, Result<usize, String>> {
+        Box::pin(async move { import_from_json(&json_content).map(|accounts| accounts.len()) })
+    }
+
+    fn switch_account(&self, account_id: String) -> BoxFuture<'static, Result<String, String>> {
+        Box::pin(async move { switch_account_internal(&account_id) })
+    }
+
+    fn refresh_account(&self, account_id: String) -> BoxFuture<'static, Result<(), String>> {
+        Box::pin(async move { refresh_account_token(&account_id, true).await.map(|_| ()) })
+    }
+
+    fn refresh_all(&self) -> BoxFuture<'static, Result<RefreshReport, String>> {
+        Box::pin(async move { Ok(refresh_all_tokens().await?.report) })
+    }
+
+    fn refresh_interval_minutes(&self) -> i32 {
+        crate::modules::config::get_user_config().cursor_auto_refresh_minutes
+    }
+
+    fn quota_alert_settings(&self) -> platform::QuotaAlertSettings {
+        let cfg = crate::modules::config::get_user_config();
+        platform::QuotaAlertSettings {
+            enabled: cfg.cursor_quota_alert_enabled,
+            threshold: cfg.cursor_quota_alert_threshold,
+        }
+    }
+}
@@ -0,0 +1,693 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use sysinfo::{ProcessRefreshKind, System, UpdateKind};
+
+use crate::models::{DefaultInstanceSettings, InstanceProfile, InstanceStore};
+use crate::modules;
+use crate::modules::instance::InstanceDefaults;
+use crate::modules::instance_manager::{
+    self, extract_user_data_dir, is_helper_process, normalize_path_for_compare, InstanceBackend,
+    InstanceManager,
+};
+use crate::modules::{cursor_account, db};
+
+pub use crate::modules::instance_store::{CreateInstanceParams, UpdateInstanceParams};
+
+static CURSOR_INSTANCE_STORE_LOCK: Mutex<()> = Mutex::new(());
+
+const CURSOR_INSTANCES_FILE: &str = "cursor_instances.json";
+
+pub struct CursorInstances;
+
+impl InstanceBackend for CursorInstances {
+    fn display_name(&self) -> &'static str {
+        "Cursor"
+    }
+
+    fn store_file(&self) -> &'static str {
+        CURSOR_INSTANCES_FILE
+    }
+
+    fn store_lock(&self) -> &'static Mutex<()> {
+        &CURSOR_INSTANCE_STORE_LOCK
+    }
+
+    fn default_user_data_dir(&self) -> Result<PathBuf, String> {
+        get_default_cursor_user_data_dir()
+    }
+
+    fn instances_root_dir(&self) -> Result<PathBuf, String> {
+        get_default_instances_root_dir()
+    }
+
+    fn supports_follow_local_account(&self) -> bool {
+        false
+    }
+}
+
+pub fn load_instance_store() -> Result<InstanceStore, String> {
+    CursorInstances.load_instance_store()
+}
+
+pub fn save_instance_store(store: &InstanceStore) -> Result<(), String> {
+    CursorInstances.save_instance_store(store)
+}
+
+pub fn load_default_settings() -> Result<DefaultInstanceSettings, String> {
+    CursorInstances.load_default_settings()
+}
+
+pub fn update_default_settings(
+    bind_account_id: Option<Option<String>>,
+    extra_args: Option<String>,
+    follow_local_account: Option<bool>,
+) -> Result<DefaultInstanceSettings, String> {
+    CursorInstances.update_default_settings(bind_account_id, extra_args, follow_local_account)
+}
+
+pub fn get_default_cursor_user_data_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "macos")]
+    {
+        let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
+        return Ok(home.join("Library/Application Support/Cursor"));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let appdata =
+            std::env::var("APPDATA").map_err(|_| "无法获取 APPDATA 环境变量".to_string())?;
+        return Ok(PathBuf::from(appdata).join("Cursor"));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
+        return Ok(home.join(".config/Cursor"));
+    }
+
+    #[allow(unreachable_code)]
+    Err("Cursor 多开实例仅支持 macOS、Windows 和 Linux".to_string())
+}
+
+pub fn get_default_instances_root_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "macos")]
+    {
+        let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
+        return Ok(home.join(".antigravity_cockpit/instances/cursor"));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let appdata =
+            std::env::var("APPDATA").map_err(|_| "无法获取 APPDATA 环境变量".to_string())?;
+        return Ok(PathBuf::from(appdata).join(".antigravity_cockpit\\instances\\cursor"));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
+        return Ok(home.join(".antigravity_cockpit/instances/cursor"));
+    }
+
+    #[allow(unreachable_code)]
+    Err("Cursor 多开实例仅支持 macOS、Windows 和 Linux".to_string())
+}
+
+pub fn get_instance_defaults() -> Result<InstanceDefaults, String> {
+    CursorInstances.get_instance_defaults()
+}
+
+pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile, String> {
+    CursorInstances.create_instance(params)
+}
+
+pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile, String> {
+    CursorInstances.update_instance(params)
+}
+
+pub fn delete_instance(instance_id: &str) -> Result<(), String> {
+    CursorInstances.delete_instance(instance_id)
+}
+
+pub fn update_instance_after_start(instance_id: &str, pid: u32) -> Result<InstanceProfile, String> {
+    CursorInstances.update_instance_after_start(instance_id, pid)
+}
+
+pub fn update_instance_pid(instance_id: &str, pid: Option<u32>) -> Result<InstanceProfile, String> {
+    CursorInstances.update_instance_pid(instance_id, pid)
+}
+
+pub fn update_default_pid(pid: Option<u32>) -> Result<DefaultInstanceSettings, String> {
+    CursorInstances.update_default_pid(pid)
+}
+
+pub fn clear_all_pids() -> Result<(), String> {
+    CursorInstances.clear_all_pids()
+}
+
+fn resolve_expected_cursor_launch_path_for_match() -> Option<String> {
+    let launch_path = match resolve_cursor_launch_path() {
+        Ok(path) => path,
+        Err(err) => {
+            modules::logger::log_warn(&format!(
+                "[Cursor Resolve] 启动路径未配置或无效，跳过 PID 匹配: {}",
+                err
+            ));
+            return None;
+        }
+    };
+    let normalized = normalize_path_for_compare(launch_path.to_string_lossy().as_ref());
+    if normalized.is_empty() {
+        modules::logger::log_warn("[Cursor Resolve] 启动路径为空，跳过 PID 匹配");
+        return None;
+    }
+    Some(normalized)
+}
+
+pub fn collect_cursor_process_entries() -> Vec<(u32, Option<String>)> {
+    let expected_launch = resolve_expected_cursor_launch_path_for_match();
+    if expected_launch.is_none() {
+        return Vec::new();
+    }
+
+    let mut entries: HashMap<u32, Option<String>> = HashMap::new();
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet),
+    );
+    let current_pid = std::process::id();
+
+    for (pid, process) in system.processes() {
+        let pid_u32 = pid.as_u32();
+        if pid_u32 == current_pid {
+            continue;
+        }
+
+        let name = process.name().to_string_lossy().to_lowercase();
+        let exe_path = process
+            .exe()
+            .and_then(|p| p.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let args_line = process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_lowercase())
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        #[cfg(target_os = "macos")]
+        let is_cursor = exe_path.contains("cursor.app/contents/");
+        #[cfg(target_os = "windows")]
+        let is_cursor = name == "cursor.exe"
+            || exe_path.ends_with("\\cursor.exe")
+            || (name == "electron.exe" && exe_path.contains("\\cursor\\"));
+        #[cfg(target_os = "linux")]
+        let is_cursor = name == "cursor"
+            || exe_path.ends_with("/cursor")
+            || exe_path.contains("/cursor/")
+            || exe_path.contains("cursor.appimage");
+
+        if !is_cursor || is_helper_process(&name, &args_line) {
+            continue;
+        }
+
+        let dir = extract_user_data_dir(process.cmd()).and_then(|value| {
+            let normalized = normalize_path_for_compare(&value);
+            if normalized.is_empty() {
+                None
+            } else {
+                Some(normalized)
+            }
+        });
+        entries.insert(pid_u32, dir);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(output) = Command::new("ps").args(["-axo", "pid,command"]).output() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for line in stdout.lines().skip(1) {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let mut parts = line.splitn(2, |ch: char| ch.is_whitespace());
+                let pid_str = parts.next().unwrap_or("").trim();
+                let cmdline = parts.next().unwrap_or("").trim();
+                let pid = match pid_str.parse::<u32>() {
+                    Ok(value) => value,
+                    Err(_) => continue,
+                };
+                let lower = cmdline.to_lowercase();
+                if !lower.contains("cursor.app/contents/") || lower.contains("--type=") {
+                    continue;
+                }
+                let dir = instance_manager::extract_user_data_dir_from_command_line(cmdline)
+                    .and_then(|value| {
+                        let normalized = normalize_path_for_compare(&value);
+                        if normalized.is_empty() {
+                            None
+                        } else {
+                            Some(normalized)
+                        }
+                    });
+                entries.entry(pid).or_insert(dir);
+            }
+        }
+    }
+
+    let mut result: Vec<(u32, Option<String>)> = entries.into_iter().collect();
+    result.sort_by_key(|(pid, _)| *pid);
+    instance_manager::filter_entries_by_launch_path(result, expected_launch, "Cursor")
+}
+
+pub fn resolve_cursor_pid_from_entries(
+    last_pid: Option<u32>,
+    user_data_dir: Option<&str>,
+    entries: &[(u32, Option<String>)],
+) -> Option<u32> {
+    CursorInstances.resolve_pid_from_entries(last_pid, user_data_dir, entries)
+}
+
+pub fn resolve_cursor_pid(last_pid: Option<u32>, user_data_dir: Option<&str>) -> Option<u32> {
+    let entries = collect_cursor_process_entries();
+    resolve_cursor_pid_from_entries(last_pid, user_data_dir, &entries)
+}
+
+pub fn focus_cursor_instance(
+    last_pid: Option<u32>,
+    user_data_dir: Option<&str>,
+) -> Result<u32, String> {
+    let pid = resolve_cursor_pid(last_pid, user_data_dir)
+        .ok_or_else(|| "实例未运行，无法定位窗口".to_string())?;
+    modules::process::focus_window_by_pid(pid)?;
+    Ok(pid)
+}
+
+fn normalize_custom_path(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn normalize_macos_app_root(path: &Path) -> Option<String> {
+    let path_str = path.to_string_lossy();
+    if let Some(index) = path_str.find(".app") {
+        return Some(path_str[..index + 4].to_string());
+    }
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn resolve_macos_exec_path(path_str: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(path_str);
+    if let Some(app_root) = normalize_macos_app_root(&path) {
+        let cursor_exec = PathBuf::from(&app_root)
+            .join("Contents")
+            .join("MacOS")
+            .join("Cursor");
+        if cursor_exec.exists() {
+            return Some(cursor_exec);
+        }
+        let electron_exec = PathBuf::from(&app_root)
+            .join("Contents")
+            .join("MacOS")
+            .join("Electron");
+        if electron_exec.exists() {
+            return Some(electron_exec);
+        }
+    }
+    if path.exists() {
+        return Some(path);
+    }
+    None
+}
+
+#[cfg(not(target_os = "macos"))]
+fn resolve_macos_exec_path(path_str: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(path_str);
+    if path.exists() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn detect_cursor_exec_path() -> Option<PathBuf> {
+    for (pid, _) in collect_cursor_process_entries() {
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            sysinfo::ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+        );
+        if let Some(process) = system.process(sysinfo::Pid::from(pid as usize)) {
+            if let Some(path) = process.exe() {
+                return Some(path.to_path_buf());
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let candidates = ["/Applications/Cursor.app/Contents/MacOS/Cursor"];
+        for candidate in candidates {
+            let path = PathBuf::from(candidate);
+            if path.exists() {
+                return Some(path);
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        if let Ok(local_appdata) = std::env::var("LOCALAPPDATA") {
+            candidates.push(
+                Path::new(&local_appdata)
+                    .join("Programs")
+                    .join("Cursor")
+                    .join("Cursor.exe"),
+            );
+            candidates.push(
+                Path::new(&local_appdata)
+                    .join("Programs")
+                    .join("Cursor")
+                    .join("Electron.exe"),
+            );
+        }
+        for candidate in candidates {
+            if candidate.exists() {
+                return Some(candidate);
+            }
+        }
+        if let Some(path) = modules::process::detect_windows_exec_path_by_signatures(
+            "cursor",
+            &["Cursor.exe", "Electron.exe"],
+            &["cursor"],
+            &["cursor"],
+            &["cursor", "anysphere"],
+        ) {
+            return Some(path);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let candidates = [
+            "/usr/bin/cursor",
+            "/opt/cursor/cursor",
+            "/opt/Cursor/cursor",
+        ];
+        for candidate in candidates {
+            let path = PathBuf::from(candidate);
+            if path.exists() {
+                return Some(path);
+            }
+        }
+    }
+
+    None
+}
+
+fn path_looks_like_cursor(path: &Path) -> bool {
+    let text = path.to_string_lossy().to_lowercase();
+    text.contains("cursor")
+}
+
+fn normalize_cursor_path_for_config(path: &Path) -> String {
+    #[cfg(target_os = "macos")]
+    {
+        return normalize_macos_app_root(path)
+            .unwrap_or_else(|| path.to_string_lossy().to_string());
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        path.to_string_lossy().to_string()
+    }
+}
+
+pub fn detect_and_save_cursor_launch_path(force: bool) -> Option<String> {
+    let current = modules::config::get_user_config();
+    if !force && normalize_custom_path(&current.cursor_app_path).is_some() {
+        return Some(current.cursor_app_path);
+    }
+
+    let detected = detect_cursor_exec_path()?;
+    let normalized = normalize_cursor_path_for_config(&detected);
+    if current.cursor_app_path != normalized {
+        let mut next = current.clone();
+        next.cursor_app_path = normalized.clone();
+        if let Err(err) = modules::config::save_user_config(&next) {
+            modules::logger::log_warn(&format!("保存 Cursor 启动路径失败（已忽略）: {}", err));
+        }
+    }
+    Some(normalized)
+}
+
+fn resolve_cursor_launch_path() -> Result<PathBuf, String> {
+    let config = modules::config::get_user_config();
+    if let Some(custom) = normalize_custom_path(&config.cursor_app_path) {
+        if let Some(exec) = resolve_macos_exec_path(&custom) {
+            if path_looks_like_cursor(&exec) {
+                return Ok(exec);
+            }
+            modules::logger::log_warn(&format!(
+                "忽略非 Cursor 启动路径配置: {}",
+                exec.to_string_lossy()
+            ));
+        }
+        return Err("APP_PATH_NOT_FOUND:cursor".to_string());
+    }
+
+    Err("APP_PATH_NOT_FOUND:cursor".to_string())
+}
+
+pub fn ensure_cursor_launch_path_configured() -> Result<(), String> {
+    resolve_cursor_launch_path().map(|_| ())
+}
+
+#[cfg(target_os = "windows")]
+fn spawn_cursor_windows(
+    launch_path: &Path,
+    user_data_dir: &str,
+    extra_args: &[String],
+    use_new_window: bool,
+) -> Result<u32, String> {
+    use std::os::windows::process::CommandExt;
+
+    let mut cmd = Command::new(launch_path);
+    cmd.creation_flags(0x08000000);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    cmd.arg("--user-data-dir").arg(user_data_dir.trim());
+    if use_new_window {
+        cmd.arg("--new-window");
+    } else {
+        cmd.arg("--reuse-window");
+    }
+    for arg in extra_args {
+        if !arg.trim().is_empty() {
+            cmd.arg(arg.trim());
+        }
+    }
+    let child = instance_manager::spawn_command_with_trace(&mut cmd, "Cursor")
+        .map_err(|e| format!("启动 Cursor 失败: {}", e))?;
+    Ok(child.id())
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn spawn_cursor_unix(
+    launch_path: &Path,
+    user_data_dir: &str,
+    extra_args: &[String],
+    use_new_window: bool,
+) -> Result<u32, String> {
+    let mut cmd = Command::new(launch_path);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    cmd.arg("--user-data-dir").arg(user_data_dir.trim());
+    if use_new_window {
+        cmd.arg("--new-window");
+    } else {
+        cmd.arg("--reuse-window");
+    }
+    for arg in extra_args {
+        if !arg.trim().is_empty() {
+            cmd.arg(arg.trim());
+        }
+    }
+    let child = instance_manager::spawn_command_with_trace(&mut cmd, "Cursor")
+        .map_err(|e| format!("启动 Cursor 失败: {}", e))?;
+    Ok(child.id())
+}
+
+pub fn start_cursor_with_args_with_new_window(
+    user_data_dir: &str,
+    extra_args: &[String],
+    use_new_window: bool,
+) -> Result<u32, String> {
+    let target = user_data_dir.trim();
+    if target.is_empty() {
+        return Err("实例目录为空，无法启动".to_string());
+    }
+    let launch_path = resolve_cursor_launch_path()?;
+
+    #[cfg(target_os = "windows")]
+    {
+        return spawn_cursor_windows(&launch_path, target, extra_args, use_new_window);
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        return spawn_cursor_unix(&launch_path, target, extra_args, use_new_window);
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (target, extra_args, use_new_window);
+        Err("Cursor 多开实例仅支持 macOS、Windows 和 Linux".to_string())
+    }
+}
+
+pub fn start_cursor_default_with_args_with_new_window(
+    extra_args: &[String],
+    use_new_window: bool,
+) -> Result<u32, String> {
+    let default_dir = get_default_cursor_user_data_dir()?;
+    start_cursor_with_args_with_new_window(
+        &default_dir.to_string_lossy(),
+        extra_args,
+        use_new_window,
+    )
+}
+
+pub fn close_cursor(user_data_dirs: &[String], timeout_secs: u64) -> Result<(), String> {
+    CursorInstances.close_instances(user_data_dirs, timeout_secs, collect_cursor_process_entries)
+}
+
+fn ensure_state_db_for_injection(profile_dir: &Path) -> Result<PathBuf, String> {
+    let global_storage = profile_dir.join("User").join("globalStorage");
+    let db_path = global_storage.join("state.vscdb");
+    if db_path.exists() {
+        return Ok(db_path);
+    }
+
+    let default_db = cursor_account::get_default_state_db_path()?;
+    if default_db.exists() {
+        fs::create_dir_all(&global_storage)
+            .map_err(|e| format!("创建 globalStorage 失败: {}", e))?;
+        fs::copy(&default_db, &db_path).map_err(|e| format!("复制 state.vscdb 失败: {}", e))?;
+    }
+
+    if !db_path.exists() {
+        return Err("未找到 state.vscdb，请先勾选复制当前登录状态或先启动实例一次".to_string());
+    }
+    Ok(db_path)
+}
+
+/// 将账号登录态写入实例目录的 state.vscdb（cursorAuth/* 键）
+pub fn inject_account_to_profile(profile_dir: &Path, account_id: &str) -> Result<(), String> {
+    modules::credential_vault::ensure_unlocked()?;
+    let account = cursor_account::load_account(account_id)
+        .ok_or_else(|| format!("绑定账号不存在: {}", account_id))?;
+    let db_path = ensure_state_db_for_injection(profile_dir)?;
+
+    let email = Some(account.email.trim()).filter(|value| !value.is_empty());
+    db::write_item_table_entries(
+        &db_path,
+        &[
+            (
+                cursor_account::LOCAL_ACCESS_TOKEN_KEY,
+                Some(account.access_token.as_str()),
+            ),
+            (
+                cursor_account::LOCAL_REFRESH_TOKEN_KEY,
+                account.refresh_token.as_deref(),
+            ),
+            (cursor_account::LOCAL_EMAIL_KEY, email),
+            (
+                cursor_account::LOCAL_SIGN_UP_TYPE_KEY,
+                account.sign_up_type.as_deref(),
+            ),
+            (
+                cursor_account::LOCAL_MEMBERSHIP_TYPE_KEY,
+                account.membership_type.as_deref(),
+            ),
+        ],
+    )?;
+
+    modules::logger::log_info(&format!(
+        "Cursor 账号已写入 state.vscdb: account_id={}, db={}",
+        account.id,
+        db_path.display()
+    ));
+    Ok(())
+}
+
+/// 启动实例前按绑定账号注入登录态（未绑定账号时跳过）
+pub fn inject_bound_account(
+    user_data_dir: &str,
+    bind_account_id: Option<&str>,
+) -> Result<(), String> {
+    let bind_id = bind_account_id
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let Some(bind_id) = bind_id else {
+        return Ok(());
+    };
+    // 在关闭实例之前检查，避免锁定时关掉实例后才失败
+    modules::credential_vault::ensure_unlocked()?;
+
+    let account = cursor_account::load_account(bind_id)
+        .ok_or_else(|| format!("绑定账号不存在: {}", bind_id))?;
+
+    modules::logger::log_info(&format!(
+        "实例启动检测到绑定 Cursor 账号，准备注入: bind_account_id={}, email={}, user_data_dir={}",
+        bind_id, account.email, user_data_dir
+    ));
+
+    close_cursor(&[user_data_dir.to_string()], 20)?;
+    inject_account_to_profile(Path::new(user_data_dir), bind_id)?;
+
+    modules::logger::log_info(&format!("Cursor 账号注入完成: {}", account.email));
+    Ok(())
+}
+
+/// 重启默认实例：关闭默认实例进程并注入绑定账号后重新启动，返回新进程 PID
+pub fn start_default_instance() -> Result<u32, String> {
+    ensure_cursor_launch_path_configured()?;
+
+    let default_dir = get_default_cursor_user_data_dir()?;
+    let default_dir_str = default_dir.to_string_lossy().to_string();
+    let default_settings = load_default_settings()?;
+    if let Some(pid) = resolve_cursor_pid(default_settings.last_pid, None) {
+        modules::process::close_pid(pid, 20)?;
+        let _ = update_default_pid(None)?;
+    }
+    close_cursor(&[default_dir_str.clone()], 20)?;
+    inject_bound_account(
+        &default_dir_str,
+        default_settings.bind_account_id.as_deref(),
+    )?;
+    let extra_args = modules::process::parse_extra_args(&default_settings.extra_args);
+    let pid = start_cursor_default_with_args_with_new_window(&extra_args, true)?;
+    modules::logger::log_info(&format!("Cursor 默认实例已启动: pid={}", pid));
+    let _ = update_default_pid(Some(pid))?;
+    Ok(pid)
+}
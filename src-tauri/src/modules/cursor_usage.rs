@@ -0,0 +1,178 @@
+//! Cursor 用量查询
+//! 通过 cursor.com 网页接口读取高级请求用量与订阅类型
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Months};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, COOKIE};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::modules::logger;
+
+const CURSOR_USAGE_URL: &str = "https://www.cursor.com/api/usage";
+const CURSOR_STRIPE_URL: &str = "https://www.cursor.com/api/auth/stripe";
+/// 高级请求在用量接口中对应的模型键
+const PREMIUM_USAGE_KEY: &str = "gpt-4";
+/// 登录态失效时的错误前缀，调用方据此标记账号状态
+pub const AUTH_EXPIRED_PREFIX: &str = "AUTH_EXPIRED:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorUsageSnapshot {
+    pub premium_requests_used: Option<f64>,
+    pub premium_requests_limit: Option<f64>,
+    pub usage_reset_at: Option<i64>,
+    pub membership_type: Option<String>,
+    pub raw: Value,
+}
+
+/// 从 access token（JWT）的 sub 中解析用户 ID，如 "auth0|user_xxx" -> "user_xxx"
+pub fn extract_user_id(access_token: &str) -> Option<String> {
+    let payload_b64 = access_token.split('.').nth(1)?;
+    let payload_bytes = URL_SAFE_NO_PAD.decode(payload_b64).ok()?;
+    let payload: Value = serde_json::from_slice(&payload_bytes).ok()?;
+    let sub = payload.get("sub").and_then(Value::as_str)?;
+    let user_id = sub.rsplit('|').next().unwrap_or(sub).trim();
+    if user_id.is_empty() {
+        None
+    } else {
+        Some(user_id.to_string())
+    }
+}
+
+pub fn is_auth_expired_error(err: &str) -> bool {
+    err.starts_with(AUTH_EXPIRED_PREFIX)
+}
+
+fn build_headers(user_id: &str, access_token: &str) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        COOKIE,
+        HeaderValue::from_str(&format!(
+            "WorkosCursorSessionToken={}%3A%3A{}",
+            user_id, access_token
+        ))
+        .map_err(|e| format!("构建 Cookie 头失败: {}", e))?,
+    );
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    Ok(headers)
+}
+
+async fn get_json(
+    client: &reqwest::Client,
+    url: &str,
+    headers: HeaderMap,
+) -> Result<Value, String> {
+    let response = client
+        .get(url)
+        .headers(headers)
+        .send()
+        .await
+        .map_err(|e| format!("请求 Cursor 接口失败: {}", e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(format!(
+            "{}Cursor 登录已失效（HTTP {}），请在 Cursor 中重新登录后再导入",
+            AUTH_EXPIRED_PREFIX,
+            status.as_u16()
+        ));
+    }
+    if !status.is_success() {
+        return Err(format!("Cursor 接口返回错误: HTTP {}", status.as_u16()));
+    }
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("解析 Cursor 接口响应失败: {}", e))
+}
+
+fn parse_number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(num) => num.as_f64(),
+        Value::String(text) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+    .filter(|value| value.is_finite())
+}
+
+/// 用量按自然月重置：startOfMonth 加一个月即下一次重置时间
+fn parse_reset_at(raw: &Value) -> Option<i64> {
+    let start = raw.get("startOfMonth").and_then(Value::as_str)?;
+    let start = DateTime::parse_from_rfc3339(start).ok()?;
+    start
+        .checked_add_months(Months::new(1))
+        .map(|value| value.timestamp())
+}
+
+fn parse_usage(raw: Value, membership_type: Option<String>) -> CursorUsageSnapshot {
+    let premium = raw.get(PREMIUM_USAGE_KEY);
+    CursorUsageSnapshot {
+        premium_requests_used: parse_number(premium.and_then(|item| item.get("numRequests"))),
+        premium_requests_limit: parse_number(premium.and_then(|item| item.get("maxRequestUsage"))),
+        usage_reset_at: parse_reset_at(&raw),
+        membership_type,
+        raw,
+    }
+}
+
+/// 查询账号的用量与订阅类型；订阅类型查询失败时仅记录日志
+pub async fn fetch_usage(user_id: &str, access_token: &str) -> Result<CursorUsageSnapshot, String> {
+    let client = reqwest::Client::new();
+    let usage_url = format!("{}?user={}", CURSOR_USAGE_URL, user_id);
+    let raw = get_json(&client, &usage_url, build_headers(user_id, access_token)?).await?;
+
+    let membership_type = match get_json(
+        &client,
+        CURSOR_STRIPE_URL,
+        build_headers(user_id, access_token)?,
+    )
+    .await
+    {
+        Ok(value) => value
+            .get("membershipType")
+            .and_then(Value::as_str)
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty()),
+        Err(err) => {
+            logger::log_warn(&format!(
+                "[Cursor Usage] 获取订阅类型失败（已忽略）: {}",
+                err
+            ));
+            None
+        }
+    };
+
+    Ok(parse_usage(raw, membership_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_user_id_from_jwt_sub() {
+        let payload = URL_SAFE_NO_PAD.encode(br#"{"sub":"auth0|user_01ABC","exp":1}"#);
+        let token = format!("header.{}.sig", payload);
+        assert_eq!(extract_user_id(&token).as_deref(), Some("user_01ABC"));
+        assert_eq!(extract_user_id("not-a-jwt"), None);
+    }
+
+    #[test]
+    fn parses_premium_usage_and_reset() {
+        let raw = serde_json::json!({
+            "gpt-4": { "numRequests": 120, "maxRequestUsage": 500 },
+            "gpt-3.5-turbo": { "numRequests": 3, "maxRequestUsage": null },
+            "startOfMonth": "2026-01-15T08:00:00.000Z"
+        });
+        let usage = parse_usage(raw, Some("pro".to_string()));
+        assert_eq!(usage.premium_requests_used, Some(120.0));
+        assert_eq!(usage.premium_requests_limit, Some(500.0));
+        assert_eq!(
+            usage.usage_reset_at,
+            DateTime::parse_from_rfc3339("2026-02-15T08:00:00Z")
+                .ok()
+                .map(|value| value.timestamp())
+        );
+    }
+}
@@ -161,3 +161,27 @@ pub fn write_service_machine_id(service_machine_id: &str) -> Result<(), String>
     crate::modules::logger::log_info(&format!("serviceMachineId 已写入: {}", service_machine_id));
     Ok(())
 }
+
+/// 在单个事务中写入/删除指定数据库 ItemTable 的若干键（值为 None 时删除该键）
+pub fn write_item_table_entries(
+    db_path: &Path,
+    entries: &[(&str, Option<&str>)],
+) -> Result<(), String> {
+    let mut conn = Connection::open(db_path).map_err(|e| format!("打开数据库失败: {}", e))?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+
+    for (key, value) in entries {
+        match value {
+            Some(value) => tx.execute(
+                "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
+                [key, value],
+            ),
+            None => tx.execute("DELETE FROM ItemTable WHERE key = ?", [key]),
+        }
+        .map_err(|e| format!("写入 {} 失败: {}", key, e))?;
+    }
+
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))
+}
@@ -4,7 +4,8 @@ use std::path::PathBuf;
 use uuid::Uuid;
 
 use crate::models::DeviceProfile;
-use crate::modules::{device, logger};
+use crate::modules::{backup, device, logger};
+use crate::utils::atomic_file;
 
 const FINGERPRINTS_FILE: &str = "fingerprints.json";
 
@@ -85,12 +86,14 @@ pub fn load_fingerprint_store() -> Result<FingerprintStore, String> {
         return Ok(FingerprintStore::new());
     }
 
-    serde_json::from_str(&content).map_err(|e| {
-        crate::error::file_corrupted_error(
-            FINGERPRINTS_FILE,
-            &path.to_string_lossy(),
-            &e.to_string(),
-        )
+    serde_json::from_str(&content).or_else(|e| {
+        atomic_file::recover_json(&path).ok_or_else(|| {
+            crate::error::file_corrupted_error(
+                FINGERPRINTS_FILE,
+                &path.to_string_lossy(),
+                &e.to_string(),
+            )
+        })
     })
 }
 
@@ -99,7 +102,7 @@ pub fn save_fingerprint_store(store: &FingerprintStore) -> Result<(), String> {
     let path = get_fingerprints_path()?;
     let content =
         serde_json::to_string_pretty(store).map_err(|e| format!("序列化指纹存储失败: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("保存指纹存储失败: {}", e))
+    atomic_file::write(&path, content).map_err(|e| format!("保存指纹存储失败: {}", e))
 }
 
 /// 获取指纹详情
@@ -209,6 +212,7 @@ pub fn delete_fingerprint(fingerprint_id: &str) -> Result<(), String> {
         return Err("原始指纹不可删除".to_string());
     }
 
+    backup::snapshot_before("delete_fingerprint");
     let mut store = load_fingerprint_store()?;
 
     let before = store.fingerprints.len();
@@ -1,19 +1,55 @@
 use crate::models::github_copilot::{
     GitHubCopilotAccount, GitHubCopilotAccountIndex, GitHubCopilotOAuthCompletePayload,
 };
-use crate::modules::{account, github_copilot_oauth, logger};
-use std::collections::HashMap;
+use crate::modules::account_store::{self, StoreSpec};
+use crate::modules::platform::{self, Platform, PlatformAccount, UnifiedQuota};
+use crate::modules::quota_cache::{self, QuotaApiError, Refreshed};
+use crate::modules::unified_quota::{self, CopilotMetricLabels};
+use crate::modules::refresh_executor::{self, RefreshReport, RefreshRun};
+use crate::modules::schema_migration::{self, SchemaSpec, VersionEncoding};
+use crate::modules::{
+    account, credential_vault, github_copilot_instance, github_copilot_oauth, logger,
+    quota_events, quota_history, store_fsck,
+};
+use crate::utils::atomic_file;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
 const ACCOUNTS_INDEX_FILE: &str = "github_copilot_accounts.json";
 const ACCOUNTS_DIR: &str = "github_copilot_accounts";
+
+/// github_copilot_accounts.json 的格式版本
+const INDEX_SCHEMA: SchemaSpec = SchemaSpec {
+    name: ACCOUNTS_INDEX_FILE,
+    version_field: "version",
+    encoding: VersionEncoding::Dotted,
+    current: 1,
+    baseline: 1,
+    migrations: &[],
+};
+
 static GHCP_ACCOUNT_INDEX_LOCK: std::sync::LazyLock<Mutex<()>> =
     std::sync::LazyLock::new(|| Mutex::new(()));
-static GHCP_QUOTA_ALERT_LAST_SENT: std::sync::LazyLock<Mutex<HashMap<String, i64>>> =
-    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
-const GHCP_QUOTA_ALERT_COOLDOWN_SECONDS: i64 = 300;
+/// 账号文件中需要由凭据保险库加密的字段
+const SECRET_FIELDS: &[&str] = &["/github_access_token", "/copilot_token"];
+
+pub const STORE_SPEC: StoreSpec = StoreSpec {
+    platform: "github_copilot",
+    base_dir: get_data_dir,
+    index_file: ACCOUNTS_INDEX_FILE,
+    accounts_dir: ACCOUNTS_DIR,
+    secret_fields: SECRET_FIELDS,
+    quota_fields: &[
+        "copilot_quota_snapshots",
+        "copilot_quota_reset_date",
+        "copilot_limited_user_quotas",
+        "copilot_limited_user_reset_date",
+    ],
+    mirror_index: false,
+};
 
 fn now_ts() -> i64 {
     chrono::Utc::now().timestamp()
@@ -23,7 +59,7 @@ fn get_data_dir() -> Result<PathBuf, String> {
     account::get_data_dir()
 }
 
-fn get_accounts_dir() -> Result<PathBuf, String> {
+pub fn get_accounts_dir() -> Result<PathBuf, String> {
     let base = get_data_dir()?;
     let dir = base.join(ACCOUNTS_DIR);
     if !dir.exists() {
@@ -46,6 +82,12 @@ pub fn load_account(account_id: &str) -> Option<GitHubCopilotAccount> {
 }
 
 fn load_account_file(account_id: &str) -> Option<GitHubCopilotAccount> {
+    if account_store::is_active() {
+        return account_store::load_record(&STORE_SPEC, account_id)
+            .ok()
+            .flatten();
+    }
+
     let account_path = get_accounts_dir()
         .ok()
         .map(|dir| dir.join(format!("{}.json", account_id)))?;
@@ -53,17 +95,24 @@ fn load_account_file(account_id: &str) -> Option<GitHubCopilotAccount> {
         return None;
     }
     let content = fs::read_to_string(account_path).ok()?;
-    serde_json::from_str(&content).ok()
+    credential_vault::from_sealed_json(&content, SECRET_FIELDS).ok()
 }
 
 fn save_account_file(account: &GitHubCopilotAccount) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_record(&STORE_SPEC, &account.id, account);
+    }
+
     let path = get_accounts_dir()?.join(format!("{}.json", account.id));
-    let content =
-        serde_json::to_string_pretty(account).map_err(|e| format!("序列化账号失败: {}", e))?;
-    fs::write(path, content).map_err(|e| format!("保存账号失败: {}", e))
+    let content = credential_vault::to_sealed_json(account, SECRET_FIELDS)?;
+    atomic_file::write_secret(path, content).map_err(|e| format!("保存账号失败: {}", e))
 }
 
 fn delete_account_file(account_id: &str) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::delete_record(&STORE_SPEC, account_id);
+    }
+
     let path = get_accounts_dir()?.join(format!("{}.json", account_id));
     if path.exists() {
         fs::remove_file(path).map_err(|e| format!("删除账号失败: {}", e))?;
@@ -72,6 +121,15 @@ fn delete_account_file(account_id: &str) -> Result<(), String> {
 }
 
 fn load_account_index() -> GitHubCopilotAccountIndex {
+    if account_store::is_active() {
+        return account_store::load_index(&STORE_SPEC)
+            .unwrap_or_else(|e| {
+                logger::log_error(&format!("读取 GitHub Copilot 账号索引失败: {}", e));
+                None
+            })
+            .unwrap_or_default();
+    }
+
     let path = match get_accounts_index_path() {
         Ok(p) => p,
         Err(_) => return GitHubCopilotAccountIndex::new(),
@@ -81,8 +139,9 @@ fn load_account_index() -> GitHubCopilotAccountIndex {
         return GitHubCopilotAccountIndex::new();
     }
 
-    match fs::read_to_string(path) {
+    match fs::read_to_string(&path) {
         Ok(content) => {
+            let content = schema_migration::upgrade_on_load(&INDEX_SCHEMA, &path, content);
             serde_json::from_str(&content).unwrap_or_else(|_| GitHubCopilotAccountIndex::new())
         }
         Err(_) => GitHubCopilotAccountIndex::new(),
@@ -90,10 +149,15 @@ fn load_account_index() -> GitHubCopilotAccountIndex {
 }
 
 fn save_account_index(index: &GitHubCopilotAccountIndex) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_index(&STORE_SPEC, index);
+    }
+
     let path = get_accounts_index_path()?;
+    schema_migration::ensure_writable(&INDEX_SCHEMA, &path)?;
     let content =
         serde_json::to_string_pretty(index).map_err(|e| format!("序列化账号索引失败: {}", e))?;
-    fs::write(path, content).map_err(|e| format!("写入账号索引失败: {}", e))
+    atomic_file::write(path, content).map_err(|e| format!("写入账号索引失败: {}", e))
 }
 
 fn refresh_summary(index: &mut GitHubCopilotAccountIndex, account: &GitHubCopilotAccount) {
@@ -109,9 +173,11 @@ fn upsert_account_record(account: GitHubCopilotAccount) -> Result<GitHubCopilotA
         .lock()
         .map_err(|_| "获取 GitHub Copilot 账号锁失败".to_string())?;
     let mut index = load_account_index();
-    save_account_file(&account)?;
     refresh_summary(&mut index, &account);
-    save_account_index(&index)?;
+    account_store::write_batch(|| {
+        save_account_file(&account)?;
+        save_account_index(&index)
+    })?;
     Ok(account)
 }
 
@@ -124,6 +190,41 @@ pub fn list_accounts() -> Vec<GitHubCopilotAccount> {
         .collect()
 }
 
+/// 以账号文件为准重建索引（一致性修复使用），返回索引中的账号数
+pub fn rebuild_account_index() -> Result<usize, String> {
+    let _lock = GHCP_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 GitHub Copilot 账号锁失败".to_string())?;
+    let previous = store_fsck::read_index(&STORE_SPEC).unwrap_or_default();
+    let accounts: Vec<GitHubCopilotAccount> = store_fsck::list_stored_ids(&STORE_SPEC)?
+        .iter()
+        .filter_map(|id| load_account(id))
+        .collect();
+    let accounts =
+        store_fsck::order_by_index(accounts, &previous.account_ids, |a| &a.id, |a| a.created_at);
+
+    let mut index = GitHubCopilotAccountIndex::new();
+    index.accounts = accounts.iter().map(GitHubCopilotAccount::summary).collect();
+    save_account_index(&index)?;
+    Ok(index.accounts.len())
+}
+
+/// 按凭据保险库当前状态重新写回所有账号文件
+pub fn reseal_account_files() -> Result<usize, String> {
+    let _lock = GHCP_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 GitHub Copilot 账号锁失败".to_string())?;
+    let index = load_account_index();
+    let mut count = 0;
+    for summary in &index.accounts {
+        if let Some(account) = load_account_file(&summary.id) {
+            save_account_file(&account)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
 pub fn upsert_account(
     payload: GitHubCopilotOAuthCompletePayload,
 ) -> Result<GitHubCopilotAccount, String> {
@@ -189,9 +290,11 @@ pub fn upsert_account(
     account.created_at = created_at;
     account.last_used = now;
 
-    save_account_file(&account)?;
     refresh_summary(&mut index, &account);
-    save_account_index(&index)?;
+    account_store::write_batch(|| {
+        save_account_file(&account)?;
+        save_account_index(&index)
+    })?;
 
     logger::log_info(&format!(
         "GitHub Copilot 账号已保存: id={}, login={}",
@@ -200,19 +303,80 @@ pub fn upsert_account(
     Ok(account)
 }
 
-pub async fn refresh_account_token(account_id: &str) -> Result<GitHubCopilotAccount, String> {
+/// 写入配额缓存的字段（不含 Copilot token）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CopilotQuotaCache {
+    plan: Option<String>,
+    chat_enabled: Option<bool>,
+    quota_snapshots: Option<serde_json::Value>,
+    quota_reset_date: Option<String>,
+    limited_user_quotas: Option<serde_json::Value>,
+    limited_user_reset_date: Option<i64>,
+}
+
+impl CopilotQuotaCache {
+    fn of_bundle(bundle: &github_copilot_oauth::CopilotTokenBundle) -> Self {
+        Self {
+            plan: bundle.plan.clone(),
+            chat_enabled: bundle.chat_enabled,
+            quota_snapshots: bundle.quota_snapshots.clone(),
+            quota_reset_date: bundle.quota_reset_date.clone(),
+            limited_user_quotas: bundle.limited_user_quotas.clone(),
+            limited_user_reset_date: bundle.limited_user_reset_date,
+        }
+    }
+
+    fn apply(self, account: &mut GitHubCopilotAccount) {
+        account.copilot_plan = self.plan;
+        account.copilot_chat_enabled = self.chat_enabled;
+        account.copilot_quota_snapshots = self.quota_snapshots;
+        account.copilot_quota_reset_date = self.quota_reset_date;
+        account.copilot_limited_user_quotas = self.limited_user_quotas;
+        account.copilot_limited_user_reset_date = self.limited_user_reset_date;
+    }
+}
+
+/// 刷新 Copilot token 与配额；`skip_cache` 为 true 时忽略配额缓存有效期（单个账号的手动刷新）。
+/// 使用缓存时只更新配额字段，沿用原有 token
+pub async fn refresh_account_token(
+    account_id: &str,
+    skip_cache: bool,
+) -> Result<GitHubCopilotAccount, String> {
+    credential_vault::ensure_unlocked()?;
     let mut account = load_account_file(account_id).ok_or_else(|| "账号不存在".to_string())?;
-    let bundle = github_copilot_oauth::refresh_copilot_token(&account.github_access_token).await?;
-
-    account.copilot_token = bundle.token;
-    account.copilot_plan = bundle.plan;
-    account.copilot_chat_enabled = bundle.chat_enabled;
-    account.copilot_expires_at = bundle.expires_at;
-    account.copilot_refresh_in = bundle.refresh_in;
-    account.copilot_quota_snapshots = bundle.quota_snapshots;
-    account.copilot_quota_reset_date = bundle.quota_reset_date;
-    account.copilot_limited_user_quotas = bundle.limited_user_quotas;
-    account.copilot_limited_user_reset_date = bundle.limited_user_reset_date;
+    if quota_cache::is_offline() {
+        // 离线模式：不发起请求，沿用上次保存的数据
+        return Ok(account);
+    }
+    let request = async {
+        github_copilot_oauth::refresh_copilot_token(&account.github_access_token)
+            .await
+            .map_err(QuotaApiError::Transient)
+    };
+    let refreshed = quota_cache::refresh(
+        GitHubCopilotPlatform.id(),
+        &account.id,
+        None,
+        skip_cache,
+        request,
+        |bundle| serde_json::to_value(CopilotQuotaCache::of_bundle(bundle)).unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match refreshed {
+        Refreshed::Fetched(bundle) => {
+            account.copilot_token = bundle.token.clone();
+            account.copilot_expires_at = bundle.expires_at;
+            account.copilot_refresh_in = bundle.refresh_in;
+            CopilotQuotaCache::of_bundle(&bundle).apply(&mut account);
+        }
+        Refreshed::Cached(cached) => {
+            serde_json::from_value::<CopilotQuotaCache>(cached.payload)
+                .map_err(|e| format!("解析 GitHub Copilot 配额缓存失败: {}", e))?
+                .apply(&mut account);
+        }
+    }
     account.last_used = now_ts();
 
     let updated = account.clone();
@@ -220,40 +384,25 @@ pub async fn refresh_account_token(account_id: &str) -> Result<GitHubCopilotAcco
     Ok(updated)
 }
 
-pub async fn refresh_all_tokens(
-) -> Result<Vec<(String, Result<GitHubCopilotAccount, String>)>, String> {
-    use futures::future::join_all;
-    use std::sync::Arc;
-    use tokio::sync::Semaphore;
-
-    const MAX_CONCURRENT: usize = 5;
-    let accounts = list_accounts();
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
-    let tasks: Vec<_> = accounts
-        .into_iter()
-        .map(|account| {
-            let id = account.id;
-            let semaphore = semaphore.clone();
-            async move {
-                let _permit = semaphore
-                    .acquire_owned()
-                    .await
-                    .map_err(|e| format!("获取 GitHub Copilot 刷新并发许可失败: {}", e))?;
-                let res = refresh_account_token(&id).await;
-                Ok::<(String, Result<GitHubCopilotAccount, String>), String>((id, res))
-            }
-        })
-        .collect();
+/// 批量刷新时按此主机限速
+const REFRESH_HOST: &str = "api.github.com";
 
-    let mut results = Vec::with_capacity(tasks.len());
-    for task in join_all(tasks).await {
-        match task {
-            Ok(item) => results.push(item),
-            Err(err) => return Err(err),
-        }
-    }
+pub async fn refresh_all_tokens() -> Result<RefreshRun<GitHubCopilotAccount>, String> {
+    let account_ids = list_accounts().into_iter().map(|account| account.id).collect();
+    let run = refresh_executor::run(
+        GitHubCopilotPlatform.id(),
+        REFRESH_HOST,
+        account_ids,
+        |id| async move { refresh_account_token(&id, false).await },
+    )
+    .await;
 
-    Ok(results)
+    quota_history::record_refreshed_logged(
+        &GitHubCopilotPlatform,
+        &quota_history::succeeded_ids(&run.results),
+    );
+    quota_events::publish_logged(&GitHubCopilotPlatform);
+    Ok(run)
 }
 
 pub fn remove_account(account_id: &str) -> Result<(), String> {
@@ -262,9 +411,10 @@ pub fn remove_account(account_id: &str) -> Result<(), String> {
         .map_err(|_| "获取 GitHub Copilot 账号锁失败".to_string())?;
     let mut index = load_account_index();
     index.accounts.retain(|item| item.id != account_id);
-    save_account_index(&index)?;
-    delete_account_file(account_id)?;
-    Ok(())
+    account_store::write_batch(|| {
+        save_account_index(&index)?;
+        delete_account_file(account_id)
+    })
 }
 
 pub fn remove_accounts(account_ids: &[String]) -> Result<(), String> {
@@ -305,6 +455,7 @@ pub fn import_from_json(json_content: &str) -> Result<Vec<GitHubCopilotAccount>,
 }
 
 pub fn export_accounts(account_ids: &[String]) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
     let accounts: Vec<GitHubCopilotAccount> = account_ids
         .iter()
         .filter_map(|id| load_account_file(id))
@@ -312,121 +463,25 @@ pub fn export_accounts(account_ids: &[String]) -> Result<String, String> {
     serde_json::to_string_pretty(&accounts).map_err(|e| format!("序列化失败: {}", e))
 }
 
-fn normalize_quota_alert_threshold(raw: i32) -> i32 {
-    raw.clamp(0, 100)
-}
-
-fn clamp_percent(value: f64) -> i32 {
-    value.round().clamp(0.0, 100.0) as i32
-}
-
-fn parse_token_map(token: &str) -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    let prefix = token.split(':').next().unwrap_or(token);
-    for item in prefix.split(';') {
-        let mut parts = item.splitn(2, '=');
-        let key = parts.next().unwrap_or("").trim();
-        if key.is_empty() {
-            continue;
-        }
-        let value = parts.next().unwrap_or("").trim();
-        map.insert(key.to_string(), value.to_string());
-    }
-    map
-}
-
-fn parse_token_number(map: &HashMap<String, String>, key: &str) -> Option<f64> {
-    map.get(key)
-        .and_then(|value| value.trim().parse::<f64>().ok())
-        .filter(|value| value.is_finite())
-}
-
-fn get_json_number(value: &serde_json::Value) -> Option<f64> {
-    match value {
-        serde_json::Value::Number(num) => num.as_f64(),
-        serde_json::Value::String(text) => text.trim().parse::<f64>().ok(),
-        _ => None,
-    }
-    .filter(|value| value.is_finite())
-}
-
-fn calc_remaining_percent(remaining: f64, total: f64) -> Option<i32> {
-    if total <= 0.0 {
-        return None;
-    }
-    Some(clamp_percent((remaining.max(0.0) / total) * 100.0))
-}
-
-fn extract_limited_metrics(account: &GitHubCopilotAccount) -> Vec<(String, i32)> {
-    let Some(limited) = account
-        .copilot_limited_user_quotas
-        .as_ref()
-        .and_then(|value| value.as_object())
-    else {
-        return Vec::new();
-    };
-
-    let token_map = parse_token_map(&account.copilot_token);
-    let mut metrics = Vec::new();
-
-    if let Some(remaining_completions) = limited.get("completions").and_then(get_json_number) {
-        let total_completions =
-            parse_token_number(&token_map, "cq").unwrap_or(remaining_completions);
-        if let Some(percent) = calc_remaining_percent(remaining_completions, total_completions) {
-            metrics.push(("Inline Suggestions".to_string(), percent));
-        }
-    }
-
-    if let Some(remaining_chat) = limited.get("chat").and_then(get_json_number) {
-        let total_chat = parse_token_number(&token_map, "tq").unwrap_or(remaining_chat);
-        if let Some(percent) = calc_remaining_percent(remaining_chat, total_chat) {
-            metrics.push(("Chat Messages".to_string(), percent));
-        }
-    }
-
-    metrics
-}
-
-fn extract_premium_metric(account: &GitHubCopilotAccount) -> Option<(String, i32)> {
-    let snapshots = account
-        .copilot_quota_snapshots
-        .as_ref()
-        .and_then(|value| value.as_object())?;
-
-    let premium = snapshots
-        .get("premium_interactions")
-        .or_else(|| snapshots.get("premium_models"))
-        .and_then(|value| value.as_object())?;
-
-    if premium.get("unlimited").and_then(|value| value.as_bool()) == Some(true) {
-        return Some(("Premium Interactions".to_string(), 100));
-    }
-
-    let percent_remaining = premium
-        .get("percent_remaining")
-        .and_then(get_json_number)
-        .map(clamp_percent)?;
-
-    Some(("Premium Interactions".to_string(), percent_remaining))
-}
-
-fn extract_quota_metrics(account: &GitHubCopilotAccount) -> Vec<(String, i32)> {
-    let mut metrics = extract_limited_metrics(account);
-    if let Some(premium) = extract_premium_metric(account) {
-        metrics.push(premium);
-    }
-    metrics
-}
+const QUOTA_METRIC_LABELS: CopilotMetricLabels = CopilotMetricLabels {
+    completions: "Inline Suggestions",
+    chat: "Chat Messages",
+    premium: "Premium Interactions",
+};
 
-fn average_quota_percentage(metrics: &[(String, i32)]) -> f64 {
-    if metrics.is_empty() {
-        return 0.0;
-    }
-    let sum: i32 = metrics.iter().map(|(_, pct)| *pct).sum();
-    sum as f64 / metrics.len() as f64
+/// 统一配额视图（令牌 + 受限用户额度 + 高级请求快照）
+pub fn unified_quota(account: &GitHubCopilotAccount) -> UnifiedQuota {
+    UnifiedQuota::new(unified_quota::copilot_metrics(
+        &account.copilot_token,
+        account.copilot_plan.as_deref(),
+        account.copilot_limited_user_quotas.as_ref(),
+        account.copilot_quota_snapshots.as_ref(),
+        &QUOTA_METRIC_LABELS,
+        quota_reset_at(account),
+    ))
 }
 
-fn resolve_current_account_id(accounts: &[GitHubCopilotAccount]) -> Option<String> {
+fn resolve_current_account_id(accounts: &[PlatformAccount]) -> Option<String> {
     if let Ok(settings) = crate::modules::github_copilot_instance::load_default_settings() {
         if let Some(bind_id) = settings.bind_account_id {
             let trimmed = bind_id.trim();
@@ -436,10 +491,7 @@ fn resolve_current_account_id(accounts: &[GitHubCopilotAccount]) -> Option<Strin
         }
     }
 
-    accounts
-        .iter()
-        .max_by_key(|account| account.last_used)
-        .map(|account| account.id.clone())
+    platform::most_recently_used(accounts)
 }
 
 fn display_email(account: &GitHubCopilotAccount) -> String {
@@ -450,108 +502,141 @@ fn display_email(account: &GitHubCopilotAccount) -> String {
         .unwrap_or_else(|| account.github_login.clone())
 }
 
-fn build_quota_alert_cooldown_key(account_id: &str, threshold: i32) -> String {
-    format!("github_copilot:{}:{}", account_id, threshold)
+pub fn run_quota_alert_if_needed(
+) -> Result<Vec<crate::modules::account::QuotaAlertPayload>, String> {
+    crate::modules::alert_rules::run(&GitHubCopilotPlatform)
+}
+
+/// 配额重置时间：优先受限用户重置时间，其次 quota_reset_date（RFC3339）
+fn quota_reset_at(account: &GitHubCopilotAccount) -> Option<i64> {
+    account.copilot_limited_user_reset_date.or_else(|| {
+        account
+            .copilot_quota_reset_date
+            .as_deref()
+            .and_then(|text| chrono::DateTime::parse_from_rfc3339(text.trim()).ok())
+            .map(|value| value.timestamp())
+    })
+}
+
+fn to_platform_account(account: &GitHubCopilotAccount) -> PlatformAccount {
+    PlatformAccount {
+        platform: GitHubCopilotPlatform.id().to_string(),
+        id: account.id.clone(),
+        email: display_email(account),
+        tags: account.tags.clone().unwrap_or_default(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+        disabled: false,
+        forbidden: false,
+        quota_error: None,
+        token_expires_at: account.copilot_expires_at,
+        quota_stale: false,
+        quota: unified_quota(account),
+    }
 }
 
-fn should_emit_quota_alert(cooldown_key: &str, now: i64) -> bool {
-    let Ok(mut state) = GHCP_QUOTA_ALERT_LAST_SENT.lock() else {
-        return true;
-    };
+/// 切换账号：更新 VS Code 默认实例绑定账号并重启默认实例（启动失败不阻断切号），返回切换结果说明
+pub fn switch_account_internal(account_id: &str) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
+    logger::log_info(&format!("开始切换 GitHub Copilot 账号: {}", account_id));
+    let account = load_account(account_id)
+        .ok_or_else(|| format!("GitHub Copilot account not found: {}", account_id))?;
+    logger::log_info(&format!(
+        "正在切换到 GitHub Copilot 账号: {} (ID: {})",
+        account.github_login, account.id
+    ));
 
-    if let Some(last_sent) = state.get(cooldown_key) {
-        if now - *last_sent < GHCP_QUOTA_ALERT_COOLDOWN_SECONDS {
-            return false;
-        }
+    // 同步更新 VS Code 默认实例绑定账号，确保后续走默认实例启动链路时注入目标明确。
+    if let Err(e) = github_copilot_instance::update_default_settings(
+        Some(Some(account_id.to_string())),
+        None,
+        Some(false),
+    ) {
+        logger::log_warn(&format!("更新 GitHub Copilot 默认实例绑定账号失败: {}", e));
+    } else {
+        logger::log_info(&format!(
+            "已同步更新 GitHub Copilot 默认实例绑定账号: {}",
+            account_id
+        ));
     }
 
-    state.insert(cooldown_key.to_string(), now);
-    true
-}
+    let launch_warning = match github_copilot_instance::start_default_instance() {
+        Ok(_) => None,
+        Err(e) if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("启动 VS Code 失败") => {
+            logger::log_warn(&format!("GitHub Copilot 默认实例启动失败: {}", e));
+            Some(e)
+        }
+        Err(e) => return Err(e),
+    };
 
-fn clear_quota_alert_cooldown(account_id: &str, threshold: i32) {
-    if let Ok(mut state) = GHCP_QUOTA_ALERT_LAST_SENT.lock() {
-        state.remove(&build_quota_alert_cooldown_key(account_id, threshold));
+    logger::log_info(&format!(
+        "GitHub Copilot 账号切换完成: {}",
+        account.github_login
+    ));
+    platform::notify_account_switched("vscode", launch_warning.as_deref());
+    if let Some(err) = launch_warning {
+        Ok(format!("切换完成，但 VS Code 启动失败: {}", err))
+    } else {
+        Ok("切换完成".to_string())
     }
 }
 
-fn pick_quota_alert_recommendation(
-    accounts: &[GitHubCopilotAccount],
-    current_id: &str,
-) -> Option<GitHubCopilotAccount> {
-    let mut candidates: Vec<GitHubCopilotAccount> = accounts
-        .iter()
-        .filter(|account| account.id != current_id)
-        .filter(|account| !extract_quota_metrics(account).is_empty())
-        .cloned()
-        .collect();
+pub struct GitHubCopilotPlatform;
 
-    if candidates.is_empty() {
-        return None;
+impl Platform for GitHubCopilotPlatform {
+    fn id(&self) -> &'static str {
+        "github_copilot"
     }
 
-    candidates.sort_by(|a, b| {
-        let avg_a = average_quota_percentage(&extract_quota_metrics(a));
-        let avg_b = average_quota_percentage(&extract_quota_metrics(b));
-        avg_b
-            .partial_cmp(&avg_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then_with(|| a.last_used.cmp(&b.last_used))
-    });
+    fn display_name(&self) -> &'static str {
+        "GitHub Copilot"
+    }
 
-    candidates.into_iter().next()
-}
+    fn list_accounts(&self) -> Result<Vec<PlatformAccount>, String> {
+        Ok(list_accounts().iter().map(to_platform_account).collect())
+    }
 
-pub fn run_quota_alert_if_needed(
-) -> Result<Option<crate::modules::account::QuotaAlertPayload>, String> {
-    let cfg = crate::modules::config::get_user_config();
-    if !cfg.ghcp_quota_alert_enabled {
-        return Ok(None);
+    fn current_account_id(&self, accounts: &[PlatformAccount]) -> Option<String> {
+        resolve_current_account_id(accounts)
     }
 
-    let threshold = normalize_quota_alert_threshold(cfg.ghcp_quota_alert_threshold);
-    let accounts = list_accounts();
-    let current_id = match resolve_current_account_id(&accounts) {
-        Some(id) => id,
-        None => return Ok(None),
-    };
+    fn remove_accounts(&self, account_ids: &[String]) -> Result<(), String> {
+        remove_accounts(account_ids)
+    }
 
-    let current = match accounts.iter().find(|account| account.id == current_id) {
-        Some(account) => account,
-        None => return Ok(None),
-    };
+    fn update_account_tags(&self, account_id: &str, tags: Vec<String>) -> Result<(), String> {
+        update_account_tags(account_id, tags).map(|_| ())
+    }
 
-    let metrics = extract_quota_metrics(current);
-    let low_models: Vec<(String, i32)> = metrics
-        .into_iter()
-        .filter(|(_, pct)| *pct <= threshold)
-        .collect();
+    fn export_accounts(&self, account_ids: &[String]) -> Result<String, String> {
+        export_accounts(account_ids)
+    }
 
-    if low_models.is_empty() {
-        clear_quota_alert_cooldown(&current_id, threshold);
-        return Ok(None);
-    }
-
-    let now = chrono::Utc::now().timestamp();
-    let cooldown_key = build_quota_alert_cooldown_key(&current_id, threshold);
-    if !should_emit_quota_alert(&cooldown_key, now) {
-        return Ok(None);
-    }
-
-    let recommendation = pick_quota_alert_recommendation(&accounts, &current_id);
-    let lowest_percentage = low_models.iter().map(|(_, pct)| *pct).min().unwrap_or(0);
-    let payload = crate::modules::account::QuotaAlertPayload {
-        platform: "github_copilot".to_string(),
-        current_account_id: current_id,
-        current_email: display_email(current),
-        threshold,
-        lowest_percentage,
-        low_models: low_models.into_iter().map(|(name, _)| name).collect(),
-        recommended_account_id: recommendation.as_ref().map(|account| account.id.clone()),
-        recommended_email: recommendation.as_ref().map(display_email),
-        triggered_at: now,
-    };
+    fn import_from_json(&self, json_content: String) -> BoxFuture<'static, Result<usize, String>> {
+        Box::pin(async move { import_from_json(&json_content).map(|accounts| accounts.len()) })
+    }
+
+    fn switch_account(&self, account_id: String) -> BoxFuture<'static, Result<String, String>> {
+        Box::pin(async move { switch_account_internal(&account_id) })
+    }
 
-    crate::modules::account::dispatch_quota_alert(&payload);
-    Ok(Some(payload))
+    fn refresh_account(&self, account_id: String) -> BoxFuture<'static, Result<(), String>> {
+        Box::pin(async move { refresh_account_token(&account_id, true).await.map(|_| ()) })
+    }
+
+    fn refresh_all(&self) -> BoxFuture<'static, Result<RefreshReport, String>> {
+        Box::pin(async move { Ok(refresh_all_tokens().await?.report) })
+    }
+
+    fn refresh_interval_minutes(&self) -> i32 {
+        crate::modules::config::get_user_config().ghcp_auto_refresh_minutes
+    }
+
+    fn quota_alert_settings(&self) -> platform::QuotaAlertSettings {
+        let cfg = crate::modules::config::get_user_config();
+        platform::QuotaAlertSettings {
+            enabled: cfg.ghcp_quota_alert_enabled,
+            threshold: cfg.ghcp_quota_alert_threshold,
+        }
+    }
 }
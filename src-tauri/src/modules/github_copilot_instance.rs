@@ -1,40 +1,55 @@
-use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
-use chrono::Utc;
-use uuid::Uuid;
-
 use crate::models::{DefaultInstanceSettings, InstanceProfile, InstanceStore};
 use crate::modules;
 use crate::modules::instance::InstanceDefaults;
-use crate::modules::instance_store;
+use crate::modules::instance_manager::{InstanceBackend, InstanceManager};
 
 pub use crate::modules::instance_store::{CreateInstanceParams, UpdateInstanceParams};
 
-static GHCP_INSTANCE_STORE_LOCK: std::sync::LazyLock<Mutex<()>> =
-    std::sync::LazyLock::new(|| Mutex::new(()));
+static GHCP_INSTANCE_STORE_LOCK: Mutex<()> = Mutex::new(());
 
 const GHCP_INSTANCES_FILE: &str = "github_copilot_instances.json";
 
-fn instances_path() -> Result<PathBuf, String> {
-    let data_dir = modules::account::get_data_dir()?;
-    Ok(data_dir.join(GHCP_INSTANCES_FILE))
+pub struct GitHubCopilotInstances;
+
+impl InstanceBackend for GitHubCopilotInstances {
+    fn display_name(&self) -> &'static str {
+        "GitHub Copilot"
+    }
+
+    fn store_file(&self) -> &'static str {
+        GHCP_INSTANCES_FILE
+    }
+
+    fn store_lock(&self) -> &'static Mutex<()> {
+        &GHCP_INSTANCE_STORE_LOCK
+    }
+
+    fn default_user_data_dir(&self) -> Result<PathBuf, String> {
+        get_default_vscode_user_data_dir()
+    }
+
+    fn instances_root_dir(&self) -> Result<PathBuf, String> {
+        get_default_instances_root_dir()
+    }
+
+    fn supports_follow_local_account(&self) -> bool {
+        false
+    }
 }
 
 pub fn load_instance_store() -> Result<InstanceStore, String> {
-    let path = instances_path()?;
-    instance_store::load_instance_store(&path, GHCP_INSTANCES_FILE)
+    GitHubCopilotInstances.load_instance_store()
 }
 
 pub fn save_instance_store(store: &InstanceStore) -> Result<(), String> {
-    let path = instances_path()?;
-    instance_store::save_instance_store(&path, GHCP_INSTANCES_FILE, store)
+    GitHubCopilotInstances.save_instance_store(store)
 }
 
 pub fn load_default_settings() -> Result<DefaultInstanceSettings, String> {
-    let store = load_instance_store()?;
-    Ok(store.default_settings)
+    GitHubCopilotInstances.load_default_settings()
 }
 
 pub fn update_default_settings(
@@ -42,29 +57,11 @@ pub fn update_default_settings(
     extra_args: Option<String>,
     follow_local_account: Option<bool>,
 ) -> Result<DefaultInstanceSettings, String> {
-    let _lock = GHCP_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let settings = &mut store.default_settings;
-
-    // GitHub Copilot 实例不支持“跟随当前账号”，直接忽略 follow_local_account。
-    if follow_local_account == Some(true) {
-        settings.follow_local_account = false;
-    }
-
-    if let Some(bind) = bind_account_id {
-        settings.bind_account_id = bind;
-        settings.follow_local_account = false;
-    }
-
-    if let Some(args) = extra_args {
-        settings.extra_args = args.trim().to_string();
-    }
-
-    let updated = settings.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    GitHubCopilotInstances.update_default_settings(
+        bind_account_id,
+        extra_args,
+        follow_local_account,
+    )
 }
 
 pub fn get_default_vscode_user_data_dir() -> Result<PathBuf, String> {
@@ -116,225 +113,98 @@ pub fn get_default_instances_root_dir() -> Result<PathBuf, String> {
 }
 
 pub fn get_instance_defaults() -> Result<InstanceDefaults, String> {
-    let root_dir = get_default_instances_root_dir()?;
-    let default_user_data_dir = get_default_vscode_user_data_dir()?;
-    Ok(InstanceDefaults {
-        root_dir: root_dir.to_string_lossy().to_string(),
-        default_user_data_dir: default_user_data_dir.to_string_lossy().to_string(),
-    })
+    GitHubCopilotInstances.get_instance_defaults()
 }
 
 pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile, String> {
-    let _lock = GHCP_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-
-    let name = instance_store::normalize_name(&params.name)?;
-    let user_data_dir = params.user_data_dir.trim().to_string();
-    if user_data_dir.is_empty() {
-        return Err("实例目录不能为空".to_string());
-    }
-
-    instance_store::ensure_unique(&store, &name, &user_data_dir, None)?;
-
-    let user_dir_path = PathBuf::from(&user_data_dir);
-    let init_mode = params
-        .init_mode
-        .as_deref()
-        .unwrap_or("copy")
-        .to_ascii_lowercase();
-    let create_empty = init_mode == "empty";
-
-    if create_empty {
-        if user_dir_path.exists() {
-            let mut has_entries = false;
-            if let Ok(mut iter) = fs::read_dir(&user_dir_path) {
-                if iter.next().is_some() {
-                    has_entries = true;
-                }
-            }
-            if has_entries {
-                let resolved_path = instance_store::display_path(&user_dir_path);
-                return Err(format!("空白实例需要目标目录为空: {}", resolved_path));
-            }
-        }
-        fs::create_dir_all(&user_dir_path).map_err(|e| format!("创建实例目录失败: {}", e))?;
-    } else {
-        let source_dir = match params.copy_source_instance_id.as_deref() {
-            Some("__default__") | None => get_default_vscode_user_data_dir()?,
-            Some(source_id) => {
-                let source_instance = store
-                    .instances
-                    .iter()
-                    .find(|item| item.id == source_id)
-                    .ok_or("复制来源实例不存在")?;
-                PathBuf::from(&source_instance.user_data_dir)
-            }
-        };
-
-        if user_dir_path.exists() {
-            let mut has_entries = false;
-            if let Ok(mut iter) = fs::read_dir(&user_dir_path) {
-                if iter.next().is_some() {
-                    has_entries = true;
-                }
-            }
-            if has_entries {
-                let resolved_path = instance_store::display_path(&user_dir_path);
-                modules::logger::log_info(&format!(
-                    "[GitHub Copilot Instance] 复制来源实例需要空目录，但目标已存在: {}",
-                    resolved_path
-                ));
-                return Err(format!("复制来源实例需要目标目录为空: {}", resolved_path));
-            }
-        }
-
-        if !source_dir.exists() {
-            return Err("未找到复制来源目录，请先确保来源实例已初始化".to_string());
-        }
-
-        instance_store::copy_dir_recursive(&source_dir, &user_dir_path)?;
-    }
-
-    let instance = InstanceProfile {
-        id: Uuid::new_v4().to_string(),
-        name,
-        user_data_dir,
-        extra_args: params.extra_args.trim().to_string(),
-        bind_account_id: if create_empty {
-            None
-        } else {
-            params.bind_account_id
-        },
-        created_at: Utc::now().timestamp_millis(),
-        last_launched_at: None,
-        last_pid: None,
-    };
-
-    store.instances.push(instance.clone());
-    save_instance_store(&store)?;
-    Ok(instance)
+    GitHubCopilotInstances.create_instance(params)
 }
 
 pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile, String> {
-    let _lock = GHCP_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let index = store
-        .instances
-        .iter()
-        .position(|instance| instance.id == params.instance_id)
-        .ok_or("实例不存在")?;
-
-    let current_id = store.instances[index].id.clone();
-    let current_dir = store.instances[index].user_data_dir.clone();
-    let next_name = params
-        .name
-        .as_ref()
-        .map(|name| instance_store::normalize_name(name))
-        .transpose()?;
-
-    if let Some(ref normalized) = next_name {
-        instance_store::ensure_unique(&store, normalized, &current_dir, Some(&current_id))?;
-    }
-
-    let instance = &mut store.instances[index];
-    if let Some(normalized) = next_name {
-        instance.name = normalized;
-    }
-    if let Some(ref extra_args) = params.extra_args {
-        instance.extra_args = extra_args.trim().to_string();
-    }
-    if let Some(bind) = params.bind_account_id.clone() {
-        instance.bind_account_id = bind;
-    }
-
-    let updated = instance.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    GitHubCopilotInstances.update_instance(params)
 }
 
 pub fn delete_instance(instance_id: &str) -> Result<(), String> {
-    let _lock = GHCP_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let index = store
-        .instances
-        .iter()
-        .position(|instance| instance.id == instance_id)
-        .ok_or("实例不存在")?;
-    let user_data_dir = store.instances[index].user_data_dir.clone();
-
-    if !user_data_dir.trim().is_empty() {
-        let dir_path = PathBuf::from(&user_data_dir);
-        modules::instance::delete_instance_directory(&dir_path)?;
-    }
-
-    store.instances.remove(index);
-    save_instance_store(&store)?;
-    Ok(())
+    GitHubCopilotInstances.delete_instance(instance_id)
 }
 
 pub fn update_instance_after_start(instance_id: &str, pid: u32) -> Result<InstanceProfile, String> {
-    let _lock = GHCP_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_launched_at = Some(Utc::now().timestamp_millis());
-            instance.last_pid = Some(pid);
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    GitHubCopilotInstances.update_instance_after_start(instance_id, pid)
 }
 
 pub fn update_instance_pid(instance_id: &str, pid: Option<u32>) -> Result<InstanceProfile, String> {
-    let _lock = GHCP_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_pid = pid;
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    GitHubCopilotInstances.update_instance_pid(instance_id, pid)
 }
 
 pub fn update_default_pid(pid: Option<u32>) -> Result<DefaultInstanceSettings, String> {
-    let _lock = GHCP_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    store.default_settings.last_pid = pid;
-    let updated = store.default_settings.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    GitHubCopilotInstances.update_default_pid(pid)
 }
 
 pub fn clear_all_pids() -> Result<(), String> {
-    let _lock = GHCP_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    store.default_settings.last_pid = None;
-    for instance in &mut store.instances {
-        instance.last_pid = None;
-    }
-    save_instance_store(&store)?;
+    GitHubCopilotInstances.clear_all_pids()
+}
+
+/// 启动实例前按绑定账号注入 GitHub Copilot Token（未绑定账号时跳过）
+pub fn inject_bound_account(
+    user_data_dir: &str,
+    bind_account_id: Option<&str>,
+) -> Result<(), String> {
+    let bind_id = bind_account_id
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let Some(bind_id) = bind_id else {
+        return Ok(());
+    };
+    // 在关闭实例之前检查，避免锁定时关掉实例后才失败
+    modules::credential_vault::ensure_unlocked()?;
+
+    let account = modules::github_copilot_account::load_account(bind_id)
+        .ok_or_else(|| format!("绑定账号不存在: {}", bind_id))?;
+    modules::logger::log_info(&format!(
+        "实例启动检测到绑定账号，准备注入: bind_account_id={}, login={}, user_data_dir={}",
+        bind_id, account.github_login, user_data_dir
+    ));
+
+    // Ensure DB is writable before injection.
+    modules::process::close_vscode(&[user_data_dir.to_string()], 20)?;
+
+    modules::logger::log_info("正在向实例目录注入 GitHub Copilot Token...");
+    let github_id = account.github_id.to_string();
+    modules::vscode_inject::inject_copilot_token_for_user_data_dir(
+        user_data_dir,
+        &account.github_login,
+        &account.github_access_token,
+        Some(&github_id),
+    )
+    .map_err(|e| {
+        modules::logger::log_error(&format!("实例绑定账号注入失败: {}", e));
+        format!("按绑定账号注入实例失败（{}）: {}", account.github_login, e)
+    })?;
+
+    modules::logger::log_info(&format!("实例绑定账号注入完成: {}", account.github_login));
+
     Ok(())
 }
+
+/// 重启默认实例：关闭默认实例进程并注入绑定账号后重新启动，返回新进程 PID
+pub fn start_default_instance() -> Result<u32, String> {
+    modules::process::ensure_vscode_launch_path_configured()?;
+
+    let default_dir = get_default_vscode_user_data_dir()?;
+    let default_dir_str = default_dir.to_string_lossy().to_string();
+    let default_settings = load_default_settings()?;
+    if let Some(pid) = modules::process::resolve_vscode_pid(default_settings.last_pid, None) {
+        modules::process::close_pid(pid, 20)?;
+        let _ = update_default_pid(None)?;
+    }
+    modules::process::close_vscode(&[default_dir_str.clone()], 20)?;
+    inject_bound_account(
+        &default_dir_str,
+        default_settings.bind_account_id.as_deref(),
+    )?;
+    let extra_args = modules::process::parse_extra_args(&default_settings.extra_args);
+    let pid = modules::process::start_vscode_default_with_args_with_new_window(&extra_args, true)?;
+    modules::logger::log_info(&format!("GitHub Copilot 默认实例已启动: pid={}", pid));
+    let _ = update_default_pid(Some(pid))?;
+    Ok(pid)
+}
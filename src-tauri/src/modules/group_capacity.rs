@@ -0,0 +1,180 @@
+//! 跨账号模型分组容量
+//!
+//! 按分组配置汇总所有启用中的 Antigravity 账号：每个账号取分组内模型剩余百分比的平均值，
+//! 分组容量为各账号之和（200% 即约等于两个满额账号），同时给出最早重置时间与已耗尽账号。
+
+use serde::Serialize;
+
+use crate::models::Account;
+use crate::modules::account;
+use crate::modules::group_settings::{self, GroupSettings};
+
+/// 单个账号在分组内的剩余容量
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupAccountCapacity {
+    pub account_id: String,
+    pub email: String,
+    /// 分组内模型剩余百分比的平均值（0-100）
+    pub remaining_pct: i32,
+    /// 分组内模型最早的重置时间（Unix 秒）
+    pub resets_at: Option<i64>,
+    pub exhausted: bool,
+}
+
+/// 分组在全部账号上的汇总容量
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupCapacity {
+    pub group_id: String,
+    pub group_name: String,
+    /// 有该分组模型配额数据的账号数
+    pub account_count: usize,
+    /// 各账号剩余百分比之和
+    pub total_remaining_pct: i32,
+    /// 所有账号中最早的重置时间（Unix 秒）
+    pub earliest_reset_at: Option<i64>,
+    pub exhausted_count: usize,
+    pub accounts: Vec<GroupAccountCapacity>,
+}
+
+impl GroupCapacity {
+    /// 分组内所有账号都已耗尽
+    pub fn is_exhausted(&self) -> bool {
+        self.account_count > 0 && self.exhausted_count == self.account_count
+    }
+
+    /// 已耗尽账号中最早恢复的时间
+    pub fn next_recovery_at(&self) -> Option<i64> {
+        self.accounts
+            .iter()
+            .filter(|item| item.exhausted)
+            .filter_map(|item| item.resets_at)
+            .min()
+    }
+}
+
+fn parse_reset_ts(reset_time: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(reset_time)
+        .ok()
+        .map(|value| value.timestamp())
+}
+
+fn account_capacity(account: &Account, group_models: &[String]) -> Option<GroupAccountCapacity> {
+    let quota = account.quota.as_ref()?;
+    if quota.is_forbidden {
+        return None;
+    }
+
+    let mut total: i64 = 0;
+    let mut count: i64 = 0;
+    let mut resets_at: Option<i64> = None;
+    for model in &quota.models {
+        let belongs = group_models
+            .iter()
+            .any(|group_model| group_settings::model_matches(&model.name, group_model));
+        if !belongs {
+            continue;
+        }
+        total += i64::from(model.percentage.clamp(0, 100));
+        count += 1;
+        if let Some(ts) = parse_reset_ts(&model.reset_time) {
+            resets_at = Some(resets_at.map_or(ts, |current| current.min(ts)));
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+
+    let remaining_pct = (total as f64 / count as f64).round() as i32;
+    Some(GroupAccountCapacity {
+        account_id: account.id.clone(),
+        email: account.email.clone(),
+        remaining_pct,
+        resets_at,
+        exhausted: remaining_pct <= 0,
+    })
+}
+
+/// 按分组排序计算各分组容量（跳过已禁用与无权限账号）
+pub fn compute(settings: &GroupSettings, accounts: &[Account]) -> Vec<GroupCapacity> {
+    settings
+        .get_ordered_groups(None)
+        .into_iter()
+        .filter_map(|group_id| {
+            let group_models = settings.get_models_in_group(&group_id);
+            if group_models.is_empty() {
+                return None;
+            }
+            let items: Vec<GroupAccountCapacity> = accounts
+                .iter()
+                .filter(|account| !account.disabled)
+                .filter_map(|account| account_capacity(account, &group_models))
+                .collect();
+            Some(GroupCapacity {
+                group_name: settings.get_group_name(&group_id),
+                group_id,
+                account_count: items.len(),
+                total_remaining_pct: items.iter().map(|item| item.remaining_pct).sum(),
+                earliest_reset_at: items.iter().filter_map(|item| item.resets_at).min(),
+                exhausted_count: items.iter().filter(|item| item.exhausted).count(),
+                accounts: items,
+            })
+        })
+        .collect()
+}
+
+pub fn group_capacity() -> Result<Vec<GroupCapacity>, String> {
+    let accounts = account::list_accounts()?;
+    Ok(compute(&group_settings::load_group_settings(), &accounts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{QuotaData, TokenData};
+
+    fn account(id: &str, models: &[(&str, i32, &str)]) -> Account {
+        let token = TokenData::new(String::new(), String::new(), 3600, None, None, None);
+        let mut account = Account::new(id.to_string(), format!("{}@example.com", id), token);
+        let mut quota = QuotaData::new();
+        for (name, percentage, reset_time) in models {
+            quota.add_model(name.to_string(), None, *percentage, reset_time.to_string());
+        }
+        account.quota = Some(quota);
+        account
+    }
+
+    #[test]
+    fn sums_group_capacity_across_enabled_accounts() {
+        let mut settings = GroupSettings {
+            group_mappings: Default::default(),
+            group_order: vec!["g3_pro".to_string()],
+            ..GroupSettings::default()
+        };
+        settings.set_model_group("gemini-3.1-pro-high", "g3_pro");
+        settings.set_model_group("gemini-3.1-pro-low", "g3_pro");
+
+        let full = account(
+            "a",
+            &[
+                ("gemini-3-pro-high", 100, "2026-01-01T10:00:00Z"),
+                ("gemini-3.1-pro-low", 80, "2026-01-01T09:00:00Z"),
+            ],
+        );
+        let empty = account("b", &[("gemini-3.1-pro-high", 0, "2026-01-01T08:00:00Z")]);
+        let mut disabled = account("c", &[("gemini-3.1-pro-high", 100, "2026-01-01T07:00:00Z")]);
+        disabled.disabled = true;
+
+        let groups = compute(&settings, &[full, empty, disabled]);
+        assert_eq!(groups.len(), 1);
+        let pro = &groups[0];
+        assert_eq!(pro.account_count, 2);
+        assert_eq!(pro.total_remaining_pct, 90);
+        assert_eq!(pro.exhausted_count, 1);
+        assert!(!pro.is_exhausted());
+        assert_eq!(
+            pro.earliest_reset_at,
+            parse_reset_ts("2026-01-01T08:00:00Z")
+        );
+        assert_eq!(pro.next_recovery_at(), pro.earliest_reset_at);
+    }
+}
@@ -11,11 +11,14 @@
 //! - groupOrder: 分组排序（插件端可自定义，桌面端只显示前3个）
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
 use super::config::get_shared_dir;
+use super::schema_migration::{self, Migration, SchemaSpec, VersionEncoding};
+use crate::utils::{atomic_file, file_lock};
 
 /// 分组配置文件名
 const GROUP_SETTINGS_FILE: &str = "group_settings.json";
@@ -29,6 +32,22 @@ const GROUP_NAME_GEMINI_FLASH: &str = "Gemini Flash";
 const DEPRECATED_GROUP_ID_G3_IMAGE: &str = "g3_image";
 const DEPRECATED_MODEL_ID_GEMINI_3_PRO_IMAGE: &str = "gemini-3-pro-image";
 
+/// 分组配置格式版本（v1 没有 schemaVersion 字段）
+const GROUP_SETTINGS_SCHEMA_VERSION: u32 = 2;
+
+const GROUP_SETTINGS_SCHEMA: SchemaSpec = SchemaSpec {
+    name: GROUP_SETTINGS_FILE,
+    version_field: "schemaVersion",
+    encoding: VersionEncoding::Integer,
+    current: GROUP_SETTINGS_SCHEMA_VERSION,
+    baseline: 1,
+    migrations: &[Migration {
+        from: 1,
+        description: "G3 分组改名为 Gemini，移除 g3_image 分组",
+        apply: migrate_v1_to_v2,
+    }],
+};
+
 /// 配置来源
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -66,6 +85,14 @@ pub struct GroupSettings {
     /// 最后更新来源
     #[serde(default)]
     pub updated_by: ConfigSource,
+
+    /// 配置格式版本
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
+}
+
+fn default_schema_version() -> u32 {
+    GROUP_SETTINGS_SCHEMA_VERSION
 }
 
 impl Default for GroupSettings {
@@ -115,6 +142,7 @@ impl Default for GroupSettings {
             group_order,
             updated_at: 0,
             updated_by: ConfigSource::Desktop,
+            schema_version: GROUP_SETTINGS_SCHEMA_VERSION,
         }
     }
 }
@@ -202,6 +230,55 @@ impl GroupSettings {
     }
 }
 
+fn normalize_model_for_match(value: &str) -> String {
+    let normalized = value.trim().to_lowercase();
+    if normalized.is_empty() {
+        return normalized;
+    }
+    if normalized.starts_with("gemini-3.1-flash")
+        || normalized.starts_with("gemini-2.5-flash")
+        || normalized.starts_with("gemini-3-flash")
+    {
+        return "gemini-3-flash".to_string();
+    }
+    if normalized.starts_with("gemini-3.1-pro-high") || normalized.starts_with("gemini-3-pro-high")
+    {
+        return "gemini-3.1-pro-high".to_string();
+    }
+    if normalized.starts_with("gemini-3.1-pro-low") || normalized.starts_with("gemini-3-pro-low") {
+        return "gemini-3.1-pro-low".to_string();
+    }
+    if normalized.starts_with("claude-sonnet-4-6") || normalized.starts_with("claude-sonnet-4-5")
+    {
+        return "claude-sonnet-4-6".to_string();
+    }
+    if normalized.starts_with("claude-opus-4-6-thinking")
+        || normalized.starts_with("claude-opus-4-5-thinking")
+    {
+        return "claude-opus-4-6-thinking".to_string();
+    }
+    match normalized.as_str() {
+        "gemini-3-pro-high" => "gemini-3.1-pro-high".to_string(),
+        "gemini-3-pro-low" => "gemini-3.1-pro-low".to_string(),
+        "claude-sonnet-4-5" => "claude-sonnet-4-6".to_string(),
+        "claude-sonnet-4-5-thinking" => "claude-sonnet-4-6".to_string(),
+        "claude-opus-4-5-thinking" => "claude-opus-4-6-thinking".to_string(),
+        _ => normalized,
+    }
+}
+
+/// 判断 API 返回的模型名是否属于分组中的模型（兼容新旧版本模型名）
+pub fn model_matches(model_name: &str, target: &str) -> bool {
+    let left = normalize_model_for_match(model_name);
+    let right = normalize_model_for_match(target);
+    if left.is_empty() || right.is_empty() {
+        return false;
+    }
+    left == right
+        || left.starts_with(&(right.clone() + "-"))
+        || right.starts_with(&(left + "-"))
+}
+
 fn migrate_legacy_group_names(settings: &mut GroupSettings) {
     let mut migrate_name = |group_id: &str, legacy_name: &str, new_name: &str| {
         if let Some(current_name) = settings.group_names.get(group_id) {
@@ -232,6 +309,15 @@ fn remove_deprecated_groups(settings: &mut GroupSettings) {
     });
 }
 
+fn migrate_v1_to_v2(value: &mut Value) -> Result<(), String> {
+    let mut settings: GroupSettings =
+        serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    migrate_legacy_group_names(&mut settings);
+    remove_deprecated_groups(&mut settings);
+    *value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 /// 获取分组配置文件路径
 fn get_group_settings_path() -> PathBuf {
     get_shared_dir().join(GROUP_SETTINGS_FILE)
@@ -248,6 +334,7 @@ pub fn load_group_settings() -> GroupSettings {
 
     match fs::read_to_string(&path) {
         Ok(content) => {
+            let content = schema_migration::upgrade_on_load(&GROUP_SETTINGS_SCHEMA, &path, content);
             let mut settings: GroupSettings = serde_json::from_str(&content).unwrap_or_else(|e| {
                 crate::modules::logger::log_warn(&format!(
                     "[GroupSettings] 解析配置失败, 返回默认配置: {}",
@@ -282,8 +369,6 @@ pub fn load_group_settings() -> GroupSettings {
                 }
             }
 
-            migrate_legacy_group_names(&mut settings);
-            remove_deprecated_groups(&mut settings);
             if settings != original_settings {
                 if let Err(e) = save_group_settings(&settings) {
                     crate::modules::logger::log_warn(&format!(
@@ -308,6 +393,8 @@ pub fn load_group_settings() -> GroupSettings {
 /// 保存分组配置
 pub fn save_group_settings(settings: &GroupSettings) -> Result<(), String> {
     let path = get_group_settings_path();
+    let _lock = file_lock::lock_exclusive(&path, "save_group_settings")?;
+    schema_migration::ensure_writable(&GROUP_SETTINGS_SCHEMA, &path)?;
 
     // 确保目录存在
     if let Some(parent) = path.parent() {
@@ -317,7 +404,7 @@ pub fn save_group_settings(settings: &GroupSettings) -> Result<(), String> {
     let content =
         serde_json::to_string_pretty(settings).map_err(|e| format!("序列化失败: {}", e))?;
 
-    fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
 
     crate::modules::logger::log_info(&format!(
         "[GroupSettings] 保存配置成功: {} 个映射, {} 个分组",
@@ -328,9 +415,17 @@ pub fn save_group_settings(settings: &GroupSettings) -> Result<(), String> {
     Ok(())
 }
 
-/// 更新分组配置
-pub fn update_group_settings(settings: GroupSettings) -> Result<(), String> {
-    save_group_settings(&settings)
+/// 在文件锁内完成「读取-修改-写回」，避免与插件端并发写入时互相覆盖
+pub fn modify_group_settings<F>(purpose: &str, f: F) -> Result<GroupSettings, String>
+where
+    F: FnOnce(&mut GroupSettings),
+{
+    let path = get_group_settings_path();
+    let _lock = file_lock::lock_exclusive(&path, purpose)?;
+    let mut settings = load_group_settings();
+    f(&mut settings);
+    save_group_settings(&settings)?;
+    Ok(settings)
 }
 
 #[cfg(test)]
@@ -406,6 +501,28 @@ mod tests {
         assert!(!settings.group_mappings.contains_key("gemini-3-pro-image"));
     }
 
+    #[test]
+    fn test_migrate_v1_group_settings() {
+        let mut value = serde_json::json!({
+            "groupMappings": { "gemini-3-pro-image": "g3_image" },
+            "groupNames": { "g3_pro": "G3-Pro", "g3_image": "Gemini Image" },
+            "groupOrder": ["g3_pro", "g3_image"]
+        });
+
+        let outcome = schema_migration::upgrade(&GROUP_SETTINGS_SCHEMA, &mut value).unwrap();
+        assert_eq!((outcome.from, outcome.to), (1, GROUP_SETTINGS_SCHEMA_VERSION));
+
+        let settings: GroupSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(settings.schema_version, GROUP_SETTINGS_SCHEMA_VERSION);
+        assert_eq!(
+            settings.group_names.get("g3_pro").map(String::as_str),
+            Some(GROUP_NAME_GEMINI_PRO)
+        );
+        assert!(!settings.group_names.contains_key("g3_image"));
+        assert!(settings.group_mappings.is_empty());
+        assert_eq!(settings.group_order, vec!["g3_pro".to_string()]);
+    }
+
     #[test]
     fn test_set_model_group() {
         let mut settings = GroupSettings::default();
@@ -602,6 +602,7 @@ pub async fn import_from_local_logic() -> Result<models::Account, String> {
 /// 从 JSON 导入账号
 pub async fn import_from_json_logic(json_content: String) -> Result<Vec<models::Account>, String> {
     modules::logger::log_info("开始从 JSON 导入账号...");
+    modules::backup::snapshot_before("import_from_json");
 
     // 简化格式: [{"email": "xxx", "refresh_token": "..."}]
     #[derive(serde::Deserialize)]
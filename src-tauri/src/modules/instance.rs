@@ -2,18 +2,15 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
 use crate::models::{DefaultInstanceSettings, InstanceProfile, InstanceStore};
 use crate::modules;
-use crate::modules::instance_store;
+use crate::modules::instance_manager::{InstanceBackend, InstanceManager};
 
 pub use crate::modules::instance_store::{CreateInstanceParams, UpdateInstanceParams};
 
-static INSTANCE_STORE_LOCK: std::sync::LazyLock<Mutex<()>> =
-    std::sync::LazyLock::new(|| Mutex::new(()));
+static INSTANCE_STORE_LOCK: Mutex<()> = Mutex::new(());
 
 const INSTANCES_FILE: &str = "instances.json";
 
@@ -24,24 +21,40 @@ pub struct InstanceDefaults {
     pub default_user_data_dir: String,
 }
 
-fn instances_path() -> Result<PathBuf, String> {
-    let data_dir = modules::account::get_data_dir()?;
-    Ok(data_dir.join(INSTANCES_FILE))
+pub struct AntigravityInstances;
+
+impl InstanceBackend for AntigravityInstances {
+    fn display_name(&self) -> &'static str {
+        "Antigravity"
+    }
+
+    fn store_file(&self) -> &'static str {
+        INSTANCES_FILE
+    }
+
+    fn store_lock(&self) -> &'static Mutex<()> {
+        &INSTANCE_STORE_LOCK
+    }
+
+    fn default_user_data_dir(&self) -> Result<PathBuf, String> {
+        get_default_user_data_dir()
+    }
+
+    fn instances_root_dir(&self) -> Result<PathBuf, String> {
+        get_default_instances_root_dir()
+    }
 }
 
 pub fn load_instance_store() -> Result<InstanceStore, String> {
-    let path = instances_path()?;
-    instance_store::load_instance_store(&path, INSTANCES_FILE)
+    AntigravityInstances.load_instance_store()
 }
 
 pub fn save_instance_store(store: &InstanceStore) -> Result<(), String> {
-    let path = instances_path()?;
-    instance_store::save_instance_store(&path, INSTANCES_FILE, store)
+    AntigravityInstances.save_instance_store(store)
 }
 
 pub fn load_default_settings() -> Result<DefaultInstanceSettings, String> {
-    let store = load_instance_store()?;
-    Ok(store.default_settings)
+    AntigravityInstances.load_default_settings()
 }
 
 pub fn update_default_settings(
@@ -49,31 +62,7 @@ pub fn update_default_settings(
     extra_args: Option<String>,
     follow_local_account: Option<bool>,
 ) -> Result<DefaultInstanceSettings, String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let settings = &mut store.default_settings;
-
-    if follow_local_account == Some(true) {
-        settings.follow_local_account = true;
-        settings.bind_account_id = None;
-    }
-
-    if let Some(bind) = bind_account_id {
-        settings.bind_account_id = bind;
-        settings.follow_local_account = false;
-    }
-
-    if follow_local_account == Some(false) && settings.bind_account_id.is_none() {
-        settings.follow_local_account = false;
-    }
-
-    if let Some(args) = extra_args {
-        settings.extra_args = args.trim().to_string();
-    }
-
-    let updated = settings.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    AntigravityInstances.update_default_settings(bind_account_id, extra_args, follow_local_account)
 }
 
 pub fn get_default_user_data_dir() -> Result<PathBuf, String> {
@@ -125,12 +114,7 @@ pub fn get_default_instances_root_dir() -> Result<PathBuf, String> {
 }
 
 pub fn get_instance_defaults() -> Result<InstanceDefaults, String> {
-    let root_dir = get_default_instances_root_dir()?;
-    let default_user_data_dir = get_default_user_data_dir()?;
-    Ok(InstanceDefaults {
-        root_dir: root_dir.to_string_lossy().to_string(),
-        default_user_data_dir: default_user_data_dir.to_string_lossy().to_string(),
-    })
+    AntigravityInstances.get_instance_defaults()
 }
 
 fn is_ignored_entry_name(name: &str) -> bool {
@@ -218,148 +202,15 @@ pub fn inject_account_to_profile(profile_dir: &Path, account_id: &str) -> Result
 }
 
 pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile, String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-
-    let name = instance_store::normalize_name(&params.name)?;
-    let user_data_dir = params.user_data_dir.trim().to_string();
-    if user_data_dir.is_empty() {
-        return Err("实例目录不能为空".to_string());
-    }
-
-    instance_store::ensure_unique(&store, &name, &user_data_dir, None)?;
-
-    let user_dir_path = PathBuf::from(&user_data_dir);
-
-    let init_mode = params
-        .init_mode
-        .as_deref()
-        .unwrap_or("copy")
-        .to_ascii_lowercase();
-    let create_empty = init_mode == "empty";
-
-    if create_empty {
-        if user_dir_path.exists() {
-            let mut has_entries = false;
-            if let Ok(mut iter) = fs::read_dir(&user_dir_path) {
-                if iter.next().is_some() {
-                    has_entries = true;
-                }
-            }
-            if has_entries {
-                let resolved_path = instance_store::display_path(&user_dir_path);
-                return Err(format!("空白实例需要目标目录为空: {}", resolved_path));
-            }
-        }
-        fs::create_dir_all(&user_dir_path).map_err(|e| format!("创建实例目录失败: {}", e))?;
-    } else {
-        let source_dir = match params.copy_source_instance_id.as_deref() {
-            Some("__default__") | None => get_default_user_data_dir()?,
-            Some(source_id) => {
-                let source_instance = store
-                    .instances
-                    .iter()
-                    .find(|item| item.id == source_id)
-                    .ok_or("复制来源实例不存在")?;
-                PathBuf::from(&source_instance.user_data_dir)
-            }
-        };
-
-        if user_dir_path.exists() {
-            let mut has_entries = false;
-            if let Ok(mut iter) = fs::read_dir(&user_dir_path) {
-                if iter.next().is_some() {
-                    has_entries = true;
-                }
-            }
-            if has_entries {
-                let resolved_path = instance_store::display_path(&user_dir_path);
-                modules::logger::log_info(&format!(
-                    "[Instance] 复制来源实例需要空目录，但目标已存在: {}",
-                    resolved_path
-                ));
-                return Err(format!("复制来源实例需要目标目录为空: {}", resolved_path));
-            }
-        }
-
-        instance_store::copy_dir_recursive(&source_dir, &user_dir_path)?;
-    }
-
-    let instance = InstanceProfile {
-        id: Uuid::new_v4().to_string(),
-        name,
-        user_data_dir,
-        extra_args: params.extra_args.trim().to_string(),
-        bind_account_id: if create_empty {
-            None
-        } else {
-            params.bind_account_id
-        },
-        created_at: Utc::now().timestamp_millis(),
-        last_launched_at: None,
-        last_pid: None,
-    };
-
-    store.instances.push(instance.clone());
-    save_instance_store(&store)?;
-    Ok(instance)
+    AntigravityInstances.create_instance(params)
 }
 
 pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile, String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let index = store
-        .instances
-        .iter()
-        .position(|instance| instance.id == params.instance_id)
-        .ok_or("实例不存在")?;
-
-    let current_id = store.instances[index].id.clone();
-    let current_dir = store.instances[index].user_data_dir.clone();
-    let next_name = params
-        .name
-        .as_ref()
-        .map(|name| instance_store::normalize_name(name))
-        .transpose()?;
-
-    if let Some(ref normalized) = next_name {
-        instance_store::ensure_unique(&store, normalized, &current_dir, Some(&current_id))?;
-    }
-
-    let instance = &mut store.instances[index];
-    if let Some(normalized) = next_name {
-        instance.name = normalized;
-    }
-    if let Some(ref extra_args) = params.extra_args {
-        instance.extra_args = extra_args.trim().to_string();
-    }
-    if let Some(bind) = params.bind_account_id.clone() {
-        instance.bind_account_id = bind;
-    }
-
-    let updated = instance.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    AntigravityInstances.update_instance(params)
 }
 
 pub fn delete_instance(instance_id: &str) -> Result<(), String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let index = store
-        .instances
-        .iter()
-        .position(|instance| instance.id == instance_id)
-        .ok_or("实例不存在")?;
-    let user_data_dir = store.instances[index].user_data_dir.clone();
-
-    if !user_data_dir.trim().is_empty() {
-        let dir_path = PathBuf::from(&user_data_dir);
-        delete_instance_directory(&dir_path)?;
-    }
-
-    store.instances.remove(index);
-    save_instance_store(&store)?;
-    Ok(())
+    AntigravityInstances.delete_instance(instance_id)
 }
 
 pub fn delete_instance_directory(dir_path: &Path) -> Result<(), String> {
@@ -379,7 +230,7 @@ pub fn delete_instance_directory(dir_path: &Path) -> Result<(), String> {
             .ok_or("实例目录无效")?;
         let mut target = trash_dir.join(&base_name);
         if target.exists() {
-            let suffix = Utc::now().timestamp_millis();
+            let suffix = chrono::Utc::now().timestamp_millis();
             target = trash_dir.join(format!("{}-{}", base_name, suffix));
         }
         match fs::rename(dir_path, &target) {
@@ -399,72 +250,18 @@ pub fn delete_instance_directory(dir_path: &Path) -> Result<(), String> {
     }
 }
 
-#[allow(dead_code)]
-pub fn update_instance_last_launched(instance_id: &str) -> Result<InstanceProfile, String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_launched_at = Some(Utc::now().timestamp_millis());
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
-}
-
 pub fn update_instance_after_start(instance_id: &str, pid: u32) -> Result<InstanceProfile, String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_launched_at = Some(Utc::now().timestamp_millis());
-            instance.last_pid = Some(pid);
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    AntigravityInstances.update_instance_after_start(instance_id, pid)
 }
 
 pub fn update_instance_pid(instance_id: &str, pid: Option<u32>) -> Result<InstanceProfile, String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_pid = pid;
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    AntigravityInstances.update_instance_pid(instance_id, pid)
 }
 
 pub fn update_default_pid(pid: Option<u32>) -> Result<DefaultInstanceSettings, String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    store.default_settings.last_pid = pid;
-    let updated = store.default_settings.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    AntigravityInstances.update_default_pid(pid)
 }
 
 pub fn clear_all_pids() -> Result<(), String> {
-    let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    store.default_settings.last_pid = None;
-    for instance in &mut store.instances {
-        instance.last_pid = None;
-    }
-    save_instance_store(&store)?;
-    Ok(())
+    AntigravityInstances.clear_all_pids()
 }
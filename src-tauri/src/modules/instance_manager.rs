@@ -0,0 +1,1027 @@
+//! 多开实例通用管理
+//!
+//! 各 IDE 的实例模块实现 `InstanceBackend`（存储文件、默认目录等差异），实例的增删改、
+//! PID 记录以及按 `--user-data-dir` 匹配进程由 `InstanceManager` 统一提供。
+//! 新增 IDE 时只需实现 backend，并补充启动与账号注入逻辑。
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sysinfo::{ProcessRefreshKind, System, UpdateKind};
+use uuid::Uuid;
+
+use crate::models::{DefaultInstanceSettings, InstanceProfile, InstanceStore};
+use crate::modules;
+use crate::modules::instance::InstanceDefaults;
+use crate::modules::instance_store::{self, CreateInstanceParams, UpdateInstanceParams};
+
+/// 复制来源为默认实例时使用的 ID
+pub const DEFAULT_SOURCE_INSTANCE_ID: &str = "__default__";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceAction {
+    Created,
+    Updated,
+    Deleted,
+    Started,
+    Stopped,
+}
+
+/// 实例生命周期变化，推送给订阅 instances 主题的 WebSocket 客户端
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceLifecycleEvent {
+    /// IDE 名称，如 "Kiro"
+    pub ide: String,
+    /// 为空表示默认实例
+    pub instance_id: Option<String>,
+    pub action: InstanceAction,
+    pub pid: Option<u32>,
+    pub occurred_at: i64,
+}
+
+fn publish_lifecycle(
+    ide: &str,
+    instance_id: Option<&str>,
+    action: InstanceAction,
+    pid: Option<u32>,
+) {
+    modules::websocket::broadcast_instance_changed(InstanceLifecycleEvent {
+        ide: ide.to_string(),
+        instance_id: instance_id.map(str::to_string),
+        action,
+        pid,
+        occurred_at: Utc::now().timestamp_millis(),
+    });
+}
+
+/// PID 变化对应的生命周期动作；PID 未变化时不产生事件
+fn pid_transition(previous: Option<u32>, next: Option<u32>) -> Option<InstanceAction> {
+    if previous == next {
+        return None;
+    }
+    Some(if next.is_some() {
+        InstanceAction::Started
+    } else {
+        InstanceAction::Stopped
+    })
+}
+
+pub trait InstanceBackend {
+    /// 日志与提示中使用的名称，如 "Kiro"
+    fn display_name(&self) -> &'static str;
+    /// 数据目录下的实例配置文件名
+    fn store_file(&self) -> &'static str;
+    /// 串行化同一配置文件读写的进程内锁
+    fn store_lock(&self) -> &'static Mutex<()>;
+    fn default_user_data_dir(&self) -> Result<PathBuf, String>;
+    fn instances_root_dir(&self) -> Result<PathBuf, String>;
+
+    /// 默认实例是否支持“跟随当前账号”
+    fn supports_follow_local_account(&self) -> bool {
+        true
+    }
+
+    fn store_path(&self) -> Result<PathBuf, String> {
+        let data_dir = modules::account::get_data_dir()?;
+        Ok(data_dir.join(self.store_file()))
+    }
+}
+
+/// 实例生命周期与 PID 跟踪，所有 `InstanceBackend` 自动获得
+pub trait InstanceManager: InstanceBackend {
+    fn load_instance_store(&self) -> Result<InstanceStore, String> {
+        let path = self.store_path()?;
+        instance_store::load_instance_store(&path, self.store_file())
+    }
+
+    fn save_instance_store(&self, store: &InstanceStore) -> Result<(), String> {
+        let path = self.store_path()?;
+        instance_store::save_instance_store(&path, self.store_file(), store)
+    }
+
+    fn load_default_settings(&self) -> Result<DefaultInstanceSettings, String> {
+        Ok(self.load_instance_store()?.default_settings)
+    }
+
+    fn update_default_settings(
+        &self,
+        bind_account_id: Option<Option<String>>,
+        extra_args: Option<String>,
+        follow_local_account: Option<bool>,
+    ) -> Result<DefaultInstanceSettings, String> {
+        let follow_supported = self.supports_follow_local_account();
+        modify_store(self, |store| {
+            let settings = &mut store.default_settings;
+
+            if follow_local_account == Some(true) {
+                // 不支持“跟随当前账号”的平台直接忽略该选项
+                settings.follow_local_account = follow_supported;
+                if follow_supported {
+                    settings.bind_account_id = None;
+                }
+            }
+
+            if let Some(bind) = bind_account_id {
+                settings.bind_account_id = bind;
+                settings.follow_local_account = false;
+            }
+
+            if follow_local_account == Some(false) && settings.bind_account_id.is_none() {
+                settings.follow_local_account = false;
+            }
+
+            if let Some(args) = extra_args {
+                settings.extra_args = args.trim().to_string();
+            }
+
+            Ok(settings.clone())
+        })
+    }
+
+    fn get_instance_defaults(&self) -> Result<InstanceDefaults, String> {
+        let root_dir = self.instances_root_dir()?;
+        let default_user_data_dir = self.default_user_data_dir()?;
+        Ok(InstanceDefaults {
+            root_dir: root_dir.to_string_lossy().to_string(),
+            default_user_data_dir: default_user_data_dir.to_string_lossy().to_string(),
+        })
+    }
+
+    fn create_instance(&self, params: CreateInstanceParams) -> Result<InstanceProfile, String> {
+        let instance = modify_store(self, |store| {
+            let name = instance_store::normalize_name(&params.name)?;
+            let user_data_dir = params.user_data_dir.trim().to_string();
+            if user_data_dir.is_empty() {
+                return Err("实例目录不能为空".to_string());
+            }
+
+            instance_store::ensure_unique(store, &name, &user_data_dir, None)?;
+
+            let user_dir_path = PathBuf::from(&user_data_dir);
+            let init_mode = params
+                .init_mode
+                .as_deref()
+                .unwrap_or("copy")
+                .to_ascii_lowercase();
+            let create_empty = init_mode == "empty";
+
+            if create_empty {
+                if dir_has_entries(&user_dir_path) {
+                    let resolved_path = instance_store::display_path(&user_dir_path);
+                    return Err(format!("空白实例需要目标目录为空: {}", resolved_path));
+                }
+                fs::create_dir_all(&user_dir_path)
+                    .map_err(|e| format!("创建实例目录失败: {}", e))?;
+            } else {
+                let source_dir = match params.copy_source_instance_id.as_deref() {
+                    Some(DEFAULT_SOURCE_INSTANCE_ID) | None => self.default_user_data_dir()?,
+                    Some(source_id) => {
+                        let source_instance = store
+                            .instances
+                            .iter()
+                            .find(|item| item.id == source_id)
+                            .ok_or("复制来源实例不存在")?;
+                        PathBuf::from(&source_instance.user_data_dir)
+                    }
+                };
+
+                if dir_has_entries(&user_dir_path) {
+                    let resolved_path = instance_store::display_path(&user_dir_path);
+                    modules::logger::log_info(&format!(
+                        "[{} Instance] 复制来源实例需要空目录，但目标已存在: {}",
+                        self.display_name(),
+                        resolved_path
+                    ));
+                    return Err(format!("复制来源实例需要目标目录为空: {}", resolved_path));
+                }
+
+                if !source_dir.exists() {
+                    return Err("未找到复制来源目录，请先确保来源实例已初始化".to_string());
+                }
+
+                instance_store::copy_dir_recursive(&source_dir, &user_dir_path)?;
+            }
+
+            let instance = InstanceProfile {
+                id: Uuid::new_v4().to_string(),
+                name,
+                user_data_dir,
+                extra_args: params.extra_args.trim().to_string(),
+                bind_account_id: if create_empty {
+                    None
+                } else {
+                    params.bind_account_id
+                },
+                created_at: Utc::now().timestamp_millis(),
+                last_launched_at: None,
+                last_pid: None,
+            };
+
+            store.instances.push(instance.clone());
+            Ok(instance)
+        })?;
+        publish_lifecycle(
+            self.display_name(),
+            Some(&instance.id),
+            InstanceAction::Created,
+            None,
+        );
+        Ok(instance)
+    }
+
+    fn update_instance(&self, params: UpdateInstanceParams) -> Result<InstanceProfile, String> {
+        let instance = modify_store(self, |store| {
+            let index = store
+                .instances
+                .iter()
+                .position(|instance| instance.id == params.instance_id)
+                .ok_or("实例不存在")?;
+
+            let current_id = store.instances[index].id.clone();
+            let current_dir = store.instances[index].user_data_dir.clone();
+            let next_name = params
+                .name
+                .as_ref()
+                .map(|name| instance_store::normalize_name(name))
+                .transpose()?;
+
+            if let Some(ref normalized) = next_name {
+                instance_store::ensure_unique(store, normalized, &current_dir, Some(&current_id))?;
+            }
+
+            let instance = &mut store.instances[index];
+            if let Some(normalized) = next_name {
+                instance.name = normalized;
+            }
+            if let Some(ref extra_args) = params.extra_args {
+                instance.extra_args = extra_args.trim().to_string();
+            }
+            if let Some(bind) = params.bind_account_id.clone() {
+                instance.bind_account_id = bind;
+            }
+
+            Ok(instance.clone())
+        })?;
+        publish_lifecycle(
+            self.display_name(),
+            Some(&instance.id),
+            InstanceAction::Updated,
+            instance.last_pid,
+        );
+        Ok(instance)
+    }
+
+    /// 删除实例记录，并移除（macOS 下移到废纸篓）实例目录
+    fn delete_instance(&self, instance_id: &str) -> Result<(), String> {
+        modify_store(self, |store| {
+            let index = store
+                .instances
+                .iter()
+                .position(|instance| instance.id == instance_id)
+                .ok_or("实例不存在")?;
+            let user_data_dir = store.instances[index].user_data_dir.clone();
+
+            if !user_data_dir.trim().is_empty() {
+                let dir_path = PathBuf::from(&user_data_dir);
+                modules::instance::delete_instance_directory(&dir_path)?;
+            }
+
+            store.instances.remove(index);
+            Ok(())
+        })?;
+        publish_lifecycle(
+            self.display_name(),
+            Some(instance_id),
+            InstanceAction::Deleted,
+            None,
+        );
+        Ok(())
+    }
+
+    fn update_instance_after_start(
+        &self,
+        instance_id: &str,
+        pid: u32,
+    ) -> Result<InstanceProfile, String> {
+        let instance = modify_instance(self, instance_id, |instance| {
+            instance.last_launched_at = Some(Utc::now().timestamp_millis());
+            instance.last_pid = Some(pid);
+        })?;
+        publish_lifecycle(
+            self.display_name(),
+            Some(instance_id),
+            InstanceAction::Started,
+            Some(pid),
+        );
+        Ok(instance)
+    }
+
+    fn update_instance_pid(
+        &self,
+        instance_id: &str,
+        pid: Option<u32>,
+    ) -> Result<InstanceProfile, String> {
+        let mut previous = None;
+        let instance = modify_instance(self, instance_id, |instance| {
+            previous = instance.last_pid;
+            instance.last_pid = pid;
+        })?;
+        if let Some(action) = pid_transition(previous, pid) {
+            publish_lifecycle(self.display_name(), Some(instance_id), action, pid);
+        }
+        Ok(instance)
+    }
+
+    fn update_default_pid(&self, pid: Option<u32>) -> Result<DefaultInstanceSettings, String> {
+        let mut previous = None;
+        let settings = modify_store(self, |store| {
+            previous = store.default_settings.last_pid;
+            store.default_settings.last_pid = pid;
+            Ok(store.default_settings.clone())
+        })?;
+        if let Some(action) = pid_transition(previous, pid) {
+            publish_lifecycle(self.display_name(), None, action, pid);
+        }
+        Ok(settings)
+    }
+
+    fn clear_all_pids(&self) -> Result<(), String> {
+        let stopped = modify_store(self, |store| {
+            let mut stopped: Vec<Option<String>> = Vec::new();
+            if store.default_settings.last_pid.take().is_some() {
+                stopped.push(None);
+            }
+            for instance in &mut store.instances {
+                if instance.last_pid.take().is_some() {
+                    stopped.push(Some(instance.id.clone()));
+                }
+            }
+            Ok(stopped)
+        })?;
+        for instance_id in stopped {
+            publish_lifecycle(
+                self.display_name(),
+                instance_id.as_deref(),
+                InstanceAction::Stopped,
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    fn normalized_default_user_data_dir(&self) -> Option<String> {
+        self.default_user_data_dir()
+            .ok()
+            .map(|dir| normalize_path_for_compare(&dir.to_string_lossy()))
+            .filter(|dir| !dir.is_empty())
+    }
+
+    /// 从进程列表中找出实例对应的 PID；`user_data_dir` 为空表示默认实例
+    fn resolve_pid_from_entries(
+        &self,
+        last_pid: Option<u32>,
+        user_data_dir: Option<&str>,
+        entries: &[(u32, Option<String>)],
+    ) -> Option<u32> {
+        let default_dir = self.normalized_default_user_data_dir();
+        let target = normalize_non_empty_path(user_data_dir).or(default_dir.clone())?;
+        let allow_missing_dir = default_dir.as_deref() == Some(target.as_str());
+        let targets = HashSet::from([target.clone()]);
+        let matches = match_pids_by_user_data_dir(entries, &targets, allow_missing_dir);
+
+        if let Some(pid) = last_pid {
+            if modules::process::is_pid_running(pid) {
+                if matches.contains(&pid) {
+                    return Some(pid);
+                }
+                modules::logger::log_warn(&format!(
+                    "[{} Resolve] 忽略不匹配的 last_pid={}，target={}，matched_pids={:?}",
+                    self.display_name(),
+                    pid,
+                    target,
+                    matches
+                ));
+            }
+        }
+
+        pick_preferred_pid(matches)
+    }
+
+    /// 关闭使用指定目录的实例进程；`collect_entries` 仅在有目标目录时调用
+    fn close_instances(
+        &self,
+        user_data_dirs: &[String],
+        timeout_secs: u64,
+        collect_entries: impl FnOnce() -> Vec<(u32, Option<String>)>,
+    ) -> Result<(), String>
+    where
+        Self: Sized,
+    {
+        let target_dirs: HashSet<String> = user_data_dirs
+            .iter()
+            .map(|value| normalize_path_for_compare(value))
+            .filter(|value| !value.is_empty())
+            .collect();
+        if target_dirs.is_empty() {
+            return Ok(());
+        }
+
+        let allow_missing_dir = self
+            .normalized_default_user_data_dir()
+            .map(|value| target_dirs.contains(&value))
+            .unwrap_or(false);
+        let mut pids =
+            match_pids_by_user_data_dir(&collect_entries(), &target_dirs, allow_missing_dir);
+        pids.sort();
+        pids.dedup();
+        if pids.is_empty() {
+            return Ok(());
+        }
+
+        for pid in &pids {
+            let _ = modules::process::close_pid(*pid, timeout_secs);
+        }
+
+        let still_running: Vec<u32> = pids
+            .into_iter()
+            .filter(|pid| modules::process::is_pid_running(*pid))
+            .collect();
+        if !still_running.is_empty() {
+            return Err(format!(
+                "无法关闭 {} 实例进程，请手动关闭后重试: {:?}",
+                self.display_name(),
+                still_running
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: InstanceBackend + ?Sized> InstanceManager for T {}
+
+/// 持锁读取配置，修改成功后写回
+fn modify_store<B, R>(
+    backend: &B,
+    apply: impl FnOnce(&mut InstanceStore) -> Result<R, String>,
+) -> Result<R, String>
+where
+    B: InstanceManager + ?Sized,
+{
+    let _lock = backend.store_lock().lock().map_err(|_| "无法获取实例锁")?;
+    let mut store = backend.load_instance_store()?;
+    let result = apply(&mut store)?;
+    backend.save_instance_store(&store)?;
+    Ok(result)
+}
+
+fn modify_instance<B>(
+    backend: &B,
+    instance_id: &str,
+    apply: impl FnOnce(&mut InstanceProfile),
+) -> Result<InstanceProfile, String>
+where
+    B: InstanceManager + ?Sized,
+{
+    modify_store(backend, |store| {
+        let instance = store
+            .instances
+            .iter_mut()
+            .find(|instance| instance.id == instance_id)
+            .ok_or("实例不存在")?;
+        apply(instance);
+        Ok(instance.clone())
+    })
+}
+
+fn dir_has_entries(path: &std::path::Path) -> bool {
+    fs::read_dir(path)
+        .map(|mut iter| iter.next().is_some())
+        .unwrap_or(false)
+}
+
+pub fn normalize_path_for_compare(raw: &str) -> String {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return String::new();
+    }
+    let resolved = fs::canonicalize(trimmed)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| trimmed.to_string());
+
+    #[cfg(target_os = "windows")]
+    {
+        return resolved.to_lowercase();
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        resolved
+    }
+}
+
+fn normalize_non_empty_path(value: Option<&str>) -> Option<String> {
+    value
+        .map(normalize_path_for_compare)
+        .filter(|text| !text.is_empty())
+}
+
+fn parse_user_data_dir_value(raw: &str) -> Option<String> {
+    let rest = raw.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    let value = if let Some(quoted) = rest.strip_prefix('"') {
+        quoted.split('"').next().unwrap_or(quoted)
+    } else if let Some(quoted) = rest.strip_prefix('\'') {
+        quoted.split('\'').next().unwrap_or(quoted)
+    } else {
+        let end = rest.find(" --").unwrap_or(rest.len());
+        &rest[..end]
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// 形如 `KEY=value` 的环境变量（macOS `ps eww` 会把环境变量接在参数后面）
+pub fn is_env_token(token: &str) -> bool {
+    let (key, _) = match token.split_once('=') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    if key.is_empty() {
+        return false;
+    }
+    let mut chars = key.chars();
+    let first = match chars.next() {
+        Some(value) => value,
+        None => return false,
+    };
+    if !(first.is_ascii_alphabetic() || first == '_') {
+        return false;
+    }
+    chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// 取 `--user-data-dir` 之后直到下一个 `--` 选项或环境变量的参数（未加引号的带空格路径会被拆成多段）
+fn join_user_data_dir_parts(tokens: &[String]) -> Option<String> {
+    let parts: Vec<&str> = tokens
+        .iter()
+        .map(String::as_str)
+        .take_while(|part| !part.starts_with("--") && !is_env_token(part))
+        .collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+/// 从进程参数中提取 `--user-data-dir`
+pub fn extract_user_data_dir(args: &[OsString]) -> Option<String> {
+    let tokens: Vec<String> = args
+        .iter()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect();
+    for (index, token) in tokens.iter().enumerate() {
+        if let Some(rest) = token.strip_prefix("--user-data-dir=") {
+            return parse_user_data_dir_value(rest);
+        }
+        if token == "--user-data-dir" {
+            return join_user_data_dir_parts(&tokens[index + 1..]);
+        }
+    }
+    None
+}
+
+/// 按空白拆分命令行，支持单引号和双引号包裹
+pub fn split_command_tokens(command_line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+
+    for ch in command_line.chars() {
+        match quote {
+            Some(q) => {
+                if ch == q {
+                    quote = None;
+                } else {
+                    current.push(ch);
+                }
+            }
+            None => {
+                if ch == '"' || ch == '\'' {
+                    quote = Some(ch);
+                } else if ch.is_whitespace() {
+                    if !current.is_empty() {
+                        tokens.push(std::mem::take(&mut current));
+                    }
+                } else {
+                    current.push(ch);
+                }
+            }
+        }
+    }
+
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+/// 从完整命令行（如 `ps -o command` 输出）中提取 `--user-data-dir`
+pub fn extract_user_data_dir_from_command_line(command_line: &str) -> Option<String> {
+    let tokens = split_command_tokens(command_line);
+    for (index, token) in tokens.iter().enumerate() {
+        if let Some(rest) = token.strip_prefix("--user-data-dir=") {
+            if let Some(value) = parse_user_data_dir_value(rest) {
+                return Some(value);
+            }
+        }
+        if token == "--user-data-dir" {
+            return join_user_data_dir_parts(&tokens[index + 1..]);
+        }
+    }
+    None
+}
+
+/// Electron 辅助进程（渲染、GPU 等），不算作实例主进程
+pub fn is_helper_process(name: &str, args_line: &str) -> bool {
+    args_line.contains("--type=")
+        || name.contains("helper")
+        || name.contains("renderer")
+        || name.contains("gpu")
+        || name.contains("utility")
+        || name.contains("crashpad")
+        || name.contains("sandbox")
+}
+
+fn command_trace_enabled() -> bool {
+    if let Ok(value) = std::env::var("COCKPIT_COMMAND_TRACE") {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => return true,
+            "0" | "false" | "no" | "off" => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn quote_command_part(part: &str) -> String {
+    if part.is_empty() {
+        return "\"\"".to_string();
+    }
+    let needs_quote = part
+        .chars()
+        .any(|ch| ch.is_whitespace() || matches!(ch, '"' | '\'' | '$' | '`' | '|' | '&' | ';'));
+    if needs_quote {
+        format!("{:?}", part)
+    } else {
+        part.to_string()
+    }
+}
+
+fn format_command_preview(command: &Command) -> String {
+    let program = quote_command_part(command.get_program().to_string_lossy().as_ref());
+    let args = command
+        .get_args()
+        .map(|arg| quote_command_part(arg.to_string_lossy().as_ref()))
+        .collect::<Vec<String>>();
+    if args.is_empty() {
+        program
+    } else {
+        format!("{} {}", program, args.join(" "))
+    }
+}
+
+/// 启动进程；设置 `COCKPIT_COMMAND_TRACE` 时记录完整命令与耗时
+pub fn spawn_command_with_trace(
+    cmd: &mut Command,
+    tag: &str,
+) -> std::io::Result<std::process::Child> {
+    let preview = format_command_preview(cmd);
+    if command_trace_enabled() {
+        modules::logger::log_info(&format!("[CmdTrace][{}] EXEC {}", tag, preview));
+    }
+    let start = std::time::Instant::now();
+    let result = cmd.spawn();
+    if command_trace_enabled() {
+        match &result {
+            Ok(child) => modules::logger::log_info(&format!(
+                "[CmdTrace][{}] SPAWN elapsed={}ms pid={} cmd={}",
+                tag,
+                start.elapsed().as_millis(),
+                child.id(),
+                preview
+            )),
+            Err(err) => modules::logger::log_warn(&format!(
+                "[CmdTrace][{}] SPAWN_ERROR elapsed={}ms cmd={} err={}",
+                tag,
+                start.elapsed().as_millis(),
+                preview,
+                err
+            )),
+        }
+    }
+    result
+}
+
+fn collect_running_process_exe_by_pid() -> HashMap<u32, String> {
+    let mut map = HashMap::new();
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+    );
+    for (pid, process) in system.processes() {
+        let Some(exe) = process.exe().and_then(|value| value.to_str()) else {
+            continue;
+        };
+        let normalized = normalize_path_for_compare(exe);
+        if normalized.is_empty() {
+            continue;
+        }
+        map.insert(pid.as_u32(), normalized);
+    }
+    map
+}
+
+/// 只保留可执行文件与配置的启动路径一致的进程；`expected` 为空时视为全部不匹配
+pub fn filter_entries_by_launch_path(
+    entries: Vec<(u32, Option<String>)>,
+    expected: Option<String>,
+    tag: &str,
+) -> Vec<(u32, Option<String>)> {
+    if entries.is_empty() {
+        return entries;
+    }
+    let Some(expected) = expected else {
+        return Vec::new();
+    };
+    let exe_by_pid = collect_running_process_exe_by_pid();
+    let mut result = Vec::new();
+    let mut missing_exe = 0usize;
+    let mut path_mismatch = 0usize;
+    for (pid, dir) in entries {
+        match exe_by_pid.get(&pid) {
+            Some(actual) if actual == &expected => result.push((pid, dir)),
+            Some(_) => path_mismatch += 1,
+            None => missing_exe += 1,
+        }
+    }
+    if result.is_empty() {
+        modules::logger::log_warn(&format!(
+            "[{} Resolve] 启动路径硬匹配未命中：expected={}, path_mismatch={}, missing_exe={}",
+            tag, expected, path_mismatch, missing_exe
+        ));
+    }
+    result
+}
+
+pub fn pick_preferred_pid(mut pids: Vec<u32>) -> Option<u32> {
+    pids.sort();
+    pids.dedup();
+    pids.first().copied()
+}
+
+/// 按 user-data-dir 匹配进程；`allow_missing_dir` 为 true 时未带该参数的进程（默认实例）也算命中
+pub fn match_pids_by_user_data_dir(
+    entries: &[(u32, Option<String>)],
+    target_dirs: &HashSet<String>,
+    allow_missing_dir: bool,
+) -> Vec<u32> {
+    entries
+        .iter()
+        .filter(|(_, dir)| match dir {
+            Some(dir) => target_dirs.contains(dir),
+            None => allow_missing_dir,
+        })
+        .map(|(pid, _)| *pid)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_STORE_LOCK: Mutex<()> = Mutex::new(());
+
+    struct TestBackend {
+        root: PathBuf,
+    }
+
+    impl InstanceBackend for TestBackend {
+        fn display_name(&self) -> &'static str {
+            "Test"
+        }
+
+        fn store_file(&self) -> &'static str {
+            "test_instances.json"
+        }
+
+        fn store_lock(&self) -> &'static Mutex<()> {
+            &TEST_STORE_LOCK
+        }
+
+        fn default_user_data_dir(&self) -> Result<PathBuf, String> {
+            Ok(self.root.join("default"))
+        }
+
+        fn instances_root_dir(&self) -> Result<PathBuf, String> {
+            Ok(self.root.join("instances"))
+        }
+
+        fn supports_follow_local_account(&self) -> bool {
+            false
+        }
+
+        fn store_path(&self) -> Result<PathBuf, String> {
+            Ok(self.root.join(self.store_file()))
+        }
+    }
+
+    fn create_params(name: &str, user_data_dir: &std::path::Path) -> CreateInstanceParams {
+        CreateInstanceParams {
+            name: name.to_string(),
+            user_data_dir: user_data_dir.to_string_lossy().to_string(),
+            extra_args: " --disable-gpu ".to_string(),
+            bind_account_id: Some("acc-1".to_string()),
+            copy_source_instance_id: None,
+            init_mode: None,
+        }
+    }
+
+    #[test]
+    fn lifecycle_tracks_pids_and_rejects_duplicates() {
+        let root = std::env::temp_dir().join(format!("cockpit-instances-{}", Uuid::new_v4()));
+        let backend = TestBackend { root: root.clone() };
+        fs::create_dir_all(root.join("default")).unwrap();
+        fs::write(root.join("default").join("state.txt"), "seed").unwrap();
+
+        let first_dir = root.join("instances").join("first");
+        let created = backend
+            .create_instance(create_params(" First ", &first_dir))
+            .unwrap();
+        assert_eq!(created.name, "First");
+        assert_eq!(created.extra_args, "--disable-gpu");
+        assert!(first_dir.join("state.txt").exists());
+        assert!(backend
+            .create_instance(create_params("first", &root.join("other")))
+            .is_err());
+
+        let empty_dir = root.join("instances").join("empty");
+        let mut params = create_params("Empty", &empty_dir);
+        params.init_mode = Some("empty".to_string());
+        let empty = backend.create_instance(params).unwrap();
+        assert_eq!(empty.bind_account_id, None);
+
+        let started = backend
+            .update_instance_after_start(&created.id, 42)
+            .unwrap();
+        assert_eq!(started.last_pid, Some(42));
+        assert!(started.last_launched_at.is_some());
+        backend.update_default_pid(Some(7)).unwrap();
+        let settings = backend
+            .update_default_settings(None, None, Some(true))
+            .unwrap();
+        assert!(!settings.follow_local_account);
+
+        backend.clear_all_pids().unwrap();
+        let store = backend.load_instance_store().unwrap();
+        assert_eq!(store.default_settings.last_pid, None);
+        assert!(store.instances.iter().all(|item| item.last_pid.is_none()));
+
+        backend.delete_instance(&empty.id).unwrap();
+        assert_eq!(backend.load_instance_store().unwrap().instances.len(), 1);
+        assert!(backend.update_instance_pid("missing", None).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn extracts_user_data_dir_from_args_and_command_line() {
+        let args: Vec<OsString> = [
+            "/app/Kiro",
+            "--user-data-dir",
+            "/tmp/my",
+            "dir",
+            "--new-window",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        assert_eq!(extract_user_data_dir(&args).as_deref(), Some("/tmp/my dir"));
+
+        let args: Vec<OsString> = ["/app/Kiro", "--user-data-dir=\"/tmp/quoted dir\""]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(
+            extract_user_data_dir(&args).as_deref(),
+            Some("/tmp/quoted dir")
+        );
+
+        assert_eq!(
+            split_command_tokens(r#"/app/Kiro --user-data-dir "/tmp/a b" --flag='x y'"#),
+            vec!["/app/Kiro", "--user-data-dir", "/tmp/a b", "--flag=x y"]
+        );
+        assert_eq!(
+            extract_user_data_dir_from_command_line(
+                "/app/Kiro --user-data-dir '/tmp/a b' --new-window"
+            )
+            .as_deref(),
+            Some("/tmp/a b")
+        );
+        assert_eq!(
+            extract_user_data_dir_from_command_line("/app/Kiro --user-data-dir --new-window"),
+            None
+        );
+    }
+
+    #[test]
+    fn user_data_dir_stops_at_env_tokens_and_strips_quotes() {
+        let args: Vec<OsString> = [
+            "/app/Code",
+            "--user-data-dir",
+            "/tmp/my",
+            "dir",
+            "HOME=/Users/me",
+            "PATH=/usr/bin",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        assert_eq!(extract_user_data_dir(&args).as_deref(), Some("/tmp/my dir"));
+        assert_eq!(
+            extract_user_data_dir_from_command_line(
+                "/app/Code --user-data-dir /tmp/my dir HOME=/Users/me _TMP=1"
+            )
+            .as_deref(),
+            Some("/tmp/my dir")
+        );
+
+        let args: Vec<OsString> = ["/app/Code", "--user-data-dir='/tmp/single q'"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(
+            extract_user_data_dir(&args).as_deref(),
+            Some("/tmp/single q")
+        );
+        assert_eq!(
+            extract_user_data_dir_from_command_line(
+                r#"/app/Code --user-data-dir="/tmp/q dir" --x"#
+            )
+            .as_deref(),
+            Some("/tmp/q dir")
+        );
+        assert!(is_env_token("ELECTRON_RUN_AS_NODE=1"));
+        assert!(!is_env_token("/tmp/a=b"));
+        assert!(!is_env_token("=x"));
+    }
+
+    #[test]
+    fn matches_pids_by_user_data_dir() {
+        let root = std::env::temp_dir().join(format!("cockpit-pid-{}", Uuid::new_v4()));
+        let backend = TestBackend { root: root.clone() };
+        let default_dir = normalize_path_for_compare(&root.join("default").to_string_lossy());
+        let entries = vec![
+            (30, Some("/tmp/instance-a".to_string())),
+            (20, None),
+            (10, Some(default_dir.clone())),
+            (40, Some("/tmp/instance-b".to_string())),
+        ];
+
+        assert_eq!(
+            backend.resolve_pid_from_entries(None, Some("/tmp/instance-a"), &entries),
+            Some(30)
+        );
+        assert_eq!(
+            backend.resolve_pid_from_entries(None, None, &entries),
+            Some(10)
+        );
+        assert_eq!(
+            backend.resolve_pid_from_entries(None, Some("/tmp/missing"), &entries),
+            None
+        );
+
+        let targets = HashSet::from(["/tmp/instance-b".to_string(), default_dir]);
+        assert_eq!(
+            match_pids_by_user_data_dir(&entries, &targets, true),
+            vec![20, 10, 40]
+        );
+        assert_eq!(pick_preferred_pid(vec![40, 10, 10]), Some(10));
+    }
+}
@@ -4,6 +4,7 @@ use std::path::Path;
 
 use crate::error::file_corrupted_error;
 use crate::models::InstanceStore;
+use crate::utils::atomic_file;
 
 #[derive(Debug, Clone)]
 pub struct CreateInstanceParams {
@@ -33,8 +34,10 @@ pub fn load_instance_store(path: &Path, file_name: &str) -> Result<InstanceStore
         return Ok(InstanceStore::new());
     }
 
-    serde_json::from_str(&content)
-        .map_err(|e| file_corrupted_error(file_name, &path.to_string_lossy(), &e.to_string()))
+    serde_json::from_str(&content).or_else(|e| {
+        atomic_file::recover_json(path)
+            .ok_or_else(|| file_corrupted_error(file_name, &path.to_string_lossy(), &e.to_string()))
+    })
 }
 
 pub fn save_instance_store(
@@ -42,12 +45,10 @@ pub fn save_instance_store(
     file_name: &str,
     store: &InstanceStore,
 ) -> Result<(), String> {
-    let data_dir = path.parent().ok_or("无法获取实例配置目录")?;
-    let temp_path = data_dir.join(format!("{}.tmp", file_name));
     let content =
         serde_json::to_string_pretty(store).map_err(|e| format!("序列化实例配置失败: {}", e))?;
-    fs::write(&temp_path, content).map_err(|e| format!("写入实例配置失败: {}", e))?;
-    fs::rename(temp_path, path).map_err(|e| format!("保存实例配置失败: {}", e))?;
+    atomic_file::write(path, content)
+        .map_err(|e| format!("保存实例配置失败: file={}, error={}", file_name, e))?;
     Ok(())
 }
 
@@ -1,4 +1,6 @@
+use futures::future::BoxFuture;
 use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs;
@@ -7,17 +9,56 @@ use std::sync::Mutex;
 use std::time::Instant;
 
 use crate::models::kiro::{KiroAccount, KiroAccountIndex, KiroOAuthCompletePayload};
-use crate::modules::{account, kiro_oauth, logger};
+use crate::modules::account_store::{self, StoreSpec};
+use crate::modules::platform::{self, Platform, PlatformAccount, QuotaMetric, UnifiedQuota};
+use crate::modules::quota_cache::{self, QuotaApiError, Refreshed};
+use crate::modules::unified_quota::QuotaWindow;
+use crate::modules::refresh_executor::{self, RefreshReport, RefreshRun};
+use crate::modules::schema_migration::{self, SchemaSpec, VersionEncoding};
+use crate::modules::{
+    account, credential_vault, kiro_instance, kiro_oauth, logger, quota_events, quota_history,
+    store_fsck,
+};
+use crate::utils::atomic_file;
 
 const ACCOUNTS_INDEX_FILE: &str = "kiro_accounts.json";
 const ACCOUNTS_DIR: &str = "kiro_accounts";
+
+/// kiro_accounts.json 的格式版本
+const INDEX_SCHEMA: SchemaSpec = SchemaSpec {
+    name: ACCOUNTS_INDEX_FILE,
+    version_field: "version",
+    encoding: VersionEncoding::Dotted,
+    current: 1,
+    baseline: 1,
+    migrations: &[],
+};
+
 const LOCAL_AUTH_TOKEN_FILE_NAME: &str = "kiro-auth-token.json";
 const LOCAL_USAGE_DB_KEY: &str = "kiro.kiroAgent";
-const KIRO_QUOTA_ALERT_COOLDOWN_SECONDS: i64 = 10 * 60;
+/// 账号文件中需要由凭据保险库加密的字段
+const SECRET_FIELDS: &[&str] = &["/access_token", "/refresh_token", "/kiro_auth_token_raw"];
+
+pub const STORE_SPEC: StoreSpec = StoreSpec {
+    platform: "kiro",
+    base_dir: get_data_dir,
+    index_file: ACCOUNTS_INDEX_FILE,
+    accounts_dir: ACCOUNTS_DIR,
+    secret_fields: SECRET_FIELDS,
+    quota_fields: &[
+        "credits_total",
+        "credits_used",
+        "bonus_total",
+        "bonus_used",
+        "usage_reset_at",
+        "bonus_expire_days",
+        "kiro_usage_raw",
+    ],
+    mirror_index: false,
+};
 
 lazy_static::lazy_static! {
     static ref KIRO_ACCOUNT_INDEX_LOCK: Mutex<()> = Mutex::new(());
-    static ref KIRO_QUOTA_ALERT_LAST_SENT: Mutex<HashMap<String, i64>> = Mutex::new(HashMap::new());
 }
 
 fn now_ts() -> i64 {
@@ -62,7 +103,7 @@ fn get_data_dir() -> Result<PathBuf, String> {
     account::get_data_dir()
 }
 
-fn get_accounts_dir() -> Result<PathBuf, String> {
+pub fn get_accounts_dir() -> Result<PathBuf, String> {
     let base = get_data_dir()?;
     let dir = base.join(ACCOUNTS_DIR);
     if !dir.exists() {
@@ -106,22 +147,38 @@ fn resolve_account_file_path(account_id: &str) -> Result<PathBuf, String> {
 }
 
 pub fn load_account(account_id: &str) -> Option<KiroAccount> {
+    if account_store::is_active() {
+        let account_id = normalize_account_id(account_id).ok()?;
+        return account_store::load_record(&STORE_SPEC, &account_id)
+            .ok()
+            .flatten();
+    }
+
     let account_path = resolve_account_file_path(account_id).ok()?;
     if !account_path.exists() {
         return None;
     }
     let content = fs::read_to_string(account_path).ok()?;
-    serde_json::from_str(&content).ok()
+    credential_vault::from_sealed_json(&content, SECRET_FIELDS).ok()
 }
 
 fn save_account_file(account: &KiroAccount) -> Result<(), String> {
+    if account_store::is_active() {
+        let account_id = normalize_account_id(account.id.as_str())?;
+        return account_store::save_record(&STORE_SPEC, &account_id, account);
+    }
+
     let path = resolve_account_file_path(account.id.as_str())?;
-    let content =
-        serde_json::to_string_pretty(account).map_err(|e| format!("序列化账号失败: {}", e))?;
-    fs::write(path, content).map_err(|e| format!("保存账号失败: {}", e))
+    let content = credential_vault::to_sealed_json(account, SECRET_FIELDS)?;
+    atomic_file::write_secret(path, content).map_err(|e| format!("保存账号失败: {}", e))
 }
 
 fn delete_account_file(account_id: &str) -> Result<(), String> {
+    if account_store::is_active() {
+        let account_id = normalize_account_id(account_id)?;
+        return account_store::delete_record(&STORE_SPEC, &account_id);
+    }
+
     let path = resolve_account_file_path(account_id)?;
     if path.exists() {
         fs::remove_file(path).map_err(|e| format!("删除账号文件失败: {}", e))?;
@@ -130,6 +187,15 @@ fn delete_account_file(account_id: &str) -> Result<(), String> {
 }
 
 fn load_account_index() -> KiroAccountIndex {
+    if account_store::is_active() {
+        return account_store::load_index(&STORE_SPEC)
+            .unwrap_or_else(|e| {
+                logger::log_error(&format!("读取 Kiro 账号索引失败: {}", e));
+                None
+            })
+            .unwrap_or_default();
+    }
+
     let path = match get_accounts_index_path() {
         Ok(p) => p,
         Err(_) => return KiroAccountIndex::new(),
@@ -139,17 +205,25 @@ fn load_account_index() -> KiroAccountIndex {
         return KiroAccountIndex::new();
     }
 
-    match fs::read_to_string(path) {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|_| KiroAccountIndex::new()),
+    match fs::read_to_string(&path) {
+        Ok(content) => {
+            let content = schema_migration::upgrade_on_load(&INDEX_SCHEMA, &path, content);
+            serde_json::from_str(&content).unwrap_or_else(|_| KiroAccountIndex::new())
+        }
         Err(_) => KiroAccountIndex::new(),
     }
 }
 
 fn save_account_index(index: &KiroAccountIndex) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_index(&STORE_SPEC, index);
+    }
+
     let path = get_accounts_index_path()?;
+    schema_migration::ensure_writable(&INDEX_SCHEMA, &path)?;
     let content =
         serde_json::to_string_pretty(index).map_err(|e| format!("序列化账号索引失败: {}", e))?;
-    fs::write(path, content).map_err(|e| format!("写入账号索引失败: {}", e))
+    atomic_file::write(path, content).map_err(|e| format!("写入账号索引失败: {}", e))
 }
 
 fn refresh_summary(index: &mut KiroAccountIndex, account: &KiroAccount) {
@@ -165,9 +239,11 @@ fn upsert_account_record(account: KiroAccount) -> Result<KiroAccount, String> {
         .lock()
         .map_err(|_| "获取 Kiro 账号锁失败".to_string())?;
     let mut index = load_account_index();
-    save_account_file(&account)?;
     refresh_summary(&mut index, &account);
-    save_account_index(&index)?;
+    account_store::write_batch(|| {
+        save_account_file(&account)?;
+        save_account_index(&index)
+    })?;
     Ok(account)
 }
 
@@ -568,13 +644,53 @@ fn normalize_account_index(index: &mut KiroAccountIndex) -> Vec<KiroAccount> {
 
 pub fn list_accounts() -> Vec<KiroAccount> {
     let mut index = load_account_index();
-    let accounts = normalize_account_index(&mut index);
-    if let Err(err) = save_account_index(&index) {
+    let mut accounts = Vec::new();
+    // 去重合并的账号写入与索引在同一事务中保存
+    let saved = account_store::write_batch(|| {
+        accounts = normalize_account_index(&mut index);
+        save_account_index(&index)
+    });
+    if let Err(err) = saved {
         logger::log_warn(&format!("[Kiro Account] 保存账号索引失败: {}", err));
     }
     accounts
 }
 
+/// 以账号文件为准重建索引（一致性修复使用），返回索引中的账号数
+pub fn rebuild_account_index() -> Result<usize, String> {
+    let _lock = KIRO_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Kiro 账号锁失败".to_string())?;
+    let previous = store_fsck::read_index(&STORE_SPEC).unwrap_or_default();
+    let accounts: Vec<KiroAccount> = store_fsck::list_stored_ids(&STORE_SPEC)?
+        .iter()
+        .filter_map(|id| load_account(id))
+        .collect();
+    let accounts =
+        store_fsck::order_by_index(accounts, &previous.account_ids, |a| &a.id, |a| a.created_at);
+
+    let mut index = KiroAccountIndex::new();
+    index.accounts = accounts.iter().map(KiroAccount::summary).collect();
+    save_account_index(&index)?;
+    Ok(index.accounts.len())
+}
+
+/// 按凭据保险库当前状态重新写回所有账号文件
+pub fn reseal_account_files() -> Result<usize, String> {
+    let _lock = KIRO_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Kiro 账号锁失败".to_string())?;
+    let index = load_account_index();
+    let mut count = 0;
+    for summary in &index.accounts {
+        if let Some(account) = load_account(&summary.id) {
+            save_account_file(&account)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
 fn apply_payload(account: &mut KiroAccount, payload: KiroOAuthCompletePayload) {
     let incoming_email = payload.email.trim().to_string();
     if !incoming_email.is_empty() {
@@ -693,9 +809,11 @@ pub fn upsert_account(payload: KiroOAuthCompletePayload) -> Result<KiroAccount,
     account.created_at = created_at;
     account.last_used = now;
 
-    save_account_file(&account)?;
     refresh_summary(&mut index, &account);
-    save_account_index(&index)?;
+    account_store::write_batch(|| {
+        save_account_file(&account)?;
+        save_account_index(&index)
+    })?;
 
     logger::log_info(&format!(
         "Kiro 账号已保存: id={}, email={}",
@@ -704,20 +822,96 @@ pub fn upsert_account(payload: KiroOAuthCompletePayload) -> Result<KiroAccount,
     Ok(account)
 }
 
-pub async fn refresh_account_token(account_id: &str) -> Result<KiroAccount, String> {
+/// 写入配额缓存的字段（不含令牌）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KiroQuotaCache {
+    plan_name: Option<String>,
+    plan_tier: Option<String>,
+    credits_total: Option<f64>,
+    credits_used: Option<f64>,
+    bonus_total: Option<f64>,
+    bonus_used: Option<f64>,
+    usage_reset_at: Option<i64>,
+    bonus_expire_days: Option<i64>,
+    kiro_usage_raw: Option<Value>,
+}
+
+impl KiroQuotaCache {
+    fn of_payload(payload: &KiroOAuthCompletePayload) -> Self {
+        Self {
+            plan_name: payload.plan_name.clone(),
+            plan_tier: payload.plan_tier.clone(),
+            credits_total: payload.credits_total,
+            credits_used: payload.credits_used,
+            bonus_total: payload.bonus_total,
+            bonus_used: payload.bonus_used,
+            usage_reset_at: payload.usage_reset_at,
+            bonus_expire_days: payload.bonus_expire_days,
+            kiro_usage_raw: payload.kiro_usage_raw.clone(),
+        }
+    }
+
+    fn apply(self, account: &mut KiroAccount) {
+        account.plan_name = self.plan_name;
+        account.plan_tier = self.plan_tier;
+        account.credits_total = self.credits_total;
+        account.credits_used = self.credits_used;
+        account.bonus_total = self.bonus_total;
+        account.bonus_used = self.bonus_used;
+        account.usage_reset_at = self.usage_reset_at;
+        account.bonus_expire_days = self.bonus_expire_days;
+        account.kiro_usage_raw = self.kiro_usage_raw;
+    }
+}
+
+/// 刷新登录态与用量；`skip_cache` 为 true 时忽略配额缓存有效期（单个账号的手动刷新）。
+/// 使用缓存时只更新用量字段，沿用原有登录态
+pub async fn refresh_account_token(
+    account_id: &str,
+    skip_cache: bool,
+) -> Result<KiroAccount, String> {
+    credential_vault::ensure_unlocked()?;
     let started_at = Instant::now();
     let mut account = load_account(account_id).ok_or_else(|| "账号不存在".to_string())?;
+    if quota_cache::is_offline() {
+        // 离线模式：不发起请求，沿用上次保存的数据
+        return Ok(account);
+    }
     logger::log_info(&format!(
         "[Kiro Refresh] 开始刷新账号: id={}, email={}",
         account.id, account.email
     ));
 
-    let payload = kiro_oauth::refresh_payload_for_account(&account).await?;
-    let tags = account.tags.clone();
-    let created_at = account.created_at;
-    apply_payload(&mut account, payload);
-    account.tags = tags;
-    account.created_at = created_at;
+    let request = async {
+        kiro_oauth::refresh_payload_for_account(&account)
+            .await
+            .map_err(QuotaApiError::Transient)
+    };
+    let refreshed = quota_cache::refresh(
+        KiroPlatform.id(),
+        &account.id,
+        None,
+        skip_cache,
+        request,
+        |payload| serde_json::to_value(KiroQuotaCache::of_payload(payload)).unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match refreshed {
+        Refreshed::Fetched(payload) => {
+            let tags = account.tags.clone();
+            let created_at = account.created_at;
+            apply_payload(&mut account, payload);
+            account.tags = tags;
+            account.created_at = created_at;
+        }
+        Refreshed::Cached(cached) => {
+            serde_json::from_value::<KiroQuotaCache>(cached.payload)
+                .map_err(|e| format!("解析 Kiro 配额缓存失败: {}", e))?
+                .apply(&mut account);
+        }
+    }
     account.last_used = now_ts();
 
     let updated = account.clone();
@@ -731,19 +925,18 @@ pub async fn refresh_account_token(account_id: &str) -> Result<KiroAccount, Stri
     Ok(updated)
 }
 
-pub async fn refresh_all_tokens() -> Result<Vec<(String, Result<KiroAccount, String>)>, String> {
-    use futures::future::join_all;
-    use std::sync::Arc;
-    use tokio::sync::Semaphore;
+/// 批量刷新时按此主机限速
+const REFRESH_HOST: &str = "q.us-east-1.amazonaws.com";
 
-    const MAX_CONCURRENT: usize = 5;
+pub async fn refresh_all_tokens() -> Result<RefreshRun<KiroAccount>, String> {
     let accounts = list_accounts();
     let total = accounts.len();
-    let active_accounts: Vec<KiroAccount> = accounts
+    let active_ids: Vec<String> = accounts
         .into_iter()
         .filter(|account| !is_banned_account(account))
+        .map(|account| account.id)
         .collect();
-    let skipped_banned = total.saturating_sub(active_accounts.len());
+    let skipped_banned = total.saturating_sub(active_ids.len());
     if skipped_banned > 0 {
         logger::log_info(&format!(
             "[Kiro Refresh] 跳过封禁账号: skipped={}, total={}",
@@ -751,32 +944,17 @@ pub async fn refresh_all_tokens() -> Result<Vec<(String, Result<KiroAccount, Str
         ));
     }
 
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
-    let tasks: Vec<_> = active_accounts
-        .into_iter()
-        .map(|account| {
-            let id = account.id;
-            let semaphore = semaphore.clone();
-            async move {
-                let _permit = semaphore
-                    .acquire_owned()
-                    .await
-                    .map_err(|e| format!("获取 Kiro 刷新并发许可失败: {}", e))?;
-                let result = refresh_account_token(&id).await;
-                Ok::<(String, Result<KiroAccount, String>), String>((id, result))
-            }
-        })
-        .collect();
-
-    let mut results = Vec::with_capacity(tasks.len());
-    for task in join_all(tasks).await {
-        match task {
-            Ok(item) => results.push(item),
-            Err(err) => return Err(err),
-        }
-    }
+    let run = refresh_executor::run(KiroPlatform.id(), REFRESH_HOST, active_ids, |id| async move {
+        refresh_account_token(&id, false).await
+    })
+    .await;
 
-    Ok(results)
+    quota_history::record_refreshed_logged(
+        &KiroPlatform,
+        &quota_history::succeeded_ids(&run.results),
+    );
+    quota_events::publish_logged(&KiroPlatform);
+    Ok(run)
 }
 
 pub fn remove_account(account_id: &str) -> Result<(), String> {
@@ -785,9 +963,10 @@ pub fn remove_account(account_id: &str) -> Result<(), String> {
         .map_err(|_| "获取 Kiro 账号锁失败".to_string())?;
     let mut index = load_account_index();
     index.accounts.retain(|item| item.id != account_id);
-    save_account_index(&index)?;
-    delete_account_file(account_id)?;
-    Ok(())
+    account_store::write_batch(|| {
+        save_account_index(&index)?;
+        delete_account_file(account_id)
+    })
 }
 
 pub fn remove_accounts(account_ids: &[String]) -> Result<(), String> {
@@ -1016,6 +1195,7 @@ pub fn import_from_json(json_content: &str) -> Result<Vec<KiroAccount>, String>
 }
 
 pub fn export_accounts(account_ids: &[String]) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
     let accounts: Vec<KiroAccount> = account_ids
         .iter()
         .filter_map(|id| load_account(id))
@@ -1023,50 +1203,36 @@ pub fn export_accounts(account_ids: &[String]) -> Result<String, String> {
     serde_json::to_string_pretty(&accounts).map_err(|e| format!("序列化失败: {}", e))
 }
 
-fn normalize_quota_alert_threshold(raw: i32) -> i32 {
-    raw.clamp(0, 100)
-}
-
-fn clamp_percent(value: f64) -> i32 {
-    value.round().clamp(0.0, 100.0) as i32
-}
-
-fn calc_remaining_percent(total: Option<f64>, used: Option<f64>) -> Option<i32> {
-    let total = total?;
-    if !total.is_finite() || total <= 0.0 {
-        return None;
-    }
-
-    let used = used.unwrap_or(0.0);
-    if !used.is_finite() {
-        return None;
-    }
-    let remaining = (total - used).max(0.0);
-    Some(clamp_percent((remaining / total) * 100.0))
-}
-
-fn extract_quota_metrics(account: &KiroAccount) -> Vec<(String, i32)> {
-    let mut metrics = Vec::new();
-
-    if let Some(pct) = calc_remaining_percent(account.credits_total, account.credits_used) {
-        metrics.push(("Prompt Credits".to_string(), pct));
-    }
-    if let Some(pct) = calc_remaining_percent(account.bonus_total, account.bonus_used) {
-        metrics.push(("Add-on Credits".to_string(), pct));
-    }
-
-    metrics
-}
-
-fn average_quota_percentage(metrics: &[(String, i32)]) -> f64 {
-    if metrics.is_empty() {
-        return 0.0;
-    }
-    let sum: i32 = metrics.iter().map(|(_, pct)| *pct).sum();
-    sum as f64 / metrics.len() as f64
+/// 统一配额视图：主额度（credits）与附加额度（bonus），均按账单周期重置
+pub fn unified_quota(account: &KiroAccount) -> UnifiedQuota {
+    let metrics = [
+        QuotaMetric::from_usage(
+            "credits",
+            "Prompt Credits",
+            account.credits_used,
+            account.credits_total,
+        ),
+        QuotaMetric::from_usage(
+            "bonus",
+            "Add-on Credits",
+            account.bonus_used,
+            account.bonus_total,
+        ),
+    ];
+    UnifiedQuota::new(
+        metrics
+            .into_iter()
+            .flatten()
+            .map(|metric| {
+                metric
+                    .with_window(Some(QuotaWindow::BillingCycle))
+                    .with_resets_at(account.usage_reset_at)
+            })
+            .collect(),
+    )
 }
 
-fn resolve_current_account_id(accounts: &[KiroAccount]) -> Option<String> {
+fn resolve_current_account_id(accounts: &[PlatformAccount]) -> Option<String> {
     if let Ok(settings) = crate::modules::kiro_instance::load_default_settings() {
         if let Some(bind_id) = settings.bind_account_id {
             let trimmed = bind_id.trim();
@@ -1076,10 +1242,7 @@ fn resolve_current_account_id(accounts: &[KiroAccount]) -> Option<String> {
         }
     }
 
-    accounts
-        .iter()
-        .max_by_key(|account| account.last_used)
-        .map(|account| account.id.clone())
+    platform::most_recently_used(accounts)
 }
 
 fn display_email(account: &KiroAccount) -> String {
@@ -1091,114 +1254,144 @@ fn display_email(account: &KiroAccount) -> String {
     }
 }
 
-fn build_quota_alert_cooldown_key(account_id: &str, threshold: i32) -> String {
-    format!("kiro:{}:{}", account_id, threshold)
+pub fn run_quota_alert_if_needed(
+) -> Result<Vec<crate::modules::account::QuotaAlertPayload>, String> {
+    crate::modules::alert_rules::run(&KiroPlatform)
 }
 
-fn should_emit_quota_alert(cooldown_key: &str, now: i64) -> bool {
-    let Ok(mut state) = KIRO_QUOTA_ALERT_LAST_SENT.lock() else {
-        return true;
+fn to_platform_account(account: &KiroAccount) -> PlatformAccount {
+    // 封禁账号不参与预警与推荐
+    let banned = is_banned_account(account);
+    let quota = if banned {
+        UnifiedQuota::default()
+    } else {
+        unified_quota(account)
     };
+    PlatformAccount {
+        platform: KiroPlatform.id().to_string(),
+        id: account.id.clone(),
+        email: display_email(account),
+        tags: account.tags.clone().unwrap_or_default(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+        disabled: banned,
+        forbidden: false,
+        quota_error: None,
+        token_expires_at: account.expires_at,
+        quota_stale: false,
+        quota,
+    }
+}
+
+/// 切换账号：更新默认实例绑定账号并重启 Kiro 默认实例（启动失败不阻断切号），返回切换结果说明
+pub fn switch_account_internal(account_id: &str) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
+    let started_at = Instant::now();
+    logger::log_info(&format!(
+        "[Kiro Switch] 开始切换账号: account_id={}",
+        account_id
+    ));
 
-    if let Some(last_sent) = state.get(cooldown_key) {
-        if now - *last_sent < KIRO_QUOTA_ALERT_COOLDOWN_SECONDS {
-            return false;
-        }
+    let account = load_account(account_id)
+        .ok_or_else(|| format!("Kiro account not found: {}", account_id))?;
+
+    if let Err(err) = kiro_instance::update_default_settings(
+        Some(Some(account_id.to_string())),
+        None,
+        Some(false),
+    ) {
+        logger::log_warn(&format!("更新 Kiro 默认实例绑定账号失败: {}", err));
     }
 
-    state.insert(cooldown_key.to_string(), now);
-    true
-}
+    let launch_warning = match kiro_instance::start_default_instance() {
+        Ok(_) => None,
+        Err(err) if err.starts_with("APP_PATH_NOT_FOUND:") || err.contains("启动 Kiro 失败") => {
+            logger::log_warn(&format!("Kiro 默认实例启动失败: {}", err));
+            Some(err)
+        }
+        Err(err) => return Err(err),
+    };
+
+    platform::notify_account_switched("kiro", launch_warning.as_deref());
 
-fn clear_quota_alert_cooldown(account_id: &str, threshold: i32) {
-    if let Ok(mut state) = KIRO_QUOTA_ALERT_LAST_SENT.lock() {
-        state.remove(&build_quota_alert_cooldown_key(account_id, threshold));
+    if let Some(err) = launch_warning {
+        logger::log_warn(&format!(
+            "[Kiro Switch] 切号完成但启动失败: account_id={}, email={}, elapsed={}ms, error={}",
+            account.id,
+            account.email,
+            started_at.elapsed().as_millis(),
+            err
+        ));
+        Ok(format!("切换完成，但 Kiro 启动失败: {}", err))
+    } else {
+        logger::log_info(&format!(
+            "[Kiro Switch] 切号成功: account_id={}, email={}, elapsed={}ms",
+            account.id,
+            account.email,
+            started_at.elapsed().as_millis()
+        ));
+        Ok(format!("切换完成: {}", account.email))
     }
 }
 
-fn pick_quota_alert_recommendation(
-    accounts: &[KiroAccount],
-    current_id: &str,
-) -> Option<KiroAccount> {
-    let mut candidates: Vec<KiroAccount> = accounts
-        .iter()
-        .filter(|account| account.id != current_id)
-        .filter(|account| !is_banned_account(account))
-        .filter(|account| !extract_quota_metrics(account).is_empty())
-        .cloned()
-        .collect();
+pub struct KiroPlatform;
 
-    if candidates.is_empty() {
-        return None;
+impl Platform for KiroPlatform {
+    fn id(&self) -> &'static str {
+        "kiro"
     }
 
-    candidates.sort_by(|a, b| {
-        let avg_a = average_quota_percentage(&extract_quota_metrics(a));
-        let avg_b = average_quota_percentage(&extract_quota_metrics(b));
-        avg_b
-            .partial_cmp(&avg_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then_with(|| a.last_used.cmp(&b.last_used))
-    });
+    fn display_name(&self) -> &'static str {
+        "Kiro"
+    }
 
-    candidates.into_iter().next()
-}
+    fn list_accounts(&self) -> Result<Vec<PlatformAccount>, String> {
+        Ok(list_accounts().iter().map(to_platform_account).collect())
+    }
 
-pub fn run_quota_alert_if_needed(
-) -> Result<Option<crate::modules::account::QuotaAlertPayload>, String> {
-    let cfg = crate::modules::config::get_user_config();
-    if !cfg.kiro_quota_alert_enabled {
-        return Ok(None);
+    fn current_account_id(&self, accounts: &[PlatformAccount]) -> Option<String> {
+        resolve_current_account_id(accounts)
     }
 
-    let threshold = normalize_quota_alert_threshold(cfg.kiro_quota_alert_threshold);
-    let accounts = list_accounts();
-    let current_id = match resolve_current_account_id(&accounts) {
-        Some(id) => id,
-        None => return Ok(None),
-    };
+    fn remove_accounts(&self, account_ids: &[String]) -> Result<(), String> {
+        remove_accounts(account_ids)
+    }
 
-    let current = match accounts.iter().find(|account| account.id == current_id) {
-        Some(account) => account,
-        None => return Ok(None),
-    };
-    if is_banned_account(current) {
-        return Ok(None);
+    fn update_account_tags(&self, account_id: &str, tags: Vec<String>) -> Result<(), String> {
+        update_account_tags(account_id, tags).map(|_| ())
     }
 
-    let metrics = extract_quota_metrics(current);
-    let low_models: Vec<(String, i32)> = metrics
-        .into_iter()
-        .filter(|(_, pct)| *pct <= threshold)
-        .collect();
+    fn export_accounts(&self, account_ids: &[String]) -> Result<String, String> {
+        export_accounts(account_ids)
+    }
 
-    if low_models.is_empty() {
-        clear_quota_alert_cooldown(&current_id, threshold);
-        return Ok(None);
+    fn import_from_json(&self, json_content: String) -> BoxFuture<'static, Result<usize, String>> {
+        Box::pin(async move { import_from_json(&json_content).map(|accounts| accounts.len()) })
     }
 
-    let now = chrono::Utc::now().timestamp();
-    let cooldown_key = build_quota_alert_cooldown_key(&current_id, threshold);
-    if !should_emit_quota_alert(&cooldown_key, now) {
-        return Ok(None);
+    fn switch_account(&self, account_id: String) -> BoxFuture<'static, Result<String, String>> {
+        Box::pin(async move { switch_account_internal(&account_id) })
     }
 
-    let recommendation = pick_quota_alert_recommendation(&accounts, &current_id);
-    let lowest_percentage = low_models.iter().map(|(_, pct)| *pct).min().unwrap_or(0);
-    let payload = crate::modules::account::QuotaAlertPayload {
-        platform: "kiro".to_string(),
-        current_account_id: current_id,
-        current_email: display_email(current),
-        threshold,
-        lowest_percentage,
-        low_models: low_models.into_iter().map(|(name, _)| name).collect(),
-        recommended_account_id: recommendation.as_ref().map(|account| account.id.clone()),
-        recommended_email: recommendation.as_ref().map(display_email),
-        triggered_at: now,
-    };
+    fn refresh_account(&self, account_id: String) -> BoxFuture<'static, Result<(), String>> {
+        Box::pin(async move { refresh_account_token(&account_id, true).await.map(|_| ()) })
+    }
+
+    fn refresh_all(&self) -> BoxFuture<'static, Result<RefreshReport, String>> {
+        Box::pin(async move { Ok(refresh_all_tokens().await?.report) })
+    }
 
-    crate::modules::account::dispatch_quota_alert(&payload);
-    Ok(Some(payload))
+    fn refresh_interval_minutes(&self) -> i32 {
+        crate::modules::config::get_user_config().kiro_auto_refresh_minutes
+    }
+
+    fn quota_alert_settings(&self) -> platform::QuotaAlertSettings {
+        let cfg = crate::modules::config::get_user_config();
+        platform::QuotaAlertSettings {
+            enabled: cfg.kiro_quota_alert_enabled,
+            threshold: cfg.kiro_quota_alert_threshold,
+        }
+    }
 }
 
 pub fn get_default_kiro_data_dir() -> Result<PathBuf, String> {
@@ -1,49 +1,68 @@
-use std::collections::{HashMap, HashSet};
-use std::ffi::OsString;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::Mutex;
 
-use chrono::Utc;
 use rusqlite::{Connection, OptionalExtension};
 use serde_json::{json, Value};
 use sysinfo::{ProcessRefreshKind, System, UpdateKind};
-use uuid::Uuid;
 
 use crate::models::kiro::KiroAccount;
 use crate::models::{DefaultInstanceSettings, InstanceProfile, InstanceStore};
 use crate::modules;
 use crate::modules::instance::InstanceDefaults;
-use crate::modules::instance_store;
+use crate::modules::instance_manager::{
+    self, extract_user_data_dir, is_helper_process, normalize_path_for_compare, InstanceBackend,
+    InstanceManager,
+};
 use crate::modules::kiro_account;
 
 pub use crate::modules::instance_store::{CreateInstanceParams, UpdateInstanceParams};
 
-static KIRO_INSTANCE_STORE_LOCK: std::sync::LazyLock<Mutex<()>> =
-    std::sync::LazyLock::new(|| Mutex::new(()));
+static KIRO_INSTANCE_STORE_LOCK: Mutex<()> = Mutex::new(());
 
 const KIRO_INSTANCES_FILE: &str = "kiro_instances.json";
 const KIRO_USAGE_DB_KEY: &str = "kiro.kiroAgent";
 
-fn instances_path() -> Result<PathBuf, String> {
-    let data_dir = modules::account::get_data_dir()?;
-    Ok(data_dir.join(KIRO_INSTANCES_FILE))
+pub struct KiroInstances;
+
+impl InstanceBackend for KiroInstances {
+    fn display_name(&self) -> &'static str {
+        "Kiro"
+    }
+
+    fn store_file(&self) -> &'static str {
+        KIRO_INSTANCES_FILE
+    }
+
+    fn store_lock(&self) -> &'static Mutex<()> {
+        &KIRO_INSTANCE_STORE_LOCK
+    }
+
+    fn default_user_data_dir(&self) -> Result<PathBuf, String> {
+        get_default_kiro_user_data_dir()
+    }
+
+    fn instances_root_dir(&self) -> Result<PathBuf, String> {
+        get_default_instances_root_dir()
+    }
+
+    fn supports_follow_local_account(&self) -> bool {
+        false
+    }
 }
 
 pub fn load_instance_store() -> Result<InstanceStore, String> {
-    let path = instances_path()?;
-    instance_store::load_instance_store(&path, KIRO_INSTANCES_FILE)
+    KiroInstances.load_instance_store()
 }
 
 pub fn save_instance_store(store: &InstanceStore) -> Result<(), String> {
-    let path = instances_path()?;
-    instance_store::save_instance_store(&path, KIRO_INSTANCES_FILE, store)
+    KiroInstances.save_instance_store(store)
 }
 
 pub fn load_default_settings() -> Result<DefaultInstanceSettings, String> {
-    let store = load_instance_store()?;
-    Ok(store.default_settings)
+    KiroInstances.load_default_settings()
 }
 
 pub fn update_default_settings(
@@ -51,29 +70,7 @@ pub fn update_default_settings(
     extra_args: Option<String>,
     follow_local_account: Option<bool>,
 ) -> Result<DefaultInstanceSettings, String> {
-    let _lock = KIRO_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let settings = &mut store.default_settings;
-
-    // Kiro 实例不支持“跟随当前账号”，直接忽略 follow_local_account。
-    if follow_local_account == Some(true) {
-        settings.follow_local_account = false;
-    }
-
-    if let Some(bind) = bind_account_id {
-        settings.bind_account_id = bind;
-        settings.follow_local_account = false;
-    }
-
-    if let Some(args) = extra_args {
-        settings.extra_args = args.trim().to_string();
-    }
-
-    let updated = settings.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    KiroInstances.update_default_settings(bind_account_id, extra_args, follow_local_account)
 }
 
 pub fn get_default_kiro_user_data_dir() -> Result<PathBuf, String> {
@@ -105,474 +102,35 @@ pub fn get_default_instances_root_dir() -> Result<PathBuf, String> {
 }
 
 pub fn get_instance_defaults() -> Result<InstanceDefaults, String> {
-    let root_dir = get_default_instances_root_dir()?;
-    let default_user_data_dir = get_default_kiro_user_data_dir()?;
-    Ok(InstanceDefaults {
-        root_dir: root_dir.to_string_lossy().to_string(),
-        default_user_data_dir: default_user_data_dir.to_string_lossy().to_string(),
-    })
+    KiroInstances.get_instance_defaults()
 }
 
 pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile, String> {
-    let _lock = KIRO_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-
-    let name = instance_store::normalize_name(&params.name)?;
-    let user_data_dir = params.user_data_dir.trim().to_string();
-    if user_data_dir.is_empty() {
-        return Err("实例目录不能为空".to_string());
-    }
-
-    instance_store::ensure_unique(&store, &name, &user_data_dir, None)?;
-
-    let user_dir_path = PathBuf::from(&user_data_dir);
-    let init_mode = params
-        .init_mode
-        .as_deref()
-        .unwrap_or("copy")
-        .to_ascii_lowercase();
-    let create_empty = init_mode == "empty";
-
-    if create_empty {
-        if user_dir_path.exists() {
-            let mut has_entries = false;
-            if let Ok(mut iter) = fs::read_dir(&user_dir_path) {
-                if iter.next().is_some() {
-                    has_entries = true;
-                }
-            }
-            if has_entries {
-                let resolved_path = instance_store::display_path(&user_dir_path);
-                return Err(format!("空白实例需要目标目录为空: {}", resolved_path));
-            }
-        }
-        fs::create_dir_all(&user_dir_path).map_err(|e| format!("创建实例目录失败: {}", e))?;
-    } else {
-        let source_dir = match params.copy_source_instance_id.as_deref() {
-            Some("__default__") | None => get_default_kiro_user_data_dir()?,
-            Some(source_id) => {
-                let source_instance = store
-                    .instances
-                    .iter()
-                    .find(|item| item.id == source_id)
-                    .ok_or("复制来源实例不存在")?;
-                PathBuf::from(&source_instance.user_data_dir)
-            }
-        };
-
-        if user_dir_path.exists() {
-            let mut has_entries = false;
-            if let Ok(mut iter) = fs::read_dir(&user_dir_path) {
-                if iter.next().is_some() {
-                    has_entries = true;
-                }
-            }
-            if has_entries {
-                let resolved_path = instance_store::display_path(&user_dir_path);
-                return Err(format!("复制来源实例需要目标目录为空: {}", resolved_path));
-            }
-        }
-
-        if !source_dir.exists() {
-            return Err("未找到复制来源目录，请先确保来源实例已初始化".to_string());
-        }
-
-        instance_store::copy_dir_recursive(&source_dir, &user_dir_path)?;
-    }
-
-    let instance = InstanceProfile {
-        id: Uuid::new_v4().to_string(),
-        name,
-        user_data_dir,
-        extra_args: params.extra_args.trim().to_string(),
-        bind_account_id: if create_empty {
-            None
-        } else {
-            params.bind_account_id
-        },
-        created_at: Utc::now().timestamp_millis(),
-        last_launched_at: None,
-        last_pid: None,
-    };
-
-    store.instances.push(instance.clone());
-    save_instance_store(&store)?;
-    Ok(instance)
+    KiroInstances.create_instance(params)
 }
 
 pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile, String> {
-    let _lock = KIRO_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let index = store
-        .instances
-        .iter()
-        .position(|instance| instance.id == params.instance_id)
-        .ok_or("实例不存在")?;
-
-    let current_id = store.instances[index].id.clone();
-    let current_dir = store.instances[index].user_data_dir.clone();
-    let next_name = params
-        .name
-        .as_ref()
-        .map(|name| instance_store::normalize_name(name))
-        .transpose()?;
-
-    if let Some(ref normalized) = next_name {
-        instance_store::ensure_unique(&store, normalized, &current_dir, Some(&current_id))?;
-    }
-
-    let instance = &mut store.instances[index];
-    if let Some(normalized) = next_name {
-        instance.name = normalized;
-    }
-    if let Some(ref extra_args) = params.extra_args {
-        instance.extra_args = extra_args.trim().to_string();
-    }
-    if let Some(bind) = params.bind_account_id.clone() {
-        instance.bind_account_id = bind;
-    }
-
-    let updated = instance.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    KiroInstances.update_instance(params)
 }
 
 pub fn delete_instance(instance_id: &str) -> Result<(), String> {
-    let _lock = KIRO_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let index = store
-        .instances
-        .iter()
-        .position(|instance| instance.id == instance_id)
-        .ok_or("实例不存在")?;
-    let user_data_dir = store.instances[index].user_data_dir.clone();
-
-    if !user_data_dir.trim().is_empty() {
-        let dir_path = PathBuf::from(&user_data_dir);
-        modules::instance::delete_instance_directory(&dir_path)?;
-    }
-
-    store.instances.remove(index);
-    save_instance_store(&store)?;
-    Ok(())
+    KiroInstances.delete_instance(instance_id)
 }
 
 pub fn update_instance_after_start(instance_id: &str, pid: u32) -> Result<InstanceProfile, String> {
-    let _lock = KIRO_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_launched_at = Some(Utc::now().timestamp_millis());
-            instance.last_pid = Some(pid);
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    KiroInstances.update_instance_after_start(instance_id, pid)
 }
 
 pub fn update_instance_pid(instance_id: &str, pid: Option<u32>) -> Result<InstanceProfile, String> {
-    let _lock = KIRO_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    let mut updated = None;
-    for instance in &mut store.instances {
-        if instance.id == instance_id {
-            instance.last_pid = pid;
-            updated = Some(instance.clone());
-            break;
-        }
-    }
-    let updated = updated.ok_or("实例不存在")?;
-    save_instance_store(&store)?;
-    Ok(updated)
+    KiroInstances.update_instance_pid(instance_id, pid)
 }
 
 pub fn update_default_pid(pid: Option<u32>) -> Result<DefaultInstanceSettings, String> {
-    let _lock = KIRO_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    store.default_settings.last_pid = pid;
-    let updated = store.default_settings.clone();
-    save_instance_store(&store)?;
-    Ok(updated)
+    KiroInstances.update_default_pid(pid)
 }
 
 pub fn clear_all_pids() -> Result<(), String> {
-    let _lock = KIRO_INSTANCE_STORE_LOCK
-        .lock()
-        .map_err(|_| "无法获取实例锁")?;
-    let mut store = load_instance_store()?;
-    store.default_settings.last_pid = None;
-    for instance in &mut store.instances {
-        instance.last_pid = None;
-    }
-    save_instance_store(&store)?;
-    Ok(())
-}
-
-fn normalize_path_for_compare(raw: &str) -> String {
-    let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        return String::new();
-    }
-    let resolved = fs::canonicalize(trimmed)
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|_| trimmed.to_string());
-
-    #[cfg(target_os = "windows")]
-    {
-        return resolved.to_lowercase();
-    }
-    #[cfg(not(target_os = "windows"))]
-    {
-        resolved
-    }
-}
-
-fn normalize_non_empty_path(value: Option<&str>) -> Option<String> {
-    value
-        .map(normalize_path_for_compare)
-        .filter(|text| !text.is_empty())
-}
-
-fn parse_user_data_dir_value(raw: &str) -> Option<String> {
-    let rest = raw.trim_start();
-    if rest.is_empty() {
-        return None;
-    }
-    let value = if rest.starts_with('"') {
-        let end = rest[1..].find('"').map(|idx| idx + 1).unwrap_or(rest.len());
-        &rest[1..end]
-    } else if rest.starts_with('\'') {
-        let end = rest[1..]
-            .find('\'')
-            .map(|idx| idx + 1)
-            .unwrap_or(rest.len());
-        &rest[1..end]
-    } else {
-        let end = rest.find(" --").unwrap_or(rest.len());
-        &rest[..end]
-    };
-    let value = value.trim();
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.to_string())
-    }
-}
-
-fn extract_user_data_dir(args: &[OsString]) -> Option<String> {
-    let tokens: Vec<String> = args
-        .iter()
-        .map(|arg| arg.to_string_lossy().to_string())
-        .collect();
-    let mut index = 0;
-    while index < tokens.len() {
-        let token = tokens[index].as_str();
-        if let Some(rest) = token.strip_prefix("--user-data-dir=") {
-            return parse_user_data_dir_value(rest);
-        }
-        if token == "--user-data-dir" {
-            index += 1;
-            if index >= tokens.len() {
-                return None;
-            }
-            let mut parts = Vec::new();
-            while index < tokens.len() {
-                let part = tokens[index].as_str();
-                if part.starts_with("--") {
-                    break;
-                }
-                parts.push(part);
-                index += 1;
-            }
-            if parts.is_empty() {
-                return None;
-            }
-            return Some(parts.join(" "));
-        }
-        index += 1;
-    }
-    None
-}
-
-fn split_command_tokens(command_line: &str) -> Vec<String> {
-    let mut tokens = Vec::new();
-    let mut current = String::new();
-    let mut quote: Option<char> = None;
-
-    for ch in command_line.chars() {
-        match quote {
-            Some(q) => {
-                if ch == q {
-                    quote = None;
-                } else {
-                    current.push(ch);
-                }
-            }
-            None => {
-                if ch == '"' || ch == '\'' {
-                    quote = Some(ch);
-                } else if ch.is_whitespace() {
-                    if !current.is_empty() {
-                        tokens.push(current.clone());
-                        current.clear();
-                    }
-                } else {
-                    current.push(ch);
-                }
-            }
-        }
-    }
-
-    if !current.is_empty() {
-        tokens.push(current);
-    }
-
-    tokens
-}
-
-fn extract_user_data_dir_from_command_line(command_line: &str) -> Option<String> {
-    let tokens = split_command_tokens(command_line);
-    let mut index = 0;
-    while index < tokens.len() {
-        let token = tokens[index].as_str();
-        if let Some(rest) = token.strip_prefix("--user-data-dir=") {
-            if !rest.trim().is_empty() {
-                return Some(rest.to_string());
-            }
-        }
-        if token == "--user-data-dir" {
-            index += 1;
-            if index >= tokens.len() {
-                return None;
-            }
-            let mut parts = Vec::new();
-            while index < tokens.len() {
-                let part = tokens[index].as_str();
-                if part.starts_with("--") {
-                    break;
-                }
-                parts.push(part);
-                index += 1;
-            }
-            if !parts.is_empty() {
-                return Some(parts.join(" "));
-            }
-            return None;
-        }
-        index += 1;
-    }
-    None
-}
-
-fn is_helper_process(name: &str, args_line: &str) -> bool {
-    args_line.contains("--type=")
-        || name.contains("helper")
-        || name.contains("renderer")
-        || name.contains("gpu")
-        || name.contains("utility")
-        || name.contains("crashpad")
-        || name.contains("sandbox")
-}
-
-fn command_trace_enabled() -> bool {
-    if let Ok(value) = std::env::var("COCKPIT_COMMAND_TRACE") {
-        match value.trim().to_ascii_lowercase().as_str() {
-            "1" | "true" | "yes" | "on" => return true,
-            "0" | "false" | "no" | "off" => return false,
-            _ => {}
-        }
-    }
-    false
-}
-
-fn quote_command_part(part: &str) -> String {
-    if part.is_empty() {
-        return "\"\"".to_string();
-    }
-    let needs_quote = part
-        .chars()
-        .any(|ch| ch.is_whitespace() || matches!(ch, '"' | '\'' | '$' | '`' | '|' | '&' | ';'));
-    if needs_quote {
-        format!("{:?}", part)
-    } else {
-        part.to_string()
-    }
-}
-
-fn format_command_preview(command: &Command) -> String {
-    let program = quote_command_part(command.get_program().to_string_lossy().as_ref());
-    let args = command
-        .get_args()
-        .map(|arg| quote_command_part(arg.to_string_lossy().as_ref()))
-        .collect::<Vec<String>>();
-    if args.is_empty() {
-        program
-    } else {
-        format!("{} {}", program, args.join(" "))
-    }
-}
-
-fn spawn_command_with_trace(cmd: &mut Command) -> std::io::Result<std::process::Child> {
-    let preview = format_command_preview(cmd);
-    if command_trace_enabled() {
-        modules::logger::log_info(&format!("[CmdTrace][Kiro] EXEC {}", preview));
-    }
-    let start = std::time::Instant::now();
-    let result = cmd.spawn();
-    if command_trace_enabled() {
-        match &result {
-            Ok(child) => modules::logger::log_info(&format!(
-                "[CmdTrace][Kiro] SPAWN elapsed={}ms pid={} cmd={}",
-                start.elapsed().as_millis(),
-                child.id(),
-                preview
-            )),
-            Err(err) => modules::logger::log_warn(&format!(
-                "[CmdTrace][Kiro] SPAWN_ERROR elapsed={}ms cmd={} err={}",
-                start.elapsed().as_millis(),
-                preview,
-                err
-            )),
-        }
-    }
-    result
-}
-
-fn collect_running_process_exe_by_pid() -> HashMap<u32, String> {
-    let mut map = HashMap::new();
-    let mut system = System::new();
-    system.refresh_processes_specifics(
-        sysinfo::ProcessesToUpdate::All,
-        true,
-        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
-    );
-    for (pid, process) in system.processes() {
-        let Some(exe) = process.exe().and_then(|value| value.to_str()) else {
-            continue;
-        };
-        let normalized = normalize_path_for_compare(exe);
-        if normalized.is_empty() {
-            continue;
-        }
-        map.insert(pid.as_u32(), normalized);
-    }
-    map
+    KiroInstances.clear_all_pids()
 }
 
 fn resolve_expected_kiro_launch_path_for_match() -> Option<String> {
@@ -594,36 +152,6 @@ fn resolve_expected_kiro_launch_path_for_match() -> Option<String> {
     Some(normalized)
 }
 
-fn filter_kiro_entries_by_launch_path(
-    entries: Vec<(u32, Option<String>)>,
-    expected: Option<String>,
-) -> Vec<(u32, Option<String>)> {
-    if entries.is_empty() {
-        return entries;
-    }
-    let Some(expected) = expected else {
-        return Vec::new();
-    };
-    let exe_by_pid = collect_running_process_exe_by_pid();
-    let mut result = Vec::new();
-    let mut missing_exe = 0usize;
-    let mut path_mismatch = 0usize;
-    for (pid, dir) in entries {
-        match exe_by_pid.get(&pid) {
-            Some(actual) if actual == &expected => result.push((pid, dir)),
-            Some(_) => path_mismatch += 1,
-            None => missing_exe += 1,
-        }
-    }
-    if result.is_empty() {
-        modules::logger::log_warn(&format!(
-            "[Kiro Resolve] 启动路径硬匹配未命中：expected={}, path_mismatch={}, missing_exe={}",
-            expected, path_mismatch, missing_exe
-        ));
-    }
-    result
-}
-
 pub fn collect_kiro_process_entries() -> Vec<(u32, Option<String>)> {
     let expected_launch = resolve_expected_kiro_launch_path_for_match();
     if expected_launch.is_none() {
@@ -698,14 +226,15 @@ pub fn collect_kiro_process_entries() -> Vec<(u32, Option<String>)> {
                 if !lower.contains("kiro.app/contents/") || lower.contains("--type=") {
                     continue;
                 }
-                let dir = extract_user_data_dir_from_command_line(cmdline).and_then(|value| {
-                    let normalized = normalize_path_for_compare(&value);
-                    if normalized.is_empty() {
-                        None
-                    } else {
-                        Some(normalized)
-                    }
-                });
+                let dir = instance_manager::extract_user_data_dir_from_command_line(cmdline)
+                    .and_then(|value| {
+                        let normalized = normalize_path_for_compare(&value);
+                        if normalized.is_empty() {
+                            None
+                        } else {
+                            Some(normalized)
+                        }
+                    });
                 entries.entry(pid).or_insert(dir);
             }
         }
@@ -713,16 +242,7 @@ pub fn collect_kiro_process_entries() -> Vec<(u32, Option<String>)> {
 
     let mut result: Vec<(u32, Option<String>)> = entries.into_iter().collect();
     result.sort_by_key(|(pid, _)| *pid);
-    filter_kiro_entries_by_launch_path(result, expected_launch)
-}
-
-fn pick_preferred_pid(mut pids: Vec<u32>) -> Option<u32> {
-    if pids.is_empty() {
-        return None;
-    }
-    pids.sort();
-    pids.dedup();
-    pids.first().copied()
+    instance_manager::filter_entries_by_launch_path(result, expected_launch, "Kiro")
 }
 
 pub fn resolve_kiro_pid_from_entries(
@@ -730,45 +250,7 @@ pub fn resolve_kiro_pid_from_entries(
     user_data_dir: Option<&str>,
     entries: &[(u32, Option<String>)],
 ) -> Option<u32> {
-    let default_dir = get_default_kiro_user_data_dir()
-        .ok()
-        .map(|dir| normalize_path_for_compare(&dir.to_string_lossy()));
-    let target = normalize_non_empty_path(user_data_dir).or(default_dir.clone());
-    let allow_none_for_target = default_dir
-        .as_ref()
-        .zip(target.as_ref())
-        .map(|(value, current)| value == current)
-        .unwrap_or(false);
-
-    let target = target?;
-
-    let mut matches = Vec::new();
-    for (pid, dir) in entries {
-        match dir.as_ref() {
-            Some(actual_dir) => {
-                let normalized = normalize_path_for_compare(actual_dir);
-                if !normalized.is_empty() && normalized == target {
-                    matches.push(*pid);
-                }
-            }
-            None if allow_none_for_target => matches.push(*pid),
-            _ => {}
-        }
-    }
-
-    if let Some(pid) = last_pid {
-        if modules::process::is_pid_running(pid) && matches.contains(&pid) {
-            return Some(pid);
-        }
-        if modules::process::is_pid_running(pid) {
-            modules::logger::log_warn(&format!(
-                "[Kiro Resolve] 忽略不匹配的 last_pid={}，target={}，matched_pids={:?}",
-                pid, target, matches
-            ));
-        }
-    }
-
-    pick_preferred_pid(matches)
+    KiroInstances.resolve_pid_from_entries(last_pid, user_data_dir, entries)
 }
 
 pub fn resolve_kiro_pid(last_pid: Option<u32>, user_data_dir: Option<&str>) -> Option<u32> {
@@ -776,71 +258,13 @@ pub fn resolve_kiro_pid(last_pid: Option<u32>, user_data_dir: Option<&str>) -> O
     resolve_kiro_pid_from_entries(last_pid, user_data_dir, &entries)
 }
 
-#[cfg(target_os = "macos")]
-fn focus_window_by_pid(pid: u32) -> Result<(), String> {
-    let script = format!(
-        "tell application \"System Events\" to set frontmost of (first process whose unix id is {}) to true",
-        pid
-    );
-    let output = Command::new("osascript")
-        .args(["-e", &script])
-        .output()
-        .map_err(|e| format!("调用 osascript 失败: {}", e))?;
-    if output.status.success() {
-        return Ok(());
-    }
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    Err(format!("定位 Kiro 窗口失败: {}", stderr.trim()))
-}
-
-#[cfg(target_os = "windows")]
-fn focus_window_by_pid(pid: u32) -> Result<(), String> {
-    use std::os::windows::process::CommandExt;
-
-    let command = format!(
-        r#"$targetPid={pid};$h=[IntPtr]::Zero;for($i=0;$i -lt 20;$i++){{$p=Get-Process -Id $targetPid -ErrorAction Stop;$h=$p.MainWindowHandle;if ($h -ne 0) {{ break }};Start-Sleep -Milliseconds 150}};if ($h -eq 0) {{ throw 'MAIN_WINDOW_HANDLE_EMPTY' }};Add-Type @'
-using System;
-using System.Runtime.InteropServices;
-public class Win32 {{
-  [DllImport("user32.dll")] public static extern bool SetForegroundWindow(IntPtr hWnd);
-  [DllImport("user32.dll")] public static extern bool ShowWindowAsync(IntPtr hWnd, int nCmdShow);
-}}
-'@;[Win32]::ShowWindowAsync($h, 9) | Out-Null;[Win32]::SetForegroundWindow($h) | Out-Null;"#
-    );
-    let output = Command::new("powershell")
-        .creation_flags(0x08000000)
-        .args(["-NoProfile", "-NonInteractive", "-Command", &command])
-        .output()
-        .map_err(|e| format!("调用 PowerShell 失败: {}", e))?;
-    if output.status.success() {
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("定位 Kiro 窗口失败: {}", stderr.trim()))
-    }
-}
-
-#[cfg(target_os = "linux")]
-fn focus_window_by_pid(pid: u32) -> Result<(), String> {
-    let output = Command::new("xdotool")
-        .args(["search", "--pid", &pid.to_string(), "windowactivate"])
-        .output()
-        .map_err(|e| format!("调用 xdotool 失败: {}", e))?;
-    if output.status.success() {
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("定位 Kiro 窗口失败: {}", stderr.trim()))
-    }
-}
-
 pub fn focus_kiro_instance(
     last_pid: Option<u32>,
     user_data_dir: Option<&str>,
 ) -> Result<u32, String> {
     let pid = resolve_kiro_pid(last_pid, user_data_dir)
         .ok_or_else(|| "实例未运行，无法定位窗口".to_string())?;
-    focus_window_by_pid(pid)?;
+    modules::process::focus_window_by_pid(pid)?;
     Ok(pid)
 }
 
@@ -1051,7 +475,7 @@ fn spawn_kiro_windows(
             cmd.arg(arg.trim());
         }
     }
-    let child = spawn_command_with_trace(&mut cmd)
+    let child = instance_manager::spawn_command_with_trace(&mut cmd, "Kiro")
         .map_err(|e| format!("启动 Kiro 失败: {}", e))?;
     Ok(child.id())
 }
@@ -1078,7 +502,7 @@ fn spawn_kiro_unix(
             cmd.arg(arg.trim());
         }
     }
-    let child = spawn_command_with_trace(&mut cmd)
+    let child = instance_manager::spawn_command_with_trace(&mut cmd, "Kiro")
         .map_err(|e| format!("启动 Kiro 失败: {}", e))?;
     Ok(child.id())
 }
@@ -1120,60 +544,7 @@ pub fn start_kiro_default_with_args_with_new_window(
 }
 
 pub fn close_kiro(user_data_dirs: &[String], timeout_secs: u64) -> Result<(), String> {
-    let target_dirs: HashSet<String> = user_data_dirs
-        .iter()
-        .map(|value| normalize_path_for_compare(value))
-        .filter(|value| !value.is_empty())
-        .collect();
-    if target_dirs.is_empty() {
-        return Ok(());
-    }
-
-    let default_dir = get_default_kiro_user_data_dir()
-        .ok()
-        .map(|value| normalize_path_for_compare(&value.to_string_lossy()))
-        .filter(|value| !value.is_empty());
-    let allow_none_for_default = default_dir
-        .as_ref()
-        .map(|value| target_dirs.contains(value))
-        .unwrap_or(false);
-
-    let entries = collect_kiro_process_entries();
-    let mut pids = Vec::new();
-    for (pid, dir) in entries {
-        match dir.as_ref() {
-            Some(value) => {
-                if target_dirs.contains(value) {
-                    pids.push(pid);
-                }
-            }
-            None if allow_none_for_default => pids.push(pid),
-            _ => {}
-        }
-    }
-
-    pids.sort();
-    pids.dedup();
-    if pids.is_empty() {
-        return Ok(());
-    }
-
-    for pid in &pids {
-        let _ = modules::process::close_pid(*pid, timeout_secs);
-    }
-
-    let still_running: Vec<u32> = pids
-        .into_iter()
-        .filter(|pid| modules::process::is_pid_running(*pid))
-        .collect();
-    if !still_running.is_empty() {
-        return Err(format!(
-            "无法关闭 Kiro 实例进程，请手动关闭后重试: {:?}",
-            still_running
-        ));
-    }
-
-    Ok(())
+    KiroInstances.close_instances(user_data_dirs, timeout_secs, collect_kiro_process_entries)
 }
 
 fn ensure_kiro_profile_dir(profile_dir: &Path) -> Result<PathBuf, String> {
@@ -1556,6 +927,7 @@ fn write_usage_snapshot_if_exists(profile_dir: &Path, account: &KiroAccount) ->
 }
 
 pub fn inject_account_to_profile(profile_dir: &Path, account_id: &str) -> Result<(), String> {
+    modules::credential_vault::ensure_unlocked()?;
     let account = kiro_account::load_account(account_id)
         .ok_or_else(|| format!("绑定账号不存在: {}", account_id))?;
 
@@ -1564,3 +936,55 @@ pub fn inject_account_to_profile(profile_dir: &Path, account_id: &str) -> Result
     let _ = write_usage_snapshot_if_exists(profile_dir, &account);
     Ok(())
 }
+
+/// 启动实例前按绑定账号注入登录态（未绑定账号时跳过）
+pub fn inject_bound_account(
+    user_data_dir: &str,
+    bind_account_id: Option<&str>,
+) -> Result<(), String> {
+    let bind_id = bind_account_id
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let Some(bind_id) = bind_id else {
+        return Ok(());
+    };
+    // 在关闭实例之前检查，避免锁定时关掉实例后才失败
+    modules::credential_vault::ensure_unlocked()?;
+
+    let account = kiro_account::load_account(bind_id)
+        .ok_or_else(|| format!("绑定账号不存在: {}", bind_id))?;
+
+    modules::logger::log_info(&format!(
+        "实例启动检测到绑定 Kiro 账号，准备注入: bind_account_id={}, email={}, user_data_dir={}",
+        bind_id, account.email, user_data_dir
+    ));
+
+    close_kiro(&[user_data_dir.to_string()], 20)?;
+    inject_account_to_profile(Path::new(user_data_dir), bind_id)?;
+
+    modules::logger::log_info(&format!("Kiro 账号注入完成: {}", account.email));
+    Ok(())
+}
+
+/// 重启默认实例：关闭默认实例进程并注入绑定账号后重新启动，返回新进程 PID
+pub fn start_default_instance() -> Result<u32, String> {
+    ensure_kiro_launch_path_configured()?;
+
+    let default_dir = get_default_kiro_user_data_dir()?;
+    let default_dir_str = default_dir.to_string_lossy().to_string();
+    let default_settings = load_default_settings()?;
+    if let Some(pid) = resolve_kiro_pid(default_settings.last_pid, None) {
+        modules::process::close_pid(pid, 20)?;
+        let _ = update_default_pid(None)?;
+    }
+    close_kiro(&[default_dir_str.clone()], 20)?;
+    inject_bound_account(
+        &default_dir_str,
+        default_settings.bind_account_id.as_deref(),
+    )?;
+    let extra_args = modules::process::parse_extra_args(&default_settings.extra_args);
+    let pid = start_kiro_default_with_args_with_new_window(&extra_args, true)?;
+    modules::logger::log_info(&format!("Kiro 默认实例已启动: pid={}", pid));
+    let _ = update_default_pid(Some(pid))?;
+    Ok(pid)
+}
@@ -1,33 +1,54 @@
 pub mod account;
+pub mod account_store;
+pub mod alert_notifier;
+pub mod alert_rules;
+pub mod alert_webhook;
 pub mod announcement;
+pub mod backup;
 pub mod codex_account;
 pub mod codex_instance;
 pub mod codex_oauth;
 pub mod codex_quota;
+pub mod cursor_account;
+pub mod cursor_instance;
+pub mod cursor_usage;
 pub mod config;
+pub mod credential_vault;
 pub mod db;
 pub mod device;
 pub mod fingerprint;
 pub mod github_copilot_account;
 pub mod github_copilot_instance;
 pub mod github_copilot_oauth;
+pub mod group_capacity;
 pub mod group_settings;
 pub mod import;
 pub mod instance;
+pub mod instance_manager;
 pub mod instance_store;
 pub mod kiro_account;
 pub mod kiro_instance;
 pub mod kiro_oauth;
 pub mod logger;
+pub mod model_catalog;
 pub mod oauth;
 pub mod oauth_server;
 pub mod opencode_auth;
+pub mod platform;
 pub mod process;
 pub mod quota;
 pub mod quota_cache;
+pub mod quota_events;
+pub mod quota_forecast;
+pub mod quota_history;
+pub mod refresh_executor;
+pub mod refresh_scheduler;
+pub mod schema_migration;
+pub mod store_fsck;
 pub mod sync_settings;
 pub mod tray;
 pub mod tray_layout;
+pub mod unified_quota;
 pub mod update_checker;
 pub mod vscode_inject;
 pub mod wakeup;
@@ -39,6 +60,8 @@ pub mod websocket;
 pub mod windsurf_account;
 pub mod windsurf_instance;
 pub mod windsurf_oauth;
+pub mod ws_auth;
+pub mod ws_topics;
 
 // 重新导出常用函数
 pub use account::*;
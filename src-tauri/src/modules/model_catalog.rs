@@ -0,0 +1,625 @@
+//! Antigravity 模型目录追踪
+//!
+//! 每次刷新配额拿到 fetchAvailableModels 的最新结果后记录该账号的模型目录，同一订阅等级下各账号目录的并集
+//! 作为该等级的快照，与上一份快照比较得出新增、下线、更名与显示名称变化，变化记录保存在 `model_catalog.json` 中。
+//! 同等级账号看到的模型不完全一致时（灰度、地区差异），并集不会随刷新顺序来回变化。
+//! 有模型上线或下线时发出通知；尚未归入任何分组的新模型会生成分组建议，避免分组静默漏掉新模型。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::modules::group_settings::{self, GroupSettings};
+use crate::modules::{account, logger};
+use crate::utils::atomic_file;
+
+const CATALOG_FILE: &str = "model_catalog.json";
+/// 最多保留的变化记录条数
+const MAX_CHANGES: usize = 200;
+/// 未能识别订阅等级时使用的快照键
+const UNKNOWN_TIER: &str = "UNKNOWN";
+/// 超过该时长未刷新的账号目录不再参与合并（账号已删除或长期未使用）
+const ACCOUNT_CATALOG_TTL_SECONDS: i64 = 7 * 24 * 3600;
+/// 账号目录未变化时，至少间隔该时长才更新一次记录时间
+const ACCOUNT_CATALOG_TOUCH_SECONDS: i64 = 24 * 3600;
+pub const CHANGED_EVENT: &str = "model_catalog:changed";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogModel {
+    pub id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogSnapshot {
+    pub tier: String,
+    pub models: Vec<CatalogModel>,
+    /// 快照时间（Unix 秒）
+    pub captured_at: i64,
+}
+
+/// 模型 ID 变化但可判定为同一模型（新旧版本名或显示名称一致）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRename {
+    pub from_id: String,
+    pub to_id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayNameChange {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogChange {
+    pub tier: String,
+    pub detected_at: i64,
+    #[serde(default)]
+    pub added: Vec<CatalogModel>,
+    #[serde(default)]
+    pub removed: Vec<CatalogModel>,
+    #[serde(default)]
+    pub renamed: Vec<ModelRename>,
+    #[serde(default)]
+    pub display_name_changes: Vec<DisplayNameChange>,
+}
+
+impl CatalogChange {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.display_name_changes.is_empty()
+    }
+
+    /// 有模型上线、下线或更名（仅显示名称变化不通知）
+    fn should_notify(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty() || !self.renamed.is_empty()
+    }
+}
+
+/// 尚未归入分组的新模型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupSuggestion {
+    pub model_id: String,
+    pub display_name: String,
+    /// 按同系列模型推测的分组，无法推测时为空
+    pub suggested_group_id: Option<String>,
+    pub detected_at: i64,
+}
+
+/// 单个账号最近一次拿到的模型目录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AccountCatalog {
+    tier: String,
+    models: Vec<CatalogModel>,
+    captured_at: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CatalogStore {
+    /// 订阅等级 -> 该等级各账号目录的并集
+    #[serde(default)]
+    snapshots: BTreeMap<String, CatalogSnapshot>,
+    /// 账号邮箱（小写）-> 该账号最近的目录
+    #[serde(default)]
+    accounts: BTreeMap<String, AccountCatalog>,
+    #[serde(default)]
+    changes: Vec<CatalogChange>,
+    #[serde(default)]
+    suggestions: Vec<GroupSuggestion>,
+    /// 用户忽略过的模型，不再生成分组建议
+    #[serde(default)]
+    dismissed: BTreeSet<String>,
+}
+
+static CATALOG_LOCK: Mutex<()> = Mutex::new(());
+
+fn get_catalog_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(CATALOG_FILE))
+}
+
+fn load_store() -> CatalogStore {
+    get_catalog_path()
+        .ok()
+        .and_then(|path| atomic_file::recover_json::<CatalogStore>(&path))
+        .unwrap_or_default()
+}
+
+fn save_store(store: &CatalogStore) -> Result<(), String> {
+    let path = get_catalog_path()?;
+    let content =
+        serde_json::to_string_pretty(store).map_err(|e| format!("序列化模型目录失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存模型目录失败: {}", e))
+}
+
+fn with_store<T>(f: impl FnOnce(&mut CatalogStore) -> T) -> Result<T, String> {
+    let _lock = CATALOG_LOCK
+        .lock()
+        .map_err(|_| "获取模型目录锁失败".to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store);
+    save_store(&store)?;
+    Ok(result)
+}
+
+/// 从 fetchAvailableModels 原始响应中提取用户可见模型（带显示名称），按 ID 排序
+pub fn catalog_from_payload(payload: &Value) -> Vec<CatalogModel> {
+    let Some(models) = payload.get("models").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let mut catalog: Vec<CatalogModel> = models
+        .iter()
+        .filter_map(|(id, info)| {
+            let display_name = info.get("displayName")?.as_str()?.trim();
+            (!display_name.is_empty()).then(|| CatalogModel {
+                id: id.clone(),
+                display_name: display_name.to_string(),
+            })
+        })
+        .collect();
+    catalog.sort_by(|a, b| a.id.cmp(&b.id));
+    catalog
+}
+
+/// 比较两份快照
+fn diff(
+    tier: &str,
+    previous: &[CatalogModel],
+    current: &[CatalogModel],
+    now: i64,
+) -> CatalogChange {
+    let previous_by_id: HashMap<&str, &CatalogModel> = previous
+        .iter()
+        .map(|model| (model.id.as_str(), model))
+        .collect();
+    let current_by_id: HashMap<&str, &CatalogModel> = current
+        .iter()
+        .map(|model| (model.id.as_str(), model))
+        .collect();
+
+    let mut added: Vec<CatalogModel> = current
+        .iter()
+        .filter(|model| !previous_by_id.contains_key(model.id.as_str()))
+        .cloned()
+        .collect();
+    let mut removed = Vec::new();
+    let mut renamed = Vec::new();
+    for old in previous
+        .iter()
+        .filter(|model| !current_by_id.contains_key(model.id.as_str()))
+    {
+        let successor = added.iter().position(|new| {
+            group_settings::model_matches(&old.id, &new.id) || old.display_name == new.display_name
+        });
+        match successor {
+            Some(index) => {
+                let new = added.remove(index);
+                renamed.push(ModelRename {
+                    from_id: old.id.clone(),
+                    to_id: new.id,
+                    display_name: new.display_name,
+                });
+            }
+            None => removed.push(old.clone()),
+        }
+    }
+
+    let display_name_changes = current
+        .iter()
+        .filter_map(|model| {
+            let old = previous_by_id.get(model.id.as_str())?;
+            (old.display_name != model.display_name).then(|| DisplayNameChange {
+                id: model.id.clone(),
+                from: old.display_name.clone(),
+                to: model.display_name.clone(),
+            })
+        })
+        .collect();
+
+    CatalogChange {
+        tier: tier.to_string(),
+        detected_at: now,
+        added,
+        removed,
+        renamed,
+        display_name_changes,
+    }
+}
+
+/// 可参与分组的模型（与配额展示一致，只统计 Gemini / Claude 系列）
+fn is_groupable(model_id: &str) -> bool {
+    model_id.contains("gemini") || model_id.contains("claude")
+}
+
+/// 模型名中的系列关键词（去掉版本号片段），例如 gemini-3.1-pro-high -> gemini / pro / high
+fn family_tokens(model_id: &str) -> BTreeSet<String> {
+    model_id
+        .to_lowercase()
+        .split(['-', '_'])
+        .filter(|token| !token.is_empty() && !token.chars().any(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 为未归组的模型推测分组：已归组则返回 None；否则取系列关键词重合最多（至少 2 个）的分组
+fn suggest_group(settings: &GroupSettings, model_id: &str) -> Option<Option<String>> {
+    if settings
+        .group_mappings
+        .keys()
+        .any(|mapped| group_settings::model_matches(model_id, mapped))
+    {
+        return None;
+    }
+
+    let tokens = family_tokens(model_id);
+    let mut best: Option<(usize, usize, &String)> = None;
+    let ordered = settings.get_ordered_groups(None);
+    for (mapped, group_id) in &settings.group_mappings {
+        let score = family_tokens(mapped).intersection(&tokens).count();
+        if score < 2 {
+            continue;
+        }
+        let rank = ordered
+            .iter()
+            .position(|id| id == group_id)
+            .unwrap_or(usize::MAX);
+        let better = match best {
+            Some((best_score, best_rank, _)) => {
+                score > best_score || (score == best_score && rank < best_rank)
+            }
+            None => true,
+        };
+        if better {
+            best = Some((score, rank, group_id));
+        }
+    }
+    Some(best.map(|(_, _, group_id)| group_id.clone()))
+}
+
+fn refresh_suggestions(store: &mut CatalogStore, settings: &GroupSettings, change: &CatalogChange) {
+    let candidates = change
+        .added
+        .iter()
+        .map(|model| (&model.id, &model.display_name))
+        .chain(
+            change
+                .renamed
+                .iter()
+                .map(|rename| (&rename.to_id, &rename.display_name)),
+        );
+    for (model_id, display_name) in candidates {
+        if !is_groupable(model_id)
+            || store.dismissed.contains(model_id)
+            || store
+                .suggestions
+                .iter()
+                .any(|item| &item.model_id == model_id)
+        {
+            continue;
+        }
+        if let Some(suggested_group_id) = suggest_group(settings, model_id) {
+            store.suggestions.push(GroupSuggestion {
+                model_id: model_id.clone(),
+                display_name: display_name.clone(),
+                suggested_group_id,
+                detected_at: change.detected_at,
+            });
+        }
+    }
+    // 已被手动归组的模型不再提示
+    store
+        .suggestions
+        .retain(|item| suggest_group(settings, &item.model_id).is_some());
+}
+
+/// 某订阅等级下各账号目录的并集，按 ID 排序；显示名称以最近刷新的账号为准
+fn merged_catalog(accounts: &BTreeMap<String, AccountCatalog>, tier: &str) -> Vec<CatalogModel> {
+    let mut entries: Vec<&AccountCatalog> = accounts
+        .values()
+        .filter(|entry| entry.tier == tier)
+        .collect();
+    entries.sort_by_key(|entry| entry.captured_at);
+    let mut merged: BTreeMap<&str, &CatalogModel> = BTreeMap::new();
+    for entry in entries {
+        for model in &entry.models {
+            merged.insert(model.id.as_str(), model);
+        }
+    }
+    merged.into_values().cloned().collect()
+}
+
+/// 更新账号目录并把该等级的并集与上一份快照比较，返回变化以及存储是否需要保存
+fn apply_observation(
+    store: &mut CatalogStore,
+    account_key: &str,
+    tier: &str,
+    models: Vec<CatalogModel>,
+    now: i64,
+) -> (Option<CatalogChange>, bool) {
+    let before = store.accounts.len();
+    store
+        .accounts
+        .retain(|_, entry| now - entry.captured_at < ACCOUNT_CATALOG_TTL_SECONDS);
+    let mut dirty = store.accounts.len() != before;
+    // 升级前的快照只来自单个账号：该等级还没有账号目录时重新建立基线，避免误报
+    let rebaseline = !store.accounts.values().any(|entry| entry.tier == tier);
+
+    let unchanged = store.accounts.get(account_key).is_some_and(|entry| {
+        entry.tier == tier
+            && entry.models == models
+            && now - entry.captured_at < ACCOUNT_CATALOG_TOUCH_SECONDS
+    });
+    if !unchanged {
+        store.accounts.insert(
+            account_key.to_string(),
+            AccountCatalog {
+                tier: tier.to_string(),
+                models,
+                captured_at: now,
+            },
+        );
+        dirty = true;
+    }
+
+    let merged = merged_catalog(&store.accounts, tier);
+    let change = match store.snapshots.get(tier) {
+        Some(previous) if !rebaseline => {
+            Some(diff(tier, &previous.models, &merged, now)).filter(|change| !change.is_empty())
+        }
+        Some(previous) if previous.models == merged => return (None, dirty),
+        _ => None,
+    };
+    if change.is_some() || rebaseline {
+        store.snapshots.insert(
+            tier.to_string(),
+            CatalogSnapshot {
+                tier: tier.to_string(),
+                models: merged,
+                captured_at: now,
+            },
+        );
+        dirty = true;
+    }
+    (change, dirty)
+}
+
+/// 记录账号的最新模型目录，返回所属订阅等级的目录变化（首次快照不产生变化记录）
+pub fn observe(
+    account_email: &str,
+    tier: Option<&str>,
+    models: Vec<CatalogModel>,
+) -> Result<Option<CatalogChange>, String> {
+    if models.is_empty() {
+        return Ok(None);
+    }
+    let tier = tier
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(UNKNOWN_TIER)
+        .to_uppercase();
+    let account_key = account_email.trim().to_lowercase();
+    let now = chrono::Utc::now().timestamp();
+
+    let _lock = CATALOG_LOCK
+        .lock()
+        .map_err(|_| "获取模型目录锁失败".to_string())?;
+    let mut store = load_store();
+    let (change, dirty) = apply_observation(&mut store, &account_key, &tier, models, now);
+    if let Some(change) = &change {
+        refresh_suggestions(&mut store, &group_settings::load_group_settings(), change);
+        store.changes.push(change.clone());
+        let overflow = store.changes.len().saturating_sub(MAX_CHANGES);
+        store.changes.drain(..overflow);
+    }
+    if dirty {
+        save_store(&store)?;
+    }
+    Ok(change)
+}
+
+fn format_models<'a>(ids: impl Iterator<Item = &'a str>) -> String {
+    ids.collect::<Vec<_>>().join(", ")
+}
+
+fn build_notification_text(change: &CatalogChange) -> (String, String) {
+    let mut parts = Vec::new();
+    if !change.added.is_empty() {
+        let names = change.added.iter().map(|model| model.display_name.as_str());
+        parts.push(format!("新增: {}", format_models(names)));
+    }
+    if !change.removed.is_empty() {
+        let names = change
+            .removed
+            .iter()
+            .map(|model| model.display_name.as_str());
+        parts.push(format!("下线: {}", format_models(names)));
+    }
+    if !change.renamed.is_empty() {
+        let renames = change
+            .renamed
+            .iter()
+            .map(|rename| format!("{} → {}", rename.from_id, rename.to_id))
+            .collect::<Vec<_>>();
+        parts.push(format!("更名: {}", renames.join(", ")));
+    }
+    (
+        format!("Antigravity 模型目录变化 ({})", change.tier),
+        parts.join("；"),
+    )
+}
+
+fn notify(change: &CatalogChange) {
+    let Some(app_handle) = crate::get_app_handle() else {
+        return;
+    };
+    use tauri::Emitter;
+    use tauri_plugin_notification::NotificationExt;
+
+    let _ = app_handle.emit(CHANGED_EVENT, change);
+    let (title, body) = build_notification_text(change);
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(&title)
+        .body(body)
+        .show()
+    {
+        logger::log_warn(&format!("[ModelCatalog] 原生通知发送失败: {}", e));
+    }
+}
+
+/// 刷新配额后调用：记录目录快照，有变化时写日志并通知（失败只记录日志）
+pub fn observe_logged(account_email: &str, tier: Option<&str>, payload: &Value) {
+    match observe(account_email, tier, catalog_from_payload(payload)) {
+        Ok(Some(change)) => {
+            logger::log_info(&format!(
+                "[ModelCatalog] {} 模型目录变化: 新增 {}，下线 {}，更名 {}，显示名称变化 {}",
+                change.tier,
+                change.added.len(),
+                change.removed.len(),
+                change.renamed.len(),
+                change.display_name_changes.len()
+            ));
+            if change.should_notify() {
+                notify(&change);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => logger::log_warn(&format!("[ModelCatalog] 记录模型目录失败: {}", e)),
+    }
+}
+
+pub fn list_snapshots() -> Vec<CatalogSnapshot> {
+    load_store().snapshots.into_values().collect()
+}
+
+/// 最近的变化记录（新的在前）
+pub fn list_changes(limit: Option<usize>) -> Vec<CatalogChange> {
+    let mut changes = load_store().changes;
+    changes.reverse();
+    if let Some(limit) = limit {
+        changes.truncate(limit);
+    }
+    changes
+}
+
+pub fn list_suggestions() -> Vec<GroupSuggestion> {
+    load_store().suggestions
+}
+
+/// 采纳分组建议：把模型加入指定分组（为空时使用推测的分组）
+pub fn accept_suggestion(model_id: &str, group_id: Option<String>) -> Result<(), String> {
+    with_store(|store| {
+        let suggestion = store
+            .suggestions
+            .iter()
+            .find(|item| item.model_id == model_id)
+            .ok_or_else(|| format!("分组建议不存在: {}", model_id))?;
+        let group_id = group_id
+            .filter(|value| !value.trim().is_empty())
+            .or_else(|| suggestion.suggested_group_id.clone())
+            .ok_or_else(|| "请选择要加入的分组".to_string())?;
+        group_settings::modify_group_settings("model_catalog_accept_suggestion", |settings| {
+            settings.set_model_group(model_id, &group_id);
+        })?;
+        store.suggestions.retain(|item| item.model_id != model_id);
+        Ok(())
+    })?
+}
+
+/// 忽略分组建议，之后不再为该模型生成建议
+pub fn dismiss_suggestion(model_id: &str) -> Result<(), String> {
+    with_store(|store| {
+        store.suggestions.retain(|item| item.model_id != model_id);
+        store.dismissed.insert(model_id.to_string());
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(id: &str, display_name: &str) -> CatalogModel {
+        CatalogModel {
+            id: id.to_string(),
+            display_name: display_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_detects_added_removed_renamed_and_display_names() {
+        let previous = vec![
+            model("claude-sonnet-4-5", "Claude Sonnet 4.5"),
+            model("gemini-3-flash", "Gemini 3 Flash"),
+            model("gemini-3-pro-high", "Gemini 3 Pro (High)"),
+            model("gpt-oss-120b-medium", "GPT-OSS 120B"),
+        ];
+        let current = vec![
+            model("claude-sonnet-4-6", "Claude Sonnet 4.6"),
+            model("gemini-3-flash", "Gemini 3 Flash (New)"),
+            model("gemini-3-pro-high", "Gemini 3 Pro (High)"),
+            model("gemini-4-pro", "Gemini 4 Pro"),
+        ];
+
+        let change = diff("PRO", &previous, &current, 100);
+        assert_eq!(change.added, vec![model("gemini-4-pro", "Gemini 4 Pro")]);
+        assert_eq!(
+            change.removed,
+            vec![model("gpt-oss-120b-medium", "GPT-OSS 120B")]
+        );
+        assert_eq!(change.renamed.len(), 1);
+        assert_eq!(change.renamed[0].from_id, "claude-sonnet-4-5");
+        assert_eq!(change.renamed[0].to_id, "claude-sonnet-4-6");
+        assert_eq!(change.display_name_changes.len(), 1);
+        assert_eq!(change.display_name_changes[0].to, "Gemini 3 Flash (New)");
+        assert!(diff("PRO", &current, &current, 100).is_empty());
+    }
+
+    #[test]
+    fn merges_accounts_of_a_tier_instead_of_flapping() {
+        let flash = model("gemini-3-flash", "Gemini 3 Flash");
+        let pro = model("gemini-3-pro-high", "Gemini 3 Pro (High)");
+        let mut store = CatalogStore::default();
+        let mut observe = |account: &str, models: &[&CatalogModel], now: i64| {
+            let models = models.iter().map(|model| (*model).clone()).collect();
+            apply_observation(&mut store, account, "PRO", models, now).0
+        };
+
+        assert_eq!(observe("a@x.com", &[&flash, &pro], 0), None);
+        assert_eq!(observe("b@x.com", &[&flash], 10), None);
+        assert_eq!(observe("a@x.com", &[&flash, &pro], 20), None);
+        assert_eq!(observe("b@x.com", &[&flash], 30), None);
+
+        let removed = observe("a@x.com", &[&flash], 40).unwrap();
+        assert_eq!(removed.removed, vec![pro.clone()]);
+        let added = observe("b@x.com", &[&flash, &pro], 50).unwrap();
+        assert_eq!(added.added, vec![pro.clone()]);
+
+        // 长期未刷新的账号不再参与合并
+        let later = 50 + ACCOUNT_CATALOG_TTL_SECONDS;
+        let expired = observe("a@x.com", &[&flash], later).unwrap();
+        assert_eq!(expired.removed, vec![pro]);
+        assert_eq!(store.accounts.len(), 1);
+    }
+
+    #[test]
+    fn suggests_group_for_unmapped_models_by_family() {
+        let settings = GroupSettings::default();
+        assert_eq!(suggest_group(&settings, "gemini-3.1-flash-lite"), None);
+        assert_eq!(
+            suggest_group(&settings, "gemini-4-flash"),
+            Some(Some("g3_flash".to_string()))
+        );
+        assert_eq!(
+            suggest_group(&settings, "gemini-4-pro-high"),
+            Some(Some("g3_pro".to_string()))
+        );
+        assert_eq!(suggest_group(&settings, "llama-5"), Some(None));
+    }
+}
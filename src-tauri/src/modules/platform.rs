@@ -0,0 +1,238 @@
+//! 多平台统一抽象
+//!
+//! 各平台账号模块实现 `Platform` 并登记到 `PLATFORMS`，通用命令、配额预警等跨平台功能只依赖该 trait。
+//! 新增平台时只需在对应模块里实现 trait，再在注册表中加一行。
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::modules::account;
+use crate::modules::refresh_executor::RefreshReport;
+use crate::modules::{
+    alert_rules, codex_account, cursor_account, github_copilot_account, kiro_account, logger, tray,
+    windsurf_account,
+};
+
+pub use crate::modules::unified_quota::{QuotaMetric, UnifiedQuota};
+
+/// 跨平台的账号视图
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformAccount {
+    pub platform: String,
+    pub id: String,
+    /// 展示用邮箱（无邮箱的平台回退为登录名或 ID）
+    pub email: String,
+    pub tags: Vec<String>,
+    pub created_at: i64,
+    pub last_used: i64,
+    /// 已禁用 / 封禁 / 无权限：不作为切换推荐
+    pub disabled: bool,
+    /// 配额接口返回无权限（403）
+    #[serde(default)]
+    pub forbidden: bool,
+    /// 最近一次配额刷新的错误信息
+    #[serde(default)]
+    pub quota_error: Option<String>,
+    /// 访问令牌过期时间（Unix 秒），平台不提供时为空
+    #[serde(default)]
+    pub token_expires_at: Option<i64>,
+    /// 展示的配额可能已过期（离线模式、最近刷新失败或使用了过期缓存）
+    #[serde(default)]
+    pub quota_stale: bool,
+    pub quota: UnifiedQuota,
+}
+
+/// 平台概要
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformInfo {
+    pub id: String,
+    pub display_name: String,
+    pub account_count: usize,
+    pub current_account_id: Option<String>,
+}
+
+/// 配额预警设置（来自用户配置中各平台对应的字段）
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaAlertSettings {
+    pub enabled: bool,
+    pub threshold: i32,
+}
+
+pub trait Platform: Send + Sync {
+    /// 平台标识，与 `QuotaAlertPayload.platform` 等处保持一致
+    fn id(&self) -> &'static str;
+    fn display_name(&self) -> &'static str;
+
+    fn list_accounts(&self) -> Result<Vec<PlatformAccount>, String>;
+    /// 当前使用中的账号；`accounts` 为刚读取的账号列表，便于按最近使用时间回退
+    fn current_account_id(&self, accounts: &[PlatformAccount]) -> Option<String>;
+    fn remove_accounts(&self, account_ids: &[String]) -> Result<(), String>;
+    fn update_account_tags(&self, account_id: &str, tags: Vec<String>) -> Result<(), String>;
+    fn export_accounts(&self, account_ids: &[String]) -> Result<String, String>;
+    /// 返回导入的账号数
+    fn import_from_json(&self, json_content: String) -> BoxFuture<'static, Result<usize, String>>;
+    /// 切换到指定账号并按平台流程重启默认实例，返回切换结果说明
+    fn switch_account(&self, account_id: String) -> BoxFuture<'static, Result<String, String>>;
+
+    /// 刷新单个账号（令牌或配额，视平台而定）
+    fn refresh_account(&self, account_id: String) -> BoxFuture<'static, Result<(), String>>;
+    /// 全量刷新，返回逐账号结果报告
+    fn refresh_all(&self) -> BoxFuture<'static, Result<RefreshReport, String>>;
+    /// 后台自动刷新间隔（分钟），不大于 0 表示禁用
+    fn refresh_interval_minutes(&self) -> i32;
+
+    /// 设置页中的配额预警开关与阈值，对应该平台的内置预警规则
+    fn quota_alert_settings(&self) -> QuotaAlertSettings;
+}
+
+/// 已注册的平台（顺序即展示顺序）
+static PLATFORMS: &[&dyn Platform] = &[
+    &account::AntigravityPlatform,
+    &codex_account::CodexPlatform,
+    &github_copilot_account::GitHubCopilotPlatform,
+    &windsurf_account::WindsurfPlatform,
+    &kiro_account::KiroPlatform,
+    &cursor_account::CursorPlatform,
+];
+
+pub fn all() -> &'static [&'static dyn Platform] {
+    PLATFORMS
+}
+
+pub fn get(platform_id: &str) -> Result<&'static dyn Platform, String> {
+    PLATFORMS
+        .iter()
+        .copied()
+        .find(|platform| platform.id() == platform_id)
+        .ok_or_else(|| format!("未知平台: {}", platform_id))
+}
+
+pub fn describe(platform: &dyn Platform) -> Result<PlatformInfo, String> {
+    let accounts = platform.list_accounts()?;
+    Ok(PlatformInfo {
+        id: platform.id().to_string(),
+        display_name: platform.display_name().to_string(),
+        account_count: accounts.len(),
+        current_account_id: platform.current_account_id(&accounts),
+    })
+}
+
+/// 导出账号；`account_ids` 为空时导出该平台全部账号
+pub fn export_accounts(platform: &dyn Platform, account_ids: &[String]) -> Result<String, String> {
+    if !account_ids.is_empty() {
+        return platform.export_accounts(account_ids);
+    }
+    let all_ids: Vec<String> = platform
+        .list_accounts()?
+        .into_iter()
+        .map(|account| account.id)
+        .collect();
+    platform.export_accounts(&all_ids)
+}
+
+/// 最近使用的账号（未显式指定当前账号的平台以此作为当前账号）
+pub fn most_recently_used(accounts: &[PlatformAccount]) -> Option<String> {
+    accounts
+        .iter()
+        .max_by_key(|account| account.last_used)
+        .map(|account| account.id.clone())
+}
+
+/// 推荐切换目标：排除当前、已禁用和没有配额数据的账号，按平均剩余配额降序、最近使用时间升序
+pub fn pick_quota_alert_recommendation<'a>(
+    accounts: &'a [PlatformAccount],
+    current_id: &str,
+) -> Option<&'a PlatformAccount> {
+    let mut candidates: Vec<&PlatformAccount> = accounts
+        .iter()
+        .filter(|account| account.id != current_id)
+        .filter(|account| !account.disabled)
+        .filter(|account| !account.quota.is_empty())
+        .collect();
+
+    // 配额非空但没有平均值，说明全部指标都不限额，按满额排序
+    candidates.sort_by(|a, b| {
+        let avg_a = a.quota.average_remaining().unwrap_or(100.0);
+        let avg_b = b.quota.average_remaining().unwrap_or(100.0);
+        avg_b
+            .partial_cmp(&avg_a)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.last_used.cmp(&b.last_used))
+    });
+
+    candidates.into_iter().next()
+}
+
+/// 切换账号后同步界面：刷新托盘菜单；启动路径缺失时通知前端引导配置
+pub fn notify_account_switched(app: &str, launch_error: Option<&str>) {
+    use tauri::Emitter;
+
+    let Some(app_handle) = crate::get_app_handle() else {
+        return;
+    };
+    if launch_error.is_some_and(|e| e.contains("APP_PATH_NOT_FOUND:")) {
+        let _ = app_handle.emit(
+            "app:path_missing",
+            serde_json::json!({ "app": app, "retry": { "kind": "default" } }),
+        );
+    }
+    let _ = tray::update_tray_menu(app_handle);
+}
+
+/// 刷新后的预警检查，失败只记录日志
+pub fn run_quota_alert_logged(platform: &dyn Platform, context: &str) {
+    if let Err(e) = alert_rules::run(platform) {
+        logger::log_warn(&format!(
+            "[QuotaAlert][{}] {}预警检查失败: {}",
+            platform.display_name(),
+            context,
+            e
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: &str, last_used: i64, disabled: bool, percentages: &[i32]) -> PlatformAccount {
+        PlatformAccount {
+            platform: "test".to_string(),
+            id: id.to_string(),
+            email: format!("{}@example.com", id),
+            tags: Vec::new(),
+            created_at: 0,
+            last_used,
+            disabled,
+            forbidden: false,
+            quota_error: None,
+            token_expires_at: None,
+            quota_stale: false,
+            quota: UnifiedQuota::new(
+                percentages
+                    .iter()
+                    .enumerate()
+                    .map(|(index, pct)| {
+                        QuotaMetric::from_percent(format!("m{}", index), format!("M{}", index), *pct)
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn recommendation_prefers_highest_average_then_least_recently_used() {
+        let accounts = vec![
+            account("current", 50, false, &[90]),
+            account("disabled", 0, true, &[100]),
+            account("empty", 0, false, &[]),
+            account("busy", 40, false, &[80, 60]),
+            account("idle", 10, false, &[70]),
+            account("fresh", 30, false, &[70]),
+        ];
+
+        let picked = pick_quota_alert_recommendation(&accounts, "current").unwrap();
+        assert_eq!(picked.id, "idle");
+        assert_eq!(most_recently_used(&accounts).as_deref(), Some("current"));
+    }
+}
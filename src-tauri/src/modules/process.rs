@@ -1,4 +1,7 @@
 use crate::modules::config;
+use crate::modules::instance_manager::{self, pick_preferred_pid};
+#[cfg(target_os = "macos")]
+use crate::modules::instance_manager::{is_env_token, split_command_tokens};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::process::{Child, Command, Stdio};
@@ -1666,104 +1669,6 @@ pub fn is_pid_running(pid: u32) -> bool {
     system.process(Pid::from(pid as usize)).is_some()
 }
 
-#[allow(dead_code)]
-fn extract_user_data_dir(args: &[std::ffi::OsString]) -> Option<String> {
-    let tokens: Vec<String> = args
-        .iter()
-        .map(|arg| arg.to_string_lossy().to_string())
-        .collect();
-    let mut index = 0;
-    while index < tokens.len() {
-        let value = tokens[index].as_str();
-        if let Some(rest) = value.strip_prefix("--user-data-dir=") {
-            return Some(rest.to_string());
-        }
-        if value == "--user-data-dir" {
-            index += 1;
-            if index >= tokens.len() {
-                return None;
-            }
-            let mut parts = Vec::new();
-            while index < tokens.len() {
-                let part = tokens[index].as_str();
-                if part.starts_with("--") {
-                    break;
-                }
-                parts.push(part);
-                index += 1;
-            }
-            if !parts.is_empty() {
-                return Some(parts.join(" "));
-            }
-            return None;
-        }
-        index += 1;
-    }
-    None
-}
-
-#[allow(dead_code)]
-fn parse_user_data_dir_value(raw: &str) -> Option<String> {
-    let rest = raw.trim_start();
-    if rest.is_empty() {
-        return None;
-    }
-    let value = if rest.starts_with('"') {
-        let end = rest[1..].find('"').map(|idx| idx + 1).unwrap_or(rest.len());
-        &rest[1..end]
-    } else if rest.starts_with('\'') {
-        let end = rest[1..]
-            .find('\'')
-            .map(|idx| idx + 1)
-            .unwrap_or(rest.len());
-        &rest[1..end]
-    } else {
-        let end = rest.find(" --").unwrap_or(rest.len());
-        &rest[..end]
-    };
-    let value = value.trim();
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.to_string())
-    }
-}
-
-#[allow(dead_code)]
-fn extract_user_data_dir_from_command_line(command_line: &str) -> Option<String> {
-    let tokens = split_command_tokens(command_line);
-    let mut index = 0;
-    while index < tokens.len() {
-        let token = tokens[index].as_str();
-        if let Some(rest) = token.strip_prefix("--user-data-dir=") {
-            if !rest.trim().is_empty() {
-                return Some(rest.to_string());
-            }
-        }
-        if token == "--user-data-dir" {
-            index += 1;
-            if index >= tokens.len() {
-                return None;
-            }
-            let mut parts = Vec::new();
-            while index < tokens.len() {
-                let part = tokens[index].as_str();
-                if part.starts_with("--") || is_env_token(part) {
-                    break;
-                }
-                parts.push(part);
-                index += 1;
-            }
-            if !parts.is_empty() {
-                return Some(parts.join(" "));
-            }
-            return None;
-        }
-        index += 1;
-    }
-    None
-}
-
 #[cfg(target_os = "macos")]
 fn parse_env_value(raw: &str) -> Option<String> {
     let rest = raw.trim_start();
@@ -1829,61 +1734,6 @@ fn extract_env_value_from_tokens(tokens: &[String], key: &str) -> Option<String>
     None
 }
 
-fn split_command_tokens(command_line: &str) -> Vec<String> {
-    let mut tokens = Vec::new();
-    let mut current = String::new();
-    let mut quote: Option<char> = None;
-
-    for ch in command_line.chars() {
-        match quote {
-            Some(q) => {
-                if ch == q {
-                    quote = None;
-                } else {
-                    current.push(ch);
-                }
-            }
-            None => {
-                if ch == '"' || ch == '\'' {
-                    quote = Some(ch);
-                } else if ch.is_whitespace() {
-                    if !current.is_empty() {
-                        tokens.push(current.clone());
-                        current.clear();
-                    }
-                } else {
-                    current.push(ch);
-                }
-            }
-        }
-    }
-
-    if !current.is_empty() {
-        tokens.push(current);
-    }
-
-    tokens
-}
-
-fn is_env_token(token: &str) -> bool {
-    let (key, _) = match token.split_once('=') {
-        Some(parts) => parts,
-        None => return false,
-    };
-    if key.is_empty() {
-        return false;
-    }
-    let mut chars = key.chars();
-    let first = match chars.next() {
-        Some(value) => value,
-        None => return false,
-    };
-    if !(first.is_ascii_alphabetic() || first == '_') {
-        return false;
-    }
-    chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
-}
-
 #[cfg(target_os = "macos")]
 fn extract_env_value(command_line: &str, key: &str) -> Option<String> {
     let needle = format!("{}=", key);
@@ -2131,7 +1981,7 @@ fn collect_antigravity_process_entries_from_ps() -> Vec<(u32, Option<String>)> {
         {
             continue;
         }
-        let dir = extract_user_data_dir_from_command_line(cmdline);
+        let dir = instance_manager::extract_user_data_dir_from_command_line(cmdline);
         result.push((pid, dir));
     }
     result
@@ -2192,7 +2042,7 @@ fn collect_antigravity_process_entries_from_powershell(
         if !is_antigravity_main_process("antigravity.exe", "", Some(&lower)) {
             continue;
         }
-        let dir = extract_user_data_dir_from_command_line(cmdline);
+        let dir = instance_manager::extract_user_data_dir_from_command_line(cmdline);
         result.push((pid, dir));
     }
     result
@@ -2270,7 +2120,7 @@ fn collect_antigravity_process_entries_from_sysinfo_fallback(
                 if used_cmdline_fallback {
                     cmdline_fallback_hit += 1;
                 }
-                let dir = extract_user_data_dir(args);
+                let dir = instance_manager::extract_user_data_dir(args);
                 result.push((pid_u32, dir));
             }
             Some(_) => path_mismatch += 1,
@@ -2333,7 +2183,7 @@ fn collect_antigravity_process_entries_from_proc() -> Vec<(u32, Option<String>)>
         if is_helper_command_line(&cmd_lower) {
             continue;
         }
-        let dir = extract_user_data_dir_from_command_line(&cmdline_str);
+        let dir = instance_manager::extract_user_data_dir_from_command_line(&cmdline_str);
         result.push((pid, dir));
     }
     result
@@ -2427,7 +2277,7 @@ pub fn collect_antigravity_process_entries() -> Vec<(u32, Option<String>)> {
             continue;
         }
 
-        let dir = extract_user_data_dir(&args);
+        let dir = instance_manager::extract_user_data_dir(&args);
         result.push((pid_u32, dir));
     }
 
@@ -2438,15 +2288,6 @@ pub fn collect_antigravity_process_entries() -> Vec<(u32, Option<String>)> {
     )
 }
 
-fn pick_preferred_pid(mut pids: Vec<u32>) -> Option<u32> {
-    if pids.is_empty() {
-        return None;
-    }
-    pids.sort();
-    pids.dedup();
-    pids.first().copied()
-}
-
 fn normalize_non_empty_path_for_compare(value: &str) -> Option<String> {
     let normalized = normalize_path_for_compare(value);
     if normalized.is_empty() {
@@ -2583,7 +2424,7 @@ pub fn resolve_antigravity_pid(last_pid: Option<u32>, user_data_dir: Option<&str
 }
 
 #[cfg(target_os = "macos")]
-fn focus_window_by_pid(pid: u32) -> Result<(), String> {
+pub fn focus_window_by_pid(pid: u32) -> Result<(), String> {
     let script = format!(
         "tell application \"System Events\" to set frontmost of (first process whose unix id is {}) to true",
         pid
@@ -2605,7 +2446,7 @@ fn focus_window_by_pid(pid: u32) -> Result<(), String> {
 }
 
 #[cfg(target_os = "windows")]
-fn focus_window_by_pid(pid: u32) -> Result<(), String> {
+pub fn focus_window_by_pid(pid: u32) -> Result<(), String> {
     let command = format!(
         r#"$targetPid={pid};$h=[IntPtr]::Zero;for($i=0;$i -lt 20;$i++){{$p=Get-Process -Id $targetPid -ErrorAction Stop;$h=$p.MainWindowHandle;if ($h -ne 0) {{ break }};Start-Sleep -Milliseconds 150}};if ($h -eq 0) {{ throw 'MAIN_WINDOW_HANDLE_EMPTY' }};Add-Type @' 
 using System; 
@@ -2631,7 +2472,7 @@ public class Win32 {{
 }
 
 #[cfg(target_os = "linux")]
-fn focus_window_by_pid(pid: u32) -> Result<(), String> {
+pub fn focus_window_by_pid(pid: u32) -> Result<(), String> {
     if let Ok(output) = Command::new("wmctrl").arg("-lp").output() {
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
@@ -2813,14 +2654,15 @@ fn collect_vscode_process_entries_from_powershell(
         if is_helper_command_line(&lower) || lower.contains("crashpad_handler") {
             continue;
         }
-        let dir = extract_user_data_dir_from_command_line(cmdline).and_then(|value| {
-            let normalized = normalize_path_for_compare(&value);
-            if normalized.is_empty() {
-                None
-            } else {
-                Some(normalized)
-            }
-        });
+        let dir =
+            instance_manager::extract_user_data_dir_from_command_line(cmdline).and_then(|value| {
+                let normalized = normalize_path_for_compare(&value);
+                if normalized.is_empty() {
+                    None
+                } else {
+                    Some(normalized)
+                }
+            });
         entries.push((pid, dir));
     }
     entries.sort_by_key(|(pid, _)| *pid);
@@ -2884,14 +2726,15 @@ fn collect_vscode_process_entries_from_sysinfo_fallback(
                 if used_cmdline_fallback {
                     cmdline_fallback_hit += 1;
                 }
-                let dir = extract_user_data_dir(process.cmd()).and_then(|value| {
-                    let normalized = normalize_path_for_compare(&value);
-                    if normalized.is_empty() {
-                        None
-                    } else {
-                        Some(normalized)
-                    }
-                });
+                let dir =
+                    instance_manager::extract_user_data_dir(process.cmd()).and_then(|value| {
+                        let normalized = normalize_path_for_compare(&value);
+                        if normalized.is_empty() {
+                            None
+                        } else {
+                            Some(normalized)
+                        }
+                    });
                 entries.push((pid_u32, dir));
             }
             Some(_) => path_mismatch += 1,
@@ -2977,7 +2820,7 @@ pub fn collect_vscode_process_entries() -> Vec<(u32, Option<String>)> {
             continue;
         }
 
-        let dir = extract_user_data_dir(process.cmd());
+        let dir = instance_manager::extract_user_data_dir(process.cmd());
         entries.push((pid_u32, dir));
     }
 
@@ -3005,7 +2848,7 @@ pub fn collect_vscode_process_entries() -> Vec<(u32, Option<String>)> {
                 if lower.contains("crashpad_handler") || is_helper_command_line(&lower) {
                     continue;
                 }
-                let dir = extract_user_data_dir_from_command_line(cmdline);
+                let dir = instance_manager::extract_user_data_dir_from_command_line(cmdline);
                 entries.push((pid, dir));
             }
         }
@@ -3044,7 +2887,7 @@ pub fn collect_vscode_process_entries() -> Vec<(u32, Option<String>)> {
                 if is_helper_command_line(&cmd_lower) {
                     continue;
                 }
-                let dir = extract_user_data_dir_from_command_line(&cmdline_str);
+                let dir = instance_manager::extract_user_data_dir_from_command_line(&cmdline_str);
                 entries.push((pid, dir));
             }
         }
@@ -3185,7 +3028,7 @@ fn list_user_data_dirs_from_ps() -> Vec<String> {
         if lower.contains("crashpad_handler") {
             continue;
         }
-        if let Some(dir) = extract_user_data_dir_from_command_line(line) {
+        if let Some(dir) = instance_manager::extract_user_data_dir_from_command_line(line) {
             let normalized = normalize_path_for_compare(&dir);
             if !normalized.is_empty() {
                 result.push(normalized);
@@ -3243,7 +3086,7 @@ fn collect_antigravity_process_entries_macos() -> Vec<(u32, Option<String>)> {
             {
                 continue;
             }
-            let dir = extract_user_data_dir_from_command_line(cmdline);
+            let dir = instance_manager::extract_user_data_dir_from_command_line(cmdline);
             result.push((pid, dir));
         }
     }
@@ -3269,7 +3112,7 @@ fn list_user_data_dirs_from_powershell() -> Vec<String> {
         if line.is_empty() {
             continue;
         }
-        if let Some(dir) = extract_user_data_dir_from_command_line(line) {
+        if let Some(dir) = instance_manager::extract_user_data_dir_from_command_line(line) {
             let normalized = normalize_path_for_compare(&dir);
             if !normalized.is_empty() {
                 result.push(normalized);
@@ -3312,7 +3155,7 @@ fn list_user_data_dirs_from_proc() -> Vec<String> {
         if cmd_lower.contains("tools") || exe_path.contains("tools") {
             continue;
         }
-        if let Some(dir) = extract_user_data_dir_from_command_line(&cmdline_str) {
+        if let Some(dir) = instance_manager::extract_user_data_dir_from_command_line(&cmdline_str) {
             let normalized = normalize_path_for_compare(&dir);
             if !normalized.is_empty() {
                 result.push(normalized);
@@ -3358,7 +3201,7 @@ fn collect_antigravity_pids_by_user_data_dir(user_data_dir: &str) -> Vec<u32> {
             continue;
         }
 
-        if let Some(dir) = extract_user_data_dir(&args) {
+        if let Some(dir) = instance_manager::extract_user_data_dir(&args) {
             let normalized = normalize_path_for_compare(&dir);
             if normalized == target {
                 result.push(pid_u32);
@@ -3401,7 +3244,9 @@ fn collect_antigravity_pids_by_user_data_dir(user_data_dir: &str) -> Vec<u32> {
                     {
                         continue;
                     }
-                    if let Some(dir) = extract_user_data_dir_from_command_line(cmdline) {
+                    if let Some(dir) =
+                        instance_manager::extract_user_data_dir_from_command_line(cmdline)
+                    {
                         let normalized = normalize_path_for_compare(&dir);
                         if normalized == target {
                             result.push(pid);
@@ -3437,7 +3282,9 @@ fn collect_antigravity_pids_by_user_data_dir(user_data_dir: &str) -> Vec<u32> {
                 if !is_antigravity_main_process("antigravity.exe", "", Some(&lower)) {
                     continue;
                 }
-                if let Some(dir) = extract_user_data_dir_from_command_line(cmdline) {
+                if let Some(dir) =
+                    instance_manager::extract_user_data_dir_from_command_line(cmdline)
+                {
                     let normalized = normalize_path_for_compare(&dir);
                     if normalized == target {
                         result.push(pid);
@@ -3483,7 +3330,9 @@ fn collect_antigravity_pids_by_user_data_dir(user_data_dir: &str) -> Vec<u32> {
             if cmd_lower.contains("tools") || exe_path.contains("tools") {
                 continue;
             }
-            if let Some(dir) = extract_user_data_dir_from_command_line(&cmdline_str) {
+            if let Some(dir) =
+                instance_manager::extract_user_data_dir_from_command_line(&cmdline_str)
+            {
                 let normalized = normalize_path_for_compare(&dir);
                 if normalized == target {
                     result.push(pid);
@@ -3525,7 +3374,9 @@ fn collect_antigravity_pids_by_user_data_dir(user_data_dir: &str) -> Vec<u32> {
             if !cmd_lower.contains("code") && !exe_path.contains("/code") {
                 continue;
             }
-            if let Some(dir) = extract_user_data_dir_from_command_line(&cmdline_str) {
+            if let Some(dir) =
+                instance_manager::extract_user_data_dir_from_command_line(&cmdline_str)
+            {
                 let normalized = normalize_path_for_compare(&dir);
                 if normalized == target {
                     result.push(pid);
@@ -3609,7 +3460,7 @@ pub fn list_antigravity_user_data_dirs() -> Vec<String> {
             continue;
         }
 
-        if let Some(dir) = extract_user_data_dir(&args) {
+        if let Some(dir) = instance_manager::extract_user_data_dir(&args) {
             let normalized = normalize_path_for_compare(&dir);
             if !normalized.is_empty() {
                 result.push(normalized);
@@ -1,11 +1,8 @@
-use crate::models::{QuotaData, TokenData};
-use crate::modules;
-use chrono::Utc;
+use crate::models::{Account, QuotaData, TokenData};
+use crate::modules::model_catalog;
+use crate::modules::quota_cache::{self, QuotaApiError};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use sha2::{Digest, Sha256};
-use std::fs;
-use std::path::PathBuf;
 
 const CLOUD_CODE_DAILY_BASE_URL: &str = "https://daily-cloudcode-pa.googleapis.com";
 const CLOUD_CODE_PROD_BASE_URL: &str = "https://cloudcode-pa.googleapis.com";
@@ -19,10 +16,8 @@ const DEFAULT_ATTEMPTS: usize = 2;
 const BACKOFF_BASE_MS: u64 = 500;
 const BACKOFF_MAX_MS: u64 = 4000;
 const ONBOARD_POLL_DELAY_MS: u64 = 500;
-const API_CACHE_DIR: &str = "cache/quota_api_v1_desktop";
-const API_CACHE_VERSION: u8 = 1;
-#[allow(dead_code)]
-const API_CACHE_TTL_MS: i64 = 60_000;
+/// 配额缓存中 Antigravity 的平台标识
+const CACHE_PLATFORM: &str = "antigravity";
 
 #[derive(Debug, Clone, Default)]
 pub struct QuotaCloudCodeContext {
@@ -112,84 +107,6 @@ fn log_subscription_tier_result(email: &str, subscription_tier: Option<&String>,
     }
 }
 
-fn hash_email(email: &str) -> String {
-    let normalized = email.trim().to_lowercase();
-    let mut hasher = Sha256::new();
-    hasher.update(normalized.as_bytes());
-    format!("{:x}", hasher.finalize())
-}
-
-fn api_cache_path(source: &str, email: &str) -> Result<PathBuf, String> {
-    let data_dir = modules::account::get_data_dir()?;
-    let dir = data_dir.join(API_CACHE_DIR).join(source);
-    if !dir.exists() {
-        fs::create_dir_all(&dir)
-            .map_err(|e| format!("Failed to create quota api cache dir: {}", e))?;
-    }
-    Ok(dir.join(format!("{}.json", hash_email(email))))
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct QuotaApiCacheRecord {
-    version: u8,
-    source: String,
-    custom_source: String,
-    email: String,
-    project_id: Option<String>,
-    updated_at: i64,
-    payload: serde_json::Value,
-}
-
-#[allow(dead_code)]
-fn read_api_cache(source: &str, email: &str) -> Option<QuotaApiCacheRecord> {
-    let path = api_cache_path(source, email).ok()?;
-    let content = fs::read_to_string(path).ok()?;
-    let record = serde_json::from_str::<QuotaApiCacheRecord>(&content).ok()?;
-    if record.version != API_CACHE_VERSION {
-        return None;
-    }
-    if record.source != source {
-        return None;
-    }
-    Some(record)
-}
-
-#[allow(dead_code)]
-fn is_api_cache_valid(record: &QuotaApiCacheRecord) -> bool {
-    let now_ms = Utc::now().timestamp_millis();
-    now_ms - record.updated_at < API_CACHE_TTL_MS
-}
-
-#[allow(dead_code)]
-fn api_cache_age_secs(record: &QuotaApiCacheRecord) -> i64 {
-    let now_ms = Utc::now().timestamp_millis();
-    std::cmp::max(0, (now_ms - record.updated_at) / 1000)
-}
-
-fn write_api_cache(
-    source: &str,
-    custom_source: &str,
-    email: &str,
-    project_id: Option<String>,
-    payload: serde_json::Value,
-) {
-    if let Ok(path) = api_cache_path(source, email) {
-        let record = QuotaApiCacheRecord {
-            version: API_CACHE_VERSION,
-            source: source.to_string(),
-            custom_source: custom_source.to_string(),
-            email: email.to_string(),
-            project_id,
-            updated_at: Utc::now().timestamp_millis(),
-            payload,
-        };
-        if let Ok(content) = serde_json::to_string_pretty(&record) {
-            let _ = fs::write(path, content);
-        }
-    }
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 struct QuotaResponse {
     models: std::collections::HashMap<String, ModelInfo>,
@@ -709,138 +626,183 @@ pub async fn fetch_quota(
         .await
 }
 
-pub async fn fetch_quota_with_context(
-    access_token: &str,
-    email: &str,
-    skip_cache: bool,
-    ctx: &QuotaCloudCodeContext,
-) -> crate::error::AppResult<QuotaFetchResult> {
-    use crate::error::AppError;
-
-    let base_url = resolve_cloud_code_base_url(ctx);
-    let (resolved_project_id, subscription_tier) =
-        fetch_project_id_with_context(access_token, email, ctx).await;
-    let effective_project_id = resolved_project_id
-        .clone()
-        .or_else(|| ctx.preferred_project_id.clone());
-
-    // 保留缓存，但缓存命中前仍先执行与 Antigravity.app 对齐的项目识别流程。
-    if !skip_cache {
-        if let Some(record) = read_api_cache("authorized", email) {
-            if is_api_cache_valid(&record) {
-                crate::modules::logger::log_info(&format!(
-                    "[QuotaApiCache] Using api cache for {} (age: {}s)",
-                    email,
-                    api_cache_age_secs(&record),
-                ));
-                if let Ok(quota_response) =
-                    serde_json::from_value::<QuotaResponse>(record.payload.clone())
-                {
-                    let quota_data =
-                        build_quota_data_from_response(quota_response, subscription_tier.clone());
-                    return Ok(QuotaFetchResult {
-                        quota: quota_data,
-                        project_id: effective_project_id.clone(),
-                        error: None,
-                    });
-                }
-            } else {
-                crate::modules::logger::log_info(&format!(
-                    "[QuotaApiCache] Cache expired for {} (age: {}s), fetching from network",
-                    email,
-                    api_cache_age_secs(&record),
-                ));
-            }
-        }
-    }
-
+/// 请求 fetchAvailableModels 并返回原始响应；403 等明确拒绝直接返回，网络错误、429 与 5xx 重试后按暂时性错误返回
+async fn request_available_models(
+    base_url: String,
+    access_token: String,
+    project_id: Option<String>,
+) -> Result<serde_json::Value, QuotaApiError> {
     let client = create_client();
-    let payload = effective_project_id
+    let payload = project_id
         .as_ref()
         .map(|id| json!({ "project": id }))
         .unwrap_or_else(|| json!({}));
 
     let max_retries = 3;
+    let mut last_error = String::new();
 
     for attempt in 1..=max_retries {
         match client
             .post(format!("{}/{}", base_url, FETCH_AVAILABLE_MODELS_PATH))
-            .bearer_auth(access_token)
+            .bearer_auth(&access_token)
             .header("User-Agent", USER_AGENT)
             .header(reqwest::header::ACCEPT_ENCODING, "gzip")
             .json(&payload)
             .send()
             .await
         {
+            Ok(response) if response.status().is_success() => {
+                let body = response
+                    .text()
+                    .await
+                    .map_err(|e| QuotaApiError::Transient(format!("读取响应失败: {}", e)))?;
+                return serde_json::from_str(&body)
+                    .map_err(|e| QuotaApiError::Transient(format!("API 响应解析失败: {}", e)));
+            }
             Ok(response) => {
-                if response.error_for_status_ref().is_err() {
-                    let status = response.status();
-
-                    if status == reqwest::StatusCode::FORBIDDEN {
-                        crate::modules::logger::log_warn(&format!(
-                            "账号无权限 (403 Forbidden), 标记为 forbidden 状态: {}",
-                            email
-                        ));
-                        let text = response.text().await.unwrap_or_default();
-                        let mut q = QuotaData::new();
-                        q.is_forbidden = true;
-                        q.subscription_tier = subscription_tier.clone();
-                        let message = if text.trim().is_empty() {
-                            "API returned 403 Forbidden".to_string()
-                        } else {
-                            text
-                        };
-                        return Ok(QuotaFetchResult {
-                            quota: q,
-                            project_id: effective_project_id.clone(),
-                            error: Some(QuotaFetchError {
-                                code: Some(status.as_u16()),
-                                message,
-                            }),
-                        });
-                    }
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    let message = if text.trim().is_empty() {
+                        format!("API returned {}", status)
+                    } else {
+                        text
+                    };
+                    return Err(QuotaApiError::Rejected {
+                        status: status.as_u16(),
+                        message,
+                    });
+                }
+                last_error = format!("API 错误: {} - {}", status, text);
+            }
+            Err(e) => last_error = format!("Network error: {}", e),
+        }
+        if attempt < max_retries {
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+    }
 
-                    if attempt < max_retries {
-                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                        continue;
-                    }
+    Err(QuotaApiError::Transient(last_error))
+}
 
-                    let text = response.text().await.unwrap_or_default();
-                    return Err(AppError::Unknown(format!("API 错误: {} - {}", status, text)));
-                }
+pub async fn fetch_quota_with_context(
+    access_token: &str,
+    email: &str,
+    skip_cache: bool,
+    ctx: &QuotaCloudCodeContext,
+) -> crate::error::AppResult<QuotaFetchResult> {
+    use crate::error::AppError;
+
+    // 离线模式下不做项目识别，由缓存层直接返回缓存数据
+    let (resolved_project_id, subscription_tier) = if quota_cache::is_offline() {
+        (None, None)
+    } else {
+        fetch_project_id_with_context(access_token, email, ctx).await
+    };
+    let effective_project_id = resolved_project_id.or_else(|| ctx.preferred_project_id.clone());
+
+    // 缓存命中前仍先执行与 Antigravity.app 对齐的项目识别流程。
+    let request = request_available_models(
+        resolve_cloud_code_base_url(ctx),
+        access_token.to_string(),
+        effective_project_id.clone(),
+    );
+    let result = quota_cache::resolve(
+        CACHE_PLATFORM,
+        email,
+        effective_project_id.clone(),
+        skip_cache,
+        request,
+    )
+    .await;
 
-                let body = response.text().await.map_err(AppError::Network)?;
-                let payload_value: serde_json::Value = serde_json::from_str(&body)
-                    .map_err(|e| AppError::Unknown(format!("API 响应解析失败: {}", e)))?;
-
-                write_api_cache(
-                    "authorized",
-                    "desktop",
-                    email,
-                    effective_project_id.clone(),
-                    payload_value.clone(),
-                );
-
-                let quota_response: QuotaResponse = serde_json::from_value(payload_value)
-                    .map_err(|e| AppError::Unknown(format!("API 响应解析失败: {}", e)))?;
-                let quota_data =
-                    build_quota_data_from_response(quota_response, subscription_tier.clone());
-
-                return Ok(QuotaFetchResult {
-                    quota: quota_data,
-                    project_id: effective_project_id.clone(),
-                    error: None,
-                });
+    match result {
+        Ok(cached) => {
+            if cached.stale {
+                crate::modules::logger::log_info(&format!(
+                    "[QuotaApiCache] Using stale api cache for {} (updated_at: {})",
+                    email, cached.updated_at
+                ));
+            } else {
+                model_catalog::observe_logged(email, subscription_tier.as_deref(), &cached.payload);
             }
-            Err(e) => {
-                if attempt < max_retries {
-                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                } else {
-                    return Err(AppError::Network(e));
-                }
+            let quota_response: QuotaResponse = serde_json::from_value(cached.payload)
+                .map_err(|e| AppError::Unknown(format!("API 响应解析失败: {}", e)))?;
+            let mut quota_data = build_quota_data_from_response(quota_response, subscription_tier);
+            if cached.stale {
+                quota_data.last_updated = cached.updated_at / 1000;
+            }
+            Ok(QuotaFetchResult {
+                quota: quota_data,
+                project_id: cached.project_id.or(effective_project_id),
+                error: None,
+            })
+        }
+        Err(QuotaApiError::Rejected { status, message }) if status == 403 => {
+            crate::modules::logger::log_warn(&format!(
+                "账号无权限 (403 Forbidden), 标记为 forbidden 状态: {}",
+                email
+            ));
+            let mut q = QuotaData::new();
+            q.is_forbidden = true;
+            q.subscription_tier = subscription_tier;
+            Ok(QuotaFetchResult {
+                quota: q,
+                project_id: effective_project_id,
+                error: Some(QuotaFetchError {
+                    code: Some(status),
+                    message,
+                }),
+            })
+        }
+        Err(e) => Err(AppError::Unknown(e.to_string())),
+    }
+}
+
+/// 缓存比账号文件更新时（例如由插件端写入），用缓存中的配额覆盖账号数据
+pub fn apply_cached_quota(account: &mut Account) -> Result<bool, String> {
+    let Some(cached) = quota_cache::read(CACHE_PLATFORM, &account.email) else {
+        return Ok(false);
+    };
+
+    let cache_updated = cached.updated_at / 1000;
+    let current_updated = account
+        .quota
+        .as_ref()
+        .map(|quota| quota.last_updated)
+        .unwrap_or(0);
+    if current_updated >= cache_updated && account.quota.is_some() {
+        return Ok(false);
+    }
+
+    let parsed = serde_json::from_value::<QuotaResponse>(cached.payload)
+        .map_err(|e| format!("Failed to parse api cache payload: {}", e))?;
+    let subscription_tier = account
+        .quota
+        .as_ref()
+        .and_then(|q| q.subscription_tier.clone());
+    let mut quota = build_quota_data_from_response(parsed, subscription_tier);
+    quota.last_updated = cache_updated;
+    quota.is_forbidden = account
+        .quota
+        .as_ref()
+        .map(|q| q.is_forbidden)
+        .unwrap_or(false);
+
+    // 容错：如果缓存的 models 为空，但账号已有配额数据，保留原有 models
+    if quota.models.is_empty() {
+        if let Some(ref existing_quota) = account.quota {
+            if !existing_quota.models.is_empty() {
+                // 只更新非 models 字段，不更新 last_updated，保留原有的时间戳
+                let mut merged_quota = existing_quota.clone();
+                merged_quota.subscription_tier = quota.subscription_tier.clone();
+                merged_quota.is_forbidden = quota.is_forbidden;
+                account.update_quota(merged_quota);
+                return Ok(true);
             }
         }
     }
 
-    Err(AppError::Unknown("配额查询失败".to_string()))
+    account.update_quota(quota);
+    Ok(true)
 }
@@ -1,155 +1,387 @@
-use std::fs;
-use std::path::PathBuf;
+//! 统一配额接口缓存
+//!
+//! 按「平台 + 账号」缓存配额接口的原始响应（`cache/quota_api_v1_desktop/<平台>/<sha256(账号)>.json`），
+//! 各平台的配额刷新经 `refresh` / `resolve` 读写缓存：
+//! - 缓存未超过 TTL 时直接使用；超过 TTL 后立即请求接口并等待结果，刷新结果总是最新数据
+//! - 请求暂时性失败（网络错误、429、5xx）时回退到过期缓存
+//! - 离线模式下不发起任何请求，只从缓存读取，返回的数据带过期标记
+//!
+//! Antigravity 沿用旧版的 `authorized` 目录与记录格式，与插件端共享缓存。
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value as JsonValue;
+use serde_json::Value;
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
 
-use crate::models::{Account, QuotaData};
-use crate::modules;
+use crate::modules::platform::PlatformAccount;
+use crate::modules::{account, logger, refresh_executor};
+use crate::utils::atomic_file;
 
 const CACHE_DIR: &str = "cache/quota_api_v1_desktop";
 const CACHE_VERSION: u8 = 1;
+const SETTINGS_FILE: &str = "quota_cache.json";
+/// Antigravity 旧版缓存目录名
+const ANTIGRAVITY_NAMESPACE: &str = "authorized";
+const CUSTOM_SOURCE: &str = "desktop";
+
+fn default_ttl_seconds() -> i64 {
+    60
+}
+
+fn default_serve_stale_on_error() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaCacheSettings {
+    /// 缓存有效期（秒），0 表示总是请求接口
+    #[serde(default = "default_ttl_seconds")]
+    pub ttl_seconds: i64,
+    /// 请求失败时使用过期缓存
+    #[serde(default = "default_serve_stale_on_error")]
+    pub serve_stale_on_error: bool,
+    /// 离线模式：不请求接口，只展示缓存数据
+    #[serde(default)]
+    pub offline_mode: bool,
+}
+
+impl Default for QuotaCacheSettings {
+    fn default() -> Self {
+        Self {
+            ttl_seconds: default_ttl_seconds(),
+            serve_stale_on_error: default_serve_stale_on_error(),
+            offline_mode: false,
+        }
+    }
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(SETTINGS_FILE))
+}
+
+pub fn load_settings() -> QuotaCacheSettings {
+    let Ok(path) = get_settings_path() else {
+        return QuotaCacheSettings::default();
+    };
+    std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .or_else(|| atomic_file::recover_json(&path))
+        .unwrap_or_default()
+}
+
+pub fn save_settings(mut settings: QuotaCacheSettings) -> Result<QuotaCacheSettings, String> {
+    settings.ttl_seconds = settings.ttl_seconds.max(0);
+    let path = get_settings_path()?;
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("序列化配额缓存设置失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存配额缓存设置失败: {}", e))?;
+    logger::log_info(&format!(
+        "[QuotaCache] 设置已更新: ttl={}s, serve_stale_on_error={}, offline={}",
+        settings.ttl_seconds, settings.serve_stale_on_error, settings.offline_mode
+    ));
+    Ok(settings)
+}
+
+pub fn is_offline() -> bool {
+    load_settings().offline_mode
+}
+
+// ============ 缓存记录 ============
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct QuotaApiCacheRecord {
+struct QuotaApiCacheRecord {
     version: u8,
     source: String,
+    #[serde(default)]
     custom_source: Option<String>,
+    /// 账号标识（Antigravity 为邮箱）
     email: String,
     project_id: Option<String>,
+    /// 写入时间（Unix 毫秒）
     updated_at: i64,
-    payload: JsonValue,
-}
-
-#[derive(Debug, Deserialize)]
-struct QuotaResponse {
-    models: std::collections::HashMap<String, ModelInfo>,
+    payload: Value,
 }
 
-#[derive(Debug, Deserialize)]
-struct ModelInfo {
-    #[serde(rename = "displayName")]
-    display_name: Option<String>,
-    #[serde(rename = "quotaInfo")]
-    quota_info: Option<QuotaInfo>,
+fn namespace(platform: &str) -> &str {
+    if platform == "antigravity" {
+        ANTIGRAVITY_NAMESPACE
+    } else {
+        platform
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct QuotaInfo {
-    #[serde(rename = "remainingFraction")]
-    remaining_fraction: Option<f64>,
-    #[serde(rename = "resetTime")]
-    reset_time: Option<String>,
+fn normalize_key(key: &str) -> String {
+    key.trim().to_lowercase()
 }
 
-fn hash_email(email: &str) -> String {
-    let normalized = email.trim().to_lowercase();
+fn hash_key(key: &str) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(normalized.as_bytes());
+    hasher.update(normalize_key(key).as_bytes());
     format!("{:x}", hasher.finalize())
 }
 
-fn cache_dir(source: &str) -> Result<PathBuf, String> {
-    let data_dir = modules::account::get_data_dir()?;
-    let dir = data_dir.join(CACHE_DIR).join(source);
+fn cache_path(platform: &str, key: &str) -> Result<PathBuf, String> {
+    let dir = account::get_data_dir()?
+        .join(CACHE_DIR)
+        .join(namespace(platform));
     if !dir.exists() {
-        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create quota cache dir: {}", e))?;
+        std::fs::create_dir_all(&dir).map_err(|e| format!("创建配额缓存目录失败: {}", e))?;
     }
-    Ok(dir)
+    Ok(dir.join(format!("{}.json", hash_key(key))))
 }
 
-fn cache_path(source: &str, email: &str) -> Result<PathBuf, String> {
-    let dir = cache_dir(source)?;
-    Ok(dir.join(format!("{}.json", hash_email(email))))
+fn read_record(platform: &str, key: &str) -> Option<QuotaApiCacheRecord> {
+    let content = std::fs::read_to_string(cache_path(platform, key).ok()?).ok()?;
+    let record = serde_json::from_str::<QuotaApiCacheRecord>(&content).ok()?;
+    (record.version == CACHE_VERSION && record.source == namespace(platform)).then_some(record)
 }
 
-pub(crate) fn read_quota_cache(source: &str, email: &str) -> Option<QuotaApiCacheRecord> {
-    let path = cache_path(source, email).ok()?;
-    let content = fs::read_to_string(path).ok()?;
-    let record = serde_json::from_str::<QuotaApiCacheRecord>(&content).ok()?;
-    if record.version != CACHE_VERSION {
-        return None;
+pub fn write(platform: &str, key: &str, project_id: Option<String>, payload: Value, now_ms: i64) {
+    let record = QuotaApiCacheRecord {
+        version: CACHE_VERSION,
+        source: namespace(platform).to_string(),
+        custom_source: Some(CUSTOM_SOURCE.to_string()),
+        email: key.to_string(),
+        project_id,
+        updated_at: now_ms,
+        payload,
+    };
+    let result = cache_path(platform, key).and_then(|path| {
+        let content = serde_json::to_string_pretty(&record)
+            .map_err(|e| format!("序列化配额缓存失败: {}", e))?;
+        atomic_file::write(&path, content).map_err(|e| format!("写入配额缓存失败: {}", e))
+    });
+    if let Err(e) = result {
+        logger::log_warn(&format!("[QuotaCache][{}] {}", platform, e));
     }
-    if record.source != source {
-        return None;
+}
+
+// ============ 读取策略 ============
+
+/// 经缓存层得到的配额响应
+#[derive(Debug, Clone)]
+pub struct CachedPayload {
+    pub payload: Value,
+    pub project_id: Option<String>,
+    /// 数据获取时间（Unix 毫秒）
+    pub updated_at: i64,
+    /// 数据来自过期缓存（离线或失败回退）
+    pub stale: bool,
+}
+
+impl CachedPayload {
+    fn from_record(record: QuotaApiCacheRecord, stale: bool) -> Self {
+        Self {
+            payload: record.payload,
+            project_id: record.project_id,
+            updated_at: record.updated_at,
+            stale,
+        }
     }
-    Some(record)
 }
 
-pub fn write_quota_cache(source: &str, email: &str, quota: &QuotaData) -> Result<(), String> {
-    let _ = source;
-    let _ = email;
-    let _ = quota;
-    Ok(())
+/// 直接读取缓存，不发起请求；超过有效期的数据标记为过期
+pub fn read(platform: &str, key: &str) -> Option<CachedPayload> {
+    let record = read_record(platform, key)?;
+    let age_ms = chrono::Utc::now().timestamp_millis() - record.updated_at;
+    let stale = !is_fresh(age_ms, &load_settings());
+    Some(CachedPayload::from_record(record, stale))
 }
 
-pub fn apply_cached_quota(account: &mut Account, source: &str) -> Result<bool, String> {
-    let record = match read_quota_cache(source, &account.email) {
-        Some(record) => record,
-        None => return Ok(false),
-    };
+/// 配额接口请求失败
+#[derive(Debug, Clone)]
+pub enum QuotaApiError {
+    /// 网络错误、429、5xx 等暂时性失败，可回退到过期缓存
+    Transient(String),
+    /// 接口明确拒绝（如 401/403），不使用缓存
+    Rejected { status: u16, message: String },
+}
+
+impl fmt::Display for QuotaApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transient(message) => f.write_str(message),
+            Self::Rejected { message, .. } => f.write_str(message),
+        }
+    }
+}
+
+fn is_fresh(age_ms: i64, settings: &QuotaCacheSettings) -> bool {
+    age_ms < settings.ttl_seconds.max(0) * 1000
+}
+
+/// 当前展示的是过期缓存的账号（`平台|账号标识`）
+fn stale_keys() -> &'static Mutex<HashSet<String>> {
+    static KEYS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    KEYS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+fn entry_key(platform: &str, key: &str) -> String {
+    format!("{}|{}", platform, normalize_key(key))
+}
+
+fn set_stale(platform: &str, key: &str, stale: bool) {
+    if let Ok(mut keys) = stale_keys().lock() {
+        let entry = entry_key(platform, key);
+        if stale {
+            keys.insert(entry);
+        } else {
+            keys.remove(&entry);
+        }
+    }
+}
 
-    let cache_updated = record.updated_at / 1000;
-    let current_updated = account
-        .quota
-        .as_ref()
-        .map(|quota| quota.last_updated)
-        .unwrap_or(0);
+/// 经缓存层刷新的结果
+#[derive(Debug, Clone)]
+pub enum Refreshed<T> {
+    /// 接口请求成功
+    Fetched(T),
+    /// 使用缓存数据（有效期内、离线模式或请求失败回退）
+    Cached(CachedPayload),
+}
 
-    if current_updated >= cache_updated && account.quota.is_some() {
-        return Ok(false);
+/// 经缓存刷新配额：`fetch` 为实际的接口请求，仅在缓存不在有效期内时执行；
+/// 请求成功后把 `cacheable` 提取的配额数据（不应包含令牌等敏感字段）写入缓存。
+/// `skip_cache` 为 true 时总是请求接口（单个账号的手动刷新），但失败时仍可回退到缓存
+pub async fn refresh<T, Fut>(
+    platform: &str,
+    key: &str,
+    project_id: Option<String>,
+    skip_cache: bool,
+    fetch: Fut,
+    cacheable: impl FnOnce(&T) -> Value,
+) -> Result<Refreshed<T>, QuotaApiError>
+where
+    Fut: Future<Output = Result<T, QuotaApiError>>,
+{
+    let settings = load_settings();
+    let cached = read_record(platform, key);
+    if settings.offline_mode {
+        set_stale(platform, key, true);
+        return cached
+            .map(|record| Refreshed::Cached(CachedPayload::from_record(record, true)))
+            .ok_or_else(|| QuotaApiError::Transient("离线模式下没有该账号的配额缓存".to_string()));
     }
 
-    let mut quota = QuotaData::new();
-    quota.last_updated = cache_updated;
-    quota.subscription_tier = account
-        .quota
-        .as_ref()
-        .and_then(|q| q.subscription_tier.clone());
-    quota.is_forbidden = account
-        .quota
-        .as_ref()
-        .map(|q| q.is_forbidden)
-        .unwrap_or(false);
-
-    let parsed = serde_json::from_value::<QuotaResponse>(record.payload.clone())
-        .map_err(|e| format!("Failed to parse api cache payload: {}", e))?;
-    for (name, info) in parsed.models {
-        let display_name = info
-            .display_name
-            .as_deref()
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .map(str::to_string);
-        if let Some(quota_info) = info.quota_info {
-            let percentage = quota_info
-                .remaining_fraction
-                .map(|f| (f * 100.0) as i32)
-                .unwrap_or(0);
-            let reset_time = quota_info.reset_time.unwrap_or_default();
-            if name.contains("gemini") || name.contains("claude") {
-                quota.add_model(name, display_name, percentage, reset_time);
-            }
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    if let Some(record) = cached.as_ref().filter(|_| !skip_cache) {
+        if is_fresh(now_ms - record.updated_at, &settings) {
+            set_stale(platform, key, false);
+            return Ok(Refreshed::Cached(CachedPayload::from_record(
+                record.clone(),
+                false,
+            )));
         }
     }
 
-    // 容错：如果缓存的 models 为空，但账号已有配额数据，保留原有 models
-    if quota.models.is_empty() {
-        if let Some(ref existing_quota) = account.quota {
-            if !existing_quota.models.is_empty() {
-                // 只更新非 models 字段
-                let mut merged_quota = existing_quota.clone();
-                merged_quota.subscription_tier = quota.subscription_tier.clone();
-                merged_quota.is_forbidden = quota.is_forbidden;
-                // 不更新 last_updated，保留原有的时间戳
-                account.update_quota(merged_quota);
-                return Ok(true);
-            }
+    match fetch.await {
+        Ok(fetched) => {
+            write(platform, key, project_id, cacheable(&fetched), now_ms);
+            set_stale(platform, key, false);
+            Ok(Refreshed::Fetched(fetched))
+        }
+        Err(QuotaApiError::Transient(message)) if settings.serve_stale_on_error => {
+            let Some(record) = cached else {
+                return Err(QuotaApiError::Transient(message));
+            };
+            logger::log_warn(&format!(
+                "[QuotaCache][{}] 请求失败，使用 {} 秒前的缓存: {}",
+                platform,
+                (now_ms - record.updated_at).max(0) / 1000,
+                message
+            ));
+            set_stale(platform, key, true);
+            Ok(Refreshed::Cached(CachedPayload::from_record(record, true)))
         }
+        Err(e) => Err(e),
+    }
+}
+
+/// 经缓存查询原始配额响应（整个响应即为配额数据）
+pub async fn resolve<Fut>(
+    platform: &str,
+    key: &str,
+    project_id: Option<String>,
+    skip_cache: bool,
+    fetch: Fut,
+) -> Result<CachedPayload, QuotaApiError>
+where
+    Fut: Future<Output = Result<Value, QuotaApiError>>,
+{
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let refreshed = refresh(
+        platform,
+        key,
+        project_id.clone(),
+        skip_cache,
+        fetch,
+        |payload: &Value| payload.clone(),
+    )
+    .await?;
+    Ok(match refreshed {
+        Refreshed::Fetched(payload) => CachedPayload {
+            payload,
+            project_id,
+            updated_at: now_ms,
+            stale: false,
+        },
+        Refreshed::Cached(cached) => cached,
+    })
+}
+
+/// 标记账号展示的配额是否可能过期：离线模式、最近刷新失败或正在使用过期缓存
+pub fn mark_staleness(platform_id: &str, accounts: &mut [PlatformAccount]) {
+    let offline = is_offline();
+    let failing = refresh_executor::failing_accounts(platform_id);
+    let stale = stale_keys()
+        .lock()
+        .map(|keys| keys.clone())
+        .unwrap_or_default();
+    for item in accounts.iter_mut() {
+        item.quota_stale = offline
+            || failing.contains(&item.id)
+            || stale.contains(&entry_key(platform_id, &item.id))
+            || stale.contains(&entry_key(platform_id, &item.email));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_cache_age_against_ttl() {
+        let settings = QuotaCacheSettings::default();
+        assert!(is_fresh(59_999, &settings));
+        assert!(!is_fresh(60_000, &settings));
+
+        let no_cache = QuotaCacheSettings {
+            ttl_seconds: 0,
+            ..settings
+        };
+        assert!(!is_fresh(0, &no_cache));
     }
 
-    account.update_quota(quota);
-    Ok(true)
+    #[test]
+    fn reads_legacy_antigravity_records() {
+        let legacy = r#"{
+            "version": 1,
+            "source": "authorized",
+            "customSource": "desktop",
+            "email": "A@example.com",
+            "projectId": null,
+            "updatedAt": 1700000000000,
+            "payload": { "models": {} }
+        }"#;
+        let record: QuotaApiCacheRecord = serde_json::from_str(legacy).unwrap();
+        assert_eq!(record.source, namespace("antigravity"));
+        assert_eq!(hash_key("a@example.com "), hash_key(&record.email));
+        assert_eq!(entry_key("codex", " ID-1"), "codex|id-1");
+    }
 }
@@ -0,0 +1,311 @@
+//! 配额变化事件
+//!
+//! 每次刷新后把平台账号的最新配额与上一次快照比较，产生类型化事件，
+//! 同时通过 Tauri 事件（`quota:changed`）和 WebSocket 广播（`event.quota_changed`）发出，
+//! 供托盘、预警与外部工具复用。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tauri::Emitter;
+
+use crate::modules::platform::{self, Platform, PlatformAccount, QuotaMetric};
+use crate::modules::{logger, websocket};
+
+pub const EVENT: &str = "quota:changed";
+
+/// 事件内容，`type` 字段为事件类型
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum QuotaChange {
+    /// 配额回升：回到 100% 或重置时间后移且剩余增加
+    #[serde(rename = "quota.reset_detected")]
+    ResetDetected {
+        metric: String,
+        label: String,
+        previous_percent: i32,
+        remaining_percent: i32,
+        resets_at: Option<i64>,
+    },
+    /// 剩余百分比向下越过阈值（平台预警阈值与 0%）
+    #[serde(rename = "quota.threshold_crossed")]
+    ThresholdCrossed {
+        metric: String,
+        label: String,
+        threshold: i32,
+        previous_percent: i32,
+        remaining_percent: i32,
+    },
+    /// 配额接口开始返回无权限
+    #[serde(rename = "quota.forbidden")]
+    Forbidden,
+    /// 出现新的配额刷新错误
+    #[serde(rename = "quota.error")]
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaEvent {
+    pub platform: String,
+    pub account_id: String,
+    pub email: String,
+    pub occurred_at: i64,
+    #[serde(flatten)]
+    pub change: QuotaChange,
+}
+
+/// 上一次观察到的账号配额状态
+#[derive(Debug, Clone, PartialEq)]
+struct AccountSnapshot {
+    forbidden: bool,
+    quota_error: Option<String>,
+    metrics: Vec<QuotaMetric>,
+}
+
+impl From<&PlatformAccount> for AccountSnapshot {
+    fn from(account: &PlatformAccount) -> Self {
+        Self {
+            forbidden: account.forbidden,
+            quota_error: account.quota_error.clone(),
+            metrics: account.quota.metrics.clone(),
+        }
+    }
+}
+
+type PlatformSnapshots = HashMap<String, AccountSnapshot>;
+
+fn snapshots() -> &'static Mutex<HashMap<&'static str, PlatformSnapshots>> {
+    static SNAPSHOTS: OnceLock<Mutex<HashMap<&'static str, PlatformSnapshots>>> = OnceLock::new();
+    SNAPSHOTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 判断同一指标两次采样之间是否发生了重置
+pub fn is_reset(previous: &QuotaMetric, current: &QuotaMetric) -> bool {
+    if current.remaining_pct <= previous.remaining_pct {
+        return false;
+    }
+    current.remaining_pct >= 100
+        || matches!(
+            (previous.resets_at, current.resets_at),
+            (Some(before), Some(after)) if after > before
+        )
+}
+
+fn diff_account(
+    previous: &AccountSnapshot,
+    current: &AccountSnapshot,
+    thresholds: &[i32],
+) -> Vec<QuotaChange> {
+    let mut changes = Vec::new();
+    if current.forbidden && !previous.forbidden {
+        changes.push(QuotaChange::Forbidden);
+    }
+    if let Some(message) = &current.quota_error {
+        if previous.quota_error.as_ref() != Some(message) {
+            changes.push(QuotaChange::Error {
+                message: message.clone(),
+            });
+        }
+    }
+
+    for metric in &current.metrics {
+        let Some(before) = previous.metrics.iter().find(|item| item.id == metric.id) else {
+            continue;
+        };
+        if is_reset(before, metric) {
+            changes.push(QuotaChange::ResetDetected {
+                metric: metric.id.clone(),
+                label: metric.label.clone(),
+                previous_percent: before.remaining_pct,
+                remaining_percent: metric.remaining_pct,
+                resets_at: metric.resets_at,
+            });
+            continue;
+        }
+        // 一次跨过多个阈值时只报告最低的一个
+        let crossed = thresholds
+            .iter()
+            .copied()
+            .filter(|threshold| {
+                before.remaining_pct > *threshold && metric.remaining_pct <= *threshold
+            })
+            .min();
+        if let Some(threshold) = crossed {
+            changes.push(QuotaChange::ThresholdCrossed {
+                metric: metric.id.clone(),
+                label: metric.label.clone(),
+                threshold,
+                previous_percent: before.remaining_pct,
+                remaining_percent: metric.remaining_pct,
+            });
+        }
+    }
+    changes
+}
+
+fn thresholds_for(target: &dyn Platform) -> Vec<i32> {
+    let threshold = target.quota_alert_settings().threshold.clamp(0, 100);
+    if threshold > 0 {
+        vec![threshold, 0]
+    } else {
+        vec![0]
+    }
+}
+
+/// 与上一次快照比较并更新快照；平台首次观察时只记录基线
+fn collect_events(target: &dyn Platform) -> Result<Vec<QuotaEvent>, String> {
+    let accounts = target.list_accounts()?;
+    let current: PlatformSnapshots = accounts
+        .iter()
+        .map(|account| (account.id.clone(), AccountSnapshot::from(account)))
+        .collect();
+
+    let mut guard = snapshots()
+        .lock()
+        .map_err(|_| "获取配额快照锁失败".to_string())?;
+    let Some(previous) = guard.insert(target.id(), current) else {
+        return Ok(Vec::new());
+    };
+
+    let thresholds = thresholds_for(target);
+    let occurred_at = chrono::Utc::now().timestamp();
+    let mut events = Vec::new();
+    for account in &accounts {
+        let Some(before) = previous.get(&account.id) else {
+            continue;
+        };
+        for change in diff_account(before, &AccountSnapshot::from(account), &thresholds) {
+            events.push(QuotaEvent {
+                platform: target.id().to_string(),
+                account_id: account.id.clone(),
+                email: account.email.clone(),
+                occurred_at,
+                change,
+            });
+        }
+    }
+    Ok(events)
+}
+
+/// 启动时记录各平台的配额基线，使首次刷新即可产生事件
+pub fn prime() {
+    for target in platform::all() {
+        if let Err(e) = collect_events(*target) {
+            logger::log_warn(&format!(
+                "[QuotaEvents][{}] 记录配额基线失败: {}",
+                target.display_name(),
+                e
+            ));
+        }
+    }
+}
+
+/// 刷新后比较配额并发出变化事件，返回本次产生的事件
+pub fn publish(target: &dyn Platform) -> Result<Vec<QuotaEvent>, String> {
+    let events = collect_events(target)?;
+    if events.is_empty() {
+        return Ok(events);
+    }
+    logger::log_info(&format!(
+        "[QuotaEvents][{}] 配额变化事件: {}",
+        target.display_name(),
+        events.len()
+    ));
+    let app_handle = crate::get_app_handle();
+    for event in &events {
+        if let Some(app_handle) = app_handle {
+            let _ = app_handle.emit(EVENT, event);
+        }
+        websocket::broadcast_quota_event(event);
+    }
+    Ok(events)
+}
+
+/// 刷新后的事件检查，失败只记录日志
+pub fn publish_logged(target: &dyn Platform) {
+    if let Err(e) = publish(target) {
+        logger::log_warn(&format!(
+            "[QuotaEvents][{}] 配额变化检查失败: {}",
+            target.display_name(),
+            e
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(metrics: &[(i32, Option<i64>)]) -> AccountSnapshot {
+        AccountSnapshot {
+            forbidden: false,
+            quota_error: None,
+            metrics: metrics
+                .iter()
+                .enumerate()
+                .map(|(index, (pct, resets_at))| {
+                    QuotaMetric::from_percent(format!("m{}", index), format!("M{}", index), *pct)
+                        .with_resets_at(*resets_at)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn detects_resets_and_lowest_crossed_threshold() {
+        let previous = snapshot(&[
+            (10, Some(100)),
+            (40, Some(100)),
+            (50, Some(100)),
+            (30, None),
+        ]);
+        let current = snapshot(&[
+            (100, Some(200)),
+            (80, Some(200)),
+            (0, Some(100)),
+            (25, None),
+        ]);
+        let changes = diff_account(&previous, &current, &[20, 0]);
+
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(
+            &changes[0],
+            QuotaChange::ResetDetected { metric, previous_percent: 10, .. } if metric == "m0"
+        ));
+        // m1 剩余回升且重置时间后移，视为重置
+        assert!(matches!(&changes[1], QuotaChange::ResetDetected { metric, .. } if metric == "m1"));
+        assert!(matches!(
+            &changes[2],
+            QuotaChange::ThresholdCrossed { metric, threshold: 0, .. } if metric == "m2"
+        ));
+    }
+
+    #[test]
+    fn reports_new_forbidden_and_errors_once() {
+        let previous = snapshot(&[]);
+        let mut current = snapshot(&[]);
+        current.forbidden = true;
+        current.quota_error = Some("403".to_string());
+        assert_eq!(
+            diff_account(&previous, &current, &[0]),
+            vec![
+                QuotaChange::Forbidden,
+                QuotaChange::Error {
+                    message: "403".to_string()
+                }
+            ]
+        );
+        assert!(diff_account(&current, &current, &[0]).is_empty());
+
+        let event = QuotaEvent {
+            platform: "antigravity".to_string(),
+            account_id: "a".to_string(),
+            email: "a@example.com".to_string(),
+            occurred_at: 1,
+            change: QuotaChange::Forbidden,
+        };
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["type"], "quota.forbidden");
+        assert_eq!(serde_json::from_value::<QuotaEvent>(value).unwrap(), event);
+    }
+}
@@ -0,0 +1,270 @@
+//! 配额耗尽预测
+//!
+//! 基于 `quota_history` 中的采样估算每个指标的消耗速度（剩余百分比 / 小时），
+//! 推算耗尽时间并与下次重置时间比较，判断是否会在重置前用完。
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::modules::platform::{Platform, PlatformAccount};
+use crate::modules::quota_history::{self, QuotaSample};
+
+/// 只使用最近 24 小时的采样
+const LOOKBACK_SECONDS: i64 = 24 * 3600;
+/// 采样跨度不足 10 分钟时不做预测，避免两次相邻刷新的抖动被放大
+const MIN_SPAN_SECONDS: i64 = 10 * 60;
+/// 剩余百分比至少回升这么多才算重置或补充额度；更小的回升视为取整抖动或平台重新计数
+const REPLENISH_MIN_RISE: i32 = 5;
+/// 回升到该值及以上时无论幅度都算重置
+const REPLENISH_FULL_PERCENT: i32 = 99;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotaForecast {
+    pub platform: String,
+    pub account_id: String,
+    /// 指标 ID（`QuotaMetric.id`）
+    pub metric: String,
+    pub label: String,
+    pub remaining_percent: i32,
+    pub reset_at: Option<i64>,
+    /// 每小时消耗的百分比；样本不足时为空，未在消耗时为 0
+    pub burn_rate_per_hour: Option<f64>,
+    /// 预计耗尽时间（Unix 秒）；未在消耗或样本不足时为空
+    pub exhausts_at: Option<i64>,
+    /// 预计在重置前耗尽（重置时间未知时只要会耗尽即为 true）
+    pub exhausts_before_reset: bool,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurnEstimate {
+    pub burn_rate_per_hour: f64,
+    pub exhausts_at: Option<i64>,
+}
+
+fn is_replenish(before: i32, after: i32) -> bool {
+    after - before >= REPLENISH_MIN_RISE || (after > before && after >= REPLENISH_FULL_PERCENT)
+}
+
+/// 取最近一次回升（重置或补充额度）之后的连续采样
+fn trailing_segment(points: &[(i64, i32)]) -> &[(i64, i32)] {
+    let start = points
+        .windows(2)
+        .rposition(|pair| is_replenish(pair[0].1, pair[1].1))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    &points[start..]
+}
+
+/// (采样时间, 剩余百分比, 重置时间)
+type HistoryPoint = (i64, i32, Option<i64>);
+
+/// 取当前配额周期的采样：相邻采样之间跨过了前一次记录的重置时间即视为进入新周期
+fn current_period(points: &[HistoryPoint]) -> Vec<(i64, i32)> {
+    let start = points
+        .windows(2)
+        .rposition(|pair| pair[0].2.is_some_and(|reset_at| pair[1].0 >= reset_at))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    points[start..]
+        .iter()
+        .map(|(sampled_at, remaining, _)| (*sampled_at, *remaining))
+        .collect()
+}
+
+/// 按最小二乘拟合剩余百分比随时间的斜率；`points` 为 (采样时间, 剩余百分比)，需按时间升序
+pub fn estimate_burn(
+    points: &[(i64, i32)],
+    remaining_percent: i32,
+    now: i64,
+) -> Option<BurnEstimate> {
+    let segment = trailing_segment(points);
+    let (first, last) = (segment.first()?, segment.last()?);
+    if segment.len() < 2 || last.0 - first.0 < MIN_SPAN_SECONDS {
+        return None;
+    }
+
+    let n = segment.len() as f64;
+    let origin = first.0;
+    let mean_t = segment
+        .iter()
+        .map(|(t, _)| (t - origin) as f64)
+        .sum::<f64>()
+        / n;
+    let mean_p = segment.iter().map(|(_, p)| f64::from(*p)).sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for (t, p) in segment {
+        let dt = (t - origin) as f64 - mean_t;
+        cov += dt * (f64::from(*p) - mean_p);
+        var += dt * dt;
+    }
+    if var <= 0.0 {
+        return None;
+    }
+
+    let burn_per_second = -(cov / var);
+    if burn_per_second <= f64::EPSILON {
+        return Some(BurnEstimate {
+            burn_rate_per_hour: 0.0,
+            exhausts_at: None,
+        });
+    }
+
+    let remaining = f64::from(remaining_percent.max(0));
+    Some(BurnEstimate {
+        burn_rate_per_hour: burn_per_second * 3600.0,
+        exhausts_at: Some(now + (remaining / burn_per_second).round() as i64),
+    })
+}
+
+fn forecast_metrics(
+    account: &PlatformAccount,
+    samples: &[QuotaSample],
+    now: i64,
+) -> Vec<QuotaForecast> {
+    let mut series: BTreeMap<&str, Vec<HistoryPoint>> = BTreeMap::new();
+    for sample in samples {
+        series.entry(sample.metric.as_str()).or_default().push((
+            sample.sampled_at,
+            sample.remaining_percent,
+            sample.reset_at,
+        ));
+    }
+
+    account
+        .quota
+        .metrics
+        .iter()
+        .map(|metric| {
+            let history = series
+                .get(metric.id.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            let points = current_period(history);
+            let estimate = estimate_burn(&points, metric.remaining_pct, now);
+            let exhausts_at = estimate.and_then(|value| value.exhausts_at);
+            QuotaForecast {
+                platform: account.platform.clone(),
+                account_id: account.id.clone(),
+                metric: metric.id.clone(),
+                label: metric.label.clone(),
+                remaining_percent: metric.remaining_pct,
+                reset_at: metric.resets_at,
+                burn_rate_per_hour: estimate.map(|value| value.burn_rate_per_hour),
+                exhausts_at,
+                exhausts_before_reset: match (exhausts_at, metric.resets_at) {
+                    (Some(exhausts_at), Some(reset_at)) => exhausts_at < reset_at,
+                    (Some(_), None) => true,
+                    _ => false,
+                },
+                samples: points.len(),
+            }
+        })
+        .collect()
+}
+
+fn forecast_for(
+    platform: &dyn Platform,
+    account: &PlatformAccount,
+) -> Result<Vec<QuotaForecast>, String> {
+    let now = chrono::Utc::now().timestamp();
+    let samples =
+        quota_history::account_samples(platform.id(), &account.id, now - LOOKBACK_SECONDS)?;
+    Ok(forecast_metrics(account, &samples, now))
+}
+
+/// 预测单个账号各指标的耗尽时间
+pub fn forecast_account(
+    platform: &dyn Platform,
+    account_id: &str,
+) -> Result<Vec<QuotaForecast>, String> {
+    let account = platform
+        .list_accounts()?
+        .into_iter()
+        .find(|account| account.id == account_id)
+        .ok_or_else(|| format!("账号不存在: {}", account_id))?;
+    forecast_for(platform, &account)
+}
+
+/// 预测平台下所有账号（已禁用账号除外）
+pub fn forecast_platform(platform: &dyn Platform) -> Result<Vec<QuotaForecast>, String> {
+    let mut forecasts = Vec::new();
+    for account in platform.list_accounts()? {
+        if account.disabled {
+            continue;
+        }
+        forecasts.extend(forecast_for(platform, &account)?);
+    }
+    Ok(forecasts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_linear_burn_since_last_replenish() {
+        // 先从 30% 回升到 100%，之后每 10 分钟消耗 5%
+        let points = [
+            (0, 40),
+            (600, 30),
+            (1_200, 100),
+            (1_800, 95),
+            (2_400, 90),
+            (3_000, 85),
+        ];
+        let estimate = estimate_burn(&points, 85, 3_000).unwrap();
+        assert!((estimate.burn_rate_per_hour - 30.0).abs() < 1e-6);
+        assert_eq!(estimate.exhausts_at, Some(3_000 + 85 * 120));
+    }
+
+    #[test]
+    fn small_rises_do_not_restart_the_segment() {
+        // 取整抖动与重新计数带来的 1-2 个百分点回升
+        let points = [
+            (0, 100),
+            (600, 96),
+            (1_200, 97),
+            (1_800, 91),
+            (2_400, 92),
+            (3_000, 86),
+            (3_600, 85),
+            (3_900, 86),
+        ];
+        let estimate = estimate_burn(&points, 86, 3_900).unwrap();
+        assert!((estimate.burn_rate_per_hour - 14.0).abs() < 1.0);
+        assert!(estimate.exhausts_at.is_some());
+
+        // 回到满额时即使幅度很小也算重置
+        assert_eq!(
+            trailing_segment(&[(0, 97), (600, 99), (1_200, 98)]).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn period_starts_after_the_previous_reset_time() {
+        let points = [
+            (0, 30, Some(1_000)),
+            (600, 20, Some(1_000)),
+            (1_200, 20, Some(19_000)),
+            (1_800, 15, Some(19_000)),
+        ];
+        assert_eq!(current_period(&points), vec![(1_200, 20), (1_800, 15)]);
+
+        // 滚动窗口的重置时间随采样后移，但尚未到达时不切分
+        let rolling = [(0, 90, Some(5_000)), (600, 85, Some(5_600))];
+        assert_eq!(current_period(&rolling).len(), 2);
+    }
+
+    #[test]
+    fn flat_or_short_series_do_not_predict_exhaustion() {
+        let flat = [(0, 50), (1_200, 50), (2_400, 50)];
+        let estimate = estimate_burn(&flat, 50, 2_400).unwrap();
+        assert_eq!(estimate.burn_rate_per_hour, 0.0);
+        assert_eq!(estimate.exhausts_at, None);
+
+        assert_eq!(estimate_burn(&[(0, 50), (300, 40)], 40, 300), None);
+        assert_eq!(estimate_burn(&[(0, 50)], 50, 0), None);
+    }
+}
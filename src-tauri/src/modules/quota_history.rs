@@ -0,0 +1,413 @@
+//! 配额历史时间序列
+//!
+//! 每次批量刷新成功后，把各账号的配额（经 `Platform` 统一为剩余百分比）追加为一条采样，
+//! 存放在独立的 `quota_history.db` 中，供前端按时间范围与分桶绘制消耗曲线。
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::modules::logger;
+use crate::modules::platform::{Platform, PlatformAccount};
+
+const DB_FILE: &str = "quota_history.db";
+/// 采样保留天数，写入时顺带清理更早的数据
+const RETENTION_DAYS: i64 = 90;
+/// 单次查询最多返回的点数，防止前端一次拉取过多原始采样
+const MAX_QUERY_POINTS: usize = 20_000;
+
+static SCHEMA_READY: AtomicBool = AtomicBool::new(false);
+
+/// 一条配额采样
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotaSample {
+    pub platform: String,
+    pub account_id: String,
+    /// 指标 ID（`QuotaMetric.id`）
+    pub metric: String,
+    pub remaining_percent: i32,
+    pub reset_at: Option<i64>,
+    pub sampled_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaHistoryQuery {
+    pub platform: Option<String>,
+    pub account_id: Option<String>,
+    pub metric: Option<String>,
+    /// 起始时间（Unix 秒，含）
+    pub from: i64,
+    /// 结束时间（Unix 秒，含）
+    pub to: i64,
+    /// 分桶宽度（秒）；为空或 <= 0 时返回原始采样
+    pub bucket_seconds: Option<i64>,
+}
+
+/// 查询结果中的一个点（原始采样时 `samples` 为 1）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaHistoryPoint {
+    pub platform: String,
+    pub account_id: String,
+    pub metric: String,
+    pub bucket_start: i64,
+    pub remaining_min: i32,
+    pub remaining_max: i32,
+    pub remaining_avg: f64,
+    /// 桶内最后一次采样的剩余百分比
+    pub remaining_last: i32,
+    pub reset_at: Option<i64>,
+    pub samples: i64,
+}
+
+fn db_err(context: &str, e: rusqlite::Error) -> String {
+    format!("{}: {}", context, e)
+}
+
+fn get_db_path() -> Result<PathBuf, String> {
+    Ok(crate::modules::account::get_data_dir()?.join(DB_FILE))
+}
+
+fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS quota_samples (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            platform TEXT NOT NULL,
+            account_id TEXT NOT NULL,
+            metric TEXT NOT NULL,
+            remaining_percent INTEGER NOT NULL,
+            reset_at INTEGER,
+            sampled_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_quota_samples_series
+            ON quota_samples (platform, account_id, metric, sampled_at);
+        CREATE INDEX IF NOT EXISTS idx_quota_samples_time
+            ON quota_samples (sampled_at);
+        ",
+    )
+    .map_err(|e| db_err("初始化配额历史数据库失败", e))
+}
+
+fn open_connection() -> Result<Connection, String> {
+    let path = get_db_path()?;
+    let conn = Connection::open(&path).map_err(|e| db_err("打开配额历史数据库失败", e))?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))
+        .map_err(|e| db_err("设置数据库超时失败", e))?;
+    conn.pragma_update(None, "journal_mode", "WAL")
+        .map_err(|e| db_err("设置数据库日志模式失败", e))?;
+    if !SCHEMA_READY.load(Ordering::Acquire) {
+        ensure_schema(&conn)?;
+        SCHEMA_READY.store(true, Ordering::Release);
+    }
+    Ok(conn)
+}
+
+/// 把账号视图展开为采样；已禁用或没有配额数据的账号不产生采样
+pub fn samples_from_accounts(accounts: &[PlatformAccount], sampled_at: i64) -> Vec<QuotaSample> {
+    accounts
+        .iter()
+        .filter(|account| !account.disabled)
+        .flat_map(|account| {
+            account.quota.metrics.iter().map(move |metric| QuotaSample {
+                platform: account.platform.clone(),
+                account_id: account.id.clone(),
+                metric: metric.id.clone(),
+                remaining_percent: metric.remaining_pct.clamp(0, 100),
+                reset_at: metric.resets_at,
+                sampled_at,
+            })
+        })
+        .collect()
+}
+
+fn insert_samples(conn: &mut Connection, samples: &[QuotaSample]) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| db_err("开启事务失败", e))?;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT INTO quota_samples
+                 (platform, account_id, metric, remaining_percent, reset_at, sampled_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|e| db_err("准备写入语句失败", e))?;
+        for sample in samples {
+            stmt.execute(params![
+                sample.platform,
+                sample.account_id,
+                sample.metric,
+                sample.remaining_percent,
+                sample.reset_at,
+                sample.sampled_at,
+            ])
+            .map_err(|e| db_err("写入配额采样失败", e))?;
+        }
+    }
+
+    if let Some(oldest) = samples.iter().map(|sample| sample.sampled_at).min() {
+        tx.execute(
+            "DELETE FROM quota_samples WHERE sampled_at < ?1",
+            params![oldest - RETENTION_DAYS * 24 * 3600],
+        )
+        .map_err(|e| db_err("清理过期配额采样失败", e))?;
+    }
+
+    tx.commit().map_err(|e| db_err("提交事务失败", e))
+}
+
+pub fn append_samples(samples: &[QuotaSample]) -> Result<usize, String> {
+    if samples.is_empty() {
+        return Ok(0);
+    }
+    let mut conn = open_connection()?;
+    insert_samples(&mut conn, samples)?;
+    Ok(samples.len())
+}
+
+/// 记录一次批量刷新后的配额；`account_ids` 为本次刷新成功的账号
+pub fn record_refreshed(platform: &dyn Platform, account_ids: &[String]) -> Result<usize, String> {
+    if account_ids.is_empty() {
+        return Ok(0);
+    }
+    let accounts: Vec<PlatformAccount> = platform
+        .list_accounts()?
+        .into_iter()
+        .filter(|account| account_ids.contains(&account.id))
+        .collect();
+    let samples = samples_from_accounts(&accounts, chrono::Utc::now().timestamp());
+    append_samples(&samples)
+}
+
+/// 批量刷新结果中成功的账号 ID
+pub fn succeeded_ids<T>(results: &[(String, Result<T, String>)]) -> Vec<String> {
+    results
+        .iter()
+        .filter(|(_, result)| result.is_ok())
+        .map(|(account_id, _)| account_id.clone())
+        .collect()
+}
+
+/// 同 `record_refreshed`，失败只记录日志，不影响刷新结果
+pub fn record_refreshed_logged(platform: &dyn Platform, account_ids: &[String]) {
+    if let Err(e) = record_refreshed(platform, account_ids) {
+        logger::log_warn(&format!(
+            "[QuotaHistory][{}] 记录配额历史失败: {}",
+            platform.display_name(),
+            e
+        ));
+    }
+}
+
+fn query_points(
+    conn: &Connection,
+    query: &QuotaHistoryQuery,
+) -> Result<Vec<QuotaHistoryPoint>, String> {
+    if query.to < query.from {
+        return Err("查询结束时间不能早于起始时间".to_string());
+    }
+    // 原始采样按 1 秒分桶处理，同一秒内的重复采样会被合并
+    let bucket = query.bucket_seconds.filter(|value| *value > 0).unwrap_or(1);
+
+    // remaining_last / reset_at 取桶内最后一次采样（按 sampled_at、id 倒序的第一条）
+    let sql = "
+        WITH bucketed AS (
+            SELECT platform, account_id, metric, remaining_percent, reset_at,
+                   (sampled_at / ?1) * ?1 AS bucket_start,
+                   ROW_NUMBER() OVER (
+                       PARTITION BY platform, account_id, metric, (sampled_at / ?1)
+                       ORDER BY sampled_at DESC, id DESC
+                   ) AS recency
+            FROM quota_samples
+            WHERE sampled_at BETWEEN ?2 AND ?3
+              AND (?4 IS NULL OR platform = ?4)
+              AND (?5 IS NULL OR account_id = ?5)
+              AND (?6 IS NULL OR metric = ?6)
+        )
+        SELECT platform, account_id, metric, bucket_start,
+               MIN(remaining_percent), MAX(remaining_percent), AVG(remaining_percent),
+               MAX(CASE WHEN recency = 1 THEN remaining_percent END),
+               MAX(CASE WHEN recency = 1 THEN reset_at END),
+               COUNT(*)
+        FROM bucketed
+        GROUP BY platform, account_id, metric, bucket_start
+        ORDER BY platform, account_id, metric, bucket_start
+        LIMIT ?7";
+
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| db_err("准备查询语句失败", e))?;
+    let rows = stmt
+        .query_map(
+            params![
+                bucket,
+                query.from,
+                query.to,
+                query.platform,
+                query.account_id,
+                query.metric,
+                (MAX_QUERY_POINTS + 1) as i64,
+            ],
+            |row| {
+                Ok(QuotaHistoryPoint {
+                    platform: row.get(0)?,
+                    account_id: row.get(1)?,
+                    metric: row.get(2)?,
+                    bucket_start: row.get(3)?,
+                    remaining_min: row.get(4)?,
+                    remaining_max: row.get(5)?,
+                    remaining_avg: row.get(6)?,
+                    remaining_last: row.get(7)?,
+                    reset_at: row.get(8)?,
+                    samples: row.get(9)?,
+                })
+            },
+        )
+        .map_err(|e| db_err("查询配额历史失败", e))?;
+
+    let points = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| db_err("读取配额历史失败", e))?;
+    if points.len() > MAX_QUERY_POINTS {
+        return Err(format!(
+            "查询结果超过 {} 个点，请缩小时间范围或增大分桶",
+            MAX_QUERY_POINTS
+        ));
+    }
+    Ok(points)
+}
+
+pub fn query(query: &QuotaHistoryQuery) -> Result<Vec<QuotaHistoryPoint>, String> {
+    let conn = open_connection()?;
+    query_points(&conn, query)
+}
+
+/// 读取单个账号自 `since` 起的原始采样，按指标、时间升序
+pub fn account_samples(
+    platform: &str,
+    account_id: &str,
+    since: i64,
+) -> Result<Vec<QuotaSample>, String> {
+    let conn = open_connection()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT platform, account_id, metric, remaining_percent, reset_at, sampled_at
+             FROM quota_samples
+             WHERE platform = ?1 AND account_id = ?2 AND sampled_at >= ?3
+             ORDER BY metric, sampled_at, id",
+        )
+        .map_err(|e| db_err("准备查询语句失败", e))?;
+    let rows = stmt
+        .query_map(params![platform, account_id, since], |row| {
+            Ok(QuotaSample {
+                platform: row.get(0)?,
+                account_id: row.get(1)?,
+                metric: row.get(2)?,
+                remaining_percent: row.get(3)?,
+                reset_at: row.get(4)?,
+                sampled_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| db_err("查询配额历史失败", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| db_err("读取配额历史失败", e))
+}
+
+/// 删除指定平台（为空时全部）的历史采样，返回删除条数
+pub fn clear(platform: Option<&str>) -> Result<usize, String> {
+    let conn = open_connection()?;
+    conn.execute(
+        "DELETE FROM quota_samples WHERE ?1 IS NULL OR platform = ?1",
+        params![platform],
+    )
+    .map_err(|e| db_err("清空配额历史失败", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::platform::{QuotaMetric, UnifiedQuota};
+
+    fn memory_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        conn
+    }
+
+    fn sample(metric: &str, remaining: i32, sampled_at: i64) -> QuotaSample {
+        QuotaSample {
+            platform: "codex".to_string(),
+            account_id: "a".to_string(),
+            metric: metric.to_string(),
+            remaining_percent: remaining,
+            reset_at: Some(sampled_at + 3600),
+            sampled_at,
+        }
+    }
+
+    #[test]
+    fn disabled_accounts_produce_no_samples() {
+        let account = |id: &str, disabled: bool| PlatformAccount {
+            platform: "kiro".to_string(),
+            id: id.to_string(),
+            email: String::new(),
+            tags: Vec::new(),
+            created_at: 0,
+            last_used: 0,
+            disabled,
+            forbidden: false,
+            quota_error: None,
+            token_expires_at: None,
+            quota_stale: false,
+            quota: UnifiedQuota::new(vec![QuotaMetric::from_percent(
+                "credits",
+                "Prompt Credits",
+                100,
+            )
+            .with_resets_at(Some(99))]),
+        };
+        let samples = samples_from_accounts(&[account("a", false), account("b", true)], 10);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].metric, "credits");
+        assert_eq!(samples[0].remaining_percent, 100);
+        assert_eq!(samples[0].reset_at, Some(99));
+    }
+
+    #[test]
+    fn buckets_aggregate_and_keep_last_sample() {
+        let mut conn = memory_conn();
+        insert_samples(
+            &mut conn,
+            &[
+                sample("5h", 90, 3_600),
+                sample("5h", 70, 4_000),
+                sample("5h", 80, 5_000),
+                sample("5h", 40, 7_300),
+                sample("Weekly", 50, 3_700),
+            ],
+        )
+        .unwrap();
+
+        let points = query_points(
+            &conn,
+            &QuotaHistoryQuery {
+                metric: Some("5h".to_string()),
+                from: 0,
+                to: 10_000,
+                bucket_seconds: Some(3_600),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].bucket_start, 3_600);
+        assert_eq!((points[0].remaining_min, points[0].remaining_max), (70, 90));
+        assert_eq!(points[0].remaining_last, 80);
+        assert_eq!(points[0].reset_at, Some(8_600));
+        assert_eq!(points[0].samples, 3);
+        assert_eq!(points[1].bucket_start, 7_200);
+        assert_eq!(points[1].remaining_last, 40);
+    }
+}
@@ -0,0 +1,444 @@
+//! 批量刷新执行器
+//!
+//! 各平台的全量刷新共用：按用户配置限制并发数，同一主机的请求之间保持最小间隔，
+//! 连续失败的账号按指数退避暂停刷新（退避状态持久化到 refresh_backoff.json，跨次运行生效），
+//! 并返回逐账号的结果报告。
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::modules::account::RefreshStats;
+use crate::modules::{config, logger, quota_cache};
+use crate::utils::atomic_file;
+
+const BACKOFF_FILE: &str = "refresh_backoff.json";
+/// 首次失败后的退避时长
+const BACKOFF_BASE_SECONDS: i64 = 60;
+/// 退避上限：6 小时
+const BACKOFF_MAX_SECONDS: i64 = 6 * 3600;
+
+pub const DEFAULT_CONCURRENCY: usize = 5;
+pub const MAX_CONCURRENCY: usize = 32;
+pub const DEFAULT_HOST_INTERVAL_MS: u64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshOutcome {
+    Refreshed,
+    Failed,
+    /// 仍在退避期内，本次未刷新
+    BackingOff,
+    /// 离线模式，本次未刷新，沿用缓存数据
+    Offline,
+}
+
+/// 单个账号的刷新结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountRefreshResult {
+    pub account_id: String,
+    pub outcome: RefreshOutcome,
+    pub error: Option<String>,
+    /// 连续失败次数（成功后清零）
+    pub consecutive_failures: u32,
+    /// 退避结束时间（Unix 秒），不在退避中时为空
+    pub next_retry_at: Option<i64>,
+    pub elapsed_ms: u64,
+}
+
+/// 一次全量刷新的结果报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshReport {
+    pub platform: String,
+    pub started_at: i64,
+    pub elapsed_ms: u64,
+    pub total: usize,
+    pub refreshed: usize,
+    pub failed: usize,
+    pub backing_off: usize,
+    pub offline: usize,
+    pub accounts: Vec<AccountRefreshResult>,
+}
+
+impl RefreshReport {
+    pub fn new(
+        platform: &str,
+        started_at: i64,
+        elapsed: Duration,
+        accounts: Vec<AccountRefreshResult>,
+    ) -> Self {
+        let count = |outcome| {
+            accounts
+                .iter()
+                .filter(|item| item.outcome == outcome)
+                .count()
+        };
+        Self {
+            platform: platform.to_string(),
+            started_at,
+            elapsed_ms: elapsed.as_millis() as u64,
+            total: accounts.len(),
+            refreshed: count(RefreshOutcome::Refreshed),
+            failed: count(RefreshOutcome::Failed),
+            backing_off: count(RefreshOutcome::BackingOff),
+            offline: count(RefreshOutcome::Offline),
+            accounts,
+        }
+    }
+}
+
+/// 兼容旧命令返回的计数汇总（退避中的账号不计入）
+impl From<&RefreshReport> for RefreshStats {
+    fn from(report: &RefreshReport) -> Self {
+        let details: Vec<String> = report
+            .accounts
+            .iter()
+            .filter(|item| item.outcome == RefreshOutcome::Failed)
+            .map(|item| {
+                format!(
+                    "Account {}: {}",
+                    item.account_id,
+                    item.error.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        RefreshStats {
+            total: report.refreshed + report.failed,
+            success: report.refreshed,
+            failed: details.len(),
+            details,
+        }
+    }
+}
+
+/// 执行结果：逐账号的原始返回值（退避中的账号不在其中）与报告
+pub struct RefreshRun<T> {
+    pub results: Vec<(String, Result<T, String>)>,
+    pub report: RefreshReport,
+}
+
+// ============ 退避状态 ============
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct BackoffEntry {
+    failures: u32,
+    next_retry_at: i64,
+    last_error: String,
+}
+
+type BackoffTable = BTreeMap<String, BackoffEntry>;
+
+static BACKOFF_LOCK: Mutex<()> = Mutex::new(());
+
+fn backoff_key(platform: &str, account_id: &str) -> String {
+    format!("{}:{}", platform, account_id)
+}
+
+/// 第 n 次连续失败后的退避时长：60s、120s、240s……封顶 6 小时
+fn backoff_seconds(failures: u32) -> i64 {
+    let exponent = failures.saturating_sub(1).min(16);
+    BACKOFF_BASE_SECONDS
+        .saturating_mul(1i64 << exponent)
+        .min(BACKOFF_MAX_SECONDS)
+}
+
+fn get_backoff_path() -> Result<PathBuf, String> {
+    Ok(crate::modules::account::get_data_dir()?.join(BACKOFF_FILE))
+}
+
+fn load_backoff() -> BackoffTable {
+    get_backoff_path()
+        .ok()
+        .and_then(|path| atomic_file::recover_json::<BackoffTable>(&path))
+        .unwrap_or_default()
+}
+
+fn save_backoff(table: &BackoffTable) -> Result<(), String> {
+    let path = get_backoff_path()?;
+    let content = serde_json::to_string_pretty(table)
+        .map_err(|e| format!("序列化刷新退避状态失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存刷新退避状态失败: {}", e))
+}
+
+/// 按本次结果更新退避表：成功清除，失败累加并顺延下次重试时间
+fn apply_results<T>(
+    table: &mut BackoffTable,
+    platform: &str,
+    results: &[(String, Result<T, String>)],
+    now: i64,
+) {
+    for (account_id, result) in results {
+        let key = backoff_key(platform, account_id);
+        match result {
+            Ok(_) => {
+                table.remove(&key);
+            }
+            Err(e) => {
+                let entry = table.entry(key).or_default();
+                entry.failures = entry.failures.saturating_add(1);
+                entry.next_retry_at = now + backoff_seconds(entry.failures);
+                entry.last_error = e.clone();
+            }
+        }
+    }
+}
+
+/// 清除账号的退避状态（手动刷新单个账号成功、删除账号等场景）
+pub fn clear_backoff(platform: &str, account_ids: &[String]) -> Result<(), String> {
+    let _lock = BACKOFF_LOCK
+        .lock()
+        .map_err(|_| "获取刷新退避锁失败".to_string())?;
+    let mut table = load_backoff();
+    let before = table.len();
+    for account_id in account_ids {
+        table.remove(&backoff_key(platform, account_id));
+    }
+    if table.len() != before {
+        save_backoff(&table)?;
+    }
+    Ok(())
+}
+
+/// 最近一次刷新失败、尚未恢复的账号
+pub fn failing_accounts(platform: &str) -> HashSet<String> {
+    let prefix = backoff_key(platform, "");
+    load_backoff()
+        .into_keys()
+        .filter_map(|key| key.strip_prefix(&prefix).map(str::to_string))
+        .collect()
+}
+
+// ============ 主机限速 ============
+
+/// 每个主机下一次允许发起请求的时间
+fn host_slots() -> &'static tokio::sync::Mutex<HashMap<&'static str, Instant>> {
+    static SLOTS: OnceLock<tokio::sync::Mutex<HashMap<&'static str, Instant>>> = OnceLock::new();
+    SLOTS.get_or_init(|| tokio::sync::Mutex::new(HashMap::new()))
+}
+
+/// 预约该主机的下一个请求时间片，必要时等待
+async fn wait_for_host(host: &'static str, interval: Duration) {
+    if interval.is_zero() {
+        return;
+    }
+    let start_at = {
+        let mut slots = host_slots().lock().await;
+        let now = Instant::now();
+        let start_at = slots
+            .get(host)
+            .copied()
+            .filter(|at| *at > now)
+            .unwrap_or(now);
+        slots.insert(host, start_at + interval);
+        start_at
+    };
+    tokio::time::sleep_until(tokio::time::Instant::from_std(start_at)).await;
+}
+
+// ============ 执行 ============
+
+/// 读取并发与主机间隔设置
+fn limits() -> (usize, Duration) {
+    let cfg = config::get_user_config();
+    let concurrency = usize::try_from(cfg.refresh_concurrency)
+        .unwrap_or(DEFAULT_CONCURRENCY)
+        .clamp(1, MAX_CONCURRENCY);
+    (
+        concurrency,
+        Duration::from_millis(cfg.refresh_host_interval_ms),
+    )
+}
+
+/// 并发刷新一批账号；`host` 为该平台刷新请求的目标主机，用于限速
+pub async fn run<T, F, Fut>(
+    platform: &str,
+    host: &'static str,
+    account_ids: Vec<String>,
+    refresh: F,
+) -> RefreshRun<T>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let started = Instant::now();
+    let started_at = chrono::Utc::now().timestamp();
+    if quota_cache::is_offline() {
+        logger::log_info(&format!(
+            "[RefreshExecutor][{}] 离线模式，跳过批量刷新: {}",
+            platform,
+            account_ids.len()
+        ));
+        let accounts = account_ids
+            .into_iter()
+            .map(|account_id| AccountRefreshResult {
+                account_id,
+                outcome: RefreshOutcome::Offline,
+                error: None,
+                consecutive_failures: 0,
+                next_retry_at: None,
+                elapsed_ms: 0,
+            })
+            .collect();
+        return RefreshRun {
+            results: Vec::new(),
+            report: RefreshReport::new(platform, started_at, started.elapsed(), accounts),
+        };
+    }
+    let (concurrency, host_interval) = limits();
+    let backoff = load_backoff();
+
+    let (due, backing_off): (Vec<String>, Vec<String>) =
+        account_ids.into_iter().partition(|account_id| {
+            backoff
+                .get(&backoff_key(platform, account_id))
+                .is_none_or(|entry| entry.next_retry_at <= started_at)
+        });
+    logger::log_info(&format!(
+        "[RefreshExecutor][{}] 开始批量刷新: due={}, backing_off={}, concurrency={}",
+        platform,
+        due.len(),
+        backing_off.len(),
+        concurrency
+    ));
+
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let refresh = &refresh;
+    let tasks = due.into_iter().map(|account_id| {
+        let semaphore = semaphore.clone();
+        async move {
+            let _permit = semaphore.acquire_owned().await;
+            wait_for_host(host, host_interval).await;
+            let account_started = Instant::now();
+            let result = refresh(account_id.clone()).await;
+            (account_id, result, account_started.elapsed())
+        }
+    });
+    let finished = join_all(tasks).await;
+
+    let now = chrono::Utc::now().timestamp();
+    let mut results = Vec::with_capacity(finished.len());
+    let mut elapsed = Vec::with_capacity(finished.len());
+    for (account_id, result, took) in finished {
+        elapsed.push(took);
+        results.push((account_id, result));
+    }
+
+    let table = {
+        let _lock = BACKOFF_LOCK.lock();
+        let mut table = load_backoff();
+        apply_results(&mut table, platform, &results, now);
+        if let Err(e) = save_backoff(&table) {
+            logger::log_warn(&format!("[RefreshExecutor][{}] {}", platform, e));
+        }
+        table
+    };
+
+    let backoff_state = |account_id: &str| {
+        table
+            .get(&backoff_key(platform, account_id))
+            .map(|entry| (entry.failures, Some(entry.next_retry_at)))
+            .unwrap_or((0, None))
+    };
+    let mut accounts: Vec<AccountRefreshResult> = results
+        .iter()
+        .zip(elapsed)
+        .map(|((account_id, result), took)| {
+            let (consecutive_failures, next_retry_at) = backoff_state(account_id);
+            AccountRefreshResult {
+                account_id: account_id.clone(),
+                outcome: if result.is_ok() {
+                    RefreshOutcome::Refreshed
+                } else {
+                    RefreshOutcome::Failed
+                },
+                error: result.as_ref().err().cloned(),
+                consecutive_failures,
+                next_retry_at,
+                elapsed_ms: took.as_millis() as u64,
+            }
+        })
+        .collect();
+    accounts.extend(backing_off.into_iter().map(|account_id| {
+        let (consecutive_failures, next_retry_at) = backoff_state(&account_id);
+        AccountRefreshResult {
+            error: table
+                .get(&backoff_key(platform, &account_id))
+                .map(|entry| entry.last_error.clone()),
+            account_id,
+            outcome: RefreshOutcome::BackingOff,
+            consecutive_failures,
+            next_retry_at,
+            elapsed_ms: 0,
+        }
+    }));
+
+    let report = RefreshReport::new(platform, started_at, started.elapsed(), accounts);
+    logger::log_info(&format!(
+        "[RefreshExecutor][{}] 批量刷新完成: refreshed={}, failed={}, backing_off={}, elapsed={}ms",
+        platform, report.refreshed, report.failed, report.backing_off, report.elapsed_ms
+    ));
+    RefreshRun { results, report }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_exponentially_and_caps() {
+        assert_eq!(backoff_seconds(1), 60);
+        assert_eq!(backoff_seconds(2), 120);
+        assert_eq!(backoff_seconds(4), 480);
+        assert_eq!(backoff_seconds(20), BACKOFF_MAX_SECONDS);
+        assert_eq!(backoff_seconds(u32::MAX), BACKOFF_MAX_SECONDS);
+    }
+
+    #[test]
+    fn results_update_backoff_table_and_stats() {
+        let mut table = BackoffTable::new();
+        let first: Vec<(String, Result<(), String>)> = vec![
+            ("a".to_string(), Ok(())),
+            ("b".to_string(), Err("timeout".to_string())),
+        ];
+        apply_results(&mut table, "kiro", &first, 1_000);
+        apply_results(&mut table, "kiro", &first, 2_000);
+        let entry = &table["kiro:b"];
+        assert_eq!((entry.failures, entry.next_retry_at), (2, 2_120));
+        assert!(!table.contains_key("kiro:a"));
+
+        apply_results(&mut table, "kiro", &[("b".to_string(), Ok(()))], 3_000);
+        assert!(table.is_empty());
+
+        let report = RefreshReport::new(
+            "kiro",
+            0,
+            Duration::ZERO,
+            vec![
+                AccountRefreshResult {
+                    account_id: "b".to_string(),
+                    outcome: RefreshOutcome::Failed,
+                    error: Some("timeout".to_string()),
+                    consecutive_failures: 1,
+                    next_retry_at: Some(60),
+                    elapsed_ms: 10,
+                },
+                AccountRefreshResult {
+                    account_id: "c".to_string(),
+                    outcome: RefreshOutcome::BackingOff,
+                    error: None,
+                    consecutive_failures: 3,
+                    next_retry_at: Some(240),
+                    elapsed_ms: 0,
+                },
+            ],
+        );
+        let stats = RefreshStats::from(&report);
+        assert_eq!((stats.total, stats.success, stats.failed), (1, 0, 1));
+        assert_eq!(stats.details, vec!["Account b: timeout".to_string()]);
+    }
+}
@@ -0,0 +1,359 @@
+//! 后台定时刷新
+//!
+//! 按用户配置中各平台的自动刷新间隔在后端刷新账号，不依赖前端窗口是否存活。
+//! 每次触发时间带随机抖动，避免多个平台同一时刻集中请求；同一平台同一时刻只允许一次刷新（含手动全量刷新）。
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::Emitter;
+use tokio::sync::Notify;
+
+use crate::modules::platform::{self, Platform};
+use crate::modules::refresh_executor::{self, RefreshReport};
+use crate::modules::{logger, quota_events, tray, websocket};
+
+/// 抖动上限：间隔的 10%，且不超过 2 分钟
+const MAX_JITTER_SECONDS: i64 = 120;
+/// 没有启用任何平台时的空转等待时间（配置变更会立即唤醒）
+const IDLE_WAIT: Duration = Duration::from_secs(3600);
+
+pub const PROGRESS_EVENT: &str = "refresh:progress";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshTrigger {
+    Scheduled,
+    Manual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshPhase {
+    Started,
+    Finished,
+    Failed,
+}
+
+/// 刷新进度事件（`refresh:progress`）
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshProgress {
+    pub platform: String,
+    pub trigger: RefreshTrigger,
+    pub phase: RefreshPhase,
+    pub report: Option<RefreshReport>,
+    pub error: Option<String>,
+}
+
+/// 单个平台的调度状态
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshScheduleStatus {
+    pub platform: String,
+    pub interval_minutes: i32,
+    /// 下次自动刷新时间（Unix 秒）
+    pub next_run_at: i64,
+    pub last_run_at: Option<i64>,
+    pub running: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Schedule {
+    interval_minutes: i32,
+    next_run_at: i64,
+    last_run_at: Option<i64>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    schedules: BTreeMap<&'static str, Schedule>,
+    running: HashSet<&'static str>,
+}
+
+static STATE: OnceLock<Mutex<SchedulerState>> = OnceLock::new();
+static WAKE: OnceLock<Notify> = OnceLock::new();
+static STARTED: OnceLock<()> = OnceLock::new();
+
+fn state() -> &'static Mutex<SchedulerState> {
+    STATE.get_or_init(|| Mutex::new(SchedulerState::default()))
+}
+
+fn wake() -> &'static Notify {
+    WAKE.get_or_init(Notify::new)
+}
+
+fn now_ts() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn max_jitter_seconds(interval_minutes: i32) -> i64 {
+    (i64::from(interval_minutes) * 6).min(MAX_JITTER_SECONDS)
+}
+
+fn random_jitter(interval_minutes: i32) -> i64 {
+    let max = max_jitter_seconds(interval_minutes);
+    if max <= 0 {
+        return 0;
+    }
+    rand::random::<u64>() as i64 % (max + 1)
+}
+
+fn next_run_after(now: i64, interval_minutes: i32, jitter: i64) -> i64 {
+    now + i64::from(interval_minutes) * 60 + jitter
+}
+
+/// 按最新配置同步调度表：新启用或间隔变更的平台重新计时，禁用的平台移除，间隔未变的保持原计划
+fn sync_schedules(
+    schedules: &mut BTreeMap<&'static str, Schedule>,
+    intervals: &[(&'static str, i32)],
+    now: i64,
+    jitter: impl Fn(i32) -> i64,
+) {
+    schedules.retain(|platform_id, _| {
+        intervals
+            .iter()
+            .any(|(id, minutes)| id == platform_id && *minutes > 0)
+    });
+    for (platform_id, minutes) in intervals.iter().copied() {
+        if minutes <= 0 {
+            continue;
+        }
+        match schedules.get_mut(platform_id) {
+            Some(schedule) if schedule.interval_minutes == minutes => {}
+            Some(schedule) => {
+                schedule.interval_minutes = minutes;
+                schedule.next_run_at = next_run_after(now, minutes, jitter(minutes));
+            }
+            None => {
+                schedules.insert(
+                    platform_id,
+                    Schedule {
+                        interval_minutes: minutes,
+                        next_run_at: next_run_after(now, minutes, jitter(minutes)),
+                        last_run_at: None,
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn load_intervals() -> Vec<(&'static str, i32)> {
+    platform::all()
+        .iter()
+        .map(|item| (item.id(), item.refresh_interval_minutes()))
+        .collect()
+}
+
+/// 重新读取各平台的刷新间隔（保存通用设置后调用）
+pub fn reschedule() {
+    let intervals = load_intervals();
+    if let Ok(mut guard) = state().lock() {
+        sync_schedules(&mut guard.schedules, &intervals, now_ts(), random_jitter);
+    }
+    wake().notify_one();
+}
+
+pub fn status() -> Vec<RefreshScheduleStatus> {
+    let Ok(guard) = state().lock() else {
+        return Vec::new();
+    };
+    guard
+        .schedules
+        .iter()
+        .map(|(platform_id, schedule)| RefreshScheduleStatus {
+            platform: platform_id.to_string(),
+            interval_minutes: schedule.interval_minutes,
+            next_run_at: schedule.next_run_at,
+            last_run_at: schedule.last_run_at,
+            running: guard.running.contains(platform_id),
+        })
+        .collect()
+}
+
+/// 单飞守卫：持有期间同一平台的其他刷新直接拒绝
+struct RunGuard(&'static str);
+
+impl RunGuard {
+    fn acquire(platform_id: &'static str) -> Option<Self> {
+        let mut guard = state().lock().ok()?;
+        guard
+            .running
+            .insert(platform_id)
+            .then_some(Self(platform_id))
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        if let Ok(mut guard) = state().lock() {
+            guard.running.remove(self.0);
+        }
+    }
+}
+
+fn emit_progress(progress: RefreshProgress) {
+    if let Some(app_handle) = crate::get_app_handle() {
+        let _ = app_handle.emit(PROGRESS_EVENT, progress);
+    }
+}
+
+/// 刷新完成后按当前间隔顺延下一次自动刷新
+fn mark_finished(platform_id: &'static str) {
+    let now = now_ts();
+    if let Ok(mut guard) = state().lock() {
+        if let Some(schedule) = guard.schedules.get_mut(platform_id) {
+            schedule.last_run_at = Some(now);
+            schedule.next_run_at = next_run_after(
+                now,
+                schedule.interval_minutes,
+                random_jitter(schedule.interval_minutes),
+            );
+        }
+    }
+}
+
+/// 刷新平台下全部账号并执行配额预警；该平台已有刷新在进行时返回错误
+pub async fn refresh_platform(
+    target: &'static dyn Platform,
+    trigger: RefreshTrigger,
+) -> Result<RefreshReport, String> {
+    let Some(_guard) = RunGuard::acquire(target.id()) else {
+        return Err(format!("{} 正在刷新中，请稍后再试", target.display_name()));
+    };
+
+    let progress = |phase, report, error| RefreshProgress {
+        platform: target.id().to_string(),
+        trigger,
+        phase,
+        report,
+        error,
+    };
+    emit_progress(progress(RefreshPhase::Started, None, None));
+
+    let result = target.refresh_all().await;
+    mark_finished(target.id());
+    match &result {
+        Ok(report) => {
+            // 规则也覆盖令牌过期、账号禁用等情况，刷新全部失败时同样需要评估
+            platform::run_quota_alert_logged(target, "全量刷新后");
+            emit_progress(progress(RefreshPhase::Finished, Some(report.clone()), None));
+        }
+        Err(e) => emit_progress(progress(RefreshPhase::Failed, None, Some(e.clone()))),
+    }
+    result
+}
+
+/// 手动刷新单个账号：发布配额变化事件，成功后清除该账号的退避状态并执行配额预警
+pub async fn refresh_account(
+    target: &'static dyn Platform,
+    account_id: String,
+) -> Result<(), String> {
+    let result = target.refresh_account(account_id.clone()).await;
+    quota_events::publish_logged(target);
+    result?;
+    if let Err(e) = refresh_executor::clear_backoff(target.id(), &[account_id]) {
+        logger::log_warn(&format!("[RefreshScheduler] 清除刷新退避状态失败: {}", e));
+    }
+    platform::run_quota_alert_logged(target, "刷新后");
+    Ok(())
+}
+
+async fn run_scheduled(target: &'static dyn Platform) {
+    match refresh_platform(target, RefreshTrigger::Scheduled).await {
+        Ok(report) => {
+            logger::log_info(&format!(
+                "[RefreshScheduler] {} 定时刷新完成: 成功 {}，失败 {}，退避中 {}",
+                target.display_name(),
+                report.refreshed,
+                report.failed,
+                report.backing_off
+            ));
+            websocket::broadcast_data_changed("scheduled_refresh");
+            if let Some(app_handle) = crate::get_app_handle() {
+                if let Err(e) = tray::update_tray_menu(app_handle) {
+                    logger::log_warn(&format!("[RefreshScheduler] 更新托盘菜单失败: {}", e));
+                }
+            }
+        }
+        Err(e) => logger::log_warn(&format!(
+            "[RefreshScheduler] {} 定时刷新失败: {}",
+            target.display_name(),
+            e
+        )),
+    }
+}
+
+/// 取出到期平台并先行顺延其计划（防止刷新耗时超过等待时间时被重复触发），返回到期平台与下一次等待时间
+fn take_due(now: i64) -> (Vec<&'static str>, Duration) {
+    let Ok(mut guard) = state().lock() else {
+        return (Vec::new(), IDLE_WAIT);
+    };
+    let SchedulerState { schedules, running } = &mut *guard;
+    let mut due = Vec::new();
+    for (platform_id, schedule) in schedules.iter_mut() {
+        if schedule.next_run_at <= now && !running.contains(platform_id) {
+            schedule.next_run_at = next_run_after(now, schedule.interval_minutes, 0);
+            due.push(*platform_id);
+        }
+    }
+    let wait = schedules
+        .values()
+        .map(|schedule| (schedule.next_run_at - now).max(1) as u64)
+        .min()
+        .map(Duration::from_secs)
+        .unwrap_or(IDLE_WAIT);
+    (due, wait)
+}
+
+pub fn start() {
+    if STARTED.set(()).is_err() {
+        return;
+    }
+    reschedule();
+    tauri::async_runtime::spawn(async {
+        loop {
+            let (due, wait) = take_due(now_ts());
+            for platform_id in due {
+                if let Ok(target) = platform::get(platform_id) {
+                    tauri::async_runtime::spawn(run_scheduled(target));
+                }
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = wake().notified() => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_reschedules_only_changed_platforms() {
+        let mut schedules = BTreeMap::new();
+        sync_schedules(&mut schedules, &[("codex", 10), ("kiro", -1)], 1_000, |_| 5);
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules["codex"].next_run_at, 1_000 + 600 + 5);
+
+        sync_schedules(&mut schedules, &[("codex", 10), ("kiro", 30)], 2_000, |_| 0);
+        assert_eq!(schedules["codex"].next_run_at, 1_605);
+        assert_eq!(schedules["kiro"].next_run_at, 2_000 + 1_800);
+
+        sync_schedules(&mut schedules, &[("codex", 5), ("kiro", 0)], 3_000, |_| 0);
+        assert_eq!(schedules["codex"].next_run_at, 3_000 + 300);
+        assert!(!schedules.contains_key("kiro"));
+    }
+
+    #[test]
+    fn jitter_is_bounded_by_interval_and_cap() {
+        assert_eq!(max_jitter_seconds(5), 30);
+        assert_eq!(max_jitter_seconds(60), MAX_JITTER_SECONDS);
+        for _ in 0..100 {
+            assert!((0..=30).contains(&random_jitter(5)));
+        }
+    }
+}
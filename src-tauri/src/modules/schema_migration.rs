@@ -0,0 +1,439 @@
+//! 持久化文件格式版本迁移
+//!
+//! 每种 JSON 文件用一个 `SchemaSpec` 描述：版本号字段、当前程序支持的版本、以及按顺序排列的升级步骤。
+//! - 读取时：旧版本文件先备份为 `<name>.v<旧版本>.bak`，再逐步升级并写回
+//! - 写入前：磁盘上的文件版本高于当前程序支持的版本时拒绝写入，避免旧版本程序降级覆盖新数据
+//! - 每个文件最近一次读取到的版本记录在 `schema_versions.json`，便于排查
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::modules::{self, logger};
+use crate::utils::{atomic_file, file_lock};
+
+const REGISTRY_FILE: &str = "schema_versions.json";
+
+static REGISTRY_CACHE: std::sync::LazyLock<Mutex<Option<SchemaRegistry>>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+
+/// 版本号在文件中的写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionEncoding {
+    /// `"2.0"` 形式的字符串
+    Dotted,
+    /// 整数
+    Integer,
+}
+
+/// 单个升级步骤：把 `from` 版本的数据升级到 `from + 1`
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    pub apply: fn(&mut Value) -> Result<(), String>,
+}
+
+/// 一种持久化文件格式的描述
+pub struct SchemaSpec {
+    /// 文件标识（同时作为 schema_versions.json 中的键）
+    pub name: &'static str,
+    pub version_field: &'static str,
+    pub encoding: VersionEncoding,
+    /// 当前程序写出的版本
+    pub current: u32,
+    /// 版本号缺失（或为 0）时视为的版本
+    pub baseline: u32,
+    /// 按 `from` 升序排列，需覆盖 baseline..current 的每一步
+    pub migrations: &'static [Migration],
+}
+
+/// 单个文件的版本记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SchemaRecord {
+    /// 磁盘上的版本（迁移完成后的版本）
+    pub version: u32,
+    /// 当前程序支持的版本
+    pub supported: u32,
+    /// 最近一次迁移前的版本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migrated_from: Option<u32>,
+    pub app_version: String,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SchemaRegistry {
+    #[serde(default)]
+    files: BTreeMap<String, SchemaRecord>,
+}
+
+/// 单个文件的迁移结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationOutcome {
+    pub from: u32,
+    pub to: u32,
+    pub applied: Vec<&'static str>,
+}
+
+impl SchemaSpec {
+    fn encode(&self, version: u32) -> Value {
+        match self.encoding {
+            VersionEncoding::Dotted => Value::String(format!("{}.0", version)),
+            VersionEncoding::Integer => Value::from(version),
+        }
+    }
+}
+
+/// 读取数据中的版本号
+pub fn detect_version(spec: &SchemaSpec, value: &Value) -> Result<u32, String> {
+    let raw = match value.get(spec.version_field) {
+        None | Some(Value::Null) => return Ok(spec.baseline),
+        Some(raw) => raw,
+    };
+
+    let version = match raw {
+        Value::Number(number) => number.as_u64().map(|v| v as u32),
+        Value::String(text) => text
+            .trim()
+            .split('.')
+            .next()
+            .and_then(|major| major.parse::<u32>().ok()),
+        _ => None,
+    }
+    .ok_or_else(|| format!("{} 的版本号无法识别: {}", spec.name, raw))?;
+
+    Ok(if version == 0 { spec.baseline } else { version })
+}
+
+/// 在内存中把数据升级到当前版本；数据版本高于当前程序时返回错误
+pub fn upgrade(spec: &SchemaSpec, value: &mut Value) -> Result<MigrationOutcome, String> {
+    let from = detect_version(spec, value)?;
+    if from > spec.current {
+        return Err(newer_version_error(spec, from));
+    }
+
+    let mut version = from;
+    let mut applied = Vec::new();
+    while version < spec.current {
+        let step = spec
+            .migrations
+            .iter()
+            .find(|step| step.from == version)
+            .ok_or_else(|| format!("{} 缺少 v{} 的升级步骤", spec.name, version))?;
+        (step.apply)(value).map_err(|e| {
+            format!(
+                "{} 升级失败 (v{} -> v{}): {}",
+                spec.name,
+                version,
+                version + 1,
+                e
+            )
+        })?;
+        applied.push(step.description);
+        version += 1;
+    }
+
+    if !applied.is_empty() {
+        let object = value
+            .as_object_mut()
+            .ok_or_else(|| format!("{} 不是 JSON 对象", spec.name))?;
+        object.insert(spec.version_field.to_string(), spec.encode(spec.current));
+    }
+
+    Ok(MigrationOutcome {
+        from,
+        to: version,
+        applied,
+    })
+}
+
+fn newer_version_error(spec: &SchemaSpec, version: u32) -> String {
+    format!(
+        "{} 的数据版本 v{} 高于当前程序支持的 v{}，请升级 Cockpit Tools 后再修改",
+        spec.name, version, spec.current
+    )
+}
+
+/// 迁移前备份路径：`<path>.v<version>.bak`
+pub fn premigration_backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    path.with_file_name(name)
+}
+
+/// 升级文件内容：需要迁移时先备份原文件，再写回升级后的内容。
+/// 返回 `None` 表示已是当前版本，无需改动。
+pub fn migrate_file(
+    spec: &SchemaSpec,
+    path: &Path,
+    content: &str,
+) -> Result<(Option<String>, MigrationOutcome), String> {
+    let mut value: Value =
+        serde_json::from_str(content).map_err(|e| format!("解析 {} 失败: {}", spec.name, e))?;
+    let outcome = upgrade(spec, &mut value)?;
+    if outcome.applied.is_empty() {
+        return Ok((None, outcome));
+    }
+
+    let backup = premigration_backup_path(path, outcome.from);
+    atomic_file::replace(&backup, content)
+        .map_err(|e| format!("迁移前备份 {} 失败: {}", spec.name, e))?;
+
+    let migrated =
+        serde_json::to_string_pretty(&value).map_err(|e| format!("序列化失败: {}", e))?;
+    atomic_file::write(path, &migrated).map_err(|e| format!("写回 {} 失败: {}", spec.name, e))?;
+
+    Ok((Some(migrated), outcome))
+}
+
+/// 读取文件后调用：按需迁移并记录版本，返回可直接反序列化的内容。
+/// 迁移失败时保留原内容并记录日志，交由调用方原有的解析/恢复逻辑处理。
+pub fn upgrade_on_load(spec: &SchemaSpec, path: &Path, content: String) -> String {
+    if content.trim().is_empty() {
+        return content;
+    }
+
+    let on_disk = match serde_json::from_str::<Value>(&content) {
+        Ok(value) => match detect_version(spec, &value) {
+            Ok(version) => version,
+            Err(e) => {
+                logger::log_warn(&format!("[Schema] {}", e));
+                return content;
+            }
+        },
+        Err(_) => return content,
+    };
+
+    if on_disk > spec.current {
+        logger::log_warn(&format!(
+            "[Schema] {} 版本 v{} 高于当前支持的 v{}，以只读方式使用",
+            spec.name, on_disk, spec.current
+        ));
+        record_version(spec, on_disk, None);
+        return content;
+    }
+
+    if on_disk == spec.current {
+        record_version(spec, on_disk, None);
+        return content;
+    }
+
+    let _lock = match file_lock::lock_exclusive(path, "schema_migration") {
+        Ok(lock) => lock,
+        Err(e) => {
+            logger::log_warn(&format!("[Schema] {} 迁移跳过: {}", spec.name, e));
+            return content;
+        }
+    };
+    // 加锁后重新读取，避免覆盖其他进程在此期间写入的内容
+    let content = fs::read_to_string(path).unwrap_or(content);
+
+    match migrate_file(spec, path, &content) {
+        Ok((None, outcome)) => {
+            record_version(spec, outcome.to, None);
+            content
+        }
+        Ok((Some(migrated), outcome)) => {
+            logger::log_info(&format!(
+                "[Schema] {} 已从 v{} 升级到 v{}: {}",
+                spec.name,
+                outcome.from,
+                outcome.to,
+                outcome.applied.join("; ")
+            ));
+            record_version(spec, outcome.to, Some(outcome.from));
+            migrated
+        }
+        Err(e) => {
+            logger::log_error(&format!("[Schema] {}", e));
+            content
+        }
+    }
+}
+
+/// 写入前调用：磁盘上的文件版本高于当前程序支持的版本时拒绝写入
+pub fn ensure_writable(spec: &SchemaSpec, path: &Path) -> Result<(), String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Ok(()),
+    };
+    let Ok(value) = serde_json::from_str::<Value>(&content) else {
+        return Ok(());
+    };
+    match detect_version(spec, &value) {
+        Ok(version) if version > spec.current => Err(newer_version_error(spec, version)),
+        _ => Ok(()),
+    }
+}
+
+fn registry_path() -> Result<PathBuf, String> {
+    Ok(modules::account::get_data_dir()?.join(REGISTRY_FILE))
+}
+
+fn read_registry(path: &Path) -> SchemaRegistry {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn record_version(spec: &SchemaSpec, version: u32, migrated_from: Option<u32>) {
+    if let Err(e) = try_record_version(spec, version, migrated_from) {
+        logger::log_warn(&format!("[Schema] 记录 {} 版本失败: {}", spec.name, e));
+    }
+}
+
+fn try_record_version(
+    spec: &SchemaSpec,
+    version: u32,
+    migrated_from: Option<u32>,
+) -> Result<(), String> {
+    let mut cache = REGISTRY_CACHE
+        .lock()
+        .map_err(|_| "获取版本记录锁失败".to_string())?;
+
+    let unchanged = |registry: &SchemaRegistry| {
+        migrated_from.is_none()
+            && registry
+                .files
+                .get(spec.name)
+                .is_some_and(|record| record.version == version && record.supported == spec.current)
+    };
+    if cache.as_ref().is_some_and(unchanged) {
+        return Ok(());
+    }
+
+    let path = registry_path()?;
+    let _lock = file_lock::lock_exclusive(&path, "schema_record_version")?;
+    let mut registry = read_registry(&path);
+    if !unchanged(&registry) {
+        let previous_from = registry
+            .files
+            .get(spec.name)
+            .and_then(|record| record.migrated_from);
+        registry.files.insert(
+            spec.name.to_string(),
+            SchemaRecord {
+                version,
+                supported: spec.current,
+                migrated_from: migrated_from.or(previous_from),
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                updated_at: chrono::Utc::now().timestamp(),
+            },
+        );
+        let content =
+            serde_json::to_string_pretty(&registry).map_err(|e| format!("序列化失败: {}", e))?;
+        atomic_file::write(&path, content).map_err(|e| format!("写入版本记录失败: {}", e))?;
+    }
+    *cache = Some(registry);
+    Ok(())
+}
+
+/// 各数据文件最近一次读取到的格式版本
+pub fn list_records() -> Result<BTreeMap<String, SchemaRecord>, String> {
+    Ok(read_registry(&registry_path()?).files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn add_items(value: &mut Value) -> Result<(), String> {
+        value["items"] = json!([]);
+        Ok(())
+    }
+
+    fn rename_title(value: &mut Value) -> Result<(), String> {
+        let title = value
+            .as_object_mut()
+            .and_then(|object| object.remove("title"))
+            .ok_or("缺少 title")?;
+        value["name"] = title;
+        Ok(())
+    }
+
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            from: 1,
+            description: "补齐 items",
+            apply: add_items,
+        },
+        Migration {
+            from: 2,
+            description: "title 改名为 name",
+            apply: rename_title,
+        },
+    ];
+
+    const TEST_SPEC: SchemaSpec = SchemaSpec {
+        name: "test.json",
+        version_field: "version",
+        encoding: VersionEncoding::Dotted,
+        current: 3,
+        baseline: 1,
+        migrations: TEST_MIGRATIONS,
+    };
+
+    #[test]
+    fn detect_version_handles_dotted_integer_and_missing() {
+        assert_eq!(
+            detect_version(&TEST_SPEC, &json!({"version": "2.0"})),
+            Ok(2)
+        );
+        assert_eq!(detect_version(&TEST_SPEC, &json!({"version": 3})), Ok(3));
+        assert_eq!(detect_version(&TEST_SPEC, &json!({})), Ok(1));
+        assert_eq!(detect_version(&TEST_SPEC, &json!({"version": 0})), Ok(1));
+        assert!(detect_version(&TEST_SPEC, &json!({"version": "abc"})).is_err());
+    }
+
+    #[test]
+    fn upgrade_runs_steps_in_order_and_stamps_version() {
+        let mut value = json!({"title": "demo"});
+        let outcome = upgrade(&TEST_SPEC, &mut value).unwrap();
+
+        assert_eq!(outcome.from, 1);
+        assert_eq!(outcome.to, 3);
+        assert_eq!(outcome.applied, vec!["补齐 items", "title 改名为 name"]);
+        assert_eq!(
+            value,
+            json!({"version": "3.0", "items": [], "name": "demo"})
+        );
+
+        let again = upgrade(&TEST_SPEC, &mut value).unwrap();
+        assert!(again.applied.is_empty());
+    }
+
+    #[test]
+    fn upgrade_refuses_newer_data() {
+        let mut value = json!({"version": "4.0"});
+        assert!(upgrade(&TEST_SPEC, &mut value).is_err());
+        assert_eq!(value, json!({"version": "4.0"}));
+    }
+
+    #[test]
+    fn migrate_file_backs_up_original_and_guards_writes() {
+        let dir = std::env::temp_dir().join(format!("cockpit-schema-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.json");
+        let original = r#"{"version":"2.0","title":"demo"}"#;
+        fs::write(&path, original).unwrap();
+
+        let (migrated, outcome) = migrate_file(&TEST_SPEC, &path, original).unwrap();
+        assert_eq!(outcome.applied.len(), 1);
+        assert_eq!(
+            fs::read_to_string(premigration_backup_path(&path, 2)).unwrap(),
+            original
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), migrated.unwrap());
+        assert!(ensure_writable(&TEST_SPEC, &path).is_ok());
+
+        fs::write(&path, r#"{"version":"9.0"}"#).unwrap();
+        assert!(ensure_writable(&TEST_SPEC, &path).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
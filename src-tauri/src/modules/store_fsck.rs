@@ -0,0 +1,777 @@
+//! 账号存储一致性检查与修复（fsck）
+//!
+//! 逐个平台检查索引与账号记录是否一致：
+//!
+//! - 索引中的账号缺少账号文件 / 账号文件不在索引中 / 账号文件无法解析
+//! - 索引重复条目、`current_account_id` 指向已删除账号
+//! - 多个账号属于同一身份（重复账号）
+//! - 实例绑定了已删除的账号
+//!
+//! 修复时以账号记录为准重建索引（不再需要用户删除整个索引文件），无法解析的账号文件按
+//! `delete_corrupted_file` 的方式改名备份，重复账号交给平台已有的去重逻辑合并。
+//! 修复后会重新检查一次，只有确实消失的问题才标记为已修复。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::InstanceStore;
+use crate::modules::account_store::{self, StoreSpec};
+use crate::modules::{
+    account, backup, codex_account, codex_instance, cursor_account, cursor_instance,
+    github_copilot_account, github_copilot_instance, instance, kiro_account, kiro_instance, logger,
+    windsurf_account, windsurf_instance,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsckIssueKind {
+    /// 索引文件无法解析
+    CorruptedIndex,
+    /// 索引中的账号缺少账号文件
+    MissingAccountFile,
+    /// 账号文件不在索引中
+    OrphanAccountFile,
+    /// 账号文件无法解析
+    CorruptedAccountFile,
+    /// 索引中同一账号出现多次
+    DuplicateIndexEntry,
+    /// 多个账号属于同一身份
+    DuplicateIdentity,
+    /// current_account_id 指向不存在的账号
+    DanglingCurrentAccount,
+    /// 实例绑定了不存在的账号
+    InstanceBoundToMissingAccount,
+}
+
+impl FsckIssueKind {
+    /// 通过重建索引即可修复的问题
+    fn fixed_by_index_rebuild(self) -> bool {
+        matches!(
+            self,
+            Self::CorruptedIndex
+                | Self::MissingAccountFile
+                | Self::OrphanAccountFile
+                | Self::CorruptedAccountFile
+                | Self::DuplicateIndexEntry
+                | Self::DanglingCurrentAccount
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckIssue {
+    pub kind: FsckIssueKind,
+    pub account_ids: Vec<String>,
+    pub detail: String,
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformFsckReport {
+    pub platform: String,
+    pub index_entries: usize,
+    pub stored_accounts: usize,
+    pub issues: Vec<FsckIssue>,
+    /// 检查本身失败时的错误（如数据库无法打开）
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckReport {
+    pub repair: bool,
+    pub checked_at: i64,
+    pub platforms: Vec<PlatformFsckReport>,
+}
+
+/// 索引中与一致性相关的部分（各平台索引结构不同，这里按 JSON 读取）
+#[derive(Debug, Clone, Default)]
+pub struct IndexView {
+    pub account_ids: Vec<String>,
+    pub current_account_id: Option<String>,
+}
+
+struct StoredRecord {
+    id: String,
+    /// 账号无法解析时的错误
+    error: Option<String>,
+    identity: Option<String>,
+}
+
+struct InstanceBinding {
+    instance: String,
+    account_id: String,
+}
+
+struct InstanceStoreAccess {
+    load: fn() -> Result<InstanceStore, String>,
+    save: fn(&InstanceStore) -> Result<(), String>,
+}
+
+struct FsckAdapter {
+    spec: &'static StoreSpec,
+    /// 从账号 JSON 中提取身份标识，用于发现重复账号
+    identity: fn(&Value) -> Option<String>,
+    /// 以账号记录为准重建索引
+    rebuild_index: fn() -> Result<usize, String>,
+    /// 平台已有的重复账号合并逻辑；None 表示只报告不合并
+    merge_duplicates: Option<fn()>,
+    instances: InstanceStoreAccess,
+}
+
+fn adapters() -> [FsckAdapter; 6] {
+    [
+        FsckAdapter {
+            spec: &account::STORE_SPEC,
+            identity: |value| identity_of(value, &["/email"]),
+            rebuild_index: account::rebuild_account_index,
+            merge_duplicates: None,
+            instances: InstanceStoreAccess {
+                load: instance::load_instance_store,
+                save: instance::save_instance_store,
+            },
+        },
+        FsckAdapter {
+            spec: &codex_account::STORE_SPEC,
+            identity: |value| identity_of(value, &["/email", "/account_id", "/organization_id"]),
+            rebuild_index: codex_account::rebuild_account_index,
+            merge_duplicates: None,
+            instances: InstanceStoreAccess {
+                load: codex_instance::load_instance_store,
+                save: codex_instance::save_instance_store,
+            },
+        },
+        FsckAdapter {
+            spec: &github_copilot_account::STORE_SPEC,
+            identity: |value| {
+                value
+                    .get("github_id")
+                    .and_then(Value::as_u64)
+                    .filter(|id| *id > 0)
+                    .map(|id| id.to_string())
+            },
+            rebuild_index: github_copilot_account::rebuild_account_index,
+            merge_duplicates: None,
+            instances: InstanceStoreAccess {
+                load: github_copilot_instance::load_instance_store,
+                save: github_copilot_instance::save_instance_store,
+            },
+        },
+        FsckAdapter {
+            spec: &windsurf_account::STORE_SPEC,
+            // windsurf_api_key 是敏感字段，存储中为加密后的密文（每次加密都不同），只能按邮箱识别
+            identity: |value| {
+                identity_of(value, &["/github_email"])
+                    .or_else(|| identity_of(value, &["/windsurf_auth_status_raw/email"]))
+                    .map(|email| format!("email:{}", email))
+            },
+            rebuild_index: windsurf_account::rebuild_account_index,
+            // list_accounts 会先执行 deduplicate_accounts_by_identity
+            merge_duplicates: Some(|| {
+                windsurf_account::list_accounts();
+            }),
+            instances: InstanceStoreAccess {
+                load: windsurf_instance::load_instance_store,
+                save: windsurf_instance::save_instance_store,
+            },
+        },
+        FsckAdapter {
+            spec: &kiro_account::STORE_SPEC,
+            identity: |value| {
+                identity_of(value, &["/user_id"])
+                    .map(|user| format!("user:{}", user))
+                    .or_else(|| {
+                        identity_of(value, &["/email"]).map(|email| format!("email:{}", email))
+                    })
+            },
+            rebuild_index: kiro_account::rebuild_account_index,
+            // list_accounts 会通过 normalize_account_index 合并重复账号并写回索引
+            merge_duplicates: Some(|| {
+                kiro_account::list_accounts();
+            }),
+            instances: InstanceStoreAccess {
+                load: kiro_instance::load_instance_store,
+                save: kiro_instance::save_instance_store,
+            },
+        },
+        FsckAdapter {
+            spec: &cursor_account::STORE_SPEC,
+            identity: |value| {
+                identity_of(value, &["/user_id"])
+                    .map(|user| format!("user:{}", user))
+                    .or_else(|| {
+                        identity_of(value, &["/email"]).map(|email| format!("email:{}", email))
+                    })
+            },
+            rebuild_index: cursor_account::rebuild_account_index,
+            merge_duplicates: None,
+            instances: InstanceStoreAccess {
+                load: cursor_instance::load_instance_store,
+                save: cursor_instance::save_instance_store,
+            },
+        },
+    ]
+}
+
+/// 取多个字段（小写、去空白后）拼接为身份标识；全部为空时返回 None
+fn identity_of(value: &Value, pointers: &[&str]) -> Option<String> {
+    let parts: Vec<String> = pointers
+        .iter()
+        .map(|pointer| {
+            value
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .map(|s| s.trim().to_lowercase())
+                .unwrap_or_default()
+        })
+        .collect();
+    if parts.iter().all(String::is_empty) {
+        None
+    } else {
+        Some(parts.join("|"))
+    }
+}
+
+// ============================================================================
+// 读取存储（与各平台的存储方式无关）
+// ============================================================================
+
+fn index_view_from_value(value: &Value) -> IndexView {
+    IndexView {
+        account_ids: value
+            .get("accounts")
+            .and_then(Value::as_array)
+            .map(|accounts| {
+                accounts
+                    .iter()
+                    .filter_map(|summary| summary.get("id").and_then(Value::as_str))
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        current_account_id: value
+            .get("current_account_id")
+            .and_then(Value::as_str)
+            .map(String::from),
+    }
+}
+
+/// 读取平台索引；索引文件损坏时返回 `file_corrupted` 错误
+pub fn read_index(spec: &StoreSpec) -> Result<IndexView, String> {
+    if account_store::is_active() {
+        return Ok(account_store::load_index::<Value>(spec)?
+            .map(|value| index_view_from_value(&value))
+            .unwrap_or_default());
+    }
+
+    let path = (spec.base_dir)()?.join(spec.index_file);
+    if !path.exists() {
+        return Ok(IndexView::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取账号索引失败: {}", e))?;
+    if content.trim().is_empty() {
+        return Ok(IndexView::default());
+    }
+    serde_json::from_str::<Value>(&content)
+        .map(|value| index_view_from_value(&value))
+        .map_err(|e| {
+            crate::error::file_corrupted_error(
+                spec.index_file,
+                &path.to_string_lossy(),
+                &e.to_string(),
+            )
+        })
+}
+
+fn account_file_path(spec: &StoreSpec, account_id: &str) -> Result<PathBuf, String> {
+    Ok((spec.base_dir)()?
+        .join(spec.accounts_dir)
+        .join(format!("{}.json", account_id)))
+}
+
+/// 列出实际存储的账号 ID（文件存储为账号目录下的 `*.json`，数据库存储为 accounts 表）
+pub fn list_stored_ids(spec: &StoreSpec) -> Result<Vec<String>, String> {
+    if account_store::is_active() {
+        return account_store::list_account_ids(spec);
+    }
+
+    let accounts_dir = (spec.base_dir)()?.join(spec.accounts_dir);
+    if !accounts_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(&accounts_dir).map_err(|e| format!("读取账号目录失败: {}", e))?;
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(String::from))
+        .filter(|stem| !stem.starts_with('.'))
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+fn load_record_value(spec: &StoreSpec, account_id: &str) -> Result<Value, String> {
+    let value = if account_store::is_active() {
+        account_store::load_record::<Value>(spec, account_id)?
+            .ok_or_else(|| format!("账号不存在: {}", account_id))?
+    } else {
+        let content = fs::read_to_string(account_file_path(spec, account_id)?)
+            .map_err(|e| format!("读取账号文件失败: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("解析账号文件失败: {}", e))?
+    };
+    if value.is_object() {
+        Ok(value)
+    } else {
+        Err("账号数据不是 JSON 对象".to_string())
+    }
+}
+
+/// 按原索引顺序排列账号，未出现在索引中的账号按创建时间追加在末尾（同时去除重复 ID）
+pub fn order_by_index<T>(
+    accounts: Vec<T>,
+    index_ids: &[String],
+    id_of: impl Fn(&T) -> &str,
+    created_at_of: impl Fn(&T) -> i64,
+) -> Vec<T> {
+    let position: HashMap<&str, usize> = index_ids
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(pos, id)| (id.as_str(), pos))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut accounts: Vec<T> = accounts
+        .into_iter()
+        .filter(|account| seen.insert(id_of(account).to_string()))
+        .collect();
+    accounts.sort_by(|a, b| {
+        let pa = position.get(id_of(a)).copied().unwrap_or(usize::MAX);
+        let pb = position.get(id_of(b)).copied().unwrap_or(usize::MAX);
+        pa.cmp(&pb)
+            .then(created_at_of(a).cmp(&created_at_of(b)))
+            .then(id_of(a).cmp(id_of(b)))
+    });
+    accounts
+}
+
+/// 修正后的当前账号：原值仍有效则保留，否则取第一个账号
+pub fn repair_current_account_id(current: Option<String>, ids: &[String]) -> Option<String> {
+    current
+        .filter(|id| ids.contains(id))
+        .or_else(|| ids.first().cloned())
+}
+
+/// 将损坏的文件改名为 `<name>.corrupted.<timestamp>`，返回备份路径
+pub fn quarantine_file(path: &Path) -> Result<PathBuf, String> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".corrupted.{}", chrono::Utc::now().timestamp()));
+    let backup_path = path.with_file_name(name);
+    fs::rename(path, &backup_path).map_err(|e| format!("备份损坏文件失败: {}", e))?;
+    Ok(backup_path)
+}
+
+// ============================================================================
+// 检查
+// ============================================================================
+
+fn collect_instance_bindings(access: &InstanceStoreAccess) -> Result<Vec<InstanceBinding>, String> {
+    let store = (access.load)()?;
+    let mut bindings = Vec::new();
+    if let Some(account_id) = store.default_settings.bind_account_id.as_ref() {
+        bindings.push(InstanceBinding {
+            instance: "default".to_string(),
+            account_id: account_id.clone(),
+        });
+    }
+    for profile in &store.instances {
+        if let Some(account_id) = profile.bind_account_id.as_ref() {
+            bindings.push(InstanceBinding {
+                instance: profile.name.clone(),
+                account_id: account_id.clone(),
+            });
+        }
+    }
+    Ok(bindings)
+}
+
+fn analyze(
+    index: &Result<IndexView, String>,
+    stored: &[StoredRecord],
+    bindings: &[InstanceBinding],
+) -> Vec<FsckIssue> {
+    let mut issues = Vec::new();
+    let mut push = |kind, account_ids: Vec<String>, detail: String| {
+        issues.push(FsckIssue {
+            kind,
+            account_ids,
+            detail,
+            repaired: false,
+        })
+    };
+
+    let stored_ids: HashSet<&str> = stored.iter().map(|r| r.id.as_str()).collect();
+    let loadable: HashSet<&str> = stored
+        .iter()
+        .filter(|r| r.error.is_none())
+        .map(|r| r.id.as_str())
+        .collect();
+
+    let empty = IndexView::default();
+    let index_view = match index {
+        Ok(view) => view,
+        Err(e) => {
+            push(FsckIssueKind::CorruptedIndex, Vec::new(), e.clone());
+            &empty
+        }
+    };
+
+    let mut seen = HashSet::new();
+    let mut reported_duplicates = HashSet::new();
+    for id in &index_view.account_ids {
+        if !seen.insert(id.as_str()) {
+            if reported_duplicates.insert(id.as_str()) {
+                push(
+                    FsckIssueKind::DuplicateIndexEntry,
+                    vec![id.clone()],
+                    "索引中重复出现".to_string(),
+                );
+            }
+            continue;
+        }
+        if !stored_ids.contains(id.as_str()) {
+            push(
+                FsckIssueKind::MissingAccountFile,
+                vec![id.clone()],
+                "索引中的账号缺少账号数据".to_string(),
+            );
+        }
+    }
+
+    let mut identities: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for record in stored {
+        if let Some(error) = &record.error {
+            push(
+                FsckIssueKind::CorruptedAccountFile,
+                vec![record.id.clone()],
+                error.clone(),
+            );
+            continue;
+        }
+        if !seen.contains(record.id.as_str()) {
+            push(
+                FsckIssueKind::OrphanAccountFile,
+                vec![record.id.clone()],
+                "账号数据不在索引中".to_string(),
+            );
+        }
+        if let Some(identity) = record.identity.as_deref() {
+            identities
+                .entry(identity)
+                .or_default()
+                .push(record.id.clone());
+        }
+    }
+    for ids in identities.into_values().filter(|ids| ids.len() > 1) {
+        push(
+            FsckIssueKind::DuplicateIdentity,
+            ids,
+            "多个账号属于同一身份".to_string(),
+        );
+    }
+
+    if let Some(current) = &index_view.current_account_id {
+        if !seen.contains(current.as_str()) || !loadable.contains(current.as_str()) {
+            push(
+                FsckIssueKind::DanglingCurrentAccount,
+                vec![current.clone()],
+                "当前账号已不存在".to_string(),
+            );
+        }
+    }
+
+    for binding in bindings {
+        if !loadable.contains(binding.account_id.as_str()) {
+            push(
+                FsckIssueKind::InstanceBoundToMissingAccount,
+                vec![binding.account_id.clone()],
+                format!("实例「{}」绑定的账号已不存在", binding.instance),
+            );
+        }
+    }
+
+    issues
+}
+
+struct Inspection {
+    index_entries: usize,
+    stored: Vec<StoredRecord>,
+    issues: Vec<FsckIssue>,
+}
+
+fn inspect(adapter: &FsckAdapter) -> Result<Inspection, String> {
+    let index = read_index(adapter.spec);
+    let stored: Vec<StoredRecord> = list_stored_ids(adapter.spec)?
+        .into_iter()
+        .map(|id| match load_record_value(adapter.spec, &id) {
+            Ok(value) => StoredRecord {
+                identity: (adapter.identity)(&value),
+                id,
+                error: None,
+            },
+            Err(e) => StoredRecord {
+                id,
+                error: Some(e),
+                identity: None,
+            },
+        })
+        .collect();
+    let bindings = collect_instance_bindings(&adapter.instances)?;
+    let issues = analyze(&index, &stored, &bindings);
+    Ok(Inspection {
+        index_entries: index.as_ref().map(|v| v.account_ids.len()).unwrap_or(0),
+        stored,
+        issues,
+    })
+}
+
+// ============================================================================
+// 修复
+// ============================================================================
+
+fn clear_missing_bindings(adapter: &FsckAdapter) -> Result<usize, String> {
+    let valid: HashSet<String> = list_stored_ids(adapter.spec)?
+        .into_iter()
+        .filter(|id| load_record_value(adapter.spec, id).is_ok())
+        .collect();
+    let mut store = (adapter.instances.load)()?;
+    let mut cleared = 0;
+    let mut clear = |binding: &mut Option<String>| {
+        if binding.as_ref().is_some_and(|id| !valid.contains(id)) {
+            *binding = None;
+            cleared += 1;
+        }
+    };
+    clear(&mut store.default_settings.bind_account_id);
+    for profile in &mut store.instances {
+        clear(&mut profile.bind_account_id);
+    }
+    if cleared > 0 {
+        (adapter.instances.save)(&store)?;
+    }
+    Ok(cleared)
+}
+
+fn repair_platform(adapter: &FsckAdapter, inspection: &Inspection) -> Result<(), String> {
+    let platform = adapter.spec.platform;
+    let kinds: HashSet<FsckIssueKind> = inspection.issues.iter().map(|i| i.kind).collect();
+
+    if kinds.contains(&FsckIssueKind::CorruptedAccountFile) && !account_store::is_active() {
+        for record in inspection.stored.iter().filter(|r| r.error.is_some()) {
+            let path = account_file_path(adapter.spec, &record.id)?;
+            let backup_path = quarantine_file(&path)?;
+            logger::log_warn(&format!(
+                "[Fsck] 已备份无法解析的账号文件: platform={}, {} -> {}",
+                platform,
+                path.display(),
+                backup_path.display()
+            ));
+        }
+    }
+
+    if kinds.iter().any(|kind| kind.fixed_by_index_rebuild()) {
+        let count = (adapter.rebuild_index)()?;
+        logger::log_info(&format!(
+            "[Fsck] 已重建账号索引: platform={}, accounts={}",
+            platform, count
+        ));
+    }
+
+    if kinds.contains(&FsckIssueKind::DuplicateIdentity) {
+        if let Some(merge) = adapter.merge_duplicates {
+            merge();
+        }
+    }
+
+    if kinds.contains(&FsckIssueKind::InstanceBoundToMissingAccount) {
+        let cleared = clear_missing_bindings(adapter)?;
+        logger::log_info(&format!(
+            "[Fsck] 已解除失效的实例绑定: platform={}, count={}",
+            platform, cleared
+        ));
+    }
+    Ok(())
+}
+
+fn issue_key(issue: &FsckIssue) -> (FsckIssueKind, Vec<String>) {
+    (issue.kind, issue.account_ids.clone())
+}
+
+fn check_platform(adapter: &FsckAdapter, repair: bool) -> Result<PlatformFsckReport, String> {
+    let inspection = inspect(adapter)?;
+    let mut report = PlatformFsckReport {
+        platform: adapter.spec.platform.to_string(),
+        index_entries: inspection.index_entries,
+        stored_accounts: inspection.stored.len(),
+        issues: inspection.issues.clone(),
+        error: None,
+    };
+    if !repair || report.issues.is_empty() {
+        return Ok(report);
+    }
+
+    if let Err(e) = repair_platform(adapter, &inspection) {
+        report.error = Some(e);
+    }
+    let remaining: HashSet<_> = inspect(adapter)?.issues.iter().map(issue_key).collect();
+    for issue in &mut report.issues {
+        issue.repaired = !remaining.contains(&issue_key(issue));
+    }
+    Ok(report)
+}
+
+/// 检查全部平台的账号存储；`repair` 为 true 时尝试修复（修复前自动快照数据目录）
+pub fn run(repair: bool) -> FsckReport {
+    if repair {
+        backup::snapshot_before("fsck_repair");
+    }
+
+    let platforms: Vec<PlatformFsckReport> = adapters()
+        .iter()
+        .map(|adapter| {
+            check_platform(adapter, repair).unwrap_or_else(|e| PlatformFsckReport {
+                platform: adapter.spec.platform.to_string(),
+                index_entries: 0,
+                stored_accounts: 0,
+                issues: Vec::new(),
+                error: Some(e),
+            })
+        })
+        .collect();
+
+    let found: usize = platforms.iter().map(|p| p.issues.len()).sum();
+    let repaired: usize = platforms
+        .iter()
+        .flat_map(|p| p.issues.iter())
+        .filter(|issue| issue.repaired)
+        .count();
+    logger::log_info(&format!(
+        "[Fsck] 检查完成: repair={}, issues={}, repaired={}",
+        repair, found, repaired
+    ));
+
+    FsckReport {
+        repair,
+        checked_at: chrono::Utc::now().timestamp(),
+        platforms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, identity: Option<&str>) -> StoredRecord {
+        StoredRecord {
+            id: id.to_string(),
+            error: None,
+            identity: identity.map(String::from),
+        }
+    }
+
+    fn kinds(issues: &[FsckIssue]) -> Vec<(FsckIssueKind, Vec<String>)> {
+        issues.iter().map(issue_key).collect()
+    }
+
+    #[test]
+    fn analyze_reports_index_and_record_mismatches() {
+        let index = Ok(IndexView {
+            account_ids: vec!["a".into(), "b".into(), "a".into(), "gone".into()],
+            current_account_id: Some("gone".into()),
+        });
+        let stored = vec![
+            record("a", Some("x@example.com")),
+            record("b", Some("x@example.com")),
+            record("orphan", None),
+            StoredRecord {
+                id: "broken".into(),
+                error: Some("解析账号文件失败".into()),
+                identity: None,
+            },
+        ];
+        let bindings = vec![InstanceBinding {
+            instance: "work".into(),
+            account_id: "gone".into(),
+        }];
+
+        let issues = kinds(&analyze(&index, &stored, &bindings));
+        let ids = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(issues.contains(&(FsckIssueKind::DuplicateIndexEntry, ids(&["a"]))));
+        assert!(issues.contains(&(FsckIssueKind::MissingAccountFile, ids(&["gone"]))));
+        assert!(issues.contains(&(FsckIssueKind::OrphanAccountFile, ids(&["orphan"]))));
+        assert!(issues.contains(&(FsckIssueKind::CorruptedAccountFile, ids(&["broken"]))));
+        assert!(issues.contains(&(FsckIssueKind::DuplicateIdentity, ids(&["a", "b"]))));
+        assert!(issues.contains(&(FsckIssueKind::DanglingCurrentAccount, ids(&["gone"]))));
+        assert!(issues.contains(&(FsckIssueKind::InstanceBoundToMissingAccount, ids(&["gone"]))));
+        assert_eq!(issues.len(), 7);
+    }
+
+    #[test]
+    fn analyze_reports_corrupted_index() {
+        let index = Err("broken".to_string());
+        let issues = analyze(&index, &[record("a", None)], &[]);
+        assert_eq!(issues[0].kind, FsckIssueKind::CorruptedIndex);
+        assert_eq!(issues[1].kind, FsckIssueKind::OrphanAccountFile);
+    }
+
+    #[test]
+    fn order_by_index_keeps_order_and_appends_orphans() {
+        let accounts = vec![("c", 3), ("a", 1), ("orphan", 0), ("b", 2), ("a", 1)];
+        let index_ids: Vec<String> = vec!["b".into(), "a".into(), "c".into()];
+        let ordered = order_by_index(accounts, &index_ids, |a| a.0, |a| a.1);
+        let ids: Vec<&str> = ordered.iter().map(|a| a.0).collect();
+        assert_eq!(ids, vec!["b", "a", "c", "orphan"]);
+    }
+
+    #[test]
+    fn repair_current_account_id_falls_back_to_first() {
+        let ids = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(
+            repair_current_account_id(Some("b".into()), &ids),
+            Some("b".into())
+        );
+        assert_eq!(
+            repair_current_account_id(Some("gone".into()), &ids),
+            Some("a".into())
+        );
+        assert_eq!(repair_current_account_id(None, &[]), None);
+    }
+
+    #[test]
+    fn windsurf_identity_ignores_sealed_api_key() {
+        let adapter = adapters()
+            .into_iter()
+            .find(|adapter| adapter.spec.platform == windsurf_account::STORE_SPEC.platform)
+            .unwrap();
+        let first = serde_json::json!({
+            "windsurf_api_key": "enc:v1:nonce-a",
+            "github_email": "User@Example.com"
+        });
+        let second = serde_json::json!({
+            "windsurf_api_key": "enc:v1:nonce-b",
+            "windsurf_auth_status_raw": { "email": "user@example.com " }
+        });
+        assert_eq!((adapter.identity)(&first), (adapter.identity)(&second));
+        assert_eq!(
+            (adapter.identity)(&first),
+            Some("email:user@example.com".to_string())
+        );
+        assert_eq!(
+            (adapter.identity)(&serde_json::json!({ "windsurf_api_key": "enc:v1:x" })),
+            None
+        );
+    }
+}
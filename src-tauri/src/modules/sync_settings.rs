@@ -16,6 +16,7 @@ use std::fs;
 use std::path::PathBuf;
 
 use super::config::get_shared_dir;
+use crate::utils::{atomic_file, file_lock};
 
 /// 同步配置文件名
 const SYNC_SETTINGS_FILE: &str = "sync_settings.json";
@@ -82,6 +83,7 @@ pub fn read_sync_settings() -> SyncSettings {
 /// 保存同步配置文件
 fn save_sync_settings(settings: &SyncSettings) -> Result<(), String> {
     let path = get_sync_settings_path();
+    let _lock = file_lock::lock_exclusive(&path, "save_sync_settings")?;
 
     // 确保目录存在
     if let Some(parent) = path.parent() {
@@ -91,7 +93,7 @@ fn save_sync_settings(settings: &SyncSettings) -> Result<(), String> {
     let content =
         serde_json::to_string_pretty(settings).map_err(|e| format!("序列化失败: {}", e))?;
 
-    fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
 
     Ok(())
 }
@@ -99,6 +101,13 @@ fn save_sync_settings(settings: &SyncSettings) -> Result<(), String> {
 /// 写入单个同步配置项
 /// 用于离线时保存配置，等对方启动时读取
 pub fn write_sync_setting(key: &str, value: &str) {
+    let _lock = match file_lock::lock_exclusive(&get_sync_settings_path(), "write_sync_setting") {
+        Ok(lock) => lock,
+        Err(e) => {
+            crate::modules::logger::log_error(&format!("[SyncSettings] 写入配置失败: {}", e));
+            return;
+        }
+    };
     let mut settings = read_sync_settings();
 
     let setting_value = SyncSettingValue {
@@ -129,6 +138,13 @@ pub fn write_sync_setting(key: &str, value: &str) {
 /// 清除单个同步配置项
 /// 用于已同步后清理，避免下次重复同步
 pub fn clear_sync_setting(key: &str) {
+    let _lock = match file_lock::lock_exclusive(&get_sync_settings_path(), "clear_sync_setting") {
+        Ok(lock) => lock,
+        Err(e) => {
+            crate::modules::logger::log_error(&format!("[SyncSettings] 清除配置失败: {}", e));
+            return;
+        }
+    };
     let mut settings = read_sync_settings();
 
     let had_value = match key {
@@ -172,6 +188,9 @@ pub fn merge_setting_on_startup(
     local_value: &str,
     local_updated_at: Option<i64>,
 ) -> Option<String> {
+    // 读取与清除需在同一把锁内完成，避免插件端在两者之间写入的新值被清掉
+    let _lock =
+        file_lock::lock_exclusive(&get_sync_settings_path(), "merge_setting_on_startup").ok()?;
     let sync_setting = get_sync_setting(key)?;
 
     // 如果共享文件的值和本地相同，不需要更新
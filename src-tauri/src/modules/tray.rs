@@ -10,7 +10,8 @@ use tauri::{
 };
 use tracing::info;
 
-use crate::modules::logger;
+use crate::modules::quota_forecast::QuotaForecast;
+use crate::modules::{group_settings, logger};
 
 /// 托盘菜单 ID
 pub const TRAY_ID: &str = "main-tray";
@@ -25,16 +26,18 @@ enum PlatformId {
     GitHubCopilot,
     Windsurf,
     Kiro,
+    Cursor,
 }
 
 impl PlatformId {
-    fn default_order() -> [Self; 5] {
+    fn default_order() -> [Self; 6] {
         [
             Self::Antigravity,
             Self::Codex,
             Self::GitHubCopilot,
             Self::Windsurf,
             Self::Kiro,
+            Self::Cursor,
         ]
     }
 
@@ -45,6 +48,7 @@ impl PlatformId {
             crate::modules::tray_layout::PLATFORM_GITHUB_COPILOT => Some(Self::GitHubCopilot),
             crate::modules::tray_layout::PLATFORM_WINDSURF => Some(Self::Windsurf),
             crate::modules::tray_layout::PLATFORM_KIRO => Some(Self::Kiro),
+            crate::modules::tray_layout::PLATFORM_CURSOR => Some(Self::Cursor),
             _ => None,
         }
     }
@@ -56,6 +60,7 @@ impl PlatformId {
             Self::GitHubCopilot => crate::modules::tray_layout::PLATFORM_GITHUB_COPILOT,
             Self::Windsurf => crate::modules::tray_layout::PLATFORM_WINDSURF,
             Self::Kiro => crate::modules::tray_layout::PLATFORM_KIRO,
+            Self::Cursor => crate::modules::tray_layout::PLATFORM_CURSOR,
         }
     }
 
@@ -66,6 +71,7 @@ impl PlatformId {
             Self::GitHubCopilot => "GitHub Copilot",
             Self::Windsurf => "Windsurf",
             Self::Kiro => "Kiro",
+            Self::Cursor => "Cursor",
         }
     }
 
@@ -76,6 +82,7 @@ impl PlatformId {
             Self::GitHubCopilot => "github-copilot",
             Self::Windsurf => "windsurf",
             Self::Kiro => "kiro",
+            Self::Cursor => "cursor",
         }
     }
 
@@ -86,6 +93,7 @@ impl PlatformId {
             Self::GitHubCopilot => 2,
             Self::Windsurf => 3,
             Self::Kiro => 4,
+            Self::Cursor => 5,
         }
     }
 }
@@ -104,20 +112,6 @@ struct AccountDisplayInfo {
     quota_lines: Vec<String>,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct CopilotMetric {
-    used_percent: Option<i32>,
-    included: bool,
-}
-
-#[derive(Debug, Clone, Copy)]
-struct CopilotUsage {
-    inline: CopilotMetric,
-    chat: CopilotMetric,
-    premium: CopilotMetric,
-    reset_ts: Option<i64>,
-}
-
 /// 创建系统托盘
 pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<TrayIcon<R>, tauri::Error> {
     info!("[Tray] 正在创建系统托盘...");
@@ -325,6 +319,10 @@ fn collect_platform_account_counts() -> HashMap<PlatformId, usize> {
         PlatformId::Kiro,
         crate::modules::kiro_account::list_accounts().len(),
     );
+    counts.insert(
+        PlatformId::Cursor,
+        crate::modules::cursor_account::list_accounts().len(),
+    );
     counts
 }
 
@@ -375,22 +373,31 @@ fn get_account_display_info(platform: PlatformId, lang: &str) -> AccountDisplayI
         PlatformId::GitHubCopilot => build_github_copilot_display_info(lang),
         PlatformId::Windsurf => build_windsurf_display_info(lang),
         PlatformId::Kiro => build_kiro_display_info(lang),
+        PlatformId::Cursor => build_cursor_display_info(lang),
     }
 }
 
 fn build_antigravity_display_info(lang: &str) -> AccountDisplayInfo {
     match crate::modules::account::get_current_account() {
         Ok(Some(account)) => {
-            let quota_lines = if let Some(quota) = &account.quota {
-                let grouped_lines = build_antigravity_group_quota_lines(lang, &quota.models);
+            let mut quota_lines = if let Some(quota) = &account.quota {
+                let forecasts = load_quota_forecasts(
+                    &crate::modules::account::AntigravityPlatform,
+                    &account.id,
+                );
+                let grouped_lines =
+                    build_antigravity_group_quota_lines(lang, &quota.models, &forecasts);
                 if grouped_lines.is_empty() {
-                    build_model_quota_lines(lang, &quota.models)
+                    build_model_quota_lines(lang, &quota.models, &forecasts)
                 } else {
                     grouped_lines
                 }
             } else {
                 vec![get_text("loading", lang)]
             };
+            if let Some(line) = build_antigravity_capacity_line(lang) {
+                quota_lines.push(line);
+            }
             AccountDisplayInfo {
                 account: format!("📧 {}", account.email),
                 quota_lines,
@@ -403,65 +410,19 @@ fn build_antigravity_display_info(lang: &str) -> AccountDisplayInfo {
     }
 }
 
-fn normalize_antigravity_model_for_match(value: &str) -> String {
-    let normalized = value.trim().to_lowercase();
-    if normalized.is_empty() {
-        return normalized;
-    }
-    if normalized.starts_with("gemini-3.1-flash")
-        || normalized.starts_with("gemini-2.5-flash")
-        || normalized.starts_with("gemini-3-flash")
-    {
-        return "gemini-3-flash".to_string();
-    }
-    if normalized.starts_with("gemini-3.1-pro-high") || normalized.starts_with("gemini-3-pro-high")
-    {
-        return "gemini-3.1-pro-high".to_string();
-    }
-    if normalized.starts_with("gemini-3.1-pro-low") || normalized.starts_with("gemini-3-pro-low") {
-        return "gemini-3.1-pro-low".to_string();
-    }
-    if normalized.starts_with("claude-sonnet-4-6") || normalized.starts_with("claude-sonnet-4-5")
-    {
-        return "claude-sonnet-4-6".to_string();
-    }
-    if normalized.starts_with("claude-opus-4-6-thinking")
-        || normalized.starts_with("claude-opus-4-5-thinking")
-    {
-        return "claude-opus-4-6-thinking".to_string();
-    }
-    match normalized.as_str() {
-        "gemini-3-pro-high" => "gemini-3.1-pro-high".to_string(),
-        "gemini-3-pro-low" => "gemini-3.1-pro-low".to_string(),
-        "claude-sonnet-4-5" => "claude-sonnet-4-6".to_string(),
-        "claude-sonnet-4-5-thinking" => "claude-sonnet-4-6".to_string(),
-        "claude-opus-4-5-thinking" => "claude-opus-4-6-thinking".to_string(),
-        _ => normalized,
-    }
-}
-
-fn antigravity_model_matches(model_name: &str, target: &str) -> bool {
-    let left = normalize_antigravity_model_for_match(model_name);
-    let right = normalize_antigravity_model_for_match(target);
-    if left.is_empty() || right.is_empty() {
-        return false;
-    }
-    left == right
-        || left.starts_with(&(right.clone() + "-"))
-        || right.starts_with(&(left + "-"))
-}
-
 fn parse_model_reset_ts(reset_time: &str) -> Option<i64> {
     chrono::DateTime::parse_from_rfc3339(reset_time)
         .ok()
         .map(|value| value.timestamp())
 }
 
+/// 分组行展示组内平均剩余、最早重置时间，以及组内最早在重置前耗尽的模型的预测
 fn build_antigravity_group_quota_lines(
     lang: &str,
     models: &[crate::models::quota::ModelQuota],
+    forecasts: &[QuotaForecast],
 ) -> Vec<String> {
-    let settings = crate::modules::group_settings::load_group_settings();
+    let settings = group_settings::load_group_settings();
     let ordered_groups = settings.get_ordered_groups(Some(3));
     if ordered_groups.is_empty() {
         return Vec::new();
@@ -477,11 +438,12 @@ fn build_antigravity_group_quota_lines(
         let mut total_percentage: i64 = 0;
         let mut count: i64 = 0;
         let mut earliest_reset_ts: Option<i64> = None;
+        let mut earliest_exhausts_at: Option<i64> = None;
 
         for model in models {
             let belongs = group_models
                 .iter()
-                .any(|group_model| antigravity_model_matches(&model.name, group_model));
+                .any(|group_model| group_settings::model_matches(&model.name, group_model));
             if !belongs {
                 continue;
             }
@@ -494,6 +456,12 @@ fn build_antigravity_group_quota_lines(
                     None => reset_ts,
                 });
             }
+            if let Some(exhausts_at) = exhausts_before_reset_at(forecasts, &model.name) {
+                earliest_exhausts_at = Some(match earliest_exhausts_at {
+                    Some(current) => current.min(exhausts_at),
+                    None => exhausts_at,
+                });
+            }
         }
 
         if count <= 0 {
@@ -502,83 +470,50 @@ fn build_antigravity_group_quota_lines(
 
         let avg_percentage = (total_percentage as f64 / count as f64).round() as i32;
         let reset_text = earliest_reset_ts.map(|ts| format_reset_time_from_ts(lang, Some(ts)));
-        lines.push(format_quota_line(
+        let line = format_quota_line(
             lang,
             &settings.get_group_name(&group_id),
             &format_percent_text(avg_percentage),
             reset_text.as_deref(),
-        ));
+        );
+        lines.push(append_exhaustion_text(line, lang, earliest_exhausts_at));
     }
 
     lines
 }
 
-fn format_codex_window_label(window_minutes: Option<i64>, fallback: &str) -> String {
-    const HOUR_MINUTES: i64 = 60;
-    const DAY_MINUTES: i64 = 24 * HOUR_MINUTES;
-    const WEEK_MINUTES: i64 = 7 * DAY_MINUTES;
-
-    let Some(minutes) = window_minutes.filter(|value| *value > 0) else {
-        return fallback.to_string();
-    };
-
-    if minutes >= WEEK_MINUTES - 1 {
-        let weeks = (minutes + WEEK_MINUTES - 1) / WEEK_MINUTES;
-        return if weeks <= 1 {
-            "Weekly".to_string()
-        } else {
-            format!("{} Week", weeks)
-        };
-    }
-
-    if minutes >= DAY_MINUTES - 1 {
-        let days = (minutes + DAY_MINUTES - 1) / DAY_MINUTES;
-        return format!("{}d", days);
-    }
-
-    if minutes >= HOUR_MINUTES {
-        let hours = (minutes + HOUR_MINUTES - 1) / HOUR_MINUTES;
-        return format!("{}h", hours);
+/// 全部账号的分组容量汇总行，例如 "全部账号: Gemini Pro 350% · Gemini Flash 0% (2h 5m)"
+fn build_antigravity_capacity_line(lang: &str) -> Option<String> {
+    let capacities = crate::modules::group_capacity::group_capacity().ok()?;
+    let parts: Vec<String> = capacities
+        .iter()
+        .filter(|group| group.account_count > 0)
+        .take(3)
+        .map(|group| {
+            let mut part = format!("{} {}%", group.group_name, group.total_remaining_pct);
+            if group.is_exhausted() {
+                let reset = format_reset_time_from_ts(lang, group.next_recovery_at());
+                part.push_str(&format!(" ({})", reset));
+            }
+            part
+        })
+        .collect();
+    if parts.is_empty() {
+        return None;
     }
-
-    format!("{}m", minutes)
+    Some(format!("{}: {}", get_text("all_accounts", lang), parts.join(" · ")))
 }
 
 fn build_codex_display_info(lang: &str) -> AccountDisplayInfo {
     if let Some(account) = crate::modules::codex_account::get_current_account() {
-        let mut quota_lines = if let Some(quota) = &account.quota {
-            let has_presence =
-                quota.hourly_window_present.is_some() || quota.weekly_window_present.is_some();
-            let mut lines = Vec::new();
-
-            if !has_presence || quota.hourly_window_present.unwrap_or(false) {
-                lines.push(format_quota_line(
-                    lang,
-                    &format_codex_window_label(quota.hourly_window_minutes, "5h"),
-                    &format_percent_text(quota.hourly_percentage),
-                    Some(&format_reset_time_from_ts(lang, quota.hourly_reset_time)),
-                ));
-            }
-
-            if !has_presence || quota.weekly_window_present.unwrap_or(false) {
-                lines.push(format_quota_line(
-                    lang,
-                    &format_codex_window_label(quota.weekly_window_minutes, "Weekly"),
-                    &format_percent_text(quota.weekly_percentage),
-                    Some(&format_reset_time_from_ts(lang, quota.weekly_reset_time)),
-                ));
-            }
-
-            if lines.is_empty() {
-                lines.push(format_quota_line(
-                    lang,
-                    &format_codex_window_label(quota.hourly_window_minutes, "5h"),
-                    &format_percent_text(quota.hourly_percentage),
-                    Some(&format_reset_time_from_ts(lang, quota.hourly_reset_time)),
-                ));
-            }
-
-            lines
+        let mut quota_lines = if account.quota.is_some() {
+            let forecasts =
+                load_quota_forecasts(&crate::modules::codex_account::CodexPlatform, &account.id);
+            build_unified_quota_lines(
+                lang,
+                &crate::modules::codex_account::unified_quota(&account),
+                &forecasts,
+            )
         } else {
             vec![get_text("loading", lang)]
         };
@@ -608,21 +543,25 @@ fn build_github_copilot_display_info(lang: &str) -> AccountDisplayInfo {
         };
     };
 
-    let usage = compute_copilot_usage(
-        &account.copilot_token,
-        account.copilot_plan.as_deref(),
-        account.copilot_limited_user_quotas.as_ref(),
-        account.copilot_quota_snapshots.as_ref(),
-        account.copilot_limited_user_reset_date,
-        account.copilot_quota_reset_date.as_deref(),
+    let forecasts = load_quota_forecasts(
+        &crate::modules::github_copilot_account::GitHubCopilotPlatform,
+        &account.id,
     );
+    let mut quota_lines = build_unified_quota_lines(
+        lang,
+        &crate::modules::github_copilot_account::unified_quota(&account),
+        &forecasts,
+    );
+    if quota_lines.is_empty() {
+        quota_lines.push(get_text("loading", lang));
+    }
 
     AccountDisplayInfo {
         account: format!(
             "📧 {}",
             display_login_email(account.github_email.as_deref(), &account.github_login)
         ),
-        quota_lines: build_copilot_quota_lines(lang, usage),
+        quota_lines,
     }
 }
 
@@ -635,16 +574,15 @@ fn build_windsurf_display_info(lang: &str) -> AccountDisplayInfo {
         };
     };
 
-    let mut usage = compute_copilot_usage(
-        &account.copilot_token,
-        account.copilot_plan.as_deref(),
-        account.copilot_limited_user_quotas.as_ref(),
-        account.copilot_quota_snapshots.as_ref(),
-        account.copilot_limited_user_reset_date,
-        account.copilot_quota_reset_date.as_deref(),
+    let forecasts =
+        load_quota_forecasts(&crate::modules::windsurf_account::WindsurfPlatform, &account.id);
+    let mut quota_lines = build_unified_quota_lines(
+        lang,
+        &crate::modules::windsurf_account::unified_quota(&account),
+        &forecasts,
     );
-    if usage.reset_ts.is_none() {
-        usage.reset_ts = resolve_windsurf_plan_end_ts(&account);
+    if quota_lines.is_empty() {
+        quota_lines.push(get_text("loading", lang));
     }
 
     AccountDisplayInfo {
@@ -652,7 +590,7 @@ fn build_windsurf_display_info(lang: &str) -> AccountDisplayInfo {
             "📧 {}",
             display_login_email(account.github_email.as_deref(), &account.github_login)
         ),
-        quota_lines: build_windsurf_quota_lines(lang, usage),
+        quota_lines,
     }
 }
 
@@ -666,7 +604,6 @@ fn build_kiro_display_info(lang: &str) -> AccountDisplayInfo {
     };
 
     let mut quota_lines = Vec::new();
-    let reset_text = format_reset_time_from_ts(lang, account.usage_reset_at);
 
     if let Some(plan) =
         first_non_empty(&[account.plan_name.as_deref(), account.plan_tier.as_deref()])
@@ -674,25 +611,50 @@ fn build_kiro_display_info(lang: &str) -> AccountDisplayInfo {
         quota_lines.push(format!("Plan: {}", plan));
     }
 
-    if let Some(remaining_pct) = calc_remaining_percent(account.credits_total, account.credits_used)
-    {
-        quota_lines.push(format_quota_line(
-            lang,
-            "Prompt",
-            &format_percent_text(remaining_pct),
-            Some(&reset_text),
-        ));
+    let forecasts = load_quota_forecasts(&crate::modules::kiro_account::KiroPlatform, &account.id);
+    quota_lines.extend(build_unified_quota_lines(
+        lang,
+        &crate::modules::kiro_account::unified_quota(&account),
+        &forecasts,
+    ));
+
+    if quota_lines.is_empty() {
+        quota_lines.push(get_text("loading", lang));
     }
 
-    if let Some(remaining_pct) = calc_remaining_percent(account.bonus_total, account.bonus_used) {
-        quota_lines.push(format_quota_line(
-            lang,
-            "Add-on",
-            &format_percent_text(remaining_pct),
-            Some(&reset_text),
-        ));
+    AccountDisplayInfo {
+        account: format!(
+            "📧 {}",
+            first_non_empty(&[Some(account.email.as_str()), Some(account.id.as_str())])
+                .unwrap_or("—")
+        ),
+        quota_lines,
+    }
+}
+
+fn build_cursor_display_info(lang: &str) -> AccountDisplayInfo {
+    let accounts = crate::modules::cursor_account::list_accounts();
+    let Some(account) = resolve_cursor_current_account(&accounts) else {
+        return AccountDisplayInfo {
+            account: format!("📧 {}", get_text("not_logged_in", lang)),
+            quota_lines: vec!["—".to_string()],
+        };
+    };
+
+    let mut quota_lines = Vec::new();
+
+    if let Some(plan) = first_non_empty(&[account.membership_type.as_deref()]) {
+        quota_lines.push(format!("Plan: {}", plan));
     }
 
+    let forecasts =
+        load_quota_forecasts(&crate::modules::cursor_account::CursorPlatform, &account.id);
+    quota_lines.extend(build_unified_quota_lines(
+        lang,
+        &crate::modules::cursor_account::unified_quota(&account),
+        &forecasts,
+    ));
+
     if quota_lines.is_empty() {
         quota_lines.push(get_text("loading", lang));
     }
@@ -767,6 +729,26 @@ fn resolve_kiro_current_account(
         .cloned()
 }
 
+fn resolve_cursor_current_account(
+    accounts: &[crate::models::cursor::CursorAccount],
+) -> Option<crate::models::cursor::CursorAccount> {
+    if let Ok(settings) = crate::modules::cursor_instance::load_default_settings() {
+        if let Some(bind_id) = settings.bind_account_id {
+            let bind_id = bind_id.trim();
+            if !bind_id.is_empty() {
+                if let Some(account) = accounts.iter().find(|account| account.id == bind_id) {
+                    return Some(account.clone());
+                }
+            }
+        }
+    }
+
+    accounts
+        .iter()
+        .max_by_key(|account| account.last_used)
+        .cloned()
+}
+
 fn first_non_empty<'a>(values: &[Option<&'a str>]) -> Option<&'a str> {
     values
         .iter()
@@ -775,21 +757,6 @@ fn first_non_empty<'a>(values: &[Option<&'a str>]) -> Option<&'a str> {
         .find(|value| !value.is_empty())
 }
 
-fn calc_remaining_percent(total: Option<f64>, used: Option<f64>) -> Option<i32> {
-    let total = total?;
-    if !total.is_finite() || total <= 0.0 {
-        return None;
-    }
-
-    let used = used.unwrap_or(0.0);
-    if !used.is_finite() {
-        return None;
-    }
-
-    let remaining = (total - used).max(0.0);
-    Some(clamp_percent((remaining / total) * 100.0))
-}
-
 fn display_login_email(email: Option<&str>, login: &str) -> String {
     email
         .map(|value| value.trim())
@@ -820,410 +787,82 @@ fn format_quota_line(lang: &str, label: &str, value_text: &str, reset_text: Opti
     }
 }
 
-fn format_copilot_metric_value(lang: &str, metric: CopilotMetric) -> Option<String> {
-    if metric.included {
-        return Some(get_text("included", lang));
-    }
-    metric
-        .used_percent
-        .map(|percentage| format!("{}%", percentage))
-}
-
-fn build_copilot_quota_lines(lang: &str, usage: CopilotUsage) -> Vec<String> {
-    let mut lines = Vec::new();
-    let reset_text = format_reset_time_from_ts(lang, usage.reset_ts);
-
-    if let Some(value_text) = format_copilot_metric_value(lang, usage.inline) {
-        lines.push(format_quota_line(
-            lang,
-            &get_text("ghcp_inline", lang),
-            &value_text,
-            Some(&reset_text),
-        ));
-    }
-    if let Some(value_text) = format_copilot_metric_value(lang, usage.chat) {
-        lines.push(format_quota_line(
-            lang,
-            &get_text("ghcp_chat", lang),
-            &value_text,
-            Some(&reset_text),
-        ));
-    }
-    let premium_value = format_copilot_metric_value(lang, usage.premium).unwrap_or_else(|| "-".to_string());
-    lines.push(format_quota_line(
-        lang,
-        &get_text("ghcp_premium", lang),
-        &premium_value,
-        None,
-    ));
-
-    if lines.is_empty() {
-        lines.push(get_text("loading", lang));
-    }
-
-    lines
-}
-
-fn build_windsurf_quota_lines(lang: &str, usage: CopilotUsage) -> Vec<String> {
-    let mut lines = Vec::new();
-    let reset_text = format_reset_time_from_ts(lang, usage.reset_ts);
-
-    if let Some(percentage) = usage.inline.used_percent {
-        lines.push(format_quota_line(
-            lang,
-            "Prompt",
-            &format_percent_text(percentage),
-            Some(&reset_text),
-        ));
-    }
-    if let Some(percentage) = usage.chat.used_percent {
-        lines.push(format_quota_line(
-            lang,
-            "Flow",
-            &format_percent_text(percentage),
-            Some(&reset_text),
-        ));
-    }
-
-    if lines.is_empty() {
-        lines.push(get_text("loading", lang));
-    }
-
-    lines
-}
-
-fn compute_copilot_usage(
-    token: &str,
-    plan: Option<&str>,
-    limited_quotas: Option<&serde_json::Value>,
-    quota_snapshots: Option<&serde_json::Value>,
-    limited_reset_ts: Option<i64>,
-    quota_reset_date: Option<&str>,
-) -> CopilotUsage {
-    let token_map = parse_token_map(token);
-    let reset_ts = limited_reset_ts
-        .or_else(|| parse_reset_date_to_ts(quota_reset_date))
-        .or_else(|| {
-            parse_token_number(&token_map, "rd")
-                .map(|value| value.floor() as i64)
-                .filter(|value| *value > 0)
-        });
-    let sku = token_map
-        .get("sku")
-        .map(|value| value.to_lowercase())
-        .unwrap_or_default();
-    let is_free_limited = sku.contains("free_limited")
-        || sku.contains("no_auth_limited")
-        || plan
-            .map(|value| value.to_lowercase().contains("free_limited"))
-            .unwrap_or(false);
-
-    let completions_snapshot = get_quota_snapshot(quota_snapshots, "completions");
-    let chat_snapshot = get_quota_snapshot(quota_snapshots, "chat");
-    let premium_snapshot = get_quota_snapshot(quota_snapshots, "premium_interactions");
-
-    let limited = limited_quotas.and_then(|value| value.as_object());
-    let remaining_inline = remaining_from_snapshot(completions_snapshot).or_else(|| {
-        limited
-            .and_then(|obj| obj.get("completions"))
-            .and_then(parse_json_number)
-    });
-    let remaining_chat = remaining_from_snapshot(chat_snapshot).or_else(|| {
-        limited
-            .and_then(|obj| obj.get("chat"))
-            .and_then(parse_json_number)
-    });
-
-    let total_inline = entitlement_from_snapshot(completions_snapshot)
-        .or_else(|| parse_token_number(&token_map, "cq"))
-        .or(remaining_inline);
-    let total_chat = entitlement_from_snapshot(chat_snapshot)
-        .or_else(|| parse_token_number(&token_map, "tq"))
-        .or_else(|| {
-            if is_free_limited {
-                remaining_chat.map(|_| 500.0)
-            } else {
-                remaining_chat
-            }
-        });
-
-    CopilotUsage {
-        inline: CopilotMetric {
-            used_percent: used_percent_from_snapshot(completions_snapshot)
-                .or_else(|| calc_used_percent(total_inline, remaining_inline)),
-            included: is_included_snapshot(completions_snapshot),
-        },
-        chat: CopilotMetric {
-            used_percent: used_percent_from_snapshot(chat_snapshot)
-                .or_else(|| calc_used_percent(total_chat, remaining_chat)),
-            included: is_included_snapshot(chat_snapshot),
-        },
-        premium: CopilotMetric {
-            used_percent: used_percent_from_snapshot(premium_snapshot),
-            included: is_included_snapshot(premium_snapshot),
-        },
-        reset_ts,
-    }
-}
-
-fn get_quota_snapshot<'a>(
-    quota_snapshots: Option<&'a serde_json::Value>,
-    key: &str,
-) -> Option<&'a serde_json::Map<String, serde_json::Value>> {
-    let snapshots = quota_snapshots.and_then(|value| value.as_object())?;
-    let primary = snapshots.get(key).and_then(|snapshot| snapshot.as_object());
-    if primary.is_some() {
-        return primary;
-    }
-    if key == "premium_interactions" {
-        return snapshots
-            .get("premium_models")
-            .and_then(|snapshot| snapshot.as_object());
-    }
-    None
-}
-
-fn entitlement_from_snapshot(
-    snapshot: Option<&serde_json::Map<String, serde_json::Value>>,
-) -> Option<f64> {
-    snapshot
-        .and_then(|data| data.get("entitlement"))
-        .and_then(parse_json_number)
-        .filter(|value| *value > 0.0)
-}
-
-fn remaining_from_snapshot(
-    snapshot: Option<&serde_json::Map<String, serde_json::Value>>,
-) -> Option<f64> {
-    if let Some(remaining) = snapshot
-        .and_then(|data| data.get("remaining"))
-        .and_then(parse_json_number)
-    {
-        return Some(remaining);
-    }
-
-    let entitlement = snapshot
-        .and_then(|data| data.get("entitlement"))
-        .and_then(parse_json_number)?;
-    let percent_remaining = snapshot
-        .and_then(|data| data.get("percent_remaining"))
-        .and_then(parse_json_number)?;
-    if entitlement <= 0.0 {
-        return None;
-    }
-    Some((entitlement * (percent_remaining / 100.0)).max(0.0))
-}
-
-fn is_included_snapshot(snapshot: Option<&serde_json::Map<String, serde_json::Value>>) -> bool {
-    if snapshot
-        .and_then(|data| data.get("unlimited"))
-        .and_then(|value| value.as_bool())
-        == Some(true)
-    {
-        return true;
-    }
-
-    snapshot
-        .and_then(|data| data.get("entitlement"))
-        .and_then(parse_json_number)
-        .map(|value| value < 0.0)
-        .unwrap_or(false)
-}
-
-fn used_percent_from_snapshot(
-    snapshot: Option<&serde_json::Map<String, serde_json::Value>>,
-) -> Option<i32> {
-    if snapshot
-        .and_then(|data| data.get("unlimited"))
-        .and_then(|value| value.as_bool())
-        == Some(true)
-    {
-        return Some(0);
-    }
-
-    let entitlement = snapshot
-        .and_then(|data| data.get("entitlement"))
-        .and_then(parse_json_number);
-    let remaining = snapshot
-        .and_then(|data| data.get("remaining"))
-        .and_then(parse_json_number);
-
-    if let (Some(total), Some(left)) = (entitlement, remaining) {
-        return calc_used_percent(Some(total), Some(left));
-    }
-
-    let percent_remaining = snapshot
-        .and_then(|data| data.get("percent_remaining"))
-        .and_then(parse_json_number)
-        .map(clamp_percent)?;
-    Some(clamp_percent((100 - percent_remaining) as f64))
-}
-
-fn resolve_windsurf_plan_end_ts(account: &crate::models::windsurf::WindsurfAccount) -> Option<i64> {
-    let mut candidates: Vec<Option<&serde_json::Value>> = Vec::new();
-    let user_status = account.windsurf_user_status.as_ref();
-    let snapshots = account.copilot_quota_snapshots.as_ref();
-
-    candidates.push(json_path(
-        user_status,
-        &["userStatus", "planStatus", "planEnd"],
-    ));
-    candidates.push(json_path(
-        user_status,
-        &["userStatus", "planStatus", "plan_end"],
-    ));
-    candidates.push(json_path(user_status, &["planStatus", "planEnd"]));
-    candidates.push(json_path(user_status, &["planStatus", "plan_end"]));
-    candidates.push(json_path(snapshots, &["windsurfPlanStatus", "planEnd"]));
-    candidates.push(json_path(snapshots, &["windsurfPlanStatus", "plan_end"]));
-    candidates.push(json_path(
-        snapshots,
-        &["windsurfPlanStatus", "planStatus", "planEnd"],
-    ));
-    candidates.push(json_path(
-        snapshots,
-        &["windsurfPlanStatus", "planStatus", "plan_end"],
-    ));
-    candidates.push(json_path(
-        snapshots,
-        &["windsurfUserStatus", "userStatus", "planStatus", "planEnd"],
-    ));
-    candidates.push(json_path(
-        snapshots,
-        &["windsurfUserStatus", "userStatus", "planStatus", "plan_end"],
-    ));
-
-    for candidate in candidates.into_iter().flatten() {
-        if let Some(ts) = parse_timestamp_like(candidate) {
-            return Some(ts);
-        }
-    }
-
-    None
-}
-
-fn json_path<'a>(
-    root: Option<&'a serde_json::Value>,
-    path: &[&str],
-) -> Option<&'a serde_json::Value> {
-    let mut current = root?;
-    for key in path {
-        current = current.as_object()?.get(*key)?;
-    }
-    Some(current)
+fn load_quota_forecasts(
+    platform: &dyn crate::modules::platform::Platform,
+    account_id: &str,
+) -> Vec<QuotaForecast> {
+    crate::modules::quota_forecast::forecast_account(platform, account_id).unwrap_or_default()
 }
 
-fn parse_timestamp_like(value: &serde_json::Value) -> Option<i64> {
-    match value {
-        serde_json::Value::Number(num) => parse_timestamp_number(num.as_f64()?),
-        serde_json::Value::String(text) => {
-            let trimmed = text.trim();
-            if trimmed.is_empty() {
-                return None;
-            }
-            if let Ok(n) = trimmed.parse::<f64>() {
-                return parse_timestamp_number(n);
-            }
-            chrono::DateTime::parse_from_rfc3339(trimmed)
-                .ok()
-                .map(|dt| dt.timestamp())
-        }
-        serde_json::Value::Object(obj) => {
-            if let Some(seconds) = obj.get("seconds").and_then(|v| v.as_i64()) {
-                return Some(seconds);
-            }
-            if let Some(seconds) = obj.get("unixSeconds").and_then(|v| v.as_i64()) {
-                return Some(seconds);
-            }
-            if let Some(inner) = obj.get("value") {
-                return parse_timestamp_like(inner);
+/// 按统一配额逐项生成托盘行（剩余百分比 + 重置倒计时 + 耗尽预测）
+fn build_unified_quota_lines(
+    lang: &str,
+    quota: &crate::modules::unified_quota::UnifiedQuota,
+    forecasts: &[QuotaForecast],
+) -> Vec<String> {
+    quota
+        .metrics
+        .iter()
+        .map(|metric| {
+            if metric.unlimited {
+                return format_quota_line(lang, &metric.label, &get_text("included", lang), None);
             }
-            None
-        }
-        _ => None,
-    }
-}
-
-fn parse_timestamp_number(raw: f64) -> Option<i64> {
-    if !raw.is_finite() || raw <= 0.0 {
-        return None;
-    }
-    if raw > 1e12 {
-        return Some((raw / 1000.0).floor() as i64);
-    }
-    Some(raw.floor() as i64)
-}
-
-fn parse_token_map(token: &str) -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    let prefix = token.split(':').next().unwrap_or(token);
-    for item in prefix.split(';') {
-        let mut parts = item.splitn(2, '=');
-        let key = parts.next().unwrap_or("").trim();
-        if key.is_empty() {
-            continue;
-        }
-        let value = parts.next().unwrap_or("").trim();
-        map.insert(key.to_string(), value.to_string());
-    }
-    map
-}
-
-fn parse_token_number(map: &HashMap<String, String>, key: &str) -> Option<f64> {
-    map.get(key)
-        .map(|value| value.trim())
-        .filter(|value| !value.is_empty())
-        .and_then(|value| value.split(':').next())
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-        .and_then(|value| value.parse::<f64>().ok())
-        .filter(|value| value.is_finite())
+            let line = format_quota_line(
+                lang,
+                &metric.label,
+                &format_percent_text(metric.remaining_pct),
+                Some(&format_reset_time_from_ts(lang, metric.resets_at)),
+            );
+            append_forecast_text(line, lang, forecasts, &metric.id)
+        })
+        .collect()
 }
 
-fn parse_json_number(value: &serde_json::Value) -> Option<f64> {
-    match value {
-        serde_json::Value::Number(num) => num.as_f64(),
-        serde_json::Value::String(text) => text.trim().parse::<f64>().ok(),
-        _ => None,
-    }
-    .filter(|value| value.is_finite())
+/// 指标预计在重置前耗尽的时间
+fn exhausts_before_reset_at(forecasts: &[QuotaForecast], metric: &str) -> Option<i64> {
+    forecasts
+        .iter()
+        .find(|forecast| forecast.metric == metric && forecast.exhausts_before_reset)
+        .and_then(|forecast| forecast.exhausts_at)
 }
 
-fn calc_used_percent(total: Option<f64>, remaining: Option<f64>) -> Option<i32> {
-    let total = total?;
-    let remaining = remaining?;
-    if total <= 0.0 {
-        return None;
-    }
-
-    let used = (total - remaining).max(0.0);
-    Some(clamp_percent((used / total) * 100.0))
+/// 预计在重置前耗尽时，在配额行末尾追加剩余可用时长
+fn append_forecast_text(
+    line: String,
+    lang: &str,
+    forecasts: &[QuotaForecast],
+    metric: &str,
+) -> String {
+    append_exhaustion_text(line, lang, exhausts_before_reset_at(forecasts, metric))
 }
 
-fn parse_reset_date_to_ts(reset_date: Option<&str>) -> Option<i64> {
-    let reset_date = reset_date?.trim();
-    if reset_date.is_empty() {
-        return None;
+fn append_exhaustion_text(line: String, lang: &str, exhausts_at: Option<i64>) -> String {
+    match exhausts_at {
+        Some(exhausts_at) => format!(
+            "{} · {} ~{}",
+            line,
+            get_text("runs_out", lang),
+            format_remaining_duration(exhausts_at - chrono::Utc::now().timestamp())
+        ),
+        None => line,
     }
-    chrono::DateTime::parse_from_rfc3339(reset_date)
-        .ok()
-        .map(|value| value.timestamp())
-}
-
-fn clamp_percent(value: f64) -> i32 {
-    value.round().clamp(0.0, 100.0) as i32
 }
 
-fn build_model_quota_lines(lang: &str, models: &[crate::models::quota::ModelQuota]) -> Vec<String> {
+fn build_model_quota_lines(
+    lang: &str,
+    models: &[crate::models::quota::ModelQuota],
+    forecasts: &[QuotaForecast],
+) -> Vec<String> {
     let mut lines = Vec::new();
     for model in models.iter().take(4) {
         let reset_text = format_reset_time(lang, &model.reset_time);
-        lines.push(format_quota_line(
+        let line = format_quota_line(
             lang,
             &model.name,
             &format_percent_text(model.percentage),
             Some(&reset_text),
-        ));
+        );
+        lines.push(append_forecast_text(line, lang, forecasts, &model.name));
     }
     if lines.is_empty() {
         lines.push("—".to_string());
@@ -1396,10 +1035,9 @@ fn get_text(key: &str, lang: &str) -> String {
         ("loading", "zh-cn") => "加载中...".to_string(),
         ("reset", "zh-cn") => "重置".to_string(),
         ("reset_done", "zh-cn") => "已重置".to_string(),
-        ("included", "zh-cn") => "包含".to_string(),
-        ("ghcp_inline", "zh-cn") => "Inline".to_string(),
-        ("ghcp_chat", "zh-cn") => "Chat".to_string(),
-        ("ghcp_premium", "zh-cn") => "Premium".to_string(),
+        ("runs_out", "zh-cn") => "预计耗尽".to_string(),
+        ("included", "zh-cn") => "已包含".to_string(),
+        ("all_accounts", "zh-cn") => "全部账号".to_string(),
         ("more_platforms", "zh-cn") => "更多平台".to_string(),
         ("no_platform_selected", "zh-cn") => "未选择托盘平台".to_string(),
 
@@ -1412,10 +1050,9 @@ fn get_text(key: &str, lang: &str) -> String {
         ("loading", "zh-tw") => "載入中...".to_string(),
         ("reset", "zh-tw") => "重置".to_string(),
         ("reset_done", "zh-tw") => "已重置".to_string(),
+        ("runs_out", "zh-tw") => "預計耗盡".to_string(),
         ("included", "zh-tw") => "已包含".to_string(),
-        ("ghcp_inline", "zh-tw") => "Inline".to_string(),
-        ("ghcp_chat", "zh-tw") => "Chat".to_string(),
-        ("ghcp_premium", "zh-tw") => "Premium".to_string(),
+        ("all_accounts", "zh-tw") => "全部帳號".to_string(),
         ("more_platforms", "zh-tw") => "更多平台".to_string(),
         ("no_platform_selected", "zh-tw") => "未選擇托盤平台".to_string(),
 
@@ -1428,10 +1065,9 @@ fn get_text(key: &str, lang: &str) -> String {
         ("loading", "en") => "Loading...".to_string(),
         ("reset", "en") => "Reset".to_string(),
         ("reset_done", "en") => "Reset done".to_string(),
+        ("runs_out", "en") => "Runs out in".to_string(),
         ("included", "en") => "Included".to_string(),
-        ("ghcp_inline", "en") => "Inline".to_string(),
-        ("ghcp_chat", "en") => "Chat".to_string(),
-        ("ghcp_premium", "en") => "Premium".to_string(),
+        ("all_accounts", "en") => "All accounts".to_string(),
         ("more_platforms", "en") => "More platforms".to_string(),
         ("no_platform_selected", "en") => "No tray platforms selected".to_string(),
 
@@ -1444,10 +1080,9 @@ fn get_text(key: &str, lang: &str) -> String {
         ("loading", "ja") => "読み込み中...".to_string(),
         ("reset", "ja") => "リセット".to_string(),
         ("reset_done", "ja") => "リセット済み".to_string(),
-        ("included", "ja") => "含まれる".to_string(),
-        ("ghcp_inline", "ja") => "Inline".to_string(),
-        ("ghcp_chat", "ja") => "Chat".to_string(),
-        ("ghcp_premium", "ja") => "Premium".to_string(),
+        ("runs_out", "ja") => "枯渇まで".to_string(),
+        ("included", "ja") => "プランに含まれる".to_string(),
+        ("all_accounts", "ja") => "全アカウント".to_string(),
         ("more_platforms", "ja") => "その他のプラットフォーム".to_string(),
         ("no_platform_selected", "ja") => {
             "トレイに表示するプラットフォームがありません".to_string()
@@ -1462,10 +1097,9 @@ fn get_text(key: &str, lang: &str) -> String {
         ("loading", "ru") => "Загрузка...".to_string(),
         ("reset", "ru") => "Сброс".to_string(),
         ("reset_done", "ru") => "Сброс выполнен".to_string(),
+        ("runs_out", "ru") => "Закончится через".to_string(),
         ("included", "ru") => "Включено".to_string(),
-        ("ghcp_inline", "ru") => "Inline".to_string(),
-        ("ghcp_chat", "ru") => "Chat".to_string(),
-        ("ghcp_premium", "ru") => "Premium".to_string(),
+        ("all_accounts", "ru") => "Все аккаунты".to_string(),
         ("more_platforms", "ru") => "Другие платформы".to_string(),
         ("no_platform_selected", "ru") => "Платформы для трея не выбраны".to_string(),
 
@@ -1478,10 +1112,9 @@ fn get_text(key: &str, lang: &str) -> String {
         ("loading", _) => "Loading...".to_string(),
         ("reset", _) => "Reset".to_string(),
         ("reset_done", _) => "Reset done".to_string(),
+        ("runs_out", _) => "Runs out in".to_string(),
         ("included", _) => "Included".to_string(),
-        ("ghcp_inline", _) => "Inline".to_string(),
-        ("ghcp_chat", _) => "Chat".to_string(),
-        ("ghcp_premium", _) => "Premium".to_string(),
+        ("all_accounts", _) => "All accounts".to_string(),
         ("more_platforms", _) => "More platforms".to_string(),
         ("no_platform_selected", _) => "No tray platforms selected".to_string(),
 
@@ -4,6 +4,7 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use crate::utils::atomic_file;
 
 const TRAY_LAYOUT_FILE: &str = "tray_layout.json";
 
@@ -12,13 +13,15 @@ pub const PLATFORM_CODEX: &str = "codex";
 pub const PLATFORM_GITHUB_COPILOT: &str = "github-copilot";
 pub const PLATFORM_WINDSURF: &str = "windsurf";
 pub const PLATFORM_KIRO: &str = "kiro";
+pub const PLATFORM_CURSOR: &str = "cursor";
 
-pub const SUPPORTED_PLATFORM_IDS: [&str; 5] = [
+pub const SUPPORTED_PLATFORM_IDS: [&str; 6] = [
     PLATFORM_ANTIGRAVITY,
     PLATFORM_CODEX,
     PLATFORM_GITHUB_COPILOT,
     PLATFORM_WINDSURF,
     PLATFORM_KIRO,
+    PLATFORM_CURSOR,
 ];
 
 pub const SORT_MODE_AUTO: &str = "auto";
@@ -97,7 +100,11 @@ fn contains_platform(ids: &[String], target: &str) -> bool {
     ids.iter().any(|id| id == target)
 }
 
-fn normalize_tray_platforms(ids: &[String], raw_order_has_kiro: bool) -> Vec<String> {
+fn normalize_tray_platforms(
+    ids: &[String],
+    raw_order_has_kiro: bool,
+    raw_order_has_cursor: bool,
+) -> Vec<String> {
     let mut sanitized = sanitize_platform_ids(ids);
 
     // 兼容旧版本（无 Kiro）配置：
@@ -114,6 +121,16 @@ fn normalize_tray_platforms(ids: &[String], raw_order_has_kiro: bool) -> Vec<Str
         sanitized.push(PLATFORM_KIRO.to_string());
     }
 
+    // 兼容旧版本（无 Cursor）配置：规则同上，旧配置恰好为历史五平台时补上 Cursor。
+    let has_cursor = contains_platform(&sanitized, PLATFORM_CURSOR);
+    let is_legacy_five = sanitized.len() == 5
+        && has_legacy_all
+        && contains_platform(&sanitized, PLATFORM_KIRO);
+
+    if !raw_order_has_cursor && !has_cursor && is_legacy_five {
+        sanitized.push(PLATFORM_CURSOR.to_string());
+    }
+
     sanitized
 }
 
@@ -129,10 +146,18 @@ fn normalize_config(config: TrayLayoutConfig) -> TrayLayoutConfig {
         .ordered_platform_ids
         .iter()
         .any(|id| id.trim() == PLATFORM_KIRO);
+    let raw_order_has_cursor = config
+        .ordered_platform_ids
+        .iter()
+        .any(|id| id.trim() == PLATFORM_CURSOR);
     TrayLayoutConfig {
         sort_mode: normalize_sort_mode(&config.sort_mode),
         ordered_platform_ids: normalize_order(&config.ordered_platform_ids),
-        tray_platform_ids: normalize_tray_platforms(&config.tray_platform_ids, raw_order_has_kiro),
+        tray_platform_ids: normalize_tray_platforms(
+            &config.tray_platform_ids,
+            raw_order_has_kiro,
+            raw_order_has_cursor,
+        ),
     }
 }
 
@@ -171,6 +196,6 @@ pub fn save_tray_layout(
     let path = get_tray_layout_path()?;
     let content = serde_json::to_string_pretty(&normalized)
         .map_err(|e| format!("序列化托盘布局配置失败: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("保存托盘布局配置失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存托盘布局配置失败: {}", e))?;
     Ok(normalized)
 }
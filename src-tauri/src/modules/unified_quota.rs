@@ -0,0 +1,372 @@
+//! 跨平台统一配额模型
+//!
+//! 各平台的原始配额（Antigravity 模型百分比、Codex 时间窗口、Kiro/Cursor 额度用量、
+//! Copilot/Windsurf 令牌与快照）统一转换为 `UnifiedQuota`，托盘、预警、配额历史与 WebSocket 只读取此结构。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 配额的重置周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QuotaWindow {
+    /// 滚动窗口（如 Codex 5 小时 / 每周窗口）
+    Rolling { minutes: i64 },
+    /// 按账单周期（通常为自然月）重置
+    BillingCycle,
+}
+
+/// 单项配额
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaMetric {
+    /// 平台内稳定的指标标识，配额历史按此关联
+    pub id: String,
+    pub label: String,
+    /// 已用量 / 总量（平台只提供百分比时为空）
+    pub used: Option<f64>,
+    pub total: Option<f64>,
+    /// 剩余百分比 0-100
+    pub remaining_pct: i32,
+    pub window: Option<QuotaWindow>,
+    /// 下次重置时间（Unix 秒）
+    pub resets_at: Option<i64>,
+    /// 计划内包含、不限额度；此时 remaining_pct 固定为 100
+    #[serde(default)]
+    pub unlimited: bool,
+}
+
+impl QuotaMetric {
+    pub fn from_percent(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        remaining_pct: i32,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            used: None,
+            total: None,
+            remaining_pct: remaining_pct.clamp(0, 100),
+            window: None,
+            resets_at: None,
+            unlimited: false,
+        }
+    }
+
+    pub fn unlimited(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            unlimited: true,
+            ..Self::from_percent(id, label, 100)
+        }
+    }
+
+    /// 由用量与总量构造；总量缺失或不为正时返回 None
+    pub fn from_usage(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        used: Option<f64>,
+        total: Option<f64>,
+    ) -> Option<Self> {
+        let total = total.filter(|value| value.is_finite() && *value > 0.0)?;
+        let used = used.unwrap_or(0.0);
+        if !used.is_finite() {
+            return None;
+        }
+        let remaining = (total - used).max(0.0);
+        Some(Self {
+            used: Some(used),
+            total: Some(total),
+            ..Self::from_percent(id, label, clamp_percent(remaining / total * 100.0))
+        })
+    }
+
+    pub fn with_window(mut self, window: Option<QuotaWindow>) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_resets_at(mut self, resets_at: Option<i64>) -> Self {
+        self.resets_at = resets_at;
+        self
+    }
+}
+
+/// 账号的统一配额视图
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnifiedQuota {
+    pub metrics: Vec<QuotaMetric>,
+}
+
+impl UnifiedQuota {
+    pub fn new(metrics: Vec<QuotaMetric>) -> Self {
+        Self { metrics }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.metrics.is_empty()
+    }
+
+    /// 有限额度指标的平均剩余百分比；不限额度的指标不参与计算，全部不限额时返回 None
+    pub fn average_remaining(&self) -> Option<f64> {
+        let limited: Vec<i32> = self
+            .metrics
+            .iter()
+            .filter(|metric| !metric.unlimited)
+            .map(|metric| metric.remaining_pct)
+            .collect();
+        if limited.is_empty() {
+            return None;
+        }
+        let sum: i32 = limited.iter().sum();
+        Some(sum as f64 / limited.len() as f64)
+    }
+}
+
+pub fn clamp_percent(value: f64) -> i32 {
+    value.round().clamp(0.0, 100.0) as i32
+}
+
+/// Copilot 格式配额的指标名称（GitHub Copilot 与 Windsurf 共用同一份令牌/快照格式）
+pub struct CopilotMetricLabels {
+    pub completions: &'static str,
+    pub chat: &'static str,
+    pub premium: &'static str,
+}
+
+fn parse_token_map(token: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let prefix = token.split(':').next().unwrap_or(token);
+    for item in prefix.split(';') {
+        let mut parts = item.splitn(2, '=');
+        let key = parts.next().unwrap_or("").trim();
+        if key.is_empty() {
+            continue;
+        }
+        let value = parts.next().unwrap_or("").trim();
+        map.insert(key.to_string(), value.to_string());
+    }
+    map
+}
+
+fn parse_token_number(map: &HashMap<String, String>, key: &str) -> Option<f64> {
+    map.get(key)
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|value| value.is_finite())
+}
+
+fn get_json_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(num) => num.as_f64(),
+        Value::String(text) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+    .filter(|value| value.is_finite())
+}
+
+type Snapshot = serde_json::Map<String, Value>;
+
+fn get_snapshot<'a>(snapshots: Option<&'a Value>, key: &str) -> Option<&'a Snapshot> {
+    let snapshots = snapshots.and_then(Value::as_object)?;
+    snapshots
+        .get(key)
+        .or_else(|| match key {
+            "premium_interactions" => snapshots.get("premium_models"),
+            _ => None,
+        })
+        .and_then(Value::as_object)
+}
+
+/// 快照标记为无限额度（unlimited 或 entitlement 为负数）
+fn is_unlimited(snapshot: &Snapshot) -> bool {
+    snapshot.get("unlimited").and_then(Value::as_bool) == Some(true)
+        || snapshot
+            .get("entitlement")
+            .and_then(get_json_number)
+            .is_some_and(|value| value < 0.0)
+}
+
+/// 优先使用快照；受限用户没有快照时由 `limited_quotas` 的剩余量与令牌中的总量（cq / tq）计算
+fn copilot_metric(
+    id: &str,
+    label: &str,
+    snapshot: Option<&Snapshot>,
+    limited_remaining: Option<f64>,
+    fallback_total: Option<f64>,
+) -> Option<QuotaMetric> {
+    if snapshot.is_some_and(is_unlimited) {
+        return Some(QuotaMetric::unlimited(id, label));
+    }
+
+    let entitlement = snapshot
+        .and_then(|data| data.get("entitlement"))
+        .and_then(get_json_number)
+        .filter(|value| *value > 0.0);
+    let percent_remaining = snapshot
+        .and_then(|data| data.get("percent_remaining"))
+        .and_then(get_json_number);
+    let remaining = snapshot
+        .and_then(|data| data.get("remaining"))
+        .and_then(get_json_number)
+        .or_else(|| Some(entitlement? * percent_remaining? / 100.0))
+        .or(limited_remaining);
+
+    let total = entitlement.or(fallback_total).or(remaining);
+    if let (Some(total), Some(remaining)) = (total, remaining) {
+        if let Some(metric) =
+            QuotaMetric::from_usage(id, label, Some(total - remaining.max(0.0)), Some(total))
+        {
+            return Some(metric);
+        }
+    }
+    percent_remaining.map(|pct| QuotaMetric::from_percent(id, label, clamp_percent(pct)))
+}
+
+/// 解析 Copilot 格式的配额（补全 / 对话 / 高级请求），重置时间缺失时回退到令牌中的 rd
+pub fn copilot_metrics(
+    token: &str,
+    plan: Option<&str>,
+    limited_quotas: Option<&Value>,
+    quota_snapshots: Option<&Value>,
+    labels: &CopilotMetricLabels,
+    resets_at: Option<i64>,
+) -> Vec<QuotaMetric> {
+    let token_map = parse_token_map(token);
+    let resets_at = resets_at.or_else(|| {
+        parse_token_number(&token_map, "rd")
+            .map(|value| value.floor() as i64)
+            .filter(|value| *value > 0)
+    });
+    let sku = token_map
+        .get("sku")
+        .map(|value| value.to_lowercase())
+        .unwrap_or_default();
+    let is_free_limited = sku.contains("free_limited")
+        || sku.contains("no_auth_limited")
+        || plan.is_some_and(|value| value.to_lowercase().contains("free_limited"));
+
+    let limited = limited_quotas.and_then(Value::as_object);
+    let limited_remaining = |key: &str| {
+        limited
+            .and_then(|obj| obj.get(key))
+            .and_then(get_json_number)
+    };
+    let chat_remaining = limited_remaining("chat");
+    // 免费受限用户的令牌不带 tq，对话额度固定为 500
+    let chat_total = parse_token_number(&token_map, "tq")
+        .or_else(|| chat_remaining.filter(|_| is_free_limited).map(|_| 500.0));
+
+    let metrics = [
+        copilot_metric(
+            "completions",
+            labels.completions,
+            get_snapshot(quota_snapshots, "completions"),
+            limited_remaining("completions"),
+            parse_token_number(&token_map, "cq"),
+        ),
+        copilot_metric(
+            "chat",
+            labels.chat,
+            get_snapshot(quota_snapshots, "chat"),
+            chat_remaining,
+            chat_total,
+        ),
+        copilot_metric(
+            "premium_interactions",
+            labels.premium,
+            get_snapshot(quota_snapshots, "premium_interactions"),
+            None,
+            None,
+        ),
+    ];
+
+    metrics
+        .into_iter()
+        .flatten()
+        .map(|metric| {
+            metric
+                .with_window(Some(QuotaWindow::BillingCycle))
+                .with_resets_at(resets_at)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABELS: CopilotMetricLabels = CopilotMetricLabels {
+        completions: "Inline Suggestions",
+        chat: "Chat Messages",
+        premium: "Premium Interactions",
+    };
+
+    #[test]
+    fn usage_metrics_compute_remaining_percent() {
+        let metric =
+            QuotaMetric::from_usage("credits", "Credits", Some(120.0), Some(500.0)).unwrap();
+        assert_eq!(metric.remaining_pct, 76);
+        assert_eq!((metric.used, metric.total), (Some(120.0), Some(500.0)));
+        assert!(QuotaMetric::from_usage("credits", "Credits", Some(1.0), Some(0.0)).is_none());
+        assert!(QuotaMetric::from_usage("credits", "Credits", Some(1.0), None).is_none());
+    }
+
+    #[test]
+    fn average_remaining_skips_unlimited_metrics() {
+        let quota = UnifiedQuota::new(vec![
+            QuotaMetric::unlimited("chat", "Chat"),
+            QuotaMetric::from_percent("premium", "Premium", 20),
+            QuotaMetric::from_percent("credits", "Credits", 40),
+        ]);
+        assert_eq!(quota.average_remaining(), Some(30.0));
+
+        let unlimited = UnifiedQuota::new(vec![QuotaMetric::unlimited("chat", "Chat")]);
+        assert_eq!(unlimited.average_remaining(), None);
+        assert_eq!(UnifiedQuota::new(Vec::new()).average_remaining(), None);
+    }
+
+    #[test]
+    fn parses_copilot_limited_and_premium_quotas() {
+        let limited = serde_json::json!({ "completions": 1500, "chat": "10" });
+        let snapshots = serde_json::json!({
+            "premium_interactions": { "percent_remaining": 42.4, "entitlement": 300, "remaining": 127 }
+        });
+        let metrics = copilot_metrics(
+            "tid=abc;cq=2000;tq=50:sig",
+            None,
+            Some(&limited),
+            Some(&snapshots),
+            &LABELS,
+            Some(1_700_000_000),
+        );
+
+        let ids: Vec<&str> = metrics.iter().map(|metric| metric.id.as_str()).collect();
+        assert_eq!(ids, vec!["completions", "chat", "premium_interactions"]);
+        assert_eq!(metrics[0].remaining_pct, 75);
+        assert_eq!(metrics[1].remaining_pct, 20);
+        assert_eq!(metrics[1].used, Some(40.0));
+        assert_eq!(metrics[2].remaining_pct, 42);
+        assert_eq!(metrics[2].used, Some(173.0));
+        assert!(metrics
+            .iter()
+            .all(|metric| metric.resets_at == Some(1_700_000_000)
+                && metric.window == Some(QuotaWindow::BillingCycle)));
+
+        let unlimited = serde_json::json!({ "premium_models": { "unlimited": true } });
+        let metrics = copilot_metrics(
+            "sku=free_limited_copilot;rd=1700000000",
+            None,
+            Some(&serde_json::json!({ "chat": 125 })),
+            Some(&unlimited),
+            &LABELS,
+            None,
+        );
+        let ids: Vec<&str> = metrics.iter().map(|metric| metric.id.as_str()).collect();
+        assert_eq!(ids, vec!["chat", "premium_interactions"]);
+        assert_eq!(metrics[0].remaining_pct, 25);
+        assert_eq!(metrics[1].remaining_pct, 100);
+        assert!(metrics[1].unlimited);
+        assert!(!metrics[0].unlimited);
+        assert_eq!(metrics[1].resets_at, Some(1_700_000_000));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::modules::logger;
+use crate::utils::atomic_file;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -250,7 +251,7 @@ pub fn save_update_settings(settings: &UpdateSettings) -> Result<(), String> {
     let content = serde_json::to_string_pretty(settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
-    std::fs::write(&settings_path, content)
+    atomic_file::write(&settings_path, content)
         .map_err(|e| format!("Failed to write settings file: {}", e))
 }
 
@@ -1,4 +1,5 @@
 use crate::modules;
+use crate::utils::atomic_file;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -53,15 +54,11 @@ pub fn load_history() -> Result<Vec<WakeupHistoryItem>, String> {
 /// 保存唤醒历史记录
 fn save_history(items: &[WakeupHistoryItem]) -> Result<(), String> {
     let path = history_path()?;
-    let data_dir = modules::account::get_data_dir()?;
-    let temp_path = data_dir.join(format!("{}.tmp", HISTORY_FILE));
 
     let content =
         serde_json::to_string_pretty(items).map_err(|e| format!("序列化唤醒历史失败: {}", e))?;
 
-    fs::write(&temp_path, content).map_err(|e| format!("写入临时历史文件失败: {}", e))?;
-
-    fs::rename(temp_path, path).map_err(|e| format!("替换历史文件失败: {}", e))
+    atomic_file::write(&path, content).map_err(|e| format!("写入历史文件失败: {}", e))
 }
 
 /// 添加历史记录（自动去重、限制数量）
@@ -8,6 +8,8 @@ use tauri::{AppHandle, Emitter};
 use tokio::time::sleep;
 
 use crate::modules;
+use crate::modules::platform::QuotaMetric;
+use crate::modules::quota_events;
 
 const DEFAULT_PROMPT: &str = "hi";
 const RESET_TRIGGER_COOLDOWN_MS: i64 = 10 * 60 * 1000;
@@ -78,9 +80,11 @@ struct ScheduleConfigNormalized {
 
 #[derive(Default, Debug, Clone)]
 struct ResetState {
-    last_reset_trigger_timestamps: HashMap<String, String>,
+    /// 模型 -> 触发唤醒时的重置时间（Unix 秒）
+    last_reset_trigger_resets_at: HashMap<String, i64>,
     last_reset_trigger_at: HashMap<String, i64>,
-    last_reset_remaining: HashMap<String, i32>,
+    /// 账号+模型 -> 上一次观察到的配额
+    last_metrics: HashMap<String, QuotaMetric>,
 }
 
 #[derive(Default, Clone)]
@@ -187,7 +191,7 @@ fn parse_time_to_minutes(value: &str) -> Option<i32> {
     Some(h * 60 + m)
 }
 
-fn is_in_time_window(start: Option<&String>, end: Option<&String>, now: DateTime<Local>) -> bool {
+pub fn is_in_time_window(start: Option<&String>, end: Option<&String>, now: DateTime<Local>) -> bool {
     let Some(start) = start else {
         return true;
     };
@@ -376,60 +380,44 @@ fn normalize_max_tokens(value: i32) -> u32 {
     }
 }
 
+/// 配额重置判断与配额变化事件共用 `quota_events::is_reset`；首次观察到满额也视为可唤醒
 fn should_trigger_on_reset(
     state: &mut ResetState,
     model_key: &str,
-    reset_at: &str,
-    remaining_percent: i32,
+    sample_key: &str,
+    current: &QuotaMetric,
 ) -> bool {
-    if remaining_percent < 100 {
-        state
-            .last_reset_remaining
-            .insert(model_key.to_string(), remaining_percent);
+    let reset = match state
+        .last_metrics
+        .insert(sample_key.to_string(), current.clone())
+    {
+        Some(previous) => quota_events::is_reset(&previous, current),
+        None => current.remaining_pct >= 100,
+    };
+    if !reset {
         return false;
     }
 
     let now = chrono::Utc::now().timestamp_millis();
-    if let Some(last_reset_at) = state.last_reset_trigger_timestamps.get(model_key) {
-        if let Ok(last_reset_time) =
-            DateTime::parse_from_rfc3339(last_reset_at).map(|dt| dt.timestamp_millis())
-        {
-            let safe_time = last_reset_time + RESET_SAFETY_MARGIN_MS;
-            if now < safe_time {
-                state
-                    .last_reset_remaining
-                    .insert(model_key.to_string(), remaining_percent);
-                return false;
-            }
+    if let Some(last_resets_at) = state.last_reset_trigger_resets_at.get(model_key) {
+        if now < last_resets_at * 1000 + RESET_SAFETY_MARGIN_MS {
+            return false;
         }
     }
-
     if let Some(last_trigger_at) = state.last_reset_trigger_at.get(model_key) {
         if now - *last_trigger_at < RESET_TRIGGER_COOLDOWN_MS {
-            state
-                .last_reset_remaining
-                .insert(model_key.to_string(), remaining_percent);
             return false;
         }
     }
-
-    if state.last_reset_trigger_timestamps.get(model_key) == Some(&reset_at.to_string()) {
-        state
-            .last_reset_remaining
-            .insert(model_key.to_string(), remaining_percent);
-        return false;
-    }
-
-    state
-        .last_reset_remaining
-        .insert(model_key.to_string(), remaining_percent);
     true
 }
 
-fn mark_reset_triggered(state: &mut ResetState, model_key: &str, reset_at: &str) {
-    state
-        .last_reset_trigger_timestamps
-        .insert(model_key.to_string(), reset_at.to_string());
+fn mark_reset_triggered(state: &mut ResetState, model_key: &str, resets_at: Option<i64>) {
+    if let Some(resets_at) = resets_at {
+        state
+            .last_reset_trigger_resets_at
+            .insert(model_key.to_string(), resets_at);
+    }
     state
         .last_reset_trigger_at
         .insert(model_key.to_string(), chrono::Utc::now().timestamp_millis());
@@ -521,22 +509,14 @@ async fn handle_quota_reset_task(app: &AppHandle, task: &WakeupTask, now: DateTi
             .or_insert_with(ResetState::default);
 
         let mut models_to_trigger: HashSet<String> = HashSet::new();
-        for model_id in &task.schedule.selected_models {
-            for account in &selected_accounts {
-                let quota_models = account
-                    .quota
-                    .as_ref()
-                    .map(|q| q.models.as_slice())
-                    .unwrap_or(&[]);
-                if let Some(quota) = quota_models.iter().find(|item| item.name == *model_id) {
-                    if should_trigger_on_reset(
-                        reset_state,
-                        model_id,
-                        &quota.reset_time,
-                        quota.percentage,
-                    ) {
+        for account in &selected_accounts {
+            let metrics = modules::account::unified_quota(account).metrics;
+            for model_id in &task.schedule.selected_models {
+                if let Some(metric) = metrics.iter().find(|metric| metric.id == *model_id) {
+                    let sample_key = format!("{}|{}", account.id, model_id);
+                    if should_trigger_on_reset(reset_state, model_id, &sample_key, metric) {
                         models_to_trigger.insert(model_id.clone());
-                        mark_reset_triggered(reset_state, model_id, &quota.reset_time);
+                        mark_reset_triggered(reset_state, model_id, metric.resets_at);
                     }
                 }
             }
@@ -671,15 +651,16 @@ async fn run_task_with_models(
         last_run_at: chrono::Utc::now().timestamp_millis(),
         records: history,
     };
+    modules::websocket::broadcast_wakeup_result(&payload);
     let _ = app.emit("wakeup://task-result", payload);
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct WakeupTaskResultPayload {
-    task_id: String,
-    last_run_at: i64,
-    records: Vec<modules::wakeup_history::WakeupHistoryItem>,
+pub struct WakeupTaskResultPayload {
+    pub task_id: String,
+    pub last_run_at: i64,
+    pub records: Vec<modules::wakeup_history::WakeupHistoryItem>,
 }
 
 // (no local helpers)
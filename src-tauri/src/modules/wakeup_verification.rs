@@ -1,4 +1,6 @@
 use crate::modules;
+use crate::modules::schema_migration::{self, Migration, SchemaSpec, VersionEncoding};
+use crate::utils::atomic_file;
 use futures::stream::{FuturesUnordered, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -12,6 +14,19 @@ const WAKEUP_ERROR_JSON_PREFIX: &str = "AG_WAKEUP_ERROR_JSON:";
 const WAKEUP_VERIFICATION_STATE_VERSION: u8 = 2;
 const MAX_HISTORY_BATCHES: usize = 100;
 
+const STATE_SCHEMA: SchemaSpec = SchemaSpec {
+    name: WAKEUP_VERIFICATION_STATE_FILE,
+    version_field: "version",
+    encoding: VersionEncoding::Integer,
+    current: WAKEUP_VERIFICATION_STATE_VERSION as u32,
+    baseline: WAKEUP_VERIFICATION_STATE_VERSION as u32,
+    migrations: &[Migration {
+        from: 1,
+        description: "补齐批次历史 history",
+        apply: migrate_state_v1_to_v2,
+    }],
+};
+
 const STATUS_IDLE: &str = "idle";
 const STATUS_SUCCESS: &str = "success";
 const STATUS_VERIFICATION_REQUIRED: &str = "verification_required";
@@ -104,6 +119,14 @@ fn default_state_file_version() -> u8 {
     WAKEUP_VERIFICATION_STATE_VERSION
 }
 
+fn migrate_state_v1_to_v2(value: &mut serde_json::Value) -> Result<(), String> {
+    let object = value.as_object_mut().ok_or("状态文件不是 JSON 对象")?;
+    object
+        .entry("history")
+        .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+    Ok(())
+}
+
 fn state_file_path() -> Result<PathBuf, String> {
     let data_dir = modules::account::get_data_dir()?;
     Ok(data_dir.join(WAKEUP_VERIFICATION_STATE_FILE))
@@ -128,6 +151,7 @@ fn load_state_file_unlocked() -> Result<WakeupVerificationStateFile, String> {
         });
     }
 
+    let content = schema_migration::upgrade_on_load(&STATE_SCHEMA, &path, content);
     let mut parsed: WakeupVerificationStateFile =
         serde_json::from_str(&content).map_err(|e| format!("解析验证状态文件失败: {}", e))?;
     if parsed.version == 0 {
@@ -138,8 +162,7 @@ fn load_state_file_unlocked() -> Result<WakeupVerificationStateFile, String> {
 
 fn save_state_file_unlocked(state: &WakeupVerificationStateFile) -> Result<(), String> {
     let path = state_file_path()?;
-    let data_dir = modules::account::get_data_dir()?;
-    let temp_path = data_dir.join(format!("{}.tmp", WAKEUP_VERIFICATION_STATE_FILE));
+    schema_migration::ensure_writable(&STATE_SCHEMA, &path)?;
 
     let payload = WakeupVerificationStateFile {
         version: WAKEUP_VERIFICATION_STATE_VERSION,
@@ -149,8 +172,7 @@ fn save_state_file_unlocked(state: &WakeupVerificationStateFile) -> Result<(), S
 
     let content =
         serde_json::to_string_pretty(&payload).map_err(|e| format!("序列化验证状态失败: {}", e))?;
-    fs::write(&temp_path, content).map_err(|e| format!("写入验证状态临时文件失败: {}", e))?;
-    fs::rename(temp_path, path).map_err(|e| format!("替换验证状态文件失败: {}", e))
+    atomic_file::write(&path, content).map_err(|e| format!("写入验证状态文件失败: {}", e))
 }
 
 pub fn load_state() -> Result<Vec<WakeupVerificationStateItem>, String> {
@@ -7,19 +7,36 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio_tungstenite::tungstenite::Message;
 
-use super::config::{get_preferred_port, init_server_status, PORT_RANGE};
+use super::config::{get_preferred_port, init_server_status, UserConfig, PORT_RANGE};
+use crate::modules::account::QuotaAlertPayload;
+use crate::modules::instance_manager::InstanceLifecycleEvent;
+use crate::modules::platform::{self, PlatformAccount};
+use crate::modules::quota_cache;
+use crate::modules::quota_events::QuotaEvent;
+use crate::modules::refresh_executor::RefreshReport;
+use crate::modules::refresh_scheduler::{self, RefreshTrigger};
+use crate::modules::wakeup_scheduler::WakeupTaskResultPayload;
+use crate::modules::ws_auth::{self, PairedClient, Session, WsScope};
+use crate::modules::ws_topics::{RetainedEvents, Subscriptions, WsTopic};
+
+type WsSink =
+    futures_util::stream::SplitSink<tokio_tungstenite::WebSocketStream<TcpStream>, Message>;
 
 /// 消息类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum WsMessage {
     // ============ 事件通知（Tools -> 扩展） ============
-    /// 服务就绪
+    /// 服务就绪（连接后需先认证才能发起请求和接收事件）
     #[serde(rename = "event.ready")]
-    Ready { version: String },
+    Ready {
+        version: String,
+        #[serde(default)]
+        auth_required: bool,
+    },
 
     /// 数据已变更，请刷新
     #[serde(rename = "event.data_changed")]
@@ -37,26 +54,116 @@ pub enum WsMessage {
     #[serde(rename = "event.switch_error")]
     SwitchError { message: String },
 
+    /// 其他平台账号切换完成（Antigravity 仍使用 event.account_switched）
+    #[serde(rename = "event.platform_account_switched")]
+    PlatformAccountSwitched {
+        platform: String,
+        account_id: String,
+        message: String,
+    },
+
     /// 唤醒功能互斥开关
     #[serde(rename = "event.wakeup_override")]
     WakeupOverride { enabled: bool },
 
+    /// 配额变化（重置、越过阈值、无权限、刷新错误）
+    #[serde(rename = "event.quota_changed")]
+    QuotaChanged(QuotaEvent),
+
+    /// 平台配额快照（订阅 quota 主题时回放）
+    #[serde(rename = "event.quota_snapshot")]
+    QuotaSnapshot(PlatformQuotaInfo),
+
+    /// 多开实例生命周期变化
+    #[serde(rename = "event.instance_changed")]
+    InstanceChanged(InstanceLifecycleEvent),
+
+    /// 唤醒任务执行结果
+    #[serde(rename = "event.wakeup_task_result")]
+    WakeupTaskResult(WakeupTaskResultPayload),
+
+    /// 配额预警
+    #[serde(rename = "event.quota_alert")]
+    QuotaAlert(QuotaAlertPayload),
+
+    /// 用户配置已变更
+    #[serde(rename = "event.config_changed")]
+    ConfigChanged { config: UserConfig },
+
     // ============ 请求（扩展 -> Tools） ============
-    /// 请求获取账号列表
+    /// 认证：共享密钥（server.json 的 auth_secret）或配对令牌二选一；scopes 为空时申请全部权限
+    #[serde(rename = "request.authenticate")]
+    Authenticate {
+        request_id: String,
+        client_name: Option<String>,
+        secret: Option<String>,
+        token: Option<String>,
+        scopes: Option<Vec<WsScope>>,
+    },
+
+    /// 发起配对，等待用户在桌面端核对配对码后批准
+    #[serde(rename = "request.pair")]
+    Pair {
+        request_id: String,
+        client_name: String,
+        scopes: Option<Vec<WsScope>>,
+    },
+
+    /// 订阅事件主题，之后只推送已订阅主题的事件；replay 默认开启，回放各主题的最新状态
+    #[serde(rename = "request.subscribe")]
+    Subscribe {
+        request_id: String,
+        topics: Vec<WsTopic>,
+        replay: Option<bool>,
+    },
+
+    /// 取消订阅事件主题
+    #[serde(rename = "request.unsubscribe")]
+    Unsubscribe {
+        request_id: String,
+        topics: Vec<WsTopic>,
+    },
+
+    /// 请求获取账号列表（platform 为空时为 Antigravity，其他平台返回 response.platform_accounts）
     #[serde(rename = "request.get_accounts")]
-    GetAccounts { request_id: String },
+    GetAccounts {
+        request_id: String,
+        platform: Option<String>,
+    },
 
     /// 请求获取账号列表（包含 Token）
     #[serde(rename = "request.get_accounts_with_tokens")]
     GetAccountsWithTokens { request_id: String },
 
-    /// 请求获取当前账号
+    /// 请求获取当前账号（其他平台返回 response.platform_current_account，含配额）
     #[serde(rename = "request.get_current_account")]
-    GetCurrentAccount { request_id: String },
+    GetCurrentAccount {
+        request_id: String,
+        platform: Option<String>,
+    },
 
-    /// 请求切换账号（真正的切换）
+    /// 请求切换账号（真正的切换）；带 request_id 时额外回复切换结果
     #[serde(rename = "request.switch_account")]
-    SwitchAccount { account_id: String },
+    SwitchAccount {
+        account_id: String,
+        platform: Option<String>,
+        request_id: Option<String>,
+    },
+
+    /// 请求刷新账号配额（account_id 为空时刷新该平台全部账号）
+    #[serde(rename = "request.refresh")]
+    Refresh {
+        request_id: String,
+        platform: Option<String>,
+        account_id: Option<String>,
+    },
+
+    /// 请求获取统一配额（platform 为空时返回所有平台）
+    #[serde(rename = "request.get_unified_quotas")]
+    GetUnifiedQuotas {
+        request_id: String,
+        platform: Option<String>,
+    },
 
     /// 请求设置语言
     #[serde(rename = "request.set_language")]
@@ -93,6 +200,39 @@ pub enum WsMessage {
     Pong,
 
     // ============ 响应（Tools -> 扩展） ============
+    /// 认证成功
+    #[serde(rename = "response.authenticated")]
+    Authenticated {
+        request_id: String,
+        client_id: Option<String>,
+        scopes: Vec<WsScope>,
+    },
+
+    /// 配对请求已创建，客户端应展示配对码供用户核对
+    #[serde(rename = "response.pairing_pending")]
+    PairingPending {
+        request_id: String,
+        pairing_id: String,
+        code: String,
+        expires_at: i64,
+    },
+
+    /// 配对已批准，连接随即获得授权；token 只返回这一次，客户端需自行保存用于之后认证
+    #[serde(rename = "response.paired")]
+    Paired {
+        request_id: String,
+        client_id: String,
+        token: String,
+        scopes: Vec<WsScope>,
+    },
+
+    /// 订阅变更后当前生效的主题
+    #[serde(rename = "response.subscriptions")]
+    SubscriptionsResponse {
+        request_id: String,
+        topics: Vec<WsTopic>,
+    },
+
     /// 账号列表响应
     #[serde(rename = "response.accounts")]
     AccountsResponse {
@@ -116,6 +256,38 @@ pub enum WsMessage {
         account: Option<AccountInfo>,
     },
 
+    /// 平台账号列表响应
+    #[serde(rename = "response.platform_accounts")]
+    PlatformAccountsResponse {
+        request_id: String,
+        platform: String,
+        accounts: Vec<PlatformAccount>,
+        current_account_id: Option<String>,
+    },
+
+    /// 平台当前账号响应
+    #[serde(rename = "response.platform_current_account")]
+    PlatformCurrentAccountResponse {
+        request_id: String,
+        platform: String,
+        account: Option<PlatformAccount>,
+    },
+
+    /// 刷新完成响应（全量刷新时附带逐账号结果统计）
+    #[serde(rename = "response.refreshed")]
+    RefreshResponse {
+        request_id: String,
+        platform: String,
+        report: Option<RefreshReport>,
+    },
+
+    /// 统一配额响应
+    #[serde(rename = "response.unified_quotas")]
+    UnifiedQuotasResponse {
+        request_id: String,
+        platforms: Vec<PlatformQuotaInfo>,
+    },
+
     /// 操作成功响应
     #[serde(rename = "response.success")]
     SuccessResponse { request_id: String, message: String },
@@ -157,18 +329,41 @@ pub struct AccountTokenInfo {
     pub project_id: Option<String>,
 }
 
+/// 单个平台的统一配额（用于 WebSocket 传输）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformQuotaInfo {
+    pub platform: String,
+    pub current_account_id: Option<String>,
+    pub accounts: Vec<PlatformAccount>,
+}
+
 /// 已连接的客户端信息
 #[derive(Debug)]
 struct Client {
     _addr: SocketAddr,
+    /// 认证状态与权限
+    session: Session,
+    /// 事件订阅
+    subscriptions: Subscriptions,
+    /// 定向发给该连接的消息（配对结果等）
+    direct: mpsc::UnboundedSender<String>,
+}
+
+/// 待广播的事件
+#[derive(Debug, Clone)]
+struct Outgoing {
+    topic: Option<WsTopic>,
+    json: String,
 }
 
 /// WebSocket 服务状态
 pub struct WsServer {
     /// 广播发送器
-    tx: broadcast::Sender<String>,
+    tx: broadcast::Sender<Outgoing>,
     /// 已连接的客户端
     clients: Arc<RwLock<HashMap<SocketAddr, Client>>>,
+    /// 各主题保留的最新事件，订阅时回放
+    retained: std::sync::Mutex<RetainedEvents>,
 }
 
 impl WsServer {
@@ -178,14 +373,81 @@ impl WsServer {
         Self {
             tx,
             clients: Arc::new(RwLock::new(HashMap::new())),
+            retained: std::sync::Mutex::new(RetainedEvents::default()),
+        }
+    }
+
+    /// 连接是否应收到该主题的事件（事件含账号信息，需要读取账号权限）
+    async fn accepts(&self, addr: SocketAddr, topic: Option<&WsTopic>) -> bool {
+        self.clients
+            .read()
+            .await
+            .get(&addr)
+            .map(|client| {
+                client.session.allows(WsScope::ReadAccounts) && client.subscriptions.accepts(topic)
+            })
+            .unwrap_or(false)
+    }
+
+    /// 修改连接的订阅，返回修改后生效的主题
+    async fn update_subscriptions(
+        &self,
+        addr: SocketAddr,
+        apply: impl FnOnce(&mut Subscriptions),
+    ) -> Vec<WsTopic> {
+        match self.clients.write().await.get_mut(&addr) {
+            Some(client) => {
+                apply(&mut client.subscriptions);
+                client.subscriptions.topics()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn retained_events(&self, topics: &[WsTopic]) -> Vec<String> {
+        self.retained
+            .lock()
+            .map(|retained| retained.replay(topics))
+            .unwrap_or_default()
+    }
+
+    async fn session(&self, addr: SocketAddr) -> Session {
+        self.clients
+            .read()
+            .await
+            .get(&addr)
+            .map(|client| client.session.clone())
+            .unwrap_or_default()
+    }
+
+    async fn set_session(&self, addr: SocketAddr, session: Session) {
+        if let Some(client) = self.clients.write().await.get_mut(&addr) {
+            client.session = session;
+        }
+    }
+
+    /// 定向发送给单个连接
+    async fn send_to(&self, addr: SocketAddr, message: &WsMessage) {
+        let Ok(json) = serde_json::to_string(message) else {
+            return;
+        };
+        if let Some(client) = self.clients.read().await.get(&addr) {
+            let _ = client.direct.send(json);
         }
     }
 
-    /// 广播消息给所有客户端
+    /// 广播消息给订阅了对应主题的客户端
     pub fn broadcast(&self, message: WsMessage) {
-        if let Ok(json) = serde_json::to_string(&message) {
-            let _ = self.tx.send(json);
+        let Ok(json) = serde_json::to_string(&message) else {
+            return;
+        };
+        let topic = topic_of(&message);
+        if let (Some(topic), Some(key)) = (&topic, retain_key(&message)) {
+            if let Ok(mut retained) = self.retained.lock() {
+                retained.retain(topic.clone(), key, json.clone());
+            }
         }
+        let _ = self.tx.send(Outgoing { topic, json });
     }
 }
 
@@ -247,6 +509,92 @@ pub fn broadcast_wakeup_override(enabled: bool) {
     crate::modules::logger::log_info(&format!("[WS] 广播唤醒互斥: enabled={}", enabled));
 }
 
+/// 广播配额变化事件
+pub fn broadcast_quota_event(event: &QuotaEvent) {
+    let server = get_server();
+    server.broadcast(WsMessage::QuotaChanged(event.clone()));
+}
+
+/// 广播实例生命周期变化
+pub fn broadcast_instance_changed(event: InstanceLifecycleEvent) {
+    get_server().broadcast(WsMessage::InstanceChanged(event));
+}
+
+/// 广播唤醒任务执行结果
+pub fn broadcast_wakeup_result(payload: &WakeupTaskResultPayload) {
+    get_server().broadcast(WsMessage::WakeupTaskResult(payload.clone()));
+}
+
+/// 广播配额预警
+pub fn broadcast_quota_alert(payload: &QuotaAlertPayload) {
+    get_server().broadcast(WsMessage::QuotaAlert(payload.clone()));
+}
+
+/// 广播用户配置变更
+pub fn broadcast_config_changed(config: &UserConfig) {
+    get_server().broadcast(WsMessage::ConfigChanged {
+        config: config.clone(),
+    });
+}
+
+/// 批准配对：授权该连接并把令牌回传给客户端
+pub async fn approve_pairing(
+    pairing_id: &str,
+    scopes: Option<Vec<WsScope>>,
+) -> Result<PairedClient, String> {
+    let approval = ws_auth::approve_pairing(pairing_id, scopes)?;
+    let server = get_server();
+    if let Some(addr) = approval.request.socket {
+        let session = Session {
+            client_id: Some(approval.client.id.clone()),
+            client_name: Some(approval.client.name.clone()),
+            scopes: approval.client.scopes.clone(),
+        };
+        server.set_session(addr, session).await;
+        server
+            .send_to(
+                addr,
+                &WsMessage::Paired {
+                    request_id: approval.request.request_id.clone(),
+                    client_id: approval.client.id.clone(),
+                    token: approval.token.clone(),
+                    scopes: approval.client.scopes.iter().copied().collect(),
+                },
+            )
+            .await;
+    }
+    Ok(approval.client)
+}
+
+/// 拒绝配对并通知等待中的客户端
+pub async fn reject_pairing(pairing_id: &str) -> Result<(), String> {
+    let request = ws_auth::reject_pairing(pairing_id)?;
+    if let Some(addr) = request.socket {
+        get_server()
+            .send_to(
+                addr,
+                &WsMessage::ErrorResponse {
+                    request_id: request.request_id,
+                    error: "配对请求已被拒绝".to_string(),
+                },
+            )
+            .await;
+    }
+    Ok(())
+}
+
+/// 撤销已配对客户端，已建立的连接立即失去全部权限
+pub async fn revoke_client(client_id: &str) -> Result<(), String> {
+    ws_auth::revoke_client(client_id)?;
+    let mut clients = get_server().clients.write().await;
+    for client in clients.values_mut() {
+        if client.session.client_id.as_deref() == Some(client_id) {
+            client.session = Session::default();
+        }
+    }
+    Ok(())
+}
+
 /// 启动 WebSocket 服务（支持动态端口尝试）
 pub async fn start_server() {
     // 从用户配置获取首选端口
@@ -320,10 +668,19 @@ async fn handle_connection(server: Arc<WsServer>, stream: TcpStream, addr: Socke
 
     crate::modules::logger::log_info(&format!("[WS] 新连接: {}", addr));
 
-    // 添加客户端
+    // 添加客户端（未认证）
+    let (direct_tx, mut direct_rx) = mpsc::unbounded_channel::<String>();
     {
         let mut clients = server.clients.write().await;
-        clients.insert(addr, Client { _addr: addr });
+        clients.insert(
+            addr,
+            Client {
+                _addr: addr,
+                session: Session::default(),
+                subscriptions: Subscriptions::default(),
+                direct: direct_tx,
+            },
+        );
     }
 
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
@@ -331,6 +688,7 @@ async fn handle_connection(server: Arc<WsServer>, stream: TcpStream, addr: Socke
     // 发送 Ready 消息
     let ready_msg = WsMessage::Ready {
         version: env!("CARGO_PKG_VERSION").to_string(),
+        auth_required: true,
     };
     if let Ok(json) = serde_json::to_string(&ready_msg) {
         let _ = ws_sender.send(Message::Text(json.into())).await;
@@ -345,7 +703,7 @@ async fn handle_connection(server: Arc<WsServer>, stream: TcpStream, addr: Socke
             msg = ws_receiver.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        if let Err(e) = handle_client_message(&server, &mut ws_sender, &text).await {
+                        if let Err(e) = handle_client_message(&server, &mut ws_sender, addr, &text).await {
                             crate::modules::logger::log_error(&format!("[WS] 处理消息失败: {}", e));
                         }
                     }
@@ -361,14 +719,23 @@ async fn handle_connection(server: Arc<WsServer>, stream: TcpStream, addr: Socke
                     _ => {}
                 }
             }
-            // 发送广播消息
+            // 发送广播消息（只推送给有读取账号权限且订阅了该主题的连接）
             msg = broadcast_rx.recv() => {
-                if let Ok(json) = msg {
+                if let Ok(Outgoing { topic, json }) = msg {
+                    if !server.accepts(addr, topic.as_ref()).await {
+                        continue;
+                    }
                     if ws_sender.send(Message::Text(json.into())).await.is_err() {
                         break;
                     }
                 }
             }
+            // 发送定向消息
+            Some(json) = direct_rx.recv() => {
+                if ws_sender.send(Message::Text(json.into())).await.is_err() {
+                    break;
+                }
+            }
         }
     }
 
@@ -377,21 +744,278 @@ async fn handle_connection(server: Arc<WsServer>, stream: TcpStream, addr: Socke
         let mut clients = server.clients.write().await;
         clients.remove(&addr);
     }
+    ws_auth::drop_pairings_for(addr);
 
     crate::modules::logger::log_info(&format!("[WS] 连接关闭: {}", addr));
 }
 
+/// 客户端发来的消息所需的权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestAccess {
+    /// 认证前即可发送（心跳、共享密钥认证与配对）
+    Open,
+    /// 已通过共享密钥或配对令牌认证，且连接被授予该权限范围
+    Requires(WsScope),
+    /// 只由服务端发出，客户端发送时拒绝
+    ServerOnly,
+}
+
+/// 逐一列出全部消息，新增消息时必须在这里决定权限，避免默认放行
+fn request_access(msg: &WsMessage) -> RequestAccess {
+    match msg {
+        WsMessage::Ping | WsMessage::Authenticate { .. } | WsMessage::Pair { .. } => {
+            RequestAccess::Open
+        }
+        WsMessage::GetAccounts { .. }
+        | WsMessage::GetCurrentAccount { .. }
+        | WsMessage::GetUnifiedQuotas { .. }
+        | WsMessage::Subscribe { .. }
+        | WsMessage::Unsubscribe { .. } => RequestAccess::Requires(WsScope::ReadAccounts),
+        WsMessage::GetAccountsWithTokens { .. } => RequestAccess::Requires(WsScope::ReadTokens),
+        WsMessage::SwitchAccount { .. } => RequestAccess::Requires(WsScope::Switch),
+        WsMessage::SetLanguage { .. }
+        | WsMessage::Refresh { .. }
+        | WsMessage::AddAccount { .. }
+        | WsMessage::DeleteAccountByEmail { .. }
+        | WsMessage::NotifyDataChanged { .. } => RequestAccess::Requires(WsScope::Write),
+        WsMessage::Ready { .. }
+        | WsMessage::DataChanged { .. }
+        | WsMessage::LanguageChanged { .. }
+        | WsMessage::AccountSwitched { .. }
+        | WsMessage::SwitchError { .. }
+        | WsMessage::PlatformAccountSwitched { .. }
+        | WsMessage::WakeupOverride { .. }
+        | WsMessage::QuotaChanged(_)
+        | WsMessage::QuotaSnapshot(_)
+        | WsMessage::InstanceChanged(_)
+        | WsMessage::WakeupTaskResult(_)
+        | WsMessage::QuotaAlert(_)
+        | WsMessage::ConfigChanged { .. }
+        | WsMessage::Pong
+        | WsMessage::Authenticated { .. }
+        | WsMessage::PairingPending { .. }
+        | WsMessage::Paired { .. }
+        | WsMessage::SubscriptionsResponse { .. }
+        | WsMessage::AccountsResponse { .. }
+        | WsMessage::AccountsWithTokensResponse { .. }
+        | WsMessage::CurrentAccountResponse { .. }
+        | WsMessage::PlatformAccountsResponse { .. }
+        | WsMessage::PlatformCurrentAccountResponse { .. }
+        | WsMessage::RefreshResponse { .. }
+        | WsMessage::UnifiedQuotasResponse { .. }
+        | WsMessage::SuccessResponse { .. }
+        | WsMessage::ErrorResponse { .. } => RequestAccess::ServerOnly,
+    }
+}
+
+fn request_id_of(msg: &WsMessage) -> String {
+    match msg {
+        WsMessage::GetAccounts { request_id, .. }
+        | WsMessage::GetAccountsWithTokens { request_id }
+        | WsMessage::GetCurrentAccount { request_id, .. }
+        | WsMessage::GetUnifiedQuotas { request_id, .. }
+        | WsMessage::Refresh { request_id, .. }
+        | WsMessage::Subscribe { request_id, .. }
+        | WsMessage::Unsubscribe { request_id, .. }
+        | WsMessage::SetLanguage { request_id, .. }
+        | WsMessage::AddAccount { request_id, .. }
+        | WsMessage::DeleteAccountByEmail { request_id, .. } => request_id.clone(),
+        WsMessage::SwitchAccount { request_id, .. } => request_id.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// 事件所属的主题；None 表示不属于任何主题，总是推送
+fn topic_of(msg: &WsMessage) -> Option<WsTopic> {
+    match msg {
+        WsMessage::DataChanged { .. }
+        | WsMessage::AccountSwitched { .. }
+        | WsMessage::SwitchError { .. }
+        | WsMessage::PlatformAccountSwitched { .. } => Some(WsTopic::Accounts),
+        WsMessage::QuotaChanged(event) => Some(WsTopic::Quota(Some(event.platform.clone()))),
+        WsMessage::QuotaSnapshot(info) => Some(WsTopic::Quota(Some(info.platform.clone()))),
+        WsMessage::InstanceChanged(_) => Some(WsTopic::Instances),
+        WsMessage::WakeupTaskResult(_) => Some(WsTopic::Wakeup),
+        WsMessage::QuotaAlert(_) => Some(WsTopic::Alerts),
+        WsMessage::LanguageChanged { .. }
+        | WsMessage::WakeupOverride { .. }
+        | WsMessage::ConfigChanged { .. } => Some(WsTopic::Config),
+        _ => None,
+    }
+}
+
+/// 状态类事件在主题内的保留键，同一键只保留最新一条；None 表示不保留
+fn retain_key(msg: &WsMessage) -> Option<String> {
+    match msg {
+        WsMessage::AccountSwitched { .. } => Some("current_account".to_string()),
+        WsMessage::PlatformAccountSwitched { platform, .. } => {
+            Some(format!("current_account:{}", platform))
+        }
+        WsMessage::InstanceChanged(event) => Some(format!(
+            "{}:{}",
+            event.ide,
+            event.instance_id.as_deref().unwrap_or("default")
+        )),
+        WsMessage::WakeupTaskResult(payload) => Some(payload.task_id.clone()),
+        WsMessage::QuotaAlert(payload) => Some(payload.platform.clone()),
+        WsMessage::LanguageChanged { .. } => Some("language".to_string()),
+        WsMessage::WakeupOverride { .. } => Some("wakeup_override".to_string()),
+        WsMessage::ConfigChanged { .. } => Some("config".to_string()),
+        _ => None,
+    }
+}
+
+/// 规范化订阅主题中的平台标识
+fn normalize_topics(topics: Vec<WsTopic>) -> Result<Vec<WsTopic>, String> {
+    topics
+        .into_iter()
+        .map(|topic| match topic {
+            WsTopic::Quota(Some(platform_id)) => {
+                let target = platform::get(&platform_id)?;
+                Ok(WsTopic::Quota(Some(target.id().to_string())))
+            }
+            topic => Ok(topic),
+        })
+        .collect()
+}
+
+/// 订阅时回放：配额主题发送最新快照，其他主题发送保留的最近事件
+async fn replay_topics(
+    server: &WsServer,
+    sender: &mut WsSink,
+    topics: &[WsTopic],
+) -> Result<(), String> {
+    for topic in topics {
+        let WsTopic::Quota(platform_id) = topic else {
+            continue;
+        };
+        match get_unified_quotas_info(platform_id.as_deref()) {
+            Ok(platforms) => {
+                for info in platforms {
+                    send_message(sender, &WsMessage::QuotaSnapshot(info)).await?;
+                }
+            }
+            Err(e) => {
+                crate::modules::logger::log_warn(&format!("[WS] 回放配额快照失败: {}", e));
+            }
+        }
+    }
+    for json in server.retained_events(topics) {
+        sender
+            .send(Message::Text(json.into()))
+            .await
+            .map_err(|e| format!("发送回放事件失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 请求的名称（用于审计日志）
+fn request_name(msg: &WsMessage) -> String {
+    serde_json::to_value(msg)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("type")
+                .and_then(|t| t.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_default()
+}
+
+async fn send_message(sender: &mut WsSink, message: &WsMessage) -> Result<(), String> {
+    let json = serde_json::to_string(message).map_err(|e| format!("序列化响应失败: {}", e))?;
+    sender
+        .send(Message::Text(json.into()))
+        .await
+        .map_err(|e| format!("发送响应失败: {}", e))
+}
+
+/// 检查连接是否有权限执行该请求，无权限时回复错误；特权请求写审计日志
+async fn authorize(
+    server: &WsServer,
+    sender: &mut WsSink,
+    addr: SocketAddr,
+    msg: &WsMessage,
+) -> Result<bool, String> {
+    let scope = match request_access(msg) {
+        RequestAccess::Open => return Ok(true),
+        RequestAccess::Requires(scope) => Some(scope),
+        RequestAccess::ServerOnly => None,
+    };
+    let session = server.session(addr).await;
+    if !scope.is_some_and(|scope| session.allows(scope)) {
+        let error = match scope {
+            None => format!("不支持的客户端消息: {}", request_name(msg)),
+            Some(scope) if session.is_authenticated() => {
+                format!("权限不足: 需要 {:?} 权限", scope)
+            }
+            Some(_) => "未认证: 请先发送 request.authenticate 或 request.pair".to_string(),
+        };
+        crate::modules::logger::log_warn(&format!(
+            "[WS][Auth] 拒绝请求 {}: client={}, addr={}, 原因={}",
+            request_name(msg),
+            session.label(),
+            addr,
+            error
+        ));
+        let response = match msg {
+            WsMessage::SwitchAccount {
+                request_id: None, ..
+            } => WsMessage::SwitchError { message: error },
+            _ => WsMessage::ErrorResponse {
+                request_id: request_id_of(msg),
+                error,
+            },
+        };
+        send_message(sender, &response).await?;
+        return Ok(false);
+    }
+    if scope.is_some_and(WsScope::is_privileged) {
+        crate::modules::logger::log_info(&format!(
+            "[WS][Audit] {}: client={}, addr={}",
+            request_name(msg),
+            session.label(),
+            addr
+        ));
+    }
+    Ok(true)
+}
+
+fn notify_pairing_request(request: &ws_auth::PairingRequest) {
+    let Some(app_handle) = crate::get_app_handle() else {
+        return;
+    };
+    use tauri::Emitter;
+    use tauri_plugin_notification::NotificationExt;
+
+    let _ = app_handle.emit(ws_auth::PAIRING_EVENT, request);
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title("客户端请求配对")
+        .body(format!(
+            "{} 请求访问，配对码 {}，请在设置中核对后批准",
+            request.client_name, request.code
+        ))
+        .show()
+    {
+        crate::modules::logger::log_warn(&format!("[WS][Auth] 配对通知发送失败: {}", e));
+    }
+}
+
 /// 处理客户端消息
 async fn handle_client_message(
     server: &WsServer,
-    sender: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    sender: &mut WsSink,
+    addr: SocketAddr,
     text: &str,
 ) -> Result<(), String> {
     let msg: WsMessage = serde_json::from_str(text).map_err(|e| format!("解析消息失败: {}", e))?;
 
+    if !authorize(server, sender, addr, &msg).await? {
+        return Ok(());
+    }
+
     match msg {
         WsMessage::Ping => {
             let pong = serde_json::to_string(&WsMessage::Pong).unwrap();
@@ -401,7 +1025,148 @@ async fn handle_client_message(
                 .map_err(|e| format!("发送 Pong 失败: {}", e))?;
         }
 
-        WsMessage::GetAccounts { request_id } => {
+        WsMessage::Authenticate {
+            request_id,
+            client_name,
+            secret,
+            token,
+            scopes,
+        } => {
+            let response = match ws_auth::authenticate(
+                client_name,
+                secret.as_deref(),
+                token.as_deref(),
+                scopes,
+            ) {
+                Ok(session) => {
+                    crate::modules::logger::log_info(&format!(
+                        "[WS][Auth] 认证成功: client={}, addr={}, scopes={:?}",
+                        session.label(),
+                        addr,
+                        session.scopes
+                    ));
+                    let response = WsMessage::Authenticated {
+                        request_id,
+                        client_id: session.client_id.clone(),
+                        scopes: session.scopes.iter().copied().collect(),
+                    };
+                    server.set_session(addr, session).await;
+                    response
+                }
+                Err(e) => {
+                    crate::modules::logger::log_warn(&format!(
+                        "[WS][Auth] 认证失败: addr={}, {}",
+                        addr, e
+                    ));
+                    WsMessage::ErrorResponse {
+                        request_id,
+                        error: e,
+                    }
+                }
+            };
+            send_message(sender, &response).await?;
+        }
+
+        WsMessage::Pair {
+            request_id,
+            client_name,
+            scopes,
+        } => {
+            let response =
+                match ws_auth::start_pairing(addr, request_id.clone(), &client_name, scopes) {
+                    Ok(request) => {
+                        notify_pairing_request(&request);
+                        WsMessage::PairingPending {
+                            request_id,
+                            pairing_id: request.pairing_id,
+                            code: request.code,
+                            expires_at: request.expires_at,
+                        }
+                    }
+                    Err(e) => WsMessage::ErrorResponse {
+                        request_id,
+                        error: e,
+                    },
+                };
+            send_message(sender, &response).await?;
+        }
+
+        WsMessage::Subscribe {
+            request_id,
+            topics,
+            replay,
+        } => match normalize_topics(topics) {
+            Ok(topics) => {
+                let current = server
+                    .update_subscriptions(addr, |subscriptions| subscriptions.subscribe(&topics))
+                    .await;
+                crate::modules::logger::log_info(&format!(
+                    "[WS] 订阅主题: addr={}, topics={:?}",
+                    addr, current
+                ));
+                let response = WsMessage::SubscriptionsResponse {
+                    request_id,
+                    topics: current,
+                };
+                send_message(sender, &response).await?;
+                if replay.unwrap_or(true) {
+                    replay_topics(server, sender, &topics).await?;
+                }
+            }
+            Err(e) => {
+                let response = WsMessage::ErrorResponse {
+                    request_id,
+                    error: e,
+                };
+                send_message(sender, &response).await?;
+            }
+        },
+
+        WsMessage::Unsubscribe { request_id, topics } => {
+            let response = match normalize_topics(topics) {
+                Ok(topics) => {
+                    let current = server
+                        .update_subscriptions(addr, |subscriptions| {
+                            subscriptions.unsubscribe(&topics)
+                        })
+                        .await;
+                    WsMessage::SubscriptionsResponse {
+                        request_id,
+                        topics: current,
+                    }
+                }
+                Err(e) => WsMessage::ErrorResponse {
+                    request_id,
+                    error: e,
+                },
+            };
+            send_message(sender, &response).await?;
+        }
+
+        WsMessage::GetAccounts {
+            request_id,
+            platform: Some(platform),
+        } if !is_antigravity(&platform) => {
+            crate::modules::logger::log_info(&format!("[WS] 收到获取 {} 账号列表请求", platform));
+
+            let response = match get_platform_accounts_info(&platform) {
+                Ok((platform, accounts, current_account_id)) => {
+                    WsMessage::PlatformAccountsResponse {
+                        request_id,
+                        platform,
+                        accounts,
+                        current_account_id,
+                    }
+                }
+                Err(e) => WsMessage::ErrorResponse {
+                    request_id,
+                    error: e,
+                },
+            };
+            send_message(sender, &response).await?;
+        }
+
+        WsMessage::GetAccounts { request_id, .. } => {
             crate::modules::logger::log_info("[WS] 收到获取账号列表请求");
 
             let response = match get_accounts_info() {
@@ -424,6 +1189,31 @@ async fn handle_client_message(
             }
         }
 
+        WsMessage::GetUnifiedQuotas {
+            request_id,
+            platform,
+        } => {
+            crate::modules::logger::log_info("[WS] 收到获取统一配额请求");
+
+            let response = match get_unified_quotas_info(platform.as_deref()) {
+                Ok(platforms) => WsMessage::UnifiedQuotasResponse {
+                    request_id,
+                    platforms,
+                },
+                Err(e) => WsMessage::ErrorResponse {
+                    request_id,
+                    error: e,
+                },
+            };
+
+            if let Ok(json) = serde_json::to_string(&response) {
+                sender
+                    .send(Message::Text(json.into()))
+                    .await
+                    .map_err(|e| format!("发送响应失败: {}", e))?;
+            }
+        }
+
         WsMessage::GetAccountsWithTokens { request_id } => {
             crate::modules::logger::log_info("[WS] 收到获取账号列表(含Token)请求");
 
@@ -447,7 +1237,31 @@ async fn handle_client_message(
             }
         }
 
-        WsMessage::GetCurrentAccount { request_id } => {
+        WsMessage::GetCurrentAccount {
+            request_id,
+            platform: Some(platform),
+        } if !is_antigravity(&platform) => {
+            crate::modules::logger::log_info(&format!("[WS] 收到获取 {} 当前账号请求", platform));
+
+            let response = match get_platform_accounts_info(&platform) {
+                Ok((platform, accounts, current_account_id)) => {
+                    WsMessage::PlatformCurrentAccountResponse {
+                        request_id,
+                        platform,
+                        account: accounts
+                            .into_iter()
+                            .find(|account| Some(&account.id) == current_account_id.as_ref()),
+                    }
+                }
+                Err(e) => WsMessage::ErrorResponse {
+                    request_id,
+                    error: e,
+                },
+            };
+            send_message(sender, &response).await?;
+        }
+
+        WsMessage::GetCurrentAccount { request_id, .. } => {
             crate::modules::logger::log_info("[WS] 收到获取当前账号请求");
 
             let response = match get_current_account_info() {
@@ -469,32 +1283,114 @@ async fn handle_client_message(
             }
         }
 
-        WsMessage::SwitchAccount { account_id } => {
+        WsMessage::SwitchAccount {
+            account_id,
+            platform: Some(platform),
+            request_id,
+        } if !is_antigravity(&platform) => {
+            crate::modules::logger::log_info(&format!("[WS] 收到 {} 切换请求", platform));
+
+            // 异步执行切换，结果广播给所有客户端，带 request_id 时另行回复请求方
+            tokio::spawn(async move {
+                let result = match platform::get(&platform) {
+                    Ok(target) => target.switch_account(account_id.clone()).await,
+                    Err(e) => Err(e),
+                };
+                let server = get_server();
+                let reply = match result {
+                    Ok(message) => {
+                        server.broadcast(WsMessage::PlatformAccountSwitched {
+                            platform,
+                            account_id,
+                            message: message.clone(),
+                        });
+                        request_id.map(|request_id| WsMessage::SuccessResponse {
+                            request_id,
+                            message,
+                        })
+                    }
+                    Err(e) => {
+                        crate::modules::logger::log_warn(&format!(
+                            "[WS] {} 切换失败: {}",
+                            platform, e
+                        ));
+                        request_id.map(|request_id| WsMessage::ErrorResponse {
+                            request_id,
+                            error: e,
+                        })
+                    }
+                };
+                if let Some(reply) = reply {
+                    server.send_to(addr, &reply).await;
+                }
+            });
+        }
+
+        WsMessage::SwitchAccount {
+            account_id,
+            request_id,
+            ..
+        } => {
             crate::modules::logger::log_info("[WS] 收到切换请求");
 
             // 异步执行切换
-            let server_clone = server.tx.clone();
             tokio::spawn(async move {
                 match crate::modules::account::switch_account_internal(&account_id).await {
                     Ok(account) => {
-                        let msg = WsMessage::AccountSwitched {
+                        get_server().broadcast(WsMessage::AccountSwitched {
                             account_id: account.id,
-                            email: account.email,
-                        };
-                        if let Ok(json) = serde_json::to_string(&msg) {
-                            let _ = server_clone.send(json);
+                            email: account.email.clone(),
+                        });
+                        if let Some(request_id) = request_id {
+                            let reply = WsMessage::SuccessResponse {
+                                request_id,
+                                message: format!("切换完成: {}", account.email),
+                            };
+                            get_server().send_to(addr, &reply).await;
                         }
                     }
                     Err(e) => {
-                        let msg = WsMessage::SwitchError { message: e };
-                        if let Ok(json) = serde_json::to_string(&msg) {
-                            let _ = server_clone.send(json);
+                        get_server().broadcast(WsMessage::SwitchError { message: e.clone() });
+                        if let Some(request_id) = request_id {
+                            let reply = WsMessage::ErrorResponse {
+                                request_id,
+                                error: e,
+                            };
+                            get_server().send_to(addr, &reply).await;
                         }
                     }
                 }
             });
         }
 
+        WsMessage::Refresh {
+            request_id,
+            platform,
+            account_id,
+        } => {
+            let platform_id = target_platform(platform.as_deref()).to_string();
+            crate::modules::logger::log_info(&format!("[WS] 收到 {} 刷新请求", platform_id));
+
+            // 刷新耗时较长，异步执行后定向回复
+            tokio::spawn(async move {
+                let response = match refresh_platform_accounts(&platform_id, account_id).await {
+                    Ok(report) => {
+                        broadcast_data_changed("ws_refresh");
+                        WsMessage::RefreshResponse {
+                            request_id,
+                            platform: platform_id,
+                            report,
+                        }
+                    }
+                    Err(e) => WsMessage::ErrorResponse {
+                        request_id,
+                        error: e,
+                    },
+                };
+                get_server().send_to(addr, &response).await;
+            });
+        }
+
         WsMessage::SetLanguage {
             request_id,
             language,
@@ -628,8 +1524,9 @@ fn get_accounts_info() -> Result<(Vec<AccountInfo>, Option<String>), String> {
 
 /// 获取账号列表信息（包含 Token）
 fn get_accounts_with_tokens_info() -> Result<(Vec<AccountTokenInfo>, Option<String>), String> {
-    use crate::modules::account;
+    use crate::modules::{account, credential_vault};
 
+    credential_vault::ensure_unlocked()?;
     let accounts = account::list_accounts()?;
     let current_id = account::get_current_account_id()?;
 
@@ -660,6 +1557,69 @@ fn get_accounts_with_tokens_info() -> Result<(Vec<AccountTokenInfo>, Option<Stri
     Ok((account_infos, current_id))
 }
 
+const ANTIGRAVITY_PLATFORM: &str = "antigravity";
+
+/// 请求的目标平台；旧版客户端不带 platform 字段（或为空）时为 Antigravity
+fn target_platform(platform: Option<&str>) -> &str {
+    match platform {
+        Some(platform_id) if !platform_id.is_empty() => platform_id,
+        _ => ANTIGRAVITY_PLATFORM,
+    }
+}
+
+fn is_antigravity(platform_id: &str) -> bool {
+    target_platform(Some(platform_id)) == ANTIGRAVITY_PLATFORM
+}
+
+/// 获取平台账号列表（附带配额过期标记）与当前账号
+fn get_platform_accounts_info(
+    platform_id: &str,
+) -> Result<(String, Vec<PlatformAccount>, Option<String>), String> {
+    let target = platform::get(platform_id)?;
+    let mut accounts = target.list_accounts()?;
+    quota_cache::mark_staleness(target.id(), &mut accounts);
+    let current_account_id = target.current_account_id(&accounts);
+    Ok((target.id().to_string(), accounts, current_account_id))
+}
+
+/// 刷新单个账号或全部账号（全量刷新返回结果统计）
+async fn refresh_platform_accounts(
+    platform_id: &str,
+    account_id: Option<String>,
+) -> Result<Option<RefreshReport>, String> {
+    let target = platform::get(platform_id)?;
+    match account_id {
+        Some(account_id) => {
+            refresh_scheduler::refresh_account(target, account_id).await?;
+            Ok(None)
+        }
+        None => refresh_scheduler::refresh_platform(target, RefreshTrigger::Manual)
+            .await
+            .map(Some),
+    }
+}
+
+/// 获取统一配额（指定平台或全部平台）
+fn get_unified_quotas_info(platform_id: Option<&str>) -> Result<Vec<PlatformQuotaInfo>, String> {
+    let platforms = match platform_id {
+        Some(id) => vec![platform::get(id)?],
+        None => platform::all().to_vec(),
+    };
+
+    platforms
+        .into_iter()
+        .map(|platform| {
+            let mut accounts = platform.list_accounts()?;
+            quota_cache::mark_staleness(platform.id(), &mut accounts);
+            Ok(PlatformQuotaInfo {
+                platform: platform.id().to_string(),
+                current_account_id: platform.current_account_id(&accounts),
+                accounts,
+            })
+        })
+        .collect()
+}
+
 /// 获取当前账号信息
 fn get_current_account_info() -> Result<Option<AccountInfo>, String> {
     use crate::modules::account;
@@ -766,6 +1726,9 @@ fn handle_set_language(language: &str, source: Option<&str>) -> Result<String, S
         ghcp_auto_refresh_minutes: current.ghcp_auto_refresh_minutes,
         windsurf_auto_refresh_minutes: current.windsurf_auto_refresh_minutes,
         kiro_auto_refresh_minutes: current.kiro_auto_refresh_minutes,
+        cursor_auto_refresh_minutes: current.cursor_auto_refresh_minutes,
+        refresh_concurrency: current.refresh_concurrency,
+        refresh_host_interval_ms: current.refresh_host_interval_ms,
         close_behavior: current.close_behavior,
         minimize_behavior: current.minimize_behavior,
         hide_dock_icon: current.hide_dock_icon,
@@ -775,6 +1738,7 @@ fn handle_set_language(language: &str, source: Option<&str>) -> Result<String, S
         vscode_app_path: current.vscode_app_path,
         windsurf_app_path: current.windsurf_app_path,
         kiro_app_path: current.kiro_app_path,
+        cursor_app_path: current.cursor_app_path,
         opencode_sync_on_switch: current.opencode_sync_on_switch,
         codex_launch_on_switch: current.codex_launch_on_switch,
         auto_switch_enabled: current.auto_switch_enabled,
@@ -789,6 +1753,8 @@ fn handle_set_language(language: &str, source: Option<&str>) -> Result<String, S
         windsurf_quota_alert_threshold: current.windsurf_quota_alert_threshold,
         kiro_quota_alert_enabled: current.kiro_quota_alert_enabled,
         kiro_quota_alert_threshold: current.kiro_quota_alert_threshold,
+        cursor_quota_alert_enabled: current.cursor_quota_alert_enabled,
+        cursor_quota_alert_threshold: current.cursor_quota_alert_threshold,
     };
 
     config::save_user_config(&new_config)?;
@@ -797,3 +1763,128 @@ fn handle_set_language(language: &str, source: Option<&str>) -> Result<String, S
 
     Ok(format!("语言已更新为 {}", normalized))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> WsMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn only_handshake_messages_are_open() {
+        assert_eq!(request_access(&WsMessage::Ping), RequestAccess::Open);
+        assert_eq!(
+            request_access(&parse(
+                r#"{"type":"request.pair","request_id":"1","client_name":"ext","scopes":null}"#
+            )),
+            RequestAccess::Open
+        );
+        assert_eq!(
+            request_access(&parse(
+                r#"{"type":"request.get_accounts_with_tokens","request_id":"1"}"#
+            )),
+            RequestAccess::Requires(WsScope::ReadTokens)
+        );
+        assert_eq!(
+            request_access(&WsMessage::DataChanged {
+                source: "ext".to_string()
+            }),
+            RequestAccess::ServerOnly
+        );
+        assert_eq!(
+            request_access(&WsMessage::SuccessResponse {
+                request_id: "1".to_string(),
+                message: String::new(),
+            }),
+            RequestAccess::ServerOnly
+        );
+    }
+
+    #[test]
+    fn legacy_requests_without_platform_target_antigravity() {
+        let WsMessage::SwitchAccount {
+            account_id,
+            platform,
+            request_id,
+        } = parse(r#"{"type":"request.switch_account","account_id":"a1"}"#)
+        else {
+            panic!("expected request.switch_account");
+        };
+        assert_eq!(account_id, "a1");
+        assert_eq!(platform, None);
+        assert_eq!(request_id, None);
+        assert_eq!(target_platform(platform.as_deref()), ANTIGRAVITY_PLATFORM);
+
+        let WsMessage::Refresh {
+            platform,
+            account_id,
+            ..
+        } = parse(r#"{"type":"request.refresh","request_id":"1"}"#)
+        else {
+            panic!("expected request.refresh");
+        };
+        assert_eq!(account_id, None);
+        assert_eq!(target_platform(platform.as_deref()), ANTIGRAVITY_PLATFORM);
+
+        for json in [
+            r#"{"type":"request.get_accounts","request_id":"1"}"#,
+            r#"{"type":"request.get_current_account","request_id":"1","platform":""}"#,
+            r#"{"type":"request.get_accounts","request_id":"1","platform":"antigravity"}"#,
+        ] {
+            let platform = match parse(json) {
+                WsMessage::GetAccounts { platform, .. }
+                | WsMessage::GetCurrentAccount { platform, .. } => platform,
+                other => panic!("unexpected message: {:?}", other),
+            };
+            assert!(is_antigravity(target_platform(platform.as_deref())));
+        }
+    }
+
+    #[test]
+    fn platform_qualified_requests_dispatch_to_that_platform() {
+        let switch = parse(
+            r#"{"type":"request.switch_account","account_id":"c1","platform":"codex","request_id":"7"}"#,
+        );
+        assert_eq!(
+            request_access(&switch),
+            RequestAccess::Requires(WsScope::Switch)
+        );
+        assert_eq!(request_id_of(&switch), "7");
+        let WsMessage::SwitchAccount { platform, .. } = switch else {
+            panic!("expected request.switch_account");
+        };
+        let platform_id = target_platform(platform.as_deref());
+        assert!(!is_antigravity(platform_id));
+        assert_eq!(platform::get(platform_id).unwrap().id(), "codex");
+
+        let refresh = parse(r#"{"type":"request.refresh","request_id":"1","platform":"kiro"}"#);
+        assert_eq!(
+            request_access(&refresh),
+            RequestAccess::Requires(WsScope::Write)
+        );
+        let WsMessage::Refresh { platform, .. } = refresh else {
+            panic!("expected request.refresh");
+        };
+        assert_eq!(
+            platform::get(target_platform(platform.as_deref()))
+                .unwrap()
+                .id(),
+            "kiro"
+        );
+
+        for json in [
+            r#"{"type":"request.get_accounts","request_id":"1","platform":"cursor"}"#,
+            r#"{"type":"request.get_current_account","request_id":"1","platform":"cursor"}"#,
+            r#"{"type":"request.get_unified_quotas","request_id":"1","platform":"cursor"}"#,
+        ] {
+            assert_eq!(
+                request_access(&parse(json)),
+                RequestAccess::Requires(WsScope::ReadAccounts)
+            );
+        }
+
+        assert!(platform::get(target_platform(Some("unknown"))).is_err());
+    }
+}
@@ -1,6 +1,8 @@
+use futures::future::BoxFuture;
 use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
@@ -9,15 +11,58 @@ use std::time::Instant;
 use crate::models::windsurf::{
     WindsurfAccount, WindsurfAccountIndex, WindsurfOAuthCompletePayload,
 };
-use crate::modules::{account, logger, windsurf_oauth};
+use crate::modules::account_store::{self, StoreSpec};
+use crate::modules::platform::{self, Platform, PlatformAccount, UnifiedQuota};
+use crate::modules::quota_cache::{self, QuotaApiError, Refreshed};
+use crate::modules::unified_quota::{self, CopilotMetricLabels};
+use crate::modules::refresh_executor::{self, RefreshReport, RefreshRun};
+use crate::modules::schema_migration::{self, SchemaSpec, VersionEncoding};
+use crate::modules::{
+    account, credential_vault, logger, quota_events, quota_history, store_fsck, windsurf_instance,
+    windsurf_oauth,
+};
+use crate::utils::atomic_file;
 
 const ACCOUNTS_INDEX_FILE: &str = "windsurf_accounts.json";
 const ACCOUNTS_DIR: &str = "windsurf_accounts";
+
+/// windsurf_accounts.json 的格式版本
+const INDEX_SCHEMA: SchemaSpec = SchemaSpec {
+    name: ACCOUNTS_INDEX_FILE,
+    version_field: "version",
+    encoding: VersionEncoding::Dotted,
+    current: 1,
+    baseline: 1,
+    migrations: &[],
+};
+
 static WINDSURF_ACCOUNT_INDEX_LOCK: std::sync::LazyLock<Mutex<()>> =
     std::sync::LazyLock::new(|| Mutex::new(()));
-static WINDSURF_QUOTA_ALERT_LAST_SENT: std::sync::LazyLock<Mutex<HashMap<String, i64>>> =
-    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
-const WINDSURF_QUOTA_ALERT_COOLDOWN_SECONDS: i64 = 300;
+/// 账号文件中需要由凭据保险库加密的字段
+const SECRET_FIELDS: &[&str] = &[
+    "/github_access_token",
+    "/copilot_token",
+    "/windsurf_api_key",
+    "/windsurf_auth_token",
+    "/windsurf_auth_status_raw",
+];
+
+pub const STORE_SPEC: StoreSpec = StoreSpec {
+    platform: "windsurf",
+    base_dir: get_data_dir,
+    index_file: ACCOUNTS_INDEX_FILE,
+    accounts_dir: ACCOUNTS_DIR,
+    secret_fields: SECRET_FIELDS,
+    quota_fields: &[
+        "copilot_quota_snapshots",
+        "copilot_quota_reset_date",
+        "copilot_limited_user_quotas",
+        "copilot_limited_user_reset_date",
+        "windsurf_user_status",
+        "windsurf_plan_status",
+    ],
+    mirror_index: false,
+};
 
 fn now_ts() -> i64 {
     chrono::Utc::now().timestamp()
@@ -27,7 +72,7 @@ fn get_data_dir() -> Result<PathBuf, String> {
     account::get_data_dir()
 }
 
-fn get_accounts_dir() -> Result<PathBuf, String> {
+pub fn get_accounts_dir() -> Result<PathBuf, String> {
     let base = get_data_dir()?;
     let dir = base.join(ACCOUNTS_DIR);
     if !dir.exists() {
@@ -45,6 +90,12 @@ pub fn accounts_index_path_string() -> Result<String, String> {
 }
 
 pub fn load_account(account_id: &str) -> Option<WindsurfAccount> {
+    if account_store::is_active() {
+        return account_store::load_record(&STORE_SPEC, account_id)
+            .ok()
+            .flatten();
+    }
+
     let account_path = get_accounts_dir()
         .ok()
         .map(|dir| dir.join(format!("{}.json", account_id)))?;
@@ -52,17 +103,24 @@ pub fn load_account(account_id: &str) -> Option<WindsurfAccount> {
         return None;
     }
     let content = fs::read_to_string(account_path).ok()?;
-    serde_json::from_str(&content).ok()
+    credential_vault::from_sealed_json(&content, SECRET_FIELDS).ok()
 }
 
 fn save_account_file(account: &WindsurfAccount) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_record(&STORE_SPEC, &account.id, account);
+    }
+
     let path = get_accounts_dir()?.join(format!("{}.json", account.id));
-    let content =
-        serde_json::to_string_pretty(account).map_err(|e| format!("序列化账号失败: {}", e))?;
-    fs::write(path, content).map_err(|e| format!("保存账号失败: {}", e))
+    let content = credential_vault::to_sealed_json(account, SECRET_FIELDS)?;
+    atomic_file::write_secret(path, content).map_err(|e| format!("保存账号失败: {}", e))
 }
 
 fn delete_account_file(account_id: &str) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::delete_record(&STORE_SPEC, account_id);
+    }
+
     let path = get_accounts_dir()?.join(format!("{}.json", account_id));
     if path.exists() {
         fs::remove_file(path).map_err(|e| format!("删除账号文件失败: {}", e))?;
@@ -71,6 +129,15 @@ fn delete_account_file(account_id: &str) -> Result<(), String> {
 }
 
 fn load_account_index() -> WindsurfAccountIndex {
+    if account_store::is_active() {
+        return account_store::load_index(&STORE_SPEC)
+            .unwrap_or_else(|e| {
+                logger::log_error(&format!("读取 Windsurf 账号索引失败: {}", e));
+                None
+            })
+            .unwrap_or_default();
+    }
+
     let path = match get_accounts_index_path() {
         Ok(p) => p,
         Err(_) => return WindsurfAccountIndex::new(),
@@ -80,8 +147,9 @@ fn load_account_index() -> WindsurfAccountIndex {
         return WindsurfAccountIndex::new();
     }
 
-    match fs::read_to_string(path) {
+    match fs::read_to_string(&path) {
         Ok(content) => {
+            let content = schema_migration::upgrade_on_load(&INDEX_SCHEMA, &path, content);
             serde_json::from_str(&content).unwrap_or_else(|_| WindsurfAccountIndex::new())
         }
         Err(_) => WindsurfAccountIndex::new(),
@@ -89,10 +157,15 @@ fn load_account_index() -> WindsurfAccountIndex {
 }
 
 fn save_account_index(index: &WindsurfAccountIndex) -> Result<(), String> {
+    if account_store::is_active() {
+        return account_store::save_index(&STORE_SPEC, index);
+    }
+
     let path = get_accounts_index_path()?;
+    schema_migration::ensure_writable(&INDEX_SCHEMA, &path)?;
     let content =
         serde_json::to_string_pretty(index).map_err(|e| format!("序列化账号索引失败: {}", e))?;
-    fs::write(path, content).map_err(|e| format!("写入账号索引失败: {}", e))
+    atomic_file::write(path, content).map_err(|e| format!("写入账号索引失败: {}", e))
 }
 
 fn refresh_summary(index: &mut WindsurfAccountIndex, account: &WindsurfAccount) {
@@ -108,9 +181,11 @@ fn upsert_account_record(account: WindsurfAccount) -> Result<WindsurfAccount, St
         .lock()
         .map_err(|_| "获取 Windsurf 账号锁失败".to_string())?;
     let mut index = load_account_index();
-    save_account_file(&account)?;
     refresh_summary(&mut index, &account);
-    save_account_index(&index)?;
+    account_store::write_batch(|| {
+        save_account_file(&account)?;
+        save_account_index(&index)
+    })?;
     Ok(account)
 }
 
@@ -452,26 +527,27 @@ fn deduplicate_accounts_by_identity() -> Result<(), String> {
 
     merged_accounts.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
 
-    for account in &merged_accounts {
-        save_account_file(account)?;
-    }
-
     let keep_ids: HashSet<String> = merged_accounts
         .iter()
         .map(|account| account.id.clone())
         .collect();
-    for old_id in old_ids {
-        if !keep_ids.contains(&old_id) {
-            delete_account_file(&old_id)?;
-        }
-    }
-
     let mut next_index = WindsurfAccountIndex::new();
     next_index.accounts = merged_accounts
         .iter()
         .map(WindsurfAccount::summary)
         .collect();
-    save_account_index(&next_index)?;
+
+    account_store::write_batch(|| {
+        for account in &merged_accounts {
+            save_account_file(account)?;
+        }
+        for old_id in &old_ids {
+            if !keep_ids.contains(old_id) {
+                delete_account_file(old_id)?;
+            }
+        }
+        save_account_index(&next_index)
+    })?;
 
     logger::log_info(&format!(
         "Windsurf 账号去重完成：合并 {} 条重复记录",
@@ -677,6 +753,41 @@ pub fn list_accounts() -> Vec<WindsurfAccount> {
         .collect()
 }
 
+/// 以账号文件为准重建索引（一致性修复使用），返回索引中的账号数
+pub fn rebuild_account_index() -> Result<usize, String> {
+    let _lock = WINDSURF_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Windsurf 账号锁失败".to_string())?;
+    let previous = store_fsck::read_index(&STORE_SPEC).unwrap_or_default();
+    let accounts: Vec<WindsurfAccount> = store_fsck::list_stored_ids(&STORE_SPEC)?
+        .iter()
+        .filter_map(|id| load_account(id))
+        .collect();
+    let accounts =
+        store_fsck::order_by_index(accounts, &previous.account_ids, |a| &a.id, |a| a.created_at);
+
+    let mut index = WindsurfAccountIndex::new();
+    index.accounts = accounts.iter().map(WindsurfAccount::summary).collect();
+    save_account_index(&index)?;
+    Ok(index.accounts.len())
+}
+
+/// 按凭据保险库当前状态重新写回所有账号文件
+pub fn reseal_account_files() -> Result<usize, String> {
+    let _lock = WINDSURF_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 Windsurf 账号锁失败".to_string())?;
+    let index = load_account_index();
+    let mut count = 0;
+    for summary in &index.accounts {
+        if let Some(account) = load_account(&summary.id) {
+            save_account_file(&account)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
 pub fn upsert_account(payload: WindsurfOAuthCompletePayload) -> Result<WindsurfAccount, String> {
     let _lock = WINDSURF_ACCOUNT_INDEX_LOCK
         .lock()
@@ -762,9 +873,11 @@ pub fn upsert_account(payload: WindsurfOAuthCompletePayload) -> Result<WindsurfA
     account.created_at = created_at;
     account.last_used = now;
 
-    save_account_file(&account)?;
     refresh_summary(&mut index, &account);
-    save_account_index(&index)?;
+    account_store::write_batch(|| {
+        save_account_file(&account)?;
+        save_account_index(&index)
+    })?;
 
     logger::log_info(&format!(
         "Windsurf 账号已保存: id={}, login={}",
@@ -773,36 +886,107 @@ pub fn upsert_account(payload: WindsurfOAuthCompletePayload) -> Result<WindsurfA
     Ok(account)
 }
 
-pub async fn refresh_account_token(account_id: &str) -> Result<WindsurfAccount, String> {
+/// 写入配额缓存的字段（不含令牌与 API Key）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WindsurfQuotaCache {
+    copilot_plan: Option<String>,
+    copilot_quota_snapshots: Option<Value>,
+    copilot_quota_reset_date: Option<String>,
+    copilot_limited_user_quotas: Option<Value>,
+    copilot_limited_user_reset_date: Option<i64>,
+    windsurf_user_status: Option<Value>,
+    windsurf_plan_status: Option<Value>,
+}
+
+impl WindsurfQuotaCache {
+    fn of_payload(payload: &WindsurfOAuthCompletePayload) -> Self {
+        Self {
+            copilot_plan: payload.copilot_plan.clone(),
+            copilot_quota_snapshots: payload.copilot_quota_snapshots.clone(),
+            copilot_quota_reset_date: payload.copilot_quota_reset_date.clone(),
+            copilot_limited_user_quotas: payload.copilot_limited_user_quotas.clone(),
+            copilot_limited_user_reset_date: payload.copilot_limited_user_reset_date,
+            windsurf_user_status: payload.windsurf_user_status.clone(),
+            windsurf_plan_status: payload.windsurf_plan_status.clone(),
+        }
+    }
+
+    fn apply(self, account: &mut WindsurfAccount) {
+        account.copilot_plan = self.copilot_plan;
+        account.copilot_quota_snapshots = self.copilot_quota_snapshots;
+        account.copilot_quota_reset_date = self.copilot_quota_reset_date;
+        account.copilot_limited_user_quotas = self.copilot_limited_user_quotas;
+        account.copilot_limited_user_reset_date = self.copilot_limited_user_reset_date;
+        account.windsurf_user_status = self.windsurf_user_status;
+        account.windsurf_plan_status = self.windsurf_plan_status;
+    }
+}
+
+/// 刷新登录态与配额；`skip_cache` 为 true 时忽略配额缓存有效期（单个账号的手动刷新）。
+/// 使用缓存时只更新配额字段，沿用原有登录态
+pub async fn refresh_account_token(
+    account_id: &str,
+    skip_cache: bool,
+) -> Result<WindsurfAccount, String> {
+    credential_vault::ensure_unlocked()?;
     let started_at = Instant::now();
     let mut account = load_account(account_id).ok_or_else(|| "账号不存在".to_string())?;
+    if quota_cache::is_offline() {
+        // 离线模式：不发起请求，沿用上次保存的数据
+        return Ok(account);
+    }
     logger::log_info(&format!(
         "[Windsurf Refresh] 开始刷新账号: id={}, login={}",
         account.id, account.github_login
     ));
     merge_local_auth_status_into_account(&mut account);
-    let mut payload = match windsurf_oauth::refresh_payload_for_account(&account).await {
-        Ok(value) => value,
+    let request = async {
+        let mut payload = windsurf_oauth::refresh_payload_for_account(&account)
+            .await
+            .map_err(QuotaApiError::Transient)?;
+        let preserved_quota = merge_refresh_payload_with_existing(&account, &mut payload);
+        Ok((payload, preserved_quota))
+    };
+    let refreshed = quota_cache::refresh(
+        WindsurfPlatform.id(),
+        &account.id,
+        None,
+        skip_cache,
+        request,
+        |(payload, _): &(WindsurfOAuthCompletePayload, bool)| {
+            serde_json::to_value(WindsurfQuotaCache::of_payload(payload)).unwrap_or_default()
+        },
+    )
+    .await;
+    let preserved_quota = match refreshed {
+        Ok(Refreshed::Fetched((payload, preserved_quota))) => {
+            if preserved_quota {
+                logger::log_warn(&format!(
+                    "[Windsurf Refresh] 未获取到有效配额快照，保留旧配额: id={}, login={}",
+                    account.id, account.github_login
+                ));
+            }
+            let tags = account.tags.clone();
+            let created_at = account.created_at;
+            apply_payload(&mut account, payload);
+            account.tags = tags;
+            account.created_at = created_at;
+            preserved_quota
+        }
+        Ok(Refreshed::Cached(cached)) => {
+            serde_json::from_value::<WindsurfQuotaCache>(cached.payload)
+                .map_err(|e| format!("解析 Windsurf 配额缓存失败: {}", e))?
+                .apply(&mut account);
+            false
+        }
         Err(err) => {
             logger::log_warn(&format!(
                 "[Windsurf Refresh] 刷新失败: id={}, login={}, error={}",
                 account.id, account.github_login, err
             ));
-            return Err(err);
+            return Err(err.to_string());
         }
     };
-    let preserved_quota = merge_refresh_payload_with_existing(&account, &mut payload);
-    if preserved_quota {
-        logger::log_warn(&format!(
-            "[Windsurf Refresh] 未获取到有效配额快照，保留旧配额: id={}, login={}",
-            account.id, account.github_login
-        ));
-    }
-    let tags = account.tags.clone();
-    let created_at = account.created_at;
-    apply_payload(&mut account, payload);
-    account.tags = tags;
-    account.created_at = created_at;
     account.last_used = now_ts();
 
     let updated = account.clone();
@@ -817,61 +1001,30 @@ pub async fn refresh_account_token(account_id: &str) -> Result<WindsurfAccount,
     Ok(updated)
 }
 
-pub async fn refresh_all_tokens() -> Result<Vec<(String, Result<WindsurfAccount, String>)>, String>
-{
-    use futures::future::join_all;
-    use std::sync::Arc;
-    use tokio::sync::Semaphore;
-
-    const MAX_CONCURRENT: usize = 5;
-    let accounts = list_accounts();
-    logger::log_info(&format!(
-        "[Windsurf Refresh] 开始批量刷新: total={}",
-        accounts.len()
-    ));
+/// 批量刷新时按此主机限速
+const REFRESH_HOST: &str = "server.codeium.com";
 
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
-    let tasks: Vec<_> = accounts
-        .into_iter()
-        .map(|account| {
-            let id = account.id;
-            let semaphore = semaphore.clone();
-            async move {
-                let _permit = semaphore
-                    .acquire_owned()
-                    .await
-                    .map_err(|e| format!("获取 Windsurf 刷新并发许可失败: {}", e))?;
-                let res = refresh_account_token(&id).await;
-                Ok::<(String, Result<WindsurfAccount, String>), String>((id, res))
-            }
-        })
-        .collect();
+pub async fn refresh_all_tokens() -> Result<RefreshRun<WindsurfAccount>, String> {
+    let account_ids = list_accounts().into_iter().map(|account| account.id).collect();
+    let run = refresh_executor::run(WindsurfPlatform.id(), REFRESH_HOST, account_ids, |id| async move {
+        refresh_account_token(&id, false).await
+    })
+    .await;
 
-    let mut results = Vec::with_capacity(tasks.len());
-    for task in join_all(tasks).await {
-        match task {
-            Ok(item) => {
-                if let Err(err) = &item.1 {
-                    logger::log_warn(&format!(
-                        "[Windsurf Refresh] 账号刷新失败: id={}, error={}",
-                        item.0, err
-                    ));
-                }
-                results.push(item);
-            }
-            Err(err) => {
-                logger::log_warn(&format!("[Windsurf Refresh] 执行任务失败: {}", err));
-            }
+    for (id, result) in &run.results {
+        if let Err(err) = result {
+            logger::log_warn(&format!(
+                "[Windsurf Refresh] 账号刷新失败: id={}, error={}",
+                id, err
+            ));
         }
     }
-
-    let success_count = results.iter().filter(|(_, item)| item.is_ok()).count();
-    let failed_count = results.len().saturating_sub(success_count);
-    logger::log_info(&format!(
-        "[Windsurf Refresh] 批量刷新结束: success={}, failed={}",
-        success_count, failed_count
-    ));
-    Ok(results)
+    quota_history::record_refreshed_logged(
+        &WindsurfPlatform,
+        &quota_history::succeeded_ids(&run.results),
+    );
+    quota_events::publish_logged(&WindsurfPlatform);
+    Ok(run)
 }
 
 pub fn remove_account(account_id: &str) -> Result<(), String> {
@@ -880,9 +1033,10 @@ pub fn remove_account(account_id: &str) -> Result<(), String> {
         .map_err(|_| "获取 Windsurf 账号锁失败".to_string())?;
     let mut index = load_account_index();
     index.accounts.retain(|item| item.id != account_id);
-    save_account_index(&index)?;
-    delete_account_file(account_id)?;
-    Ok(())
+    account_store::write_batch(|| {
+        save_account_index(&index)?;
+        delete_account_file(account_id)
+    })
 }
 
 pub fn remove_accounts(account_ids: &[String]) -> Result<(), String> {
@@ -920,6 +1074,7 @@ pub fn import_from_json(json_content: &str) -> Result<Vec<WindsurfAccount>, Stri
 }
 
 pub fn export_accounts(account_ids: &[String]) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
     let accounts: Vec<WindsurfAccount> = account_ids
         .iter()
         .filter_map(|id| load_account(id))
@@ -996,246 +1151,285 @@ pub fn read_local_login_hint() -> Option<String> {
         .map(|value| value.to_string())
 }
 
-fn normalize_quota_alert_threshold(raw: i32) -> i32 {
-    raw.clamp(0, 100)
-}
+const QUOTA_METRIC_LABELS: CopilotMetricLabels = CopilotMetricLabels {
+    completions: "Prompt Credits",
+    chat: "Flow Action Credits",
+    premium: "Premium Interactions",
+};
 
-fn clamp_percent(value: f64) -> i32 {
-    value.round().clamp(0.0, 100.0) as i32
+/// 统一配额视图（令牌 + 受限用户额度 + 高级请求快照）
+pub fn unified_quota(account: &WindsurfAccount) -> UnifiedQuota {
+    UnifiedQuota::new(unified_quota::copilot_metrics(
+        &account.copilot_token,
+        account.copilot_plan.as_deref(),
+        account.copilot_limited_user_quotas.as_ref(),
+        account.copilot_quota_snapshots.as_ref(),
+        &QUOTA_METRIC_LABELS,
+        quota_reset_at(account),
+    ))
 }
 
-fn parse_token_map(token: &str) -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    let prefix = token.split(':').next().unwrap_or(token);
-    for item in prefix.split(';') {
-        let mut parts = item.splitn(2, '=');
-        let key = parts.next().unwrap_or("").trim();
-        if key.is_empty() {
-            continue;
+fn resolve_current_account_id(accounts: &[PlatformAccount]) -> Option<String> {
+    if let Ok(settings) = crate::modules::windsurf_instance::load_default_settings() {
+        if let Some(bind_id) = settings.bind_account_id {
+            let trimmed = bind_id.trim();
+            if !trimmed.is_empty() {
+                return Some(trimmed.to_string());
+            }
         }
-        let value = parts.next().unwrap_or("").trim();
-        map.insert(key.to_string(), value.to_string());
     }
-    map
-}
 
-fn parse_token_number(map: &HashMap<String, String>, key: &str) -> Option<f64> {
-    map.get(key)
-        .and_then(|value| value.trim().parse::<f64>().ok())
-        .filter(|value| value.is_finite())
+    platform::most_recently_used(accounts)
 }
 
-fn get_json_number(value: &serde_json::Value) -> Option<f64> {
-    match value {
-        serde_json::Value::Number(num) => num.as_f64(),
-        serde_json::Value::String(text) => text.trim().parse::<f64>().ok(),
-        _ => None,
-    }
-    .filter(|value| value.is_finite())
+fn display_email(account: &WindsurfAccount) -> String {
+    account
+        .github_email
+        .clone()
+        .filter(|text| !text.trim().is_empty())
+        .unwrap_or_else(|| account.github_login.clone())
 }
 
-fn calc_remaining_percent(remaining: f64, total: f64) -> Option<i32> {
-    if total <= 0.0 {
-        return None;
-    }
-    Some(clamp_percent((remaining.max(0.0) / total) * 100.0))
+pub fn run_quota_alert_if_needed(
+) -> Result<Vec<crate::modules::account::QuotaAlertPayload>, String> {
+    crate::modules::alert_rules::run(&WindsurfPlatform)
+}
+
+/// 配额重置时间：优先受限用户重置时间，其次 quota_reset_date（RFC3339）
+fn quota_reset_at(account: &WindsurfAccount) -> Option<i64> {
+    account.copilot_limited_user_reset_date.or_else(|| {
+        account
+            .copilot_quota_reset_date
+            .as_deref()
+            .and_then(|text| chrono::DateTime::parse_from_rfc3339(text.trim()).ok())
+            .map(|value| value.timestamp())
+    })
+    .or_else(|| resolve_plan_end_ts(account))
 }
 
-fn extract_limited_metrics(account: &WindsurfAccount) -> Vec<(String, i32)> {
-    let Some(limited) = account
-        .copilot_limited_user_quotas
-        .as_ref()
-        .and_then(|value| value.as_object())
-    else {
-        return Vec::new();
-    };
-
-    let token_map = parse_token_map(&account.copilot_token);
-    let mut metrics = Vec::new();
+/// 没有额度重置日期时，以订阅结束时间（planEnd）作为重置时间
+fn resolve_plan_end_ts(account: &WindsurfAccount) -> Option<i64> {
+    let mut candidates: Vec<Option<&serde_json::Value>> = Vec::new();
+    let user_status = account.windsurf_user_status.as_ref();
+    let snapshots = account.copilot_quota_snapshots.as_ref();
 
-    if let Some(remaining_completions) = limited.get("completions").and_then(get_json_number) {
-        let total_completions =
-            parse_token_number(&token_map, "cq").unwrap_or(remaining_completions);
-        if let Some(percent) = calc_remaining_percent(remaining_completions, total_completions) {
-            metrics.push(("Prompt Credits".to_string(), percent));
-        }
-    }
+    candidates.push(json_path(
+        user_status,
+        &["userStatus", "planStatus", "planEnd"],
+    ));
+    candidates.push(json_path(
+        user_status,
+        &["userStatus", "planStatus", "plan_end"],
+    ));
+    candidates.push(json_path(user_status, &["planStatus", "planEnd"]));
+    candidates.push(json_path(user_status, &["planStatus", "plan_end"]));
+    candidates.push(json_path(snapshots, &["windsurfPlanStatus", "planEnd"]));
+    candidates.push(json_path(snapshots, &["windsurfPlanStatus", "plan_end"]));
+    candidates.push(json_path(
+        snapshots,
+        &["windsurfPlanStatus", "planStatus", "planEnd"],
+    ));
+    candidates.push(json_path(
+        snapshots,
+        &["windsurfPlanStatus", "planStatus", "plan_end"],
+    ));
+    candidates.push(json_path(
+        snapshots,
+        &["windsurfUserStatus", "userStatus", "planStatus", "planEnd"],
+    ));
+    candidates.push(json_path(
+        snapshots,
+        &["windsurfUserStatus", "userStatus", "planStatus", "plan_end"],
+    ));
 
-    if let Some(remaining_chat) = limited.get("chat").and_then(get_json_number) {
-        let total_chat = parse_token_number(&token_map, "tq").unwrap_or(remaining_chat);
-        if let Some(percent) = calc_remaining_percent(remaining_chat, total_chat) {
-            metrics.push(("Flow Action Credits".to_string(), percent));
+    for candidate in candidates.into_iter().flatten() {
+        if let Some(ts) = parse_timestamp_like(candidate) {
+            return Some(ts);
         }
     }
 
-    metrics
+    None
 }
 
-fn extract_premium_metric(account: &WindsurfAccount) -> Option<(String, i32)> {
-    let snapshots = account
-        .copilot_quota_snapshots
-        .as_ref()
-        .and_then(|value| value.as_object())?;
-
-    let premium = snapshots
-        .get("premium_interactions")
-        .or_else(|| snapshots.get("premium_models"))
-        .and_then(|value| value.as_object())?;
-
-    if premium.get("unlimited").and_then(|value| value.as_bool()) == Some(true) {
-        return Some(("Premium Interactions".to_string(), 100));
+fn json_path<'a>(
+    root: Option<&'a serde_json::Value>,
+    path: &[&str],
+) -> Option<&'a serde_json::Value> {
+    let mut current = root?;
+    for key in path {
+        current = current.as_object()?.get(*key)?;
     }
-
-    let percent_remaining = premium
-        .get("percent_remaining")
-        .and_then(get_json_number)
-        .map(clamp_percent)?;
-
-    Some(("Premium Interactions".to_string(), percent_remaining))
+    Some(current)
 }
 
-fn extract_quota_metrics(account: &WindsurfAccount) -> Vec<(String, i32)> {
-    let mut metrics = extract_limited_metrics(account);
-    if let Some(premium) = extract_premium_metric(account) {
-        metrics.push(premium);
+fn parse_timestamp_like(value: &serde_json::Value) -> Option<i64> {
+    match value {
+        serde_json::Value::Number(num) => parse_timestamp_number(num.as_f64()?),
+        serde_json::Value::String(text) => {
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                return None;
+            }
+            if let Ok(n) = trimmed.parse::<f64>() {
+                return parse_timestamp_number(n);
+            }
+            chrono::DateTime::parse_from_rfc3339(trimmed)
+                .ok()
+                .map(|dt| dt.timestamp())
+        }
+        serde_json::Value::Object(obj) => {
+            if let Some(seconds) = obj.get("seconds").and_then(|v| v.as_i64()) {
+                return Some(seconds);
+            }
+            if let Some(seconds) = obj.get("unixSeconds").and_then(|v| v.as_i64()) {
+                return Some(seconds);
+            }
+            if let Some(inner) = obj.get("value") {
+                return parse_timestamp_like(inner);
+            }
+            None
+        }
+        _ => None,
     }
-    metrics
 }
 
-fn average_quota_percentage(metrics: &[(String, i32)]) -> f64 {
-    if metrics.is_empty() {
-        return 0.0;
+fn parse_timestamp_number(raw: f64) -> Option<i64> {
+    if !raw.is_finite() || raw <= 0.0 {
+        return None;
     }
-    let sum: i32 = metrics.iter().map(|(_, pct)| *pct).sum();
-    sum as f64 / metrics.len() as f64
+    if raw > 1e12 {
+        return Some((raw / 1000.0).floor() as i64);
+    }
+    Some(raw.floor() as i64)
 }
 
-fn resolve_current_account_id(accounts: &[WindsurfAccount]) -> Option<String> {
-    if let Ok(settings) = crate::modules::windsurf_instance::load_default_settings() {
-        if let Some(bind_id) = settings.bind_account_id {
-            let trimmed = bind_id.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
-        }
+fn to_platform_account(account: &WindsurfAccount) -> PlatformAccount {
+    PlatformAccount {
+        platform: WindsurfPlatform.id().to_string(),
+        id: account.id.clone(),
+        email: display_email(account),
+        tags: account.tags.clone().unwrap_or_default(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+        disabled: false,
+        forbidden: false,
+        quota_error: None,
+        token_expires_at: account.copilot_expires_at,
+        quota_stale: false,
+        quota: unified_quota(account),
     }
-
-    accounts
-        .iter()
-        .max_by_key(|account| account.last_used)
-        .map(|account| account.id.clone())
 }
 
-fn display_email(account: &WindsurfAccount) -> String {
-    account
-        .github_email
-        .clone()
-        .filter(|text| !text.trim().is_empty())
-        .unwrap_or_else(|| account.github_login.clone())
-}
+/// 切换账号：更新默认实例绑定账号并重启 Windsurf 默认实例（启动失败不阻断切号），返回切换结果说明
+pub fn switch_account_internal(account_id: &str) -> Result<String, String> {
+    credential_vault::ensure_unlocked()?;
+    let started_at = Instant::now();
+    logger::log_info(&format!(
+        "[Windsurf Switch] 开始切换账号: account_id={}",
+        account_id
+    ));
+    let account = load_account(account_id)
+        .ok_or_else(|| format!("Windsurf account not found: {}", account_id))?;
+    logger::log_info(&format!(
+        "[Windsurf Switch] 目标账号信息: login={}, email={}",
+        account.github_login,
+        account.github_email.as_deref().unwrap_or("-")
+    ));
 
-fn build_quota_alert_cooldown_key(account_id: &str, threshold: i32) -> String {
-    format!("windsurf:{}:{}", account_id, threshold)
-}
+    if let Err(e) = windsurf_instance::update_default_settings(
+        Some(Some(account_id.to_string())),
+        None,
+        Some(false),
+    ) {
+        logger::log_warn(&format!("更新 Windsurf 默认实例绑定账号失败: {}", e));
+    }
 
-fn should_emit_quota_alert(cooldown_key: &str, now: i64) -> bool {
-    let Ok(mut state) = WINDSURF_QUOTA_ALERT_LAST_SENT.lock() else {
-        return true;
+    let launch_warning = match windsurf_instance::start_default_instance() {
+        Ok(_) => None,
+        Err(e) if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("启动 Windsurf 失败") => {
+            logger::log_warn(&format!("Windsurf 默认实例启动失败: {}", e));
+            Some(e)
+        }
+        Err(e) => return Err(e),
     };
 
-    if let Some(last_sent) = state.get(cooldown_key) {
-        if now - *last_sent < WINDSURF_QUOTA_ALERT_COOLDOWN_SECONDS {
-            return false;
-        }
+    platform::notify_account_switched("windsurf", launch_warning.as_deref());
+    if let Some(err) = launch_warning {
+        logger::log_warn(&format!(
+            "[Windsurf Switch] 切号完成但启动失败: account_id={}, login={}, elapsed={}ms, error={}",
+            account.id,
+            account.github_login,
+            started_at.elapsed().as_millis(),
+            err
+        ));
+        Ok(format!("切换完成，但 Windsurf 启动失败: {}", err))
+    } else {
+        logger::log_info(&format!(
+            "[Windsurf Switch] 切号成功: account_id={}, login={}, elapsed={}ms",
+            account.id,
+            account.github_login,
+            started_at.elapsed().as_millis()
+        ));
+        Ok(format!("切换完成: {}", account.github_login))
     }
-
-    state.insert(cooldown_key.to_string(), now);
-    true
 }
 
-fn clear_quota_alert_cooldown(account_id: &str, threshold: i32) {
-    if let Ok(mut state) = WINDSURF_QUOTA_ALERT_LAST_SENT.lock() {
-        state.remove(&build_quota_alert_cooldown_key(account_id, threshold));
+pub struct WindsurfPlatform;
+
+impl Platform for WindsurfPlatform {
+    fn id(&self) -> &'static str {
+        "windsurf"
     }
-}
 
-fn pick_quota_alert_recommendation(
-    accounts: &[WindsurfAccount],
-    current_id: &str,
-) -> Option<WindsurfAccount> {
-    let mut candidates: Vec<WindsurfAccount> = accounts
-        .iter()
-        .filter(|account| account.id != current_id)
-        .filter(|account| !extract_quota_metrics(account).is_empty())
-        .cloned()
-        .collect();
+    fn display_name(&self) -> &'static str {
+        "Windsurf"
+    }
 
-    if candidates.is_empty() {
-        return None;
+    fn list_accounts(&self) -> Result<Vec<PlatformAccount>, String> {
+        Ok(list_accounts().iter().map(to_platform_account).collect())
     }
 
-    candidates.sort_by(|a, b| {
-        let avg_a = average_quota_percentage(&extract_quota_metrics(a));
-        let avg_b = average_quota_percentage(&extract_quota_metrics(b));
-        avg_b
-            .partial_cmp(&avg_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then_with(|| a.last_used.cmp(&b.last_used))
-    });
+    fn current_account_id(&self, accounts: &[PlatformAccount]) -> Option<String> {
+        resolve_current_account_id(accounts)
+    }
 
-    candidates.into_iter().next()
-}
+    fn remove_accounts(&self, account_ids: &[String]) -> Result<(), String> {
+        remove_accounts(account_ids)
+    }
 
-pub fn run_quota_alert_if_needed(
-) -> Result<Option<crate::modules::account::QuotaAlertPayload>, String> {
-    let cfg = crate::modules::config::get_user_config();
-    if !cfg.windsurf_quota_alert_enabled {
-        return Ok(None);
+    fn update_account_tags(&self, account_id: &str, tags: Vec<String>) -> Result<(), String> {
+        update_account_tags(account_id, tags).map(|_| ())
     }
 
-    let threshold = normalize_quota_alert_threshold(cfg.windsurf_quota_alert_threshold);
-    let accounts = list_accounts();
-    let current_id = match resolve_current_account_id(&accounts) {
-        Some(id) => id,
-        None => return Ok(None),
-    };
+    fn export_accounts(&self, account_ids: &[String]) -> Result<String, String> {
+        export_accounts(account_ids)
+    }
 
-    let current = match accounts.iter().find(|account| account.id == current_id) {
-        Some(account) => account,
-        None => return Ok(None),
-    };
+    fn import_from_json(&self, json_content: String) -> BoxFuture<'static, Result<usize, String>> {
+        Box::pin(async move { import_from_json(&json_content).map(|accounts| accounts.len()) })
+    }
 
-    let metrics = extract_quota_metrics(current);
-    let low_models: Vec<(String, i32)> = metrics
-        .into_iter()
-        .filter(|(_, pct)| *pct <= threshold)
-        .collect();
+    fn switch_account(&self, account_id: String) -> BoxFuture<'static, Result<String, String>> {
+        Box::pin(async move { switch_account_internal(&account_id) })
+    }
 
-    if low_models.is_empty() {
-        clear_quota_alert_cooldown(&current_id, threshold);
-        return Ok(None);
+    fn refresh_account(&self, account_id: String) -> BoxFuture<'static, Result<(), String>> {
+        Box::pin(async move { refresh_account_token(&account_id, true).await.map(|_| ()) })
     }
 
-    let now = chrono::Utc::now().timestamp();
-    let cooldown_key = build_quota_alert_cooldown_key(&current_id, threshold);
-    if !should_emit_quota_alert(&cooldown_key, now) {
-        return Ok(None);
+    fn refresh_all(&self) -> BoxFuture<'static, Result<RefreshReport, String>> {
+        Box::pin(async move { Ok(refresh_all_tokens().await?.report) })
     }
 
-    let recommendation = pick_quota_alert_recommendation(&accounts, &current_id);
-    let lowest_percentage = low_models.iter().map(|(_, pct)| *pct).min().unwrap_or(0);
-    let payload = crate::modules::account::QuotaAlertPayload {
-        platform: "windsurf".to_string(),
-        current_account_id: current_id,
-        current_email: display_email(current),
-        threshold,
-        lowest_percentage,
-        low_models: low_models.into_iter().map(|(name, _)| name).collect(),
-        recommended_account_id: recommendation.as_ref().map(|account| account.id.clone()),
-        recommended_email: recommendation.as_ref().map(display_email),
-        triggered_at: now,
-    };
+    fn refresh_interval_minutes(&self) -> i32 {
+        crate::modules::config::get_user_config().windsurf_auto_refresh_minutes
+    }
 
-    crate::modules::account::dispatch_quota_alert(&payload);
-    Ok(Some(payload))
+    fn quota_alert_settings(&self) -> platform::QuotaAlertSettings {
+        let cfg = crate::modules::config::get_user_config();
+        platform::QuotaAlertSettings {
+            enabled: cfg.windsurf_quota_alert_enabled,
+            threshold: cfg.windsurf_quota_alert_threshold,
+        }
+    }
 }
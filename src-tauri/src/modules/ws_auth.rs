@@ -0,0 +1,558 @@
+//! WebSocket 客户端认证与授权
+//!
+//! 客户端连接后需先认证，两种方式：
+//! - 共享密钥：服务启动时生成随机密钥写入 `server.json`（`auth_secret`），能读取该文件的本机客户端凭密钥认证；
+//! - 配对：客户端发起配对请求，桌面端展示配对码，用户核对后批准，客户端获得长期有效的令牌（只保存哈希）。
+//!
+//! 每个客户端按授予的权限范围访问：读取账号、读取令牌、切换账号、写入。
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use crate::modules::{account, logger};
+use crate::utils::atomic_file;
+
+const CLIENTS_FILE: &str = "ws_clients.json";
+/// 配对请求有效期（秒）
+const PAIRING_TTL_SECONDS: i64 = 300;
+/// 同时等待批准的配对请求上限
+const MAX_PENDING_PAIRINGS: usize = 8;
+/// last_seen_at 的最小写入间隔，避免每次令牌认证都重写客户端文件
+const LAST_SEEN_WRITE_INTERVAL_SECONDS: i64 = 600;
+pub const PAIRING_EVENT: &str = "ws:pairing_request";
+
+/// 客户端权限范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WsScope {
+    /// 读取账号列表、当前账号与配额，并接收事件推送
+    ReadAccounts,
+    /// 读取账号令牌（refresh_token / access_token）
+    ReadTokens,
+    /// 切换账号
+    Switch,
+    /// 修改数据（添加 / 删除账号、设置语言、通知数据变更）
+    Write,
+}
+
+impl WsScope {
+    pub const ALL: [WsScope; 4] = [
+        WsScope::ReadAccounts,
+        WsScope::ReadTokens,
+        WsScope::Switch,
+        WsScope::Write,
+    ];
+
+    /// 需要写审计日志的权限
+    pub fn is_privileged(self) -> bool {
+        !matches!(self, WsScope::ReadAccounts)
+    }
+}
+
+/// 连接的认证状态
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    /// 配对客户端 ID；凭共享密钥认证时为空
+    pub client_id: Option<String>,
+    pub client_name: Option<String>,
+    pub scopes: BTreeSet<WsScope>,
+}
+
+impl Session {
+    pub fn is_authenticated(&self) -> bool {
+        !self.scopes.is_empty()
+    }
+
+    pub fn allows(&self, scope: WsScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// 日志中的客户端标识
+    pub fn label(&self) -> String {
+        match (&self.client_name, &self.client_id) {
+            (Some(name), Some(id)) => format!("{} ({})", name, id),
+            (Some(name), None) => format!("{} (secret)", name),
+            (None, Some(id)) => id.clone(),
+            (None, None) => "secret".to_string(),
+        }
+    }
+}
+
+/// 已配对的客户端
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedClient {
+    pub id: String,
+    pub name: String,
+    pub scopes: BTreeSet<WsScope>,
+    pub created_at: i64,
+    #[serde(default)]
+    pub last_seen_at: Option<i64>,
+}
+
+/// 持久化记录：令牌只保存 SHA-256（十六进制）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ClientRecord {
+    #[serde(flatten)]
+    client: PairedClient,
+    token_hash: String,
+}
+
+/// 等待用户批准的配对请求
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingRequest {
+    pub pairing_id: String,
+    pub client_name: String,
+    pub scopes: BTreeSet<WsScope>,
+    /// 客户端同时展示的配对码，供用户核对
+    pub code: String,
+    pub addr: String,
+    pub expires_at: i64,
+    #[serde(skip)]
+    pub request_id: String,
+    #[serde(skip)]
+    pub socket: Option<SocketAddr>,
+}
+
+/// 批准配对后的结果，由 WebSocket 服务回传给客户端
+#[derive(Debug, Clone)]
+pub struct PairingApproval {
+    pub request: PairingRequest,
+    pub client: PairedClient,
+    pub token: String,
+}
+
+static SERVER_SECRET: OnceLock<String> = OnceLock::new();
+static CLIENTS_LOCK: Mutex<()> = Mutex::new(());
+static PENDING: OnceLock<Mutex<HashMap<String, PairingRequest>>> = OnceLock::new();
+
+fn pending() -> &'static Mutex<HashMap<String, PairingRequest>> {
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn now_ts() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn random_hex(bytes: usize) -> String {
+    (0..bytes)
+        .map(|_| format!("{:02x}", rand::random::<u8>()))
+        .collect()
+}
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// 定长比较，避免按字节提前返回泄露匹配长度
+fn constant_time_eq(left: &str, right: &str) -> bool {
+    let (left, right) = (left.as_bytes(), right.as_bytes());
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// 本次运行的共享密钥（随服务状态写入 server.json，每次启动重新生成）
+pub fn server_secret() -> &'static str {
+    SERVER_SECRET.get_or_init(|| random_hex(32))
+}
+
+fn normalize_scopes(scopes: Option<Vec<WsScope>>) -> BTreeSet<WsScope> {
+    match scopes {
+        Some(scopes) if !scopes.is_empty() => scopes.into_iter().collect(),
+        _ => WsScope::ALL.into_iter().collect(),
+    }
+}
+
+// ============ 已配对客户端 ============
+
+fn get_clients_path() -> Result<PathBuf, String> {
+    // 测试使用临时文件，不改动真实数据目录
+    if cfg!(test) {
+        let name = format!("cockpit-{}-{}", std::process::id(), CLIENTS_FILE);
+        return Ok(std::env::temp_dir().join(name));
+    }
+    Ok(account::get_data_dir()?.join(CLIENTS_FILE))
+}
+
+fn load_clients() -> Vec<ClientRecord> {
+    get_clients_path()
+        .ok()
+        .and_then(|path| atomic_file::recover_json::<Vec<ClientRecord>>(&path))
+        .unwrap_or_default()
+}
+
+fn save_clients(clients: &[ClientRecord]) -> Result<(), String> {
+    let path = get_clients_path()?;
+    let content = serde_json::to_string_pretty(clients)
+        .map_err(|e| format!("序列化已配对客户端失败: {}", e))?;
+    atomic_file::write(&path, content).map_err(|e| format!("保存已配对客户端失败: {}", e))
+}
+
+fn modify_clients<T>(f: impl FnOnce(&mut Vec<ClientRecord>) -> T) -> Result<T, String> {
+    let _lock = CLIENTS_LOCK
+        .lock()
+        .map_err(|_| "获取客户端列表锁失败".to_string())?;
+    let mut clients = load_clients();
+    let result = f(&mut clients);
+    save_clients(&clients)?;
+    Ok(result)
+}
+
+pub fn list_clients() -> Vec<PairedClient> {
+    load_clients()
+        .into_iter()
+        .map(|record| record.client)
+        .collect()
+}
+
+pub fn revoke_client(client_id: &str) -> Result<(), String> {
+    let removed = modify_clients(|clients| {
+        let before = clients.len();
+        clients.retain(|record| record.client.id != client_id);
+        before != clients.len()
+    })?;
+    if !removed {
+        return Err(format!("客户端不存在: {}", client_id));
+    }
+    logger::log_info(&format!("[WS][Auth] 已撤销客户端授权: {}", client_id));
+    Ok(())
+}
+
+// ============ 认证 ============
+
+/// 凭共享密钥或配对令牌认证；请求的权限范围为空时授予全部
+///
+/// 配对令牌只授予请求与批准范围的交集，交集为空时拒绝；最近连接时间按间隔节流写入。
+pub fn authenticate(
+    client_name: Option<String>,
+    secret: Option<&str>,
+    token: Option<&str>,
+    scopes: Option<Vec<WsScope>>,
+) -> Result<Session, String> {
+    let requested = normalize_scopes(scopes);
+
+    if let Some(secret) = secret.filter(|value| !value.is_empty()) {
+        if !constant_time_eq(secret, server_secret()) {
+            return Err("认证失败: 密钥无效".to_string());
+        }
+        return Ok(Session {
+            client_id: None,
+            client_name,
+            scopes: requested,
+        });
+    }
+
+    let Some(token) = token.filter(|value| !value.is_empty()) else {
+        return Err("认证失败: 缺少密钥或令牌".to_string());
+    };
+    let token_hash = hash_token(token);
+    let client = load_clients()
+        .into_iter()
+        .find(|record| constant_time_eq(&record.token_hash, &token_hash))
+        .map(|record| record.client)
+        .ok_or_else(|| "认证失败: 令牌无效或已撤销".to_string())?;
+    let scopes: BTreeSet<WsScope> = client.scopes.intersection(&requested).copied().collect();
+    if scopes.is_empty() {
+        return Err("认证失败: 请求的权限均未获批准".to_string());
+    }
+
+    let now = now_ts();
+    if client
+        .last_seen_at
+        .is_none_or(|seen| now - seen >= LAST_SEEN_WRITE_INTERVAL_SECONDS)
+    {
+        let touched = modify_clients(|clients| {
+            if let Some(record) = clients
+                .iter_mut()
+                .find(|record| record.client.id == client.id)
+            {
+                record.client.last_seen_at = Some(now);
+            }
+        });
+        if let Err(e) = touched {
+            logger::log_warn(&format!("[WS][Auth] 更新客户端最近连接时间失败: {}", e));
+        }
+    }
+
+    Ok(Session {
+        scopes,
+        client_name: Some(client.name),
+        client_id: Some(client.id),
+    })
+}
+
+// ============ 配对 ============
+
+fn prune_expired(pending: &mut HashMap<String, PairingRequest>, now: i64) {
+    pending.retain(|_, request| request.expires_at > now);
+}
+
+/// 创建配对请求，等待用户在桌面端批准
+pub fn start_pairing(
+    socket: SocketAddr,
+    request_id: String,
+    client_name: &str,
+    scopes: Option<Vec<WsScope>>,
+) -> Result<PairingRequest, String> {
+    let client_name = client_name.trim();
+    if client_name.is_empty() {
+        return Err("客户端名称不能为空".to_string());
+    }
+    let now = now_ts();
+    let mut pending = pending()
+        .lock()
+        .map_err(|_| "获取配对请求锁失败".to_string())?;
+    prune_expired(&mut pending, now);
+    if pending.len() >= MAX_PENDING_PAIRINGS {
+        return Err("等待批准的配对请求过多，请稍后再试".to_string());
+    }
+
+    let request = PairingRequest {
+        pairing_id: uuid::Uuid::new_v4().to_string(),
+        client_name: client_name.to_string(),
+        scopes: normalize_scopes(scopes),
+        code: format!("{:06}", rand::random::<u32>() % 1_000_000),
+        addr: socket.to_string(),
+        expires_at: now + PAIRING_TTL_SECONDS,
+        request_id,
+        socket: Some(socket),
+    };
+    pending.insert(request.pairing_id.clone(), request.clone());
+    logger::log_info(&format!(
+        "[WS][Auth] 新配对请求: client={}, addr={}",
+        request.client_name, request.addr
+    ));
+    Ok(request)
+}
+
+pub fn list_pairings() -> Vec<PairingRequest> {
+    let Ok(mut pending) = pending().lock() else {
+        return Vec::new();
+    };
+    prune_expired(&mut pending, now_ts());
+    let mut requests: Vec<PairingRequest> = pending.values().cloned().collect();
+    requests.sort_by_key(|request| request.expires_at);
+    requests
+}
+
+fn take_pairing(pairing_id: &str) -> Result<PairingRequest, String> {
+    let mut pending = pending()
+        .lock()
+        .map_err(|_| "获取配对请求锁失败".to_string())?;
+    prune_expired(&mut pending, now_ts());
+    pending
+        .remove(pairing_id)
+        .ok_or_else(|| "配对请求不存在或已过期".to_string())
+}
+
+/// 批准配对：可收窄客户端申请的权限范围，返回新客户端及其令牌（令牌只在此时返回一次）
+pub fn approve_pairing(
+    pairing_id: &str,
+    scopes: Option<Vec<WsScope>>,
+) -> Result<PairingApproval, String> {
+    let request = take_pairing(pairing_id)?;
+    let granted: BTreeSet<WsScope> = match scopes {
+        Some(scopes) => scopes
+            .into_iter()
+            .filter(|scope| request.scopes.contains(scope))
+            .collect(),
+        None => request.scopes.clone(),
+    };
+    if granted.is_empty() {
+        return Err("至少需要授予一项权限".to_string());
+    }
+
+    let token = random_hex(32);
+    let client = PairedClient {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: request.client_name.clone(),
+        scopes: granted,
+        created_at: now_ts(),
+        last_seen_at: None,
+    };
+    modify_clients(|clients| {
+        clients.push(ClientRecord {
+            client: client.clone(),
+            token_hash: hash_token(&token),
+        })
+    })?;
+    logger::log_info(&format!(
+        "[WS][Auth] 已批准配对: client={}, scopes={:?}",
+        client.name, client.scopes
+    ));
+    Ok(PairingApproval {
+        request,
+        client,
+        token,
+    })
+}
+
+pub fn reject_pairing(pairing_id: &str) -> Result<PairingRequest, String> {
+    let request = take_pairing(pairing_id)?;
+    logger::log_info(&format!(
+        "[WS][Auth] 已拒绝配对: client={}, addr={}",
+        request.client_name, request.addr
+    ));
+    Ok(request)
+}
+
+/// 连接断开时丢弃其未处理的配对请求
+pub fn drop_pairings_for(socket: SocketAddr) {
+    if let Ok(mut pending) = pending().lock() {
+        pending.retain(|_, request| request.socket != Some(socket));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_grants_requested_scopes_only() {
+        let session = authenticate(
+            Some("ext".to_string()),
+            Some(server_secret()),
+            None,
+            Some(vec![WsScope::ReadAccounts]),
+        )
+        .unwrap();
+        assert!(session.allows(WsScope::ReadAccounts));
+        assert!(!session.allows(WsScope::ReadTokens));
+
+        let full = authenticate(None, Some(server_secret()), None, None).unwrap();
+        assert_eq!(full.scopes.len(), WsScope::ALL.len());
+
+        assert!(authenticate(None, Some("wrong"), None, None).is_err());
+        assert!(authenticate(None, None, None, None).is_err());
+    }
+
+    fn socket() -> SocketAddr {
+        "127.0.0.1:50000".parse().unwrap()
+    }
+
+    fn pair(scopes: Vec<WsScope>, granted: Option<Vec<WsScope>>) -> PairingApproval {
+        let request = start_pairing(socket(), "req".to_string(), "ext", Some(scopes)).unwrap();
+        assert!(list_pairings()
+            .iter()
+            .any(|pending| pending.pairing_id == request.pairing_id));
+        approve_pairing(&request.pairing_id, granted).unwrap()
+    }
+
+    fn stored_last_seen(client_id: &str) -> Option<i64> {
+        list_clients()
+            .into_iter()
+            .find(|client| client.id == client_id)
+            .and_then(|client| client.last_seen_at)
+    }
+
+    #[test]
+    fn approved_pairing_token_grants_narrowed_scopes() {
+        let approval = pair(
+            vec![WsScope::ReadAccounts, WsScope::ReadTokens],
+            Some(vec![WsScope::ReadAccounts, WsScope::Write]),
+        );
+        // 批准时只能收窄，不能授予客户端未申请的权限
+        assert_eq!(
+            approval.client.scopes,
+            BTreeSet::from([WsScope::ReadAccounts])
+        );
+        assert!(approve_pairing(&approval.request.pairing_id, None).is_err());
+
+        let session = authenticate(None, None, Some(&approval.token), None).unwrap();
+        assert_eq!(
+            session.client_id.as_deref(),
+            Some(approval.client.id.as_str())
+        );
+        assert!(session.allows(WsScope::ReadAccounts));
+        assert!(!session.allows(WsScope::ReadTokens));
+        assert!(!session.allows(WsScope::Write));
+
+        // 只申请未获批准的权限时拒绝认证
+        assert!(authenticate(
+            None,
+            None,
+            Some(&approval.token),
+            Some(vec![WsScope::ReadTokens])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rejected_or_unsatisfiable_pairings_are_not_approved() {
+        let request = start_pairing(
+            socket(),
+            "req".to_string(),
+            "ext",
+            Some(vec![WsScope::Switch]),
+        )
+        .unwrap();
+        assert!(approve_pairing(&request.pairing_id, Some(vec![WsScope::Write])).is_err());
+
+        let request = start_pairing(socket(), "req".to_string(), "ext", None).unwrap();
+        reject_pairing(&request.pairing_id).unwrap();
+        assert!(approve_pairing(&request.pairing_id, None).is_err());
+        assert!(start_pairing(socket(), "req".to_string(), "  ", None).is_err());
+    }
+
+    #[test]
+    fn revoked_token_is_rejected() {
+        let approval = pair(vec![WsScope::Switch], None);
+        assert!(authenticate(None, None, Some(&approval.token), None).is_ok());
+
+        revoke_client(&approval.client.id).unwrap();
+        assert!(authenticate(None, None, Some(&approval.token), None).is_err());
+        assert!(revoke_client(&approval.client.id).is_err());
+        assert!(authenticate(None, None, Some("unknown-token"), None).is_err());
+    }
+
+    #[test]
+    fn unauthenticated_session_allows_nothing() {
+        let session = Session::default();
+        assert!(!session.is_authenticated());
+        assert!(WsScope::ALL.iter().all(|scope| !session.allows(*scope)));
+    }
+
+    #[test]
+    fn token_auth_throttles_last_seen_writes() {
+        let approval = pair(vec![WsScope::ReadAccounts], None);
+        let client_id = approval.client.id.clone();
+        let set_last_seen = |value: i64| {
+            modify_clients(|clients| {
+                for record in clients
+                    .iter_mut()
+                    .filter(|record| record.client.id == client_id)
+                {
+                    record.client.last_seen_at = Some(value);
+                }
+            })
+            .unwrap();
+        };
+
+        authenticate(None, None, Some(&approval.token), None).unwrap();
+        assert!(stored_last_seen(&client_id).is_some());
+
+        let recent = now_ts() - 5;
+        set_last_seen(recent);
+        authenticate(None, None, Some(&approval.token), None).unwrap();
+        assert_eq!(stored_last_seen(&client_id), Some(recent));
+
+        let stale = now_ts() - LAST_SEEN_WRITE_INTERVAL_SECONDS - 1;
+        set_last_seen(stale);
+        authenticate(None, None, Some(&approval.token), None).unwrap();
+        assert!(stored_last_seen(&client_id).unwrap() > stale);
+    }
+
+    #[test]
+    fn constant_time_eq_compares_whole_value() {
+        assert!(constant_time_eq("abc", "abc"));
+        assert!(!constant_time_eq("abc", "abd"));
+        assert!(!constant_time_eq("abc", "abcd"));
+    }
+}
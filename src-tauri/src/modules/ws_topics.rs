@@ -0,0 +1,216 @@
+//! WebSocket 事件订阅主题
+//!
+//! 客户端通过 request.subscribe / request.unsubscribe 选择要接收的事件主题，
+//! 从未订阅过的连接保持原有行为，接收全部事件。
+//! 状态类事件（当前账号、语言、实例状态、唤醒结果等）按主题保留最近一条，订阅时回放给客户端。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// 事件主题，传输时使用字符串形式，如 "accounts"、"quota"、"quota.codex"
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum WsTopic {
+    /// 账号数据变更与账号切换
+    Accounts,
+    /// 配额变化；平台为空时表示全部平台
+    Quota(Option<String>),
+    /// 多开实例的创建、删除、启动与关闭
+    Instances,
+    /// 唤醒任务执行结果
+    Wakeup,
+    /// 配额预警
+    Alerts,
+    /// 语言、唤醒互斥开关与用户配置变更
+    Config,
+}
+
+impl WsTopic {
+    /// 未指定主题时的全部主题
+    pub fn all() -> Vec<WsTopic> {
+        vec![
+            WsTopic::Accounts,
+            WsTopic::Quota(None),
+            WsTopic::Instances,
+            WsTopic::Wakeup,
+            WsTopic::Alerts,
+            WsTopic::Config,
+        ]
+    }
+
+    /// 订阅该主题时是否应收到 `event` 主题的事件
+    pub fn covers(&self, event: &WsTopic) -> bool {
+        match (self, event) {
+            (WsTopic::Quota(None), WsTopic::Quota(_)) => true,
+            _ => self == event,
+        }
+    }
+}
+
+impl fmt::Display for WsTopic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WsTopic::Accounts => write!(f, "accounts"),
+            WsTopic::Quota(None) => write!(f, "quota"),
+            WsTopic::Quota(Some(platform)) => write!(f, "quota.{}", platform),
+            WsTopic::Instances => write!(f, "instances"),
+            WsTopic::Wakeup => write!(f, "wakeup"),
+            WsTopic::Alerts => write!(f, "alerts"),
+            WsTopic::Config => write!(f, "config"),
+        }
+    }
+}
+
+impl TryFrom<String> for WsTopic {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "accounts" => Ok(WsTopic::Accounts),
+            "quota" => Ok(WsTopic::Quota(None)),
+            "instances" => Ok(WsTopic::Instances),
+            "wakeup" => Ok(WsTopic::Wakeup),
+            "alerts" => Ok(WsTopic::Alerts),
+            "config" => Ok(WsTopic::Config),
+            _ => match value.strip_prefix("quota.") {
+                Some(platform) if !platform.is_empty() => {
+                    Ok(WsTopic::Quota(Some(platform.to_string())))
+                }
+                _ => Err(format!("未知的订阅主题: {}", value)),
+            },
+        }
+    }
+}
+
+impl From<WsTopic> for String {
+    fn from(value: WsTopic) -> Self {
+        value.to_string()
+    }
+}
+
+/// 单个连接的订阅状态
+#[derive(Debug, Clone, Default)]
+pub struct Subscriptions {
+    /// None 表示从未订阅过，接收全部事件
+    topics: Option<BTreeSet<WsTopic>>,
+}
+
+impl Subscriptions {
+    /// 是否接收该主题的事件；没有主题的事件总是推送
+    pub fn accepts(&self, topic: Option<&WsTopic>) -> bool {
+        match (&self.topics, topic) {
+            (Some(topics), Some(topic)) => topics.iter().any(|item| item.covers(topic)),
+            _ => true,
+        }
+    }
+
+    pub fn subscribe(&mut self, topics: &[WsTopic]) {
+        self.topics
+            .get_or_insert_with(BTreeSet::new)
+            .extend(topics.iter().cloned());
+    }
+
+    /// 按订阅时的主题名取消；从未订阅过的连接视为已订阅全部主题
+    pub fn unsubscribe(&mut self, topics: &[WsTopic]) {
+        let current = self
+            .topics
+            .get_or_insert_with(|| WsTopic::all().into_iter().collect());
+        for topic in topics {
+            current.remove(topic);
+        }
+    }
+
+    /// 当前生效的主题列表
+    pub fn topics(&self) -> Vec<WsTopic> {
+        match &self.topics {
+            Some(topics) => topics.iter().cloned().collect(),
+            None => WsTopic::all(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RetainedEvent {
+    seq: u64,
+    topic: WsTopic,
+    json: String,
+}
+
+/// 各主题保留的最近事件（同一主题下按 key 覆盖）
+#[derive(Debug, Default)]
+pub struct RetainedEvents {
+    seq: u64,
+    events: HashMap<(WsTopic, String), RetainedEvent>,
+}
+
+impl RetainedEvents {
+    pub fn retain(&mut self, topic: WsTopic, key: String, json: String) {
+        self.seq += 1;
+        let event = RetainedEvent {
+            seq: self.seq,
+            topic: topic.clone(),
+            json,
+        };
+        self.events.insert((topic, key), event);
+    }
+
+    /// 按产生顺序返回订阅主题下保留的事件
+    pub fn replay(&self, topics: &[WsTopic]) -> Vec<String> {
+        let mut events: Vec<&RetainedEvent> = self
+            .events
+            .values()
+            .filter(|event| topics.iter().any(|topic| topic.covers(&event.topic)))
+            .collect();
+        events.sort_by_key(|event| event.seq);
+        events.into_iter().map(|event| event.json.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_topics_and_filters_by_subscription() {
+        let codex: WsTopic = serde_json::from_str("\"quota.codex\"").unwrap();
+        assert_eq!(codex, WsTopic::Quota(Some("codex".to_string())));
+        assert_eq!(serde_json::to_string(&codex).unwrap(), "\"quota.codex\"");
+        assert!(serde_json::from_str::<WsTopic>("\"quota.\"").is_err());
+        assert!(serde_json::from_str::<WsTopic>("\"unknown\"").is_err());
+
+        let kiro = WsTopic::Quota(Some("kiro".to_string()));
+        let mut subscriptions = Subscriptions::default();
+        assert!(subscriptions.accepts(Some(&WsTopic::Alerts)));
+
+        subscriptions.subscribe(&[codex.clone(), WsTopic::Alerts]);
+        assert!(subscriptions.accepts(Some(&codex)));
+        assert!(!subscriptions.accepts(Some(&kiro)));
+        assert!(!subscriptions.accepts(Some(&WsTopic::Accounts)));
+        assert!(subscriptions.accepts(None));
+
+        subscriptions.subscribe(&[WsTopic::Quota(None)]);
+        assert!(subscriptions.accepts(Some(&kiro)));
+
+        let mut legacy = Subscriptions::default();
+        legacy.unsubscribe(&[WsTopic::Wakeup]);
+        assert!(!legacy.accepts(Some(&WsTopic::Wakeup)));
+        assert!(legacy.accepts(Some(&kiro)));
+    }
+
+    #[test]
+    fn replays_latest_event_per_key_in_order() {
+        let mut retained = RetainedEvents::default();
+        retained.retain(WsTopic::Config, "language".to_string(), "zh".to_string());
+        retained.retain(WsTopic::Wakeup, "task-1".to_string(), "first".to_string());
+        retained.retain(WsTopic::Config, "language".to_string(), "en".to_string());
+        retained.retain(WsTopic::Accounts, "current".to_string(), "a".to_string());
+
+        assert_eq!(
+            retained.replay(&[WsTopic::Config, WsTopic::Wakeup]),
+            vec!["first".to_string(), "en".to_string()]
+        );
+        assert!(retained.replay(&[WsTopic::Alerts]).is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! 崩溃安全的文件写入
+//!
+//! 写入流程：临时文件 -> fsync -> 备份旧文件为 `<name>.bak` -> rename 覆盖 -> fsync 目录。
+//! 任意时刻断电，目标文件要么是旧版本要么是新版本，不会出现写了一半的内容。
+//! 包含凭据的账号文件不保留 `.bak`，避免加密后旧的明文版本留在磁盘上。
+
+use serde::de::DeserializeOwned;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 备份文件路径：`<path>.bak`
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(
+        ".tmp.{}.{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+    ));
+    path.with_file_name(name)
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(handle) = File::open(dir) {
+        let _ = handle.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
+
+/// 仅当前用户可读写（Unix）；其他平台依赖用户目录本身的访问控制
+#[cfg(unix)]
+fn restrict_to_owner(options: &mut OpenOptions) {
+    use std::os::unix::fs::OpenOptionsExt;
+    options.mode(0o600);
+}
+
+#[cfg(not(unix))]
+fn restrict_to_owner(_options: &mut OpenOptions) {}
+
+#[cfg(unix)]
+fn set_owner_only(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn set_owner_only(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn write_durable(path: &Path, contents: &[u8], keep_backup: bool, private: bool) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    fs::create_dir_all(&parent)?;
+
+    let tmp = temp_path(path);
+    let result = (|| {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        if private {
+            // 写入内容前权限就已生效，密钥不会短暂以默认权限落盘
+            restrict_to_owner(&mut options);
+        }
+        let mut file = options.open(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+
+        if keep_backup && path.exists() {
+            let bak = backup_path(path);
+            fs::copy(path, &bak)?;
+            if private {
+                // 旧版本写出的文件可能是默认权限，fs::copy 会沿用
+                set_owner_only(&bak)?;
+            }
+            if let Ok(handle) = File::open(&bak) {
+                let _ = handle.sync_all();
+            }
+        }
+
+        fs::rename(&tmp, path)?;
+        sync_dir(&parent);
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// 原子写入并保留上一版本为 `.bak`（用于本应用自有的数据文件）
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    write_durable(path.as_ref(), contents.as_ref(), true, false)
+}
+
+/// 同 `write`，但文件（含 `.bak`）仅当前用户可读写，用于包含密钥的文件
+pub fn write_private(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    write_durable(path.as_ref(), contents.as_ref(), true, true)
+}
+
+/// 原子写入包含凭据的文件：仅当前用户可读写，不生成 `.bak` 并删除已有的备份
+/// （旧版本可能是保险库启用前的明文凭据）
+pub fn write_secret(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+    write_durable(path, contents.as_ref(), false, true)?;
+    remove_backup(path)
+}
+
+/// 删除 `<path>.bak`（不存在时忽略）
+pub fn remove_backup(path: &Path) -> io::Result<()> {
+    match fs::remove_file(backup_path(path)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// 原子写入但不生成 `.bak`（用于写入其他程序的配置文件，如 auth.json）
+pub fn replace(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    write_durable(path.as_ref(), contents.as_ref(), false, false)
+}
+
+/// 主文件解析失败时尝试从 `.bak` 恢复：备份可解析则写回主文件并返回内容
+pub fn recover_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let bak = backup_path(path);
+    let content = fs::read_to_string(&bak).ok()?;
+    let value = serde_json::from_str::<T>(&content).ok()?;
+    match replace(path, &content) {
+        Ok(()) => crate::modules::logger::log_warn(&format!(
+            "[AtomicFile] 文件已从备份恢复: {}",
+            path.display()
+        )),
+        Err(e) => crate::modules::logger::log_warn(&format!(
+            "[AtomicFile] 使用备份内容，但写回主文件失败: path={}, error={}",
+            path.display(),
+            e
+        )),
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cockpit-atomic-file-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_keeps_previous_version_as_backup() {
+        let dir = scratch_dir("backup");
+        let path = dir.join("data.json");
+
+        write(&path, "{\"v\":1}").unwrap();
+        assert!(!backup_path(&path).exists());
+
+        write(&path, "{\"v\":2}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"v\":2}");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "{\"v\":1}");
+
+        let leftovers: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".tmp."))
+            .collect();
+        assert!(leftovers.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn recover_json_restores_from_backup() {
+        let dir = scratch_dir("recover");
+        let path = dir.join("data.json");
+
+        write(&path, "{\"v\":1}").unwrap();
+        write(&path, "{\"v\":2}").unwrap();
+        fs::write(&path, "{\"v\":").unwrap();
+
+        let value: serde_json::Value = recover_json(&path).unwrap();
+        assert_eq!(value["v"], 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"v\":1}");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn write_secret_drops_existing_backup() {
+        let dir = scratch_dir("secret");
+        let path = dir.join("account.json");
+
+        write(&path, "{\"token\":\"plain-1\"}").unwrap();
+        write(&path, "{\"token\":\"plain-2\"}").unwrap();
+        assert!(backup_path(&path).exists());
+
+        write_secret(&path, "{\"token\":\"enc:v1:abc\"}").unwrap();
+        assert!(!backup_path(&path).exists());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"token\":\"enc:v1:abc\"}"
+        );
+
+        write_secret(&path, "{\"token\":\"enc:v1:def\"}").unwrap();
+        assert!(!backup_path(&path).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_private_restricts_file_and_backup() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = scratch_dir("private");
+        let path = dir.join("status.json");
+        fs::write(&path, "{\"secret\":\"old\"}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, "{\"secret\":\"new\"}").unwrap();
+        let mode = |p: &Path| fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(&backup_path(&path)), 0o600);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,275 @@
+//! 跨进程文件锁
+//!
+//! 桌面端与 VS Code 插件共享 `~/.antigravity_cockpit` 下的数据文件，进程内的 `Mutex` 无法阻止双方同时
+//! 「读取-修改-写回」。这里为每个共享文件使用一个旁路锁文件 `<name>.lock` 加建议锁
+//! （Linux/macOS 为 `flock`，Windows 为 `LockFileEx`）：
+//!
+//! - 获得锁后把持有者信息（pid、用途、时间）写入锁文件，便于排查是谁占用了锁
+//! - 同一线程内可重入，嵌套调用不会自锁
+//! - 超时后返回包含持有者信息的错误，而不是无限等待
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 等待超过该时长时记录一次持有者信息
+const LOCK_WARN_AFTER: Duration = Duration::from_secs(2);
+const LOCK_OWNER_APP: &str = "cockpit-tools";
+
+thread_local! {
+    /// 当前线程已持有的锁及嵌套深度
+    static HELD_LOCKS: RefCell<HashMap<PathBuf, usize>> = RefCell::new(HashMap::new());
+}
+
+/// 锁持有者信息（写入锁文件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    #[serde(default)]
+    pub app: String,
+    #[serde(default)]
+    pub purpose: String,
+    /// 获得锁的时间（毫秒时间戳）
+    #[serde(default)]
+    pub acquired_at: i64,
+}
+
+/// 锁诊断信息
+#[derive(Debug, Clone, Serialize)]
+pub struct FileLockStatus {
+    pub file: String,
+    pub lock_path: String,
+    pub locked: bool,
+    pub holder: Option<LockHolder>,
+    /// 持有者进程是否仍在运行（无法判断时为 None）
+    pub holder_alive: Option<bool>,
+    pub held_for_ms: Option<i64>,
+}
+
+pub struct FileLockGuard {
+    key: PathBuf,
+    file: Option<File>,
+}
+
+impl Drop for FileLockGuard {
+    fn drop(&mut self) {
+        let outermost = HELD_LOCKS.with(|held| {
+            let mut held = held.borrow_mut();
+            match held.get_mut(&self.key) {
+                Some(depth) if *depth > 1 => {
+                    *depth -= 1;
+                    false
+                }
+                _ => {
+                    held.remove(&self.key);
+                    true
+                }
+            }
+        });
+        if !outermost {
+            return;
+        }
+        if let Some(file) = self.file.take() {
+            let _ = file.set_len(0);
+            let _ = file.unlock();
+        }
+    }
+}
+
+/// 锁文件路径：`<path>.lock`
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+fn open_lock_file(lock_file: &Path) -> Result<File, String> {
+    if let Some(parent) = lock_file.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建锁文件目录失败: {}", e))?;
+    }
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_file)
+        .map_err(|e| format!("打开锁文件失败: {}", e))
+}
+
+fn read_holder(lock_file: &Path) -> Option<LockHolder> {
+    let content = fs::read_to_string(lock_file).ok()?;
+    serde_json::from_str(content.trim()).ok()
+}
+
+fn is_pid_alive(pid: u32) -> bool {
+    let mut system = sysinfo::System::new();
+    let pid = sysinfo::Pid::from(pid as usize);
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        true,
+        sysinfo::ProcessRefreshKind::nothing(),
+    );
+    system.process(pid).is_some()
+}
+
+fn describe_holder(holder: Option<&LockHolder>) -> String {
+    match holder {
+        Some(holder) => format!(
+            "pid={}, app={}, purpose={}, acquired_at={}",
+            holder.pid, holder.app, holder.purpose, holder.acquired_at
+        ),
+        None => "未知持有者".to_string(),
+    }
+}
+
+fn write_holder(file: &mut File, purpose: &str) {
+    let holder = LockHolder {
+        pid: std::process::id(),
+        app: LOCK_OWNER_APP.to_string(),
+        purpose: purpose.to_string(),
+        acquired_at: chrono::Utc::now().timestamp_millis(),
+    };
+    if let Ok(content) = serde_json::to_string(&holder) {
+        let _ = file.set_len(0);
+        let _ = file.seek(SeekFrom::Start(0));
+        let _ = file.write_all(content.as_bytes());
+        let _ = file.sync_data();
+    }
+}
+
+/// 获取共享文件的排他锁，在返回的 guard 生命周期内有效
+pub fn lock_exclusive(path: &Path, purpose: &str) -> Result<FileLockGuard, String> {
+    let lock_file = lock_path(path);
+    let reentrant = HELD_LOCKS.with(|held| {
+        let mut held = held.borrow_mut();
+        match held.get_mut(&lock_file) {
+            Some(depth) => {
+                *depth += 1;
+                true
+            }
+            None => false,
+        }
+    });
+    if reentrant {
+        return Ok(FileLockGuard {
+            key: lock_file,
+            file: None,
+        });
+    }
+
+    let mut file = open_lock_file(&lock_file)?;
+    let started = Instant::now();
+    let mut warned = false;
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) => {
+                let waited = started.elapsed();
+                if waited >= LOCK_TIMEOUT {
+                    let holder = read_holder(&lock_file);
+                    return Err(format!(
+                        "等待文件锁超时 ({}s): {}，当前持有者: {}",
+                        LOCK_TIMEOUT.as_secs(),
+                        path.display(),
+                        describe_holder(holder.as_ref())
+                    ));
+                }
+                if !warned && waited >= LOCK_WARN_AFTER {
+                    warned = true;
+                    crate::modules::logger::log_warn(&format!(
+                        "[FileLock] 等待文件锁: file={}, purpose={}, holder=({})",
+                        path.display(),
+                        purpose,
+                        describe_holder(read_holder(&lock_file).as_ref())
+                    ));
+                }
+                std::thread::sleep(LOCK_POLL_INTERVAL);
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(format!("获取文件锁失败: {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    write_holder(&mut file, purpose);
+    HELD_LOCKS.with(|held| {
+        held.borrow_mut().insert(lock_file.clone(), 1);
+    });
+    Ok(FileLockGuard {
+        key: lock_file,
+        file: Some(file),
+    })
+}
+
+/// 诊断共享文件的锁状态（不会长时间占用锁）
+pub fn inspect(path: &Path) -> FileLockStatus {
+    let lock_file = lock_path(path);
+    let mut status = FileLockStatus {
+        file: path.to_string_lossy().to_string(),
+        lock_path: lock_file.to_string_lossy().to_string(),
+        locked: false,
+        holder: None,
+        holder_alive: None,
+        held_for_ms: None,
+    };
+    if !lock_file.exists() {
+        return status;
+    }
+
+    let held_here = HELD_LOCKS.with(|held| held.borrow().contains_key(&lock_file));
+    status.locked = held_here
+        || match open_lock_file(&lock_file) {
+            Ok(file) => match file.try_lock() {
+                Ok(()) => {
+                    let _ = file.unlock();
+                    false
+                }
+                Err(_) => true,
+            },
+            Err(_) => false,
+        };
+
+    if status.locked {
+        status.holder = read_holder(&lock_file);
+        if let Some(holder) = &status.holder {
+            status.holder_alive = Some(is_pid_alive(holder.pid));
+            if holder.acquired_at > 0 {
+                status.held_for_ms =
+                    Some(chrono::Utc::now().timestamp_millis() - holder.acquired_at);
+            }
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_is_reentrant_and_released_on_drop() {
+        let dir = std::env::temp_dir().join(format!("cockpit-file-lock-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("accounts.json");
+
+        let outer = lock_exclusive(&path, "outer").unwrap();
+        let inner = lock_exclusive(&path, "inner").unwrap();
+        drop(inner);
+        assert!(inspect(&path).locked);
+
+        let holder = read_holder(&lock_path(&path)).unwrap();
+        assert_eq!(holder.pid, std::process::id());
+        assert_eq!(holder.purpose, "outer");
+
+        drop(outer);
+        assert!(!inspect(&path).locked);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,2 +1,4 @@
+pub mod atomic_file;
+pub mod file_lock;
 pub mod http;
 pub mod protobuf;
@@ -128,6 +128,12 @@ type QuotaAlertPayload = {
   recommended_account_id?: string | null;
   recommended_email?: string | null;
   triggered_at: number;
+  rule_id?: string;
+  rule_name?: string;
+  severity?: 'info' | 'warning' | 'critical';
+  message?: string;
+  /** 汇总通知包含的原始预警 */
+  digest?: QuotaAlertPayload[];
 };
 
 type QuotaAlertPlatform = 'antigravity' | 'codex' | 'github_copilot' | 'windsurf' | 'kiro';
@@ -430,8 +436,8 @@ function App() {
         : t('quotaAlert.modal.unknownModel', '未知模型');
 
       showModal({
-        title: t('quotaAlert.modal.title', '配额预警'),
-        description: t(
+        title: payload.rule_name || t('quotaAlert.modal.title', '配额预警'),
+        description: payload.message || t(
           'quotaAlert.modal.desc',
           '当前账号配额已达到预警阈值，请尽快处理。'
         ),
@@ -1,10 +1,13 @@
 import { useCallback, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { useAccountStore } from '../stores/useAccountStore';
 import { useCodexAccountStore } from '../stores/useCodexAccountStore';
 import { useGitHubCopilotAccountStore } from '../stores/useGitHubCopilotAccountStore';
 import { useWindsurfAccountStore } from '../stores/useWindsurfAccountStore';
 import { useKiroAccountStore } from '../stores/useKiroAccountStore';
+import { useCursorAccountStore } from '../stores/useCursorAccountStore';
+import type { RefreshProgress } from '../services/platformService';
 
 interface GeneralConfig {
   language: string;
@@ -14,6 +17,7 @@ interface GeneralConfig {
   ghcp_auto_refresh_minutes: number;
   windsurf_auto_refresh_minutes: number;
   kiro_auto_refresh_minutes: number;
+  cursor_auto_refresh_minutes: number;
   auto_switch_enabled: boolean;
   close_behavior: string;
   opencode_app_path?: string;
@@ -26,28 +30,17 @@ interface GeneralConfig {
 }
 
 export function useAutoRefresh() {
-  const refreshAllQuotas = useAccountStore((state) => state.refreshAllQuotas);
   const syncCurrentFromClient = useAccountStore((state) => state.syncCurrentFromClient);
   const fetchAccounts = useAccountStore((state) => state.fetchAccounts);
   const fetchCurrentAccount = useAccountStore((state) => state.fetchCurrentAccount);
 
-  const refreshAllCodexQuotas = useCodexAccountStore((state) => state.refreshAllQuotas);
-  const refreshAllGhcpTokens = useGitHubCopilotAccountStore((state) => state.refreshAllTokens);
-  const refreshAllWindsurfTokens = useWindsurfAccountStore((state) => state.refreshAllTokens);
-  const refreshAllKiroTokens = useKiroAccountStore((state) => state.refreshAllTokens);
+  const fetchCodexAccounts = useCodexAccountStore((state) => state.fetchAccounts);
+  const fetchGhcpAccounts = useGitHubCopilotAccountStore((state) => state.fetchAccounts);
+  const fetchWindsurfAccounts = useWindsurfAccountStore((state) => state.fetchAccounts);
+  const fetchKiroAccounts = useKiroAccountStore((state) => state.fetchAccounts);
+  const fetchCursorAccounts = useCursorAccountStore((state) => state.fetchAccounts);
 
-  const agIntervalRef = useRef<number | null>(null);
   const autoSwitchIntervalRef = useRef<number | null>(null);
-  const codexIntervalRef = useRef<number | null>(null);
-  const ghcpIntervalRef = useRef<number | null>(null);
-  const windsurfIntervalRef = useRef<number | null>(null);
-  const kiroIntervalRef = useRef<number | null>(null);
-
-  const agRefreshingRef = useRef(false);
-  const codexRefreshingRef = useRef(false);
-  const ghcpRefreshingRef = useRef(false);
-  const windsurfRefreshingRef = useRef(false);
-  const kiroRefreshingRef = useRef(false);
   const autoSwitchRefreshingRef = useRef(false);
 
   const setupRunningRef = useRef(false);
@@ -55,32 +48,53 @@ export function useAutoRefresh() {
   const destroyedRef = useRef(false);
 
   const clearAllIntervals = useCallback(() => {
-    if (agIntervalRef.current) {
-      window.clearInterval(agIntervalRef.current);
-      agIntervalRef.current = null;
-    }
-    if (codexIntervalRef.current) {
-      window.clearInterval(codexIntervalRef.current);
-      codexIntervalRef.current = null;
-    }
-    if (ghcpIntervalRef.current) {
-      window.clearInterval(ghcpIntervalRef.current);
-      ghcpIntervalRef.current = null;
-    }
     if (autoSwitchIntervalRef.current) {
       window.clearInterval(autoSwitchIntervalRef.current);
       autoSwitchIntervalRef.current = null;
     }
-    if (windsurfIntervalRef.current) {
-      window.clearInterval(windsurfIntervalRef.current);
-      windsurfIntervalRef.current = null;
-    }
-    if (kiroIntervalRef.current) {
-      window.clearInterval(kiroIntervalRef.current);
-      kiroIntervalRef.current = null;
-    }
   }, []);
 
+  // 定时刷新由后端 refresh_scheduler 负责，这里只在刷新完成后同步界面数据
+  const handleRefreshProgress = useCallback(
+    async (progress: RefreshProgress) => {
+      if (progress.phase !== 'finished' || progress.trigger !== 'scheduled') {
+        return;
+      }
+      switch (progress.platform) {
+        case 'antigravity':
+          await fetchAccounts();
+          await fetchCurrentAccount();
+          break;
+        case 'codex':
+          await fetchCodexAccounts();
+          break;
+        case 'github_copilot':
+          await fetchGhcpAccounts();
+          break;
+        case 'windsurf':
+          await fetchWindsurfAccounts();
+          break;
+        case 'kiro':
+          await fetchKiroAccounts();
+          break;
+        case 'cursor':
+          await fetchCursorAccounts();
+          break;
+        default:
+          break;
+      }
+    },
+    [
+      fetchAccounts,
+      fetchCodexAccounts,
+      fetchCurrentAccount,
+      fetchCursorAccounts,
+      fetchGhcpAccounts,
+      fetchKiroAccounts,
+      fetchWindsurfAccounts,
+    ],
+  );
+
   const setupAutoRefresh = useCallback(async () => {
     if (destroyedRef.current) {
       return;
@@ -134,6 +148,7 @@ export function useAutoRefresh() {
                     ghcpAutoRefreshMinutes: config.ghcp_auto_refresh_minutes,
                     windsurfAutoRefreshMinutes: config.windsurf_auto_refresh_minutes,
                     kiroAutoRefreshMinutes: config.kiro_auto_refresh_minutes,
+                    cursorAutoRefreshMinutes: config.cursor_auto_refresh_minutes,
                     closeBehavior: config.close_behavior || 'ask',
                     opencodeAppPath: config.opencode_app_path ?? '',
                     antigravityAppPath: config.antigravity_app_path ?? '',
@@ -157,122 +172,6 @@ export function useAutoRefresh() {
 
           clearAllIntervals();
 
-          if (config.auto_refresh_minutes > 0) {
-            console.log(`[AutoRefresh] Antigravity 已启用: 每 ${config.auto_refresh_minutes} 分钟`);
-            const agMs = config.auto_refresh_minutes * 60 * 1000;
-
-            agIntervalRef.current = window.setInterval(async () => {
-              if (agRefreshingRef.current) {
-                return;
-              }
-              agRefreshingRef.current = true;
-
-              try {
-                console.log('[AutoRefresh] 触发定时配额刷新...');
-                await syncCurrentFromClient();
-                await refreshAllQuotas();
-              } catch (e) {
-                console.error('[AutoRefresh] 刷新失败:', e);
-              } finally {
-                agRefreshingRef.current = false;
-              }
-            }, agMs);
-          } else {
-            console.log('[AutoRefresh] Antigravity 已禁用');
-          }
-
-          if (config.codex_auto_refresh_minutes > 0) {
-            console.log(`[AutoRefresh] Codex 已启用: 每 ${config.codex_auto_refresh_minutes} 分钟`);
-            const codexMs = config.codex_auto_refresh_minutes * 60 * 1000;
-
-            codexIntervalRef.current = window.setInterval(async () => {
-              if (codexRefreshingRef.current) {
-                return;
-              }
-              codexRefreshingRef.current = true;
-
-              try {
-                console.log('[AutoRefresh] 触发 Codex 配额刷新...');
-                await refreshAllCodexQuotas();
-              } catch (e) {
-                console.error('[AutoRefresh] Codex 刷新失败:', e);
-              } finally {
-                codexRefreshingRef.current = false;
-              }
-            }, codexMs);
-          } else {
-            console.log('[AutoRefresh] Codex 已禁用');
-          }
-
-          if (config.ghcp_auto_refresh_minutes > 0) {
-            console.log(`[AutoRefresh] GitHub Copilot 已启用: 每 ${config.ghcp_auto_refresh_minutes} 分钟`);
-            const ghcpMs = config.ghcp_auto_refresh_minutes * 60 * 1000;
-
-            ghcpIntervalRef.current = window.setInterval(async () => {
-              if (ghcpRefreshingRef.current) {
-                return;
-              }
-              ghcpRefreshingRef.current = true;
-
-              try {
-                console.log('[AutoRefresh] 触发 GitHub Copilot Token 刷新...');
-                await refreshAllGhcpTokens();
-              } catch (e) {
-                console.error('[AutoRefresh] GitHub Copilot 刷新失败:', e);
-              } finally {
-                ghcpRefreshingRef.current = false;
-              }
-            }, ghcpMs);
-          } else {
-            console.log('[AutoRefresh] GitHub Copilot 已禁用');
-          }
-
-          if (config.windsurf_auto_refresh_minutes > 0) {
-            console.log(`[AutoRefresh] Windsurf 已启用: 每 ${config.windsurf_auto_refresh_minutes} 分钟`);
-            const windsurfMs = config.windsurf_auto_refresh_minutes * 60 * 1000;
-
-            windsurfIntervalRef.current = window.setInterval(async () => {
-              if (windsurfRefreshingRef.current) {
-                return;
-              }
-              windsurfRefreshingRef.current = true;
-
-              try {
-                console.log('[AutoRefresh] 触发 Windsurf 配额刷新...');
-                await refreshAllWindsurfTokens();
-              } catch (e) {
-                console.error('[AutoRefresh] Windsurf 刷新失败:', e);
-              } finally {
-                windsurfRefreshingRef.current = false;
-              }
-            }, windsurfMs);
-          } else {
-            console.log('[AutoRefresh] Windsurf 已禁用');
-          }
-
-          if (config.kiro_auto_refresh_minutes > 0) {
-            console.log(`[AutoRefresh] Kiro 已启用: 每 ${config.kiro_auto_refresh_minutes} 分钟`);
-            const kiroMs = config.kiro_auto_refresh_minutes * 60 * 1000;
-
-            kiroIntervalRef.current = window.setInterval(async () => {
-              if (kiroRefreshingRef.current) {
-                return;
-              }
-              kiroRefreshingRef.current = true;
-
-              try {
-                console.log('[AutoRefresh] 触发 Kiro 配额刷新...');
-                await refreshAllKiroTokens();
-              } catch (e) {
-                console.error('[AutoRefresh] Kiro 刷新失败:', e);
-              } finally {
-                kiroRefreshingRef.current = false;
-              }
-            }, kiroMs);
-          } else {
-            console.log('[AutoRefresh] Kiro 已禁用');
-          }
-
           // 自动切号开启时，额外每 60 秒刷新当前账号（不影响原有配额自动刷新规则）
           if (config.auto_switch_enabled) {
             console.log('[AutoRefresh] 自动切号已启用: 每 60 秒刷新当前账号');
@@ -303,17 +202,7 @@ export function useAutoRefresh() {
     } finally {
       setupRunningRef.current = false;
     }
-  }, [
-    clearAllIntervals,
-    fetchAccounts,
-    fetchCurrentAccount,
-    refreshAllCodexQuotas,
-    refreshAllGhcpTokens,
-    refreshAllKiroTokens,
-    refreshAllQuotas,
-    refreshAllWindsurfTokens,
-    syncCurrentFromClient,
-  ]);
+  }, [clearAllIntervals, fetchAccounts, fetchCurrentAccount, syncCurrentFromClient]);
 
   useEffect(() => {
     destroyedRef.current = false;
@@ -326,11 +215,25 @@ export function useAutoRefresh() {
 
     window.addEventListener('config-updated', handleConfigUpdate);
 
+    let unlistenProgress: UnlistenFn | undefined;
+    listen<RefreshProgress>('refresh:progress', (event) => {
+      void handleRefreshProgress(event.payload).catch((e) => {
+        console.error('[AutoRefresh] 同步刷新结果失败:', e);
+      });
+    }).then((fn) => {
+      if (destroyedRef.current) {
+        fn();
+      } else {
+        unlistenProgress = fn;
+      }
+    });
+
     return () => {
       destroyedRef.current = true;
       setupPendingRef.current = false;
       clearAllIntervals();
       window.removeEventListener('config-updated', handleConfigUpdate);
+      unlistenProgress?.();
     };
-  }, [clearAllIntervals, setupAutoRefresh]);
+  }, [clearAllIntervals, handleRefreshProgress, setupAutoRefresh]);
 }
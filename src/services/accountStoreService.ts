@@ -0,0 +1,40 @@
+/**
+ * 统一账号存储服务
+ * 与后端 account_store 模块（cockpit.db）交互
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface PlatformStoreReport {
+  platform: string;
+  accounts: number;
+  skipped: number;
+}
+
+/** 统一账号存储状态 */
+export interface AccountStoreStatus {
+  active: boolean;
+  db_path: string;
+  schema_version: number | null;
+  imported_at: number | null;
+  rolled_back_at: number | null;
+  platforms: PlatformStoreReport[];
+}
+
+export async function getAccountStoreStatus(): Promise<AccountStoreStatus> {
+  return invoke<AccountStoreStatus>('account_store_get_status');
+}
+
+/**
+ * 从 JSON 文件布局导入并启用数据库存储（默认不启用；导入后删除原账号文件）
+ */
+export async function importAccountStoreFromJson(): Promise<PlatformStoreReport[]> {
+  return invoke<PlatformStoreReport[]>('account_store_import_from_json');
+}
+
+/**
+ * 导出回 JSON 文件布局并切回文件存储
+ */
+export async function rollbackAccountStoreToJson(): Promise<PlatformStoreReport[]> {
+  return invoke<PlatformStoreReport[]>('account_store_rollback_to_json');
+}
@@ -0,0 +1,29 @@
+/**
+ * 预警通知调度服务
+ * 免打扰时段内暂存预警、跨平台去重，以及按周期合并为一条汇总通知
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface AlertNotifySettings {
+  quiet_hours_enabled: boolean;
+  /** HH:MM，本地时间 */
+  quiet_start: string;
+  /** HH:MM，早于开始时间表示跨天 */
+  quiet_end: string;
+  digest_enabled: boolean;
+  /** 汇总周期（分钟），最少 5 分钟 */
+  digest_interval_minutes: number;
+  /** 去重窗口（分钟），0 表示不去重 */
+  dedup_window_minutes: number;
+}
+
+export async function getAlertNotifySettings(): Promise<AlertNotifySettings> {
+  return await invoke('alert_notify_get_settings');
+}
+
+export async function saveAlertNotifySettings(
+  settings: AlertNotifySettings,
+): Promise<AlertNotifySettings> {
+  return await invoke('alert_notify_save_settings', { settings });
+}
@@ -0,0 +1,71 @@
+/**
+ * 配额预警规则服务
+ * 内置规则由各平台的配额预警设置生成（只读），自定义规则可按平台 / 标签 / 账号 / 模型分组设置条件
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { PlatformId } from './platformService';
+
+export interface AlertScope {
+  platform?: PlatformId | null;
+  tag?: string | null;
+  account_id?: string | null;
+  /** 模型分组 ID（来自分组设置） */
+  model_group?: string | null;
+  /** 只检查平台当前账号 */
+  current_only?: boolean;
+}
+
+export type AlertCondition =
+  | { type: 'remaining_below'; percent: number }
+  | { type: 'exhausts_within'; minutes: number }
+  | { type: 'token_expiring'; minutes: number }
+  | { type: 'account_disabled' };
+
+export type AlertSeverity = 'info' | 'warning' | 'critical';
+
+export interface AlertRule {
+  /** 新建时留空 */
+  id: string;
+  name: string;
+  enabled: boolean;
+  scope: AlertScope;
+  condition: AlertCondition;
+  cooldown_minutes: number;
+  severity: AlertSeverity;
+  builtin: boolean;
+}
+
+export interface AlertMatch {
+  platform: PlatformId;
+  account_id: string;
+  email: string;
+  metrics: string[];
+  lowest_percentage: number | null;
+  message: string;
+}
+
+/**
+ * 列出全部预警规则
+ */
+export async function listAlertRules(): Promise<AlertRule[]> {
+  return await invoke('alert_rule_list');
+}
+
+/**
+ * 保存自定义规则，返回保存后的规则（含生成的 id）
+ */
+export async function saveAlertRule(rule: AlertRule): Promise<AlertRule> {
+  return await invoke('alert_rule_save', { rule });
+}
+
+export async function deleteAlertRule(ruleId: string): Promise<void> {
+  return await invoke('alert_rule_delete', { ruleId });
+}
+
+/**
+ * 用当前数据试运行规则，返回命中的账号
+ */
+export async function testAlertRule(rule: AlertRule): Promise<AlertMatch[]> {
+  return await invoke('alert_rule_test', { rule });
+}
@@ -0,0 +1,40 @@
+/**
+ * 预警 Webhook 通知服务
+ * 预警发出时推送到 HTTP Webhook，支持通用 JSON 与 Slack / Discord / 飞书消息格式
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type WebhookTemplate = 'json' | 'slack' | 'discord' | 'feishu';
+
+export interface WebhookSettings {
+  enabled: boolean;
+  url: string;
+  template: WebhookTemplate;
+  /** 签名密钥，留空则不签名 */
+  secret?: string | null;
+  /** 失败重试次数（不含首次），最多 10 */
+  max_retries: number;
+  timeout_seconds: number;
+}
+
+export interface WebhookDelivery {
+  status: number;
+  /** 实际发送次数（含首次） */
+  attempts: number;
+}
+
+export async function getWebhookSettings(): Promise<WebhookSettings> {
+  return await invoke('alert_webhook_get_settings');
+}
+
+export async function saveWebhookSettings(settings: WebhookSettings): Promise<WebhookSettings> {
+  return await invoke('alert_webhook_save_settings', { settings });
+}
+
+/**
+ * 发送测试消息；传入 settings 时使用未保存的设置
+ */
+export async function sendWebhookTest(settings?: WebhookSettings): Promise<WebhookDelivery> {
+  return await invoke('alert_webhook_send_test', { settings: settings ?? null });
+}
@@ -0,0 +1,72 @@
+/**
+ * 数据目录快照备份服务
+ * 与后端 backup 模块交互
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** 快照元数据 */
+export interface SnapshotMeta {
+  id: string;
+  /** scheduled / manual / before_restore / before_<操作名> */
+  reason: string;
+  created_at: number;
+  file_count: number;
+  total_size: number;
+  compressed_size: number;
+}
+
+export interface SnapshotDiff {
+  from: string;
+  /** 快照 ID，或 current 表示当前数据目录 */
+  to: string;
+  added: string[];
+  removed: string[];
+  modified: string[];
+  unchanged: number;
+}
+
+export interface RestoreReport {
+  snapshot_id: string;
+  /** 恢复前自动创建的快照，可用于撤销 */
+  safety_snapshot_id: string;
+  restored: number;
+  removed: number;
+}
+
+export interface BackupSettings {
+  enabled: boolean;
+  interval_hours: number;
+  retention_count: number;
+  retention_days: number;
+}
+
+export async function listSnapshots(): Promise<SnapshotMeta[]> {
+  return invoke<SnapshotMeta[]>('backup_list_snapshots');
+}
+
+export async function createSnapshot(): Promise<SnapshotMeta> {
+  return invoke<SnapshotMeta>('backup_create_snapshot');
+}
+
+/**
+ * 对比快照；不传 compareTo 时与当前数据目录对比
+ */
+export async function diffSnapshot(snapshotId: string, compareTo?: string): Promise<SnapshotDiff> {
+  return invoke<SnapshotDiff>('backup_diff_snapshot', { snapshotId, compareTo });
+}
+
+/**
+ * 恢复到指定快照（恢复前会自动为当前状态创建快照）
+ */
+export async function restoreSnapshot(snapshotId: string): Promise<RestoreReport> {
+  return invoke<RestoreReport>('backup_restore_snapshot', { snapshotId });
+}
+
+export async function getBackupSettings(): Promise<BackupSettings> {
+  return invoke<BackupSettings>('backup_get_settings');
+}
+
+export async function saveBackupSettings(settings: BackupSettings): Promise<BackupSettings> {
+  return invoke<BackupSettings>('backup_save_settings', { settings });
+}
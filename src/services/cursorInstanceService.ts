@@ -0,0 +1,13 @@
+import { createPlatformInstanceService } from './platform/createPlatformInstanceService';
+
+const service = createPlatformInstanceService('cursor');
+
+export const getInstanceDefaults = service.getInstanceDefaults;
+export const listInstances = service.listInstances;
+export const createInstance = service.createInstance;
+export const updateInstance = service.updateInstance;
+export const deleteInstance = service.deleteInstance;
+export const startInstance = service.startInstance;
+export const stopInstance = service.stopInstance;
+export const closeAllInstances = service.closeAllInstances;
+export const openInstanceWindow = service.openInstanceWindow;
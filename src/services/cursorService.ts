@@ -0,0 +1,55 @@
+import { invoke } from '@tauri-apps/api/core';
+import { CursorAccount } from '../types/cursor';
+
+/** 列出所有 Cursor 账号 */
+export async function listCursorAccounts(): Promise<CursorAccount[]> {
+  return await invoke('list_cursor_accounts');
+}
+
+/** 删除 Cursor 账号 */
+export async function deleteCursorAccount(accountId: string): Promise<void> {
+  return await invoke('delete_cursor_account', { accountId });
+}
+
+/** 批量删除 Cursor 账号 */
+export async function deleteCursorAccounts(accountIds: string[]): Promise<void> {
+  return await invoke('delete_cursor_accounts', { accountIds });
+}
+
+/** 从 JSON 字符串导入账号 */
+export async function importCursorFromJson(jsonContent: string): Promise<CursorAccount[]> {
+  return await invoke('import_cursor_from_json', { jsonContent });
+}
+
+/** 从本机 Cursor 客户端导入当前登录账号 */
+export async function importCursorFromLocal(): Promise<CursorAccount[]> {
+  return await invoke('import_cursor_from_local');
+}
+
+/** 导出 Cursor 账号 */
+export async function exportCursorAccounts(accountIds: string[]): Promise<string> {
+  return await invoke('export_cursor_accounts', { accountIds });
+}
+
+/** 刷新单个账号用量 */
+export async function refreshCursorToken(accountId: string): Promise<CursorAccount> {
+  return await invoke('refresh_cursor_token', { accountId });
+}
+
+/** 刷新全部账号用量 */
+export async function refreshAllCursorTokens(): Promise<number> {
+  return await invoke('refresh_all_cursor_tokens');
+}
+
+export async function updateCursorAccountTags(accountId: string, tags: string[]): Promise<CursorAccount> {
+  return await invoke('update_cursor_account_tags', { accountId, tags });
+}
+
+export async function getCursorAccountsIndexPath(): Promise<string> {
+  return await invoke('get_cursor_accounts_index_path');
+}
+
+/** 将 Cursor 账号注入到 Cursor 默认实例 */
+export async function injectCursorToVSCode(accountId: string): Promise<string> {
+  return await invoke('inject_cursor_to_vscode', { accountId });
+}
@@ -21,6 +21,26 @@ export interface DisplayGroup {
   models: string[];
 }
 
+/** 单个账号在分组内的剩余容量 */
+export interface GroupAccountCapacity {
+  account_id: string;
+  email: string;
+  remaining_pct: number;          // 分组内模型剩余百分比平均值
+  resets_at: number | null;       // 最早重置时间（Unix 秒）
+  exhausted: boolean;
+}
+
+/** 分组在全部账号上的汇总容量 */
+export interface GroupCapacity {
+  group_id: string;
+  group_name: string;
+  account_count: number;
+  total_remaining_pct: number;    // 各账号剩余百分比之和
+  earliest_reset_at: number | null;
+  exhausted_count: number;
+  accounts: GroupAccountCapacity[];
+}
+
 /**
  * 获取完整分组配置
  */
@@ -85,6 +105,13 @@ export async function getDisplayGroups(): Promise<DisplayGroup[]> {
   return invoke<DisplayGroup[]>('get_display_groups');
 }
 
+/**
+ * 获取各分组在全部 Antigravity 账号上的汇总容量
+ */
+export async function getGroupCapacity(): Promise<GroupCapacity[]> {
+  return invoke<GroupCapacity[]>('get_group_capacity');
+}
+
 /**
  * 默认分组配置（用于初始化）
  */
@@ -0,0 +1,74 @@
+/**
+ * 模型目录服务
+ * 按订阅等级记录 Antigravity 模型目录快照与变化，并为未归组的新模型提供分组建议
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** 目录变化事件名（载荷为 CatalogChange） */
+export const MODEL_CATALOG_CHANGED_EVENT = 'model_catalog:changed';
+
+export interface CatalogModel {
+  id: string;
+  display_name: string;
+}
+
+export interface CatalogSnapshot {
+  /** 订阅等级（FREE / PRO / ULTRA / UNKNOWN） */
+  tier: string;
+  models: CatalogModel[];
+  /** Unix 秒 */
+  captured_at: number;
+}
+
+export interface ModelRename {
+  from_id: string;
+  to_id: string;
+  display_name: string;
+}
+
+export interface DisplayNameChange {
+  id: string;
+  from: string;
+  to: string;
+}
+
+export interface CatalogChange {
+  tier: string;
+  detected_at: number;
+  added: CatalogModel[];
+  removed: CatalogModel[];
+  renamed: ModelRename[];
+  display_name_changes: DisplayNameChange[];
+}
+
+export interface GroupSuggestion {
+  model_id: string;
+  display_name: string;
+  /** 按同系列模型推测的分组，无法推测时为 null */
+  suggested_group_id: string | null;
+  detected_at: number;
+}
+
+export async function listModelCatalogSnapshots(): Promise<CatalogSnapshot[]> {
+  return await invoke('model_catalog_list_snapshots');
+}
+
+/** 最近的变化记录（新的在前） */
+export async function listModelCatalogChanges(limit?: number): Promise<CatalogChange[]> {
+  return await invoke('model_catalog_list_changes', { limit: limit ?? null });
+}
+
+export async function listGroupSuggestions(): Promise<GroupSuggestion[]> {
+  return await invoke('model_catalog_list_suggestions');
+}
+
+/** 把新模型加入分组；不传 groupId 时使用推测的分组 */
+export async function acceptGroupSuggestion(modelId: string, groupId?: string): Promise<void> {
+  return await invoke('model_catalog_accept_suggestion', { modelId, groupId: groupId ?? null });
+}
+
+/** 忽略建议，之后不再为该模型生成建议 */
+export async function dismissGroupSuggestion(modelId: string): Promise<void> {
+  return await invoke('model_catalog_dismiss_suggestion', { modelId });
+}
@@ -0,0 +1,187 @@
+/**
+ * 跨平台通用账号服务
+ * 与后端 platform 注册表交互，平台标识: antigravity / codex / github_copilot / windsurf / kiro / cursor
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type PlatformId = 'antigravity' | 'codex' | 'github_copilot' | 'windsurf' | 'kiro' | 'cursor';
+
+export interface PlatformInfo {
+  id: PlatformId;
+  display_name: string;
+  account_count: number;
+  current_account_id: string | null;
+}
+
+/** 配额重置周期：滚动窗口（分钟）或按账单周期 */
+export type QuotaWindow = { kind: 'rolling'; minutes: number } | { kind: 'billing_cycle' };
+
+export interface QuotaMetric {
+  /** 平台内稳定的指标标识 */
+  id: string;
+  label: string;
+  /** 已用量 / 总量（平台只提供百分比时为 null） */
+  used: number | null;
+  total: number | null;
+  /** 剩余百分比 0-100 */
+  remaining_pct: number;
+  window: QuotaWindow | null;
+  /** 下次重置时间（Unix 秒） */
+  resets_at: number | null;
+  /** 计划内包含、不限额度（此时 remaining_pct 固定为 100） */
+  unlimited: boolean;
+}
+
+export interface UnifiedQuota {
+  metrics: QuotaMetric[];
+}
+
+export interface PlatformAccount {
+  platform: PlatformId;
+  id: string;
+  email: string;
+  tags: string[];
+  created_at: number;
+  last_used: number;
+  /** 已禁用 / 封禁 / 无权限 */
+  disabled: boolean;
+  /** 配额接口返回无权限 */
+  forbidden: boolean;
+  /** 最近一次配额刷新的错误信息 */
+  quota_error: string | null;
+  /** 访问令牌过期时间（Unix 秒） */
+  token_expires_at: number | null;
+  /** 配额可能已过期（离线模式、最近刷新失败或使用了过期缓存） */
+  quota_stale: boolean;
+  quota: UnifiedQuota;
+}
+
+/** 配额变化事件（事件 quota:changed，WebSocket event.quota_changed） */
+export type QuotaChange =
+  | {
+      type: 'quota.reset_detected';
+      metric: string;
+      label: string;
+      previous_percent: number;
+      remaining_percent: number;
+      resets_at: number | null;
+    }
+  | {
+      type: 'quota.threshold_crossed';
+      metric: string;
+      label: string;
+      threshold: number;
+      previous_percent: number;
+      remaining_percent: number;
+    }
+  | { type: 'quota.forbidden' }
+  | { type: 'quota.error'; message: string };
+
+export type QuotaEvent = QuotaChange & {
+  platform: PlatformId;
+  account_id: string;
+  email: string;
+  occurred_at: number;
+};
+
+export interface RefreshStats {
+  total: number;
+  success: number;
+  failed: number;
+  details: string[];
+}
+
+export type RefreshOutcome = 'refreshed' | 'failed' | 'backing_off' | 'offline';
+
+export interface AccountRefreshResult {
+  account_id: string;
+  outcome: RefreshOutcome;
+  error: string | null;
+  /** 连续失败次数（成功后清零） */
+  consecutive_failures: number;
+  /** 退避结束时间（Unix 秒） */
+  next_retry_at: number | null;
+  elapsed_ms: number;
+}
+
+/** 一次全量刷新的逐账号结果报告 */
+export interface RefreshReport {
+  platform: PlatformId;
+  started_at: number;
+  elapsed_ms: number;
+  total: number;
+  refreshed: number;
+  failed: number;
+  backing_off: number;
+  /** 离线模式下跳过的账号数 */
+  offline: number;
+  accounts: AccountRefreshResult[];
+}
+
+/** 后端定时刷新进度（事件 refresh:progress） */
+export interface RefreshProgress {
+  platform: PlatformId;
+  trigger: 'scheduled' | 'manual';
+  phase: 'started' | 'finished' | 'failed';
+  report: RefreshReport | null;
+  error: string | null;
+}
+
+export interface RefreshScheduleStatus {
+  platform: PlatformId;
+  interval_minutes: number;
+  /** 下次自动刷新时间（Unix 秒） */
+  next_run_at: number;
+  last_run_at: number | null;
+  running: boolean;
+}
+
+/** 列出已注册的平台 */
+export async function listPlatforms(): Promise<PlatformInfo[]> {
+  return invoke('platform_list');
+}
+
+export async function listPlatformAccounts(platform: PlatformId): Promise<PlatformAccount[]> {
+  return invoke('platform_list_accounts', { platform });
+}
+
+export async function deletePlatformAccounts(platform: PlatformId, accountIds: string[]): Promise<void> {
+  return invoke('platform_delete_accounts', { platform, accountIds });
+}
+
+export async function updatePlatformAccountTags(
+  platform: PlatformId,
+  accountId: string,
+  tags: string[],
+): Promise<void> {
+  return invoke('platform_update_account_tags', { platform, accountId, tags });
+}
+
+/** accountIds 为空时导出该平台全部账号 */
+export async function exportPlatformAccounts(platform: PlatformId, accountIds: string[] = []): Promise<string> {
+  return invoke('platform_export_accounts', { platform, accountIds });
+}
+
+/** 返回导入的账号数 */
+export async function importPlatformAccounts(platform: PlatformId, jsonContent: string): Promise<number> {
+  return invoke('platform_import_from_json', { platform, jsonContent });
+}
+
+/** 切换账号，返回切换结果提示 */
+export async function switchPlatformAccount(platform: PlatformId, accountId: string): Promise<string> {
+  return invoke('platform_switch_account', { platform, accountId });
+}
+
+export async function refreshPlatformAccount(platform: PlatformId, accountId: string): Promise<void> {
+  return invoke('platform_refresh_account', { platform, accountId });
+}
+
+export async function refreshAllPlatformAccounts(platform: PlatformId): Promise<RefreshReport> {
+  return invoke('platform_refresh_all', { platform });
+}
+
+/** 各平台后台自动刷新计划（仅包含已启用的平台） */
+export async function getRefreshSchedule(): Promise<RefreshScheduleStatus[]> {
+  return invoke('platform_refresh_schedule');
+}
@@ -0,0 +1,25 @@
+/**
+ * 配额接口缓存服务
+ * 按平台 + 账号缓存配额接口响应；请求失败时回退到过期缓存，离线模式下只展示缓存数据
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface QuotaCacheSettings {
+  /** 缓存有效期（秒），0 表示总是请求接口 */
+  ttl_seconds: number;
+  /** 请求失败时使用过期缓存 */
+  serve_stale_on_error: boolean;
+  /** 离线模式：不请求接口，账号配额带过期标记（quota_stale） */
+  offline_mode: boolean;
+}
+
+export async function getQuotaCacheSettings(): Promise<QuotaCacheSettings> {
+  return await invoke('quota_cache_get_settings');
+}
+
+export async function saveQuotaCacheSettings(
+  settings: QuotaCacheSettings,
+): Promise<QuotaCacheSettings> {
+  return await invoke('quota_cache_save_settings', { settings });
+}
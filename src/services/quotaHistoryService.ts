@@ -0,0 +1,69 @@
+/**
+ * 配额历史服务
+ * 与后端 quota_history 模块交互，按时间范围与分桶查询各账号的剩余配额曲线
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface QuotaHistoryQuery {
+  platform?: string | null;
+  accountId?: string | null;
+  metric?: string | null;
+  /** 起始时间（Unix 秒，含） */
+  from: number;
+  /** 结束时间（Unix 秒，含） */
+  to: number;
+  /** 分桶宽度（秒）；为空时返回原始采样 */
+  bucketSeconds?: number | null;
+}
+
+export interface QuotaHistoryPoint {
+  platform: string;
+  accountId: string;
+  metric: string;
+  bucketStart: number;
+  remainingMin: number;
+  remainingMax: number;
+  remainingAvg: number;
+  /** 桶内最后一次采样的剩余百分比 */
+  remainingLast: number;
+  resetAt: number | null;
+  samples: number;
+}
+
+/**
+ * 查询配额历史
+ */
+export async function queryQuotaHistory(query: QuotaHistoryQuery): Promise<QuotaHistoryPoint[]> {
+  return await invoke('quota_history_query', { query });
+}
+
+/**
+ * 清空配额历史；不传平台时清空全部，返回删除的采样数
+ */
+export async function clearQuotaHistory(platform?: string): Promise<number> {
+  return await invoke('quota_history_clear', { platform: platform ?? null });
+}
+
+export interface QuotaForecast {
+  platform: string;
+  account_id: string;
+  /** 指标 ID（QuotaMetric.id） */
+  metric: string;
+  label: string;
+  remaining_percent: number;
+  reset_at: number | null;
+  /** 每小时消耗的百分比；样本不足时为 null */
+  burn_rate_per_hour: number | null;
+  /** 预计耗尽时间（Unix 秒） */
+  exhausts_at: number | null;
+  exhausts_before_reset: boolean;
+  samples: number;
+}
+
+/**
+ * 预测配额耗尽时间；不传账号时返回该平台全部账号
+ */
+export async function listQuotaForecasts(platform: string, accountId?: string): Promise<QuotaForecast[]> {
+  return await invoke('quota_forecast_list', { platform, accountId: accountId ?? null });
+}
@@ -0,0 +1,44 @@
+/**
+ * 账号存储一致性检查服务
+ * 与后端 store_fsck 模块交互
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type FsckIssueKind =
+  | 'corrupted_index'
+  | 'missing_account_file'
+  | 'orphan_account_file'
+  | 'corrupted_account_file'
+  | 'duplicate_index_entry'
+  | 'duplicate_identity'
+  | 'dangling_current_account'
+  | 'instance_bound_to_missing_account';
+
+export interface FsckIssue {
+  kind: FsckIssueKind;
+  account_ids: string[];
+  detail: string;
+  repaired: boolean;
+}
+
+export interface PlatformFsckReport {
+  platform: string;
+  index_entries: number;
+  stored_accounts: number;
+  issues: FsckIssue[];
+  error: string | null;
+}
+
+export interface FsckReport {
+  repair: boolean;
+  checked_at: number;
+  platforms: PlatformFsckReport[];
+}
+
+/**
+ * 检查所有平台的账号存储；repair 为 true 时尝试修复（修复前会自动快照数据目录）
+ */
+export async function runStoreFsck(repair = false): Promise<FsckReport> {
+  return invoke<FsckReport>('store_fsck_run', { repair });
+}
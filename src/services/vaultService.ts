@@ -0,0 +1,45 @@
+/**
+ * 凭据保险库服务
+ * 与后端 credential_vault 模块交互
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type VaultKeySource = 'passphrase' | 'keyring';
+
+/** 保险库状态 */
+export interface VaultStatus {
+  enabled: boolean;
+  unlocked: boolean;
+  key_source: VaultKeySource | null;
+  migrated_at: number | null;
+}
+
+export async function getVaultStatus(): Promise<VaultStatus> {
+  return invoke<VaultStatus>('vault_get_status');
+}
+
+/**
+ * 启用保险库；passphrase 模式需要传入口令
+ */
+export async function enableVault(
+  keySource: VaultKeySource,
+  passphrase?: string
+): Promise<VaultStatus> {
+  return invoke<VaultStatus>('vault_enable', { keySource, passphrase });
+}
+
+export async function unlockVault(passphrase?: string): Promise<VaultStatus> {
+  return invoke<VaultStatus>('vault_unlock', { passphrase });
+}
+
+export async function lockVault(): Promise<VaultStatus> {
+  return invoke<VaultStatus>('vault_lock');
+}
+
+/**
+ * 关闭保险库，账号恢复为明文存储
+ */
+export async function disableVault(passphrase?: string): Promise<VaultStatus> {
+  return invoke<VaultStatus>('vault_disable', { passphrase });
+}
@@ -0,0 +1,55 @@
+/**
+ * WebSocket 客户端授权服务
+ * 本机客户端凭 server.json 中的密钥认证，或发起配对由用户核对配对码后批准；每个客户端按权限范围访问
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** 新配对请求事件名（载荷为 PairingRequest） */
+export const WS_PAIRING_EVENT = 'ws:pairing_request';
+
+export type WsScope = 'read_accounts' | 'read_tokens' | 'switch' | 'write';
+
+export interface PairedClient {
+  id: string;
+  name: string;
+  scopes: WsScope[];
+  created_at: number;
+  last_seen_at: number | null;
+}
+
+export interface PairingRequest {
+  pairing_id: string;
+  client_name: string;
+  scopes: WsScope[];
+  /** 客户端同时展示的配对码，批准前请核对 */
+  code: string;
+  addr: string;
+  /** Unix 秒 */
+  expires_at: number;
+}
+
+export async function listWsClients(): Promise<PairedClient[]> {
+  return await invoke('ws_auth_list_clients');
+}
+
+/** 撤销授权，已建立的连接立即失去权限 */
+export async function revokeWsClient(clientId: string): Promise<void> {
+  return await invoke('ws_auth_revoke_client', { clientId });
+}
+
+export async function listWsPairings(): Promise<PairingRequest[]> {
+  return await invoke('ws_auth_list_pairings');
+}
+
+/** 批准配对；不传 scopes 时授予客户端申请的全部权限 */
+export async function approveWsPairing(
+  pairingId: string,
+  scopes?: WsScope[],
+): Promise<PairedClient> {
+  return await invoke('ws_auth_approve_pairing', { pairingId, scopes: scopes ?? null });
+}
+
+export async function rejectWsPairing(pairingId: string): Promise<void> {
+  return await invoke('ws_auth_reject_pairing', { pairingId });
+}
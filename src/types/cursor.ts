@@ -0,0 +1,56 @@
+/** Cursor 账号数据（后端原样返回） */
+export interface CursorAccount {
+  id: string;
+  email: string;
+  user_id?: string | null;
+  sign_up_type?: string | null;
+  tags?: string[] | null;
+
+  access_token: string;
+  refresh_token?: string | null;
+
+  membership_type?: string | null;
+  premium_requests_used?: number | null;
+  premium_requests_limit?: number | null;
+  usage_reset_at?: number | null;
+
+  cursor_usage_raw?: unknown;
+  status?: string | null;
+  status_reason?: string | null;
+
+  created_at: number;
+  last_used: number;
+}
+
+/** 高级请求剩余百分比；缺少上限时返回 null */
+export function getCursorPremiumRemainingPercent(account: CursorAccount): number | null {
+  const limit = account.premium_requests_limit;
+  if (typeof limit !== 'number' || !Number.isFinite(limit) || limit <= 0) {
+    return null;
+  }
+  const used = Math.max(0, account.premium_requests_used ?? 0);
+  return Math.max(0, Math.min(100, Math.round(((limit - used) / limit) * 100)));
+}
+
+export function isCursorAccountExpired(account: CursorAccount): boolean {
+  return (account.status || '').trim().toLowerCase() === 'expired';
+}
+
+export function getCursorAccountDisplayEmail(account: CursorAccount): string {
+  return account.email?.trim() || account.user_id?.trim() || account.id;
+}
+
+/** 会员类型（free / pro / business 等），缺失时为 UNKNOWN */
+export function getCursorPlanBadge(account: CursorAccount): string {
+  return account.membership_type?.trim().toUpperCase() || 'UNKNOWN';
+}
+
+export function getCursorUsage(account: CursorAccount) {
+  const remaining = getCursorPremiumRemainingPercent(account);
+  return {
+    inlineSuggestionsUsedPercent: null,
+    chatMessagesUsedPercent: null,
+    premiumRequestsUsedPercent: remaining == null ? null : 100 - remaining,
+    allowanceResetAt: account.usage_reset_at ?? null,
+  };
+}